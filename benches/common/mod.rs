@@ -0,0 +1,31 @@
+//! Helpers shared by the benchmarks.
+
+#![allow(dead_code)]
+
+use std::fs::read;
+
+/// Number of frames in the generated capture.
+pub(crate) const FRAMES: usize = 1000;
+
+/// Paths of the frames the capture is generated from.
+pub(crate) const PATHS: [&str; 4] = [
+    "tests/assets/default.frame",
+    "tests/assets/effects_0.frame",
+    "tests/assets/effects_1.frame",
+    "tests/assets/effects_2.frame",
+];
+
+/// Returns a capture of [`FRAMES`] frames stored back to back, together with
+/// the individual frames.
+pub(crate) fn capture() -> (Vec<u8>, Vec<Vec<u8>>) {
+    let frames = PATHS.map(|path| read(path).unwrap());
+
+    let frames = frames
+        .iter()
+        .cycle()
+        .take(FRAMES)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    (frames.concat(), frames)
+}
@@ -0,0 +1,48 @@
+#![allow(missing_docs)]
+
+mod common;
+
+use std::hint::black_box;
+use std::io::{sink, Write};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use high_flow_next::misc::{checksum, CrcReader, CrcWriter, Reader};
+
+use self::common::capture;
+
+fn crc(c: &mut Criterion) {
+    let (data, _) = capture();
+
+    let mut group = c.benchmark_group("crc");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+
+    group.bench_function("checksum", |b| {
+        b.iter(|| checksum(black_box(&data)));
+    });
+
+    group.bench_function("reader_bytewise", |b| {
+        b.iter(|| {
+            let mut reader = black_box(&data[..]);
+            let mut crc = CrcReader::new(&mut reader);
+            for _ in 0..data.len() {
+                crc.read_u8().unwrap();
+            }
+
+            crc.finalize()
+        });
+    });
+
+    group.bench_function("writer", |b| {
+        b.iter(|| {
+            let mut writer = CrcWriter::new(sink());
+            writer.write_all(black_box(&data)).unwrap();
+
+            writer.finalize().1
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, crc);
+criterion_main!(benches);
@@ -0,0 +1,103 @@
+#![allow(missing_docs)]
+
+mod common;
+
+use std::fs::{remove_file, write, File};
+use std::hint::black_box;
+use std::io::{BufReader, Cursor, Seek, SeekFrom};
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use high_flow_next::{
+    misc::Decode,
+    protocol::{decode_frames, Frame},
+};
+
+use self::common::{capture, FRAMES};
+
+fn decode(c: &mut Criterion) {
+    let (data, frames) = capture();
+
+    let mut group = c.benchmark_group("decode");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+
+    group.bench_function("frame_by_frame", |b| {
+        b.iter_batched(
+            || frames.clone(),
+            |frames| {
+                frames
+                    .into_iter()
+                    .map(|frame| Frame::decode(&mut Cursor::new(frame)).unwrap())
+                    .collect::<Vec<_>>()
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    group.bench_function("slice", |b| {
+        b.iter(|| {
+            let mut reader = black_box(&data[..]);
+
+            (0..FRAMES)
+                .map(|_| Frame::decode(&mut reader).unwrap())
+                .collect::<Vec<_>>()
+        });
+    });
+
+    group.bench_function("decode_frames", |b| {
+        b.iter(|| decode_frames(black_box(&data)).unwrap());
+    });
+
+    #[cfg(feature = "rayon")]
+    group.bench_function("decode_frames_par", |b| {
+        use high_flow_next::protocol::decode_frames_par;
+
+        b.iter(|| {
+            decode_frames_par(black_box(&data))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        });
+    });
+
+    group.finish();
+}
+
+fn decode_file(c: &mut Criterion) {
+    let (data, _) = capture();
+
+    let path = std::env::temp_dir().join(format!("high_flow_next-{}.bin", std::process::id()));
+    write(&path, &data).unwrap();
+
+    let mut group = c.benchmark_group("decode_file");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+
+    group.bench_function("file", |b| {
+        let mut file = File::open(&path).unwrap();
+
+        b.iter(|| {
+            file.seek(SeekFrom::Start(0)).unwrap();
+
+            (0..FRAMES)
+                .map(|_| Frame::decode(&mut file).unwrap())
+                .collect::<Vec<_>>()
+        });
+    });
+
+    group.bench_function("buffered", |b| {
+        let mut file = BufReader::new(File::open(&path).unwrap());
+
+        b.iter(|| {
+            file.seek(SeekFrom::Start(0)).unwrap();
+
+            (0..FRAMES)
+                .map(|_| Frame::decode(&mut file).unwrap())
+                .collect::<Vec<_>>()
+        });
+    });
+
+    group.finish();
+
+    remove_file(&path).unwrap();
+}
+
+criterion_group!(benches, decode, decode_file);
+criterion_main!(benches);
@@ -0,0 +1,36 @@
+#![allow(missing_docs)]
+
+//! Covers the backup archives only, the settings frames can not be encoded
+//! (see the `device` module).
+
+mod common;
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use high_flow_next::backup::SettingsArchive;
+
+use self::common::PATHS;
+
+fn encode(c: &mut Criterion) {
+    let frame = std::fs::read(PATHS[0]).unwrap();
+    let archive = SettingsArchive::new(0x0102, frame).unwrap();
+
+    let mut buffer = Vec::new();
+    archive.write(&mut buffer).unwrap();
+
+    let mut group = c.benchmark_group("encode");
+    group.throughput(Throughput::Bytes(buffer.len() as u64));
+
+    group.bench_function("archive", |b| {
+        b.iter(|| {
+            buffer.clear();
+            black_box(&archive).write(&mut buffer).unwrap();
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, encode);
+criterion_main!(benches);
@@ -0,0 +1,67 @@
+#![allow(missing_docs)]
+
+use std::env::args;
+use std::fs::File;
+use std::io::BufWriter;
+
+use anyhow::{bail, Context, Result};
+
+use hidapi::HidApi;
+use high_flow_next::{backup::SettingsArchive, device::Device, misc::Decode};
+
+const USAGE: &str = "Usage: backup <backup|restore> <FILE> [--force]";
+
+fn main() -> Result<()> {
+    let args = args().skip(1).collect::<Vec<_>>();
+    let (command, path, force) = match &args[..] {
+        [command, path] => (command.as_str(), path, false),
+        [command, path, force] if force == "--force" => (command.as_str(), path, true),
+        _ => bail!("{USAGE}"),
+    };
+
+    let api = HidApi::new()?;
+    let mut dev = Device::open(&api).context("Unable to open device")?;
+    let firmware_version = dev.firmware_version()?;
+
+    match command {
+        "backup" => backup(&mut dev, firmware_version, path),
+        "restore" => restore(&mut dev, firmware_version, path, force),
+        _ => bail!("{USAGE}"),
+    }
+}
+
+fn backup(dev: &mut Device, firmware_version: u16, path: &str) -> Result<()> {
+    let report = dev
+        .read_report(0x03)
+        .context("Unable to get feature report")?;
+
+    let archive = SettingsArchive::new(firmware_version, report.to_vec())
+        .context("Device returned invalid settings")?;
+    let json = serde_json::to_string_pretty(&archive.settings()?)?;
+    let archive = archive.with_json(json);
+
+    let file = File::create(path).with_context(|| format!("Unable to create {path}"))?;
+    archive.write(BufWriter::new(file))?;
+
+    println!("Settings of firmware {firmware_version:#06x} saved to {path}");
+
+    Ok(())
+}
+
+fn restore(dev: &mut Device, firmware_version: u16, path: &str, force: bool) -> Result<()> {
+    let mut file = File::open(path).with_context(|| format!("Unable to open {path}"))?;
+    let archive = SettingsArchive::decode(&mut file).context("Invalid archive")?;
+
+    let frame = archive
+        .restore(firmware_version, force)
+        .context("Refusing to restore (use --force to override)")?;
+    dev.write_report(frame)
+        .context("Unable to send feature report")?;
+
+    println!(
+        "Settings of firmware {:#06x} restored from {path}",
+        archive.firmware_version()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+#![allow(missing_docs)]
+
+use std::env::args;
+use std::fs::File;
+use std::io::BufWriter;
+
+use anyhow::{bail, Context, Result};
+
+use hidapi::HidApi;
+use high_flow_next::{bundle::SupportBundle, device::Device};
+
+const USAGE: &str = "Usage: bundle debug bundle <FILE> [--no-redact]";
+
+fn main() -> Result<()> {
+    let args = args().skip(1).collect::<Vec<_>>();
+    let (path, redact) = match &args[..] {
+        [debug, bundle, path] if debug == "debug" && bundle == "bundle" => (path, true),
+        [debug, bundle, path, flag]
+            if debug == "debug" && bundle == "bundle" && flag == "--no-redact" =>
+        {
+            (path, false)
+        }
+        _ => bail!("{USAGE}"),
+    };
+
+    let api = HidApi::new()?;
+    let mut dev = Device::open(&api).context("Unable to open device")?;
+    let info = dev
+        .transport()
+        .get_device_info()
+        .context("Unable to get device info")?;
+
+    let mut bundle = SupportBundle::read(&mut dev)
+        .context("Unable to get feature report")?
+        .firmware_version(info.release_number())
+        .path(info.path().to_string_lossy())
+        .redact(redact);
+    if let Some(serial_number) = info.serial_number() {
+        bundle = bundle.serial_number(serial_number);
+    }
+
+    let file = File::create(path).with_context(|| format!("Unable to create {path}"))?;
+    bundle.write(BufWriter::new(file))?;
+
+    println!("Support bundle saved to {path}");
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+#![allow(missing_docs)]
+
+use std::env::args;
+use std::fs::read;
+
+use anyhow::{bail, Context, Result};
+
+use high_flow_next::{
+    backup::{SettingsArchive, ARCHIVE_MAGIC},
+    misc::Decode,
+    protocol::{
+        settings::{Settings, SettingsDiff},
+        Frame,
+    },
+};
+
+fn main() -> Result<()> {
+    let args = args().skip(1).collect::<Vec<_>>();
+    let [old, new] = &args[..] else {
+        bail!("Usage: diff <OLD> <NEW> (raw frames, backup archives or JSON files)");
+    };
+
+    let old = load(old)?;
+    let new = load(new)?;
+
+    let diff = SettingsDiff::new(&old, &new);
+    if diff.is_empty() {
+        println!("Settings are equal");
+    } else {
+        print!("{diff}");
+    }
+
+    Ok(())
+}
+
+/// Loads the settings from a raw settings frame, a backup archive or the
+/// settings serialized to JSON.
+fn load(path: &str) -> Result<Settings> {
+    let data = read(path).with_context(|| format!("Unable to read {path}"))?;
+
+    if data.trim_ascii_start().starts_with(b"{") {
+        serde_json::from_slice(&data).with_context(|| format!("Invalid JSON {path}"))
+    } else if data.starts_with(&ARCHIVE_MAGIC) {
+        let archive = SettingsArchive::decode(&mut &data[..])
+            .with_context(|| format!("Invalid archive {path}"))?;
+
+        Ok(archive.settings()?)
+    } else {
+        let Frame::Settings(settings) =
+            Frame::decode(&mut &data[..]).with_context(|| format!("Invalid frame {path}"))?;
+
+        Ok(settings)
+    }
+}
@@ -0,0 +1,28 @@
+#![allow(missing_docs)]
+
+use anyhow::{Context, Result};
+
+use hidapi::HidApi;
+
+const VID: u16 = 0x0C70;
+const PID: u16 = 0xF012;
+
+fn main() -> Result<()> {
+    let api = HidApi::new()?;
+    let dev = api
+        .open(VID, PID)
+        .with_context(|| format!("Open VID={:#06x} PID={:#06x}", VID, PID))?;
+
+    let info = dev.get_device_info().context("Unable to get device info")?;
+
+    println!(
+        "Manufacturer:     {}",
+        info.manufacturer_string().unwrap_or("-")
+    );
+    println!("Product:          {}", info.product_string().unwrap_or("-"));
+    println!("Serial Number:    {}", info.serial_number().unwrap_or("-"));
+    println!("Firmware Version: {:#06x}", info.release_number());
+    println!("Path:             {}", info.path().to_string_lossy());
+
+    Ok(())
+}
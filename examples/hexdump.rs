@@ -9,6 +9,8 @@ use anyhow::{Context, Result};
 use crc::{Crc, CRC_16_USB};
 use hidapi::HidApi;
 
+use high_flow_next::protocol::settings::CaptureDiff;
+
 const VID: u16 = 0x0C70;
 const PID: u16 = 0xF012;
 
@@ -75,6 +77,13 @@ fn main() -> Result<()> {
 
         print_diff(0, &data_old, data_new);
 
+        if o == 0 && !data_old.is_empty() {
+            match CaptureDiff::new(&data_old, data_new) {
+                Ok(diff) => print!("{diff}"),
+                Err(error) => println!("Unable to analyze the frames: {error}"),
+            }
+        }
+
         data_new.clone_into(&mut data_old);
     }
 
@@ -0,0 +1,118 @@
+#![allow(missing_docs)]
+
+use std::env::args;
+use std::fs::read;
+
+use anyhow::{bail, Context, Result};
+
+use crc::{Crc, CRC_16_USB};
+use high_flow_next::{
+    misc::{Decode, IoError, PositionReader},
+    protocol::{settings::SettingsDiff, Frame},
+};
+
+const USAGE: &str = "Usage: replay <FILE> [--mutate <COUNT>] [--seed <SEED>] [--fix-crc]";
+
+fn main() -> Result<()> {
+    let mut args = args().skip(1);
+    let Some(path) = args.next() else {
+        bail!("{USAGE}");
+    };
+
+    let mut mutate = 0;
+    let mut seed = 0x2545_F491_4F6C_DD1D_u64;
+    let mut fix_crc = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--mutate" => mutate = args.next().context(USAGE)?.parse()?,
+            "--seed" => seed = args.next().context(USAGE)?.parse()?,
+            "--fix-crc" => fix_crc = true,
+            _ => bail!("{USAGE}"),
+        }
+    }
+
+    let data = read(&path).with_context(|| format!("Unable to read {path}"))?;
+    let original = match decode(&data) {
+        Ok(frame) => frame,
+        Err((offset, error)) => {
+            println!("Decoding {path} failed at offset {offset:#06x}: {error}");
+
+            return Ok(());
+        }
+    };
+    println!("Decoded {path} ({} bytes)", data.len());
+
+    if mutate == 0 {
+        return Ok(());
+    }
+
+    let mut rng = XorShift(seed.max(1));
+    let mut mutated = data.clone();
+    for _ in 0..mutate {
+        let offset = rng.next_usize(mutated.len());
+        let value = rng.next_u8();
+
+        println!(
+            "Mutate offset {offset:#06x}: {:02X} -> {value:02X}",
+            mutated[offset]
+        );
+        mutated[offset] = value;
+    }
+
+    if fix_crc && mutated.len() >= 3 {
+        let len = mutated.len();
+        let crc = Crc::<u16>::new(&CRC_16_USB).checksum(&mutated[1..len - 2]);
+
+        mutated[len - 2..].copy_from_slice(&crc.to_be_bytes());
+    }
+
+    match decode(&mutated) {
+        Ok(frame) => {
+            let (Frame::Settings(old), Frame::Settings(new)) = (&original, &frame);
+            let diff = SettingsDiff::new(old, new);
+
+            if diff.is_empty() {
+                println!("Mutated frame decodes to the same settings");
+            } else {
+                println!("Mutated frame diverges:");
+                print!("{diff}");
+            }
+        }
+        Err((offset, error)) => {
+            println!("Decoding mutated frame failed at offset {offset:#06x}: {error}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Decodes the passed `data` and returns the offset at which decoding failed
+/// in case of an error.
+fn decode(data: &[u8]) -> Result<Frame, (usize, IoError)> {
+    let mut data = data;
+    let mut reader = PositionReader::new(&mut data);
+
+    Frame::decode(&mut reader).map_err(|error| (reader.position(), error))
+}
+
+/// Minimal xorshift random number generator to make mutations reproducible.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+
+        self.0
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn next_usize(&mut self, max: usize) -> usize {
+        (self.next() % max as u64) as usize
+    }
+
+    fn next_u8(&mut self) -> u8 {
+        self.next().to_be_bytes()[0]
+    }
+}
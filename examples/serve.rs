@@ -0,0 +1,56 @@
+#![allow(missing_docs)]
+
+use std::env::args;
+use std::sync::{Arc, Mutex};
+use std::thread::{sleep, spawn};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+
+use hidapi::HidApi;
+use high_flow_next::{
+    device::{Device, SettingsCache},
+    server::{ApiServer, ServerState},
+};
+
+const USAGE: &str = "Usage: serve [ADDR] [--interval <SECONDS>] [--cors <ORIGIN>]";
+
+fn main() -> Result<()> {
+    let mut args = args().skip(1);
+
+    let mut addr = "127.0.0.1:8080".to_string();
+    let mut interval = Duration::from_secs(10);
+    let mut cors = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--interval" => {
+                interval = Duration::from_secs(args.next().context(USAGE)?.parse()?);
+            }
+            "--cors" => cors = Some(args.next().context(USAGE)?),
+            arg if !arg.starts_with('-') => addr = arg.into(),
+            _ => bail!("{USAGE}"),
+        }
+    }
+
+    let api = HidApi::new()?;
+    let mut dev = Device::open(&api).context("Unable to open device")?;
+    let mut cache = SettingsCache::new(interval);
+
+    let state = Arc::new(Mutex::new(ServerState::new(3600)));
+    let mut server = ApiServer::bind(&addr, state.clone())
+        .with_context(|| format!("Unable to listen on {addr}"))?;
+    if let Some(origin) = cors {
+        server = server.with_cors(origin);
+    }
+    println!("Serving /readings, /history and /settings on http://{addr}");
+    spawn(move || server.serve());
+
+    loop {
+        match cache.poll(&mut dev, Instant::now()) {
+            Ok(_) => state.lock().unwrap().update_settings(&cache),
+            Err(error) => eprintln!("Unable to read settings: {error}"),
+        }
+
+        sleep(interval);
+    }
+}
@@ -0,0 +1,242 @@
+//! Audit trail for the settings written to a device.
+//!
+//! The [`AuditedTransport`] wraps the [`Transport`] of a
+//! [`Device`](crate::device::Device) and records every settings report that
+//! is sent through it as [`AuditRecord`]. A record contains the point in time
+//! of the write, the field level [`SettingsDiff`] against the settings that
+//! were stored on the device before, and a free form source tag (e.g. the
+//! user or the tool that performed the write). The records are passed to an
+//! [`AuditStore`], so shared systems can answer "who changed the alarm limit
+//! and when".
+//!
+//! Reports other than the settings report are passed through without being
+//! recorded.
+//!
+//! ```rust,no_run
+//! # #[cfg(feature = "hidapi")]
+//! # fn main() {
+//! use hidapi::HidApi;
+//!
+//! use high_flow_next::audit::{AuditedTransport, LogStore};
+//! use high_flow_next::device::{Device, VENDOR_ID, PRODUCT_ID};
+//!
+//! let api = HidApi::new().unwrap();
+//! let hid = api.open(VENDOR_ID, PRODUCT_ID).unwrap();
+//! let log = std::fs::File::options().append(true).create(true).open("audit.log").unwrap();
+//!
+//! let mut device = Device::new(AuditedTransport::new(hid, LogStore::new(log)));
+//! device.transport_mut().set_source("alice@workstation");
+//! # }
+//! # #[cfg(not(feature = "hidapi"))]
+//! # fn main() {}
+//! ```
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::Write;
+
+use crate::device::{Transport, REPORT_BUFFER_SIZE, SETTINGS_REPORT_ID};
+use crate::misc::{Decode, IoError};
+use crate::monitor::Timestamp;
+use crate::protocol::{settings::SettingsDiff, Frame, Settings};
+
+/// A single settings write recorded by the [`AuditedTransport`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditRecord {
+    /// Point in time the settings were written.
+    pub timestamp: Timestamp,
+
+    /// Source tag that was set when the settings were written (see
+    /// [`AuditedTransport::set_source`]).
+    pub source: String,
+
+    /// Difference between the settings stored on the device before the write
+    /// and the written settings.
+    pub diff: SettingsDiff,
+}
+
+impl Display for AuditRecord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "[{:.3}] settings written by {}",
+            self.timestamp.unix_seconds(),
+            self.source
+        )?;
+
+        if self.diff.is_empty() {
+            return write!(f, " (unchanged)");
+        }
+
+        for change in &self.diff {
+            write!(f, "\n    {change}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Store the [`AuditRecord`]s are written to.
+pub trait AuditStore {
+    /// Stores the passed `record`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the record could not be stored.
+    fn record(&mut self, record: AuditRecord) -> Result<(), IoError>;
+}
+
+/// Keeps the records in memory.
+impl AuditStore for Vec<AuditRecord> {
+    fn record(&mut self, record: AuditRecord) -> Result<(), IoError> {
+        self.push(record);
+
+        Ok(())
+    }
+}
+
+/// Writes the records as human readable text (see the [`Display`]
+/// implementation of [`AuditRecord`]) to the underlying writer, e.g. a log
+/// file opened in append mode.
+#[derive(Debug)]
+pub struct LogStore<W> {
+    writer: W,
+}
+
+impl<W> LogStore<W>
+where
+    W: Write,
+{
+    /// Creates a new store that writes to the passed `writer`.
+    #[must_use]
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Returns the underlying writer.
+    #[must_use]
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W> AuditStore for LogStore<W>
+where
+    W: Write,
+{
+    fn record(&mut self, record: AuditRecord) -> Result<(), IoError> {
+        writeln!(self.writer, "{record}")?;
+        self.writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// [`Transport`] that records every settings write to an [`AuditStore`].
+///
+/// See the [module documentation](self) for details.
+#[derive(Debug)]
+pub struct AuditedTransport<T, S> {
+    transport: T,
+    store: S,
+    source: String,
+    buffer: Box<[u8]>,
+}
+
+impl<T, S> AuditedTransport<T, S>
+where
+    T: Transport,
+    S: AuditStore,
+{
+    /// Source tag used if no source was set.
+    pub const DEFAULT_SOURCE: &'static str = "unknown";
+
+    /// Creates a new transport that passes all reports to `transport` and
+    /// records the settings writes to `store`.
+    #[must_use]
+    pub fn new(transport: T, store: S) -> Self {
+        Self {
+            transport,
+            store,
+            source: Self::DEFAULT_SOURCE.into(),
+            buffer: vec![0; REPORT_BUFFER_SIZE].into_boxed_slice(),
+        }
+    }
+
+    /// Sets the source tag of the following settings writes.
+    pub fn set_source<X: Into<String>>(&mut self, source: X) {
+        self.source = source.into();
+    }
+
+    /// Returns the current source tag.
+    #[must_use]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns a reference to the audit store.
+    #[must_use]
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Returns a reference to the underlying transport.
+    #[must_use]
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Returns the underlying transport and the audit store.
+    #[must_use]
+    pub fn into_inner(self) -> (T, S) {
+        (self.transport, self.store)
+    }
+
+    fn read_settings(&mut self) -> Result<Settings, IoError> {
+        self.buffer[0] = SETTINGS_REPORT_ID;
+        let len = self
+            .transport
+            .get_feature_report(&mut self.buffer)?
+            .min(self.buffer.len());
+
+        decode_settings(&self.buffer[..len])
+    }
+}
+
+impl<T, S> Transport for AuditedTransport<T, S>
+where
+    T: Transport,
+    S: AuditStore,
+{
+    fn get_feature_report(&mut self, buffer: &mut [u8]) -> Result<usize, IoError> {
+        self.transport.get_feature_report(buffer)
+    }
+
+    /// Sends the passed feature report.
+    ///
+    /// If the report is a settings report, the settings currently stored on
+    /// the device are read before, and the write is recorded after the report
+    /// was sent successfully. Settings reports that can not be decoded are
+    /// rejected without sending them.
+    fn send_feature_report(&mut self, data: &[u8]) -> Result<(), IoError> {
+        if data.first() != Some(&SETTINGS_REPORT_ID) {
+            return self.transport.send_feature_report(data);
+        }
+
+        let new = decode_settings(data)?;
+        let old = self.read_settings()?;
+
+        self.transport.send_feature_report(data)?;
+
+        self.store.record(AuditRecord {
+            timestamp: Timestamp::now(),
+            source: self.source.clone(),
+            diff: SettingsDiff::new(&old, &new),
+        })
+    }
+}
+
+fn decode_settings(mut report: &[u8]) -> Result<Settings, IoError> {
+    match Frame::decode(&mut report)? {
+        Frame::Settings(settings) => Ok(settings),
+    }
+}
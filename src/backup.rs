@@ -0,0 +1,226 @@
+//! Versioned backup archives for the device settings.
+//!
+//! A [`SettingsArchive`] stores the raw settings frame as it was read from the
+//! device together with the firmware version of the device and a checksum
+//! over the whole archive. The raw frame is restored instead of the decoded
+//! [`Settings`] so that a restore writes back exactly the bytes that were
+//! read, including the regions that are not modeled by this crate. The
+//! decoded settings can be stored as JSON next to the frame (see
+//! [`SettingsArchive::with_json`]), so the archive can be inspected without
+//! this crate.
+//!
+//! Binary layout (all values big-endian):
+//!
+//! ```text
+//! Offset    Type        Description
+//! 0x0000    uint8[4]    Magic "HFNB"
+//! 0x0004    uint16be    Archive format version
+//! 0x0006    uint16be    Firmware version of the device
+//! 0x0008    uint16be    Length of the raw frame (N)
+//! 0x000A    uint8[N]    Raw frame (op code, payload and frame checksum)
+//! N+0x0A    uint32be    Length of the decoded settings (M, 0 if not stored)
+//! N+0x0E    uint8[M]    Decoded settings as UTF-8 encoded JSON
+//! N+M+0x0E  uint16be    Checksum (CRC-16/USB over all preceding bytes)
+//! ```
+//!
+//! Archives of version 1 do not contain the length and the JSON of the
+//! decoded settings, they can still be read.
+
+use std::io::Write;
+
+use crate::misc::{CrcReader, CrcWriter, Decode, Guard, GuardOutput, IoError, Reader};
+use crate::protocol::{Frame, Settings};
+
+/// Magic bytes every archive starts with.
+pub const ARCHIVE_MAGIC: [u8; 4] = *b"HFNB";
+
+/// Current version of the archive format.
+pub const ARCHIVE_VERSION: u16 = 2;
+
+/// Maximum length of the decoded settings stored in an archive.
+pub const MAX_ARCHIVE_JSON_LEN: usize = 1024 * 1024;
+
+/// A backup of the device settings.
+///
+/// See the [module documentation](self) for the binary layout.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SettingsArchive {
+    firmware_version: u16,
+    frame: Vec<u8>,
+    json: Option<String>,
+}
+
+impl SettingsArchive {
+    /// Creates a new archive from the raw settings `frame` read from a device
+    /// running the passed `firmware_version`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the passed `frame` is not a valid settings frame.
+    pub fn new(firmware_version: u16, frame: Vec<u8>) -> Result<Self, IoError> {
+        let archive = Self {
+            firmware_version,
+            frame,
+            json: None,
+        };
+
+        archive.settings()?;
+
+        Ok(archive)
+    }
+
+    /// Sets the decoded settings stored next to the raw frame (e.g. the
+    /// [`settings`](Self::settings) serialized to JSON), and returns the
+    /// updated archive.
+    ///
+    /// The JSON is only stored for reference, [`restore`](Self::restore)
+    /// always returns the raw frame.
+    #[must_use]
+    pub fn with_json(mut self, json: String) -> Self {
+        self.json = Some(json);
+
+        self
+    }
+
+    /// Returns the decoded settings stored next to the raw frame, if any.
+    #[must_use]
+    pub fn json(&self) -> Option<&str> {
+        self.json.as_deref()
+    }
+
+    /// Returns the firmware version of the device the archive was created from.
+    #[must_use]
+    pub fn firmware_version(&self) -> u16 {
+        self.firmware_version
+    }
+
+    /// Returns the raw settings frame stored in the archive.
+    #[must_use]
+    pub fn frame(&self) -> &[u8] {
+        &self.frame
+    }
+
+    /// Decodes the settings stored in the archive.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored frame could not be decoded.
+    pub fn settings(&self) -> Result<Settings, IoError> {
+        match Frame::decode(&mut &self.frame[..])? {
+            Frame::Settings(settings) => Ok(settings),
+        }
+    }
+
+    /// Returns the raw frame that should be written to a device running the
+    /// passed `firmware_version`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IoError::FirmwareMismatch`] if the firmware version of the
+    /// archive does not match the passed one and `force` is not set.
+    pub fn restore(&self, firmware_version: u16, force: bool) -> Result<&[u8], IoError> {
+        if !force && self.firmware_version != firmware_version {
+            return Err(IoError::FirmwareMismatch {
+                expected: self.firmware_version,
+                actual: firmware_version,
+            });
+        }
+
+        Ok(&self.frame)
+    }
+
+    /// Writes the archive to the passed `writer`.
+    ///
+    /// # Errors
+    ///
+    /// Forwards errors from the underlying writer, and returns
+    /// [`IoError::InvalidValue`] if the JSON is longer than
+    /// [`MAX_ARCHIVE_JSON_LEN`].
+    pub fn write<W: Write>(&self, writer: W) -> Result<(), IoError> {
+        let len = u16::try_from(self.frame.len())
+            .map_err(|_| IoError::InvalidValue("ArchiveFrameLength", self.frame.len()))?;
+
+        let json = self.json.as_deref().unwrap_or_default();
+        let json_len = u32::try_from(json.len())
+            .ok()
+            .filter(|_| json.len() <= MAX_ARCHIVE_JSON_LEN)
+            .ok_or(IoError::InvalidValue("ArchiveJsonLength", json.len()))?;
+
+        let mut writer = CrcWriter::new(writer);
+        writer.write_all(&ARCHIVE_MAGIC)?;
+        writer.write_all(&ARCHIVE_VERSION.to_be_bytes())?;
+        writer.write_all(&self.firmware_version.to_be_bytes())?;
+        writer.write_all(&len.to_be_bytes())?;
+        writer.write_all(&self.frame)?;
+        writer.write_all(&json_len.to_be_bytes())?;
+        writer.write_all(json.as_bytes())?;
+
+        let (mut writer, crc) = writer.finalize();
+        writer.write_all(&crc.to_be_bytes())?;
+        writer.flush()?;
+
+        Ok(())
+    }
+}
+
+impl Decode for SettingsArchive {
+    fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
+        let mut crc = CrcReader::new(reader);
+
+        let mut magic = [0; 4];
+        crc.read_exact(&mut magic)?;
+        if magic != ARCHIVE_MAGIC {
+            Err(IoError::InvalidValue(
+                "ArchiveMagic",
+                u32::from_be_bytes(magic) as usize,
+            ))?;
+        }
+
+        let version = crc.read_u16be()?;
+        if version != 1 && version != ARCHIVE_VERSION {
+            Err(IoError::InvalidValue("ArchiveVersion", version.into()))?;
+        }
+
+        let firmware_version = crc.read_u16be()?;
+        let len = crc.read_u16be()?;
+
+        let mut frame = vec![0; len.into()];
+        crc.read_exact(&mut frame)?;
+
+        let json = if version == 1 {
+            None
+        } else {
+            let mut len = [0; 4];
+            crc.read_exact(&mut len)?;
+
+            let len = u32::from_be_bytes(len) as usize;
+            if len > MAX_ARCHIVE_JSON_LEN {
+                Err(IoError::InvalidValue("ArchiveJsonLength", len))?;
+            }
+
+            let mut json = vec![0; len];
+            crc.read_exact(&mut json)?;
+
+            let json = String::from_utf8(json).map_err(|err| {
+                IoError::InvalidValue("ArchiveJson", err.utf8_error().valid_up_to())
+            })?;
+
+            (len > 0).then_some(json)
+        };
+
+        let crc_actual = crc.finalize();
+        let crc_expected = reader.read_u16be()?;
+
+        if crc_actual != crc_expected {
+            Err(IoError::ChecksumMismatch)?;
+        }
+
+        let ret = R::guard(|_| {
+            let archive = Self::new(firmware_version, frame)?;
+
+            Ok(Self { json, ..archive })
+        });
+
+        R::Guard::transpose_result(ret)
+    }
+}
@@ -0,0 +1,201 @@
+//! Support bundles for bug reports.
+//!
+//! A [`SupportBundle`] collects everything that is needed to reproduce a
+//! protocol issue into a single `tar` archive users can attach to a bug
+//! report:
+//!
+//! - `info.txt`: version of this crate, firmware version and device info
+//! - `settings.frame`: raw settings report as it was read from the device
+//! - `settings.json`: decoded settings (or `settings.error.txt` if the
+//!   settings could not be decoded)
+//! - `readings.csv`: recent sensor readings (see [`CsvSink`])
+//!
+//! By default the bundle is redacted: the serial number and the path of the
+//! device are replaced by a placeholder.
+//!
+//! The `bundle` example implements the `debug bundle` command on top of this
+//! module.
+
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tar::{Builder, Header};
+
+use crate::device::{Device, Transport, SETTINGS_REPORT_ID};
+use crate::misc::{Decode, IoError};
+use crate::monitor::{CsvSink, SensorReadings, Sink};
+use crate::protocol::Frame;
+
+/// Placeholder for redacted values.
+pub const REDACTED: &str = "<redacted>";
+
+/// Collected data of a support bundle.
+///
+/// See the [module documentation](self) for details.
+#[derive(Debug, Clone)]
+pub struct SupportBundle {
+    frame: Vec<u8>,
+    firmware_version: Option<u16>,
+    serial_number: Option<String>,
+    path: Option<String>,
+    readings: Vec<SensorReadings>,
+    redact: bool,
+    created_at: SystemTime,
+}
+
+impl SupportBundle {
+    /// Creates a new bundle for the raw settings `frame` read from a device.
+    #[must_use]
+    pub fn new(frame: Vec<u8>) -> Self {
+        Self {
+            frame,
+            firmware_version: None,
+            serial_number: None,
+            path: None,
+            readings: Vec::new(),
+            redact: true,
+            created_at: SystemTime::now(),
+        }
+    }
+
+    /// Creates a new bundle for the settings report read from the passed
+    /// `device`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the report could not be received. The report is
+    /// not decoded, so invalid settings still end up in the bundle.
+    pub fn read<T>(device: &mut Device<T>) -> Result<Self, IoError>
+    where
+        T: Transport,
+    {
+        let report = device.read_report(SETTINGS_REPORT_ID)?;
+
+        Ok(Self::new(report.to_vec()))
+    }
+
+    /// Sets the firmware version of the device.
+    #[must_use]
+    pub fn firmware_version(mut self, firmware_version: u16) -> Self {
+        self.firmware_version = Some(firmware_version);
+
+        self
+    }
+
+    /// Sets the serial number of the device.
+    #[must_use]
+    pub fn serial_number<S: Into<String>>(mut self, serial_number: S) -> Self {
+        self.serial_number = Some(serial_number.into());
+
+        self
+    }
+
+    /// Sets the (platform specific) path of the device.
+    #[must_use]
+    pub fn path<S: Into<String>>(mut self, path: S) -> Self {
+        self.path = Some(path.into());
+
+        self
+    }
+
+    /// Adds the passed recent `readings` (e.g. from a
+    /// [`History`](crate::monitor::History)).
+    #[must_use]
+    pub fn readings<I>(mut self, readings: I) -> Self
+    where
+        I: IntoIterator<Item = SensorReadings>,
+    {
+        self.readings.extend(readings);
+
+        self
+    }
+
+    /// Sets whether to redact the serial number and the path of the device
+    /// or not (enabled by default).
+    #[must_use]
+    pub fn redact(mut self, redact: bool) -> Self {
+        self.redact = redact;
+
+        self
+    }
+
+    /// Returns the files of the bundle as `(name, content)` pairs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the decoded settings could not be serialized.
+    pub fn files(&self) -> Result<Vec<(&'static str, Vec<u8>)>, IoError> {
+        let mut files = vec![
+            ("info.txt", self.info().into_bytes()),
+            ("settings.frame", self.frame.clone()),
+        ];
+
+        match Frame::decode(&mut &self.frame[..]) {
+            Ok(Frame::Settings(settings)) => {
+                let json = serde_json::to_vec_pretty(&settings).map_err(std::io::Error::other)?;
+
+                files.push(("settings.json", json));
+            }
+            Err(err) => files.push(("settings.error.txt", err.to_string().into_bytes())),
+        }
+
+        let mut csv = CsvSink::new(Vec::new());
+        csv.publish_batch(&self.readings)?;
+        files.push(("readings.csv", csv.into_inner()));
+
+        Ok(files)
+    }
+
+    /// Writes the bundle as `tar` archive to the passed `writer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive could not be written.
+    pub fn write<W: Write>(&self, writer: W) -> Result<(), IoError> {
+        let mtime = self
+            .created_at
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |x| x.as_secs());
+
+        let mut builder = Builder::new(writer);
+        for (name, data) in self.files()? {
+            let mut header = Header::new_ustar();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(mtime);
+            header.set_cksum();
+
+            builder.append_data(&mut header, name, &data[..])?;
+        }
+
+        builder.into_inner()?.flush()?;
+
+        Ok(())
+    }
+
+    fn info(&self) -> String {
+        let redacted = |value: &Option<String>| match value {
+            Some(_) if self.redact => REDACTED.to_owned(),
+            Some(value) => value.clone(),
+            None => "unknown".to_owned(),
+        };
+
+        let firmware_version = self
+            .firmware_version
+            .map_or_else(|| "unknown".to_owned(), |x| format!("{x:#06x}"));
+        let created_at = self
+            .created_at
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |x| x.as_secs());
+
+        format!(
+            "crate: {} {}\ncreated at: {created_at}\nfirmware version: {firmware_version}\nserial number: {}\npath: {}\nsettings frame: {} bytes\nreadings: {}\n",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            redacted(&self.serial_number),
+            redacted(&self.path),
+            self.frame.len(),
+            self.readings.len(),
+        )
+    }
+}
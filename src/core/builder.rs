@@ -0,0 +1,105 @@
+use super::checksum;
+
+/// Low-level builder for hand-crafted frames.
+///
+/// Composes a frame from an arbitrary op code and payload bytes and appends
+/// the CRC checksum of the payload, so frames with unknown op codes can be
+/// sent to the device while reverse engineering the protocol. The builder
+/// does not validate the payload in any way.
+///
+/// # Example
+///
+/// ```rust
+/// use high_flow_next::protocol::FrameBuilder;
+///
+/// let frame = FrameBuilder::new(0x42).u8(0x01).u16be(0x0203).build();
+///
+/// assert_eq!(frame[..4], [0x42, 0x01, 0x02, 0x03]);
+/// assert_eq!(frame.len(), 6);
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FrameBuilder {
+    op_code: u8,
+    payload: Vec<u8>,
+    crc: Option<u16>,
+}
+
+impl FrameBuilder {
+    /// Creates a new builder for a frame with the passed `op_code` and an
+    /// empty payload.
+    #[must_use]
+    pub fn new(op_code: u8) -> Self {
+        Self {
+            op_code,
+            payload: Vec::new(),
+            crc: None,
+        }
+    }
+
+    /// Returns the op code of the frame.
+    #[must_use]
+    pub fn op_code(&self) -> u8 {
+        self.op_code
+    }
+
+    /// Returns the payload added so far.
+    #[must_use]
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Appends an unsigned 8-bit integer to the payload.
+    #[must_use]
+    pub fn u8(mut self, value: u8) -> Self {
+        self.payload.push(value);
+
+        self
+    }
+
+    /// Appends a big-endian unsigned 16-bit integer to the payload.
+    #[must_use]
+    pub fn u16be(self, value: u16) -> Self {
+        self.bytes(&value.to_be_bytes())
+    }
+
+    /// Appends a big-endian signed 16-bit integer to the payload.
+    #[must_use]
+    pub fn i16be(self, value: i16) -> Self {
+        self.bytes(&value.to_be_bytes())
+    }
+
+    /// Appends the passed raw `bytes` to the payload.
+    #[must_use]
+    pub fn bytes(mut self, bytes: &[u8]) -> Self {
+        self.payload.extend_from_slice(bytes);
+
+        self
+    }
+
+    /// Overrides the checksum of the frame.
+    ///
+    /// Useful to test how the device reacts to corrupted frames.
+    #[must_use]
+    pub fn crc(mut self, crc: u16) -> Self {
+        self.crc = Some(crc);
+
+        self
+    }
+
+    /// Returns the checksum that is appended to the frame.
+    #[must_use]
+    pub fn checksum(&self) -> u16 {
+        self.crc.unwrap_or_else(|| checksum(&self.payload))
+    }
+
+    /// Builds the frame (op code, payload and big-endian checksum).
+    #[must_use]
+    pub fn build(&self) -> Vec<u8> {
+        let mut frame = Vec::with_capacity(1 + self.payload.len() + 2);
+        frame.push(self.op_code);
+        frame.extend_from_slice(&self.payload);
+        frame.extend_from_slice(&self.checksum().to_be_bytes());
+
+        frame
+    }
+}
@@ -3,7 +3,7 @@ use std::io::{Result as IoResult, Write};
 
 use crc::{Crc, Digest, Table, CRC_16_USB};
 
-use crate::misc::{IoError, Reader};
+use super::{IoError, Reader};
 
 /// A writer wrapper that calculates a CRC checksum while writing data.
 ///
@@ -12,7 +12,7 @@ use crate::misc::{IoError, Reader};
 /// to obtain both the inner writer and the computed CRC value.
 pub struct CrcWriter<W> {
     writer: W,
-    digest: Digest<'static, u16, Table<1>>,
+    digest: Digest<'static, u16, CrcTable>,
 }
 
 impl<W> CrcWriter<W> {
@@ -69,7 +69,7 @@ where
 /// reading, [`finalize`](CrcReader::finalize) returns the computed CRC value.
 pub struct CrcReader<'a, R> {
     reader: &'a mut R,
-    digest: Digest<'static, u16, Table<1>>,
+    digest: Digest<'static, u16, CrcTable>,
 }
 
 impl<'a, R> CrcReader<'a, R> {
@@ -123,5 +123,25 @@ where
     }
 }
 
+/// Calculates the CRC checksum of the passed `data` at once.
+///
+/// Equivalent to feeding `data` through a [`CrcReader`] or [`CrcWriter`], but
+/// faster for large buffers.
+#[must_use]
+pub fn checksum(data: &[u8]) -> u16 {
+    CRC.checksum(data)
+}
+
+/// Lookup table used for the checksum calculation.
+///
+/// Uses a single 256 entry table by default, which keeps the binary small on
+/// embedded targets. The `fast-crc` feature switches to a slice-by-16 table
+/// (16 × 256 entries), which calculates the checksum considerably faster when
+/// processing capture files or streaming data.
+#[cfg(not(feature = "fast-crc"))]
+type CrcTable = Table<1>;
+#[cfg(feature = "fast-crc")]
+type CrcTable = Table<16>;
+
 /// Constant CRC definition using the USB CRC-16 polynomial.
-const CRC: Crc<u16> = Crc::<u16>::new(&CRC_16_USB);
+const CRC: Crc<u16, CrcTable> = Crc::<u16, CrcTable>::new(&CRC_16_USB);
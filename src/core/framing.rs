@@ -0,0 +1,106 @@
+use super::{checksum, CrcReader, GuardOutput, IoError, Reader};
+
+/// Set of frames of a device that uses the Aqua Computer framing.
+///
+/// Each frame starts with an operation code, followed by a payload and a
+/// trailing big-endian CRC checksum of the payload. Implementors only
+/// define the size and the payload of the frames they support, the framing
+/// itself is handled by [`decode_frame`] and [`decode_frames`] (and the
+/// [`FrameBuilder`](super::FrameBuilder) for the opposite direction).
+pub trait FrameSet: Sized {
+    /// Returns the size in bytes of a frame with the passed `op_code`
+    /// (including the op code and the checksum), or `None` if the op code is
+    /// unknown.
+    fn frame_size(op_code: u8) -> Option<usize>;
+
+    /// Decodes the payload of a frame with the passed `op_code`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the op code is unknown or the payload could not be
+    /// decoded.
+    fn decode_payload<R: Reader>(
+        op_code: u8,
+        reader: &mut R,
+    ) -> Result<GuardOutput<R, Self>, IoError>;
+}
+
+/// Decodes a single frame of the frame set `F` from the passed `reader`,
+/// verifying the checksum while reading.
+///
+/// # Errors
+///
+/// Returns an error if the frame could not be decoded or the checksum does
+/// not match.
+pub fn read_frame<F, R>(reader: &mut R) -> Result<GuardOutput<R, F>, IoError>
+where
+    F: FrameSet,
+    R: Reader,
+{
+    let op_code = reader.read_u8()?;
+    let mut crc = CrcReader::new(reader);
+
+    let ret = F::decode_payload(op_code, &mut crc)?;
+
+    let crc_actual = crc.finalize();
+    let crc_expected = reader.read_u16be()?;
+    if crc_actual != crc_expected {
+        return Err(IoError::ChecksumMismatch);
+    }
+
+    Ok(ret)
+}
+
+/// Decodes a single frame of the frame set `F` from the start of `reader`
+/// and advances it to the end of the frame.
+///
+/// The checksum is calculated over the whole payload at once, which is
+/// faster than [`read_frame`] for data that is already in memory.
+///
+/// # Errors
+///
+/// Returns an error if the frame could not be decoded or the checksum does
+/// not match.
+pub fn decode_frame<F>(reader: &mut &[u8]) -> Result<F, IoError>
+where
+    F: FrameSet,
+{
+    let op_code = reader.read_u8()?;
+    let payload = *reader;
+    let frame = F::decode_payload(op_code, reader)?;
+
+    let crc_actual = checksum(&payload[..payload.len() - reader.len()]);
+    let crc_expected = reader.read_u16be()?;
+    if crc_actual != crc_expected {
+        return Err(IoError::ChecksumMismatch);
+    }
+
+    Ok(frame)
+}
+
+/// Decodes all frames of the frame set `F` stored back to back in `data`.
+///
+/// The result vector is allocated once based on the size of the first frame.
+///
+/// # Errors
+///
+/// Returns an error if one of the frames could not be decoded. Trailing data
+/// that does not form a complete frame is reported as I/O error as well.
+pub fn decode_frames<F>(data: &[u8]) -> Result<Vec<F>, IoError>
+where
+    F: FrameSet,
+{
+    let mut reader = data;
+    let mut frames = Vec::new();
+
+    while !reader.is_empty() {
+        frames.push(decode_frame(&mut reader)?);
+
+        if frames.len() == 1 {
+            let size = data.len() - reader.len();
+            frames.reserve(reader.len() / size);
+        }
+    }
+
+    Ok(frames)
+}
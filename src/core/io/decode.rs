@@ -1,12 +1,17 @@
 use arrayvec::ArrayVec;
 
-use super::{reader::SkipReader, Error, Guard, GuardOutput, Reader};
+use super::{Error, Guard, GuardOutput, Reader};
 
 /// A trait for decoding values from a binary [`Reader`].
 ///
 /// Implementors define how to parse themselves from a given reader.
 /// The trait also provides helpers for optional decoding and skipping
 /// over values without fully decoding them.
+///
+/// Decoding does not perform any heap allocations as long as the reader does
+/// not allocate (e.g. when reading from a `&[u8]`), so frames can be decoded
+/// in hot loops. This includes the errors of invalid values, see
+/// [`AnyRangeError`](crate::misc::AnyRangeError).
 pub trait Decode: Sized {
     /// Decodes a value of this type from the given reader.
     ///
@@ -30,7 +35,10 @@ pub trait Decode: Sized {
     fn decode_opt<R: Reader>(
         reader: &mut R,
         read: bool,
-    ) -> Result<GuardOutput<R, Option<Self>>, Error> {
+    ) -> Result<GuardOutput<R, Option<Self>>, Error>
+    where
+        Self: FixedSize,
+    {
         if read {
             let val = Self::decode(reader)?;
 
@@ -44,19 +52,46 @@ pub trait Decode: Sized {
 
     /// Skips the bytes corresponding to a value of this type.
     ///
-    /// This is implemented by wrapping the provided reader in a [`SkipReader`]
-    /// and invoking [`Decode::decode`] to advance its cursor without keeping the value.
+    /// Advances the reader by [`FixedSize::SIZE`] bytes without decoding or
+    /// validating the skipped data.
     ///
     /// # Errors
     ///
-    /// See [`Decode::decode`] for more details.
-    fn skip_bytes<R: Reader>(reader: &mut R) -> Result<(), Error> {
-        let mut reader = SkipReader(reader);
+    /// Forwards the error from the reader.
+    fn skip_bytes<R: Reader>(reader: &mut R) -> Result<(), Error>
+    where
+        Self: FixedSize,
+    {
+        reader.skip_n(Self::SIZE)
+    }
+}
 
-        Self::decode(&mut reader)?;
+/// Types that always occupy the same number of bytes in the binary
+/// representation, independent of their value.
+///
+/// Required to skip a value using [`Decode::skip_bytes`] without decoding it.
+pub trait FixedSize {
+    /// Number of bytes the type occupies in the binary representation.
+    const SIZE: usize;
+}
 
-        Ok(())
-    }
+impl FixedSize for u8 {
+    const SIZE: usize = 1;
+}
+
+impl FixedSize for u16 {
+    const SIZE: usize = 2;
+}
+
+impl FixedSize for i16 {
+    const SIZE: usize = 2;
+}
+
+impl<T, const N: usize> FixedSize for [T; N]
+where
+    T: FixedSize,
+{
+    const SIZE: usize = N * T::SIZE;
 }
 
 impl Decode for u8 {
@@ -0,0 +1,175 @@
+use std::convert::Infallible;
+use std::fmt::{Debug, Display};
+use std::io::Error as IoError;
+
+use thiserror::Error;
+
+use crate::core::wrapped::{AnyRangeError, RangeError};
+
+/// Error type for protocol and I/O operations.
+///
+/// This enum unifies all error conditions that may occur while
+/// reading, decoding, or validating frames and settings from the
+/// high flow NEXT device.
+#[derive(Error, Debug)]
+pub enum Error {
+    /// I/O error while reading from a stream or device.
+    #[error("IO Error: {0}")]
+    IoError(#[from] IoError),
+
+    /// Encountered an invalid or unknown value in the protocol stream.
+    ///
+    /// Carries the field name and the unexpected numeric value.
+    #[error("Invalid or unknown value (name={0}, value={1})")]
+    InvalidValue(&'static str, usize),
+
+    /// A decoded value was out of its valid range.
+    ///
+    /// Wraps a [`RangeError`] describing the bounds violation. The typed
+    /// bounds can be accessed using [`AnyRangeError::downcast_ref`].
+    #[error("Range Error: {0}")]
+    RangeError(AnyRangeError),
+
+    /// A CRC checksum mismatch was detected in a frame.
+    #[error("Checksum does not match!")]
+    ChecksumMismatch,
+
+    /// The firmware version of a stored frame does not match the firmware
+    /// version of the target device.
+    #[error("Firmware version does not match (expected={expected:#06x}, actual={actual:#06x})")]
+    FirmwareMismatch {
+        /// Firmware version the frame was created with.
+        expected: u16,
+
+        /// Firmware version of the target device.
+        actual: u16,
+    },
+
+    /// A CRC checksum mismatch was detected in a frame received from a
+    /// [`Device`](crate::device::Device).
+    ///
+    /// In contrast to [`ChecksumMismatch`](Self::ChecksumMismatch) the raw
+    /// bytes of the frame (and of the last valid frame received before) are
+    /// retained, so they can be persisted for later analysis. Use
+    /// [`is_checksum_mismatch`](Self::is_checksum_mismatch) to handle both
+    /// variants alike.
+    #[error("Checksum does not match! (frame length={}, retained={})", .0.len, .0.frame.len())]
+    CorruptFrame(Box<CorruptFrame>),
+
+    /// A limit of the [`DecodeLimits`](crate::protocol::DecodeLimits) was
+    /// hit while decoding untrusted input.
+    ///
+    /// Carries the name of the limit (e.g. `max_frames`) and the actual
+    /// value that exceeded it.
+    #[error("Decode limit exceeded (limit={0}, actual={1})")]
+    LimitExceeded(&'static str, usize),
+}
+
+impl Error {
+    /// Returns `true` if the error is a [`ChecksumMismatch`](Self::ChecksumMismatch)
+    /// or a [`CorruptFrame`](Self::CorruptFrame).
+    #[must_use]
+    pub fn is_checksum_mismatch(&self) -> bool {
+        matches!(self, Self::ChecksumMismatch | Self::CorruptFrame(_))
+    }
+
+    /// Returns the name of the limit and the actual value if the error is a
+    /// [`LimitExceeded`](Self::LimitExceeded) error.
+    #[must_use]
+    pub fn limit_exceeded(&self) -> Option<(&'static str, usize)> {
+        match *self {
+            Self::LimitExceeded(name, actual) => Some((name, actual)),
+            _ => None,
+        }
+    }
+}
+
+/// Raw data of a frame with a CRC mismatch (see [`Error::CorruptFrame`]).
+///
+/// The frames are truncated to the retention limit of the device (see
+/// [`Device::with_retained_frame_limit`](crate::device::Device::with_retained_frame_limit)).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CorruptFrame {
+    /// Raw bytes of the frame with the CRC mismatch (starting with the op
+    /// code).
+    pub frame: Vec<u8>,
+
+    /// Length of the frame before it was truncated.
+    pub len: usize,
+
+    /// Raw bytes of the last frame that was decoded successfully before,
+    /// if any.
+    pub previous: Option<Vec<u8>>,
+}
+
+impl CorruptFrame {
+    /// Returns `true` if the frame was truncated to the retention limit.
+    #[must_use]
+    pub fn is_truncated(&self) -> bool {
+        self.frame.len() < self.len
+    }
+}
+
+impl<T> From<RangeError<T>> for Error
+where
+    T: Debug + Display + Send + Sync + 'static,
+{
+    fn from(value: RangeError<T>) -> Self {
+        Self::RangeError(AnyRangeError::new(value))
+    }
+}
+
+impl From<Infallible> for Error {
+    fn from(_error: Infallible) -> Self {
+        unreachable!()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Error {
+    fn format(&self, fmt: defmt::Formatter<'_>) {
+        match self {
+            Self::IoError(err) => {
+                defmt::write!(fmt, "IO Error: {}", defmt::Display2Format(err));
+            }
+            Self::InvalidValue(name, value) => {
+                defmt::write!(
+                    fmt,
+                    "Invalid or unknown value (name={=str}, value={=usize})",
+                    name,
+                    value
+                );
+            }
+            Self::RangeError(err) => {
+                let err = err.to_owned();
+
+                defmt::write!(
+                    fmt,
+                    "Range Error: Value out of range (min={=str}, max={=str}, val={=str})!",
+                    err.min.as_str(),
+                    err.max.as_str(),
+                    err.val.as_str()
+                );
+            }
+            Self::ChecksumMismatch => defmt::write!(fmt, "Checksum does not match!"),
+            Self::CorruptFrame(frame) => defmt::write!(
+                fmt,
+                "Checksum does not match! (frame length={=usize}, retained={=usize})",
+                frame.len,
+                frame.frame.len()
+            ),
+            Self::FirmwareMismatch { expected, actual } => defmt::write!(
+                fmt,
+                "Firmware version does not match (expected={=u16:#06x}, actual={=u16:#06x})",
+                expected,
+                actual
+            ),
+            Self::LimitExceeded(name, actual) => defmt::write!(
+                fmt,
+                "Decode limit exceeded (limit={=str}, actual={=usize})",
+                name,
+                actual
+            ),
+        }
+    }
+}
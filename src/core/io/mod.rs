@@ -0,0 +1,7 @@
+mod decode;
+mod error;
+mod reader;
+
+pub use self::decode::{Decode, FixedSize};
+pub use self::error::{CorruptFrame, Error};
+pub use self::reader::{Guard, GuardOutput, PositionReader, Reader, ValueGuard};
@@ -1,5 +1,4 @@
 use std::io::Read;
-use std::marker::PhantomData;
 
 use super::Error;
 
@@ -8,9 +7,10 @@ use super::Error;
 /// Implementations provide methods to read primitive values (`u8`, `u16`, etc.)
 /// and to manage a [`Guard`] type that wraps decoded results.
 ///
-/// The `Guard` mechanism allows the same decode logic to be reused in both
-/// *normal* mode (returning real values) and *skip* mode (advancing the cursor
-/// without keeping values).
+/// The `Guard` mechanism allows the same decode logic to be reused by readers
+/// that represent the decoded values differently. Values that only need to be
+/// skipped are not decoded at all (see
+/// [`Decode::skip_bytes`](super::Decode::skip_bytes)).
 pub trait Reader: Sized {
     /// The [`Guard`] implementation associated with this reader.
     type Guard: Guard;
@@ -25,6 +25,19 @@ pub trait Reader: Sized {
         Ok(())
     }
 
+    /// Skips over `count` bytes in the input.
+    fn skip_n(&mut self, mut count: usize) -> Result<(), Error> {
+        let mut buf = [0; 64];
+
+        while count > 0 {
+            let len = count.min(buf.len());
+            self.read_exact(&mut buf[..len])?;
+            count -= len;
+        }
+
+        Ok(())
+    }
+
     /// Reads an unsigned 8-bit integer.
     fn read_u8(&mut self) -> Result<u8, Error> {
         let mut buf = [0; 1];
@@ -63,8 +76,7 @@ pub type GuardOutput<R, T> = <<R as Reader>::Guard as Guard>::Output<T>;
 
 /// Defines how to wrap, access, and extract values during decoding.
 ///
-/// - In **normal mode** (see [`ValueGuard`]), `Output<T>` is just `T`.
-/// - In **skip mode** (see [`SkipGuard`]), `Output<T>` is `PhantomData<T>` to avoid allocation.
+/// For the readers of this crate (see [`ValueGuard`]) `Output<T>` is just `T`.
 pub trait Guard {
     /// Type of wrapped output for a value of type `T`.
     type Output<T>;
@@ -100,28 +112,49 @@ where
     }
 }
 
-/// A wrapper around a [`Reader`] that discards values instead of keeping them.
+/// A wrapper around a [`Reader`] that tracks the number of bytes read.
 ///
-/// Used by [`Decode::skip_bytes`](super::Decode::skip_bytes).
+/// Useful to find out at which offset of a frame the decoding failed.
 #[derive(Debug)]
-pub struct SkipReader<'a, R: Reader>(pub(super) &'a mut R);
+pub struct PositionReader<'a, R> {
+    reader: &'a mut R,
+    position: usize,
+}
+
+impl<'a, R> PositionReader<'a, R> {
+    /// Creates a new [`PositionReader`] wrapping the given reader.
+    pub fn new(reader: &'a mut R) -> Self {
+        Self {
+            reader,
+            position: 0,
+        }
+    }
 
-impl<R> Reader for SkipReader<'_, R>
+    /// Returns the number of bytes read so far.
+    #[must_use]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl<R> Reader for PositionReader<'_, R>
 where
     R: Reader,
 {
-    type Guard = SkipGuard;
+    type Guard = R::Guard;
 
     fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Error> {
-        self.0.read_exact(buf)
+        self.reader.read_exact(buf)?;
+        self.position += buf.len();
+
+        Ok(())
     }
 
     fn guard<F, T>(f: F) -> <Self::Guard as Guard>::Output<T>
     where
         F: FnOnce(Self::Guard) -> T,
     {
-        let _ = f;
-        PhantomData
+        R::guard(f)
     }
 }
 
@@ -153,33 +186,3 @@ impl Guard for ValueGuard {
         value
     }
 }
-
-/// A [`Guard`] implementation for **skip mode**.
-/// Values are replaced with [`PhantomData`] and cannot be accessed.
-#[derive(Debug)]
-pub struct SkipGuard;
-
-impl Guard for SkipGuard {
-    type Output<T> = PhantomData<T>;
-
-    #[inline]
-    fn transpose_result<T, E>(res: Self::Output<Result<T, E>>) -> Result<Self::Output<T>, E> {
-        let _res = res;
-        Ok(PhantomData)
-    }
-
-    #[inline]
-    fn get<'a, T>(&self, _value: &'a Self::Output<T>) -> &'a T {
-        panic!("Unable to get value in `Skip` mode!");
-    }
-
-    #[inline]
-    fn get_mut<'a, T>(&self, _value: &'a mut Self::Output<T>) -> &'a mut T {
-        panic!("Unable to get value in `Skip` mode!");
-    }
-
-    #[inline]
-    fn extract<T>(&self, _value: Self::Output<T>) -> T {
-        panic!("Unable to extract value in `Skip` mode!");
-    }
-}
@@ -0,0 +1,33 @@
+//! Device-agnostic core shared by all devices of Aqua Computer.
+//!
+//! Aqua Computer USB devices (high flow NEXT, Quadro, Octo, D5 Next, ...)
+//! use the same framing: an op code, a payload of big-endian values and a
+//! CRC-16/USB checksum of the payload. This module contains the parts that do
+//! not depend on a specific device, so support for other devices can be
+//! layered on top of it:
+//!
+//! - the binary [`Reader`] and the [`Decode`] / [`FixedSize`] traits,
+//! - the checksum ([`checksum`], [`CrcReader`], [`CrcWriter`]),
+//! - the [`FrameSet`] trait and the generic frame decoding
+//!   ([`read_frame`], [`decode_frame`], [`decode_frames`]) and the
+//!   [`FrameBuilder`],
+//! - the range checked [`Wrapped`] values.
+//!
+//! The high flow NEXT specific frames are defined in
+//! [`protocol`](crate::protocol). The items are re-exported by
+//! [`misc`](crate::misc) as well.
+
+mod builder;
+mod crc;
+mod framing;
+mod io;
+mod wrapped;
+
+pub use self::builder::FrameBuilder;
+pub use self::crc::{checksum, CrcReader, CrcWriter};
+pub use self::framing::{decode_frame, decode_frames, read_frame, FrameSet};
+pub use self::io::{
+    CorruptFrame, Decode, Error as IoError, FixedSize, Guard, GuardOutput, PositionReader, Reader,
+    ValueGuard,
+};
+pub use self::wrapped::{AnyRangeError, Percent, RangeError, Ranged, ValueVerifier, Wrapped};
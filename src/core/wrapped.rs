@@ -0,0 +1,741 @@
+use std::any::Any;
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use thiserror::Error;
+
+use super::{Decode, FixedSize, Guard, GuardOutput, IoError, Reader};
+
+/// Macro to define a new typed wrapper around a primitive value.
+///
+/// The conversions from and into the primitive value and the comparisons
+/// with it (e.g. `flow < 60`) are implemented by [`Wrapped`] itself.
+///
+/// Example:
+/// ```rust
+/// use high_flow_next::{define_wrapped, impl_ranged, impl_verify_simple};
+///
+/// define_wrapped! {
+///     /// Speed of a fan (0..100).
+///     pub type FanSpeed<u8, FanSpeedTag>;
+/// }
+/// impl_ranged!(FanSpeed<u8, FanSpeedTag>, 0, 100);
+///
+/// define_wrapped! {
+///     /// Brightness level (0..255).
+///     pub type Brightness<u8, BrightnessTag>;
+/// }
+/// impl_verify_simple!(Brightness<u8, BrightnessTag>);
+///
+/// let speed = FanSpeed::try_from(42).unwrap();
+/// assert!(speed == 42 && 42 == speed);
+/// assert!(speed < 50 && 10 < speed);
+/// assert_eq!(u8::from(speed), 42);
+/// assert!(FanSpeed::try_from(101).is_err());
+///
+/// assert!(Brightness::from_value(255).is_ok_and(|x| x == 255));
+/// ```
+#[macro_export]
+macro_rules! define_wrapped {
+    ($(#[$meta:meta])* $pub:vis type $name:ident<$base:ty, $tag:ident> ;) => {
+        $(#[$meta])*
+        $pub type $name = $crate::core::Wrapped<$base, $tag>;
+
+        #[allow(missing_docs)]
+        #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+        pub struct $tag;
+    };
+}
+
+/// Macro to implement a trivial verifier that accepts all values.
+///
+/// Use this when no validation is needed for the type.
+#[macro_export]
+macro_rules! impl_verify_simple {
+    ($value_type:ident<$base:ty, $tag:ident>) => {
+        impl $crate::core::ValueVerifier<$base> for $tag {
+            type Error = ::core::convert::Infallible;
+
+            fn verify(val: $base) -> Result<$base, Self::Error> {
+                Ok(val)
+            }
+        }
+    };
+}
+
+/// Macro to implement a ranged verifier for a wrapper type.
+///
+/// The type will only accept values within the inclusive range [`min`, `max`].
+#[macro_export]
+macro_rules! impl_ranged {
+    ($value_type:ident<$base:ty, $tag:ident>, $min:expr, $max:expr) => {
+        impl $crate::core::Ranged<$base> for $tag {
+            #[inline]
+            fn min_inclusive() -> $base {
+                $min
+            }
+
+            #[inline]
+            fn max_inclusive() -> $base {
+                $max
+            }
+        }
+    };
+}
+
+/// Macro to implement [`Percent`] for a wrapper type.
+///
+/// `scale` is the number of raw units of the wrapper per percent (e.g. `100.0`
+/// for values stored in 1/100 %).
+#[macro_export]
+macro_rules! impl_percent {
+    ($value_type:ident<$base:ty, $tag:ident>, $scale:expr) => {
+        impl $crate::core::Percent for $tag {
+            const SCALE: f64 = $scale;
+        }
+    };
+}
+
+/// A strongly typed wrapper around a primitive value with validation.
+///
+/// Wrappers are parameterized by a phantom `tag` type which implements
+/// either [`Ranged`] or [`ValueVerifier`]. This allows creating distinct
+/// types from the same base primitive while enforcing domain-specific rules.
+///
+/// Wrappers are ordered and hashed by their primitive value, so they can be
+/// used as keys of a `HashMap` or `BTreeMap`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Wrapped<T, X> {
+    value: T,
+    tag: PhantomData<X>,
+}
+
+impl<T, X> Wrapped<T, X>
+where
+    T: Ord,
+    X: Ranged<T>,
+{
+    /// Returns the minimum allowed value.
+    #[must_use]
+    pub fn min_inclusive() -> T {
+        X::min_inclusive()
+    }
+
+    /// Returns the maximum allowed value.
+    #[must_use]
+    pub fn max_inclusive() -> T {
+        X::max_inclusive()
+    }
+}
+
+impl<T, X> Wrapped<T, X>
+where
+    T: Ord + Copy + Into<f64> + TryFrom<i64>,
+    X: Ranged<T> + Percent,
+{
+    /// Creates a new wrapper from a value in percent.
+    ///
+    /// The value is rounded to the precision of the wrapper and clamped to
+    /// its valid range.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn from_percent(percent: f64) -> Self {
+        let min = X::min_inclusive().into();
+        let max = X::max_inclusive().into();
+
+        let raw = (percent * X::SCALE).round();
+        let raw = if raw.is_nan() {
+            min
+        } else {
+            raw.clamp(min, max)
+        };
+        let value = T::try_from(raw as i64).unwrap_or_else(|_| unreachable!());
+
+        Self {
+            value,
+            tag: PhantomData,
+        }
+    }
+
+    /// Returns the value in percent.
+    #[must_use]
+    pub fn percent(&self) -> f64 {
+        self.value.into() / X::SCALE
+    }
+
+    /// Returns the difference between the value the device stores for the
+    /// `requested` percentage and the `requested` percentage itself (see
+    /// [`from_percent`](Self::from_percent)).
+    ///
+    /// This is `0.0` if the value can be represented exactly.
+    #[must_use]
+    pub fn quantization_error(requested: f64) -> f64 {
+        Self::from_percent(requested).percent() - requested
+    }
+}
+
+/// Arithmetic that keeps the result within the range of the wrapper.
+///
+/// The operands are raw values in the unit of the wrapper, so fade / ramp
+/// logic (e.g. gradually changing the speed of an effect) can operate on the
+/// typed values directly.
+impl<T, X> Wrapped<T, X>
+where
+    T: Ord + Copy + Into<i64> + TryFrom<i64>,
+    X: Ranged<T>,
+{
+    /// Adds `rhs` to the value, returning `None` if the result is outside the
+    /// valid range.
+    #[must_use]
+    pub fn checked_add(self, rhs: T) -> Option<Self> {
+        Self::checked(self.value.into() + rhs.into())
+    }
+
+    /// Subtracts `rhs` from the value, returning `None` if the result is
+    /// outside the valid range.
+    #[must_use]
+    pub fn checked_sub(self, rhs: T) -> Option<Self> {
+        Self::checked(self.value.into() - rhs.into())
+    }
+
+    /// Adds `rhs` to the value, clamping the result to the valid range.
+    #[must_use]
+    pub fn saturating_add(self, rhs: T) -> Self {
+        Self::clamped(self.value.into() + rhs.into())
+    }
+
+    /// Subtracts `rhs` from the value, clamping the result to the valid range.
+    #[must_use]
+    pub fn saturating_sub(self, rhs: T) -> Self {
+        Self::clamped(self.value.into() - rhs.into())
+    }
+
+    /// Linear interpolation between `self` (`t = 0.0`) and `other`
+    /// (`t = 1.0`).
+    ///
+    /// `t` is clamped to `0.0..=1.0` and the result is rounded to the nearest
+    /// raw value, so it always lies between both values.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    pub fn lerp(&self, other: &Self, t: f64) -> Self {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+
+        let a = self.value.into();
+        let b = other.value.into();
+        let raw = a + ((b - a) as f64 * t).round() as i64;
+
+        Self::clamped(raw)
+    }
+
+    /// Returns the position of the value within the valid range, from `0.0`
+    /// (minimum) to `1.0` (maximum).
+    ///
+    /// Useful to map values of different types uniformly onto gauges or bar
+    /// graphs.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    pub fn fraction(&self) -> f32 {
+        let min = X::min_inclusive().into();
+        let max = X::max_inclusive().into();
+
+        if max == min {
+            return 0.0;
+        }
+
+        ((self.value.into() - min) as f64 / (max - min) as f64) as f32
+    }
+
+    /// Creates a new wrapper from its position within the valid range (see
+    /// [`fraction`](Self::fraction)).
+    ///
+    /// `fraction` is clamped to `0.0..=1.0` and the value is rounded to the
+    /// nearest raw value.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    pub fn from_fraction(fraction: f32) -> Self {
+        let fraction = if fraction.is_nan() {
+            0.0
+        } else {
+            f64::from(fraction.clamp(0.0, 1.0))
+        };
+
+        let min = X::min_inclusive().into();
+        let max = X::max_inclusive().into();
+        let raw = min + ((max - min) as f64 * fraction).round() as i64;
+
+        Self::clamped(raw)
+    }
+
+    fn checked(raw: i64) -> Option<Self> {
+        let value = T::try_from(raw).ok()?;
+
+        if value < X::min_inclusive() || value > X::max_inclusive() {
+            return None;
+        }
+
+        Some(Self {
+            value,
+            tag: PhantomData,
+        })
+    }
+
+    fn clamped(raw: i64) -> Self {
+        let raw = raw.clamp(X::min_inclusive().into(), X::max_inclusive().into());
+        let value = T::try_from(raw).unwrap_or_else(|_| unreachable!());
+
+        Self {
+            value,
+            tag: PhantomData,
+        }
+    }
+}
+
+impl<T, X> Wrapped<T, X>
+where
+    X: ValueVerifier<T>,
+{
+    /// Creates a new wrapper from a primitive value,
+    /// validating it with the associated verifier.
+    pub fn from_value(val: T) -> Result<Self, X::Error> {
+        let val = X::verify(val)?;
+
+        Ok(Self {
+            value: val,
+            tag: PhantomData,
+        })
+    }
+}
+
+impl<T, X> PartialEq<T> for Wrapped<T, X>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &T) -> bool {
+        self.value == *other
+    }
+}
+
+impl<T, X> PartialOrd<T> for Wrapped<T, X>
+where
+    T: PartialOrd,
+{
+    fn partial_cmp(&self, other: &T) -> Option<Ordering> {
+        self.value.partial_cmp(other)
+    }
+}
+
+/// Implements the conversions between the wrapper and the primitive value
+/// and the comparisons of the primitive value with the wrapper.
+///
+/// Ranged wrappers implement `TryFrom<$base>`, returning a [`RangeError`]
+/// for values outside the range.
+macro_rules! impl_primitive {
+    ($( $base:ty ),*) => {
+        $(
+            impl<X> TryFrom<$base> for Wrapped<$base, X>
+            where
+                X: Ranged<$base>,
+            {
+                type Error = RangeError<$base>;
+
+                fn try_from(value: $base) -> Result<Self, Self::Error> {
+                    Self::from_value(value)
+                }
+            }
+
+            impl<X> From<Wrapped<$base, X>> for $base {
+                fn from(value: Wrapped<$base, X>) -> Self {
+                    value.value
+                }
+            }
+
+            impl<X> PartialEq<Wrapped<$base, X>> for $base {
+                fn eq(&self, other: &Wrapped<$base, X>) -> bool {
+                    *self == other.value
+                }
+            }
+
+            impl<X> PartialOrd<Wrapped<$base, X>> for $base {
+                fn partial_cmp(&self, other: &Wrapped<$base, X>) -> Option<Ordering> {
+                    self.partial_cmp(&other.value)
+                }
+            }
+        )*
+    };
+}
+
+impl_primitive!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+impl<T, X> Deref for Wrapped<T, X> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+/// Serializes the wrapper as its primitive value.
+#[cfg(feature = "serde")]
+impl<T, X> serde::Serialize for Wrapped<T, X>
+where
+    T: serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serializer)
+    }
+}
+
+/// Deserializes the wrapper from its primitive value, validating it with
+/// the associated verifier.
+#[cfg(feature = "serde")]
+impl<'de, T, X> serde::Deserialize<'de> for Wrapped<T, X>
+where
+    T: serde::Deserialize<'de>,
+    X: ValueVerifier<T>,
+    X::Error: Display,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = T::deserialize(deserializer)?;
+
+        Self::from_value(value).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Formats the wrapper as its primitive value.
+#[cfg(feature = "defmt")]
+impl<T, X> defmt::Format for Wrapped<T, X>
+where
+    T: defmt::Format,
+{
+    fn format(&self, fmt: defmt::Formatter<'_>) {
+        self.value.format(fmt);
+    }
+}
+
+impl<T, X> FixedSize for Wrapped<T, X>
+where
+    T: FixedSize,
+{
+    const SIZE: usize = T::SIZE;
+}
+
+/// Implements [`Decode`] for `Wrapped<u8, X>`.
+impl<X> Decode for Wrapped<u8, X>
+where
+    X: ValueVerifier<u8>,
+    IoError: From<X::Error>,
+{
+    fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
+        let val = reader.read_u8()?;
+        let ret = R::guard(|_| Self::from_value(val));
+
+        Ok(R::Guard::transpose_result(ret)?)
+    }
+}
+
+/// Implements [`Decode`] for `Wrapped<u16, X>`.
+impl<X> Decode for Wrapped<u16, X>
+where
+    X: ValueVerifier<u16>,
+    IoError: From<X::Error>,
+{
+    fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
+        let val = reader.read_u16be()?;
+        let ret = R::guard(|_| Self::from_value(val));
+
+        Ok(R::Guard::transpose_result(ret)?)
+    }
+}
+
+/// Implements [`Decode`] for `Wrapped<i16, X>`.
+impl<X> Decode for Wrapped<i16, X>
+where
+    X: ValueVerifier<i16>,
+    IoError: From<X::Error>,
+{
+    fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
+        let val = reader.read_i16be()?;
+        let ret = R::guard(|_| Self::from_value(val));
+
+        Ok(R::Guard::transpose_result(ret)?)
+    }
+}
+
+/// Trait for types that define inclusive minimum and maximum bounds.
+pub trait Ranged<T> {
+    /// Return the minimum inclusive value for this range.
+    fn min_inclusive() -> T;
+
+    /// Return the maximum inclusive value for this range.
+    fn max_inclusive() -> T;
+}
+
+/// Trait for types that describe a percentage stored in fixed steps.
+pub trait Percent {
+    /// Number of raw units per percent.
+    const SCALE: f64;
+}
+
+/// Trait for types that verify whether a value is valid.
+///
+/// Custom verifiers can reject values outside ranges, apply additional
+/// constraints, or simply accept all values.
+pub trait ValueVerifier<T> {
+    /// Error returned by the verifier.
+    type Error;
+
+    /// Verify if the given `value` is valid, returning either the valid value
+    /// `Ok(value)` or a suitable error `Err(error)`.
+    fn verify(value: T) -> Result<T, Self::Error>;
+}
+
+/// Default implementation of [`ValueVerifier`] for any [`Ranged`] type.
+///
+/// Rejects values outside the inclusive min/max bounds.
+impl<T, X> ValueVerifier<T> for X
+where
+    X: Ranged<T>,
+    T: Ord,
+{
+    type Error = RangeError<T>;
+
+    fn verify(val: T) -> Result<T, Self::Error> {
+        let min = X::min_inclusive();
+        let max = X::max_inclusive();
+
+        if val < min || val > max {
+            Err(RangeError { min, max, val })
+        } else {
+            Ok(val)
+        }
+    }
+}
+
+/// Error returned when a value lies outside the allowed range.
+#[derive(Debug, Error)]
+#[error("Value out of range (min={min}, max={max}, val={val})!")]
+pub struct RangeError<T> {
+    /// Minimum inclusive value of the range.
+    pub min: T,
+
+    /// Maximum inclusive value of the range.
+    pub max: T,
+
+    /// Actual value (not in range).
+    pub val: T,
+}
+
+impl<T> RangeError<T>
+where
+    T: Display,
+{
+    /// Converts this error into an owned `RangeError<String>`,
+    /// useful for error reporting where `T` is not `Clone`.
+    pub fn to_owned(&self) -> RangeError<String> {
+        RangeError {
+            min: self.min.to_string(),
+            max: self.max.to_string(),
+            val: self.val.to_string(),
+        }
+    }
+}
+
+/// Type erased [`RangeError`] used in the error types of this crate (e.g.
+/// [`IoError::RangeError`]).
+///
+/// In contrast to [`RangeError::to_owned`] the typed bounds are retained and
+/// can be accessed using [`downcast_ref`](Self::downcast_ref), or as
+/// floating point values using [`to_f64`](Self::to_f64), e.g. to render an
+/// input with the correct bounds.
+///
+/// Errors of primitive numeric values are stored inline, so creating them
+/// (e.g. while decoding a frame) does not allocate. Errors of other types
+/// are boxed.
+#[derive(Debug)]
+pub struct AnyRangeError(Inner);
+
+impl AnyRangeError {
+    /// Creates a new type erased error from the passed typed `error`.
+    pub fn new<T>(error: RangeError<T>) -> Self
+    where
+        T: Debug + Display + Send + Sync + 'static,
+    {
+        Self(Inner::new(error))
+    }
+
+    /// Returns the typed error if it contains values of type `T`.
+    #[must_use]
+    pub fn downcast_ref<T>(&self) -> Option<&RangeError<T>>
+    where
+        T: 'static,
+    {
+        self.0.as_erased().as_any().downcast_ref()
+    }
+
+    /// Returns the typed error if it contains values of type `T`, or `self`
+    /// otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns `self` if the error does not contain values of type `T`.
+    pub fn downcast<T>(self) -> Result<RangeError<T>, Self>
+    where
+        T: 'static,
+    {
+        self.0.downcast().map_err(Self)
+    }
+
+    /// Returns the bounds and the value as floating point values, or `None`
+    /// if the error does not contain primitive numeric values.
+    #[must_use]
+    pub fn to_f64(&self) -> Option<RangeError<f64>> {
+        self.0.as_erased().to_f64()
+    }
+
+    /// Returns the bounds and the value formatted as strings.
+    #[must_use]
+    pub fn to_owned(&self) -> RangeError<String> {
+        self.0.as_erased().to_strings()
+    }
+}
+
+impl Display for AnyRangeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Display::fmt(self.0.as_erased(), f)
+    }
+}
+
+impl std::error::Error for AnyRangeError {}
+
+impl<T> From<RangeError<T>> for AnyRangeError
+where
+    T: Debug + Display + Send + Sync + 'static,
+{
+    fn from(error: RangeError<T>) -> Self {
+        Self::new(error)
+    }
+}
+
+macro_rules! define_inner {
+    ($( $variant:ident($ty:ty), )*) => {
+        #[derive(Debug)]
+        enum Inner {
+            $( $variant(RangeError<$ty>), )*
+            Boxed(Box<dyn ErasedRangeError>),
+        }
+
+        impl Inner {
+            fn new<T>(error: RangeError<T>) -> Self
+            where
+                T: Debug + Display + Send + Sync + 'static,
+            {
+                $(
+                    let error = match cast(error) {
+                        Ok(error) => return Self::$variant(error),
+                        Err(error) => error,
+                    };
+                )*
+
+                Self::Boxed(Box::new(error))
+            }
+
+            fn as_erased(&self) -> &dyn ErasedRangeError {
+                match self {
+                    $( Self::$variant(error) => error, )*
+                    Self::Boxed(error) => &**error,
+                }
+            }
+
+            fn downcast<T: 'static>(self) -> Result<RangeError<T>, Self> {
+                match self {
+                    $( Self::$variant(error) => cast(error).map_err(Self::$variant), )*
+                    Self::Boxed(error) if error.as_any().is::<RangeError<T>>() => {
+                        let any: Box<dyn Any> = error.into_any();
+
+                        Ok(*any.downcast().unwrap_or_else(|_| unreachable!()))
+                    }
+                    Self::Boxed(error) => Err(Self::Boxed(error)),
+                }
+            }
+        }
+    };
+}
+
+define_inner! {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+}
+
+/// Moves the passed `error` into a `RangeError<T>` if it contains values of
+/// type `T`, without allocating.
+fn cast<T: 'static, X: 'static>(error: RangeError<X>) -> Result<RangeError<T>, RangeError<X>> {
+    let mut error = Some(error);
+
+    match (&mut error as &mut dyn Any).downcast_mut::<Option<RangeError<T>>>() {
+        Some(x) => Ok(x.take().unwrap_or_else(|| unreachable!())),
+        None => Err(error.unwrap_or_else(|| unreachable!())),
+    }
+}
+
+trait ErasedRangeError: Debug + Display + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+
+    fn to_f64(&self) -> Option<RangeError<f64>>;
+
+    fn to_strings(&self) -> RangeError<String>;
+}
+
+impl<T> ErasedRangeError for RangeError<T>
+where
+    T: Debug + Display + Send + Sync + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn to_f64(&self) -> Option<RangeError<f64>> {
+        fn convert<T>(error: &dyn Any) -> Option<RangeError<f64>>
+        where
+            T: Copy + Into<f64> + 'static,
+        {
+            let error = error.downcast_ref::<RangeError<T>>()?;
+
+            Some(RangeError {
+                min: error.min.into(),
+                max: error.max.into(),
+                val: error.val.into(),
+            })
+        }
+
+        let any = self.as_any();
+
+        convert::<u8>(any)
+            .or_else(|| convert::<u16>(any))
+            .or_else(|| convert::<u32>(any))
+            .or_else(|| convert::<i8>(any))
+            .or_else(|| convert::<i16>(any))
+            .or_else(|| convert::<i32>(any))
+            .or_else(|| convert::<f32>(any))
+            .or_else(|| convert::<f64>(any))
+    }
+
+    fn to_strings(&self) -> RangeError<String> {
+        RangeError::to_owned(self)
+    }
+}
@@ -0,0 +1,204 @@
+use std::time::{Duration, Instant};
+
+use crate::misc::{Decode, IoError};
+use crate::protocol::{settings::SettingsDiff, Frame, Settings};
+
+use super::{Device, Transport, SETTINGS_REPORT_ID};
+
+/// Event emitted by the [`SettingsCache`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SettingsEvent {
+    /// The settings stored on the device changed since the last read (e.g.
+    /// they were edited using the buttons of the device).
+    SettingsChanged(SettingsDiff),
+}
+
+/// Read-only cache of the settings of a [`Device`].
+///
+/// The cache stores the last decoded settings together with the raw report
+/// they were decoded from. [`poll`](Self::poll) re-reads the settings once the
+/// refresh interval elapsed and reports a [`SettingsEvent::SettingsChanged`]
+/// if the settings on the device changed, so callers do not have to diff the
+/// settings manually. The report is only decoded if the raw bytes changed.
+#[derive(Debug, Clone)]
+pub struct SettingsCache {
+    interval: Duration,
+    settings: Option<Settings>,
+    raw: Vec<u8>,
+    last_read: Option<Instant>,
+}
+
+impl SettingsCache {
+    /// Creates a new empty cache that re-reads the settings every `interval`.
+    #[must_use]
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            settings: None,
+            raw: Vec::new(),
+            last_read: None,
+        }
+    }
+
+    /// Returns the refresh interval of the cache.
+    #[must_use]
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Returns the cached settings (`None` if the settings were not read
+    /// yet).
+    #[must_use]
+    pub fn settings(&self) -> Option<&Settings> {
+        self.settings.as_ref()
+    }
+
+    /// Returns the raw report the cached settings were decoded from (empty if
+    /// the settings were not read yet).
+    #[must_use]
+    pub fn raw(&self) -> &[u8] {
+        &self.raw
+    }
+
+    /// Returns the point in time the settings were read the last time.
+    #[must_use]
+    pub fn last_read(&self) -> Option<Instant> {
+        self.last_read
+    }
+
+    /// Clears the cache, so the settings are read by the next call to
+    /// [`poll`](Self::poll).
+    pub fn invalidate(&mut self) {
+        self.settings = None;
+        self.raw.clear();
+        self.last_read = None;
+    }
+
+    /// Re-reads the settings from the `device` if the cache is empty or the
+    /// refresh interval elapsed since the last read.
+    ///
+    /// Returns [`SettingsEvent::SettingsChanged`] if the settings changed.
+    /// The initial read does not emit an event.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings could not be read or decoded. The
+    /// cached settings are kept in this case.
+    pub fn poll<T>(
+        &mut self,
+        device: &mut Device<T>,
+        now: Instant,
+    ) -> Result<Option<SettingsEvent>, IoError>
+    where
+        T: Transport,
+    {
+        match self.last_read {
+            Some(last_read) if now.saturating_duration_since(last_read) < self.interval => Ok(None),
+            _ => self.refresh(device, now),
+        }
+    }
+
+    /// Re-reads the settings from the `device`, independent of the refresh
+    /// interval (see [`poll`](Self::poll)).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings could not be read or decoded.
+    pub fn refresh<T>(
+        &mut self,
+        device: &mut Device<T>,
+        now: Instant,
+    ) -> Result<Option<SettingsEvent>, IoError>
+    where
+        T: Transport,
+    {
+        let mut report = device.read_report(SETTINGS_REPORT_ID)?;
+        if self.settings.is_some() && report == self.raw {
+            self.last_read = Some(now);
+
+            return Ok(None);
+        }
+
+        let raw = report;
+        let Frame::Settings(settings) = Frame::decode(&mut report)?;
+
+        self.raw.clear();
+        self.raw.extend_from_slice(raw);
+        self.last_read = Some(now);
+
+        let diff = self
+            .settings
+            .as_ref()
+            .map(|old| SettingsDiff::new(old, &settings));
+        self.settings = Some(settings);
+
+        match diff {
+            Some(diff) if !diff.is_empty() => Ok(Some(SettingsEvent::SettingsChanged(diff))),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Blocking iterator over the changes of the settings of a [`Device`].
+///
+/// Created by [`Device::watch_settings`]. Every call to [`next`](Iterator::next)
+/// re-reads the settings once per interval (using a [`SettingsCache`]) until
+/// they changed, and returns the changes as [`SettingsDiff`]. Errors are
+/// returned as they occur, the next call continues watching. The iterator
+/// never ends.
+#[derive(Debug)]
+pub struct SettingsWatch<'a, T> {
+    device: &'a mut Device<T>,
+    cache: SettingsCache,
+    next_read: Option<Instant>,
+}
+
+impl<T> SettingsWatch<'_, T> {
+    /// Returns the cache holding the last read settings.
+    #[must_use]
+    pub fn cache(&self) -> &SettingsCache {
+        &self.cache
+    }
+}
+
+impl<T> Iterator for SettingsWatch<'_, T>
+where
+    T: Transport,
+{
+    type Item = Result<SettingsDiff, IoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(next_read) = self.next_read {
+                std::thread::sleep(next_read.saturating_duration_since(Instant::now()));
+            }
+
+            let now = Instant::now();
+            self.next_read = Some(now + self.cache.interval);
+
+            match self.cache.refresh(self.device, now) {
+                Ok(Some(SettingsEvent::SettingsChanged(diff))) => return Some(Ok(diff)),
+                Ok(None) => (),
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+impl<T> Device<T>
+where
+    T: Transport,
+{
+    /// Watches the settings of the device for changes made on the device side
+    /// (e.g. using the buttons of the device), reading them every `interval`.
+    ///
+    /// The returned iterator blocks until the next change (see
+    /// [`SettingsWatch`]), so user interfaces can update live.
+    pub fn watch_settings(&mut self, interval: Duration) -> SettingsWatch<'_, T> {
+        SettingsWatch {
+            device: self,
+            cache: SettingsCache::new(interval),
+            next_read: None,
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::misc::IoError;
+use crate::protocol::settings::{CurrentWarning, SettingsDiff};
+use crate::protocol::Settings;
+
+use super::{Device, Transport};
+
+/// Result of [`Device::write_settings_dry_run`].
+///
+/// Describes what a settings write would change on the device.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DryRun {
+    /// Difference between the settings stored on the device and the settings
+    /// that would be written.
+    pub diff: SettingsDiff,
+
+    /// Warning if the estimated current of the lighting configuration is not
+    /// covered by the configured current draw (see
+    /// [`Settings::check_current_draw`]).
+    pub current_warning: Option<CurrentWarning>,
+}
+
+impl DryRun {
+    /// Returns `true` if writing the settings would not change anything.
+    #[must_use]
+    pub fn is_noop(&self) -> bool {
+        self.diff.is_empty()
+    }
+}
+
+impl Display for DryRun {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if self.diff.is_empty() {
+            writeln!(f, "No changes")?;
+        } else {
+            write!(f, "{}", self.diff)?;
+        }
+
+        if let Some(warning) = &self.current_warning {
+            writeln!(f, "Warning: {warning}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Device<T>
+where
+    T: Transport,
+{
+    /// Validates the passed `settings` and compares them with the settings
+    /// stored on the device, without sending anything to the device.
+    ///
+    /// The values of the settings are already validated by their types, so
+    /// the validation only covers checks across multiple values (see
+    /// [`DryRun::current_warning`]).
+    ///
+    /// The dry run can not verify that the settings can be encoded (see the
+    /// [module documentation](crate::device)).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the current settings could not be read from the
+    /// device.
+    pub fn write_settings_dry_run(&mut self, settings: &Settings) -> Result<DryRun, IoError> {
+        let current = self.read_settings()?;
+
+        Ok(DryRun {
+            diff: SettingsDiff::new(&current, settings),
+            current_warning: settings.check_current_draw(),
+        })
+    }
+}
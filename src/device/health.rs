@@ -0,0 +1,216 @@
+use std::fmt::Write as _;
+use std::time::{Duration, SystemTime};
+
+use crate::misc::IoError;
+use crate::monitor::WatchdogEvent;
+
+/// Health metric of the connection to a device.
+///
+/// Combines the number of failed USB requests, the number of received frames
+/// with a CRC mismatch and the time the sensor data was stale (see
+/// [`Watchdog`](crate::monitor::Watchdog)) into a single [`score`](Self::score),
+/// so flaky cabling or hub issues show up as a metric instead of sporadic
+/// log lines.
+///
+/// The [`Device`](super::Device) records its requests automatically (see
+/// [`Device::health`](super::Device::health)), the events of the watchdog
+/// have to be passed to [`record_watchdog`](Self::record_watchdog).
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+pub struct DeviceHealth {
+    requests: u64,
+    usb_errors: u64,
+    crc_mismatches: u64,
+    stale_intervals: u64,
+    stale_time: Duration,
+    stale_since: Option<SystemTime>,
+    first_seen: Option<SystemTime>,
+    last_seen: Option<SystemTime>,
+}
+
+impl DeviceHealth {
+    /// Creates a new health metric without any recorded events.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of recorded requests.
+    #[must_use]
+    pub fn requests(&self) -> u64 {
+        self.requests
+    }
+
+    /// Returns the number of requests that failed with an USB error.
+    #[must_use]
+    pub fn usb_errors(&self) -> u64 {
+        self.usb_errors
+    }
+
+    /// Returns the number of received frames with a CRC mismatch.
+    #[must_use]
+    pub fn crc_mismatches(&self) -> u64 {
+        self.crc_mismatches
+    }
+
+    /// Returns the number of times the sensor data became stale.
+    #[must_use]
+    pub fn stale_intervals(&self) -> u64 {
+        self.stale_intervals
+    }
+
+    /// Returns the total time the sensor data was stale.
+    #[must_use]
+    pub fn stale_time(&self) -> Duration {
+        let current = self
+            .stale_since
+            .zip(self.last_seen)
+            .and_then(|(since, last)| last.duration_since(since).ok())
+            .unwrap_or_default();
+
+        self.stale_time + current
+    }
+
+    /// Records a request that was performed at `at`.
+    ///
+    /// [`IoError::IoError`]s are counted as USB errors, and checksum
+    /// mismatches (see [`IoError::is_checksum_mismatch`]) as CRC mismatch. Other errors (e.g.
+    /// invalid values) are not related to the connection and only count as
+    /// request.
+    pub fn record_request(&mut self, at: SystemTime, result: Result<(), &IoError>) {
+        self.requests += 1;
+        self.observe(at);
+
+        match result {
+            Err(IoError::IoError(_)) => self.usb_errors += 1,
+            Err(err) if err.is_checksum_mismatch() => self.crc_mismatches += 1,
+            _ => (),
+        }
+    }
+
+    /// Records a frame with a CRC mismatch, that was received by a request
+    /// recorded before.
+    pub fn record_crc_mismatch(&mut self) {
+        self.crc_mismatches += 1;
+    }
+
+    /// Records an event of the [`Watchdog`](crate::monitor::Watchdog).
+    pub fn record_watchdog(&mut self, event: &WatchdogEvent) {
+        match *event {
+            WatchdogEvent::Stale { last_seen, at } => {
+                let since = last_seen.unwrap_or(at);
+
+                self.stale_intervals += 1;
+                self.stale_since = Some(since);
+                self.observe(since);
+                self.observe(at);
+            }
+            WatchdogEvent::Recovered { at, after } => {
+                self.stale_time += after;
+                self.stale_since = None;
+                self.observe(at);
+            }
+        }
+    }
+
+    /// Returns the rate of requests that failed with an USB error or a CRC
+    /// mismatch (`0.0` to `1.0`).
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn error_rate(&self) -> f64 {
+        if self.requests == 0 {
+            return 0.0;
+        }
+
+        let errors = self.usb_errors + self.crc_mismatches;
+
+        (errors as f64 / self.requests as f64).min(1.0)
+    }
+
+    /// Returns the fraction of the observed time the sensor data was stale
+    /// (`0.0` to `1.0`).
+    #[must_use]
+    pub fn stale_rate(&self) -> f64 {
+        let observed = self
+            .first_seen
+            .zip(self.last_seen)
+            .and_then(|(first, last)| last.duration_since(first).ok())
+            .unwrap_or_default();
+
+        if observed.is_zero() {
+            return if self.stale_since.is_some() { 1.0 } else { 0.0 };
+        }
+
+        (self.stale_time().as_secs_f64() / observed.as_secs_f64()).min(1.0)
+    }
+
+    /// Returns the health score of the connection, from `0.0` (unusable) to
+    /// `1.0` (healthy).
+    ///
+    /// The score is calculated as `(1 - error_rate) * (1 - stale_rate)`.
+    #[must_use]
+    pub fn score(&self) -> f64 {
+        (1.0 - self.error_rate()) * (1.0 - self.stale_rate())
+    }
+
+    /// Resets all recorded events, e.g. after the cabling was fixed.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Writes the health metric in the Prometheus / `OpenMetrics` text format
+    /// to `out`, prefixing all metric names with `prefix`.
+    pub fn write_metrics(&self, out: &mut String, prefix: &str) {
+        let counters = [
+            (
+                "requests",
+                "Number of requests sent to the device.",
+                self.requests,
+            ),
+            (
+                "usb_errors",
+                "Number of failed USB requests.",
+                self.usb_errors,
+            ),
+            (
+                "crc_mismatches",
+                "Number of received frames with a CRC mismatch.",
+                self.crc_mismatches,
+            ),
+            (
+                "stale_intervals",
+                "Number of times the sensor data became stale.",
+                self.stale_intervals,
+            ),
+        ];
+
+        for (name, help, value) in counters {
+            let _ = writeln!(out, "# TYPE {prefix}_health_{name} counter");
+            let _ = writeln!(out, "# HELP {prefix}_health_{name} {help}");
+            let _ = writeln!(out, "{prefix}_health_{name}_total {value}");
+        }
+
+        let gauges = [
+            (
+                "stale_seconds",
+                "Total time the sensor data was stale in seconds.",
+                self.stale_time().as_secs_f64(),
+            ),
+            (
+                "score",
+                "Health score of the connection (0 = unusable, 1 = healthy).",
+                self.score(),
+            ),
+        ];
+
+        for (name, help, value) in gauges {
+            let _ = writeln!(out, "# TYPE {prefix}_health_{name} gauge");
+            let _ = writeln!(out, "# HELP {prefix}_health_{name} {help}");
+            let _ = writeln!(out, "{prefix}_health_{name} {value}");
+        }
+    }
+
+    fn observe(&mut self, at: SystemTime) {
+        self.first_seen = Some(self.first_seen.map_or(at, |x| x.min(at)));
+        self.last_seen = Some(self.last_seen.map_or(at, |x| x.max(at)));
+    }
+}
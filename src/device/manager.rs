@@ -0,0 +1,235 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+#[cfg(feature = "hidapi")]
+use hidapi::{HidApi, HidDevice};
+
+use crate::misc::IoError;
+use crate::protocol::settings::{AquaBusAddress, LightingSettings};
+use crate::protocol::Settings;
+
+#[cfg(feature = "hidapi")]
+use super::{hid_error, PRODUCT_ID, VENDOR_ID};
+use super::{Device, Transport};
+
+/// Manages all high flow NEXT devices connected to the host.
+///
+/// Devices are identified by their index in [`devices`](Self::devices).
+#[derive(Debug)]
+pub struct DeviceManager<
+    #[cfg(feature = "hidapi")] T = HidDevice,
+    #[cfg(not(feature = "hidapi"))] T,
+> {
+    devices: Vec<Device<T>>,
+}
+
+#[cfg(feature = "hidapi")]
+impl DeviceManager<HidDevice> {
+    /// Opens all high flow NEXT devices found by the passed `api`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the devices could not be opened.
+    pub fn open_all(api: &HidApi) -> Result<Self, IoError> {
+        let devices = api
+            .device_list()
+            .filter(|info| info.vendor_id() == VENDOR_ID && info.product_id() == PRODUCT_ID)
+            .map(|info| info.open_device(api).map(Device::new).map_err(hid_error))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { devices })
+    }
+}
+
+impl<T> DeviceManager<T>
+where
+    T: Transport,
+{
+    /// Creates a new manager for the passed `devices`.
+    #[must_use]
+    pub fn new(devices: Vec<Device<T>>) -> Self {
+        Self { devices }
+    }
+
+    /// Returns the managed devices.
+    #[must_use]
+    pub fn devices(&self) -> &[Device<T>] {
+        &self.devices
+    }
+
+    /// Returns the managed devices as mutable slice.
+    #[must_use]
+    pub fn devices_mut(&mut self) -> &mut [Device<T>] {
+        &mut self.devices
+    }
+
+    /// Returns the managed devices.
+    #[must_use]
+    pub fn into_inner(self) -> Vec<Device<T>> {
+        self.devices
+    }
+
+    /// Reads the Aqua-Bus address of every managed device.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings of a device could not be read.
+    pub fn aqua_bus_addresses(&mut self) -> Result<Vec<AquaBusAddress>, IoError> {
+        self.devices
+            .iter_mut()
+            .map(|device| Ok(device.read_settings()?.system.aqua_bus_address))
+            .collect()
+    }
+
+    /// Reads the Aqua-Bus address of every managed device and returns the
+    /// addresses that are used by more than one device.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings of a device could not be read.
+    pub fn address_conflicts(&mut self) -> Result<Vec<AddressConflict>, IoError> {
+        let addresses = self.aqua_bus_addresses()?;
+
+        Ok(AddressConflict::find(addresses))
+    }
+
+    /// Copies the lighting settings of the device `source` to the devices
+    /// `targets`.
+    ///
+    /// If the LED strip of a target covers a different number of LEDs than
+    /// the strip of the source (see [`LightingSettings::strip_len`]), the
+    /// regions of the strip controllers are scaled to the strip of the
+    /// target (see [`LightingSettings::remap_strip`]).
+    ///
+    /// The updated settings of the targets are returned in the order of
+    /// `targets` (see the [module documentation](crate::device)).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings of a device could not be read.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `source` or one of the `targets` is not a valid device
+    /// index.
+    pub fn sync_lighting(
+        &mut self,
+        source: usize,
+        targets: &[usize],
+    ) -> Result<Vec<Settings>, IoError> {
+        self.sync(source, targets, |source, target| {
+            target.lighting = remapped(source, target);
+        })
+    }
+
+    /// Copies all settings of the device `source` to the devices `targets`,
+    /// like [`sync_lighting`](Self::sync_lighting).
+    ///
+    /// The Aqua-Bus address of the targets is kept, so the devices do not
+    /// conflict afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings of a device could not be read.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `source` or one of the `targets` is not a valid device
+    /// index.
+    pub fn sync_settings(
+        &mut self,
+        source: usize,
+        targets: &[usize],
+    ) -> Result<Vec<Settings>, IoError> {
+        self.sync(source, targets, |source, target| {
+            let lighting = remapped(source, target);
+            let aqua_bus_address = target.system.aqua_bus_address;
+
+            target.clone_from(source);
+            target.lighting = lighting;
+            target.system.aqua_bus_address = aqua_bus_address;
+        })
+    }
+
+    fn sync<F>(&mut self, source: usize, targets: &[usize], f: F) -> Result<Vec<Settings>, IoError>
+    where
+        F: Fn(&Settings, &mut Settings),
+    {
+        let source = self.devices[source].read_settings()?;
+
+        targets
+            .iter()
+            .map(|&index| {
+                let mut target = self.devices[index].read_settings()?;
+                f(&source, &mut target);
+
+                Ok(target)
+            })
+            .collect()
+    }
+}
+
+fn remapped(source: &Settings, target: &Settings) -> Option<LightingSettings> {
+    let mut lighting = source.lighting.clone()?;
+
+    if let Some(target) = &target.lighting {
+        let to = target.strip_len();
+        if to > 0 {
+            lighting.remap_strip(lighting.strip_len(), to);
+        }
+    }
+
+    Some(lighting)
+}
+
+/// An Aqua-Bus address that is used by more than one device.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AddressConflict {
+    /// The address used by the devices.
+    pub address: AquaBusAddress,
+
+    /// Indices of the devices that use the address.
+    pub devices: Vec<usize>,
+}
+
+impl AddressConflict {
+    /// Returns the addresses that are used more than once in `addresses`,
+    /// ordered by address.
+    ///
+    /// The devices are identified by the index of their address in
+    /// `addresses`.
+    #[must_use]
+    pub fn find<I>(addresses: I) -> Vec<Self>
+    where
+        I: IntoIterator<Item = AquaBusAddress>,
+    {
+        let mut conflicts = AquaBusAddress::ALL.map(|address| Self {
+            address,
+            devices: Vec::new(),
+        });
+
+        for (index, address) in addresses.into_iter().enumerate() {
+            conflicts[usize::from(address.number() - 1)]
+                .devices
+                .push(index);
+        }
+
+        conflicts
+            .into_iter()
+            .filter(|conflict| conflict.devices.len() > 1)
+            .collect()
+    }
+}
+
+impl Display for AddressConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "Aqua-Bus address {} is used by devices", self.address)?;
+
+        for (i, index) in self.devices.iter().enumerate() {
+            let sep = if i == 0 { " " } else { ", " };
+
+            write!(f, "{sep}{index}")?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,312 @@
+//! Access to a high flow NEXT device.
+//!
+//! The [`Device`] talks to the device using HID feature reports. The reports
+//! are exchanged through a [`Transport`], which is implemented for
+//! `hidapi::HidDevice` (with the `hidapi` feature) and can be implemented by
+//! tests or emulators to run without real hardware.
+//!
+//! The device owns a single report buffer that is reused for every read, so
+//! polling the settings in a loop does not allocate. The last received report
+//! is exposed as slice, which can be decoded directly using the slice
+//! [`Reader`](crate::misc::Reader).
+//!
+//! Multiple devices can be handled using the [`DeviceManager`], which also
+//! detects devices that are configured to the same Aqua-Bus address.
+//!
+//! The crate does not implement the encoder of the settings frame yet, so
+//! the settings can only be read from the device. Functions that prepare new
+//! settings (e.g. [`DeviceManager::sync_lighting`]) return them instead of
+//! writing them, and [`Device::write_settings_dry_run`] shows what writing
+//! them would change, without sending anything to the device.
+//!
+//! The [`SettingsCache`] keeps the last read settings and reports changes
+//! made on the device side (e.g. using the buttons of the device).
+//! [`Device::watch_settings`] streams these changes as blocking iterator.
+//!
+//! The [`TracingTransport`] records the requests of a session together with
+//! the responses of the device and renders them as sequence diagram, e.g. to
+//! discuss the behavior of the protocol in an issue.
+//!
+//! Every device tracks the health of its connection (failed USB requests and
+//! CRC mismatches) in a [`DeviceHealth`] metric. Frames with a CRC mismatch
+//! are returned as [`IoError::CorruptFrame`], together with the last valid
+//! frame, so daemons can persist them for later analysis.
+
+mod cache;
+mod dry_run;
+mod health;
+mod manager;
+mod trace;
+
+use std::time::SystemTime;
+
+#[cfg(feature = "hidapi")]
+use hidapi::{HidApi, HidDevice, HidError};
+
+use crate::misc::{CorruptFrame, Decode, FixedSize, IoError};
+use crate::protocol::{Frame, Settings};
+
+pub use self::cache::{SettingsCache, SettingsEvent, SettingsWatch};
+pub use self::dry_run::DryRun;
+pub use self::health::DeviceHealth;
+pub use self::manager::{AddressConflict, DeviceManager};
+pub use self::trace::{ProtocolTrace, TraceEntry, TraceRequest, TracingTransport};
+
+/// USB vendor ID of the high flow NEXT.
+pub const VENDOR_ID: u16 = 0x0C70;
+
+/// USB product ID of the high flow NEXT.
+pub const PRODUCT_ID: u16 = 0xF012;
+
+/// Size of the report buffer of a [`Device`].
+///
+/// Large enough for every report the device sends.
+pub const REPORT_BUFFER_SIZE: usize = 0x1000;
+
+/// Default number of bytes of a frame retained in an
+/// [`IoError::CorruptFrame`] (the size of a settings frame).
+pub const DEFAULT_RETAINED_FRAME_LIMIT: usize = 1 + Settings::SIZE + 2;
+
+/// Report ID of the settings report.
+pub(crate) const SETTINGS_REPORT_ID: u8 = 0x03;
+
+/// Transport used by a [`Device`] to exchange HID feature reports.
+pub trait Transport {
+    /// Requests the feature report with the report ID stored in `buffer[0]`
+    /// and stores it in `buffer`.
+    ///
+    /// Returns the number of bytes written to `buffer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the report could not be received.
+    fn get_feature_report(&mut self, buffer: &mut [u8]) -> Result<usize, IoError>;
+
+    /// Sends the passed feature report (starting with the report ID).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the report could not be sent.
+    fn send_feature_report(&mut self, data: &[u8]) -> Result<(), IoError>;
+}
+
+#[cfg(feature = "hidapi")]
+impl Transport for HidDevice {
+    fn get_feature_report(&mut self, buffer: &mut [u8]) -> Result<usize, IoError> {
+        HidDevice::get_feature_report(self, buffer).map_err(hid_error)
+    }
+
+    fn send_feature_report(&mut self, data: &[u8]) -> Result<(), IoError> {
+        HidDevice::send_feature_report(self, data).map_err(hid_error)
+    }
+}
+
+/// A high flow NEXT device.
+///
+/// See the [module documentation](self) for details.
+#[derive(Debug)]
+pub struct Device<#[cfg(feature = "hidapi")] T = HidDevice, #[cfg(not(feature = "hidapi"))] T> {
+    transport: T,
+    buffer: Box<[u8]>,
+    len: usize,
+    health: DeviceHealth,
+    retained_frame_limit: usize,
+    previous: Vec<u8>,
+}
+
+#[cfg(feature = "hidapi")]
+impl Device<HidDevice> {
+    /// Opens the first high flow NEXT device found by the passed `api`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no device was found or it could not be opened.
+    pub fn open(api: &HidApi) -> Result<Self, IoError> {
+        let transport = api.open(VENDOR_ID, PRODUCT_ID).map_err(hid_error)?;
+
+        Ok(Self::new(transport))
+    }
+
+    /// Returns the firmware version of the device.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the device info could not be requested.
+    pub fn firmware_version(&self) -> Result<u16, IoError> {
+        let info = self.transport.get_device_info().map_err(hid_error)?;
+
+        Ok(info.release_number())
+    }
+}
+
+impl<T> Device<T>
+where
+    T: Transport,
+{
+    /// Creates a new device that uses the passed `transport`.
+    #[must_use]
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            buffer: vec![0; REPORT_BUFFER_SIZE].into_boxed_slice(),
+            len: 0,
+            health: DeviceHealth::new(),
+            retained_frame_limit: DEFAULT_RETAINED_FRAME_LIMIT,
+            previous: Vec::with_capacity(DEFAULT_RETAINED_FRAME_LIMIT),
+        }
+    }
+
+    /// Sets the maximum number of bytes of a frame that are retained in an
+    /// [`IoError::CorruptFrame`] and returns the updated device.
+    ///
+    /// Longer frames are truncated. If the limit is `0`, no frames are
+    /// retained and CRC mismatches are reported as
+    /// [`IoError::ChecksumMismatch`].
+    #[must_use]
+    pub fn with_retained_frame_limit(mut self, limit: usize) -> Self {
+        self.retained_frame_limit = limit;
+        self.previous = Vec::with_capacity(limit.min(REPORT_BUFFER_SIZE));
+
+        self
+    }
+
+    /// Returns the health metric of the connection to the device.
+    #[must_use]
+    pub fn health(&self) -> &DeviceHealth {
+        &self.health
+    }
+
+    /// Returns the health metric of the connection to the device as mutable
+    /// reference (e.g. to record the events of a
+    /// [`Watchdog`](crate::monitor::Watchdog)).
+    #[must_use]
+    pub fn health_mut(&mut self) -> &mut DeviceHealth {
+        &mut self.health
+    }
+
+    /// Returns a reference to the underlying transport.
+    #[must_use]
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Returns a mutable reference to the underlying transport.
+    #[must_use]
+    pub fn transport_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// Returns the underlying transport.
+    #[must_use]
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+
+    /// Returns the last report received by [`read_report`](Self::read_report)
+    /// (empty if no report was received yet).
+    #[must_use]
+    pub fn report(&self) -> &[u8] {
+        &self.buffer[..self.len]
+    }
+
+    /// Requests the feature report with the passed `report_id` and returns
+    /// it.
+    ///
+    /// The report is stored in the report buffer of the device, so it is only
+    /// valid until the next report is requested.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the report could not be received.
+    pub fn read_report(&mut self, report_id: u8) -> Result<&[u8], IoError> {
+        self.len = 0;
+        self.buffer[0] = report_id;
+
+        let result = self.transport.get_feature_report(&mut self.buffer);
+        self.health
+            .record_request(SystemTime::now(), result.as_ref().map(|_| ()));
+        self.len = result?.min(self.buffer.len());
+
+        Ok(self.report())
+    }
+
+    /// Sends the passed feature report (starting with the report ID) to the
+    /// device.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the report could not be sent.
+    pub fn write_report(&mut self, data: &[u8]) -> Result<(), IoError> {
+        let result = self.transport.send_feature_report(data);
+        self.health
+            .record_request(SystemTime::now(), result.as_ref().copied());
+
+        result
+    }
+
+    /// Reads the current settings of the device.
+    ///
+    /// The settings are decoded directly from the report buffer, so reading
+    /// the settings does not allocate.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the report could not be received or decoded.
+    pub fn read_settings(&mut self) -> Result<Settings, IoError> {
+        let mut report = self.read_report(SETTINGS_REPORT_ID)?;
+
+        match Frame::decode(&mut report) {
+            Ok(Frame::Settings(settings)) => {
+                self.retain_previous();
+
+                Ok(settings)
+            }
+            Err(IoError::ChecksumMismatch) => {
+                self.health.record_crc_mismatch();
+
+                Err(self.corrupt_frame())
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Keeps the frame stored in the report buffer as last valid frame.
+    fn retain_previous(&mut self) {
+        let frame = frame(&self.buffer[..self.len]);
+        let frame = &frame[..frame.len().min(self.retained_frame_limit)];
+
+        // The buffer is allocated up front, so polling does not allocate.
+        self.previous.clear();
+        self.previous.extend_from_slice(frame);
+    }
+
+    /// Creates the error for the corrupted frame stored in the report buffer.
+    fn corrupt_frame(&self) -> IoError {
+        if self.retained_frame_limit == 0 {
+            return IoError::ChecksumMismatch;
+        }
+
+        let frame = frame(self.report());
+
+        IoError::CorruptFrame(Box::new(CorruptFrame {
+            frame: frame[..frame.len().min(self.retained_frame_limit)].to_vec(),
+            len: frame.len(),
+            previous: (!self.previous.is_empty()).then(|| self.previous.clone()),
+        }))
+    }
+}
+
+/// Returns the bytes of the frame stored at the start of the `report`.
+fn frame(report: &[u8]) -> &[u8] {
+    let len = report
+        .first()
+        .and_then(|x| Frame::size(*x))
+        .map_or(report.len(), |x| x.min(report.len()));
+
+    &report[..len]
+}
+
+#[cfg(feature = "hidapi")]
+fn hid_error(error: HidError) -> IoError {
+    IoError::IoError(std::io::Error::other(error))
+}
@@ -0,0 +1,354 @@
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::time::Duration;
+
+use crate::misc::{Decode, IoError};
+use crate::monitor::Timestamp;
+use crate::protocol::Frame;
+
+use super::Transport;
+
+/// Request sent to the device, recorded by the [`TracingTransport`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TraceRequest {
+    /// A feature report with the report ID was requested.
+    GetFeatureReport(u8),
+
+    /// The feature report was sent to the device.
+    SendFeatureReport(Vec<u8>),
+}
+
+/// Single request / response pair recorded by the [`TracingTransport`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TraceEntry {
+    /// Point in time the request was sent.
+    pub timestamp: Timestamp,
+
+    /// The request sent to the device.
+    pub request: TraceRequest,
+
+    /// The received report (empty for sent reports), or the message of the
+    /// error returned by the transport.
+    pub response: Result<Vec<u8>, String>,
+}
+
+impl TraceEntry {
+    /// Returns a short description of the request.
+    #[must_use]
+    pub fn describe_request(&self) -> String {
+        match &self.request {
+            TraceRequest::GetFeatureReport(report_id) => {
+                format!("GET_REPORT {report_id:#04x}")
+            }
+            TraceRequest::SendFeatureReport(data) => {
+                format!("SET_REPORT {}", describe_report(data))
+            }
+        }
+    }
+
+    /// Returns a short description of the response.
+    #[must_use]
+    pub fn describe_response(&self) -> String {
+        match (&self.request, &self.response) {
+            (_, Err(error)) => format!("error: {error}"),
+            (TraceRequest::GetFeatureReport(_), Ok(data)) => describe_report(data),
+            (TraceRequest::SendFeatureReport(_), Ok(_)) => "ok".into(),
+        }
+    }
+}
+
+/// Recorded session of a [`TracingTransport`].
+///
+/// The session can be rendered as sequence diagram using
+/// [`to_mermaid`](Self::to_mermaid) or [`to_plantuml`](Self::to_plantuml),
+/// e.g. to discuss the behavior of the protocol in an issue.
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+pub struct ProtocolTrace {
+    entries: VecDeque<TraceEntry>,
+}
+
+impl ProtocolTrace {
+    /// Returns the number of recorded entries.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no entries were recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator over the recorded entries, starting with the
+    /// oldest one.
+    #[must_use]
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &TraceEntry> + '_ {
+        self.entries.iter()
+    }
+
+    /// Renders the session as [Mermaid](https://mermaid.js.org) sequence
+    /// diagram.
+    ///
+    /// Each message is prefixed with the time since the first request.
+    #[must_use]
+    pub fn to_mermaid(&self) -> String {
+        let mut diagram = String::from(
+            "sequenceDiagram\n    participant Host\n    participant Device as high flow NEXT\n",
+        );
+
+        for (offset, entry) in self.offsets() {
+            let arrow = if entry.response.is_ok() {
+                "-->>"
+            } else {
+                "--x"
+            };
+
+            let _ = writeln!(
+                diagram,
+                "    Host->>Device: [{}] {}",
+                format_offset(offset),
+                mermaid_escape(&entry.describe_request())
+            );
+            let _ = writeln!(
+                diagram,
+                "    Device{arrow}Host: {}",
+                mermaid_escape(&entry.describe_response())
+            );
+        }
+
+        diagram
+    }
+
+    /// Renders the session as [PlantUML](https://plantuml.com) sequence
+    /// diagram.
+    ///
+    /// Each message is prefixed with the time since the first request.
+    #[must_use]
+    pub fn to_plantuml(&self) -> String {
+        let mut diagram =
+            String::from("@startuml\nparticipant Host\nparticipant \"high flow NEXT\" as Device\n");
+
+        for (offset, entry) in self.offsets() {
+            let arrow = if entry.response.is_ok() {
+                "-->"
+            } else {
+                "-->x"
+            };
+
+            let _ = writeln!(
+                diagram,
+                "Host -> Device: [{}] {}",
+                format_offset(offset),
+                plantuml_escape(&entry.describe_request())
+            );
+            let _ = writeln!(
+                diagram,
+                "Device {arrow} Host: {}",
+                plantuml_escape(&entry.describe_response())
+            );
+        }
+
+        diagram.push_str("@enduml\n");
+
+        diagram
+    }
+
+    fn offsets(&self) -> impl Iterator<Item = (Duration, &TraceEntry)> + '_ {
+        let start = self.entries.front().map(|entry| entry.timestamp);
+
+        self.entries.iter().map(move |entry| {
+            let offset = start
+                .and_then(|start| {
+                    entry
+                        .timestamp
+                        .as_system_time()
+                        .duration_since(start.as_system_time())
+                        .ok()
+                })
+                .unwrap_or_default();
+
+            (offset, entry)
+        })
+    }
+}
+
+/// [`Transport`] that records every request and its response as
+/// [`TraceEntry`] of a [`ProtocolTrace`].
+///
+/// Wrap the transport of a [`Device`](super::Device) to trace a session:
+///
+/// ```rust
+/// use high_flow_next::device::{Device, TracingTransport, Transport};
+/// use high_flow_next::misc::IoError;
+///
+/// struct Disconnected;
+///
+/// impl Transport for Disconnected {
+///     fn get_feature_report(&mut self, _buffer: &mut [u8]) -> Result<usize, IoError> {
+///         Err(IoError::IoError(std::io::Error::other("device disconnected")))
+///     }
+///
+///     fn send_feature_report(&mut self, _data: &[u8]) -> Result<(), IoError> {
+///         Ok(())
+///     }
+/// }
+///
+/// let mut device = Device::new(TracingTransport::new(Disconnected));
+/// assert!(device.read_settings().is_err());
+///
+/// let trace = device.transport_mut().take_trace();
+/// println!("{}", trace.to_mermaid());
+/// ```
+#[derive(Debug)]
+pub struct TracingTransport<T> {
+    transport: T,
+    trace: ProtocolTrace,
+    limit: Option<usize>,
+}
+
+impl<T> TracingTransport<T> {
+    /// Creates a new transport that passes all requests to `transport` and
+    /// records them.
+    #[must_use]
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            trace: ProtocolTrace::default(),
+            limit: None,
+        }
+    }
+
+    /// Sets the maximum number of recorded entries and returns the updated
+    /// transport. Once the limit is reached the oldest entries are dropped.
+    #[must_use]
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+
+        self
+    }
+
+    /// Returns the trace of the current session.
+    #[must_use]
+    pub fn trace(&self) -> &ProtocolTrace {
+        &self.trace
+    }
+
+    /// Returns the trace of the current session and starts a new one.
+    pub fn take_trace(&mut self) -> ProtocolTrace {
+        std::mem::take(&mut self.trace)
+    }
+
+    /// Returns a reference to the underlying transport.
+    #[must_use]
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Returns the underlying transport and the trace of the current
+    /// session.
+    #[must_use]
+    pub fn into_inner(self) -> (T, ProtocolTrace) {
+        (self.transport, self.trace)
+    }
+
+    fn record(&mut self, entry: TraceEntry) {
+        if self.limit == Some(0) {
+            return;
+        }
+
+        while self
+            .limit
+            .is_some_and(|limit| self.trace.entries.len() >= limit)
+        {
+            self.trace.entries.pop_front();
+        }
+
+        self.trace.entries.push_back(entry);
+    }
+}
+
+impl<T> Transport for TracingTransport<T>
+where
+    T: Transport,
+{
+    fn get_feature_report(&mut self, buffer: &mut [u8]) -> Result<usize, IoError> {
+        let timestamp = Timestamp::now();
+        let request = TraceRequest::GetFeatureReport(buffer.first().copied().unwrap_or_default());
+
+        let result = self.transport.get_feature_report(buffer);
+        let response = match &result {
+            Ok(len) => Ok(buffer[..(*len).min(buffer.len())].to_vec()),
+            Err(error) => Err(error.to_string()),
+        };
+
+        self.record(TraceEntry {
+            timestamp,
+            request,
+            response,
+        });
+
+        result
+    }
+
+    fn send_feature_report(&mut self, data: &[u8]) -> Result<(), IoError> {
+        let timestamp = Timestamp::now();
+
+        let result = self.transport.send_feature_report(data);
+        let response = match &result {
+            Ok(()) => Ok(Vec::new()),
+            Err(error) => Err(error.to_string()),
+        };
+
+        self.record(TraceEntry {
+            timestamp,
+            request: TraceRequest::SendFeatureReport(data.to_vec()),
+            response,
+        });
+
+        result
+    }
+}
+
+/// Returns a short description of the passed report (ID, size and content).
+fn describe_report(data: &[u8]) -> String {
+    let Some(report_id) = data.first() else {
+        return "empty report".into();
+    };
+
+    let content = match Frame::size(*report_id) {
+        Some(_) => match Frame::decode(&mut &data[..]) {
+            Ok(Frame::Settings(_)) => "settings".into(),
+            Err(error) => format!("invalid frame: {error}"),
+        },
+        None => "unknown".into(),
+    };
+
+    format!("{report_id:#04x} ({content}, {} bytes)", data.len())
+}
+
+fn format_offset(offset: Duration) -> String {
+    format!("+{:.3}s", offset.as_secs_f64())
+}
+
+/// Escapes the characters that end a message in Mermaid.
+fn mermaid_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '#' => escaped.push_str("#35;"),
+            ';' => escaped.push_str("#59;"),
+            '\n' => escaped.push_str("<br>"),
+            '\r' => (),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Escapes the line breaks that end a message in `PlantUML`.
+fn plantuml_escape(text: &str) -> String {
+    text.replace('\r', "").replace('\n', "\\n")
+}
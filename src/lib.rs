@@ -1,4 +1,21 @@
 #![doc = include_str!(concat!(env!("OUT_DIR"), "/README.md"))]
 
+pub mod audit;
+pub mod backup;
+#[cfg(feature = "bundle")]
+pub mod bundle;
+pub mod core;
+pub mod device;
+pub mod locale;
 pub mod misc;
+pub mod monitor;
+#[cfg(feature = "config")]
+pub mod profiles;
 pub mod protocol;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,347 @@
+//! Localization of the human readable names and descriptions.
+//!
+//! Every localizable text is identified by a stable key (e.g.
+//! `effect.color_change` or `standby.display_off`) and has a built-in English
+//! text. A [`Catalog`] maps the keys to translated texts, texts that are not
+//! contained in the catalog fall back to English. This way GUI frontends in
+//! other languages only have to supply a map of translations instead of
+//! re-mapping every enum themselves.
+//!
+//! Texts may contain placeholders in curly braces (e.g. `Chart {index}`),
+//! which are replaced by the arguments of the text. Translations should
+//! contain the same placeholders.
+//!
+//! The following types can be localized (see [`Localize`]):
+//!
+//! - [`EffectKind`] and [`Effect`] (`effect.*`)
+//! - [`Page`] (`page.*`)
+//! - [`Channel`] (`channel.*`)
+//! - [`Message`]s returned by
+//!   [`StandbyFlags::messages`](crate::protocol::settings::StandbyFlags::messages)
+//!   (`standby.*`) and
+//!   [`AlarmSettings::messages`](crate::protocol::settings::AlarmSettings::messages)
+//!   (`alarm.*`)
+//!
+//! # Example
+//!
+//! ```rust
+//! use high_flow_next::locale::{Catalog, Localize};
+//! use high_flow_next::monitor::Page;
+//! use high_flow_next::protocol::settings::EffectKind;
+//!
+//! let catalog = Catalog::from_iter([
+//!     ("effect.rainbow", "Regenbogen"),
+//!     ("page.chart", "Diagramm {index}"),
+//! ]);
+//!
+//! assert_eq!(EffectKind::Rainbow.localize(&catalog), "Regenbogen");
+//! assert_eq!(EffectKind::ColorChange.localize(&catalog), "Color change");
+//! assert_eq!(Page::Chart(1).localize(&catalog), "Diagramm 2");
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::monitor::{Channel, Page};
+use crate::protocol::settings::{Effect, EffectKind};
+
+/// Map of translated texts, identified by their key.
+///
+/// See the [module documentation](self) for details.
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Catalog {
+    texts: BTreeMap<String, String>,
+}
+
+impl Catalog {
+    /// Creates a new empty catalog, which uses the built-in English texts.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the translated `text` for the passed `key`.
+    pub fn insert<K: Into<String>, V: Into<String>>(&mut self, key: K, text: V) {
+        self.texts.insert(key.into(), text.into());
+    }
+
+    /// Returns the number of translated texts.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.texts.len()
+    }
+
+    /// Returns `true` if the catalog does not contain any translated text.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.texts.is_empty()
+    }
+
+    /// Returns the translated text for `key`, or `english` if the catalog
+    /// does not contain a translation.
+    #[must_use]
+    pub fn text<'a>(&'a self, key: &str, english: &'a str) -> &'a str {
+        self.texts.get(key).map_or(english, String::as_str)
+    }
+
+    /// Returns the translated text for `key` (see [`text`](Self::text)) with
+    /// all placeholders replaced by the passed `args`.
+    #[must_use]
+    pub fn format(&self, key: &str, english: &str, args: &[(&str, String)]) -> String {
+        let mut ret = self.text(key, english).to_owned();
+
+        for (name, value) in args {
+            ret = ret.replace(&format!("{{{name}}}"), value);
+        }
+
+        ret
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for Catalog
+where
+    K: Into<String>,
+    V: Into<String>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut ret = Self::new();
+        ret.extend(iter);
+
+        ret
+    }
+}
+
+impl<K, V> Extend<(K, V)> for Catalog
+where
+    K: Into<String>,
+    V: Into<String>,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, text) in iter {
+            self.insert(key, text);
+        }
+    }
+}
+
+/// Trait for types that have a localizable human readable name or
+/// description.
+pub trait Localize {
+    /// Returns the key that identifies the text.
+    fn key(&self) -> &'static str;
+
+    /// Returns the built-in English text.
+    fn english(&self) -> &'static str;
+
+    /// Returns the values of the placeholders of the text.
+    fn args(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+
+    /// Returns the text translated using the passed `catalog`.
+    fn localize(&self, catalog: &Catalog) -> String {
+        catalog.format(self.key(), self.english(), &self.args())
+    }
+}
+
+/// A localizable description, e.g. returned by
+/// [`StandbyFlags::messages`](crate::protocol::settings::StandbyFlags::messages).
+///
+/// The [`Display`] implementation returns the English text.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Message {
+    /// Key of the message.
+    pub key: &'static str,
+
+    /// Built-in English text of the message.
+    pub english: &'static str,
+
+    /// Values of the placeholders of the message.
+    pub args: Vec<(&'static str, String)>,
+}
+
+impl Message {
+    /// Creates a new message without placeholders.
+    #[must_use]
+    pub fn new(key: &'static str, english: &'static str) -> Self {
+        Self {
+            key,
+            english,
+            args: Vec::new(),
+        }
+    }
+
+    /// Adds the `value` for the placeholder `name`.
+    #[must_use]
+    pub fn arg<V: Display>(mut self, name: &'static str, value: V) -> Self {
+        self.args.push((name, value.to_string()));
+
+        self
+    }
+}
+
+impl Localize for Message {
+    fn key(&self) -> &'static str {
+        self.key
+    }
+
+    fn english(&self) -> &'static str {
+        self.english
+    }
+
+    fn args(&self) -> Vec<(&'static str, String)> {
+        self.args.clone()
+    }
+}
+
+impl Display for Message {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(&Catalog::new().format(self.key, self.english, &self.args))
+    }
+}
+
+impl Localize for EffectKind {
+    fn key(&self) -> &'static str {
+        match self {
+            Self::Static => "effect.static",
+            Self::Breathing => "effect.breathing",
+            Self::Rainbow => "effect.rainbow",
+            Self::Blink => "effect.blink",
+            Self::ColorChange => "effect.color_change",
+            Self::Sequence => "effect.sequence",
+            Self::Scanner => "effect.scanner",
+            Self::Laser => "effect.laser",
+            Self::Wave => "effect.wave",
+            Self::ColorSequence => "effect.color_sequence",
+            Self::ColorShift => "effect.color_shift",
+            Self::BarGraph => "effect.bar_graph",
+            Self::Flame => "effect.flame",
+            Self::Rain => "effect.rain",
+            Self::Snow => "effect.snow",
+            Self::Stardust => "effect.stardust",
+            Self::ColorSwitch => "effect.color_switch",
+            Self::SwipingRainbow => "effect.swiping_rainbow",
+            Self::SoundFlash => "effect.sound_flash",
+            Self::SoundBars => "effect.sound_bars",
+            Self::SoundSlider => "effect.sound_slider",
+            Self::SoundShift => "effect.sound_shift",
+            Self::Ambient => "effect.ambient",
+            Self::ColorGradient => "effect.color_gradient",
+        }
+    }
+
+    fn english(&self) -> &'static str {
+        match self {
+            Self::Static => "Static",
+            Self::Breathing => "Breathing",
+            Self::Rainbow => "Rainbow",
+            Self::Blink => "Blink",
+            Self::ColorChange => "Color change",
+            Self::Sequence => "Sequence",
+            Self::Scanner => "Scanner",
+            Self::Laser => "Laser",
+            Self::Wave => "Wave",
+            Self::ColorSequence => "Color sequence",
+            Self::ColorShift => "Color shift",
+            Self::BarGraph => "Bar graph",
+            Self::Flame => "Flame",
+            Self::Rain => "Rain",
+            Self::Snow => "Snow",
+            Self::Stardust => "Stardust",
+            Self::ColorSwitch => "Color switch",
+            Self::SwipingRainbow => "Swiping rainbow",
+            Self::SoundFlash => "Sound flash",
+            Self::SoundBars => "Sound bars",
+            Self::SoundSlider => "Sound slider",
+            Self::SoundShift => "Sound shift",
+            Self::Ambient => "Ambient",
+            Self::ColorGradient => "Color gradient",
+        }
+    }
+}
+
+impl Localize for Effect {
+    fn key(&self) -> &'static str {
+        self.kind().key()
+    }
+
+    fn english(&self) -> &'static str {
+        self.kind().english()
+    }
+}
+
+impl Localize for Page {
+    fn key(&self) -> &'static str {
+        match self {
+            Self::DeviceInfo => "page.device_info",
+            Self::Flow => "page.flow",
+            Self::WaterTemperature => "page.water_temperature",
+            Self::ExternalTemperature => "page.external_temperature",
+            Self::Conductivity => "page.conductivity",
+            Self::WaterQuality => "page.water_quality",
+            Self::Volume => "page.volume",
+            Self::Power => "page.power",
+            Self::FlowWaterTemperature => "page.flow_water_temperature",
+            Self::ConductivityWaterQuality => "page.conductivity_water_quality",
+            Self::Temperatures => "page.temperatures",
+            Self::FlowVolume => "page.flow_volume",
+            Self::Chart(_) => "page.chart",
+        }
+    }
+
+    fn english(&self) -> &'static str {
+        match self {
+            Self::DeviceInfo => "Device info",
+            Self::Flow => "Flow",
+            Self::WaterTemperature => "Water temperature",
+            Self::ExternalTemperature => "External temperature",
+            Self::Conductivity => "Conductivity",
+            Self::WaterQuality => "Water quality",
+            Self::Volume => "Volume",
+            Self::Power => "Power",
+            Self::FlowWaterTemperature => "Flow / water temperature",
+            Self::ConductivityWaterQuality => "Conductivity / water quality",
+            Self::Temperatures => "Temperatures",
+            Self::FlowVolume => "Flow / volume",
+            Self::Chart(_) => "Chart {index}",
+        }
+    }
+
+    /// Charts are numbered starting at 1 (`index`).
+    fn args(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Self::Chart(index) => vec![("index", (index + 1).to_string())],
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Localize for Channel {
+    fn key(&self) -> &'static str {
+        match self {
+            Self::Flow => "channel.flow",
+            Self::WaterTemperature => "channel.water_temperature",
+            Self::ExternalTemperature => "channel.external_temperature",
+            Self::Conductivity => "channel.conductivity",
+            Self::WaterQuality => "channel.water_quality",
+            Self::Power => "channel.power",
+            Self::Voltage => "channel.voltage",
+        }
+    }
+
+    fn english(&self) -> &'static str {
+        match self {
+            Self::Flow => "Flow",
+            Self::WaterTemperature => "Water temperature",
+            Self::ExternalTemperature => "External temperature",
+            Self::Conductivity => "Conductivity",
+            Self::WaterQuality => "Water quality",
+            Self::Power => "Power",
+            Self::Voltage => "Voltage",
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::cell::Cell;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Source of the current time.
+///
+/// Time-dependent components (like the [`Scheduler`](crate::monitor::Scheduler),
+/// the [`AlarmEngine`](crate::monitor::AlarmEngine) or the
+/// [`Simulator`](crate::protocol::settings::simulate::Simulator))
+/// accept a clock, so they can be driven by the [`ManualClock`] in tests
+/// instead of waiting for the real time to pass.
+pub trait Clock {
+    /// Returns the current point in time of the monotonic clock.
+    fn now(&self) -> Instant;
+
+    /// Returns the current wall clock time.
+    fn system_time(&self) -> SystemTime;
+
+    /// Blocks until `duration` elapsed.
+    fn sleep(&self, duration: Duration);
+}
+
+/// [`Clock`] that uses the real time of the system.
+#[derive(Default, Debug, Clone, Copy, Eq, PartialEq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// [`Clock`] that only advances if it is told to.
+///
+/// [`sleep`](Clock::sleep) returns immediately and advances the clock by the
+/// passed duration, so loops that wait for the next due point in time run
+/// without any delay.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// use high_flow_next::misc::{Clock, ManualClock};
+///
+/// let clock = ManualClock::new();
+/// let start = clock.now();
+///
+/// clock.advance(Duration::from_secs(5));
+/// clock.sleep(Duration::from_secs(1));
+///
+/// assert_eq!(clock.now() - start, Duration::from_secs(6));
+/// assert_eq!(clock.system_time(), UNIX_EPOCH + Duration::from_secs(6));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    start: Instant,
+    system_start: SystemTime,
+    elapsed: Cell<Duration>,
+}
+
+impl ManualClock {
+    /// Creates a new clock whose wall clock time starts at the unix epoch.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_system_time(UNIX_EPOCH)
+    }
+
+    /// Creates a new clock whose wall clock time starts at `system_time`.
+    #[must_use]
+    pub fn with_system_time(system_time: SystemTime) -> Self {
+        Self {
+            start: Instant::now(),
+            system_start: system_time,
+            elapsed: Cell::new(Duration::ZERO),
+        }
+    }
+
+    /// Returns the time the clock was advanced by since it was created.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed.get()
+    }
+
+    /// Advances the clock by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        self.elapsed.set(self.elapsed.get() + duration);
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed.get()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        self.system_start + self.elapsed.get()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+}
+
+impl<T> Clock for &T
+where
+    T: Clock + ?Sized,
+{
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        (**self).system_time()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        (**self).sleep(duration);
+    }
+}
@@ -1,7 +0,0 @@
-mod decode;
-mod error;
-mod reader;
-
-pub use self::decode::Decode;
-pub use self::error::Error;
-pub use self::reader::{Guard, GuardOutput, Reader, SkipGuard, SkipReader, ValueGuard};
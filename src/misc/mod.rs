@@ -1,14 +1,15 @@
 //! Miscellaneous utilities for binary I/O, checksums, and typed wrappers.
 //!
 //! This module collects helper traits and types that are reused across
-//! different parts of the codebase.
+//! different parts of the codebase, like the [`Clock`] used by the
+//! time-dependent components. The device-agnostic parts are defined in
+//! [`core`](crate::core) and re-exported here.
 
-mod crc;
-mod io;
-mod wrapped;
+mod clock;
 
-pub use self::crc::{CrcReader, CrcWriter};
-pub use self::io::{
-    Decode, Error as IoError, Guard, GuardOutput, Reader, SkipGuard, SkipReader, ValueGuard,
+pub use self::clock::{Clock, ManualClock, SystemClock};
+pub use crate::core::{
+    checksum, AnyRangeError, CorruptFrame, CrcReader, CrcWriter, Decode, FixedSize, Guard,
+    GuardOutput, IoError, Percent, PositionReader, RangeError, Ranged, Reader, ValueGuard,
+    ValueVerifier, Wrapped,
 };
-pub use self::wrapped::{RangeError, Ranged, ValueVerifier, Wrapped};
@@ -0,0 +1,233 @@
+use std::time::{Duration, SystemTime};
+
+use crate::misc::Clock;
+
+use super::{Channel, SensorReadings};
+
+/// Comparison used by an [`AlarmRule`] to check the value of a channel
+/// against the threshold.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Comparison {
+    /// The rule is violated if the value raises above the threshold.
+    Above,
+
+    /// The rule is violated if the value drops below the threshold.
+    Below,
+}
+
+/// Rule that is evaluated by the [`AlarmEngine`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlarmRule {
+    /// Channel of the [`SensorReadings`] the rule is checked against.
+    pub source: Channel,
+
+    /// How to compare the value of the channel with the `threshold`.
+    pub comparison: Comparison,
+
+    /// Threshold in the physical unit of the `source` channel.
+    pub threshold: f64,
+
+    /// Time the threshold has to be violated before the alarm is raised.
+    pub for_duration: Duration,
+
+    /// Distance to the threshold the value has to go back before the alarm
+    /// is cleared again.
+    pub hysteresis: f64,
+}
+
+impl AlarmRule {
+    /// Creates a new rule that raises an alarm as soon as the value of the
+    /// `source` channel violates the `threshold`.
+    #[must_use]
+    pub fn new(source: Channel, comparison: Comparison, threshold: f64) -> Self {
+        Self {
+            source,
+            comparison,
+            threshold,
+            for_duration: Duration::ZERO,
+            hysteresis: 0.0,
+        }
+    }
+
+    /// Set the time the threshold has to be violated before the alarm is raised.
+    #[must_use]
+    pub fn for_duration(mut self, for_duration: Duration) -> Self {
+        self.for_duration = for_duration;
+
+        self
+    }
+
+    /// Set the hysteresis that is used to clear the alarm again.
+    #[must_use]
+    pub fn hysteresis(mut self, hysteresis: f64) -> Self {
+        self.hysteresis = hysteresis;
+
+        self
+    }
+
+    fn is_violated(&self, value: f64) -> bool {
+        match self.comparison {
+            Comparison::Above => value > self.threshold,
+            Comparison::Below => value < self.threshold,
+        }
+    }
+
+    fn is_cleared(&self, value: f64) -> bool {
+        match self.comparison {
+            Comparison::Above => value <= self.threshold - self.hysteresis,
+            Comparison::Below => value >= self.threshold + self.hysteresis,
+        }
+    }
+}
+
+/// Identifier of a rule added to the [`AlarmEngine`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct AlarmId(pub usize);
+
+/// Event emitted by the [`AlarmEngine`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlarmEvent {
+    /// The alarm of the rule was raised.
+    Raised {
+        /// Rule that raised the alarm.
+        id: AlarmId,
+
+        /// Value that raised the alarm.
+        value: f64,
+
+        /// Point in time the alarm was raised at.
+        at: SystemTime,
+    },
+
+    /// The alarm of the rule was cleared.
+    Cleared {
+        /// Rule that cleared the alarm.
+        id: AlarmId,
+
+        /// Value that cleared the alarm.
+        value: f64,
+
+        /// Point in time the alarm was cleared at.
+        at: SystemTime,
+    },
+}
+
+impl AlarmEvent {
+    /// Returns the identifier of the rule that emitted the event.
+    #[must_use]
+    pub fn id(&self) -> AlarmId {
+        match self {
+            Self::Raised { id, .. } | Self::Cleared { id, .. } => *id,
+        }
+    }
+}
+
+/// Evaluates a set of [`AlarmRule`]s over the stream of [`SensorReadings`].
+#[derive(Default, Debug, Clone)]
+pub struct AlarmEngine {
+    rules: Vec<(AlarmRule, RuleState)>,
+}
+
+#[derive(Default, Debug, Clone, Copy, Eq, PartialEq)]
+enum RuleState {
+    #[default]
+    Idle,
+    Pending(SystemTime),
+    Active,
+}
+
+impl AlarmEngine {
+    /// Creates a new engine without any rules.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a new rule to the engine and returns its identifier.
+    pub fn add_rule(&mut self, rule: AlarmRule) -> AlarmId {
+        self.rules.push((rule, RuleState::Idle));
+
+        AlarmId(self.rules.len() - 1)
+    }
+
+    /// Returns the rule with the passed `id`.
+    #[must_use]
+    pub fn rule(&self, id: AlarmId) -> Option<&AlarmRule> {
+        self.rules.get(id.0).map(|(rule, _)| rule)
+    }
+
+    /// Returns `true` if the alarm of the rule with the passed `id` is raised.
+    #[must_use]
+    pub fn is_active(&self, id: AlarmId) -> bool {
+        matches!(self.rules.get(id.0), Some((_, RuleState::Active)))
+    }
+
+    /// Returns an iterator over the identifiers of all raised alarms.
+    pub fn active(&self) -> impl Iterator<Item = AlarmId> + '_ {
+        self.rules
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, state))| *state == RuleState::Active)
+            .map(|(i, _)| AlarmId(i))
+    }
+
+    /// Evaluates all rules against the passed `readings` and returns the
+    /// alarms that were raised or cleared.
+    ///
+    /// Rules whose channel is not available in the readings keep their state.
+    pub fn evaluate(&mut self, readings: &SensorReadings) -> Vec<AlarmEvent> {
+        self.evaluate_at(readings, readings.captured_at)
+    }
+
+    /// Like [`evaluate`](Self::evaluate), but uses the wall clock time of the
+    /// passed `clock` instead of the capture time of the readings (e.g. for
+    /// readings of sources that do not provide a reliable timestamp).
+    pub fn evaluate_with_clock<K>(
+        &mut self,
+        readings: &SensorReadings,
+        clock: &K,
+    ) -> Vec<AlarmEvent>
+    where
+        K: Clock,
+    {
+        self.evaluate_at(readings, clock.system_time())
+    }
+
+    fn evaluate_at(&mut self, readings: &SensorReadings, at: SystemTime) -> Vec<AlarmEvent> {
+        let mut events = Vec::new();
+
+        for (i, (rule, state)) in self.rules.iter_mut().enumerate() {
+            let id = AlarmId(i);
+            let Some(value) = readings.value(rule.source) else {
+                continue;
+            };
+
+            match *state {
+                RuleState::Idle | RuleState::Pending(_) if !rule.is_violated(value) => {
+                    *state = RuleState::Idle;
+                }
+                RuleState::Idle | RuleState::Pending(_) => {
+                    let since = match *state {
+                        RuleState::Pending(since) => since,
+                        _ => at,
+                    };
+                    let elapsed = at.duration_since(since).unwrap_or_default();
+
+                    if elapsed >= rule.for_duration {
+                        *state = RuleState::Active;
+                        events.push(AlarmEvent::Raised { id, value, at });
+                    } else {
+                        *state = RuleState::Pending(since);
+                    }
+                }
+                RuleState::Active if rule.is_cleared(value) => {
+                    *state = RuleState::Idle;
+                    events.push(AlarmEvent::Cleared { id, value, at });
+                }
+                RuleState::Active => (),
+            }
+        }
+
+        events
+    }
+}
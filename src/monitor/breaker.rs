@@ -0,0 +1,328 @@
+use std::collections::VecDeque;
+use std::io::Error as IoError;
+use std::mem::discriminant;
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant};
+
+use crate::misc::{Clock, SystemClock};
+
+use super::{SensorReadings, Sink};
+
+/// Event emitted by the [`CircuitBreaker`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SinkEvent {
+    /// Delivering the buffered readings to the inner sink failed.
+    Failed {
+        /// Number of consecutive failures.
+        failures: u32,
+
+        /// Delay until the next attempt to deliver the readings.
+        retry_in: Duration,
+    },
+
+    /// The circuit was opened after too many consecutive failures.
+    Tripped {
+        /// Number of consecutive failures.
+        failures: u32,
+    },
+
+    /// The inner sink accepted the buffered readings again after the circuit
+    /// was opened.
+    Recovered {
+        /// Number of consecutive failures before the recovery.
+        failures: u32,
+
+        /// Number of readings that were delivered with the recovery.
+        delivered: usize,
+    },
+
+    /// The buffer was full and the oldest readings were dropped.
+    Dropped {
+        /// Number of dropped readings.
+        count: usize,
+    },
+}
+
+/// [`Sink`] that protects the polling loop from a failing inner sink (e.g.
+/// an MQTT broker or an HTTP endpoint that is down).
+///
+/// Published readings are collected in a bounded buffer and delivered to the
+/// inner sink as batch. If the delivery fails, the readings are kept and the
+/// next attempt is delayed by an exponential backoff (starting at
+/// [`with_backoff`](Self::with_backoff)'s `initial` delay and doubled for
+/// every consecutive failure). Readings published in the meantime are only
+/// buffered, so a failing sink does not block the polling loop. After
+/// [`with_threshold`](Self::with_threshold) consecutive failures the circuit
+/// is opened until a delivery succeeds again. If the buffer is full, the
+/// oldest readings are dropped.
+///
+/// Errors of the inner sink are not returned by [`publish`](Sink::publish),
+/// but reported as [`SinkEvent`]s (see [`take_events`](Self::take_events)).
+/// Use [`with_events`](Self::with_events) to receive the events of a sink
+/// that was added to a [`Publisher`](super::Publisher).
+/// Readings of a batch that failed partially may be delivered twice.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use high_flow_next::monitor::{CircuitBreaker, CsvSink, ErrorPolicy, Publisher};
+///
+/// let sink = CircuitBreaker::new(CsvSink::new(std::io::stdout()))
+///     .with_capacity(3600)
+///     .with_threshold(3)
+///     .with_backoff(Duration::from_secs(1), Duration::from_mins(1));
+///
+/// let mut publisher = Publisher::new();
+/// publisher.add_sink(sink, ErrorPolicy::Ignore);
+/// ```
+#[derive(Debug)]
+pub struct CircuitBreaker<S, K = SystemClock> {
+    sink: S,
+    clock: K,
+    capacity: usize,
+    threshold: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    buffer: VecDeque<SensorReadings>,
+    failures: u32,
+    next_attempt: Option<Instant>,
+    open: bool,
+    events: Vec<SinkEvent>,
+    sender: Option<Sender<SinkEvent>>,
+}
+
+impl<S: Sink> CircuitBreaker<S> {
+    /// Default value for the capacity of the buffer.
+    pub const DEFAULT_CAPACITY: usize = 1000;
+
+    /// Default value for the number of consecutive failures that open the
+    /// circuit.
+    pub const DEFAULT_THRESHOLD: u32 = 5;
+
+    /// Default value for the initial backoff.
+    pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+    /// Default value for the maximum backoff.
+    pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_mins(5);
+
+    /// Creates a new sink that protects the passed `sink`.
+    pub fn new(sink: S) -> Self {
+        Self::with_clock(sink, SystemClock)
+    }
+}
+
+impl<S: Sink, K: Clock> CircuitBreaker<S, K> {
+    /// Creates a new sink that protects the passed `sink`, using the passed
+    /// `clock` to schedule the retries.
+    pub fn with_clock(sink: S, clock: K) -> Self {
+        Self {
+            sink,
+            clock,
+            capacity: CircuitBreaker::<S>::DEFAULT_CAPACITY,
+            threshold: CircuitBreaker::<S>::DEFAULT_THRESHOLD,
+            initial_backoff: CircuitBreaker::<S>::DEFAULT_INITIAL_BACKOFF,
+            max_backoff: CircuitBreaker::<S>::DEFAULT_MAX_BACKOFF,
+            buffer: VecDeque::new(),
+            failures: 0,
+            next_attempt: None,
+            open: false,
+            events: Vec::new(),
+            sender: None,
+        }
+    }
+
+    /// Sets the maximum number of buffered readings and returns the updated
+    /// sink.
+    #[must_use]
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+
+        self
+    }
+
+    /// Sets the number of consecutive failures that open the circuit and
+    /// returns the updated sink.
+    #[must_use]
+    pub fn with_threshold(mut self, threshold: u32) -> Self {
+        self.threshold = threshold.max(1);
+
+        self
+    }
+
+    /// Sets the delay after the first failure and the maximum delay between
+    /// two attempts, and returns the updated sink.
+    #[must_use]
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+
+        self
+    }
+
+    /// Sends the events to the passed `sender` instead of collecting them
+    /// for [`take_events`](Self::take_events), and returns the updated sink.
+    #[must_use]
+    pub fn with_events(mut self, sender: Sender<SinkEvent>) -> Self {
+        self.sender = Some(sender);
+
+        self
+    }
+
+    /// Returns `true` if the circuit is open, i.e. the inner sink failed
+    /// too often.
+    #[must_use]
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Returns the number of consecutive failures of the inner sink.
+    #[must_use]
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Returns the number of readings that were not delivered yet.
+    #[must_use]
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns the point in time the next delivery is attempted at (`None`
+    /// if the last delivery succeeded).
+    #[must_use]
+    pub fn next_attempt(&self) -> Option<Instant> {
+        self.next_attempt
+    }
+
+    /// Returns the events emitted since the last call.
+    ///
+    /// Consecutive [`SinkEvent::Failed`] and [`SinkEvent::Dropped`] events
+    /// are merged into one event (the latest failure, the total number of
+    /// dropped readings) until the circuit is opened or closed.
+    pub fn take_events(&mut self) -> Vec<SinkEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Returns the inner sink, dropping the readings that were not
+    /// delivered yet.
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+
+    fn emit(&mut self, event: SinkEvent) {
+        let event = match &self.sender {
+            Some(sender) => match sender.send(event) {
+                Ok(()) => return,
+                Err(error) => error.0,
+            },
+            None => event,
+        };
+
+        // Merge the event with the previous event of the same kind since the
+        // circuit was opened or closed, so the queue stays bounded if the
+        // events are not taken.
+        let previous = self
+            .events
+            .iter_mut()
+            .rev()
+            .take_while(|x| matches!(x, SinkEvent::Failed { .. } | SinkEvent::Dropped { .. }))
+            .find(|x| discriminant(&**x) == discriminant(&event));
+
+        match (previous, event) {
+            (Some(SinkEvent::Dropped { count: dropped }), SinkEvent::Dropped { count }) => {
+                *dropped += count;
+            }
+            (Some(previous), event) => *previous = event,
+            (None, event) => self.events.push(event),
+        }
+    }
+
+    fn deliver(&mut self) -> Result<(), IoError> {
+        let result = match self.buffer.make_contiguous() {
+            [] => Ok(()),
+            [readings] => self.sink.publish(readings),
+            readings => self.sink.publish_batch(readings),
+        };
+
+        match result {
+            Ok(()) => {
+                if self.open {
+                    self.emit(SinkEvent::Recovered {
+                        failures: self.failures,
+                        delivered: self.buffer.len(),
+                    });
+                }
+
+                self.buffer.clear();
+                self.failures = 0;
+                self.next_attempt = None;
+                self.open = false;
+
+                Ok(())
+            }
+            Err(error) => {
+                self.failures = self.failures.saturating_add(1);
+
+                let factor = 1_u32.checked_shl(self.failures - 1).unwrap_or(u32::MAX);
+                let retry_in = self
+                    .initial_backoff
+                    .saturating_mul(factor)
+                    .min(self.max_backoff);
+
+                self.next_attempt = Some(self.clock.now() + retry_in);
+                self.emit(SinkEvent::Failed {
+                    failures: self.failures,
+                    retry_in,
+                });
+
+                if !self.open && self.failures >= self.threshold {
+                    self.open = true;
+                    self.emit(SinkEvent::Tripped {
+                        failures: self.failures,
+                    });
+                }
+
+                Err(error)
+            }
+        }
+    }
+}
+
+impl<S: Sink, K: Clock> Sink for CircuitBreaker<S, K> {
+    fn publish(&mut self, readings: &SensorReadings) -> Result<(), IoError> {
+        if self.buffer.len() >= self.capacity {
+            let count = self.buffer.len() + 1 - self.capacity;
+            self.buffer.drain(..count);
+
+            self.emit(SinkEvent::Dropped { count });
+        }
+
+        self.buffer.push_back(readings.clone());
+
+        if self
+            .next_attempt
+            .is_some_and(|next| next > self.clock.now())
+        {
+            return Ok(());
+        }
+
+        let _ = self.deliver();
+
+        Ok(())
+    }
+
+    /// Delivers the buffered readings, independent of the backoff, and
+    /// flushes the inner sink.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the inner sink if the readings could not be
+    /// delivered. The readings stay buffered in this case.
+    fn flush(&mut self) -> Result<(), IoError> {
+        self.deliver()?;
+
+        self.sink.flush()
+    }
+}
@@ -0,0 +1,273 @@
+use std::cmp::Ordering;
+use std::time::{Duration, Instant};
+
+use crate::protocol::settings::{Brightness, DisplayBrightness};
+use crate::protocol::Settings;
+
+/// Length of a day, used to wrap the time of day.
+const DAY: Duration = Duration::from_hours(24);
+
+/// Brightness values applied by the [`BrightnessScheduler`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct BrightnessLevel {
+    /// Display brightness during normal operation.
+    pub display: DisplayBrightness,
+
+    /// Display brightness during standby (`None` turns the display off).
+    pub idle_display: Option<DisplayBrightness>,
+
+    /// Brightness of the LED effects (`None` keeps the configured value).
+    pub leds: Option<Brightness>,
+}
+
+impl BrightnessLevel {
+    /// Creates a new level that only changes the display brightness.
+    #[must_use]
+    pub fn new(display: DisplayBrightness, idle_display: Option<DisplayBrightness>) -> Self {
+        Self {
+            display,
+            idle_display,
+            leds: None,
+        }
+    }
+
+    /// Sets the brightness of the LED effects.
+    #[must_use]
+    pub fn leds(mut self, leds: Brightness) -> Self {
+        self.leds = Some(leds);
+
+        self
+    }
+
+    fn is_applied(self, settings: &Settings) -> bool {
+        let display = &settings.display;
+        let leds = match (self.leds, &settings.lighting) {
+            (Some(leds), Some(lighting)) => lighting.brightness == leds,
+            _ => true,
+        };
+
+        display.display_brightness == self.display
+            && display.idle_display_brightness == self.idle_display
+            && leds
+    }
+
+    fn apply(self, settings: &mut Settings) {
+        settings.display.display_brightness = self.display;
+        settings.display.idle_display_brightness = self.idle_display;
+
+        if let (Some(leds), Some(lighting)) = (self.leds, &mut settings.lighting) {
+            lighting.brightness = leds;
+        }
+    }
+}
+
+/// Switches the display brightness (and optionally the LED brightness) based
+/// on the time of day or an ambient input (e.g. the value of a light sensor).
+///
+/// The scheduler only modifies the [`Settings`]; writing them to the device
+/// is up to the caller. To keep the number of writes to the flash of the
+/// device low, [`update`](Self::update) only reports a change if the
+/// brightness actually differs from the settings, and not more often than
+/// every [`min_interval`](Self::min_interval). Ambient rules use a
+/// [`hysteresis`](Self::hysteresis), so a value close to a threshold does
+/// not toggle the brightness.
+///
+/// If an ambient value is passed to [`update`](Self::update) and ambient
+/// rules are configured, they take precedence over the time rules.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use high_flow_next::monitor::{BrightnessLevel, BrightnessScheduler};
+/// use high_flow_next::protocol::settings::DisplayBrightness;
+///
+/// let scheduler = BrightnessScheduler::new()
+///     .at(
+///         Duration::from_secs(7 * 3600),
+///         BrightnessLevel::new(DisplayBrightness::Maximum, Some(DisplayBrightness::Low)),
+///     )
+///     .at(
+///         Duration::from_secs(22 * 3600),
+///         BrightnessLevel::new(DisplayBrightness::Low, None),
+///     );
+///
+/// let night = scheduler.target(Duration::from_secs(3600), None).unwrap();
+/// assert_eq!(night.display, DisplayBrightness::Low);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BrightnessScheduler {
+    time_rules: Vec<(Duration, BrightnessLevel)>,
+    ambient_rules: Vec<(f64, BrightnessLevel)>,
+    hysteresis: f64,
+    min_interval: Duration,
+    active_ambient: Option<usize>,
+    last_change: Option<Instant>,
+}
+
+impl BrightnessScheduler {
+    /// Default value for [`min_interval`](Self::min_interval).
+    pub const DEFAULT_MIN_INTERVAL: Duration = Duration::from_mins(10);
+
+    /// Creates a new scheduler without any rules.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            time_rules: Vec::new(),
+            ambient_rules: Vec::new(),
+            hysteresis: 0.0,
+            min_interval: Self::DEFAULT_MIN_INTERVAL,
+            active_ambient: None,
+            last_change: None,
+        }
+    }
+
+    /// Adds a rule that applies the passed `level` from `time_of_day` (time
+    /// since midnight) until the next time rule.
+    #[must_use]
+    pub fn at(mut self, time_of_day: Duration, level: BrightnessLevel) -> Self {
+        let time_of_day = time_of_day.as_secs() % DAY.as_secs();
+        let time_of_day = Duration::from_secs(time_of_day);
+        let index = self.time_rules.partition_point(|x| x.0 <= time_of_day);
+
+        self.time_rules.insert(index, (time_of_day, level));
+
+        self
+    }
+
+    /// Adds a rule that applies the passed `level` if the ambient value is at
+    /// least `threshold` (and below the next higher threshold).
+    ///
+    /// Values below the lowest threshold use the level of the lowest
+    /// threshold.
+    #[must_use]
+    pub fn above(mut self, threshold: f64, level: BrightnessLevel) -> Self {
+        let index = self.ambient_rules.partition_point(|x| x.0 <= threshold);
+
+        self.ambient_rules.insert(index, (threshold, level));
+
+        self
+    }
+
+    /// Sets the distance the ambient value has to cross a threshold by before
+    /// the brightness is switched.
+    #[must_use]
+    pub fn hysteresis(mut self, hysteresis: f64) -> Self {
+        self.hysteresis = hysteresis.abs();
+
+        self
+    }
+
+    /// Sets the minimum time between two changes reported by
+    /// [`update`](Self::update).
+    #[must_use]
+    pub fn min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = min_interval;
+
+        self
+    }
+
+    /// Returns the level for the passed `time_of_day` (time since midnight)
+    /// and `ambient` value, without considering the hysteresis.
+    ///
+    /// Returns `None` if no rule applies.
+    #[must_use]
+    pub fn target(&self, time_of_day: Duration, ambient: Option<f64>) -> Option<BrightnessLevel> {
+        if let Some(ambient) = ambient {
+            if let Some(index) = self.ambient_index(ambient) {
+                return Some(self.ambient_rules[index].1);
+            }
+        }
+
+        let time_of_day = Duration::from_secs(time_of_day.as_secs() % DAY.as_secs());
+        let index = self.time_rules.partition_point(|x| x.0 <= time_of_day);
+
+        // Before the first rule of the day the last rule of the previous day applies
+        let index = index
+            .checked_sub(1)
+            .unwrap_or(self.time_rules.len().checked_sub(1)?);
+
+        Some(self.time_rules[index].1)
+    }
+
+    /// Applies the level for the passed `time_of_day` (time since midnight)
+    /// and `ambient` value to the `settings`.
+    ///
+    /// Returns `true` if the settings were changed and should be written to
+    /// the device. The settings are not changed if they already match the
+    /// level, or if the last change was less than
+    /// [`min_interval`](Self::min_interval) before `now`.
+    pub fn update(
+        &mut self,
+        settings: &mut Settings,
+        now: Instant,
+        time_of_day: Duration,
+        ambient: Option<f64>,
+    ) -> bool {
+        let ambient = ambient.filter(|_| !self.ambient_rules.is_empty());
+        let level = match ambient {
+            Some(ambient) => {
+                let index = self.ambient_index_with_hysteresis(ambient);
+                self.active_ambient = index;
+
+                index.map(|index| self.ambient_rules[index].1)
+            }
+            None => {
+                self.active_ambient = None;
+
+                self.target(time_of_day, None)
+            }
+        };
+
+        let Some(level) = level else {
+            return false;
+        };
+
+        if level.is_applied(settings) {
+            return false;
+        }
+
+        if let Some(last_change) = self.last_change {
+            if now.saturating_duration_since(last_change) < self.min_interval {
+                return false;
+            }
+        }
+
+        level.apply(settings);
+        self.last_change = Some(now);
+
+        true
+    }
+
+    fn ambient_index(&self, ambient: f64) -> Option<usize> {
+        if self.ambient_rules.is_empty() {
+            return None;
+        }
+
+        let index = self.ambient_rules.partition_point(|x| x.0 <= ambient);
+
+        Some(index.saturating_sub(1))
+    }
+
+    fn ambient_index_with_hysteresis(&self, ambient: f64) -> Option<usize> {
+        let target = self.ambient_index(ambient)?;
+        let Some(active) = self.active_ambient else {
+            return Some(target);
+        };
+
+        let switch = match target.cmp(&active) {
+            Ordering::Greater => ambient >= self.ambient_rules[active + 1].0 + self.hysteresis,
+            Ordering::Less => ambient < self.ambient_rules[active].0 - self.hysteresis,
+            Ordering::Equal => false,
+        };
+
+        Some(if switch { target } else { active })
+    }
+}
+
+impl Default for BrightnessScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,191 @@
+use crate::misc::IoError;
+use crate::protocol::settings::{Flow, FlowCorrection};
+
+/// Default flows of the correction table as they are configured by the
+/// device (in 1/10 l/h).
+const DEFAULT_FLOWS: [u16; 10] = [200, 300, 500, 700, 1000, 1250, 1500, 2000, 2500, 3000];
+
+/// Records pairs of reference and device flow values and fits the
+/// [`FlowCorrection`] table of the device.
+///
+/// The samples have to be recorded with a neutral correction table (all
+/// corrections set to zero), so the device flow is the raw measured value.
+///
+/// The correction of every support point of the table is the weighted mean
+/// of the relative deviations of the samples next to it, using the same
+/// linear interpolation between two support points the device uses to apply
+/// the table. Support points without any samples nearby are interpolated
+/// from their neighbors.
+#[derive(Debug, Clone)]
+pub struct CalibrationSession {
+    flows: [Flow; 10],
+    samples: Vec<(f64, f64)>,
+}
+
+/// Result of a [`CalibrationSession`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Calibration {
+    /// Fitted correction table, ready to be written to
+    /// [`SensorSettings::flow_correction`](crate::protocol::settings::SensorSettings::flow_correction).
+    pub table: [(Flow, FlowCorrection); 10],
+
+    /// Root mean square of the remaining error of the corrected samples in
+    /// liter per hour (l/h).
+    pub rms_error: f64,
+
+    /// Maximum absolute remaining error of the corrected samples in liter
+    /// per hour (l/h).
+    pub max_error: f64,
+}
+
+impl CalibrationSession {
+    /// Creates a new session that fits the corrections for the passed
+    /// support `flows`.
+    ///
+    /// The flows are sorted in ascending order.
+    #[must_use]
+    pub fn new(mut flows: [Flow; 10]) -> Self {
+        flows.sort_by_key(|flow| **flow);
+
+        Self {
+            flows,
+            samples: Vec::new(),
+        }
+    }
+
+    /// Returns the number of recorded samples.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Returns `true` if no samples were recorded yet.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Records a sample of the `reference` flow and the flow reported by the
+    /// `device`, both in liter per hour (l/h).
+    ///
+    /// Samples with a non-positive device flow are ignored.
+    pub fn record(&mut self, reference: f64, device: f64) {
+        if device > 0.0 {
+            self.samples.push((reference, device));
+        }
+    }
+
+    /// Removes all recorded samples.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Fits the correction table and calculates the remaining error.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IoError::InvalidValue`] if no samples were recorded and
+    /// [`IoError::RangeError`] if a fitted correction exceeds the range
+    /// supported by the device.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    pub fn fit(&self) -> Result<Calibration, IoError> {
+        if self.samples.is_empty() {
+            return Err(IoError::InvalidValue("CalibrationSamples", 0));
+        }
+
+        let points = self.flows.map(|flow| f64::from(*flow) / 10.0);
+
+        let mut sums = [(0.0, 0.0); 10];
+        for &(reference, device) in &self.samples {
+            let deviation = reference / device - 1.0;
+
+            for (i, weight) in weights(&points, device) {
+                sums[i].0 += weight * deviation;
+                sums[i].1 += weight;
+            }
+        }
+
+        let known = sums.map(|(sum, weight)| (weight > 0.0).then(|| sum / weight));
+        let corrections = fill(&points, &known);
+
+        let mut squares = 0.0;
+        let mut max_error = 0.0_f64;
+        for &(reference, device) in &self.samples {
+            let correction = weights(&points, device)
+                .map(|(i, weight)| weight * corrections[i])
+                .sum::<f64>();
+            let error = (device * (1.0 + correction) - reference).abs();
+
+            squares += error * error;
+            max_error = max_error.max(error);
+        }
+
+        let mut table = [(self.flows[0], FlowCorrection::from_value(0)?); 10];
+        for (entry, (flow, correction)) in table.iter_mut().zip(self.flows.iter().zip(corrections))
+        {
+            let correction = (correction * 10_000.0).round() as i16;
+
+            *entry = (*flow, FlowCorrection::from_value(correction)?);
+        }
+
+        Ok(Calibration {
+            table,
+            rms_error: (squares / self.samples.len() as f64).sqrt(),
+            max_error,
+        })
+    }
+}
+
+impl Default for CalibrationSession {
+    /// Creates a session for the default support flows of the device.
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_FLOWS.map(|flow| Flow::from_value(flow).unwrap_or_else(|_| unreachable!())),
+        )
+    }
+}
+
+/// Returns the interpolation weights of the support `points` for the value `x`.
+fn weights(points: &[f64; 10], x: f64) -> impl Iterator<Item = (usize, f64)> {
+    let upper = points.partition_point(|point| *point < x);
+
+    let ret = match upper {
+        0 => [(0, 1.0), (0, 0.0)],
+        10 => [(9, 1.0), (9, 0.0)],
+        i => {
+            let span = points[i] - points[i - 1];
+            let t = if span > 0.0 {
+                (x - points[i - 1]) / span
+            } else {
+                1.0
+            };
+
+            [(i - 1, 1.0 - t), (i, t)]
+        }
+    };
+
+    ret.into_iter().filter(|(_, weight)| *weight > 0.0)
+}
+
+/// Fills the unknown values by linear interpolation between the known
+/// neighbors (or the nearest known value at the edges).
+fn fill(points: &[f64; 10], known: &[Option<f64>; 10]) -> [f64; 10] {
+    std::array::from_fn(|i| {
+        if let Some(value) = known[i] {
+            return value;
+        }
+
+        let prev = (0..i).rev().find_map(|j| Some((j, known[j]?)));
+        let next = (i + 1..10).find_map(|j| Some((j, known[j]?)));
+
+        match (prev, next) {
+            (Some((a, va)), Some((b, vb))) => {
+                let t = (points[i] - points[a]) / (points[b] - points[a]);
+
+                va + t * (vb - va)
+            }
+            (Some((_, value)), None) | (None, Some((_, value))) => value,
+            (None, None) => 0.0,
+        }
+    })
+}
@@ -0,0 +1,343 @@
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime};
+
+use super::{Channel, SensorReadings};
+
+/// Confidence of an [`Advisory`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Confidence {
+    /// The anomaly was barely detected.
+    Low,
+
+    /// The anomaly was clearly detected.
+    Medium,
+
+    /// The anomaly exceeds the configured threshold by far.
+    High,
+}
+
+impl Confidence {
+    /// Derives the confidence from the ratio between the detected deviation
+    /// and the configured threshold.
+    fn from_ratio(ratio: f64) -> Self {
+        if ratio >= 2.0 {
+            Self::High
+        } else if ratio >= 1.5 {
+            Self::Medium
+        } else {
+            Self::Low
+        }
+    }
+}
+
+/// Kind of anomaly an [`Advisory`] was emitted for.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum AdvisoryKind {
+    /// The flow dropped suddenly (e.g. blockage or pump failure).
+    SuddenFlowDrop,
+
+    /// The conductivity rose suddenly (e.g. leak or contamination).
+    ConductivitySpike,
+
+    /// The flow is degrading slowly (e.g. clogging filter or fin stack).
+    FlowDegradation,
+}
+
+/// Advisory event emitted by a [`Detector`].
+///
+/// Advisories are heuristics, they indicate a possible problem that should
+/// be checked by the user.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Advisory {
+    /// Kind of the detected anomaly.
+    pub kind: AdvisoryKind,
+
+    /// Confidence of the detection.
+    pub confidence: Confidence,
+
+    /// Value that triggered the advisory, in the physical unit of the channel
+    /// (for [`AdvisoryKind::FlowDegradation`] the change of the flow in liter
+    /// per hour per hour).
+    pub value: f64,
+
+    /// Reference value the `value` was compared with.
+    pub reference: f64,
+
+    /// Point in time the anomaly was detected at.
+    pub at: SystemTime,
+}
+
+/// Detects anomalies in the stream of [`SensorReadings`].
+pub trait Detector {
+    /// Feeds the passed `readings` into the detector and returns an advisory
+    /// if an anomaly was detected.
+    ///
+    /// An advisory is only returned once per anomaly, the detector has to
+    /// return to normal before the next advisory is emitted.
+    fn push(&mut self, readings: &SensorReadings) -> Option<Advisory>;
+}
+
+/// Detects sudden drops of the flow.
+///
+/// Compares the current flow with the highest flow within the configured
+/// time window.
+#[derive(Debug, Clone)]
+pub struct FlowDropDetector {
+    within: Duration,
+    threshold: f64,
+    samples: Samples,
+    triggered: bool,
+}
+
+impl FlowDropDetector {
+    /// Creates a new detector that reports a drop of at least `threshold`
+    /// (relative, `0.0..=1.0`) within the last `within`.
+    #[must_use]
+    pub fn new(within: Duration, threshold: f64) -> Self {
+        Self {
+            within,
+            threshold: threshold.clamp(f64::EPSILON, 1.0),
+            samples: Samples::default(),
+            triggered: false,
+        }
+    }
+}
+
+impl Default for FlowDropDetector {
+    /// Reports a flow drop of 30 % within 10 seconds.
+    fn default() -> Self {
+        Self::new(Duration::from_secs(10), 0.3)
+    }
+}
+
+impl Detector for FlowDropDetector {
+    fn push(&mut self, readings: &SensorReadings) -> Option<Advisory> {
+        let value = readings.value(Channel::Flow)?;
+        let at = readings.captured_at;
+
+        self.samples.push(at, value, self.within);
+
+        let reference = self.samples.max();
+        let drop = if reference > 0.0 {
+            (reference - value) / reference
+        } else {
+            0.0
+        };
+
+        trigger(&mut self.triggered, drop >= self.threshold).then(|| Advisory {
+            kind: AdvisoryKind::SuddenFlowDrop,
+            confidence: Confidence::from_ratio(drop / self.threshold),
+            value,
+            reference,
+            at,
+        })
+    }
+}
+
+/// Detects sudden spikes of the conductivity.
+///
+/// Compares the current conductivity with the mean conductivity within the
+/// configured time window.
+#[derive(Debug, Clone)]
+pub struct ConductivitySpikeDetector {
+    within: Duration,
+    threshold: f64,
+    samples: Samples,
+    triggered: bool,
+}
+
+impl ConductivitySpikeDetector {
+    /// Creates a new detector that reports a rise of at least `threshold`
+    /// (in µS/cm) compared to the mean within the last `within`.
+    #[must_use]
+    pub fn new(within: Duration, threshold: f64) -> Self {
+        Self {
+            within,
+            threshold: threshold.abs().max(f64::EPSILON),
+            samples: Samples::default(),
+            triggered: false,
+        }
+    }
+}
+
+impl Default for ConductivitySpikeDetector {
+    /// Reports a rise of 10 µS/cm compared to the mean of the last 10 minutes.
+    fn default() -> Self {
+        Self::new(Duration::from_mins(10), 10.0)
+    }
+}
+
+impl Detector for ConductivitySpikeDetector {
+    fn push(&mut self, readings: &SensorReadings) -> Option<Advisory> {
+        let value = readings.value(Channel::Conductivity)?;
+        let at = readings.captured_at;
+
+        let reference = self.samples.mean().unwrap_or(value);
+        let rise = value - reference;
+
+        self.samples.push(at, value, self.within);
+
+        trigger(&mut self.triggered, rise >= self.threshold).then(|| Advisory {
+            kind: AdvisoryKind::ConductivitySpike,
+            confidence: Confidence::from_ratio(rise / self.threshold),
+            value,
+            reference,
+            at,
+        })
+    }
+}
+
+/// Detects slow degradation of the flow.
+///
+/// Fits a linear trend (least squares) through the flow values within the
+/// configured time window. The confidence is derived from the coefficient
+/// of determination (R²) of the fit.
+#[derive(Debug, Clone)]
+pub struct FlowTrendDetector {
+    window: Duration,
+    threshold: f64,
+    samples: Samples,
+    triggered: bool,
+}
+
+impl FlowTrendDetector {
+    /// Creates a new detector that reports a decline of at least `threshold`
+    /// liter per hour per hour within the last `window`.
+    #[must_use]
+    pub fn new(window: Duration, threshold: f64) -> Self {
+        Self {
+            window,
+            threshold: threshold.abs(),
+            samples: Samples::default(),
+            triggered: false,
+        }
+    }
+}
+
+impl Default for FlowTrendDetector {
+    /// Reports a decline of 1 l/h per hour over the last 24 hours.
+    fn default() -> Self {
+        Self::new(Duration::from_hours(24), 1.0)
+    }
+}
+
+impl Detector for FlowTrendDetector {
+    fn push(&mut self, readings: &SensorReadings) -> Option<Advisory> {
+        let value = readings.value(Channel::Flow)?;
+        let at = readings.captured_at;
+
+        self.samples.push(at, value, self.window);
+
+        let covered = self.samples.span() >= self.window / 2;
+        let trend = covered.then(|| self.samples.trend()).flatten();
+        let (slope, r2) = trend.unwrap_or((0.0, 0.0));
+
+        trigger(&mut self.triggered, -slope >= self.threshold).then(|| Advisory {
+            kind: AdvisoryKind::FlowDegradation,
+            confidence: if r2 >= 0.9 {
+                Confidence::High
+            } else if r2 >= 0.6 {
+                Confidence::Medium
+            } else {
+                Confidence::Low
+            },
+            value: slope,
+            reference: -self.threshold,
+            at,
+        })
+    }
+}
+
+/// Returns `true` if `matched` changed from `false` to `true`.
+fn trigger(triggered: &mut bool, matched: bool) -> bool {
+    let ret = matched && !*triggered;
+
+    *triggered = matched;
+
+    ret
+}
+
+#[derive(Default, Debug, Clone)]
+struct Samples(VecDeque<(SystemTime, f64)>);
+
+impl Samples {
+    fn push(&mut self, at: SystemTime, value: f64, within: Duration) {
+        self.0.push_back((at, value));
+
+        if let Some(limit) = at.checked_sub(within) {
+            while self.0.front().is_some_and(|(time, _)| *time < limit) {
+                self.0.pop_front();
+            }
+        }
+    }
+
+    fn max(&self) -> f64 {
+        self.0.iter().map(|(_, x)| *x).fold(f64::MIN, f64::max)
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn mean(&self) -> Option<f64> {
+        if self.0.is_empty() {
+            return None;
+        }
+
+        Some(self.0.iter().map(|(_, x)| *x).sum::<f64>() / self.0.len() as f64)
+    }
+
+    fn span(&self) -> Duration {
+        match (self.0.front(), self.0.back()) {
+            (Some((first, _)), Some((last, _))) => last.duration_since(*first).unwrap_or_default(),
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Returns the slope (per hour) and the coefficient of determination of
+    /// the linear least squares fit through the samples.
+    #[allow(clippy::cast_precision_loss)]
+    fn trend(&self) -> Option<(f64, f64)> {
+        let (first, _) = self.0.front()?;
+        let points = self
+            .0
+            .iter()
+            .map(|(time, value)| {
+                let hours = time
+                    .duration_since(*first)
+                    .unwrap_or_default()
+                    .as_secs_f64()
+                    / 3600.0;
+
+                (hours, *value)
+            })
+            .collect::<Vec<_>>();
+
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+        let sxx = points
+            .iter()
+            .map(|(x, _)| (x - mean_x).powi(2))
+            .sum::<f64>();
+        let syy = points
+            .iter()
+            .map(|(_, y)| (y - mean_y).powi(2))
+            .sum::<f64>();
+        let sxy = points
+            .iter()
+            .map(|(x, y)| (x - mean_x) * (y - mean_y))
+            .sum::<f64>();
+
+        if sxx == 0.0 {
+            return None;
+        }
+
+        let slope = sxy / sxx;
+        let r2 = if syy == 0.0 {
+            1.0
+        } else {
+            sxy * sxy / (sxx * syy)
+        };
+
+        Some((slope, r2))
+    }
+}
@@ -0,0 +1,261 @@
+use std::time::{Duration, SystemTime};
+
+use crate::protocol::Settings;
+
+use super::{Channel, SensorReadings};
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Persistent state of a [`DriftAnalyzer`].
+///
+/// The state only contains the sums of the linear regression, so its size
+/// does not grow with the tracked period. It can be converted to and from a
+/// fixed size byte array using [`to_bytes`](Self::to_bytes) and
+/// [`from_bytes`](Self::from_bytes).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DriftState {
+    /// Point in time the analysis was started at.
+    pub started_at: SystemTime,
+
+    /// Point in time the last sample was taken at.
+    pub last_sample: Option<SystemTime>,
+
+    /// Number of samples.
+    pub count: u64,
+
+    /// Sum of the sample times (days since `started_at`).
+    pub sum_t: f64,
+
+    /// Sum of the sampled conductivity values.
+    pub sum_y: f64,
+
+    /// Sum of the squared sample times.
+    pub sum_tt: f64,
+
+    /// Sum of the products of sample time and conductivity.
+    pub sum_ty: f64,
+}
+
+impl DriftState {
+    /// Size of the serialized state in bytes.
+    pub const SIZE: usize = 56;
+
+    /// Creates a new empty state started at the passed point in time.
+    #[must_use]
+    pub fn new(started_at: SystemTime) -> Self {
+        Self {
+            started_at,
+            last_sample: None,
+            count: 0,
+            sum_t: 0.0,
+            sum_y: 0.0,
+            sum_tt: 0.0,
+            sum_ty: 0.0,
+        }
+    }
+
+    /// Converts the state into its binary representation (big-endian, times
+    /// in seconds since the unix epoch, `u64::MAX` if no sample was taken
+    /// yet, sums as IEEE 754 doubles).
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let secs = |time: SystemTime| {
+            time.duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+        };
+
+        let values = [
+            secs(self.started_at),
+            self.last_sample.map_or(u64::MAX, secs),
+            self.count,
+            self.sum_t.to_bits(),
+            self.sum_y.to_bits(),
+            self.sum_tt.to_bits(),
+            self.sum_ty.to_bits(),
+        ];
+
+        let mut bytes = [0; Self::SIZE];
+        for (chunk, value) in bytes.chunks_exact_mut(8).zip(values) {
+            chunk.copy_from_slice(&value.to_be_bytes());
+        }
+
+        bytes
+    }
+
+    /// Creates the state from its binary representation
+    /// (see [`to_bytes`](Self::to_bytes)).
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        let mut values = [0_u64; 7];
+        for (value, chunk) in values.iter_mut().zip(bytes.chunks_exact(8)) {
+            let mut buf = [0; 8];
+            buf.copy_from_slice(chunk);
+
+            *value = u64::from_be_bytes(buf);
+        }
+
+        let time = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+
+        Self {
+            started_at: time(values[0]),
+            last_sample: (values[1] != u64::MAX).then(|| time(values[1])),
+            count: values[2],
+            sum_t: f64::from_bits(values[3]),
+            sum_y: f64::from_bits(values[4]),
+            sum_tt: f64::from_bits(values[5]),
+            sum_ty: f64::from_bits(values[6]),
+        }
+    }
+}
+
+/// Tracks the conductivity of the coolant over long periods to estimate its
+/// aging.
+///
+/// The conductivity is sampled at most once per
+/// [`interval`](Self::interval) and a linear drift is fitted through all
+/// samples (least squares).
+#[derive(Debug, Clone)]
+pub struct DriftAnalyzer {
+    state: DriftState,
+    interval: Duration,
+}
+
+impl DriftAnalyzer {
+    /// Default value for [`interval`](Self::interval).
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_hours(1);
+
+    /// Creates a new analyzer started at the passed point in time.
+    #[must_use]
+    pub fn new(started_at: SystemTime) -> Self {
+        Self::from_state(DriftState::new(started_at))
+    }
+
+    /// Creates a new analyzer that continues the analysis of the passed `state`.
+    #[must_use]
+    pub fn from_state(state: DriftState) -> Self {
+        Self {
+            state,
+            interval: Self::DEFAULT_INTERVAL,
+        }
+    }
+
+    /// Set the minimum interval between two samples.
+    #[must_use]
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+
+        self
+    }
+
+    /// Returns the current state of the analyzer that should be persisted.
+    #[must_use]
+    pub fn state(&self) -> DriftState {
+        self.state
+    }
+
+    /// Adds the conductivity of the passed `readings` to the analysis.
+    ///
+    /// Returns `true` if the readings were sampled.
+    pub fn push(&mut self, readings: &SensorReadings) -> bool {
+        let Some(value) = readings.value(Channel::Conductivity) else {
+            return false;
+        };
+
+        let at = readings.captured_at;
+        let due = self
+            .state
+            .last_sample
+            .is_none_or(|last| at.duration_since(last).is_ok_and(|x| x >= self.interval));
+        if !due {
+            return false;
+        }
+
+        let t = self.days(at);
+        let state = &mut self.state;
+        state.last_sample = Some(at);
+        state.count += 1;
+        state.sum_t += t;
+        state.sum_y += value;
+        state.sum_tt += t * t;
+        state.sum_ty += t * value;
+
+        true
+    }
+
+    /// Returns the fitted drift rate of the conductivity in µS/cm per day.
+    ///
+    /// Returns `None` if not enough samples were taken.
+    #[must_use]
+    pub fn drift_rate(&self) -> Option<f64> {
+        self.fit().map(|(slope, _)| slope)
+    }
+
+    /// Returns the fitted conductivity at the passed point in time.
+    #[must_use]
+    pub fn estimate(&self, at: SystemTime) -> Option<f64> {
+        let (slope, intercept) = self.fit()?;
+
+        Some(intercept + slope * self.days(at))
+    }
+
+    /// Estimates the days from `now` until the fitted conductivity reaches
+    /// the passed `limit` (µS/cm).
+    ///
+    /// Returns `Some(0.0)` if the limit is already reached and `None` if not
+    /// enough samples were taken or the conductivity is not rising.
+    #[must_use]
+    pub fn days_until(&self, limit: f64, now: SystemTime) -> Option<f64> {
+        let (slope, intercept) = self.fit()?;
+        let current = intercept + slope * self.days(now);
+
+        if current >= limit {
+            Some(0.0)
+        } else if slope > 0.0 {
+            Some((limit - current) / slope)
+        } else {
+            None
+        }
+    }
+
+    /// Estimates the days from `now` until the water quality alarm of the
+    /// passed `settings` is raised.
+    ///
+    /// The water quality limit is converted to the conductivity using the
+    /// conductivities the device maps to 100 % and 0 % water quality. Returns
+    /// `None` if the alarm is disabled (see also [`days_until`](Self::days_until)).
+    #[must_use]
+    pub fn days_until_alarm(&self, settings: &Settings, now: SystemTime) -> Option<f64> {
+        let limit = f64::from(*settings.alarms.water_quality_limit?) / 10_000.0;
+        let best = f64::from(*settings.sensor.water_quality_max);
+        let worst = f64::from(*settings.sensor.water_quality_min);
+
+        self.days_until(worst - limit * (worst - best), now)
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn fit(&self) -> Option<(f64, f64)> {
+        let state = &self.state;
+        if state.count < 2 {
+            return None;
+        }
+
+        let n = state.count as f64;
+        let denominator = n * state.sum_tt - state.sum_t * state.sum_t;
+        if denominator.abs() < f64::EPSILON {
+            return None;
+        }
+
+        let slope = (n * state.sum_ty - state.sum_t * state.sum_y) / denominator;
+        let intercept = (state.sum_y - slope * state.sum_t) / n;
+
+        Some((slope, intercept))
+    }
+
+    fn days(&self, at: SystemTime) -> f64 {
+        match at.duration_since(self.state.started_at) {
+            Ok(x) => x.as_secs_f64() / SECONDS_PER_DAY,
+            Err(err) => -err.duration().as_secs_f64() / SECONDS_PER_DAY,
+        }
+    }
+}
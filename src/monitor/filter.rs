@@ -0,0 +1,226 @@
+use std::time::{Duration, SystemTime};
+
+use crate::protocol::settings::{Controller, PowerDamping};
+
+/// Common interface of the smoothing filters.
+pub trait Filter {
+    /// Feeds a new raw `value` into the filter and returns the filtered value.
+    fn update(&mut self, value: f64) -> f64;
+
+    /// Returns the current filtered value (`None` if no value was fed yet).
+    fn value(&self) -> Option<f64>;
+
+    /// Resets the filter to its initial state.
+    fn reset(&mut self);
+}
+
+/// Exponential moving average.
+///
+/// Each new value is weighted with `alpha` (`0.0..=1.0`), the previous output
+/// with `1.0 - alpha`. An `alpha` of `1.0` disables the smoothing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExponentialFilter {
+    alpha: f64,
+    value: Option<f64>,
+}
+
+impl ExponentialFilter {
+    /// Creates a new filter using the passed smoothing factor `alpha`.
+    #[must_use]
+    pub fn new(alpha: f64) -> Self {
+        Self {
+            alpha: alpha.clamp(0.0, 1.0),
+            value: None,
+        }
+    }
+
+    /// Returns the smoothing factor of the filter.
+    #[must_use]
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+}
+
+impl Filter for ExponentialFilter {
+    fn update(&mut self, value: f64) -> f64 {
+        let value = match self.value {
+            Some(prev) => prev + self.alpha * (value - prev),
+            None => value,
+        };
+
+        self.value = Some(value);
+
+        value
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.value
+    }
+
+    fn reset(&mut self) {
+        self.value = None;
+    }
+}
+
+/// Exponential moving average for irregular sampling intervals.
+///
+/// The smoothing factor is derived from the elapsed time between two values
+/// and the `time_constant` of the filter, so the result does not depend on
+/// the polling rate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedExponentialFilter {
+    time_constant: Duration,
+    last: Option<(SystemTime, f64)>,
+}
+
+impl TimedExponentialFilter {
+    /// Creates a new filter with the passed `time_constant`.
+    #[must_use]
+    pub fn new(time_constant: Duration) -> Self {
+        Self {
+            time_constant,
+            last: None,
+        }
+    }
+
+    /// Feeds a new raw `value` captured at `time` into the filter and returns
+    /// the filtered value.
+    pub fn update_at(&mut self, time: SystemTime, value: f64) -> f64 {
+        let value = match self.last {
+            Some((prev_time, prev)) if !self.time_constant.is_zero() => {
+                let dt = time.duration_since(prev_time).unwrap_or_default();
+                let alpha = 1.0 - (-dt.as_secs_f64() / self.time_constant.as_secs_f64()).exp();
+
+                prev + alpha * (value - prev)
+            }
+            _ => value,
+        };
+
+        self.last = Some((time, value));
+
+        value
+    }
+
+    /// Returns the current filtered value (`None` if no value was fed yet).
+    #[must_use]
+    pub fn value(&self) -> Option<f64> {
+        self.last.map(|(_, value)| value)
+    }
+
+    /// Resets the filter to its initial state.
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+/// Filter with separate smoothing for rising and falling values.
+///
+/// Approximates the sensor attenuation of the device
+/// ([`Controller::sensor_attenuation_rising`] and
+/// [`Controller::sensor_attenuation_falling`]): an attenuation of `0` follows
+/// the input immediately, higher values follow it more slowly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttenuationFilter {
+    rising: u8,
+    falling: u8,
+    value: Option<f64>,
+}
+
+impl AttenuationFilter {
+    /// Creates a new filter with the passed `rising` and `falling` attenuation.
+    #[must_use]
+    pub fn new(rising: u8, falling: u8) -> Self {
+        Self {
+            rising,
+            falling,
+            value: None,
+        }
+    }
+
+    /// Creates a new filter with the attenuation configured for the passed
+    /// `controller`.
+    #[must_use]
+    pub fn from_controller(controller: &Controller) -> Self {
+        Self::new(
+            controller.sensor_attenuation_rising,
+            controller.sensor_attenuation_falling,
+        )
+    }
+
+    fn alpha(attenuation: u8) -> f64 {
+        1.0 / (1.0 + f64::from(attenuation))
+    }
+}
+
+impl Filter for AttenuationFilter {
+    fn update(&mut self, value: f64) -> f64 {
+        let value = match self.value {
+            Some(prev) if value > prev => prev + Self::alpha(self.rising) * (value - prev),
+            Some(prev) => prev + Self::alpha(self.falling) * (value - prev),
+            None => value,
+        };
+
+        self.value = Some(value);
+
+        value
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.value
+    }
+
+    fn reset(&mut self) {
+        self.value = None;
+    }
+}
+
+/// Filter that ignores changes smaller than a configured threshold.
+///
+/// Approximates the power damping of the device
+/// ([`SensorSettings::power_damping`](crate::protocol::settings::SensorSettings::power_damping)):
+/// the output only follows the input once it differs by at least the
+/// damping value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeadbandFilter {
+    threshold: f64,
+    value: Option<f64>,
+}
+
+impl DeadbandFilter {
+    /// Creates a new filter that ignores changes smaller than `threshold`.
+    #[must_use]
+    pub fn new(threshold: f64) -> Self {
+        Self {
+            threshold: threshold.abs(),
+            value: None,
+        }
+    }
+
+    /// Creates a new filter for power values in watts using the passed
+    /// `damping` of the device.
+    #[must_use]
+    pub fn from_power_damping(damping: PowerDamping) -> Self {
+        Self::new(f64::from(*damping) / 1000.0)
+    }
+}
+
+impl Filter for DeadbandFilter {
+    fn update(&mut self, value: f64) -> f64 {
+        match self.value {
+            Some(prev) if (value - prev).abs() < self.threshold => prev,
+            _ => {
+                self.value = Some(value);
+
+                value
+            }
+        }
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.value
+    }
+
+    fn reset(&mut self) {
+        self.value = None;
+    }
+}
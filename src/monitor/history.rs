@@ -0,0 +1,162 @@
+use std::collections::VecDeque;
+use std::time::SystemTime;
+
+use super::{Channel, SensorReadings};
+
+/// Ring buffer storing the last [`SensorReadings`].
+///
+/// Once the configured capacity is reached the oldest readings are dropped.
+/// The readings are expected to be pushed in chronological order.
+#[derive(Debug, Clone)]
+pub struct History {
+    capacity: usize,
+    readings: VecDeque<SensorReadings>,
+}
+
+impl History {
+    /// Creates a new history that stores up to `capacity` readings.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            readings: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the maximum number of readings stored in the history.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of readings stored in the history.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.readings.len()
+    }
+
+    /// Returns `true` if the history does not contain any readings.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.readings.is_empty()
+    }
+
+    /// Adds the passed `readings` to the history, dropping the oldest
+    /// readings if the capacity is exceeded.
+    pub fn push(&mut self, readings: SensorReadings) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        while self.readings.len() >= self.capacity {
+            self.readings.pop_front();
+        }
+
+        self.readings.push_back(readings);
+    }
+
+    /// Removes all readings from the history.
+    pub fn clear(&mut self) {
+        self.readings.clear();
+    }
+
+    /// Returns the most recent readings.
+    #[must_use]
+    pub fn latest(&self) -> Option<&SensorReadings> {
+        self.readings.back()
+    }
+
+    /// Returns an iterator over all readings, starting with the oldest one.
+    #[must_use]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &SensorReadings> + '_ {
+        self.readings.iter()
+    }
+
+    /// Returns an iterator over the readings captured within `from..to`.
+    ///
+    /// The bounds may be passed as [`SystemTime`], [`Timestamp`](super::Timestamp) or (with the
+    /// `time` / `chrono` feature) as date time of the respective crate.
+    #[must_use]
+    pub fn range(
+        &self,
+        from: impl Into<SystemTime>,
+        to: impl Into<SystemTime>,
+    ) -> impl DoubleEndedIterator<Item = &SensorReadings> + '_ {
+        let from = from.into();
+        let to = to.into();
+        let start = self.readings.partition_point(|x| x.captured_at < from);
+        let end = self.readings.partition_point(|x| x.captured_at < to);
+
+        self.readings.range(start..end.max(start))
+    }
+
+    /// Returns the values of the passed `channel` within `from..to`,
+    /// downsampled to at most `buckets` points.
+    ///
+    /// The time range is split into `buckets` intervals of equal length. For
+    /// every interval that contains at least one value the mean of the values
+    /// is returned together with the start of the interval.
+    #[must_use]
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    pub fn downsample(
+        &self,
+        channel: Channel,
+        from: impl Into<SystemTime>,
+        to: impl Into<SystemTime>,
+        buckets: usize,
+    ) -> Vec<(SystemTime, f64)> {
+        let from = from.into();
+        let to = to.into();
+        let Ok(span) = to.duration_since(from) else {
+            return Vec::new();
+        };
+
+        if buckets == 0 || span.is_zero() {
+            return Vec::new();
+        }
+
+        let step = span / u32::try_from(buckets).unwrap_or(u32::MAX);
+        let mut sums = vec![(0.0, 0_usize); buckets];
+
+        for readings in self.range(from, to) {
+            let Some(value) = readings.value(channel) else {
+                continue;
+            };
+
+            let offset = readings
+                .captured_at
+                .duration_since(from)
+                .unwrap_or_default();
+            let index = (offset.as_secs_f64() / span.as_secs_f64() * buckets as f64) as usize;
+            let (sum, count) = &mut sums[index.min(buckets - 1)];
+
+            *sum += value;
+            *count += 1;
+        }
+
+        sums.into_iter()
+            .zip(0_u32..)
+            .filter(|((_, count), _)| *count > 0)
+            .map(|((sum, count), i)| (from + step * i, sum / count as f64))
+            .collect()
+    }
+}
+
+impl Default for History {
+    /// Creates a history for one hour of readings polled once per second.
+    fn default() -> Self {
+        Self::new(3600)
+    }
+}
+
+impl Extend<SensorReadings> for History {
+    fn extend<I: IntoIterator<Item = SensorReadings>>(&mut self, iter: I) {
+        for readings in iter {
+            self.push(readings);
+        }
+    }
+}
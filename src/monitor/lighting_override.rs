@@ -0,0 +1,170 @@
+use std::collections::BTreeSet;
+
+use arrayvec::ArrayVec;
+
+use crate::protocol::settings::{
+    Brightness, Color, Controller, Effect, EffectBlink, EffectPercent, LightingSettings,
+};
+use crate::protocol::Settings;
+
+use super::{AlarmEvent, AlarmId};
+
+/// Overrides the lighting of the device while an alarm of the
+/// [`AlarmEngine`](super::AlarmEngine) is active.
+///
+/// When the first alarm is raised, the current lighting settings are saved
+/// and replaced by the override (e.g. all LEDs blinking red, see
+/// [`blink`](Self::blink)). When the last active alarm is cleared, the saved
+/// lighting settings are restored.
+///
+/// The override only modifies the [`Settings`]; writing them to the device
+/// is up to the caller.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::SystemTime;
+///
+/// use high_flow_next::monitor::{AlarmEvent, AlarmId, AlarmOverride};
+/// use high_flow_next::protocol::settings::Color;
+/// # use high_flow_next::{misc::Decode, protocol::Frame};
+/// # let mut reader = std::fs::File::open("tests/assets/default.frame").unwrap();
+/// # let Frame::Settings(mut settings) = Frame::decode(&mut reader).unwrap();
+///
+/// let mut alarm_override = AlarmOverride::blink(Color::from_rgb(255, 0, 0));
+/// let original = settings.lighting.clone();
+///
+/// let raised = AlarmEvent::Raised { id: AlarmId(0), value: 50.0, at: SystemTime::now() };
+/// assert!(alarm_override.handle(&raised, &mut settings));
+/// assert_ne!(settings.lighting, original);
+///
+/// let cleared = AlarmEvent::Cleared { id: AlarmId(0), value: 40.0, at: SystemTime::now() };
+/// assert!(alarm_override.handle(&cleared, &mut settings));
+/// assert_eq!(settings.lighting, original);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AlarmOverride {
+    effect: Effect,
+    brightness: Option<Brightness>,
+    active: BTreeSet<AlarmId>,
+    saved: Option<LightingSettings>,
+    is_active: bool,
+}
+
+impl AlarmOverride {
+    /// Creates a new override that displays the passed `effect` on all
+    /// configured controllers, keeping their regions.
+    #[must_use]
+    pub fn new(effect: Effect) -> Self {
+        Self {
+            effect,
+            brightness: None,
+            active: BTreeSet::new(),
+            saved: None,
+            is_active: false,
+        }
+    }
+
+    /// Creates a new override that lets all LEDs blink in the passed
+    /// `color`.
+    #[must_use]
+    pub fn blink(color: Color) -> Self {
+        let mut colors = ArrayVec::new();
+        colors.push(color);
+
+        Self::new(Effect::Blink(EffectBlink {
+            background: Color::from_rgb(0, 0, 0),
+            colors,
+            speed: EffectPercent::from_percent(75.0),
+            fade_in: false,
+            fade_out: false,
+            random_color: false,
+            slide_colors: false,
+            source_control_speed: None,
+            source_control_brightness: None,
+        }))
+    }
+
+    /// Sets the brightness of the LEDs while the override is active (by
+    /// default the configured brightness is kept).
+    #[must_use]
+    pub fn brightness(mut self, brightness: Brightness) -> Self {
+        self.brightness = Some(brightness);
+
+        self
+    }
+
+    /// Returns `true` if the override is currently applied.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    /// Returns the lighting settings that are restored when the last alarm
+    /// is cleared (`None` if the override is not active or the lighting was
+    /// disabled).
+    #[must_use]
+    pub fn saved(&self) -> Option<&LightingSettings> {
+        self.saved.as_ref()
+    }
+
+    /// Updates the override with the passed alarm `event`.
+    ///
+    /// Returns `true` if the `settings` were changed and should be written
+    /// to the device.
+    pub fn handle(&mut self, event: &AlarmEvent, settings: &mut Settings) -> bool {
+        match event {
+            AlarmEvent::Raised { id, .. } => {
+                self.active.insert(*id);
+
+                if self.is_active {
+                    return false;
+                }
+
+                self.is_active = true;
+                self.saved.clone_from(&settings.lighting);
+
+                self.apply(settings)
+            }
+            AlarmEvent::Cleared { id, .. } => {
+                self.active.remove(id);
+
+                if !self.is_active || !self.active.is_empty() {
+                    return false;
+                }
+
+                self.is_active = false;
+                let lighting = self.saved.take();
+
+                let changed = settings.lighting != lighting;
+                settings.lighting = lighting;
+
+                changed
+            }
+        }
+    }
+
+    fn apply(&self, settings: &mut Settings) -> bool {
+        let Some(lighting) = &mut settings.lighting else {
+            return false;
+        };
+
+        if let Some(brightness) = self.brightness {
+            lighting.brightness = brightness;
+        }
+
+        let controllers = lighting
+            .strip_controllers
+            .iter_mut()
+            .chain(&mut lighting.sensor_controllers);
+        for controller in controllers {
+            *controller = Controller {
+                effect: self.effect.clone(),
+                data_source: None,
+                ..controller.clone()
+            };
+        }
+
+        true
+    }
+}
@@ -0,0 +1,159 @@
+use std::fmt::Write as _;
+
+use crate::device::DeviceHealth;
+
+use super::{Channel, SensorReadings, Statistics, Window};
+
+const PREFIX: &str = "high_flow_next";
+
+/// Encodes the passed `readings` and `statistics` in the
+/// [OpenMetrics](https://openmetrics.io) text format.
+///
+/// Every [`Channel`] is exported as gauge `high_flow_next_<channel>` with the
+/// capture time of the readings as timestamp. The statistics of each channel
+/// are exported as summary `high_flow_next_<channel>_window` (percentiles,
+/// count and sum) and the gauges `high_flow_next_<channel>_window_min` /
+/// `_max`, labeled with the window they were calculated for (e.g. `5m`, `1h`
+/// or `boot`). External values are exported as `high_flow_next_external`
+/// labeled with their name.
+///
+/// The returned string is terminated by `# EOF` and can be served as is with
+/// the content type `application/openmetrics-text; version=1.0.0`.
+#[must_use]
+pub fn encode_openmetrics(readings: &SensorReadings, statistics: &Statistics) -> String {
+    let mut out = String::new();
+    write_readings(&mut out, readings, statistics);
+    out.push_str("# EOF\n");
+
+    out
+}
+
+/// Encodes the passed `readings` and `statistics` like
+/// [`encode_openmetrics`], and additionally exports the passed device
+/// `health` (see [`DeviceHealth::write_metrics`]).
+#[must_use]
+pub fn encode_openmetrics_with_health(
+    readings: &SensorReadings,
+    statistics: &Statistics,
+    health: &DeviceHealth,
+) -> String {
+    let mut out = String::new();
+    write_readings(&mut out, readings, statistics);
+    health.write_metrics(&mut out, PREFIX);
+    out.push_str("# EOF\n");
+
+    out
+}
+
+fn write_readings(out: &mut String, readings: &SensorReadings, statistics: &Statistics) {
+    let timestamp = readings.timestamp().unix_seconds();
+
+    for channel in Channel::ALL {
+        let name = format!("{PREFIX}_{}", channel.name());
+
+        if let Some(value) = readings.value(channel) {
+            let _ = writeln!(out, "# TYPE {name} gauge");
+            let _ = writeln!(
+                out,
+                "# HELP {name} Current {} in {}.",
+                description(channel),
+                channel.unit()
+            );
+            let _ = writeln!(out, "{name} {value} {timestamp:.3}");
+        }
+
+        let windows = statistics
+            .windows()
+            .filter_map(|window| Some((window, statistics.get(window, channel)?)))
+            .collect::<Vec<_>>();
+        if windows.is_empty() {
+            continue;
+        }
+
+        let _ = writeln!(out, "# TYPE {name}_window summary");
+        let _ = writeln!(
+            out,
+            "# HELP {name}_window Statistics of the {} in {}.",
+            description(channel),
+            channel.unit()
+        );
+        for (window, stats) in &windows {
+            let window = window_label(*window);
+            let quantiles = [(0.5, stats.p50), (0.9, stats.p90), (0.99, stats.p99)];
+
+            for (quantile, value) in quantiles {
+                if let Some(value) = value {
+                    let _ = writeln!(
+                        out,
+                        "{name}_window{{window=\"{window}\",quantile=\"{quantile}\"}} {value}"
+                    );
+                }
+            }
+
+            #[allow(clippy::cast_precision_loss)]
+            let sum = stats.mean * stats.count as f64;
+            let _ = writeln!(
+                out,
+                "{name}_window_count{{window=\"{window}\"}} {}",
+                stats.count
+            );
+            let _ = writeln!(out, "{name}_window_sum{{window=\"{window}\"}} {sum}");
+        }
+
+        for (suffix, help) in [("min", "Minimum"), ("max", "Maximum")] {
+            let _ = writeln!(out, "# TYPE {name}_window_{suffix} gauge");
+            let _ = writeln!(
+                out,
+                "# HELP {name}_window_{suffix} {help} {} in {}.",
+                description(channel),
+                channel.unit()
+            );
+            for (window, stats) in &windows {
+                let value = if suffix == "min" {
+                    stats.min
+                } else {
+                    stats.max
+                };
+                let _ = writeln!(
+                    out,
+                    "{name}_window_{suffix}{{window=\"{}\"}} {value}",
+                    window_label(*window)
+                );
+            }
+        }
+    }
+
+    if !readings.external.is_empty() {
+        let _ = writeln!(out, "# TYPE {PREFIX}_external gauge");
+        let _ = writeln!(out, "# HELP {PREFIX}_external Values of external sensors.");
+        for (name, value) in &readings.external {
+            let _ = writeln!(
+                out,
+                "{PREFIX}_external{{name=\"{}\"}} {value} {timestamp:.3}",
+                escape(name)
+            );
+        }
+    }
+}
+
+fn description(channel: Channel) -> String {
+    channel.name().replace('_', " ")
+}
+
+fn window_label(window: Window) -> String {
+    match window {
+        Window::Boot => "boot".into(),
+        Window::Duration(duration) => match duration.as_secs() {
+            secs if secs != 0 && secs % 3600 == 0 => format!("{}h", secs / 3600),
+            secs if secs != 0 && secs % 60 == 0 => format!("{}m", secs / 60),
+            secs => format!("{secs}s"),
+        },
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,65 @@
+//! Host-side monitoring of the sensor values of the device.
+//!
+//! This module defines the data model for the current sensor values
+//! ([`SensorReadings`]) that is shared by all monitoring components, and the
+//! components that consume them.
+
+mod alarm;
+mod breaker;
+mod brightness;
+mod calibration;
+mod detector;
+mod drift;
+mod filter;
+mod history;
+mod lighting_override;
+mod metrics;
+mod notify;
+mod preview;
+mod readings;
+mod resample;
+mod scheduler;
+mod sink;
+mod source;
+mod standby;
+mod statistics;
+mod timestamp;
+mod totalizer;
+mod watchdog;
+mod watcher;
+
+#[cfg(feature = "protobuf")]
+pub mod proto;
+
+pub use self::alarm::{AlarmEngine, AlarmEvent, AlarmId, AlarmRule, Comparison};
+pub use self::breaker::{CircuitBreaker, SinkEvent};
+pub use self::brightness::{BrightnessLevel, BrightnessScheduler};
+pub use self::calibration::{Calibration, CalibrationSession};
+pub use self::detector::{
+    Advisory, AdvisoryKind, ConductivitySpikeDetector, Confidence, Detector, FlowDropDetector,
+    FlowTrendDetector,
+};
+pub use self::drift::{DriftAnalyzer, DriftState};
+pub use self::filter::{
+    AttenuationFilter, DeadbandFilter, ExponentialFilter, Filter, TimedExponentialFilter,
+};
+pub use self::history::History;
+pub use self::lighting_override::AlarmOverride;
+pub use self::metrics::{encode_openmetrics, encode_openmetrics_with_health};
+#[cfg(feature = "desktop-notifications")]
+pub use self::notify::DesktopNotifier;
+pub use self::notify::{EmailNotifier, Notification, Notifier, WebhookNotifier};
+pub use self::preview::{DisplayPreview, Framebuffer, Page, DISPLAY_HEIGHT, DISPLAY_WIDTH};
+pub use self::readings::{Channel, SensorReadings, SOFTWARE_SENSOR_SOURCE};
+pub use self::resample::{Interpolation, Resampled, Resampler};
+pub use self::scheduler::{Scheduler, TaskFailure, TaskId};
+pub use self::sink::{Batched, CsvSink, ErrorPolicy, PrometheusSink, Publisher, Sink};
+pub use self::source::{FnSource, Merger, ReadingSource};
+pub use self::standby::{
+    PowerStateSource, StandbyEvent, StandbyMirror, SysfsPowerState, UsbPowerState,
+};
+pub use self::statistics::{ChannelStatistics, Statistics, Window, WindowSnapshot};
+pub use self::timestamp::Timestamp;
+pub use self::totalizer::{Totalizer, TotalizerState};
+pub use self::watchdog::{Watchdog, WatchdogEvent};
+pub use self::watcher::{Condition, WatchEvent, WatchId, Watcher};
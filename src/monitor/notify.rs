@@ -0,0 +1,501 @@
+use std::fmt::Write as _;
+use std::io::{Error as IoError, ErrorKind, Read, Write};
+use std::iter::once;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::{Duration, SystemTime};
+
+use super::{AlarmEngine, AlarmEvent, AlarmId, Channel, Comparison, Timestamp};
+
+/// Notification about an alarm raised or cleared by the [`AlarmEngine`].
+///
+/// Combines the [`AlarmEvent`] with the [`AlarmRule`](super::AlarmRule) that
+/// emitted it, so a [`Notifier`] can render a human readable message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    /// Rule that emitted the event.
+    pub id: AlarmId,
+
+    /// `true` if the alarm was raised, `false` if it was cleared.
+    pub raised: bool,
+
+    /// Channel the rule is checked against.
+    pub channel: Channel,
+
+    /// How the rule compares the value with the `threshold`.
+    pub comparison: Comparison,
+
+    /// Threshold of the rule in the physical unit of the `channel`.
+    pub threshold: f64,
+
+    /// Value that raised or cleared the alarm.
+    pub value: f64,
+
+    /// Point in time the alarm was raised or cleared at.
+    pub at: SystemTime,
+}
+
+impl Notification {
+    /// Returns the short summary of the notification (e.g. used as subject
+    /// or title).
+    #[must_use]
+    pub fn title(&self) -> String {
+        let state = if self.raised { "raised" } else { "cleared" };
+
+        format!("High Flow Next: {} alarm {state}", self.channel.name())
+    }
+
+    /// Returns the human readable description of the notification.
+    #[must_use]
+    pub fn message(&self) -> String {
+        let comparison = match self.comparison {
+            Comparison::Above => "above",
+            Comparison::Below => "below",
+        };
+        let unit = self.channel.unit();
+
+        if self.raised {
+            format!(
+                "The {} is {} {unit} ({comparison} the limit of {} {unit}).",
+                self.channel.name().replace('_', " "),
+                self.value,
+                self.threshold,
+            )
+        } else {
+            format!(
+                "The {} is back to {} {unit} (limit {} {unit}).",
+                self.channel.name().replace('_', " "),
+                self.value,
+                self.threshold,
+            )
+        }
+    }
+
+    /// Returns the notification as JSON object.
+    ///
+    /// ```json
+    /// {"alarm":0,"state":"raised","channel":"flow","comparison":"below",
+    ///  "threshold":50,"value":12.5,"timestamp":1700000000.0,
+    ///  "title":"...","message":"..."}
+    /// ```
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+
+        let _ = write!(
+            json,
+            "{{\"alarm\":{},\"state\":\"{}\",\"channel\":\"{}\",\"comparison\":\"{}\",\"threshold\":{},\"value\":{},\"timestamp\":{:.3},\"title\":\"{}\",\"message\":\"{}\"}}",
+            self.id.0,
+            if self.raised { "raised" } else { "cleared" },
+            self.channel.name(),
+            match self.comparison {
+                Comparison::Above => "above",
+                Comparison::Below => "below",
+            },
+            number(self.threshold),
+            number(self.value),
+            Timestamp::from(self.at).unix_seconds(),
+            escape(&self.title()),
+            escape(&self.message()),
+        );
+
+        json
+    }
+}
+
+impl AlarmEngine {
+    /// Returns the [`Notification`] for the passed `event`, or `None` if the
+    /// event was not emitted by a rule of this engine.
+    #[must_use]
+    pub fn notification(&self, event: &AlarmEvent) -> Option<Notification> {
+        let rule = self.rule(event.id())?;
+        let (raised, value, at) = match *event {
+            AlarmEvent::Raised { value, at, .. } => (true, value, at),
+            AlarmEvent::Cleared { value, at, .. } => (false, value, at),
+        };
+
+        Some(Notification {
+            id: event.id(),
+            raised,
+            channel: rule.source,
+            comparison: rule.comparison,
+            threshold: rule.threshold,
+            value,
+            at,
+        })
+    }
+}
+
+/// Destination the [`Notification`]s of the alarms are sent to.
+pub trait Notifier {
+    /// Sends the passed `notification`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the notification could not be sent.
+    fn notify(&mut self, notification: &Notification) -> Result<(), IoError>;
+}
+
+/// Sends the notifications to all notifiers of the list and returns the
+/// first error after all notifiers were called.
+impl Notifier for Vec<Box<dyn Notifier>> {
+    fn notify(&mut self, notification: &Notification) -> Result<(), IoError> {
+        let mut result = Ok(());
+
+        for notifier in self.iter_mut() {
+            if let Err(error) = notifier.notify(notification) {
+                if result.is_ok() {
+                    result = Err(error);
+                }
+            }
+        }
+
+        result
+    }
+}
+
+/// Notifier that posts the [`JSON`](Notification::to_json) of the
+/// notifications to a webhook.
+///
+/// Only plain `http://` URLs are supported. To post to an HTTPS endpoint
+/// (e.g. a chat service) use a local relay or reverse proxy.
+///
+/// # Example
+///
+/// ```rust
+/// use high_flow_next::monitor::WebhookNotifier;
+///
+/// let notifier = WebhookNotifier::new("http://localhost:8080/hooks/coolant")
+///     .unwrap()
+///     .with_header("Authorization", "Bearer secret")
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct WebhookNotifier {
+    host: String,
+    port: u16,
+    path: String,
+    headers: Vec<(String, String)>,
+    timeout: Duration,
+}
+
+impl WebhookNotifier {
+    /// Creates a new notifier that posts to the passed `url`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`ErrorKind::InvalidInput`] if the URL is not
+    /// a valid `http://` URL, or if the host or the path contain whitespace
+    /// or control characters, which would allow to inject additional headers
+    /// or requests.
+    pub fn new(url: &str) -> Result<Self, IoError> {
+        let invalid = || IoError::new(ErrorKind::InvalidInput, format!("Invalid URL: {url}"));
+
+        let rest = url.strip_prefix("http://").ok_or_else(invalid)?;
+        let (authority, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => {
+                (host, port.parse().map_err(|_| invalid())?)
+            }
+            _ => (authority, 80),
+        };
+        let is_invalid = |c: char| c.is_whitespace() || c.is_control();
+        if host.is_empty() || host.contains(is_invalid) || path.contains(is_invalid) {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            host: host.into(),
+            port,
+            path: path.into(),
+            headers: Vec::new(),
+            timeout: Duration::from_secs(10),
+        })
+    }
+
+    /// Adds a header that is sent with every request (e.g. for
+    /// authentication) and returns the updated notifier.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`ErrorKind::InvalidInput`] if the name is
+    /// empty or contains a colon, or if the name or the value contain a line
+    /// break, which would allow to inject additional headers.
+    pub fn with_header<N, V>(mut self, name: N, value: V) -> Result<Self, IoError>
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        let name = name.into();
+        let value = value.into();
+
+        if name.is_empty() || name.contains(':') || name.contains(is_line_break) {
+            return Err(IoError::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid header name: {name:?}"),
+            ));
+        }
+        if value.contains(is_line_break) {
+            return Err(IoError::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid value of header {name}"),
+            ));
+        }
+
+        self.headers.push((name, value));
+
+        Ok(self)
+    }
+
+    /// Sets the timeout for connecting, sending and receiving (default: 10
+    /// seconds) and returns the updated notifier.
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+
+        self
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn notify(&mut self, notification: &Notification) -> Result<(), IoError> {
+        let host = self.host.trim_start_matches('[').trim_end_matches(']');
+        let addr = (host, self.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| IoError::new(ErrorKind::NotFound, "Unable to resolve host"))?;
+
+        let mut stream = TcpStream::connect_timeout(&addr, self.timeout)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        let body = notification.to_json();
+        let mut request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+            self.path,
+            self.host,
+            self.port,
+            body.len(),
+        );
+        for (name, value) in &self.headers {
+            let _ = write!(request, "{name}: {value}\r\n");
+        }
+        request.push_str("\r\n");
+        request.push_str(&body);
+
+        stream.write_all(request.as_bytes())?;
+        stream.flush()?;
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response)?;
+
+        let status = String::from_utf8_lossy(&response)
+            .lines()
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|status| status.parse::<u16>().ok())
+            .ok_or_else(|| IoError::new(ErrorKind::InvalidData, "Invalid HTTP response"))?;
+        if !(200..300).contains(&status) {
+            return Err(IoError::other(format!("Webhook returned status {status}")));
+        }
+
+        Ok(())
+    }
+}
+
+/// Notifier that sends the notifications as email using a `sendmail`
+/// compatible program (e.g. `sendmail`, `msmtp` or `ssmtp`).
+///
+/// The message is passed to the standard input of the program, which is
+/// called with the `-t` flag to read the recipients from the headers.
+#[derive(Debug, Clone)]
+pub struct EmailNotifier {
+    program: String,
+    from: String,
+    to: Vec<String>,
+}
+
+impl EmailNotifier {
+    /// Creates a new notifier that sends the notifications from the `from`
+    /// address to the `to` addresses using `sendmail`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`ErrorKind::InvalidInput`] if one of the
+    /// addresses contains a line break, which would allow to inject
+    /// additional recipients or headers.
+    pub fn new<F, I, T>(from: F, to: I) -> Result<Self, IoError>
+    where
+        F: Into<String>,
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        let from = from.into();
+        let to = to.into_iter().map(Into::into).collect::<Vec<String>>();
+
+        if let Some(address) = once(&from).chain(&to).find(|x| x.contains(is_line_break)) {
+            return Err(IoError::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid email address: {address:?}"),
+            ));
+        }
+
+        Ok(Self {
+            program: "sendmail".into(),
+            from,
+            to,
+        })
+    }
+
+    /// Sets the `sendmail` compatible program that is used to send the
+    /// emails and returns the updated notifier.
+    #[must_use]
+    pub fn with_program<P: Into<String>>(mut self, program: P) -> Self {
+        self.program = program.into();
+
+        self
+    }
+
+    /// Returns the email for the passed `notification` in the format that
+    /// is passed to the program.
+    #[must_use]
+    pub fn message(&self, notification: &Notification) -> String {
+        format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}\r\n",
+            self.from,
+            self.to.join(", "),
+            notification.title(),
+            notification.message(),
+        )
+    }
+}
+
+impl Notifier for EmailNotifier {
+    fn notify(&mut self, notification: &Notification) -> Result<(), IoError> {
+        let message = self.message(notification);
+
+        let mut child = Command::new(&self.program)
+            .arg("-t")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()?;
+
+        // Wait for the program even if writing the message failed, so it
+        // does not remain as zombie process.
+        let written = match child.stdin.take() {
+            Some(mut stdin) => stdin.write_all(message.as_bytes()),
+            None => Ok(()),
+        };
+        let status = child.wait()?;
+        written?;
+
+        check_status(&self.program, status)
+    }
+}
+
+/// Notifier that shows the notifications on the desktop of the current user.
+///
+/// Uses `notify-send` on Linux and `osascript` on macOS. Only available with
+/// the `desktop-notifications` feature.
+#[cfg(feature = "desktop-notifications")]
+#[derive(Default, Debug, Clone)]
+pub struct DesktopNotifier;
+
+#[cfg(feature = "desktop-notifications")]
+impl DesktopNotifier {
+    /// Creates a new desktop notifier.
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[cfg(feature = "desktop-notifications")]
+impl Notifier for DesktopNotifier {
+    fn notify(&mut self, notification: &Notification) -> Result<(), IoError> {
+        let title = notification.title();
+        let message = notification.message();
+
+        let (program, status) = if cfg!(target_os = "macos") {
+            // The texts are passed as arguments of the script, so they do
+            // not need to be escaped.
+            (
+                "osascript",
+                Command::new("osascript")
+                    .args(["-e", "on run argv"])
+                    .args([
+                        "-e",
+                        "display notification (item 1 of argv) with title (item 2 of argv)",
+                    ])
+                    .args(["-e", "end run"])
+                    .arg(message)
+                    .arg(title)
+                    .status()?,
+            )
+        } else if cfg!(unix) {
+            let urgency = if notification.raised {
+                "critical"
+            } else {
+                "normal"
+            };
+
+            (
+                "notify-send",
+                Command::new("notify-send")
+                    .arg("--urgency")
+                    .arg(urgency)
+                    .arg(title)
+                    .arg(message)
+                    .status()?,
+            )
+        } else {
+            return Err(IoError::new(
+                ErrorKind::Unsupported,
+                "Desktop notifications are not supported on this platform",
+            ));
+        };
+
+        check_status(program, status)
+    }
+}
+
+fn is_line_break(c: char) -> bool {
+    c == '\r' || c == '\n'
+}
+
+fn check_status(program: &str, status: ExitStatus) -> Result<(), IoError> {
+    if status.success() {
+        Ok(())
+    } else {
+        Err(IoError::other(format!("{program} failed: {status}")))
+    }
+}
+
+fn number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".into()
+    }
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
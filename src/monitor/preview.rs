@@ -0,0 +1,592 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::time::Duration;
+
+use crate::protocol::settings::{
+    ChartSource, DisplayFlags, DisplaySettings, FlowUnit, PageFlags, TemperatureUnit,
+};
+
+use super::{Channel, History, SensorReadings};
+
+/// Width of the display of the device in pixels.
+pub const DISPLAY_WIDTH: usize = 128;
+
+/// Height of the display of the device in pixels.
+pub const DISPLAY_HEIGHT: usize = 64;
+
+/// Liters per US gallon.
+const LITERS_PER_GALLON: f64 = 3.785_411_784;
+
+/// Width of a glyph of the font in pixels (without spacing).
+const GLYPH_WIDTH: usize = 5;
+
+/// Height of a glyph of the font in pixels.
+const GLYPH_HEIGHT: usize = 7;
+
+/// A page shown on the display of the device.
+///
+/// Each page corresponds to one of the [`PageFlags`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Page {
+    /// Device logo page ([`PageFlags::DEVICE_INFO`]).
+    DeviceInfo,
+    /// Water flow page ([`PageFlags::FLOW`]).
+    Flow,
+    /// Water temperature page ([`PageFlags::WATER_TEMP`]).
+    WaterTemperature,
+    /// External temperature page ([`PageFlags::EXTERNAL_TEMP`]).
+    ExternalTemperature,
+    /// Conductivity page ([`PageFlags::CONDUCTIVITY`]).
+    Conductivity,
+    /// Water quality page ([`PageFlags::WATER_QUALITY`]).
+    WaterQuality,
+    /// Volume counter page ([`PageFlags::VOLUME_COUNT`]).
+    Volume,
+    /// Power consumption page ([`PageFlags::POWER_SENSOR`]).
+    Power,
+    /// Flow / water temperature page ([`PageFlags::FLOW_WATERTEMP`]).
+    FlowWaterTemperature,
+    /// Conductivity / water quality page ([`PageFlags::COND_QUALITY`]).
+    ConductivityWaterQuality,
+    /// Water temperature / external temperature page ([`PageFlags::TEMPERATURES`]).
+    Temperatures,
+    /// Flow / volume page ([`PageFlags::FLOW_VOLUME`]).
+    FlowVolume,
+    /// Chart page with the index of the chart in [`DisplaySettings::charts`]
+    /// ([`PageFlags::CHART1`] to [`PageFlags::CHART4`]).
+    Chart(usize),
+}
+
+impl Page {
+    /// All pages, in the order they are shown by the device.
+    pub const ALL: [Self; 16] = [
+        Self::DeviceInfo,
+        Self::Flow,
+        Self::WaterTemperature,
+        Self::ExternalTemperature,
+        Self::Conductivity,
+        Self::WaterQuality,
+        Self::Volume,
+        Self::Power,
+        Self::FlowWaterTemperature,
+        Self::ConductivityWaterQuality,
+        Self::Temperatures,
+        Self::FlowVolume,
+        Self::Chart(0),
+        Self::Chart(1),
+        Self::Chart(2),
+        Self::Chart(3),
+    ];
+
+    /// Returns the flag that enables the page.
+    #[must_use]
+    pub fn flag(&self) -> PageFlags {
+        match self {
+            Self::DeviceInfo => PageFlags::DEVICE_INFO,
+            Self::Flow => PageFlags::FLOW,
+            Self::WaterTemperature => PageFlags::WATER_TEMP,
+            Self::ExternalTemperature => PageFlags::EXTERNAL_TEMP,
+            Self::Conductivity => PageFlags::CONDUCTIVITY,
+            Self::WaterQuality => PageFlags::WATER_QUALITY,
+            Self::Volume => PageFlags::VOLUME_COUNT,
+            Self::Power => PageFlags::POWER_SENSOR,
+            Self::FlowWaterTemperature => PageFlags::FLOW_WATERTEMP,
+            Self::ConductivityWaterQuality => PageFlags::COND_QUALITY,
+            Self::Temperatures => PageFlags::TEMPERATURES,
+            Self::FlowVolume => PageFlags::FLOW_VOLUME,
+            Self::Chart(0) => PageFlags::CHART1,
+            Self::Chart(1) => PageFlags::CHART2,
+            Self::Chart(2) => PageFlags::CHART3,
+            Self::Chart(3) => PageFlags::CHART4,
+            Self::Chart(_) => PageFlags::empty(),
+        }
+    }
+}
+
+/// Monochrome framebuffer with the rendered content of a display page.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<bool>,
+}
+
+impl Framebuffer {
+    /// Creates a new framebuffer with all pixels turned off.
+    #[must_use]
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![false; width * height],
+        }
+    }
+
+    /// Returns the width of the framebuffer in pixels.
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the framebuffer in pixels.
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns `true` if the pixel at the passed position is turned on.
+    ///
+    /// Pixels outside of the framebuffer are turned off.
+    #[must_use]
+    pub fn pixel(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.pixels[y * self.width + x]
+    }
+
+    /// Turns the pixel at the passed position on or off.
+    ///
+    /// Pixels outside of the framebuffer are ignored.
+    pub fn set_pixel(&mut self, x: usize, y: usize, on: bool) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = on;
+        }
+    }
+
+    /// Returns the pixels row by row, starting at the top left corner.
+    #[must_use]
+    pub fn pixels(&self) -> &[bool] {
+        &self.pixels
+    }
+
+    /// Returns the number of pixels that are turned on.
+    #[must_use]
+    pub fn lit_pixels(&self) -> usize {
+        self.pixels.iter().filter(|x| **x).count()
+    }
+
+    /// Encodes the framebuffer as binary portable bitmap (PBM, `P4`), which
+    /// can be opened by most image viewers and converted using common tools.
+    #[must_use]
+    pub fn to_pbm(&self) -> Vec<u8> {
+        let mut data = format!("P4\n{} {}\n", self.width, self.height).into_bytes();
+
+        for row in self.pixels.chunks(self.width.max(1)) {
+            for bits in row.chunks(8) {
+                let byte = bits
+                    .iter()
+                    .enumerate()
+                    .fold(0_u8, |byte, (i, on)| byte | (u8::from(*on) << (7 - i)));
+
+                data.push(byte);
+            }
+        }
+
+        data
+    }
+
+    fn invert(&mut self) {
+        for pixel in &mut self.pixels {
+            *pixel = !*pixel;
+        }
+    }
+
+    fn rotate(&mut self) {
+        self.pixels.reverse();
+    }
+
+    fn hline(&mut self, x: usize, y: usize, len: usize) {
+        for x in x..x + len {
+            self.set_pixel(x, y, true);
+        }
+    }
+
+    fn vline(&mut self, x: usize, y: usize, len: usize) {
+        for y in y..y + len {
+            self.set_pixel(x, y, true);
+        }
+    }
+
+    fn text(&mut self, x: usize, y: usize, scale: usize, text: &str) {
+        for (i, c) in text.chars().enumerate() {
+            let glyph = glyph(c);
+            let left = x + i * (GLYPH_WIDTH + 1) * scale;
+
+            for (col, bits) in glyph.iter().enumerate() {
+                for row in 0..GLYPH_HEIGHT {
+                    if bits & (1 << row) == 0 {
+                        continue;
+                    }
+
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            self.set_pixel(left + col * scale + dx, y + row * scale + dy, true);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn text_centered(&mut self, y: usize, scale: usize, text: &str) {
+        let width = text_width(text, scale);
+        let x = self.width.saturating_sub(width) / 2;
+
+        self.text(x, y, scale, text);
+    }
+
+    fn single(&mut self, title: &str, value: &Value) {
+        let text = value.text();
+        let scale = if text_width(&text, 3) <= self.width {
+            3
+        } else {
+            2
+        };
+
+        self.text_centered(2, 1, title);
+        self.hline(0, 11, self.width);
+        self.text_centered(18, scale, &text);
+        self.text_centered(50, 1, value.unit);
+    }
+
+    fn double(&mut self, top: (&str, Value), bottom: (&str, Value)) {
+        for (y, (label, value)) in [(4, top), (36, bottom)] {
+            let text = format!("{} {}", value.text(), value.unit);
+
+            self.text(2, y, 1, label);
+            self.text_centered(y + 10, 2, &text);
+        }
+
+        self.hline(0, 31, self.width);
+    }
+}
+
+/// Renders the framebuffer as text, using `#` for pixels that are turned on
+/// and `.` for pixels that are turned off.
+impl Display for Framebuffer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for row in self.pixels.chunks(self.width.max(1)) {
+            for on in row {
+                f.write_str(if *on { "#" } else { "." })?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Renders an approximation of the display pages of the device.
+///
+/// The preview is driven by the [`DisplaySettings`] (enabled pages, units,
+/// charts, rotation and inversion) and the values passed to the preview.
+/// The layout and the font do not match the firmware pixel by pixel, but
+/// show which values are displayed on which page and how they are
+/// formatted. Values that are not available are shown as `---`.
+///
+/// [`DisplayFlags::AUTO_INVERT`] periodically inverts the display of the
+/// device and is ignored by the preview.
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayPreview<'a> {
+    settings: &'a DisplaySettings,
+    readings: Option<&'a SensorReadings>,
+    history: Option<&'a History>,
+    volume: Option<f64>,
+}
+
+impl<'a> DisplayPreview<'a> {
+    /// Creates a new preview for the passed `settings` without any values.
+    #[must_use]
+    pub fn new(settings: &'a DisplaySettings) -> Self {
+        Self {
+            settings,
+            readings: None,
+            history: None,
+            volume: None,
+        }
+    }
+
+    /// Sets the readings that are displayed on the value pages.
+    #[must_use]
+    pub fn with_readings(mut self, readings: &'a SensorReadings) -> Self {
+        self.readings = Some(readings);
+
+        self
+    }
+
+    /// Sets the history that is displayed on the chart pages.
+    ///
+    /// If no readings were set, the latest readings of the history are used
+    /// for the value pages.
+    #[must_use]
+    pub fn with_history(mut self, history: &'a History) -> Self {
+        self.history = Some(history);
+
+        self
+    }
+
+    /// Sets the value of the volume counter in liters (l).
+    #[must_use]
+    pub fn with_volume(mut self, volume: f64) -> Self {
+        self.volume = Some(volume);
+
+        self
+    }
+
+    /// Returns the pages enabled in the settings, in the order they are shown
+    /// by the device.
+    #[must_use]
+    pub fn pages(&self) -> Vec<Page> {
+        Page::ALL
+            .into_iter()
+            .filter(|page| self.settings.page_flags.contains(page.flag()))
+            .collect()
+    }
+
+    /// Renders all enabled pages (see [`pages`](Self::pages)).
+    #[must_use]
+    pub fn render_all(&self) -> Vec<(Page, Framebuffer)> {
+        self.pages()
+            .into_iter()
+            .map(|page| (page, self.render(page)))
+            .collect()
+    }
+
+    /// Renders the passed `page`, even if it is not enabled in the settings.
+    #[must_use]
+    pub fn render(&self, page: Page) -> Framebuffer {
+        let mut fb = Framebuffer::new(DISPLAY_WIDTH, DISPLAY_HEIGHT);
+
+        match page {
+            Page::DeviceInfo => {
+                fb.text_centered(18, 2, "HIGH FLOW");
+                fb.text_centered(38, 2, "NEXT");
+            }
+            Page::Flow => fb.single("FLOW", &self.flow()),
+            Page::WaterTemperature => {
+                fb.single("WATER TEMP", &self.temperature(Channel::WaterTemperature));
+            }
+            Page::ExternalTemperature => fb.single(
+                "EXTERNAL TEMP",
+                &self.temperature(Channel::ExternalTemperature),
+            ),
+            Page::Conductivity => fb.single("CONDUCTIVITY", &self.conductivity()),
+            Page::WaterQuality => fb.single("WATER QUALITY", &self.water_quality()),
+            Page::Volume => fb.single("VOLUME", &self.volume()),
+            Page::Power => fb.single("POWER", &self.power()),
+            Page::FlowWaterTemperature => fb.double(
+                ("FLOW", self.flow()),
+                ("WATER", self.temperature(Channel::WaterTemperature)),
+            ),
+            Page::ConductivityWaterQuality => fb.double(
+                ("COND.", self.conductivity()),
+                ("QUALITY", self.water_quality()),
+            ),
+            Page::Temperatures => fb.double(
+                ("WATER", self.temperature(Channel::WaterTemperature)),
+                ("EXTERNAL", self.temperature(Channel::ExternalTemperature)),
+            ),
+            Page::FlowVolume => {
+                fb.double(("FLOW", self.flow()), ("VOLUME", self.volume()));
+            }
+            Page::Chart(index) => self.chart(&mut fb, index),
+        }
+
+        if self.settings.display_flags.contains(DisplayFlags::INVERT) {
+            fb.invert();
+        }
+        if self.settings.display_flags.contains(DisplayFlags::ROTATE) {
+            fb.rotate();
+        }
+
+        fb
+    }
+
+    fn readings(&self) -> Option<&SensorReadings> {
+        self.readings
+            .or_else(|| self.history.and_then(History::latest))
+    }
+
+    fn value(&self, channel: Channel) -> Option<f64> {
+        self.readings()?.value(channel)
+    }
+
+    fn flow(&self) -> Value {
+        match self.settings.flow_unit {
+            FlowUnit::Liter => Value::new(self.value(Channel::Flow), 1, "L/H"),
+            FlowUnit::Gallons => Value::new(
+                self.value(Channel::Flow).map(|x| x / LITERS_PER_GALLON),
+                1,
+                "GAL/H",
+            ),
+        }
+    }
+
+    fn temperature(&self, channel: Channel) -> Value {
+        let value = self.value(channel);
+
+        match self.settings.temperature_unit {
+            TemperatureUnit::C => Value::new(value, 1, "°C"),
+            TemperatureUnit::F => Value::new(
+                value.map(|x| TemperatureUnit::C.convert(x, TemperatureUnit::F)),
+                1,
+                "°F",
+            ),
+        }
+    }
+
+    fn conductivity(&self) -> Value {
+        Value::new(self.value(Channel::Conductivity), 0, "US/CM")
+    }
+
+    fn water_quality(&self) -> Value {
+        Value::new(self.value(Channel::WaterQuality), 0, "%")
+    }
+
+    fn power(&self) -> Value {
+        Value::new(self.value(Channel::Power), 1, "W")
+    }
+
+    fn volume(&self) -> Value {
+        match self.settings.flow_unit {
+            FlowUnit::Liter => Value::new(self.volume, 1, "L"),
+            FlowUnit::Gallons => Value::new(self.volume.map(|x| x / LITERS_PER_GALLON), 1, "GAL"),
+        }
+    }
+
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn chart(&self, fb: &mut Framebuffer, index: usize) {
+        const TOP: usize = 12;
+        const BOTTOM: usize = DISPLAY_HEIGHT - 2;
+
+        let Some(chart) = self.settings.charts.get(index) else {
+            return;
+        };
+
+        let (title, channel) = match chart.source {
+            ChartSource::Flow => ("FLOW", Channel::Flow),
+            ChartSource::WaterTemp => ("WATER TEMP", Channel::WaterTemperature),
+            ChartSource::ExternalTemp => ("EXTERNAL TEMP", Channel::ExternalTemperature),
+            ChartSource::Conductivity => ("CONDUCTIVITY", Channel::Conductivity),
+            ChartSource::WaterQuality => ("WATER QUALITY", Channel::WaterQuality),
+            ChartSource::PowerConsumption => ("POWER", Channel::Power),
+            ChartSource::SystemVoltage => ("VOLTAGE", Channel::Voltage),
+        };
+
+        fb.text(2, 2, 1, title);
+        fb.vline(0, TOP, BOTTOM - TOP + 1);
+        fb.hline(0, BOTTOM, fb.width());
+
+        let Some(history) = self.history else {
+            return;
+        };
+        let Some(latest) = history.latest() else {
+            return;
+        };
+
+        let width = fb.width() - 2;
+        let to = latest.captured_at + Duration::from_millis(1);
+        let span = Duration::from_millis(100) * u32::from(*chart.interval) * width as u32;
+        let from = to.checked_sub(span).unwrap_or(std::time::UNIX_EPOCH);
+        let points = history.downsample(channel, from, to, width);
+
+        let min = points.iter().map(|x| x.1).fold(f64::INFINITY, f64::min);
+        let max = points.iter().map(|x| x.1).fold(f64::NEG_INFINITY, f64::max);
+        let range = (max - min).max(f64::EPSILON);
+        let height = (BOTTOM - TOP - 1) as f64;
+
+        for (time, value) in points {
+            let offset = time.duration_since(from).unwrap_or_default();
+            let x = 2 + (offset.as_secs_f64() / span.as_secs_f64() * width as f64) as usize;
+            let y = BOTTOM - 1 - ((value - min) / range * height).round() as usize;
+
+            fb.set_pixel(x, y, true);
+        }
+    }
+}
+
+/// Formatted value of a display page.
+struct Value {
+    value: Option<f64>,
+    decimals: usize,
+    unit: &'static str,
+}
+
+impl Value {
+    fn new(value: Option<f64>, decimals: usize, unit: &'static str) -> Self {
+        Self {
+            value,
+            decimals,
+            unit,
+        }
+    }
+
+    fn text(&self) -> String {
+        match self.value {
+            Some(value) => format!("{value:.*}", self.decimals),
+            None => "---".into(),
+        }
+    }
+}
+
+fn text_width(text: &str, scale: usize) -> usize {
+    let len = text.chars().count();
+
+    (len * (GLYPH_WIDTH + 1)).saturating_sub(1) * scale
+}
+
+/// Returns the glyph of the passed character. Each byte is a column of the
+/// glyph, the least significant bit is the top row.
+///
+/// Lower case letters are displayed as upper case letters, unknown
+/// characters as box.
+fn glyph(c: char) -> [u8; GLYPH_WIDTH] {
+    match c.to_ascii_uppercase() {
+        ' ' => [0x00, 0x00, 0x00, 0x00, 0x00],
+        '%' => [0x23, 0x13, 0x08, 0x64, 0x62],
+        '-' => [0x08, 0x08, 0x08, 0x08, 0x08],
+        '.' => [0x00, 0x60, 0x60, 0x00, 0x00],
+        '/' => [0x20, 0x10, 0x08, 0x04, 0x02],
+        ':' => [0x00, 0x36, 0x36, 0x00, 0x00],
+        '°' => [0x00, 0x06, 0x09, 0x09, 0x06],
+        '0' => [0x3E, 0x51, 0x49, 0x45, 0x3E],
+        '1' => [0x00, 0x42, 0x7F, 0x40, 0x00],
+        '2' => [0x42, 0x61, 0x51, 0x49, 0x46],
+        '3' => [0x21, 0x41, 0x45, 0x4B, 0x31],
+        '4' => [0x18, 0x14, 0x12, 0x7F, 0x10],
+        '5' => [0x27, 0x45, 0x45, 0x45, 0x39],
+        '6' => [0x3C, 0x4A, 0x49, 0x49, 0x30],
+        '7' => [0x01, 0x71, 0x09, 0x05, 0x03],
+        '8' => [0x36, 0x49, 0x49, 0x49, 0x36],
+        '9' => [0x06, 0x49, 0x49, 0x29, 0x1E],
+        'A' => [0x7E, 0x11, 0x11, 0x11, 0x7E],
+        'B' => [0x7F, 0x49, 0x49, 0x49, 0x36],
+        'C' => [0x3E, 0x41, 0x41, 0x41, 0x22],
+        'D' => [0x7F, 0x41, 0x41, 0x22, 0x1C],
+        'E' => [0x7F, 0x49, 0x49, 0x49, 0x41],
+        'F' => [0x7F, 0x09, 0x09, 0x09, 0x01],
+        'G' => [0x3E, 0x41, 0x49, 0x49, 0x7A],
+        'H' => [0x7F, 0x08, 0x08, 0x08, 0x7F],
+        'I' => [0x00, 0x41, 0x7F, 0x41, 0x00],
+        'J' => [0x20, 0x40, 0x41, 0x3F, 0x01],
+        'K' => [0x7F, 0x08, 0x14, 0x22, 0x41],
+        'L' => [0x7F, 0x40, 0x40, 0x40, 0x40],
+        'M' => [0x7F, 0x02, 0x0C, 0x02, 0x7F],
+        'N' => [0x7F, 0x04, 0x08, 0x10, 0x7F],
+        'O' => [0x3E, 0x41, 0x41, 0x41, 0x3E],
+        'P' => [0x7F, 0x09, 0x09, 0x09, 0x06],
+        'Q' => [0x3E, 0x41, 0x51, 0x21, 0x5E],
+        'R' => [0x7F, 0x09, 0x19, 0x29, 0x46],
+        'S' => [0x46, 0x49, 0x49, 0x49, 0x31],
+        'T' => [0x01, 0x01, 0x7F, 0x01, 0x01],
+        'U' => [0x3F, 0x40, 0x40, 0x40, 0x3F],
+        'V' => [0x1F, 0x20, 0x40, 0x20, 0x1F],
+        'W' => [0x3F, 0x40, 0x38, 0x40, 0x3F],
+        'X' => [0x63, 0x14, 0x08, 0x14, 0x63],
+        'Y' => [0x07, 0x08, 0x70, 0x08, 0x07],
+        'Z' => [0x61, 0x51, 0x49, 0x45, 0x43],
+        _ => [0x7F, 0x41, 0x41, 0x41, 0x7F],
+    }
+}
@@ -0,0 +1,131 @@
+//! Protobuf messages for the [`SensorReadings`](super::SensorReadings) and
+//! [`AlarmEvent`](super::AlarmEvent)s.
+//!
+//! The messages are defined in `proto/high_flow_next.proto`, which is shipped
+//! with the crate, so other languages can generate matching types. The Rust
+//! types in this module are the `prost` types for this schema. They are
+//! maintained by hand, so building the crate does not require `protoc`.
+//!
+//! Use [`prost::Message::encode_to_vec`] to serialize the messages.
+
+use std::collections::HashMap;
+
+use super::Timestamp;
+
+/// Sensor values of a high flow NEXT device.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SensorReadings {
+    /// Point in time the readings were captured at (milliseconds since the
+    /// unix epoch).
+    #[prost(int64, tag = "1")]
+    pub captured_at_ms: i64,
+
+    /// Water flow in liter per hour (l/h).
+    #[prost(double, tag = "2")]
+    pub flow: f64,
+
+    /// Water temperature in degree celsius (°C), if available.
+    #[prost(double, optional, tag = "3")]
+    pub water_temperature: Option<f64>,
+
+    /// External temperature in degree celsius (°C), if available.
+    #[prost(double, optional, tag = "4")]
+    pub external_temperature: Option<f64>,
+
+    /// Conductivity in micro siemens per centimeter (µS/cm).
+    #[prost(double, tag = "5")]
+    pub conductivity: f64,
+
+    /// Water quality in percent (%).
+    #[prost(double, tag = "6")]
+    pub water_quality: f64,
+
+    /// Power consumption in watts (W).
+    #[prost(double, tag = "7")]
+    pub power: f64,
+
+    /// System voltage in volts (V).
+    #[prost(double, tag = "8")]
+    pub voltage: f64,
+
+    /// Values of external sensors merged into the readings.
+    #[prost(map = "string, double", tag = "9")]
+    pub external: HashMap<String, f64>,
+}
+
+/// Event emitted by the [`AlarmEngine`](super::AlarmEngine).
+#[derive(Clone, Copy, PartialEq, prost::Message)]
+pub struct AlarmEvent {
+    /// Kind of the event (see [`alarm_event::Kind`]).
+    #[prost(enumeration = "alarm_event::Kind", tag = "1")]
+    pub kind: i32,
+
+    /// Identifier of the rule that emitted the event.
+    #[prost(uint64, tag = "2")]
+    pub alarm_id: u64,
+
+    /// Value that raised or cleared the alarm.
+    #[prost(double, tag = "3")]
+    pub value: f64,
+
+    /// Point in time the event was emitted at (milliseconds since the unix
+    /// epoch).
+    #[prost(int64, tag = "4")]
+    pub at_ms: i64,
+}
+
+/// Nested types of [`AlarmEvent`].
+pub mod alarm_event {
+    /// Kind of an [`AlarmEvent`](super::AlarmEvent).
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Kind {
+        /// The alarm of the rule was raised.
+        Raised = 0,
+
+        /// The alarm of the rule was cleared.
+        Cleared = 1,
+    }
+}
+
+impl From<&super::SensorReadings> for SensorReadings {
+    fn from(readings: &super::SensorReadings) -> Self {
+        use super::Channel;
+
+        Self {
+            captured_at_ms: readings.timestamp().unix_millis(),
+            flow: readings.value(Channel::Flow).unwrap_or_default(),
+            water_temperature: readings.value(Channel::WaterTemperature),
+            external_temperature: readings.value(Channel::ExternalTemperature),
+            conductivity: readings.value(Channel::Conductivity).unwrap_or_default(),
+            water_quality: readings.value(Channel::WaterQuality).unwrap_or_default(),
+            power: readings.power,
+            voltage: readings.voltage,
+            external: readings
+                .external
+                .iter()
+                .map(|(name, value)| (name.clone(), *value))
+                .collect(),
+        }
+    }
+}
+
+impl From<&super::AlarmEvent> for AlarmEvent {
+    fn from(event: &super::AlarmEvent) -> Self {
+        let (kind, id, value, at) = match *event {
+            super::AlarmEvent::Raised { id, value, at } => {
+                (alarm_event::Kind::Raised, id, value, at)
+            }
+            super::AlarmEvent::Cleared { id, value, at } => {
+                (alarm_event::Kind::Cleared, id, value, at)
+            }
+        };
+
+        Self {
+            kind: kind as i32,
+            alarm_id: id.0 as u64,
+            value,
+            at_ms: Timestamp::from(at).unix_millis(),
+        }
+    }
+}
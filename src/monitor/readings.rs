@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+use super::Timestamp;
+use crate::protocol::settings::{
+    Conductivity, DataSource, Flow, Medium, Temperature, WaterQuality,
+};
+
+/// Name of the [`ReadingSource`](super::ReadingSource) that provides the
+/// values of the software sensors (see [`SensorReadings::value_for`]).
+///
+/// The source is expected to return the values named `1` to `8` in the
+/// physical unit of the sensor.
+pub const SOFTWARE_SENSOR_SOURCE: &str = "software_sensor";
+
+/// Current sensor values of a high flow NEXT device.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SensorReadings {
+    /// Point in time the readings were captured at.
+    pub captured_at: SystemTime,
+
+    /// Current water flow.
+    pub flow: Flow,
+
+    /// Current water temperature (`None` if the sensor is not available).
+    pub water_temperature: Option<Temperature>,
+
+    /// Current external temperature (`None` if no sensor is connected).
+    pub external_temperature: Option<Temperature>,
+
+    /// Current conductivity of the coolant.
+    pub conductivity: Conductivity,
+
+    /// Current water quality.
+    pub water_quality: WaterQuality,
+
+    /// Current power consumption in watts (W).
+    pub power: f64,
+
+    /// Current system voltage in volts (V).
+    pub voltage: f64,
+
+    /// Values of external sensors merged into the readings
+    /// (see [`Merger`](super::Merger)).
+    pub external: BTreeMap<String, f64>,
+}
+
+impl SensorReadings {
+    /// Returns the point in time the readings were captured at.
+    #[must_use]
+    pub fn timestamp(&self) -> Timestamp {
+        self.captured_at.into()
+    }
+
+    /// Returns the value of the passed `channel` in its physical unit
+    /// (see [`Channel`] for the units).
+    ///
+    /// Returns `None` if the channel is not available.
+    #[must_use]
+    pub fn value(&self, channel: Channel) -> Option<f64> {
+        match channel {
+            Channel::Flow => Some(f64::from(*self.flow) / 10.0),
+            Channel::WaterTemperature => self.water_temperature.map(|x| f64::from(*x) / 100.0),
+            Channel::ExternalTemperature => {
+                self.external_temperature.map(|x| f64::from(*x) / 100.0)
+            }
+            Channel::Conductivity => Some(f64::from(*self.conductivity)),
+            Channel::WaterQuality => Some(f64::from(*self.water_quality) / 100.0),
+            Channel::Power => Some(self.power),
+            Channel::Voltage => Some(self.voltage),
+        }
+    }
+
+    /// Returns the current value of the passed data `source`.
+    ///
+    /// The value is returned in the raw unit the effects use for the data
+    /// source (see [`DataSource::scale`]), which is also the unit of
+    /// [`SourceControl::input_min`](crate::protocol::settings::SourceControl::input_min),
+    /// so it can be passed to
+    /// [`SourceControl::map`](crate::protocol::settings::SourceControl::map)
+    /// as it is.
+    ///
+    /// The software sensors are read from the [`external`](Self::external)
+    /// values `software_sensor/1` to `software_sensor/8` (see
+    /// [`SOFTWARE_SENSOR_SOURCE`]). Returns `None` if the value is not
+    /// available, which is always the case for [`DataSource::Sound`].
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn value_for(&self, source: DataSource) -> Option<f32> {
+        let value = match source {
+            DataSource::Flow => self.value(Channel::Flow),
+            DataSource::WaterTemperature => self.value(Channel::WaterTemperature),
+            DataSource::ExternalTemperature => self.value(Channel::ExternalTemperature),
+            DataSource::Conductivity => self.value(Channel::Conductivity),
+            DataSource::WaterQuality => self.value(Channel::WaterQuality),
+            DataSource::Power => self.value(Channel::Power),
+            DataSource::Sound => None,
+            DataSource::SoftwareSensor1 => self.software_sensor(1),
+            DataSource::SoftwareSensor2 => self.software_sensor(2),
+            DataSource::SoftwareSensor3 => self.software_sensor(3),
+            DataSource::SoftwareSensor4 => self.software_sensor(4),
+            DataSource::SoftwareSensor5 => self.software_sensor(5),
+            DataSource::SoftwareSensor6 => self.software_sensor(6),
+            DataSource::SoftwareSensor7 => self.software_sensor(7),
+            DataSource::SoftwareSensor8 => self.software_sensor(8),
+        }?;
+
+        Some((value * source.scale()) as f32)
+    }
+
+    fn software_sensor(&self, index: usize) -> Option<f64> {
+        self.external(&format!("{SOFTWARE_SENSOR_SOURCE}/{index}"))
+    }
+
+    /// Returns the value of the external sensor with the passed `name`.
+    #[must_use]
+    pub fn external(&self, name: &str) -> Option<f64> {
+        self.external.get(name).copied()
+    }
+
+    /// Returns the heat in watts (W) that is transported by the coolant,
+    /// using the external temperature as inlet temperature.
+    ///
+    /// Returns `None` if one of the temperatures is not available.
+    /// See [`heat_load_from`](Self::heat_load_from) for details.
+    #[must_use]
+    pub fn heat_load(&self, medium: Medium) -> Option<f64> {
+        let inlet = self.value(Channel::ExternalTemperature)?;
+
+        self.heat_load_from(medium, inlet)
+    }
+
+    /// Returns the heat in watts (W) that is transported by the coolant
+    /// between the passed `inlet` temperature (°C) and the water temperature.
+    ///
+    /// The heat is calculated as `mass flow * specific heat * ΔT`. A positive
+    /// value means the water temperature is above the inlet temperature.
+    /// Returns `None` if the water temperature is not available.
+    #[must_use]
+    pub fn heat_load_from(&self, medium: Medium, inlet: f64) -> Option<f64> {
+        let water_temperature = self.value(Channel::WaterTemperature)?;
+        let flow = self.value(Channel::Flow)?;
+        let mass_flow = flow / 3600.0 * medium.density();
+
+        Some(mass_flow * medium.specific_heat() * (water_temperature - inlet))
+    }
+}
+
+/// Formats the readings in their physical units. The capture time is
+/// formatted as milliseconds since the unix epoch, external values are
+/// omitted.
+#[cfg(feature = "defmt")]
+impl defmt::Format for SensorReadings {
+    fn format(&self, fmt: defmt::Formatter<'_>) {
+        defmt::write!(
+            fmt,
+            "SensorReadings {{ captured_at: {=i64}, flow: {}, water_temperature: {}, \
+             external_temperature: {}, conductivity: {}, water_quality: {}, power: {=f64}, \
+             voltage: {=f64} }}",
+            self.timestamp().unix_millis(),
+            self.value(Channel::Flow),
+            self.value(Channel::WaterTemperature),
+            self.value(Channel::ExternalTemperature),
+            self.value(Channel::Conductivity),
+            self.value(Channel::WaterQuality),
+            self.power,
+            self.voltage,
+        );
+    }
+}
+
+/// A single measured value of the [`SensorReadings`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Channel {
+    /// Water flow in liter per hour (l/h).
+    Flow,
+
+    /// Water temperature in degree celsius (°C).
+    WaterTemperature,
+
+    /// External temperature in degree celsius (°C).
+    ExternalTemperature,
+
+    /// Conductivity in micro siemens per centimeter (µS/cm).
+    Conductivity,
+
+    /// Water quality in percent (%).
+    WaterQuality,
+
+    /// Power consumption in watts (W).
+    Power,
+
+    /// System voltage in volts (V).
+    Voltage,
+}
+
+impl Channel {
+    /// List of all available channels.
+    pub const ALL: [Self; 7] = [
+        Self::Flow,
+        Self::WaterTemperature,
+        Self::ExternalTemperature,
+        Self::Conductivity,
+        Self::WaterQuality,
+        Self::Power,
+        Self::Voltage,
+    ];
+
+    /// Returns the name of the channel in `snake_case`.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Flow => "flow",
+            Self::WaterTemperature => "water_temperature",
+            Self::ExternalTemperature => "external_temperature",
+            Self::Conductivity => "conductivity",
+            Self::WaterQuality => "water_quality",
+            Self::Power => "power",
+            Self::Voltage => "voltage",
+        }
+    }
+
+    /// Returns the physical unit of the channel.
+    #[must_use]
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Self::Flow => "l/h",
+            Self::WaterTemperature | Self::ExternalTemperature => "°C",
+            Self::Conductivity => "µS/cm",
+            Self::WaterQuality => "%",
+            Self::Power => "W",
+            Self::Voltage => "V",
+        }
+    }
+}
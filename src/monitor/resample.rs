@@ -0,0 +1,251 @@
+use std::io::Error as IoError;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::{SensorReadings, Sink};
+
+/// Defines how the [`Resampler`] calculates the values between two readings.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum Interpolation {
+    /// Interpolates the values linearly between the surrounding readings.
+    #[default]
+    Linear,
+
+    /// Uses the values of the last readings before the grid point.
+    LastValue,
+}
+
+/// Resamples the irregular stream of [`SensorReadings`] onto a fixed grid.
+///
+/// Some storage backends (e.g. round robin databases) and dashboards expect
+/// the values in uniform intervals. The grid points are multiples of the
+/// interval since the unix epoch, so different resamplers with the same
+/// interval produce the same points in time. Every pushed readings return the
+/// grid points up to their capture time, calculated using the configured
+/// [`Interpolation`]. Readings older than the previous readings are ignored.
+///
+/// Values that are not available in one of the surrounding readings (e.g.
+/// the external temperature) and values of external sensors that are missing
+/// in the newer readings use the last value.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use high_flow_next::monitor::{CsvSink, Interpolation, Resampled, Resampler};
+///
+/// // Export the readings every 10 seconds, skip gaps of more than a minute.
+/// let resampler = Resampler::new(Duration::from_secs(10), Interpolation::Linear)
+///     .with_max_gap(Duration::from_secs(60));
+/// let sink = Resampled::new(CsvSink::new(std::io::stdout()), resampler);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    interval: Duration,
+    interpolation: Interpolation,
+    max_gap: Option<Duration>,
+    previous: Option<SensorReadings>,
+    next: Option<SystemTime>,
+}
+
+impl Resampler {
+    /// Creates a new resampler for a grid with the passed `interval`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `interval` is zero.
+    #[must_use]
+    pub fn new(interval: Duration, interpolation: Interpolation) -> Self {
+        assert!(!interval.is_zero(), "interval must not be zero");
+
+        Self {
+            interval,
+            interpolation,
+            max_gap: None,
+            previous: None,
+            next: None,
+        }
+    }
+
+    /// Sets the maximum gap between two readings that is interpolated and
+    /// returns the updated resampler.
+    ///
+    /// Grid points within larger gaps (e.g. while the device was
+    /// disconnected) are skipped instead of being filled with made up values.
+    #[must_use]
+    pub fn with_max_gap(mut self, max_gap: Duration) -> Self {
+        self.max_gap = Some(max_gap);
+
+        self
+    }
+
+    /// Returns the interval of the grid.
+    #[must_use]
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Adds the passed `readings` and returns the readings of the grid points
+    /// up to (and including) their capture time.
+    pub fn push(&mut self, readings: &SensorReadings) -> Vec<SensorReadings> {
+        let at = readings.captured_at;
+        if self
+            .previous
+            .as_ref()
+            .is_some_and(|previous| at < previous.captured_at)
+        {
+            return Vec::new();
+        }
+
+        let previous = self.previous.take().filter(|previous| {
+            let gap = at.duration_since(previous.captured_at).unwrap_or_default();
+
+            self.max_gap.is_none_or(|max_gap| gap <= max_gap)
+        });
+        let mut next = match (&previous, self.next) {
+            (Some(_), Some(next)) => next,
+            _ => align(at, self.interval),
+        };
+
+        let mut samples = Vec::new();
+        while next <= at {
+            let sample = match &previous {
+                Some(previous) if next < at => self.interpolate(previous, readings, next),
+                _ => SensorReadings {
+                    captured_at: next,
+                    ..readings.clone()
+                },
+            };
+
+            samples.push(sample);
+            next += self.interval;
+        }
+
+        self.next = Some(next);
+        self.previous = Some(readings.clone());
+
+        samples
+    }
+
+    /// Resets the resampler, so the next readings start a new grid.
+    pub fn reset(&mut self) {
+        self.previous = None;
+        self.next = None;
+    }
+
+    fn interpolate(
+        &self,
+        previous: &SensorReadings,
+        next: &SensorReadings,
+        at: SystemTime,
+    ) -> SensorReadings {
+        if self.interpolation == Interpolation::LastValue {
+            return SensorReadings {
+                captured_at: at,
+                ..previous.clone()
+            };
+        }
+
+        let span = next.captured_at.duration_since(previous.captured_at);
+        let offset = at.duration_since(previous.captured_at);
+        let t = match (span, offset) {
+            (Ok(span), Ok(offset)) if !span.is_zero() => offset.as_secs_f64() / span.as_secs_f64(),
+            _ => 0.0,
+        };
+
+        SensorReadings {
+            captured_at: at,
+            flow: lerp_raw(previous.flow, next.flow, t),
+            water_temperature: lerp_opt(previous.water_temperature, next.water_temperature, t),
+            external_temperature: lerp_opt(
+                previous.external_temperature,
+                next.external_temperature,
+                t,
+            ),
+            conductivity: lerp_raw(previous.conductivity, next.conductivity, t),
+            water_quality: lerp_raw(previous.water_quality, next.water_quality, t),
+            power: lerp(previous.power, next.power, t),
+            voltage: lerp(previous.voltage, next.voltage, t),
+            external: previous
+                .external
+                .iter()
+                .map(|(name, value)| {
+                    let value = next
+                        .external
+                        .get(name)
+                        .map_or(*value, |x| lerp(*value, *x, t));
+
+                    (name.clone(), value)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// [`Sink`] that resamples the readings (see [`Resampler`]) before they are
+/// forwarded to the inner sink.
+#[derive(Debug)]
+pub struct Resampled<S> {
+    sink: S,
+    resampler: Resampler,
+}
+
+impl<S: Sink> Resampled<S> {
+    /// Creates a new sink that forwards the readings resampled by `resampler`
+    /// to `sink`.
+    pub fn new(sink: S, resampler: Resampler) -> Self {
+        Self { sink, resampler }
+    }
+
+    /// Returns the inner sink.
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+}
+
+impl<S: Sink> Sink for Resampled<S> {
+    fn publish(&mut self, readings: &SensorReadings) -> Result<(), IoError> {
+        match &self.resampler.push(readings)[..] {
+            [] => Ok(()),
+            [readings] => self.sink.publish(readings),
+            readings => self.sink.publish_batch(readings),
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), IoError> {
+        self.sink.flush()
+    }
+}
+
+/// Returns the first grid point at or after `at`.
+fn align(at: SystemTime, interval: Duration) -> SystemTime {
+    let since_epoch = at.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+    let interval = interval.as_nanos();
+    let aligned = since_epoch.div_ceil(interval) * interval;
+
+    UNIX_EPOCH + Duration::from_nanos(u64::try_from(aligned).unwrap_or(u64::MAX))
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn lerp_raw<W>(a: W, b: W, t: f64) -> W
+where
+    W: Copy + Into<u16> + TryFrom<u16>,
+{
+    let raw = lerp(f64::from(a.into()), f64::from(b.into()), t).round() as u16;
+
+    W::try_from(raw).unwrap_or(a)
+}
+
+fn lerp_opt<W>(a: Option<W>, b: Option<W>, t: f64) -> Option<W>
+where
+    W: Copy + Into<u16> + TryFrom<u16>,
+{
+    match (a, b) {
+        (Some(a), Some(b)) => Some(lerp_raw(a, b, t)),
+        (a, _) => a,
+    }
+}
@@ -0,0 +1,211 @@
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::time::{Duration, Instant};
+
+use crate::misc::{Clock, SystemClock};
+
+/// Identifier of a task added to the [`Scheduler`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct TaskId(pub usize);
+
+/// Error returned by a task of the [`Scheduler`].
+#[derive(Debug)]
+pub struct TaskFailure<E> {
+    /// Identifier of the task that failed.
+    pub id: TaskId,
+
+    /// Error returned by the task.
+    pub error: E,
+
+    /// Delay until the task is executed again.
+    pub retry_in: Duration,
+}
+
+/// Executes tasks at individual rates, sharing a single context.
+///
+/// Every task is a closure that gets mutable access to the context `C`
+/// (usually the device handle), so different values can be polled at
+/// different rates without opening the device multiple times. If a task
+/// fails, its interval is doubled for every consecutive failure (up to
+/// [`max_backoff`](Self::max_backoff)) until it succeeds again.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::{Duration, Instant};
+///
+/// use high_flow_next::monitor::Scheduler;
+///
+/// let mut scheduler = Scheduler::<Vec<&str>, ()>::new();
+/// scheduler.add_task(Duration::from_secs(1), |log| {
+///     log.push("readings");
+///
+///     Ok(())
+/// });
+///
+/// let mut log = Vec::new();
+/// scheduler.run_pending(&mut log, Instant::now());
+/// assert_eq!(log, ["readings"]);
+/// ```
+pub struct Scheduler<C, E> {
+    tasks: Vec<Task<C, E>>,
+    max_backoff: Duration,
+}
+
+impl<C, E> Scheduler<C, E> {
+    /// Default value for [`max_backoff`](Self::max_backoff).
+    pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_mins(5);
+
+    /// Creates a new scheduler without any tasks.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            tasks: Vec::new(),
+            max_backoff: Self::DEFAULT_MAX_BACKOFF,
+        }
+    }
+
+    /// Set the maximum interval a failing task is delayed to.
+    #[must_use]
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+
+        self
+    }
+
+    /// Adds a new task that is executed every `interval`.
+    ///
+    /// The task is due immediately.
+    pub fn add_task<F>(&mut self, interval: Duration, task: F) -> TaskId
+    where
+        F: FnMut(&mut C) -> Result<(), E> + 'static,
+    {
+        self.tasks.push(Task {
+            interval,
+            failures: 0,
+            next_due: None,
+            run: Box::new(task),
+        });
+
+        TaskId(self.tasks.len() - 1)
+    }
+
+    /// Returns the number of consecutive failures of the task with the passed `id`.
+    #[must_use]
+    pub fn failures(&self, id: TaskId) -> Option<u32> {
+        self.tasks.get(id.0).map(|task| task.failures)
+    }
+
+    /// Returns the point in time the next task is due at.
+    ///
+    /// Returns `None` if no task was executed yet, meaning that all tasks
+    /// are due immediately, or if the scheduler has no tasks.
+    #[must_use]
+    pub fn next_due(&self) -> Option<Instant> {
+        if self.tasks.iter().any(|task| task.next_due.is_none()) {
+            return None;
+        }
+
+        self.tasks.iter().filter_map(|task| task.next_due).min()
+    }
+
+    /// Executes all tasks that are due at `now` and returns the failures.
+    pub fn run_pending(&mut self, context: &mut C, now: Instant) -> Vec<TaskFailure<E>> {
+        let mut failures = Vec::new();
+
+        for (i, task) in self.tasks.iter_mut().enumerate() {
+            if task.next_due.is_some_and(|due| due > now) {
+                continue;
+            }
+
+            match (task.run)(context) {
+                Ok(()) => {
+                    task.failures = 0;
+                    task.next_due = Some(now + task.interval);
+                }
+                Err(error) => {
+                    task.failures = task.failures.saturating_add(1);
+
+                    let factor = 1_u32.checked_shl(task.failures).unwrap_or(u32::MAX);
+                    let retry_in = task
+                        .interval
+                        .saturating_mul(factor)
+                        .min(self.max_backoff.max(task.interval));
+
+                    task.next_due = Some(now + retry_in);
+                    failures.push(TaskFailure {
+                        id: TaskId(i),
+                        error,
+                        retry_in,
+                    });
+                }
+            }
+        }
+
+        failures
+    }
+
+    /// Executes the tasks in an endless loop, sleeping until the next task is due.
+    ///
+    /// The passed `on_failure` callback is invoked for every failed task.
+    /// Returns once the callback returns `false`.
+    pub fn run<F>(&mut self, context: &mut C, on_failure: F)
+    where
+        F: FnMut(TaskFailure<E>) -> bool,
+    {
+        self.run_with_clock(context, &SystemClock, on_failure);
+    }
+
+    /// Like [`run`](Self::run), but uses the passed `clock` to get the
+    /// current time and to sleep until the next task is due.
+    pub fn run_with_clock<K, F>(&mut self, context: &mut C, clock: &K, mut on_failure: F)
+    where
+        K: Clock,
+        F: FnMut(TaskFailure<E>) -> bool,
+    {
+        loop {
+            for failure in self.run_pending(context, clock.now()) {
+                if !on_failure(failure) {
+                    return;
+                }
+            }
+
+            if let Some(due) = self.next_due() {
+                clock.sleep(due.saturating_duration_since(clock.now()));
+            }
+        }
+    }
+}
+
+impl<C, E> Default for Scheduler<C, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C, E> Debug for Scheduler<C, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Scheduler")
+            .field("tasks", &self.tasks)
+            .field("max_backoff", &self.max_backoff)
+            .finish()
+    }
+}
+
+type TaskFn<C, E> = Box<dyn FnMut(&mut C) -> Result<(), E>>;
+
+struct Task<C, E> {
+    interval: Duration,
+    failures: u32,
+    next_due: Option<Instant>,
+    run: TaskFn<C, E>,
+}
+
+impl<C, E> Debug for Task<C, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Task")
+            .field("interval", &self.interval)
+            .field("failures", &self.failures)
+            .field("next_due", &self.next_due)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,330 @@
+use std::collections::VecDeque;
+use std::fmt::{Debug, Formatter, Result as FmtResult, Write as _};
+use std::io::{Error as IoError, Write};
+use std::time::SystemTime;
+
+use crate::device::DeviceHealth;
+
+use super::{Channel, SensorReadings};
+
+/// Destination the [`SensorReadings`] are exported to.
+pub trait Sink {
+    /// Publishes the passed `readings`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the readings could not be published.
+    fn publish(&mut self, readings: &SensorReadings) -> Result<(), IoError>;
+
+    /// Publishes a batch of `readings` at once.
+    ///
+    /// The default implementation publishes the readings one by one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the readings could not be published.
+    fn publish_batch(&mut self, readings: &[SensorReadings]) -> Result<(), IoError> {
+        for readings in readings {
+            self.publish(readings)?;
+        }
+
+        Ok(())
+    }
+
+    /// Flushes any buffered data of the sink.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the buffered data could not be written.
+    fn flush(&mut self) -> Result<(), IoError> {
+        Ok(())
+    }
+}
+
+/// Sink that collects the readings and forwards them to the inner sink in
+/// batches of a fixed size.
+///
+/// If the inner sink fails, the batch is kept and delivered again with the
+/// next reading. At most `size` readings are buffered, so the oldest readings
+/// are dropped while the inner sink fails. Use a
+/// [`CircuitBreaker`](super::CircuitBreaker) to buffer the readings of a
+/// longer outage.
+#[derive(Debug)]
+pub struct Batched<S> {
+    sink: S,
+    size: usize,
+    buffer: VecDeque<SensorReadings>,
+}
+
+impl<S: Sink> Batched<S> {
+    /// Creates a new sink that forwards batches of `size` readings to `sink`.
+    pub fn new(sink: S, size: usize) -> Self {
+        Self {
+            sink,
+            size: size.max(1),
+            buffer: VecDeque::new(),
+        }
+    }
+
+    /// Returns the number of readings that were not delivered yet.
+    #[must_use]
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns the inner sink.
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+}
+
+impl<S: Sink> Sink for Batched<S> {
+    fn publish(&mut self, readings: &SensorReadings) -> Result<(), IoError> {
+        if self.buffer.len() >= self.size {
+            self.buffer.pop_front();
+        }
+
+        self.buffer.push_back(readings.clone());
+
+        if self.buffer.len() >= self.size {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), IoError> {
+        if !self.buffer.is_empty() {
+            self.sink.publish_batch(self.buffer.make_contiguous())?;
+            self.buffer.clear();
+        }
+
+        self.sink.flush()
+    }
+}
+
+/// Defines how the [`Publisher`] reacts on errors of a sink.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ErrorPolicy {
+    /// Report the error and keep the sink.
+    Ignore,
+
+    /// Retry publishing up to the passed number of times before reporting
+    /// the error.
+    Retry(u32),
+
+    /// Report the error and remove the sink from the publisher.
+    Disable,
+}
+
+/// Publishes the readings to multiple [`Sink`]s.
+#[derive(Default)]
+pub struct Publisher {
+    sinks: Vec<(Box<dyn Sink>, ErrorPolicy)>,
+}
+
+impl Publisher {
+    /// Creates a new publisher without any sinks.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the passed `sink` using the passed error `policy`.
+    pub fn add_sink<S>(&mut self, sink: S, policy: ErrorPolicy)
+    where
+        S: Sink + 'static,
+    {
+        self.sinks.push((Box::new(sink), policy));
+    }
+
+    /// Returns the number of sinks of the publisher.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.sinks.len()
+    }
+
+    /// Returns `true` if the publisher does not have any sinks.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    /// Publishes the passed `readings` to all sinks and returns the errors
+    /// that were not handled by the error policy of the sinks.
+    pub fn publish(&mut self, readings: &SensorReadings) -> Vec<IoError> {
+        let mut errors = Vec::new();
+
+        self.sinks.retain_mut(|(sink, policy)| {
+            let mut result = sink.publish(readings);
+
+            if let ErrorPolicy::Retry(count) = *policy {
+                for _ in 0..count {
+                    if result.is_ok() {
+                        break;
+                    }
+
+                    result = sink.publish(readings);
+                }
+            }
+
+            match result {
+                Ok(()) => true,
+                Err(error) => {
+                    errors.push(error);
+
+                    *policy != ErrorPolicy::Disable
+                }
+            }
+        });
+
+        errors
+    }
+
+    /// Flushes all sinks and returns the errors that occurred.
+    pub fn flush(&mut self) -> Vec<IoError> {
+        self.sinks
+            .iter_mut()
+            .filter_map(|(sink, _)| sink.flush().err())
+            .collect()
+    }
+}
+
+impl Debug for Publisher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Publisher")
+            .field("sinks", &self.sinks.len())
+            .finish()
+    }
+}
+
+/// Sink that writes the readings as comma separated values.
+///
+/// The first line contains the header with the timestamp (seconds since the
+/// unix epoch) followed by the names of all [`Channel`]s. Values that are
+/// not available are left empty.
+#[derive(Debug)]
+pub struct CsvSink<W> {
+    writer: W,
+    header: bool,
+}
+
+impl<W: Write> CsvSink<W> {
+    /// Creates a new sink that writes to the passed `writer`.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            header: false,
+        }
+    }
+
+    /// Returns the inner writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Sink for CsvSink<W> {
+    fn publish(&mut self, readings: &SensorReadings) -> Result<(), IoError> {
+        if !self.header {
+            write!(self.writer, "timestamp")?;
+            for channel in Channel::ALL {
+                write!(self.writer, ",{}", channel.name())?;
+            }
+            writeln!(self.writer)?;
+
+            self.header = true;
+        }
+
+        write!(self.writer, "{:.3}", timestamp(readings.captured_at))?;
+        for channel in Channel::ALL {
+            match readings.value(channel) {
+                Some(value) => write!(self.writer, ",{value}")?,
+                None => write!(self.writer, ",")?,
+            }
+        }
+        writeln!(self.writer)?;
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), IoError> {
+        self.writer.flush()
+    }
+}
+
+/// Sink that renders the latest readings in the Prometheus text exposition
+/// format.
+///
+/// The rendered metrics can be served by any HTTP server using
+/// [`metrics`](Self::metrics). The health of the device can be exported
+/// additionally using [`set_health`](Self::set_health).
+#[derive(Debug, Clone)]
+pub struct PrometheusSink {
+    prefix: String,
+    metrics: String,
+    health: String,
+}
+
+impl PrometheusSink {
+    /// Creates a new sink that prefixes all metric names with `prefix`.
+    pub fn new<S: Into<String>>(prefix: S) -> Self {
+        Self {
+            prefix: prefix.into(),
+            metrics: String::new(),
+            health: String::new(),
+        }
+    }
+
+    /// Updates the exported health metric of the device. The health is
+    /// rendered after the readings of the next call to
+    /// [`publish`](Sink::publish).
+    pub fn set_health(&mut self, health: &DeviceHealth) {
+        self.health.clear();
+        health.write_metrics(&mut self.health, &self.prefix);
+    }
+
+    /// Returns the metrics of the latest published readings.
+    #[must_use]
+    pub fn metrics(&self) -> &str {
+        &self.metrics
+    }
+}
+
+impl Default for PrometheusSink {
+    fn default() -> Self {
+        Self::new("high_flow_next")
+    }
+}
+
+impl Sink for PrometheusSink {
+    fn publish(&mut self, readings: &SensorReadings) -> Result<(), IoError> {
+        self.metrics.clear();
+
+        for channel in Channel::ALL {
+            let Some(value) = readings.value(channel) else {
+                continue;
+            };
+
+            let name = format!("{}_{}", self.prefix, channel.name());
+            let _ = writeln!(
+                self.metrics,
+                "# HELP {name} {} in {}",
+                channel.name(),
+                channel.unit()
+            );
+            let _ = writeln!(self.metrics, "# TYPE {name} gauge");
+            let _ = writeln!(self.metrics, "{name} {value}");
+        }
+
+        self.metrics.push_str(&self.health);
+
+        Ok(())
+    }
+}
+
+fn timestamp(time: SystemTime) -> f64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
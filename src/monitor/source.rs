@@ -0,0 +1,119 @@
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::io::Error as IoError;
+
+use super::SensorReadings;
+
+/// Source of external readings (e.g. other hwmon sensors of the host) that
+/// are merged into the [`SensorReadings`] of the device.
+pub trait ReadingSource {
+    /// Name of the source, used as prefix for the names of its values.
+    fn name(&self) -> &str;
+
+    /// Reads the current values of the source as `(name, value)` pairs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the values could not be read.
+    fn read(&mut self) -> Result<Vec<(String, f64)>, IoError>;
+}
+
+/// [`ReadingSource`] that is implemented by a closure.
+pub struct FnSource<F> {
+    name: String,
+    read: F,
+}
+
+impl<F> FnSource<F>
+where
+    F: FnMut() -> Result<Vec<(String, f64)>, IoError>,
+{
+    /// Creates a new source with the passed `name` that reads its values by
+    /// calling `read`.
+    pub fn new<S: Into<String>>(name: S, read: F) -> Self {
+        Self {
+            name: name.into(),
+            read,
+        }
+    }
+}
+
+impl<F> ReadingSource for FnSource<F>
+where
+    F: FnMut() -> Result<Vec<(String, f64)>, IoError>,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn read(&mut self) -> Result<Vec<(String, f64)>, IoError> {
+        (self.read)()
+    }
+}
+
+impl<F> Debug for FnSource<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("FnSource")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Merges the values of multiple [`ReadingSource`]s into the
+/// [`SensorReadings`] of the device.
+///
+/// The values are stored in [`SensorReadings::external`] using the key
+/// `"<source>/<name>"`.
+#[derive(Default)]
+pub struct Merger {
+    sources: Vec<Box<dyn ReadingSource>>,
+}
+
+impl Merger {
+    /// Creates a new merger without any sources.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the passed `source` to the merger.
+    pub fn add_source<S>(&mut self, source: S)
+    where
+        S: ReadingSource + 'static,
+    {
+        self.sources.push(Box::new(source));
+    }
+
+    /// Reads all sources and merges their values into the passed `readings`.
+    ///
+    /// Sources that fail to read are skipped, their errors are returned
+    /// together with the name of the source.
+    pub fn merge(&mut self, readings: &mut SensorReadings) -> Vec<(String, IoError)> {
+        let mut errors = Vec::new();
+
+        for source in &mut self.sources {
+            match source.read() {
+                Ok(values) => {
+                    for (name, value) in values {
+                        readings
+                            .external
+                            .insert(format!("{}/{name}", source.name()), value);
+                    }
+                }
+                Err(error) => errors.push((source.name().to_owned(), error)),
+            }
+        }
+
+        errors
+    }
+}
+
+impl Debug for Merger {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Merger")
+            .field(
+                "sources",
+                &self.sources.iter().map(|x| x.name()).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
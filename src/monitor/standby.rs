@@ -0,0 +1,292 @@
+use std::fs::read_to_string;
+use std::io::{Error as IoError, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::protocol::settings::{Settings, StandbyFlags};
+
+use super::{Watchdog, WatchdogEvent};
+
+/// Power state of the USB connection between the host and the device.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum UsbPowerState {
+    /// The connection is active and the device can be polled.
+    Active,
+
+    /// The USB host (or the port of the device) is suspended.
+    Suspended,
+
+    /// The device is not connected.
+    Disconnected,
+}
+
+/// Source of the [`UsbPowerState`] of the device, as exposed by the platform.
+///
+/// The trait is implemented for closures, so platforms without a dedicated
+/// implementation can report the state from their own suspend / resume
+/// notifications.
+pub trait PowerStateSource {
+    /// Returns the current power state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state could not be determined.
+    fn power_state(&mut self) -> Result<UsbPowerState, IoError>;
+}
+
+impl<F> PowerStateSource for F
+where
+    F: FnMut() -> Result<UsbPowerState, IoError>,
+{
+    fn power_state(&mut self) -> Result<UsbPowerState, IoError> {
+        self()
+    }
+}
+
+/// [`PowerStateSource`] that reads the runtime power management state of a
+/// USB device from the Linux sysfs (`power/runtime_status`).
+#[derive(Debug, Clone)]
+pub struct SysfsPowerState {
+    device_dir: PathBuf,
+}
+
+impl SysfsPowerState {
+    /// Creates a new source for the USB device in `device_dir` (e.g.
+    /// `/sys/bus/usb/devices/1-4`).
+    pub fn new<P: Into<PathBuf>>(device_dir: P) -> Self {
+        Self {
+            device_dir: device_dir.into(),
+        }
+    }
+
+    /// Returns the directory of the USB device.
+    #[must_use]
+    pub fn device_dir(&self) -> &Path {
+        &self.device_dir
+    }
+}
+
+impl PowerStateSource for SysfsPowerState {
+    fn power_state(&mut self) -> Result<UsbPowerState, IoError> {
+        if !self.device_dir.exists() {
+            return Ok(UsbPowerState::Disconnected);
+        }
+
+        match read_to_string(self.device_dir.join("power/runtime_status")) {
+            Ok(status) => Ok(match status.trim() {
+                "suspended" | "suspending" => UsbPowerState::Suspended,
+                _ => UsbPowerState::Active,
+            }),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(UsbPowerState::Disconnected),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Event emitted by the [`StandbyMirror`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum StandbyEvent {
+    /// The USB connection left the active state, polling should be paused.
+    ///
+    /// Buffered state (e.g. [`Batched`](super::Batched) sinks or the
+    /// [`Totalizer`](super::Totalizer) state) should be flushed now.
+    Paused {
+        /// Point in time the state change was detected.
+        at: SystemTime,
+
+        /// New power state of the USB connection.
+        state: UsbPowerState,
+
+        /// Whether the device entered its standby as well (see
+        /// [`StandbyMirror::is_standby`]).
+        standby: bool,
+    },
+
+    /// The USB connection is active again, polling can be resumed.
+    Resumed {
+        /// Point in time the state change was detected.
+        at: SystemTime,
+
+        /// Duration polling was paused for.
+        after: Duration,
+    },
+}
+
+/// Mirrors the standby behavior of the device ([`StandbyFlags`]) on the host.
+///
+/// The current [`UsbPowerState`] is passed to [`update`](Self::update)
+/// periodically (e.g. read from a [`PowerStateSource`] using
+/// [`poll`](Self::poll)). While the connection is not active, polling the
+/// device is paused and [`check_watchdog`](Self::check_watchdog) suppresses
+/// stale data reports, so daemons do not raise spurious alarms while the host
+/// is sleeping. After resuming, the [`Watchdog`] is restarted.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::{Duration, SystemTime};
+///
+/// use high_flow_next::monitor::{StandbyEvent, StandbyMirror, UsbPowerState, Watchdog};
+/// use high_flow_next::protocol::settings::StandbyFlags;
+///
+/// let start = SystemTime::UNIX_EPOCH;
+/// let mut mirror = StandbyMirror::new(StandbyFlags::fully_dark());
+/// let mut watchdog = Watchdog::new(Duration::from_secs(5), start);
+///
+/// let event = mirror.update(UsbPowerState::Suspended, start);
+/// assert!(matches!(event, Some(StandbyEvent::Paused { standby: true, .. })));
+/// assert!(!mirror.should_poll());
+///
+/// let later = start + Duration::from_secs(3600);
+/// assert_eq!(mirror.check_watchdog(&mut watchdog, later), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct StandbyMirror {
+    flags: StandbyFlags,
+    state: UsbPowerState,
+    paused_since: Option<SystemTime>,
+    resumed_at: Option<SystemTime>,
+}
+
+impl StandbyMirror {
+    /// Creates a new mirror for a device with the passed standby `flags`.
+    ///
+    /// The USB connection is assumed to be active.
+    #[must_use]
+    pub fn new(flags: StandbyFlags) -> Self {
+        Self {
+            flags,
+            state: UsbPowerState::Active,
+            paused_since: None,
+            resumed_at: None,
+        }
+    }
+
+    /// Creates a new mirror for a device with the passed `settings`.
+    #[must_use]
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self::new(settings.system.standby_flags)
+    }
+
+    /// Returns the standby flags of the device.
+    #[must_use]
+    pub fn flags(&self) -> StandbyFlags {
+        self.flags
+    }
+
+    /// Updates the standby flags (e.g. after the settings were written).
+    pub fn set_flags(&mut self, flags: StandbyFlags) {
+        self.flags = flags;
+    }
+
+    /// Returns the last known power state of the USB connection.
+    #[must_use]
+    pub fn state(&self) -> UsbPowerState {
+        self.state
+    }
+
+    /// Returns `true` if the device should be polled, i.e. the USB
+    /// connection is active.
+    #[must_use]
+    pub fn should_poll(&self) -> bool {
+        self.state == UsbPowerState::Active
+    }
+
+    /// Returns `true` if the device is in standby, based on the current
+    /// power state and the standby flags.
+    #[must_use]
+    pub fn is_standby(&self) -> bool {
+        match self.state {
+            UsbPowerState::Active => false,
+            UsbPowerState::Suspended => self.flags.contains(StandbyFlags::STANDBY_ON_SUSPEND),
+            UsbPowerState::Disconnected => self.flags.contains(StandbyFlags::STANDBY_NO_USB),
+        }
+    }
+
+    /// Returns `true` if the device does not detect alarms at the moment
+    /// ([`StandbyFlags::DISABLE_ALARM_DETECT`]).
+    #[must_use]
+    pub fn alarms_paused(&self) -> bool {
+        self.is_standby() && self.flags.contains(StandbyFlags::DISABLE_ALARM_DETECT)
+    }
+
+    /// Returns `true` if the device does not count the volume at the moment
+    /// ([`StandbyFlags::DISABLE_VOLUME_COUNTER`]).
+    #[must_use]
+    pub fn volume_counter_paused(&self) -> bool {
+        self.is_standby() && self.flags.contains(StandbyFlags::DISABLE_VOLUME_COUNTER)
+    }
+
+    /// Updates the power state of the USB connection to `state` at `at`.
+    ///
+    /// Returns an event if polling should be paused or resumed.
+    pub fn update(&mut self, state: UsbPowerState, at: SystemTime) -> Option<StandbyEvent> {
+        let previous = self.state;
+        self.state = state;
+
+        match (previous, state) {
+            (UsbPowerState::Active, UsbPowerState::Active) => None,
+            (UsbPowerState::Active, _) => {
+                self.paused_since = Some(at);
+                self.resumed_at = None;
+
+                Some(StandbyEvent::Paused {
+                    at,
+                    state,
+                    standby: self.is_standby(),
+                })
+            }
+            (_, UsbPowerState::Active) => {
+                let since = self.paused_since.take().unwrap_or(at);
+                self.resumed_at = Some(at);
+
+                Some(StandbyEvent::Resumed {
+                    at,
+                    after: at.duration_since(since).unwrap_or_default(),
+                })
+            }
+            (_, _) => None,
+        }
+    }
+
+    /// Reads the power state from the passed `source` and updates the
+    /// mirror (see [`update`](Self::update)).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the power state could not be read. The mirror is
+    /// not changed in this case.
+    pub fn poll<S>(
+        &mut self,
+        source: &mut S,
+        at: SystemTime,
+    ) -> Result<Option<StandbyEvent>, IoError>
+    where
+        S: PowerStateSource + ?Sized,
+    {
+        let state = source.power_state()?;
+
+        Ok(self.update(state, at))
+    }
+
+    /// Checks the passed `watchdog` at `now`, unless polling is paused.
+    ///
+    /// The first check after the connection was resumed restarts the
+    /// watchdog at the time of the resume, so the time spent in standby is
+    /// not reported as stale data.
+    pub fn check_watchdog(
+        &mut self,
+        watchdog: &mut Watchdog,
+        now: SystemTime,
+    ) -> Option<WatchdogEvent> {
+        if !self.should_poll() {
+            return None;
+        }
+
+        if let Some(at) = self.resumed_at.take() {
+            watchdog.restart(at);
+        }
+
+        watchdog.check(now)
+    }
+}
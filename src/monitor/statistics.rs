@@ -0,0 +1,290 @@
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime};
+
+use super::{Channel, SensorReadings};
+
+/// Time window the [`Statistics`] are calculated for.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Window {
+    /// Sliding window covering the last readings within the passed duration.
+    Duration(Duration),
+
+    /// All readings since the statistics were created.
+    ///
+    /// Only `min`, `max` and `mean` are tracked for this window, since the
+    /// percentiles would require keeping every single reading.
+    Boot,
+}
+
+/// Aggregated values of a single [`Channel`] within a [`Window`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelStatistics {
+    /// Number of readings within the window.
+    pub count: usize,
+
+    /// Minimum value within the window.
+    pub min: f64,
+
+    /// Maximum value within the window.
+    pub max: f64,
+
+    /// Arithmetic mean of the values within the window.
+    pub mean: f64,
+
+    /// Median (50th percentile) of the values (`None` for [`Window::Boot`]).
+    pub p50: Option<f64>,
+
+    /// 90th percentile of the values (`None` for [`Window::Boot`]).
+    pub p90: Option<f64>,
+
+    /// 99th percentile of the values (`None` for [`Window::Boot`]).
+    pub p99: Option<f64>,
+}
+
+/// Snapshot of the statistics of all channels for a single [`Window`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowSnapshot {
+    /// Window the statistics were calculated for.
+    pub window: Window,
+
+    /// Statistics of the channels that had at least one reading within the window.
+    pub channels: Vec<(Channel, ChannelStatistics)>,
+}
+
+/// Rolling statistics over the [`SensorReadings`].
+///
+/// Maintains minimum, maximum, mean and percentiles for every [`Channel`]
+/// over a set of configurable [`Window`]s.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use high_flow_next::monitor::{Statistics, Window};
+///
+/// let statistics = Statistics::new([
+///     Window::Duration(Duration::from_secs(60)),
+///     Window::Boot,
+/// ]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Statistics {
+    windows: Vec<WindowState>,
+}
+
+impl Statistics {
+    /// Creates new statistics for the passed `windows`.
+    pub fn new<I>(windows: I) -> Self
+    where
+        I: IntoIterator<Item = Window>,
+    {
+        let windows = windows.into_iter().map(WindowState::new).collect();
+
+        Self { windows }
+    }
+
+    /// Returns the windows the statistics are calculated for.
+    pub fn windows(&self) -> impl Iterator<Item = Window> + '_ {
+        self.windows.iter().map(|x| x.window)
+    }
+
+    /// Adds the passed `readings` to the statistics.
+    pub fn push(&mut self, readings: &SensorReadings) {
+        for window in &mut self.windows {
+            window.push(readings);
+        }
+    }
+
+    /// Returns the statistics of the passed `channel` within the passed `window`.
+    ///
+    /// Returns `None` if the window is unknown or there are no readings of
+    /// the channel within the window.
+    #[must_use]
+    pub fn get(&self, window: Window, channel: Channel) -> Option<ChannelStatistics> {
+        self.windows
+            .iter()
+            .find(|x| x.window == window)?
+            .channel(channel)
+            .statistics()
+    }
+
+    /// Returns the `p`th percentile (`0.0..=100.0`) of the passed `channel`
+    /// within the passed `window`.
+    ///
+    /// Returns `None` if the window is unknown, does not track percentiles or
+    /// there are no readings of the channel within the window.
+    #[must_use]
+    pub fn percentile(&self, window: Window, channel: Channel, p: f64) -> Option<f64> {
+        let state = self.windows.iter().find(|x| x.window == window)?;
+        let ChannelState::Samples(samples) = state.channel(channel) else {
+            return None;
+        };
+
+        percentile(&sorted(samples), p)
+    }
+
+    /// Creates a snapshot of the statistics of all channels and windows.
+    #[must_use]
+    pub fn snapshot(&self) -> Vec<WindowSnapshot> {
+        self.windows
+            .iter()
+            .map(|state| WindowSnapshot {
+                window: state.window,
+                channels: Channel::ALL
+                    .into_iter()
+                    .filter_map(|channel| Some((channel, state.channel(channel).statistics()?)))
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+impl Default for Statistics {
+    /// Creates statistics for the last 5 minutes, the last hour and all
+    /// readings since boot.
+    fn default() -> Self {
+        Self::new([
+            Window::Duration(Duration::from_mins(5)),
+            Window::Duration(Duration::from_hours(1)),
+            Window::Boot,
+        ])
+    }
+}
+
+#[derive(Debug, Clone)]
+struct WindowState {
+    window: Window,
+    channels: [ChannelState; Channel::ALL.len()],
+}
+
+impl WindowState {
+    fn new(window: Window) -> Self {
+        let channels = std::array::from_fn(|_| match window {
+            Window::Duration(_) => ChannelState::Samples(VecDeque::new()),
+            Window::Boot => ChannelState::Running(None),
+        });
+
+        Self { window, channels }
+    }
+
+    fn channel(&self, channel: Channel) -> &ChannelState {
+        &self.channels[channel as usize]
+    }
+
+    fn push(&mut self, readings: &SensorReadings) {
+        let now = readings.captured_at;
+        let limit = match self.window {
+            Window::Duration(duration) => now.checked_sub(duration),
+            Window::Boot => None,
+        };
+
+        for channel in Channel::ALL {
+            let state = &mut self.channels[channel as usize];
+
+            if let Some(value) = readings.value(channel) {
+                state.push(now, value);
+            }
+
+            if let (ChannelState::Samples(samples), Some(limit)) = (state, limit) {
+                while samples.front().is_some_and(|(time, _)| *time < limit) {
+                    samples.pop_front();
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ChannelState {
+    Samples(VecDeque<(SystemTime, f64)>),
+    Running(Option<Running>),
+}
+
+impl ChannelState {
+    fn push(&mut self, time: SystemTime, value: f64) {
+        match self {
+            Self::Samples(samples) => samples.push_back((time, value)),
+            Self::Running(None) => {
+                *self = Self::Running(Some(Running {
+                    count: 1,
+                    min: value,
+                    max: value,
+                    sum: value,
+                }));
+            }
+            Self::Running(Some(running)) => {
+                running.count += 1;
+                running.min = running.min.min(value);
+                running.max = running.max.max(value);
+                running.sum += value;
+            }
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn statistics(&self) -> Option<ChannelStatistics> {
+        match self {
+            Self::Samples(samples) => {
+                let sorted = sorted(samples);
+                let count = sorted.len();
+                let sum = sorted.iter().sum::<f64>();
+
+                Some(ChannelStatistics {
+                    count,
+                    min: *sorted.first()?,
+                    max: *sorted.last()?,
+                    mean: sum / count as f64,
+                    p50: percentile(&sorted, 50.0),
+                    p90: percentile(&sorted, 90.0),
+                    p99: percentile(&sorted, 99.0),
+                })
+            }
+            Self::Running(running) => {
+                let running = running.as_ref()?;
+
+                Some(ChannelStatistics {
+                    count: running.count,
+                    min: running.min,
+                    max: running.max,
+                    mean: running.sum / running.count as f64,
+                    p50: None,
+                    p90: None,
+                    p99: None,
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Running {
+    count: usize,
+    min: f64,
+    max: f64,
+    sum: f64,
+}
+
+fn sorted(samples: &VecDeque<(SystemTime, f64)>) -> Vec<f64> {
+    let mut values = samples.iter().map(|(_, value)| *value).collect::<Vec<_>>();
+    values.sort_by(f64::total_cmp);
+
+    values
+}
+
+/// Calculates the `p`th percentile of the already sorted `values` using the
+/// nearest-rank method.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn percentile(values: &[f64], p: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let rank = (p.clamp(0.0, 100.0) / 100.0 * values.len() as f64).ceil() as usize;
+
+    Some(values[rank.saturating_sub(1)])
+}
@@ -0,0 +1,104 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Point in time a value was captured at.
+///
+/// Thin wrapper around [`SystemTime`] that is used for the timestamps of the
+/// [`SensorReadings`](super::SensorReadings) and the [`History`](super::History).
+/// With the `time` or `chrono` feature enabled it can be converted from and
+/// into `time::OffsetDateTime` and `chrono::DateTime<Utc>`, so the readings
+/// can be passed to time-series tooling without re-wrapping them.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Timestamp(SystemTime);
+
+impl Timestamp {
+    /// The unix epoch (`1970-01-01 00:00:00 UTC`).
+    pub const UNIX_EPOCH: Self = Self(UNIX_EPOCH);
+
+    /// Returns the current point in time.
+    #[must_use]
+    pub fn now() -> Self {
+        Self(SystemTime::now())
+    }
+
+    /// Returns the timestamp as [`SystemTime`].
+    #[must_use]
+    pub fn as_system_time(&self) -> SystemTime {
+        self.0
+    }
+
+    /// Returns the (signed) number of seconds since the unix epoch.
+    #[must_use]
+    pub fn unix_seconds(&self) -> f64 {
+        match self.0.duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs_f64(),
+            Err(err) => -err.duration().as_secs_f64(),
+        }
+    }
+
+    /// Returns the (signed) number of milliseconds since the unix epoch.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn unix_millis(&self) -> i64 {
+        match self.0.duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_millis() as i64,
+            Err(err) => -(err.duration().as_millis() as i64),
+        }
+    }
+
+    /// Creates a timestamp from the passed number of milliseconds since the
+    /// unix epoch.
+    ///
+    /// Returns `None` if the point in time can not be represented by the
+    /// [`SystemTime`] of the platform (e.g. on Windows, where it starts in
+    /// the year 1601).
+    #[must_use]
+    pub fn from_unix_millis(millis: i64) -> Option<Self> {
+        let offset = Duration::from_millis(millis.unsigned_abs());
+
+        if millis < 0 {
+            UNIX_EPOCH.checked_sub(offset).map(Self)
+        } else {
+            UNIX_EPOCH.checked_add(offset).map(Self)
+        }
+    }
+}
+
+impl From<SystemTime> for Timestamp {
+    fn from(value: SystemTime) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Timestamp> for SystemTime {
+    fn from(value: Timestamp) -> Self {
+        value.0
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for Timestamp {
+    fn from(value: time::OffsetDateTime) -> Self {
+        Self(value.into())
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<Timestamp> for time::OffsetDateTime {
+    fn from(value: Timestamp) -> Self {
+        value.0.into()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for Timestamp {
+    fn from(value: chrono::DateTime<Tz>) -> Self {
+        Self(value.into())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<Timestamp> for chrono::DateTime<chrono::Utc> {
+    fn from(value: Timestamp) -> Self {
+        value.0.into()
+    }
+}
@@ -0,0 +1,147 @@
+use std::time::{Duration, SystemTime};
+
+use super::{Channel, SensorReadings};
+
+/// Persistent state of a [`Totalizer`].
+///
+/// The state can be converted to and from a fixed size byte array using
+/// [`to_bytes`](Self::to_bytes) and [`from_bytes`](Self::from_bytes), so
+/// callers can store it wherever they like and restore the counter after a
+/// restart of the application or a reset of the device.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TotalizerState {
+    /// Total volume in liters (l).
+    pub volume: f64,
+
+    /// Point in time the counter was started at.
+    pub started_at: SystemTime,
+}
+
+impl TotalizerState {
+    /// Size of the serialized state in bytes.
+    pub const SIZE: usize = 16;
+
+    /// Creates a new empty state started at the passed point in time.
+    #[must_use]
+    pub fn new(started_at: SystemTime) -> Self {
+        Self {
+            volume: 0.0,
+            started_at,
+        }
+    }
+
+    /// Converts the state into its binary representation (big-endian volume
+    /// as IEEE 754 double, followed by the start time in seconds since the
+    /// unix epoch).
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let started_at = self
+            .started_at
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let mut bytes = [0; Self::SIZE];
+        bytes[..8].copy_from_slice(&self.volume.to_bits().to_be_bytes());
+        bytes[8..].copy_from_slice(&started_at.to_be_bytes());
+
+        bytes
+    }
+
+    /// Creates the state from its binary representation
+    /// (see [`to_bytes`](Self::to_bytes)).
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        let mut volume = [0; 8];
+        let mut started_at = [0; 8];
+        volume.copy_from_slice(&bytes[..8]);
+        started_at.copy_from_slice(&bytes[8..]);
+
+        Self {
+            volume: f64::from_bits(u64::from_be_bytes(volume)),
+            started_at: SystemTime::UNIX_EPOCH
+                + Duration::from_secs(u64::from_be_bytes(started_at)),
+        }
+    }
+}
+
+/// Software volume counter.
+///
+/// Integrates the flow of the [`SensorReadings`] over time (trapezoidal rule)
+/// independent of the internal counter of the device. Gaps between two
+/// readings that are larger than [`max_gap`](Self::max_gap) are not
+/// integrated, so the counter does not extrapolate while the device was not
+/// polled.
+#[derive(Debug, Clone)]
+pub struct Totalizer {
+    state: TotalizerState,
+    max_gap: Duration,
+    last: Option<(SystemTime, f64)>,
+}
+
+impl Totalizer {
+    /// Default value for [`max_gap`](Self::max_gap).
+    pub const DEFAULT_MAX_GAP: Duration = Duration::from_mins(1);
+
+    /// Creates a new totalizer starting at zero.
+    #[must_use]
+    pub fn new(started_at: SystemTime) -> Self {
+        Self::from_state(TotalizerState::new(started_at))
+    }
+
+    /// Creates a new totalizer that continues counting from the passed `state`.
+    #[must_use]
+    pub fn from_state(state: TotalizerState) -> Self {
+        Self {
+            state,
+            max_gap: Self::DEFAULT_MAX_GAP,
+            last: None,
+        }
+    }
+
+    /// Set the maximum gap between two readings that is still integrated.
+    #[must_use]
+    pub fn max_gap(mut self, max_gap: Duration) -> Self {
+        self.max_gap = max_gap;
+
+        self
+    }
+
+    /// Returns the current state of the totalizer that should be persisted.
+    #[must_use]
+    pub fn state(&self) -> TotalizerState {
+        self.state
+    }
+
+    /// Returns the total volume in liters (l).
+    #[must_use]
+    pub fn volume(&self) -> f64 {
+        self.state.volume
+    }
+
+    /// Adds the flow of the passed `readings` to the counter.
+    pub fn push(&mut self, readings: &SensorReadings) {
+        let Some(flow) = readings.value(Channel::Flow) else {
+            return;
+        };
+
+        let now = readings.captured_at;
+        if let Some((time, prev)) = self.last {
+            if let Ok(elapsed) = now.duration_since(time) {
+                if elapsed <= self.max_gap {
+                    let hours = elapsed.as_secs_f64() / 3600.0;
+
+                    self.state.volume += f64::midpoint(prev, flow) * hours;
+                }
+            }
+        }
+
+        self.last = Some((now, flow));
+    }
+
+    /// Resets the counter to zero.
+    pub fn reset(&mut self, started_at: SystemTime) {
+        self.state = TotalizerState::new(started_at);
+        self.last = None;
+    }
+}
@@ -0,0 +1,120 @@
+use std::time::{Duration, SystemTime};
+
+use super::SensorReadings;
+
+/// Event emitted by the [`Watchdog`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum WatchdogEvent {
+    /// No fresh readings arrived within the deadline.
+    Stale {
+        /// Point in time the last readings were captured at (`None` if no
+        /// readings arrived at all).
+        last_seen: Option<SystemTime>,
+
+        /// Point in time the readings were detected as stale.
+        at: SystemTime,
+    },
+
+    /// Fresh readings arrived after the data was stale.
+    Recovered {
+        /// Point in time the fresh readings were captured at.
+        at: SystemTime,
+
+        /// Duration the data was stale for.
+        after: Duration,
+    },
+}
+
+/// Detects stale data if no fresh readings arrived within a deadline (e.g.
+/// device unplugged or USB suspend).
+///
+/// Every received [`SensorReadings`] is passed to [`feed`](Self::feed), while
+/// [`check`](Self::check) is called periodically (independent of the
+/// readings) to detect the timeout.
+#[derive(Debug, Clone)]
+pub struct Watchdog {
+    deadline: Duration,
+    started_at: SystemTime,
+    last_seen: Option<SystemTime>,
+    stale_since: Option<SystemTime>,
+}
+
+impl Watchdog {
+    /// Creates a new watchdog that reports stale data if no readings arrived
+    /// within `deadline`, starting at `started_at`.
+    #[must_use]
+    pub fn new(deadline: Duration, started_at: SystemTime) -> Self {
+        Self {
+            deadline,
+            started_at,
+            last_seen: None,
+            stale_since: None,
+        }
+    }
+
+    /// Returns the deadline of the watchdog.
+    #[must_use]
+    pub fn deadline(&self) -> Duration {
+        self.deadline
+    }
+
+    /// Returns the point in time the last readings were captured at.
+    #[must_use]
+    pub fn last_seen(&self) -> Option<SystemTime> {
+        self.last_seen
+    }
+
+    /// Returns `true` if the data is currently stale.
+    #[must_use]
+    pub fn is_stale(&self) -> bool {
+        self.stale_since.is_some()
+    }
+
+    /// Restarts the watchdog at `started_at`, forgetting the last readings
+    /// and a detected stale state (e.g. after the host resumed from
+    /// suspend).
+    pub fn restart(&mut self, started_at: SystemTime) {
+        self.started_at = started_at;
+        self.last_seen = None;
+        self.stale_since = None;
+    }
+
+    /// Feeds fresh `readings` into the watchdog.
+    ///
+    /// Returns [`WatchdogEvent::Recovered`] if the data was stale before.
+    pub fn feed(&mut self, readings: &SensorReadings) -> Option<WatchdogEvent> {
+        let at = readings.captured_at;
+
+        self.last_seen = Some(self.last_seen.map_or(at, |last| last.max(at)));
+
+        let since = self.stale_since.take()?;
+
+        Some(WatchdogEvent::Recovered {
+            at,
+            after: at.duration_since(since).unwrap_or_default(),
+        })
+    }
+
+    /// Checks if the data is stale at `now`.
+    ///
+    /// Returns [`WatchdogEvent::Stale`] once when the deadline was exceeded.
+    pub fn check(&mut self, now: SystemTime) -> Option<WatchdogEvent> {
+        if self.stale_since.is_some() {
+            return None;
+        }
+
+        let reference = self.last_seen.unwrap_or(self.started_at);
+        let elapsed = now.duration_since(reference).unwrap_or_default();
+
+        if elapsed <= self.deadline {
+            return None;
+        }
+
+        self.stale_since = Some(reference + self.deadline);
+
+        Some(WatchdogEvent::Stale {
+            last_seen: self.last_seen,
+            at: now,
+        })
+    }
+}
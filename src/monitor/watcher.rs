@@ -0,0 +1,272 @@
+use std::collections::VecDeque;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::sync::mpsc::Sender;
+use std::time::{Duration, SystemTime};
+
+use super::{Channel, Comparison, SensorReadings};
+
+/// Condition a [`Watcher`] checks the [`SensorReadings`] against.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// The value of the channel is above or below the threshold
+    /// (e.g. "flow < 40 l/h").
+    Threshold {
+        /// Channel to check.
+        channel: Channel,
+
+        /// How to compare the value of the channel with the `threshold`.
+        comparison: Comparison,
+
+        /// Threshold in the physical unit of the channel.
+        threshold: f64,
+    },
+
+    /// The value of the channel dropped by at least `amount` compared to the
+    /// highest value within the last `within` (e.g. "quality dropped 5 % in
+    /// 10 min").
+    Drop {
+        /// Channel to check.
+        channel: Channel,
+
+        /// Minimum drop in the physical unit of the channel.
+        amount: f64,
+
+        /// Time window the drop is detected within.
+        within: Duration,
+    },
+
+    /// The value of the channel rose by at least `amount` compared to the
+    /// lowest value within the last `within`.
+    Rise {
+        /// Channel to check.
+        channel: Channel,
+
+        /// Minimum rise in the physical unit of the channel.
+        amount: f64,
+
+        /// Time window the rise is detected within.
+        within: Duration,
+    },
+}
+
+impl Condition {
+    /// Returns the channel the condition is checked against.
+    #[must_use]
+    pub fn channel(&self) -> Channel {
+        match self {
+            Self::Threshold { channel, .. }
+            | Self::Drop { channel, .. }
+            | Self::Rise { channel, .. } => *channel,
+        }
+    }
+}
+
+/// Identifier of a condition registered at the [`Watcher`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct WatchId(pub usize);
+
+/// Event emitted by the [`Watcher`] when a condition becomes true.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchEvent {
+    /// Identifier of the condition that became true.
+    pub id: WatchId,
+
+    /// Channel the condition is checked against.
+    pub channel: Channel,
+
+    /// Value of the channel that made the condition true.
+    pub value: f64,
+
+    /// Point in time the condition became true.
+    pub at: SystemTime,
+}
+
+/// Dispatches events for registered [`Condition`]s to closures or channels.
+///
+/// The [`SensorReadings`] are passed to [`push`](Self::push) by the polling
+/// loop. An event is only emitted when a condition becomes true, not for
+/// every reading it stays true.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::mpsc::channel;
+///
+/// use high_flow_next::monitor::{Channel, Comparison, Condition, Watcher};
+///
+/// let (sender, receiver) = channel();
+/// let mut watcher = Watcher::new();
+/// watcher.watch_channel(
+///     Condition::Threshold {
+///         channel: Channel::Flow,
+///         comparison: Comparison::Below,
+///         threshold: 40.0,
+///     },
+///     sender,
+/// );
+/// ```
+#[derive(Default)]
+pub struct Watcher {
+    watches: Vec<Option<Watch>>,
+}
+
+impl Watcher {
+    /// Creates a new watcher without any conditions.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the passed `callback` to be called when `condition` becomes true.
+    pub fn watch<F>(&mut self, condition: Condition, callback: F) -> WatchId
+    where
+        F: FnMut(&WatchEvent) + Send + 'static,
+    {
+        self.add(condition, Handler::Callback(Box::new(callback)))
+    }
+
+    /// Registers the passed `sender` to receive an event when `condition`
+    /// becomes true.
+    ///
+    /// The condition is removed automatically once the receiver was dropped.
+    pub fn watch_channel(&mut self, condition: Condition, sender: Sender<WatchEvent>) -> WatchId {
+        self.add(condition, Handler::Sender(sender))
+    }
+
+    /// Removes the condition with the passed `id`.
+    ///
+    /// Returns `true` if the condition was registered.
+    pub fn unwatch(&mut self, id: WatchId) -> bool {
+        self.watches.get_mut(id.0).and_then(Option::take).is_some()
+    }
+
+    /// Returns the number of registered conditions.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.watches.iter().flatten().count()
+    }
+
+    /// Returns `true` if no conditions are registered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks all registered conditions against the passed `readings` and
+    /// dispatches the events of the conditions that became true.
+    pub fn push(&mut self, readings: &SensorReadings) {
+        for (i, slot) in self.watches.iter_mut().enumerate() {
+            let Some(watch) = slot else {
+                continue;
+            };
+
+            let channel = watch.condition.channel();
+            let Some(value) = readings.value(channel) else {
+                continue;
+            };
+
+            let at = readings.captured_at;
+            let matched = watch.check(at, value);
+            let triggered = matched && !watch.matched;
+            watch.matched = matched;
+
+            if !triggered {
+                continue;
+            }
+
+            let event = WatchEvent {
+                id: WatchId(i),
+                channel,
+                value,
+                at,
+            };
+
+            match &mut watch.handler {
+                Handler::Callback(callback) => callback(&event),
+                Handler::Sender(sender) => {
+                    if sender.send(event).is_err() {
+                        *slot = None;
+                    }
+                }
+            }
+        }
+    }
+
+    fn add(&mut self, condition: Condition, handler: Handler) -> WatchId {
+        self.watches.push(Some(Watch {
+            condition,
+            handler,
+            matched: false,
+            samples: VecDeque::new(),
+        }));
+
+        WatchId(self.watches.len() - 1)
+    }
+}
+
+impl Debug for Watcher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Watcher")
+            .field(
+                "conditions",
+                &self
+                    .watches
+                    .iter()
+                    .flatten()
+                    .map(|x| &x.condition)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+struct Watch {
+    condition: Condition,
+    handler: Handler,
+    matched: bool,
+    samples: VecDeque<(SystemTime, f64)>,
+}
+
+impl Watch {
+    fn check(&mut self, at: SystemTime, value: f64) -> bool {
+        match self.condition {
+            Condition::Threshold {
+                comparison,
+                threshold,
+                ..
+            } => match comparison {
+                Comparison::Above => value > threshold,
+                Comparison::Below => value < threshold,
+            },
+            Condition::Drop { amount, within, .. } => {
+                self.record(at, value, within);
+
+                let max = self.samples.iter().map(|(_, x)| *x).fold(value, f64::max);
+
+                max - value >= amount
+            }
+            Condition::Rise { amount, within, .. } => {
+                self.record(at, value, within);
+
+                let min = self.samples.iter().map(|(_, x)| *x).fold(value, f64::min);
+
+                value - min >= amount
+            }
+        }
+    }
+
+    fn record(&mut self, at: SystemTime, value: f64, within: Duration) {
+        self.samples.push_back((at, value));
+
+        if let Some(limit) = at.checked_sub(within) {
+            while self.samples.front().is_some_and(|(time, _)| *time < limit) {
+                self.samples.pop_front();
+            }
+        }
+    }
+}
+
+enum Handler {
+    Callback(Box<dyn FnMut(&WatchEvent) + Send>),
+    Sender(Sender<WatchEvent>),
+}
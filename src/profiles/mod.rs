@@ -0,0 +1,309 @@
+//! Library of named lighting profiles.
+//!
+//! A [`ProfileLibrary`] stores multiple named [`Profile`]s in a directory on
+//! disk, one TOML file per profile (`<name>.toml`). A profile either contains
+//! only the [`LightingSettings`] (e.g. "gaming", "silent night" or "alarm")
+//! or the full [`Settings`] of the device. Profiles containing the full
+//! settings use the versioned configuration format (see [`ConfigFormat`]),
+//! lighting profiles only contain the `version` and the `lighting` table of
+//! it.
+//!
+//! [`Device::apply`] loads a profile and merges it into the settings read
+//! from the device, so profiles can be switched by scripts or a hotkey
+//! daemon. The [`ProfileSwitcher`] switches profiles automatically based on
+//! the time of day (or sunrise and sunset with the `solar` feature).
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use high_flow_next::profiles::{Profile, ProfileLibrary};
+//! # let lighting: high_flow_next::protocol::settings::LightingSettings = unimplemented!();
+//!
+//! let library = ProfileLibrary::new("/home/user/.config/high_flow_next/profiles");
+//! library.save("gaming", &Profile::Lighting(lighting))?;
+//!
+//! for name in library.names()? {
+//!     println!("{name}");
+//! }
+//! # Ok::<(), high_flow_next::profiles::ProfileError>(())
+//! ```
+
+mod switcher;
+
+use std::fs::{read_dir, read_to_string, remove_file, write};
+use std::io::{Error as StdIoError, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::device::{Device, Transport};
+use crate::misc::IoError;
+use crate::protocol::settings::{ConfigError, ConfigFormat, LightingSettings, CONFIG_VERSION};
+use crate::protocol::Settings;
+
+#[cfg(feature = "solar")]
+pub use self::switcher::Location;
+pub use self::switcher::{LocalTime, ProfileSwitcher, SwitchTime};
+
+/// File extension of the profile files.
+pub const PROFILE_EXTENSION: &str = "toml";
+
+/// Error returned while loading, storing or applying a profile.
+#[derive(Debug, Error)]
+pub enum ProfileError {
+    /// The name is not a valid profile name.
+    ///
+    /// Names must not be empty, must not start with a `.` and must not
+    /// contain path separators.
+    #[error("Invalid profile name: {0:?}")]
+    InvalidName(String),
+
+    /// The library does not contain a profile with the passed name.
+    #[error("Unknown profile: {0:?}")]
+    NotFound(String),
+
+    /// Error while reading or writing the profile file.
+    #[error("Config Error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    /// Error while accessing the profile directory.
+    #[error("IO Error: {0}")]
+    StdIoError(#[from] StdIoError),
+
+    /// Error while reading the settings from the device.
+    #[error("Device Error: {0}")]
+    DeviceError(#[from] IoError),
+}
+
+/// A named profile stored in a [`ProfileLibrary`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Profile {
+    /// Profile that only replaces the lighting settings.
+    Lighting(LightingSettings),
+
+    /// Profile that replaces all settings.
+    Settings(Settings),
+}
+
+impl Profile {
+    /// Returns the lighting settings of the profile.
+    #[must_use]
+    pub fn lighting(&self) -> Option<&LightingSettings> {
+        match self {
+            Self::Lighting(lighting) => Some(lighting),
+            Self::Settings(settings) => settings.lighting.as_ref(),
+        }
+    }
+
+    /// Merges the profile into the passed `settings`.
+    pub fn apply_to(&self, settings: &mut Settings) {
+        match self {
+            Self::Lighting(lighting) => settings.lighting = Some(lighting.clone()),
+            Self::Settings(profile) => settings.clone_from(profile),
+        }
+    }
+
+    /// Writes the profile in the format of the profile files.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the profile could not be serialized.
+    pub fn to_config_str(&self) -> Result<String, ConfigError> {
+        match self {
+            Self::Lighting(lighting) => Ok(toml::to_string_pretty(&LightingFile {
+                version: CONFIG_VERSION,
+                lighting: lighting.clone(),
+            })?),
+            Self::Settings(settings) => settings.to_config_str(ConfigFormat::Toml),
+        }
+    }
+
+    /// Reads a profile from the format of the profile files.
+    ///
+    /// Files containing a `system` table are read as full settings,
+    /// otherwise only the `lighting` table is read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file could not be parsed, was written with an
+    /// unsupported version or contains invalid values.
+    pub fn from_config_str(s: &str) -> Result<Self, ConfigError> {
+        let Header { version, system } = toml::from_str(s)?;
+        if version > CONFIG_VERSION {
+            return Err(ConfigError::UnsupportedVersion(version));
+        }
+
+        if system.is_some() {
+            Ok(Self::Settings(Settings::from_config_str(
+                s,
+                ConfigFormat::Toml,
+            )?))
+        } else {
+            let LightingFile { lighting, .. } = toml::from_str(s)?;
+
+            Ok(Self::Lighting(lighting))
+        }
+    }
+}
+
+/// Directory of named [`Profile`]s.
+///
+/// See the [module documentation](self) for details.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ProfileLibrary {
+    dir: PathBuf,
+}
+
+impl ProfileLibrary {
+    /// Creates a new library that stores its profiles in `dir`.
+    ///
+    /// The directory is created when the first profile is saved.
+    #[must_use]
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Returns the directory the profiles are stored in.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the path of the file of the profile `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProfileError::InvalidName`] if `name` is not a valid profile
+    /// name.
+    pub fn path(&self, name: &str) -> Result<PathBuf, ProfileError> {
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+            return Err(ProfileError::InvalidName(name.into()));
+        }
+
+        Ok(self.dir.join(format!("{name}.{PROFILE_EXTENSION}")))
+    }
+
+    /// Returns the sorted names of all profiles of the library.
+    ///
+    /// A library whose directory does not exist yet is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory could not be read.
+    pub fn names(&self) -> Result<Vec<String>, ProfileError> {
+        let entries = match read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut names = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|x| x.to_str()) != Some(PROFILE_EXTENSION) {
+                continue;
+            }
+
+            if let Some(name) = path.file_stem().and_then(|x| x.to_str()) {
+                names.push(name.to_owned());
+            }
+        }
+
+        names.sort();
+
+        Ok(names)
+    }
+
+    /// Loads the profile `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProfileError::NotFound`] if the library does not contain the
+    /// profile, or an error if the file could not be read.
+    pub fn load(&self, name: &str) -> Result<Profile, ProfileError> {
+        let s = match read_to_string(self.path(name)?) {
+            Ok(s) => s,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                return Err(ProfileError::NotFound(name.into()))
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(Profile::from_config_str(&s)?)
+    }
+
+    /// Stores the passed `profile` as `name`, replacing an existing profile
+    /// with the same name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is invalid or the file could not be
+    /// written.
+    pub fn save(&self, name: &str, profile: &Profile) -> Result<(), ProfileError> {
+        let path = self.path(name)?;
+        let s = profile.to_config_str()?;
+
+        std::fs::create_dir_all(&self.dir)?;
+        write(path, s)?;
+
+        Ok(())
+    }
+
+    /// Removes the profile `name` from the library.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProfileError::NotFound`] if the library does not contain the
+    /// profile, or an error if the file could not be removed.
+    pub fn remove(&self, name: &str) -> Result<(), ProfileError> {
+        match remove_file(self.path(name)?) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                Err(ProfileError::NotFound(name.into()))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+impl<T> Device<T>
+where
+    T: Transport,
+{
+    /// Loads the profile `profile_name` from the passed `library` and merges
+    /// it into the settings currently stored on the device.
+    ///
+    /// The merged settings are returned instead of being written to the
+    /// device (see the [`device`](crate::device) module).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the profile could not be loaded or the current
+    /// settings could not be read from the device.
+    pub fn apply(
+        &mut self,
+        library: &ProfileLibrary,
+        profile_name: &str,
+    ) -> Result<Settings, ProfileError> {
+        let profile = library.load(profile_name)?;
+        let mut settings = self.read_settings()?;
+        profile.apply_to(&mut settings);
+
+        Ok(settings)
+    }
+}
+
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+    #[serde(default)]
+    system: Option<IgnoredAny>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LightingFile {
+    version: u32,
+    lighting: LightingSettings,
+}
@@ -0,0 +1,309 @@
+use std::time::Duration;
+
+use crate::device::{Device, Transport};
+use crate::protocol::Settings;
+
+use super::{ProfileError, ProfileLibrary};
+
+/// Length of a day, used to wrap the time of day.
+const DAY: Duration = Duration::from_hours(24);
+
+/// Local wall-clock time passed to the [`ProfileSwitcher`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct LocalTime {
+    /// Day of the year (1 for January 1st).
+    pub day_of_year: u16,
+
+    /// Time since midnight.
+    pub time_of_day: Duration,
+}
+
+impl LocalTime {
+    /// Creates a new local time.
+    #[must_use]
+    pub fn new(day_of_year: u16, time_of_day: Duration) -> Self {
+        Self {
+            day_of_year,
+            time_of_day,
+        }
+    }
+}
+
+/// Point in time a rule of the [`ProfileSwitcher`] becomes active at.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SwitchTime {
+    /// Fixed time since midnight.
+    At(Duration),
+
+    /// Sunrise at the `Location` of the switcher.
+    ///
+    /// Only resolved with the `solar` feature, the rule is ignored otherwise.
+    Sunrise,
+
+    /// Sunset at the `Location` of the switcher.
+    ///
+    /// Only resolved with the `solar` feature, the rule is ignored otherwise.
+    Sunset,
+}
+
+/// Geographic location used to calculate sunrise and sunset.
+#[cfg(feature = "solar")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Location {
+    /// Latitude in degrees (positive north of the equator).
+    pub latitude: f64,
+
+    /// Longitude in degrees (positive east of Greenwich).
+    pub longitude: f64,
+
+    /// Offset of the local time to UTC in minutes (e.g. `60` for CET).
+    pub utc_offset_minutes: i32,
+}
+
+#[cfg(feature = "solar")]
+impl Location {
+    /// Creates a new location.
+    #[must_use]
+    pub fn new(latitude: f64, longitude: f64, utc_offset_minutes: i32) -> Self {
+        Self {
+            latitude,
+            longitude,
+            utc_offset_minutes,
+        }
+    }
+
+    /// Returns the local time of the sunrise at the passed `day_of_year`.
+    ///
+    /// Returns `None` if the sun does not rise or set at this day (polar day
+    /// or night).
+    #[must_use]
+    pub fn sunrise(&self, day_of_year: u16) -> Option<Duration> {
+        self.solar_event(day_of_year, 1.0)
+    }
+
+    /// Returns the local time of the sunset at the passed `day_of_year`.
+    ///
+    /// Returns `None` if the sun does not rise or set at this day (polar day
+    /// or night).
+    #[must_use]
+    pub fn sunset(&self, day_of_year: u16) -> Option<Duration> {
+        self.solar_event(day_of_year, -1.0)
+    }
+
+    /// Calculates sunrise (`sign = 1`) or sunset (`sign = -1`) using the
+    /// NOAA approximation, which is accurate to a few minutes.
+    fn solar_event(&self, day_of_year: u16, sign: f64) -> Option<Duration> {
+        use std::f64::consts::PI;
+
+        let gamma = 2.0 * PI / 365.0 * (f64::from(day_of_year) - 1.0);
+        let eqtime = 229.18
+            * (0.000_075 + 0.001_868 * gamma.cos()
+                - 0.032_077 * gamma.sin()
+                - 0.014_615 * (2.0 * gamma).cos()
+                - 0.040_849 * (2.0 * gamma).sin());
+        let decl = 0.006_918 - 0.399_912 * gamma.cos() + 0.070_257 * gamma.sin()
+            - 0.006_758 * (2.0 * gamma).cos()
+            + 0.000_907 * (2.0 * gamma).sin()
+            - 0.002_697 * (3.0 * gamma).cos()
+            + 0.001_48 * (3.0 * gamma).sin();
+
+        let latitude = self.latitude.to_radians();
+        let cos_ha = 90.833_f64.to_radians().cos() / (latitude.cos() * decl.cos())
+            - latitude.tan() * decl.tan();
+        if !(-1.0..=1.0).contains(&cos_ha) {
+            return None;
+        }
+
+        let ha = cos_ha.acos().to_degrees();
+        let minutes = 720.0 - 4.0 * (self.longitude + sign * ha) - eqtime
+            + f64::from(self.utc_offset_minutes);
+        let minutes = minutes.rem_euclid(DAY.as_secs_f64() / 60.0);
+
+        Some(Duration::from_secs_f64(minutes * 60.0))
+    }
+}
+
+/// Switches between the profiles of a [`ProfileLibrary`] based on the
+/// wall-clock time (e.g. a "day" profile in the morning and a "night"
+/// profile in the evening).
+///
+/// Each rule activates its profile from its [`SwitchTime`] until the next
+/// rule of the day. With the `solar` feature enabled rules can also be bound
+/// to sunrise and sunset at a `Location`.
+///
+/// The switcher is meant to be executed as task of the
+/// [`Scheduler`](crate::monitor::Scheduler): [`poll`](Self::poll) only
+/// applies a profile if the target profile changed. The switcher does not
+/// write the settings itself (see the [`device`](crate::device) module), so
+/// the caller reports a successful write using
+/// [`mark_applied`](Self::mark_applied). Until then every call applies the
+/// profile again, so the scheduler takes care of the retries and their
+/// backoff.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use high_flow_next::profiles::{LocalTime, ProfileSwitcher};
+///
+/// let switcher = ProfileSwitcher::new()
+///     .at(Duration::from_hours(7), "day")
+///     .at(Duration::from_hours(22), "night");
+///
+/// let time = LocalTime::new(100, Duration::from_hours(1));
+/// assert_eq!(switcher.target(time), Some("night"));
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct ProfileSwitcher {
+    rules: Vec<(SwitchTime, String)>,
+    #[cfg(feature = "solar")]
+    location: Option<Location>,
+    active: Option<String>,
+}
+
+impl ProfileSwitcher {
+    /// Creates a new switcher without any rules.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule that activates the profile `name` at `time_of_day` (time
+    /// since midnight).
+    #[must_use]
+    pub fn at<S: Into<String>>(self, time_of_day: Duration, name: S) -> Self {
+        let time_of_day = Duration::from_secs(time_of_day.as_secs() % DAY.as_secs());
+
+        self.rule(SwitchTime::At(time_of_day), name)
+    }
+
+    /// Adds a rule that activates the profile `name` at the passed `time`.
+    #[must_use]
+    pub fn rule<S: Into<String>>(mut self, time: SwitchTime, name: S) -> Self {
+        self.rules.push((time, name.into()));
+
+        self
+    }
+
+    /// Sets the location used to calculate sunrise and sunset.
+    ///
+    /// Sunrise and sunset rules are ignored until a location is set.
+    #[cfg(feature = "solar")]
+    #[must_use]
+    pub fn location(mut self, location: Location) -> Self {
+        self.location = Some(location);
+
+        self
+    }
+
+    /// Adds a rule that activates the profile `name` at sunrise.
+    #[cfg(feature = "solar")]
+    #[must_use]
+    pub fn at_sunrise<S: Into<String>>(self, name: S) -> Self {
+        self.rule(SwitchTime::Sunrise, name)
+    }
+
+    /// Adds a rule that activates the profile `name` at sunset.
+    #[cfg(feature = "solar")]
+    #[must_use]
+    pub fn at_sunset<S: Into<String>>(self, name: S) -> Self {
+        self.rule(SwitchTime::Sunset, name)
+    }
+
+    /// Returns the name of the profile that was marked as applied last.
+    #[must_use]
+    pub fn active(&self) -> Option<&str> {
+        self.active.as_deref()
+    }
+
+    /// Marks the profile `name` as applied to the device, so
+    /// [`poll`](Self::poll) does not apply it again.
+    pub fn mark_applied(&mut self, name: &str) {
+        self.active = Some(name.to_owned());
+    }
+
+    /// Forgets the active profile, so the next call to [`poll`](Self::poll)
+    /// applies the target profile again (e.g. after the device was
+    /// reconnected).
+    pub fn reset(&mut self) {
+        self.active = None;
+    }
+
+    /// Returns the name of the profile that should be active at the passed
+    /// `time`.
+    ///
+    /// Before the first rule of the day the last rule of the previous day
+    /// applies. Returns `None` if no rule can be resolved.
+    #[must_use]
+    pub fn target(&self, time: LocalTime) -> Option<&str> {
+        let now = Duration::from_secs(time.time_of_day.as_secs() % DAY.as_secs());
+
+        let mut current = None::<(Duration, &str)>;
+        let mut last = None::<(Duration, &str)>;
+        for (rule, name) in &self.rules {
+            let Some(at) = self.resolve(*rule, time.day_of_year) else {
+                continue;
+            };
+
+            if at <= now && current.is_none_or(|(x, _)| at >= x) {
+                current = Some((at, name));
+            }
+            if last.is_none_or(|(x, _)| at >= x) {
+                last = Some((at, name));
+            }
+        }
+
+        current.or(last).map(|(_, name)| name)
+    }
+
+    /// Applies the target profile for the passed `time` to the `device`, if
+    /// it differs from the [`active`](Self::active) profile.
+    ///
+    /// Returns the name of the target profile and the merged settings (see
+    /// [`Device::apply`]), and `None` if the profile did not change. The
+    /// active profile is not changed, pass the name to
+    /// [`mark_applied`](Self::mark_applied) after the settings were written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the profile could not be applied.
+    pub fn poll<T>(
+        &mut self,
+        device: &mut Device<T>,
+        library: &ProfileLibrary,
+        time: LocalTime,
+    ) -> Result<Option<(String, Settings)>, ProfileError>
+    where
+        T: Transport,
+    {
+        let Some(target) = self.target(time) else {
+            return Ok(None);
+        };
+
+        if self.active.as_deref() == Some(target) {
+            return Ok(None);
+        }
+
+        let target = target.to_owned();
+        let settings = device.apply(library, &target)?;
+
+        Ok(Some((target, settings)))
+    }
+
+    #[cfg_attr(
+        not(feature = "solar"),
+        allow(unused_variables, clippy::unused_self, clippy::unnecessary_wraps)
+    )]
+    fn resolve(&self, time: SwitchTime, day_of_year: u16) -> Option<Duration> {
+        match time {
+            SwitchTime::At(at) => Some(at),
+            #[cfg(feature = "solar")]
+            SwitchTime::Sunrise => self.location?.sunrise(day_of_year),
+            #[cfg(feature = "solar")]
+            SwitchTime::Sunset => self.location?.sunset(day_of_year),
+            #[cfg(not(feature = "solar"))]
+            SwitchTime::Sunrise | SwitchTime::Sunset => None,
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+use crate::misc::{checksum, IoError, Reader};
+
+use super::{decode_frame, Frame, FrameBuilder};
+
+/// A frame with an op code that is not supported by [`Frame`].
+///
+/// Returned by [`FrameHooks::decode`] for op codes registered at runtime. The
+/// checksum of the frame is already verified.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RawFrame {
+    /// Op code of the frame.
+    pub op_code: u8,
+
+    /// Payload of the frame (without the op code and the checksum).
+    pub payload: Vec<u8>,
+}
+
+impl RawFrame {
+    /// Encodes the frame (see [`FrameBuilder::build`]).
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        FrameBuilder::new(self.op_code).bytes(&self.payload).build()
+    }
+}
+
+/// Frame decoded by [`FrameHooks::decode`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum HookedFrame {
+    /// A frame with an op code supported by this crate.
+    Frame(Box<Frame>),
+
+    /// A frame with an op code registered using [`FrameHooks::register`].
+    Raw(RawFrame),
+}
+
+/// Decoder that accepts experimental op codes registered at runtime.
+///
+/// The framing of experimental frames (op code, payload, checksum) is the
+/// same as for the known frames, only the size of the payload has to be
+/// registered for each op code. The registered hook is invoked for every
+/// decoded frame with that op code, so its payload can be inspected (or
+/// rejected by returning an error) without forking the crate. Op codes
+/// supported by [`Frame`] are decoded as usual and can not be overridden.
+///
+/// # Example
+///
+/// ```rust
+/// use high_flow_next::protocol::{FrameBuilder, FrameHooks, HookedFrame};
+///
+/// let mut hooks = FrameHooks::new();
+/// hooks.register(0x42, 2, |frame| {
+///     println!("Experimental frame: {:02X?}", frame.payload);
+///
+///     Ok(())
+/// });
+///
+/// let data = FrameBuilder::new(0x42).u16be(0x1234).build();
+/// let frame = hooks.decode(&mut &data[..]).unwrap();
+///
+/// assert!(matches!(frame, HookedFrame::Raw(raw) if raw.payload == [0x12, 0x34]));
+/// ```
+#[derive(Default)]
+pub struct FrameHooks {
+    hooks: BTreeMap<u8, Hook>,
+}
+
+struct Hook {
+    payload_len: usize,
+    run: HookFn,
+}
+
+type HookFn = Box<dyn FnMut(&RawFrame) -> Result<(), IoError>>;
+
+impl FrameHooks {
+    /// Creates a new decoder without any registered op codes.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the experimental `op_code` with a payload of `payload_len`
+    /// bytes, and the `hook` that is invoked for every decoded frame.
+    ///
+    /// Replaces the hook previously registered for the op code. Returns
+    /// `false` (and does not register anything) if the op code is supported
+    /// by [`Frame`].
+    pub fn register<F>(&mut self, op_code: u8, payload_len: usize, hook: F) -> bool
+    where
+        F: FnMut(&RawFrame) -> Result<(), IoError> + 'static,
+    {
+        if Frame::size(op_code).is_some() {
+            return false;
+        }
+
+        self.hooks.insert(
+            op_code,
+            Hook {
+                payload_len,
+                run: Box::new(hook),
+            },
+        );
+
+        true
+    }
+
+    /// Removes the hook registered for `op_code`.
+    ///
+    /// Returns `true` if a hook was registered.
+    pub fn unregister(&mut self, op_code: u8) -> bool {
+        self.hooks.remove(&op_code).is_some()
+    }
+
+    /// Returns the size in bytes of a frame with the passed `op_code`
+    /// (including the op code and the checksum), or `None` if the op code is
+    /// neither supported nor registered (see [`Frame::size`]).
+    #[must_use]
+    pub fn size(&self, op_code: u8) -> Option<usize> {
+        Frame::size(op_code).or_else(|| {
+            self.hooks
+                .get(&op_code)
+                .map(|hook| 1 + hook.payload_len + 2)
+        })
+    }
+
+    /// Decodes a single frame from the start of `reader` and advances it to
+    /// the end of the frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the frame could not be decoded, the checksum does
+    /// not match, or the hook of the op code returned an error.
+    pub fn decode(&mut self, reader: &mut &[u8]) -> Result<HookedFrame, IoError> {
+        let Some(hook) = reader
+            .first()
+            .and_then(|op_code| self.hooks.get_mut(op_code))
+        else {
+            return Ok(HookedFrame::Frame(Box::new(decode_frame(reader)?)));
+        };
+
+        let op_code = reader.read_u8()?;
+        let mut payload = vec![0; hook.payload_len];
+        reader.read_exact(&mut payload)?;
+
+        let crc_expected = reader.read_u16be()?;
+        if checksum(&payload) != crc_expected {
+            return Err(IoError::ChecksumMismatch);
+        }
+
+        let frame = RawFrame { op_code, payload };
+        (hook.run)(&frame)?;
+
+        Ok(HookedFrame::Raw(frame))
+    }
+}
+
+impl Debug for FrameHooks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("FrameHooks")
+            .field("op_codes", &self.hooks.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
@@ -0,0 +1,121 @@
+use crate::misc::IoError;
+
+use super::{decode_frame, Frame, ResyncDecoder};
+
+/// Limits applied while decoding frames from untrusted sources.
+///
+/// Frames may be received from bridges or capture files that are not under
+/// the control of the application. The limits cap the amount of work and
+/// memory spent on such input. If a limit is hit, decoding fails with
+/// [`IoError::LimitExceeded`] carrying the name of the field of the limit
+/// (e.g. `max_frames`) and the actual value.
+///
+/// The [`Default`] limits are unlimited, so decoding with the default limits
+/// behaves exactly like [`decode_frames`](super::decode_frames).
+///
+/// # Example
+///
+/// ```rust
+/// use high_flow_next::protocol::DecodeLimits;
+///
+/// let limits = DecodeLimits {
+///     max_input_len: 64 * 1024,
+///     max_frames: 16,
+///     ..DecodeLimits::default()
+/// };
+///
+/// assert!(limits.decode_frames(&[0x03; 128 * 1024]).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct DecodeLimits {
+    /// Maximum number of bytes of the input data.
+    pub max_input_len: usize,
+
+    /// Maximum number of bytes of the payload of a single frame (without the
+    /// op code and the checksum).
+    pub max_payload_len: usize,
+
+    /// Maximum number of frames decoded from the input.
+    pub max_frames: usize,
+
+    /// Maximum number of lighting controllers (strip and sensor) of a
+    /// settings frame.
+    pub max_controllers: usize,
+
+    /// Maximum number of bytes the [`ResyncDecoder`] discards while
+    /// searching for the next frame.
+    pub max_discarded: usize,
+}
+
+impl DecodeLimits {
+    /// Limits that never trigger.
+    pub const UNLIMITED: Self = Self {
+        max_input_len: usize::MAX,
+        max_payload_len: usize::MAX,
+        max_frames: usize::MAX,
+        max_controllers: usize::MAX,
+        max_discarded: usize::MAX,
+    };
+
+    /// Decodes all frames stored back to back in `data` (see
+    /// [`decode_frames`](super::decode_frames)) while enforcing the limits.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IoError::LimitExceeded`] if one of the limits is hit, or any
+    /// other error returned by [`decode_frames`](super::decode_frames).
+    pub fn decode_frames(&self, data: &[u8]) -> Result<Vec<Frame>, IoError> {
+        check("max_input_len", self.max_input_len, data.len())?;
+
+        let mut reader = data;
+        let mut frames = Vec::new();
+
+        while !reader.is_empty() {
+            check("max_frames", self.max_frames, frames.len() + 1)?;
+
+            frames.push(self.decode_frame(&mut reader)?);
+        }
+
+        Ok(frames)
+    }
+
+    /// Creates a [`ResyncDecoder`] for the frames stored in `data` that
+    /// enforces the limits (see [`ResyncDecoder::with_limits`]).
+    #[must_use]
+    pub fn decode_frames_resync<'a>(&self, data: &'a [u8]) -> ResyncDecoder<'a> {
+        super::decode_frames_resync(data).with_limits(*self)
+    }
+
+    /// Decodes a single frame from the start of `reader` while enforcing the
+    /// payload and controller limits.
+    pub(super) fn decode_frame(&self, reader: &mut &[u8]) -> Result<Frame, IoError> {
+        if let Some(size) = reader.first().copied().and_then(Frame::size) {
+            check("max_payload_len", self.max_payload_len, size - 3)?;
+        }
+
+        let frame = decode_frame(reader)?;
+
+        let Frame::Settings(settings) = &frame;
+        let controllers = settings.lighting.as_ref().map_or(0, |x| {
+            x.strip_controllers.len() + x.sensor_controllers.len()
+        });
+        check("max_controllers", self.max_controllers, controllers)?;
+
+        Ok(frame)
+    }
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self::UNLIMITED
+    }
+}
+
+/// Returns an error if `actual` exceeds the limit `max` named `name`.
+pub(super) fn check(name: &'static str, max: usize, actual: usize) -> Result<(), IoError> {
+    if actual > max {
+        Err(IoError::LimitExceeded(name, actual))
+    } else {
+        Ok(())
+    }
+}
@@ -2,11 +2,34 @@
 //!
 //! This module defines the wire protocol used by the device and
 //! provides encoding and decoding support for binary frames.
+//!
+//! For protocol experiments, the [`FrameBuilder`] composes frames with
+//! arbitrary op codes and payloads, and [`FrameHooks`] decodes frames with
+//! op codes that are registered at runtime. Downstream crates can register
+//! typed decoders for the op codes of other devices that share the framing in
+//! a [`ProtocolRegistry`].
+//!
+//! Differences between the firmware versions of the device are described in
+//! the [`QuirkDatabase`].
 
 pub mod settings;
 
-use crate::misc::{CrcReader, Decode, Guard, GuardOutput, IoError, Reader};
+mod hooks;
+mod limits;
+mod quirks;
+mod registry;
+mod resync;
+
+use crate::core::{self, FrameSet};
+use crate::misc::{Decode, FixedSize, Guard, GuardOutput, IoError, Reader};
+
+pub use crate::core::FrameBuilder;
 
+pub use self::hooks::{FrameHooks, HookedFrame, RawFrame};
+pub use self::limits::DecodeLimits;
+pub use self::quirks::{Quirk, QuirkDatabase, QuirkError, QuirkRule};
+pub use self::registry::{AnyFrame, ExtensionFrame, ProtocolRegistry};
+pub use self::resync::{decode_frames_resync, ResyncDecoder, ResyncedFrame};
 pub use self::settings::Settings;
 
 /// A top-level protocol frame received from or sent to the device.
@@ -18,6 +41,8 @@ pub use self::settings::Settings;
 /// Currently supported:
 /// - `0x03` → [`Frame::Settings`]
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Frame {
     /// Frame carrying the full device settings (decoded into [`Settings`]).
     Settings(Settings),
@@ -25,27 +50,108 @@ pub enum Frame {
 
 impl Decode for Frame {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
-        // Read the operation code
-        let op_code = reader.read_u8()?;
-        let mut crc = CrcReader::new(reader);
+        core::read_frame(reader)
+    }
+}
+
+impl Frame {
+    /// Returns the size in bytes of a frame with the passed `op_code`
+    /// (including the op code and the checksum), or `None` if the op code is
+    /// unknown.
+    #[must_use]
+    pub fn size(op_code: u8) -> Option<usize> {
+        match op_code {
+            0x03 => Some(1 + Settings::SIZE + 2),
+            _ => None,
+        }
+    }
+}
+
+impl FrameSet for Frame {
+    fn frame_size(op_code: u8) -> Option<usize> {
+        Self::size(op_code)
+    }
 
+    fn decode_payload<R: Reader>(
+        op_code: u8,
+        reader: &mut R,
+    ) -> Result<GuardOutput<R, Self>, IoError> {
         // Dispatch based on op code
-        let ret = match op_code {
+        match op_code {
             0x03 => {
-                let ret = Settings::decode(&mut crc)?;
-                R::guard(|x| Self::Settings(x.extract(ret)))
+                let ret = Settings::decode(reader)?;
+
+                Ok(R::guard(|x| Self::Settings(x.extract(ret))))
             }
-            op_code => Err(IoError::InvalidValue("OpCode", op_code.into()))?,
-        };
+            op_code => Err(IoError::InvalidValue("OpCode", op_code.into())),
+        }
+    }
+}
 
-        // Verify CRC
-        let crc_actual = crc.finalize();
-        let crc_expceted = reader.read_u16be()?;
+/// Decodes all frames stored back to back in `data`.
+///
+/// Intended for capture files containing a large number of frames: the frames
+/// are decoded directly from the slice without any intermediate buffers, the
+/// checksum of each frame is calculated over the whole payload at once (which
+/// profits from the `fast-crc` feature), and the result vector is allocated
+/// once based on the size of the first frame.
+///
+/// # Errors
+///
+/// Returns an error if one of the frames could not be decoded. Trailing data
+/// that does not form a complete frame is reported as I/O error as well.
+pub fn decode_frames(data: &[u8]) -> Result<Vec<Frame>, IoError> {
+    core::decode_frames(data)
+}
+
+/// Decodes all frames stored back to back in `data` in parallel.
+///
+/// The data is split on frame boundaries (using [`Frame::size`]) and the
+/// frames are decoded in batches on the `rayon` thread pool. The returned
+/// iterator yields the frames in the order they are stored in `data`, so only
+/// one batch of decoded frames is kept in memory at a time.
+///
+/// If a frame could not be decoded, the error is yielded at its position. If
+/// the op code of a frame is unknown (or the data is truncated) the boundaries
+/// of the following frames are unknown as well, so the error is the last item
+/// of the iterator.
+#[cfg(feature = "rayon")]
+pub fn decode_frames_par(data: &[u8]) -> impl Iterator<Item = Result<Frame, IoError>> + '_ {
+    use rayon::prelude::*;
+
+    const BATCH_SIZE: usize = 4096;
 
-        if crc_actual != crc_expceted {
-            Err(IoError::ChecksumMismatch)?;
+    let mut remaining = data;
+    let mut chunks = std::iter::from_fn(move || {
+        if remaining.is_empty() {
+            return None;
         }
 
-        Ok(ret)
-    }
+        let size = Frame::size(remaining[0]).map_or(remaining.len(), |s| s.min(remaining.len()));
+        let (chunk, rest) = remaining.split_at(size);
+        remaining = rest;
+
+        Some(chunk)
+    });
+
+    std::iter::from_fn(move || {
+        let batch = chunks.by_ref().take(BATCH_SIZE).collect::<Vec<_>>();
+        if batch.is_empty() {
+            return None;
+        }
+
+        let frames = batch
+            .into_par_iter()
+            .map(|mut chunk| decode_frame(&mut chunk))
+            .collect::<Vec<_>>();
+
+        Some(frames)
+    })
+    .flatten()
+}
+
+/// Decodes a single frame from the start of `reader` and advances it to the
+/// end of the frame.
+fn decode_frame(reader: &mut &[u8]) -> Result<Frame, IoError> {
+    core::decode_frame(reader)
 }
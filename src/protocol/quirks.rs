@@ -0,0 +1,215 @@
+use std::borrow::Cow;
+
+use thiserror::Error;
+
+use crate::misc::IoError;
+
+use super::settings::{Access, Value, ValueError};
+use super::{decode_frames, Frame, Settings};
+
+/// Behavioral difference of a firmware compared to the settings model of this
+/// crate.
+///
+/// Fields are addressed by their [`Access`] path (e.g.
+/// `alarms.water_temperature_limit`).
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Quirk {
+    /// The field is not supported by the firmware, the decoded value is
+    /// meaningless and is cleared. Only optional fields can be absent.
+    Absent {
+        /// Path of the field.
+        path: Cow<'static, str>,
+    },
+
+    /// The firmware stores the field in a different scale. The decoded raw
+    /// value is multiplied by `numerator / denominator` to get the value in
+    /// the scale of this crate.
+    Scale {
+        /// Path of the field.
+        path: Cow<'static, str>,
+
+        /// Numerator of the scaling factor.
+        numerator: i64,
+
+        /// Denominator of the scaling factor.
+        denominator: i64,
+    },
+}
+
+/// A [`Quirk`] that applies to a range of firmware versions.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuirkRule {
+    /// First firmware version the quirk applies to.
+    pub min_firmware: u16,
+
+    /// Last firmware version the quirk applies to.
+    pub max_firmware: u16,
+
+    /// The behavioral difference.
+    pub quirk: Quirk,
+}
+
+impl QuirkRule {
+    /// Returns `true` if the rule applies to the passed `firmware` version.
+    #[must_use]
+    pub fn matches(&self, firmware: u16) -> bool {
+        (self.min_firmware..=self.max_firmware).contains(&firmware)
+    }
+}
+
+/// Error returned while applying the quirks of a firmware.
+#[derive(Debug, Error)]
+pub enum QuirkError {
+    /// The frames could not be decoded.
+    #[error("IO Error: {0}")]
+    IoError(#[from] IoError),
+
+    /// The quirk could not be applied to the field at the path.
+    #[error("Invalid quirk (path={0}): {1}")]
+    ValueError(String, ValueError),
+}
+
+/// Database of the [`Quirk`]s of the different firmware versions.
+///
+/// The decoding of this crate follows the latest known firmware. Differences
+/// of other firmware versions are described as data in the database and
+/// applied to the decoded settings, so supporting a new firmware only needs
+/// new [`QuirkRule`]s instead of version checks in the decoding logic. The
+/// [`builtin`](Self::builtin) database contains the known quirks, custom rules
+/// can be added (e.g. loaded from a file with the `serde` feature).
+///
+/// The quirks are only applied after decoding (see the
+/// [`device`](crate::device) module for the missing encoder).
+///
+/// # Example
+///
+/// ```rust
+/// use high_flow_next::protocol::{Quirk, QuirkDatabase, QuirkRule};
+///
+/// let mut quirks = QuirkDatabase::builtin();
+/// quirks.add(QuirkRule {
+///     min_firmware: 1000,
+///     max_firmware: 1009,
+///     quirk: Quirk::Absent {
+///         path: "alarms.external_temperature_limit".into(),
+///     },
+/// });
+///
+/// assert_eq!(quirks.quirks(1005).count(), 1);
+/// assert_eq!(quirks.quirks(1010).count(), 0);
+/// ```
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+pub struct QuirkDatabase {
+    rules: Vec<QuirkRule>,
+}
+
+impl QuirkDatabase {
+    /// Known quirks of the released firmware versions.
+    ///
+    /// No differences between the firmware versions are known yet.
+    pub const BUILTIN: &'static [QuirkRule] = &[];
+
+    /// Creates a new database without any rules.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new database containing the [`BUILTIN`](Self::BUILTIN) rules.
+    #[must_use]
+    pub fn builtin() -> Self {
+        Self {
+            rules: Self::BUILTIN.to_vec(),
+        }
+    }
+
+    /// Adds a rule to the database.
+    pub fn add(&mut self, rule: QuirkRule) {
+        self.rules.push(rule);
+    }
+
+    /// Returns all rules of the database.
+    #[must_use]
+    pub fn rules(&self) -> &[QuirkRule] {
+        &self.rules
+    }
+
+    /// Returns the quirks of the passed `firmware` version.
+    pub fn quirks(&self, firmware: u16) -> impl Iterator<Item = &Quirk> + '_ {
+        self.rules
+            .iter()
+            .filter(move |rule| rule.matches(firmware))
+            .map(|rule| &rule.quirk)
+    }
+
+    /// Applies the quirks of the passed `firmware` version to the decoded
+    /// `settings`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a quirk names an unknown field, or if the adjusted
+    /// value does not fit into the field.
+    pub fn apply(&self, firmware: u16, settings: &mut Settings) -> Result<(), QuirkError> {
+        for quirk in self.quirks(firmware) {
+            let (path, result) = match quirk {
+                Quirk::Absent { path } => (path, settings.set(path, Value::None)),
+                Quirk::Scale {
+                    path,
+                    numerator,
+                    denominator,
+                } => (path, scale(settings, path, *numerator, *denominator)),
+            };
+
+            result.map_err(|error| QuirkError::ValueError(path.to_string(), error))?;
+        }
+
+        Ok(())
+    }
+
+    /// Decodes all frames stored back to back in `data` (see
+    /// [`decode_frames`]) and applies the quirks of the passed `firmware`
+    /// version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the frames could not be decoded or the quirks
+    /// could not be applied.
+    pub fn decode_frames(&self, firmware: u16, data: &[u8]) -> Result<Vec<Frame>, QuirkError> {
+        let mut frames = decode_frames(data)?;
+
+        for Frame::Settings(settings) in &mut frames {
+            self.apply(firmware, settings)?;
+        }
+
+        Ok(frames)
+    }
+}
+
+/// Scales the numeric value of the field at `path`.
+fn scale(
+    settings: &mut Settings,
+    path: &str,
+    numerator: i64,
+    denominator: i64,
+) -> Result<(), ValueError> {
+    let scale = |x: i64| {
+        x.checked_mul(numerator)
+            .and_then(|x| x.checked_div(denominator))
+    };
+
+    let value = match settings.get(path).ok_or(ValueError::UnknownPath)? {
+        Value::None => return Ok(()),
+        Value::Integer(x) => scale(x).map(Value::Integer),
+        Value::Temperature(x) => scale(x).map(Value::Temperature),
+        Value::Flow(x) => scale(x).map(Value::Flow),
+        Value::Percent(x) => scale(x).map(Value::Percent),
+        value => return Err(ValueError::InvalidValue(value)),
+    };
+
+    settings.set(
+        path,
+        value.ok_or(ValueError::InvalidValue(Value::Integer(numerator)))?,
+    )
+}
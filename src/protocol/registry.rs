@@ -0,0 +1,187 @@
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::io::ErrorKind;
+
+use crate::misc::{checksum, Decode, FixedSize, IoError, Reader};
+
+use super::{decode_frame, Frame};
+
+/// Frame type of a downstream crate, decoded by the [`ProtocolRegistry`].
+///
+/// Implemented for every `'static` type that implements [`Debug`] and can be
+/// shared between threads. To be registered, the type has to implement
+/// [`Decode`] and [`FixedSize`] as well.
+pub trait ExtensionFrame: Any + Debug + Send + Sync {}
+
+impl<T> ExtensionFrame for T where T: Any + Debug + Send + Sync {}
+
+/// Frame decoded by [`ProtocolRegistry::decode_any`].
+#[derive(Debug)]
+pub enum AnyFrame {
+    /// A frame with an op code supported by this crate.
+    Frame(Box<Frame>),
+
+    /// A frame decoded by a decoder registered using
+    /// [`ProtocolRegistry::register`].
+    Extension {
+        /// Op code of the frame.
+        op_code: u8,
+
+        /// The decoded frame.
+        frame: Box<dyn ExtensionFrame>,
+    },
+}
+
+impl AnyFrame {
+    /// Returns the frame as `T` if it is an extension frame of type `T`.
+    #[must_use]
+    pub fn downcast_ref<T>(&self) -> Option<&T>
+    where
+        T: ExtensionFrame,
+    {
+        match self {
+            Self::Frame(_) => None,
+            Self::Extension { frame, .. } => (&**frame as &dyn Any).downcast_ref(),
+        }
+    }
+}
+
+/// Registry of decoders for op codes that are not supported by [`Frame`].
+///
+/// Other Aqua Computer USB devices use the same framing (op code, payload,
+/// CRC checksum). Downstream crates can register their own frame types for
+/// additional op codes and decode them using [`decode_any`](Self::decode_any)
+/// without changes to this crate. A frame type is any type implementing
+/// [`Decode`] and [`FixedSize`], the size is used as size of the payload.
+///
+/// # Example
+///
+/// ```rust
+/// use high_flow_next::protocol::{FrameBuilder, ProtocolRegistry};
+///
+/// let mut registry = ProtocolRegistry::new();
+/// registry.register::<u16>(0x42);
+///
+/// let data = FrameBuilder::new(0x42).u16be(1234).build();
+/// let frame = registry.decode_any(&mut &data[..]).unwrap();
+///
+/// assert_eq!(frame.downcast_ref::<u16>(), Some(&1234));
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct ProtocolRegistry {
+    decoders: BTreeMap<u8, Decoder>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Decoder {
+    payload_len: usize,
+    decode: DecodeFn,
+}
+
+type DecodeFn = fn(&[u8]) -> Result<Box<dyn ExtensionFrame>, IoError>;
+
+impl ProtocolRegistry {
+    /// Creates a new registry without any registered decoders.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `T` as frame type for the passed `op_code`.
+    ///
+    /// Replaces the decoder previously registered for the op code. Returns
+    /// `false` (and does not register anything) if the op code is supported
+    /// by [`Frame`].
+    pub fn register<T>(&mut self, op_code: u8) -> bool
+    where
+        T: Decode + FixedSize + ExtensionFrame,
+    {
+        if Frame::size(op_code).is_some() {
+            return false;
+        }
+
+        self.decoders.insert(
+            op_code,
+            Decoder {
+                payload_len: T::SIZE,
+                decode: decode_boxed::<T>,
+            },
+        );
+
+        true
+    }
+
+    /// Returns `true` if a decoder is registered for the passed `op_code`.
+    #[must_use]
+    pub fn is_registered(&self, op_code: u8) -> bool {
+        self.decoders.contains_key(&op_code)
+    }
+
+    /// Returns the size in bytes of a frame with the passed `op_code`
+    /// (including the op code and the checksum), or `None` if the op code is
+    /// neither supported nor registered (see [`Frame::size`]).
+    #[must_use]
+    pub fn size(&self, op_code: u8) -> Option<usize> {
+        Frame::size(op_code).or_else(|| {
+            self.decoders
+                .get(&op_code)
+                .map(|decoder| 1 + decoder.payload_len + 2)
+        })
+    }
+
+    /// Decodes a single frame of any supported or registered op code from
+    /// the start of `reader` and advances it to the end of the frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the op code is unknown, the frame could not be
+    /// decoded, or the checksum does not match.
+    pub fn decode_any(&self, reader: &mut &[u8]) -> Result<AnyFrame, IoError> {
+        let Some(decoder) = reader
+            .first()
+            .and_then(|op_code| self.decoders.get(op_code))
+        else {
+            return Ok(AnyFrame::Frame(Box::new(decode_frame(reader)?)));
+        };
+
+        let op_code = reader.read_u8()?;
+        let Some((payload, rest)) = reader.split_at_checked(decoder.payload_len) else {
+            return Err(IoError::IoError(ErrorKind::UnexpectedEof.into()));
+        };
+        *reader = rest;
+
+        let frame = (decoder.decode)(payload)?;
+
+        let crc_expected = reader.read_u16be()?;
+        if checksum(payload) != crc_expected {
+            return Err(IoError::ChecksumMismatch);
+        }
+
+        Ok(AnyFrame::Extension { op_code, frame })
+    }
+
+    /// Decodes all frames stored back to back in `data` (see
+    /// [`decode_any`](Self::decode_any)).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the frames could not be decoded.
+    pub fn decode_all(&self, data: &[u8]) -> Result<Vec<AnyFrame>, IoError> {
+        let mut reader = data;
+        let mut frames = Vec::new();
+
+        while !reader.is_empty() {
+            frames.push(self.decode_any(&mut reader)?);
+        }
+
+        Ok(frames)
+    }
+}
+
+fn decode_boxed<T>(mut payload: &[u8]) -> Result<Box<dyn ExtensionFrame>, IoError>
+where
+    T: Decode + ExtensionFrame,
+{
+    Ok(Box::new(T::decode(&mut payload)?))
+}
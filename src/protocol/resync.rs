@@ -0,0 +1,149 @@
+use crate::misc::{checksum, IoError};
+
+use super::limits::check;
+use super::{DecodeLimits, Frame};
+
+/// Decodes the frames of a byte stream that may have lost sync.
+///
+/// Byte streams received over serial bridges may contain garbage or truncated
+/// frames. Instead of failing at the first invalid frame (like
+/// [`decode_frames`](super::decode_frames)), the decoder scans for the next
+/// plausible frame (a known op code followed by a payload with a valid
+/// checksum) and resumes decoding there. The number of discarded bytes is
+/// reported with each frame.
+///
+/// Created by [`decode_frames_resync`].
+#[derive(Debug, Clone)]
+pub struct ResyncDecoder<'a> {
+    data: &'a [u8],
+    discarded: usize,
+    frames: usize,
+    limits: DecodeLimits,
+    exceeded: Option<(&'static str, usize)>,
+}
+
+/// A frame yielded by the [`ResyncDecoder`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ResyncedFrame {
+    /// Number of bytes that were discarded in front of the frame.
+    pub discarded: usize,
+
+    /// The decoded frame.
+    pub frame: Frame,
+}
+
+/// Creates a [`ResyncDecoder`] for the frames stored in `data`.
+#[must_use]
+pub fn decode_frames_resync(data: &[u8]) -> ResyncDecoder<'_> {
+    ResyncDecoder {
+        data,
+        discarded: 0,
+        frames: 0,
+        limits: DecodeLimits::UNLIMITED,
+        exceeded: None,
+    }
+}
+
+impl ResyncDecoder<'_> {
+    /// Sets the limits enforced by the decoder and returns the updated
+    /// decoder.
+    ///
+    /// The decoder stops as soon as a limit is hit, the hit limit is then
+    /// returned by [`error`](Self::error). [`DecodeLimits::max_discarded`]
+    /// is checked for the bytes discarded in front of each frame.
+    #[must_use]
+    pub fn with_limits(mut self, limits: DecodeLimits) -> Self {
+        self.limits = limits;
+        self.exceeded = check("max_input_len", limits.max_input_len, self.data.len())
+            .err()
+            .and_then(|x| x.limit_exceeded());
+
+        self
+    }
+
+    /// Returns [`IoError::LimitExceeded`] if the decoder was stopped because
+    /// one of its [`DecodeLimits`] was hit.
+    #[must_use]
+    pub fn error(&self) -> Option<IoError> {
+        self.exceeded
+            .map(|(name, actual)| IoError::LimitExceeded(name, actual))
+    }
+
+    /// Returns the total number of bytes discarded so far.
+    ///
+    /// After the decoder is exhausted this includes trailing data that does
+    /// not form a valid frame.
+    #[must_use]
+    pub fn discarded(&self) -> usize {
+        self.discarded
+    }
+}
+
+impl Iterator for ResyncDecoder<'_> {
+    type Item = ResyncedFrame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.exceeded.is_some() {
+            return None;
+        }
+
+        let mut discarded = 0;
+
+        while !self.data.is_empty() {
+            if is_plausible(self.data) {
+                let mut reader = self.data;
+                match self.limits.decode_frame(&mut reader) {
+                    Ok(frame) => {
+                        self.data = reader;
+                        self.discarded += discarded;
+                        self.frames += 1;
+
+                        if let Err(error) = check("max_frames", self.limits.max_frames, self.frames)
+                        {
+                            return self.stop(&error, 0);
+                        }
+
+                        return Some(ResyncedFrame { discarded, frame });
+                    }
+                    Err(error @ IoError::LimitExceeded(..)) => return self.stop(&error, discarded),
+                    Err(_) => (),
+                }
+            }
+
+            self.data = &self.data[1..];
+            discarded += 1;
+
+            if let Err(error) = check("max_discarded", self.limits.max_discarded, discarded) {
+                return self.stop(&error, discarded);
+            }
+        }
+
+        self.discarded += discarded;
+
+        None
+    }
+}
+
+impl ResyncDecoder<'_> {
+    fn stop(&mut self, error: &IoError, discarded: usize) -> Option<ResyncedFrame> {
+        self.discarded += discarded;
+        self.exceeded = error.limit_exceeded();
+
+        None
+    }
+}
+
+/// Returns `true` if `data` starts with a known op code followed by a payload
+/// with a valid checksum.
+fn is_plausible(data: &[u8]) -> bool {
+    let Some(size) = Frame::size(data[0]) else {
+        return false;
+    };
+    let Some(frame) = data.get(..size) else {
+        return false;
+    };
+
+    let crc = u16::from_be_bytes([frame[size - 2], frame[size - 1]]);
+
+    checksum(&frame[1..size - 2]) == crc
+}
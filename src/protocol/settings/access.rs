@@ -0,0 +1,315 @@
+use arrayvec::ArrayVec;
+
+use crate::misc::Wrapped;
+
+use super::{
+    AlarmFlags, AquaBusAddress, ChartSource, Color, ConnectorType, DataSource, DisplayBrightness,
+    DisplayFlags, Effect, FlowUnit, Medium, OutputSignal, PageFlags, PowerFlags, SoundEffect,
+    StandbyFlags, TemperatureUnit, Value, ValueError,
+};
+
+/// Trait for types whose fields can be read and written using their path.
+///
+/// Paths use the same syntax as the [`SettingsDiff`](super::SettingsDiff)
+/// (e.g. `alarms.water_temperature_limit` or
+/// `lighting.strip_controllers[2].effect.speed`), and the values are passed
+/// as [`Value`]. This allows generic editors and command line tools to modify
+/// single fields without knowing the structure of the settings.
+///
+/// # Example
+///
+/// ```rust
+/// use high_flow_next::protocol::settings::{Access, Settings, Value};
+///
+/// fn raise_limit(settings: &mut Settings) {
+///     settings
+///         .set("alarms.water_temperature_limit", Value::Temperature(5000))
+///         .unwrap();
+///
+///     assert_eq!(
+///         settings.get("alarms.water_temperature_limit"),
+///         Some(Value::Temperature(5000))
+///     );
+/// }
+/// ```
+pub trait Access {
+    /// Returns the value of the field at `path`, or `None` if the path does
+    /// not name a leaf field.
+    ///
+    /// An empty `path` refers to the value itself.
+    fn get(&self, path: &str) -> Option<Value>;
+
+    /// Sets the field at `path` to `value`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path is unknown, or if the value could not be
+    /// converted into the type of the field.
+    fn set(&mut self, path: &str, value: Value) -> Result<(), ValueError>;
+
+    /// Creates a new instance from the passed `value`.
+    ///
+    /// Used to set optional fields that are currently unset. Only supported
+    /// by leaf types, all other types return an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value could not be converted.
+    fn create(value: Value) -> Result<Self, ValueError>
+    where
+        Self: Sized,
+    {
+        Err(ValueError::InvalidValue(value))
+    }
+}
+
+macro_rules! impl_access_leaf {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Access for $ty {
+                fn get(&self, path: &str) -> Option<Value> {
+                    path.is_empty().then(|| Value::from(*self))
+                }
+
+                fn set(&mut self, path: &str, value: Value) -> Result<(), ValueError> {
+                    if !path.is_empty() {
+                        return Err(ValueError::UnknownPath);
+                    }
+
+                    *self = Self::create(value)?;
+
+                    Ok(())
+                }
+
+                fn create(value: Value) -> Result<Self, ValueError> {
+                    value.try_into()
+                }
+            }
+        )*
+    };
+}
+
+impl_access_leaf!(
+    u8,
+    u16,
+    bool,
+    Color,
+    AquaBusAddress,
+    Medium,
+    ConnectorType,
+    OutputSignal,
+    TemperatureUnit,
+    FlowUnit,
+    DisplayBrightness,
+    ChartSource,
+    DataSource,
+    SoundEffect,
+    StandbyFlags,
+    PowerFlags,
+    AlarmFlags,
+    DisplayFlags,
+    PageFlags,
+);
+
+impl<T, X> Access for Wrapped<T, X>
+where
+    Self: Copy + Into<Value> + TryFrom<Value, Error = ValueError>,
+{
+    fn get(&self, path: &str) -> Option<Value> {
+        path.is_empty().then(|| (*self).into())
+    }
+
+    fn set(&mut self, path: &str, value: Value) -> Result<(), ValueError> {
+        if !path.is_empty() {
+            return Err(ValueError::UnknownPath);
+        }
+
+        *self = value.try_into()?;
+
+        Ok(())
+    }
+
+    fn create(value: Value) -> Result<Self, ValueError> {
+        value.try_into()
+    }
+}
+
+impl<T> Access for Option<T>
+where
+    T: Access,
+{
+    fn get(&self, path: &str) -> Option<Value> {
+        match self {
+            Some(x) => x.get(path),
+            None => path.is_empty().then_some(Value::None),
+        }
+    }
+
+    fn set(&mut self, path: &str, value: Value) -> Result<(), ValueError> {
+        if path.is_empty() && value == Value::None {
+            *self = None;
+
+            return Ok(());
+        }
+
+        match self {
+            Some(x) => x.set(path, value),
+            None if path.is_empty() => {
+                *self = Some(T::create(value)?);
+
+                Ok(())
+            }
+            None => Err(ValueError::UnknownPath),
+        }
+    }
+}
+
+impl<T, const N: usize> Access for [T; N]
+where
+    T: Access,
+{
+    fn get(&self, path: &str) -> Option<Value> {
+        let (index, path) = split_index(path)?;
+
+        self.as_slice().get(index)?.get(path)
+    }
+
+    fn set(&mut self, path: &str, value: Value) -> Result<(), ValueError> {
+        let (index, path) = split_index(path).ok_or(ValueError::UnknownPath)?;
+
+        self.as_mut_slice()
+            .get_mut(index)
+            .ok_or(ValueError::UnknownPath)?
+            .set(path, value)
+    }
+}
+
+impl<T, const N: usize> Access for ArrayVec<T, N>
+where
+    T: Access,
+{
+    fn get(&self, path: &str) -> Option<Value> {
+        let (index, path) = split_index(path)?;
+
+        self.as_slice().get(index)?.get(path)
+    }
+
+    fn set(&mut self, path: &str, value: Value) -> Result<(), ValueError> {
+        let (index, path) = split_index(path).ok_or(ValueError::UnknownPath)?;
+
+        self.as_mut_slice()
+            .get_mut(index)
+            .ok_or(ValueError::UnknownPath)?
+            .set(path, value)
+    }
+}
+
+impl<A, B> Access for (A, B)
+where
+    A: Access,
+    B: Access,
+{
+    fn get(&self, path: &str) -> Option<Value> {
+        match split_field(path) {
+            ("0", path) => self.0.get(path),
+            ("1", path) => self.1.get(path),
+            _ => None,
+        }
+    }
+
+    fn set(&mut self, path: &str, value: Value) -> Result<(), ValueError> {
+        match split_field(path) {
+            ("0", path) => self.0.set(path, value),
+            ("1", path) => self.1.set(path, value),
+            _ => Err(ValueError::UnknownPath),
+        }
+    }
+}
+
+impl<A, B, C> Access for (A, B, C)
+where
+    A: Access,
+    B: Access,
+    C: Access,
+{
+    fn get(&self, path: &str) -> Option<Value> {
+        match split_field(path) {
+            ("0", path) => self.0.get(path),
+            ("1", path) => self.1.get(path),
+            ("2", path) => self.2.get(path),
+            _ => None,
+        }
+    }
+
+    fn set(&mut self, path: &str, value: Value) -> Result<(), ValueError> {
+        match split_field(path) {
+            ("0", path) => self.0.set(path, value),
+            ("1", path) => self.1.set(path, value),
+            ("2", path) => self.2.set(path, value),
+            _ => Err(ValueError::UnknownPath),
+        }
+    }
+}
+
+macro_rules! effect_dispatch {
+    ($effect:expr, $x:ident => $call:expr) => {
+        match $effect {
+            Effect::Static($x) => $call,
+            Effect::Breathing($x) => $call,
+            Effect::Rainbow($x) => $call,
+            Effect::Blink($x) => $call,
+            Effect::ColorChange($x) => $call,
+            Effect::Sequence($x) => $call,
+            Effect::Scanner($x) | Effect::Laser($x) => $call,
+            Effect::Wave($x) => $call,
+            Effect::ColorSequence($x) => $call,
+            Effect::ColorShift($x) => $call,
+            Effect::BarGraph($x) | Effect::SoundBars($x) => $call,
+            Effect::Flame($x) => $call,
+            Effect::Rain($x) | Effect::Snow($x) | Effect::Stardust($x) => $call,
+            Effect::ColorSwitch($x) => $call,
+            Effect::SwipingRainbow($x) => $call,
+            Effect::SoundFlash($x) => $call,
+            Effect::SoundSlider($x) => $call,
+            Effect::SoundShift($x) => $call,
+            Effect::Ambient($x) => $call,
+            Effect::ColorGradient($x) => $call,
+        }
+    };
+}
+
+/// The fields of the effect are addressed directly by the path of the
+/// effect (e.g. `effect.speed`). The effect itself reads as its name, but can
+/// not be changed using [`Access::set`].
+impl Access for Effect {
+    fn get(&self, path: &str) -> Option<Value> {
+        if path.is_empty() {
+            return Some(Value::Enum(self.name().into()));
+        }
+
+        effect_dispatch!(self, x => x.get(path))
+    }
+
+    fn set(&mut self, path: &str, value: Value) -> Result<(), ValueError> {
+        if path.is_empty() {
+            return Err(ValueError::InvalidValue(value));
+        }
+
+        effect_dispatch!(self, x => x.set(path, value))
+    }
+}
+
+/// Splits `path` into the name of the first field and the remaining path.
+pub(super) fn split_field(path: &str) -> (&str, &str) {
+    let end = path.find(['.', '[']).unwrap_or(path.len());
+    let (field, path) = path.split_at(end);
+
+    (field, path.strip_prefix('.').unwrap_or(path))
+}
+
+/// Splits a leading `[index]` from `path`.
+fn split_index(path: &str) -> Option<(usize, &str)> {
+    let (index, path) = path.strip_prefix('[')?.split_once(']')?;
+
+    Some((index.parse().ok()?, path.strip_prefix('.').unwrap_or(path)))
+}
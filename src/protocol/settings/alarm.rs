@@ -1,14 +1,21 @@
 use bitflags::bitflags;
 
 use crate::{
-    define_wrapped, impl_ranged,
-    misc::{Decode, Guard, GuardOutput, IoError, Reader},
+    define_wrapped, impl_percent, impl_ranged,
+    locale::Message,
+    misc::{
+        Decode, FixedSize, Guard, GuardOutput, IoError, Percent, RangeError, Ranged, Reader,
+        Wrapped,
+    },
 };
 
-use super::{flag_set, Flow};
+use super::{flag_set, Flow, Settings, TemperatureUnit};
 
 /// Alarm related settings for a high flow NEXT device.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub struct AlarmSettings {
     /// Different flags.
     pub flags: AlarmFlags,
@@ -36,6 +43,17 @@ pub struct AlarmSettings {
     pub output_signal: OutputSignal,
 }
 
+impl FixedSize for AlarmSettings {
+    const SIZE: usize = AlarmFlags::SIZE
+        + u8::SIZE
+        + 1
+        + StartupDelay::SIZE
+        + Flow::SIZE
+        + 2 * Temperature::SIZE
+        + WaterQuality::SIZE
+        + OutputSignal::SIZE;
+}
+
 impl Decode for AlarmSettings {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         let flags = AlarmFlags::decode(reader)?;
@@ -60,10 +78,553 @@ impl Decode for AlarmSettings {
     }
 }
 
+impl AlarmSettings {
+    /// Creates new alarm settings with all alarms and indicators disabled,
+    /// no startup delay and the passed `output_signal`.
+    ///
+    /// Use the [`AlarmConfig`] to enable alarms.
+    #[must_use]
+    pub fn new(output_signal: OutputSignal) -> Self {
+        Self {
+            flags: AlarmFlags::empty(),
+            startup_delay: StartupDelay::from_value(0).unwrap_or_else(|_| unreachable!()),
+            flow_alarm_limit: None,
+            water_temperature_limit: None,
+            external_temperature_limit: None,
+            water_quality_limit: None,
+            output_signal,
+        }
+    }
+
+    /// Returns a human readable description of the configured alarms, one
+    /// entry per alarm or indicator.
+    ///
+    /// Flows are reported in l/h and temperatures in °C (see [`AlarmConfig`]).
+    #[must_use]
+    pub fn describe(&self) -> Vec<String> {
+        self.messages().iter().map(ToString::to_string).collect()
+    }
+
+    /// Returns the localizable [`Message`]s of the description (see
+    /// [`describe`](Self::describe)).
+    ///
+    /// The limits are passed as `value` argument.
+    #[must_use]
+    pub fn messages(&self) -> Vec<Message> {
+        let mut ret = Vec::new();
+
+        if let Some(flow) = self.flow_alarm_limit {
+            ret.push(
+                Message::new(
+                    "alarm.flow_below",
+                    "Raises an alarm if the flow drops below {value} l/h.",
+                )
+                .arg(
+                    "value",
+                    format!("{:.1}", f64::from(*flow) / AlarmConfig::FLOW_SCALE),
+                ),
+            );
+        }
+        if let Some(temp) = self.water_temperature_limit {
+            ret.push(
+                Message::new(
+                    "alarm.water_temp_above",
+                    "Raises an alarm if the water temperature rises above {value} °C.",
+                )
+                .arg(
+                    "value",
+                    format!("{:.2}", f64::from(*temp) / AlarmConfig::TEMPERATURE_SCALE),
+                ),
+            );
+        }
+        if let Some(temp) = self.external_temperature_limit {
+            ret.push(
+                Message::new(
+                    "alarm.external_temp_above",
+                    "Raises an alarm if the external temperature rises above {value} °C.",
+                )
+                .arg(
+                    "value",
+                    format!("{:.2}", f64::from(*temp) / AlarmConfig::TEMPERATURE_SCALE),
+                ),
+            );
+        }
+        if let Some(quality) = self.water_quality_limit {
+            ret.push(
+                Message::new(
+                    "alarm.water_quality_below",
+                    "Raises an alarm if the water quality drops below {value} %.",
+                )
+                .arg("value", format!("{:.2}", quality.percent())),
+            );
+        }
+
+        if self.flags.contains(AlarmFlags::ENABLE_ACUSTIC_INDICATOR) {
+            ret.push(Message::new(
+                "alarm.acoustic_indicator",
+                "Beeps during an alarm.",
+            ));
+        }
+        if self.flags.contains(AlarmFlags::ENABLE_OPTICAL_INDICATOR) {
+            ret.push(Message::new(
+                "alarm.optical_indicator",
+                "Flashes the red ring during an alarm.",
+            ));
+        }
+        if self
+            .flags
+            .contains(AlarmFlags::DISABLE_SIGNAL_OUTPUT_DURING_ALARM)
+        {
+            ret.push(Message::new(
+                "alarm.disable_signal_output",
+                "Disables the signal output during an alarm.",
+            ));
+        }
+
+        if *self.startup_delay > 0 {
+            ret.push(
+                Message::new(
+                    "alarm.startup_delay",
+                    "Ignores alarms for {value} s after startup.",
+                )
+                .arg("value", *self.startup_delay),
+            );
+        }
+
+        ret
+    }
+}
+
+/// Unit aware access to the temperature alarm limits.
+///
+/// The device stores the temperature limits in the unit configured for the
+/// display ([`DisplaySettings::temperature_unit`](super::DisplaySettings::temperature_unit)),
+/// so the raw value of [`AlarmSettings::water_temperature_limit`] changes its
+/// meaning if only the unit is switched. These methods accept and return the
+/// limits in an explicit unit and convert them based on the configured unit.
+impl Settings {
+    /// Returns the water temperature alarm limit in the passed `unit`.
+    #[must_use]
+    pub fn water_temperature_limit_in(&self, unit: TemperatureUnit) -> Option<f64> {
+        self.alarms
+            .water_temperature_limit
+            .map(|x| self.temperature_in(x, unit))
+    }
+
+    /// Returns the external temperature alarm limit in the passed `unit`.
+    #[must_use]
+    pub fn external_temperature_limit_in(&self, unit: TemperatureUnit) -> Option<f64> {
+        self.alarms
+            .external_temperature_limit
+            .map(|x| self.temperature_in(x, unit))
+    }
+
+    /// Sets the water temperature alarm limit to `limit` in the passed `unit`
+    /// (or disables the alarm if `limit` is `None`).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IoError::RangeError`] (with the bounds in the passed `unit`)
+    /// if the limit can not be represented by the device.
+    pub fn set_water_temperature_limit_in(
+        &mut self,
+        limit: Option<f64>,
+        unit: TemperatureUnit,
+    ) -> Result<(), IoError> {
+        self.alarms.water_temperature_limit = self.stored_temperature(limit, unit)?;
+
+        Ok(())
+    }
+
+    /// Sets the external temperature alarm limit to `limit` in the passed
+    /// `unit` (or disables the alarm if `limit` is `None`).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IoError::RangeError`] (with the bounds in the passed `unit`)
+    /// if the limit can not be represented by the device.
+    pub fn set_external_temperature_limit_in(
+        &mut self,
+        limit: Option<f64>,
+        unit: TemperatureUnit,
+    ) -> Result<(), IoError> {
+        self.alarms.external_temperature_limit = self.stored_temperature(limit, unit)?;
+
+        Ok(())
+    }
+
+    /// Changes the temperature unit of the display to `unit` and rewrites the
+    /// temperature alarm limits, so they keep their physical value.
+    ///
+    /// Setting [`DisplaySettings::temperature_unit`](super::DisplaySettings::temperature_unit)
+    /// directly keeps the raw limits, e.g. a limit of 45 °C turns into 45 °F.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IoError::RangeError`] (with the bounds in the new `unit`) if
+    /// a converted limit can not be represented by the device (e.g. 60 °C
+    /// exceeds the maximum of 100 °F). The settings are not changed in this
+    /// case.
+    pub fn set_temperature_unit(&mut self, unit: TemperatureUnit) -> Result<(), IoError> {
+        let from = self.display.temperature_unit;
+        let water = convert_limit(self.alarms.water_temperature_limit, from, unit)?;
+        let external = convert_limit(self.alarms.external_temperature_limit, from, unit)?;
+
+        self.display.temperature_unit = unit;
+        self.alarms.water_temperature_limit = water;
+        self.alarms.external_temperature_limit = external;
+
+        Ok(())
+    }
+
+    fn temperature_in(&self, temperature: Temperature, unit: TemperatureUnit) -> f64 {
+        let value = f64::from(*temperature) / AlarmConfig::TEMPERATURE_SCALE;
+
+        self.display.temperature_unit.convert(value, unit)
+    }
+
+    fn stored_temperature(
+        &self,
+        limit: Option<f64>,
+        unit: TemperatureUnit,
+    ) -> Result<Option<Temperature>, IoError> {
+        let stored = self.display.temperature_unit;
+
+        limit
+            .map(|x| {
+                scaled(unit.convert(x, stored), AlarmConfig::TEMPERATURE_SCALE).map_err(|err| {
+                    RangeError {
+                        min: stored.convert(err.min, unit),
+                        max: stored.convert(err.max, unit),
+                        val: x,
+                    }
+                })
+            })
+            .transpose()
+            .map_err(Into::into)
+    }
+}
+
+/// Converts the raw temperature `limit` from the unit `from` to the unit `to`.
+fn convert_limit(
+    limit: Option<Temperature>,
+    from: TemperatureUnit,
+    to: TemperatureUnit,
+) -> Result<Option<Temperature>, RangeError<f64>> {
+    limit
+        .map(|x| {
+            let value = from.convert(f64::from(*x) / AlarmConfig::TEMPERATURE_SCALE, to);
+
+            scaled(value, AlarmConfig::TEMPERATURE_SCALE)
+        })
+        .transpose()
+}
+
+/// Builder for the alarm related parts of the [`AlarmSettings`].
+///
+/// Enabling an alarm in the [`AlarmSettings`] directly requires to know the
+/// field, its scale and the flags of the matching indicator. The builder
+/// accepts the limits in physical units instead and produces consistent
+/// settings:
+///
+/// - Flows are passed in l/h.
+/// - Temperatures are passed in °C. The device stores them in the temperature
+///   unit configured for the display, so use [`apply_to`](Self::apply_to) or
+///   [`build_for`](Self::build_for) to convert them to the configured unit.
+///   [`apply`](Self::apply) and [`build`](Self::build) store them unchanged.
+/// - The water quality is passed in percent.
+///
+/// Alarms that are not configured are disabled. The startup delay, the output
+/// signal and [`AlarmFlags::DISABLE_SIGNAL_OUTPUT_DURING_ALARM`] are only
+/// changed if they were set explicitly.
+///
+/// # Example
+///
+/// ```rust
+/// use std::fs::read;
+///
+/// use high_flow_next::misc::Decode;
+/// use high_flow_next::protocol::settings::{AlarmConfig, AlarmIndicator};
+/// use high_flow_next::protocol::Frame;
+///
+/// let data = read("tests/assets/default.frame").unwrap();
+/// let Frame::Settings(settings) = Frame::decode(&mut &data[..]).unwrap();
+///
+/// let config = AlarmConfig::new()
+///     .flow_below(60.0)
+///     .water_temp_above(43.0)
+///     .indicator(AlarmIndicator::ACOUSTIC | AlarmIndicator::OPTICAL);
+///
+/// let alarms = config.build(&settings.alarms).unwrap();
+///
+/// assert_eq!(alarms.flow_alarm_limit.map(|x| *x), Some(600));
+/// assert_eq!(alarms.water_temperature_limit.map(|x| *x), Some(4300));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct AlarmConfig {
+    flow_below: Option<f64>,
+    water_temp_above: Option<f64>,
+    external_temp_above: Option<f64>,
+    water_quality_below: Option<f64>,
+    indicator: AlarmIndicator,
+    startup_delay: Option<u8>,
+    output_signal: Option<OutputSignal>,
+    disable_signal_output: Option<bool>,
+}
+
+impl AlarmConfig {
+    const FLOW_SCALE: f64 = 10.0;
+    const TEMPERATURE_SCALE: f64 = 100.0;
+
+    /// Creates a new config with all alarms and indicators disabled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Raises an alarm if the flow drops below `flow` (in l/h).
+    #[must_use]
+    pub fn flow_below(mut self, flow: f64) -> Self {
+        self.flow_below = Some(flow);
+
+        self
+    }
+
+    /// Raises an alarm if the water temperature rises above `temp` (in °C).
+    #[must_use]
+    pub fn water_temp_above(mut self, temp: f64) -> Self {
+        self.water_temp_above = Some(temp);
+
+        self
+    }
+
+    /// Raises an alarm if the external temperature rises above `temp`
+    /// (in °C).
+    #[must_use]
+    pub fn external_temp_above(mut self, temp: f64) -> Self {
+        self.external_temp_above = Some(temp);
+
+        self
+    }
+
+    /// Raises an alarm if the water quality drops below `quality`
+    /// (in percent).
+    #[must_use]
+    pub fn water_quality_below(mut self, quality: f64) -> Self {
+        self.water_quality_below = Some(quality);
+
+        self
+    }
+
+    /// Sets the indicators to use during an alarm.
+    #[must_use]
+    pub fn indicator(mut self, indicator: AlarmIndicator) -> Self {
+        self.indicator = indicator;
+
+        self
+    }
+
+    /// Sets the time to disable the alarms after boot (in seconds).
+    #[must_use]
+    pub fn startup_delay(mut self, secs: u8) -> Self {
+        self.startup_delay = Some(secs);
+
+        self
+    }
+
+    /// Sets the signal to output at the signal output connector.
+    #[must_use]
+    pub fn output_signal(mut self, signal: OutputSignal) -> Self {
+        self.output_signal = Some(signal);
+
+        self
+    }
+
+    /// Sets whether to disable the signal output during an alarm or not.
+    #[must_use]
+    pub fn disable_signal_output_during_alarm(mut self, value: bool) -> Self {
+        self.disable_signal_output = Some(value);
+
+        self
+    }
+
+    /// Applies the config to the passed `settings`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IoError::RangeError`] (with the bounds in the physical unit
+    /// of the value) if one of the values can not be represented by the
+    /// device. The `settings` are not changed in this case.
+    pub fn apply(&self, settings: &mut AlarmSettings) -> Result<(), IoError> {
+        *settings = self.build(settings)?;
+
+        Ok(())
+    }
+
+    /// Applies the config to the passed `base` settings and returns the
+    /// result (see [`apply`](Self::apply)).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IoError::RangeError`] if one of the values can not be
+    /// represented by the device.
+    pub fn build(&self, base: &AlarmSettings) -> Result<AlarmSettings, IoError> {
+        self.build_in(base, TemperatureUnit::C)
+    }
+
+    /// Applies the config to the alarms of the passed `settings`, converting
+    /// the temperatures to the temperature unit configured for the display.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IoError::RangeError`] if one of the values can not be
+    /// represented by the device. The `settings` are not changed in this
+    /// case.
+    pub fn apply_to(&self, settings: &mut Settings) -> Result<(), IoError> {
+        settings.alarms = self.build_for(settings)?;
+
+        Ok(())
+    }
+
+    /// Applies the config to the alarms of the passed `settings` and returns
+    /// the result (see [`apply_to`](Self::apply_to)).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IoError::RangeError`] if one of the values can not be
+    /// represented by the device.
+    pub fn build_for(&self, settings: &Settings) -> Result<AlarmSettings, IoError> {
+        self.build_in(&settings.alarms, settings.display.temperature_unit)
+    }
+
+    fn build_in(
+        &self,
+        base: &AlarmSettings,
+        unit: TemperatureUnit,
+    ) -> Result<AlarmSettings, IoError> {
+        let temperature =
+            |x: f64| scaled(TemperatureUnit::C.convert(x, unit), Self::TEMPERATURE_SCALE);
+
+        let mut flags = base.flags - AlarmIndicator::all().as_flags();
+        flags |= self.indicator.as_flags();
+        if let Some(disable) = self.disable_signal_output {
+            flags.set(AlarmFlags::DISABLE_SIGNAL_OUTPUT_DURING_ALARM, disable);
+        }
+
+        let startup_delay = match self.startup_delay {
+            Some(secs) => StartupDelay::from_value(secs)?,
+            None => base.startup_delay,
+        };
+
+        Ok(AlarmSettings {
+            flags,
+            startup_delay,
+            flow_alarm_limit: self
+                .flow_below
+                .map(|x| scaled(x, Self::FLOW_SCALE))
+                .transpose()?,
+            water_temperature_limit: self.water_temp_above.map(temperature).transpose()?,
+            external_temperature_limit: self.external_temp_above.map(temperature).transpose()?,
+            water_quality_limit: self
+                .water_quality_below
+                .map(|x| scaled(x, <WaterQualityTag as Percent>::SCALE))
+                .transpose()?,
+            output_signal: self.output_signal.unwrap_or(base.output_signal),
+        })
+    }
+}
+
+/// Creates the config from the passed alarm settings. The temperature limits
+/// are taken unchanged as °C, use the conversion from [`Settings`] to take the
+/// configured temperature unit into account.
+impl From<&AlarmSettings> for AlarmConfig {
+    fn from(settings: &AlarmSettings) -> Self {
+        Self {
+            flow_below: settings
+                .flow_alarm_limit
+                .map(|x| f64::from(*x) / Self::FLOW_SCALE),
+            water_temp_above: settings
+                .water_temperature_limit
+                .map(|x| f64::from(*x) / Self::TEMPERATURE_SCALE),
+            external_temp_above: settings
+                .external_temperature_limit
+                .map(|x| f64::from(*x) / Self::TEMPERATURE_SCALE),
+            water_quality_below: settings.water_quality_limit.map(|x| x.percent()),
+            indicator: AlarmIndicator::from_flags(settings.flags),
+            startup_delay: Some(*settings.startup_delay),
+            output_signal: Some(settings.output_signal),
+            disable_signal_output: Some(
+                settings
+                    .flags
+                    .contains(AlarmFlags::DISABLE_SIGNAL_OUTPUT_DURING_ALARM),
+            ),
+        }
+    }
+}
+
+/// Creates the config from the alarms of the passed `settings`, converting
+/// the temperatures from the temperature unit configured for the display to
+/// °C.
+impl From<&Settings> for AlarmConfig {
+    fn from(settings: &Settings) -> Self {
+        Self {
+            water_temp_above: settings.water_temperature_limit_in(TemperatureUnit::C),
+            external_temp_above: settings.external_temperature_limit_in(TemperatureUnit::C),
+            ..Self::from(&settings.alarms)
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn scaled<X>(value: f64, scale: f64) -> Result<Wrapped<u16, X>, RangeError<f64>>
+where
+    X: Ranged<u16>,
+{
+    let min = f64::from(X::min_inclusive());
+    let max = f64::from(X::max_inclusive());
+    let raw = (value * scale).round();
+
+    if !(min..=max).contains(&raw) {
+        return Err(RangeError {
+            min: min / scale,
+            max: max / scale,
+            val: value,
+        });
+    }
+
+    Ok(Wrapped::from_value(raw as u16).unwrap_or_else(|_| unreachable!()))
+}
+
+bitflags! {
+    /// Indicators used by the device during an alarm.
+    ///
+    /// Used in [`AlarmConfig::indicator`].
+    #[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+    pub struct AlarmIndicator: u8 {
+        /// Flash the red ring (see [`AlarmFlags::ENABLE_OPTICAL_INDICATOR`]).
+        const OPTICAL = 0x40;
+
+        /// Beep (see [`AlarmFlags::ENABLE_ACUSTIC_INDICATOR`]).
+        const ACOUSTIC = 0x80;
+    }
+}
+
+impl AlarmIndicator {
+    fn as_flags(self) -> AlarmFlags {
+        AlarmFlags::from_bits_truncate(self.bits())
+    }
+
+    fn from_flags(flags: AlarmFlags) -> Self {
+        Self::from_bits_truncate(flags.bits())
+    }
+}
+
 /// Signal to output at the signal output connector.
 ///
 /// Used in [`AlarmSettings::output_signal`].
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum OutputSignal {
     /// Generate a constant speed signal.
     ConstantSpeed,
@@ -84,6 +645,10 @@ pub enum OutputSignal {
     PermanentOff,
 }
 
+impl FixedSize for OutputSignal {
+    const SIZE: usize = u8::SIZE;
+}
+
 impl Decode for OutputSignal {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         match reader.read_u8()? {
@@ -101,6 +666,7 @@ impl Decode for OutputSignal {
 bitflags! {
     /// Different flags uses in [`AlarmSettings::flags`].
     #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct AlarmFlags: u8 {
         /// Disable signal output during alarm.
         const DISABLE_SIGNAL_OUTPUT_DURING_ALARM = 0x20;
@@ -113,6 +679,10 @@ bitflags! {
     }
 }
 
+impl FixedSize for AlarmFlags {
+    const SIZE: usize = u8::SIZE;
+}
+
 impl Decode for AlarmFlags {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         let bits = reader.read_u8()?;
@@ -146,3 +716,4 @@ define_wrapped! {
     pub type WaterQuality<u16, WaterQualityTag>;
 }
 impl_ranged!(WaterQuality<u16, WaterQualityTag>, 0, 10000);
+impl_percent!(WaterQuality<u16, WaterQualityTag>, 100.0);
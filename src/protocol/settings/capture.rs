@@ -0,0 +1,152 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::ops::Range;
+
+use crate::misc::{Decode, FixedSize, IoError};
+use crate::protocol::Frame;
+
+use super::{Settings, SettingsDiff};
+
+/// Difference between two raw settings frames.
+///
+/// Automates the reverse engineering workflow of comparing two captures of the
+/// settings frame: in addition to the changed fields of the decoded
+/// [`Settings`] it reports the changed byte ranges that do not map to any known
+/// field.
+///
+/// Whether a changed byte maps to a known field is detected by applying the
+/// new byte to the old payload (and the old byte to the new payload) and
+/// decoding it again: if the decoded settings do not change in both cases, the
+/// byte is not (yet) part of the settings model. Note that this also applies to
+/// values that are not decoded in both frames because they are disabled (e.g.
+/// the limit of a disabled alarm).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CaptureDiff {
+    settings: SettingsDiff,
+    unknown: Vec<ByteRange>,
+}
+
+impl CaptureDiff {
+    /// Compares the `old` and the `new` settings frame (op code, payload and
+    /// checksum).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the frames could not be decoded.
+    pub fn new(old: &[u8], new: &[u8]) -> Result<Self, IoError> {
+        let old_settings = decode_settings(old)?;
+        let new_settings = decode_settings(new)?;
+
+        let settings = SettingsDiff::new(&old_settings, &new_settings);
+
+        // Only the payload is analyzed, the checksum changes with every change
+        // of the payload.
+        let payload = 1..1 + Settings::SIZE;
+        let mut patched_old = old[payload.clone()].to_vec();
+        let mut patched_new = new[payload.clone()].to_vec();
+        let mut unknown = Vec::<ByteRange>::new();
+
+        for offset in payload.clone() {
+            if old[offset] == new[offset] {
+                continue;
+            }
+
+            let index = offset - payload.start;
+            let known = is_known(&mut patched_old, index, new[offset], &old_settings)
+                || is_known(&mut patched_new, index, old[offset], &new_settings);
+
+            if known {
+                continue;
+            }
+
+            match unknown.last_mut() {
+                Some(range) if range.range.end == offset => {
+                    range.range.end += 1;
+                    range.old.push(old[offset]);
+                    range.new.push(new[offset]);
+                }
+                _ => unknown.push(ByteRange {
+                    range: offset..offset + 1,
+                    old: vec![old[offset]],
+                    new: vec![new[offset]],
+                }),
+            }
+        }
+
+        Ok(Self { settings, unknown })
+    }
+
+    /// Returns `true` if neither a known field nor an unknown byte changed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.settings.is_empty() && self.unknown.is_empty()
+    }
+
+    /// Returns the changed fields of the decoded settings.
+    #[must_use]
+    pub fn settings(&self) -> &SettingsDiff {
+        &self.settings
+    }
+
+    /// Returns the changed byte ranges that do not map to any known field.
+    #[must_use]
+    pub fn unknown(&self) -> &[ByteRange] {
+        &self.unknown
+    }
+}
+
+impl Display for CaptureDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", self.settings)?;
+
+        for range in &self.unknown {
+            writeln!(f, "{range}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Range of changed bytes of a [`CaptureDiff`] that does not map to any known
+/// field.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ByteRange {
+    /// Offset of the bytes in the frame (including the op code).
+    pub range: Range<usize>,
+
+    /// Bytes of the old frame.
+    pub old: Vec<u8>,
+
+    /// Bytes of the new frame.
+    pub new: Vec<u8>,
+}
+
+impl Display for ByteRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{:#06X}..{:#06X}:", self.range.start, self.range.end)?;
+        for byte in &self.old {
+            write!(f, " {byte:02X}")?;
+        }
+        write!(f, " ->")?;
+        for byte in &self.new {
+            write!(f, " {byte:02X}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns `true` if setting the byte at `index` of `payload` to `value`
+/// changes the decoded `settings`. The payload is restored afterwards.
+fn is_known(payload: &mut [u8], index: usize, value: u8, settings: &Settings) -> bool {
+    let prev = std::mem::replace(&mut payload[index], value);
+    let known = Settings::decode(&mut &payload[..]).map_or(true, |x| x != *settings);
+    payload[index] = prev;
+
+    known
+}
+
+fn decode_settings(mut frame: &[u8]) -> Result<Settings, IoError> {
+    let Frame::Settings(settings) = Frame::decode(&mut frame)?;
+
+    Ok(settings)
+}
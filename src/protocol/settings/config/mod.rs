@@ -0,0 +1,464 @@
+//! Versioned on-disk configuration format for the [`Settings`].
+
+mod template;
+
+use std::fmt::{Debug, Display};
+use std::io::Error as StdIoError;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::misc::{AnyRangeError, RangeError, ValueVerifier, Wrapped};
+
+use super::{
+    AlarmFlags, AlarmSettings, AquaBusAddress, Chart, ChartSource, ConnectorType,
+    DisplayBrightness, DisplayFlags, DisplaySettings, FlowUnit, LightingSettings, Medium,
+    OutputSignal, PageFlags, PowerFlags, SensorSettings, Settings, StandbyFlags, SystemSettings,
+    TemperatureUnit,
+};
+
+pub use self::template::Variables;
+
+/// Current version of the configuration format.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// File format of a configuration file.
+///
+/// In contrast to the plain `serde` representation of the settings model the
+/// configuration format is meant to be read and edited by humans:
+///
+/// - Every file starts with a `version` field. Files with a newer version than
+///   [`CONFIG_VERSION`] are rejected, unknown fields are ignored, so files
+///   written by newer versions of the same schema version can still be read.
+/// - Values are stored in physical units and the unit is part of the field
+///   name (e.g. `water_temp_offset_celsius = -0.5` instead of
+///   `water_temp_offset = -50`). Temperatures are stored in °C and flows in
+///   l/h, independent of the units configured for the display.
+/// - Optional values are omitted from the file if they are disabled.
+///
+/// The lighting settings do not carry any physical units and are stored using
+/// their `serde` representation.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ConfigFormat {
+    /// [TOML](https://toml.io)
+    Toml,
+
+    /// [YAML](https://yaml.org)
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// Returns the format matching the extension of the passed `path`
+    /// (`.toml`, `.yaml` or `.yml`).
+    #[must_use]
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        match path.as_ref().extension()?.to_str()? {
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// Error returned while reading or writing a configuration file.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    /// The file was written with a newer, unsupported version of the format.
+    #[error("Unsupported config version (version={0}, supported={CONFIG_VERSION})")]
+    UnsupportedVersion(u32),
+
+    /// A value could not be represented by the device.
+    #[error("Invalid value (name={0}, value={1})")]
+    InvalidValue(&'static str, f64),
+
+    /// A value was out of its valid range.
+    ///
+    /// The bounds are reported as the raw value of the device (see
+    /// [`AnyRangeError::downcast_ref`]).
+    #[error("Range Error (name={0}): {1}")]
+    RangeError(&'static str, AnyRangeError),
+
+    /// Error while parsing a TOML file.
+    #[error("TOML Error: {0}")]
+    TomlDe(#[from] toml::de::Error),
+
+    /// Error while writing a TOML file.
+    #[error("TOML Error: {0}")]
+    TomlSer(#[from] toml::ser::Error),
+
+    /// Error while parsing or writing a YAML file.
+    #[error("YAML Error: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
+    /// A placeholder of a template references a variable that is not defined
+    /// (see [`Variables`]).
+    #[error("Undefined variable: {0}")]
+    UndefinedVariable(String),
+
+    /// A placeholder of a template starting at the contained byte offset is
+    /// not closed.
+    #[error("Invalid template (offset={0})")]
+    InvalidTemplate(usize),
+
+    /// Error while reading a file.
+    #[error("IO Error: {0}")]
+    IoError(#[from] StdIoError),
+}
+
+impl Settings {
+    /// Writes the settings in the versioned configuration format
+    /// (see [`ConfigFormat`] for details).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings could not be serialized.
+    pub fn to_config_str(&self, format: ConfigFormat) -> Result<String, ConfigError> {
+        let config = ConfigFile::from(self);
+
+        match format {
+            ConfigFormat::Toml => Ok(toml::to_string_pretty(&config)?),
+            ConfigFormat::Yaml => Ok(serde_yaml::to_string(&config)?),
+        }
+    }
+
+    /// Reads settings from the versioned configuration format
+    /// (see [`ConfigFormat`] for details).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file could not be parsed, was written with an
+    /// unsupported version or contains invalid values.
+    pub fn from_config_str(s: &str, format: ConfigFormat) -> Result<Self, ConfigError> {
+        let Header { version } = parse(s, format)?;
+        if version > CONFIG_VERSION {
+            return Err(ConfigError::UnsupportedVersion(version));
+        }
+
+        parse::<ConfigFile>(s, format)?.try_into()
+    }
+}
+
+fn parse<T>(s: &str, format: ConfigFormat) -> Result<T, ConfigError>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    match format {
+        ConfigFormat::Toml => Ok(toml::from_str(s)?),
+        ConfigFormat::Yaml => Ok(serde_yaml::from_str(s)?),
+    }
+}
+
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ConfigFile {
+    version: u32,
+    system: SystemConfig,
+    sensor: SensorConfig,
+    alarms: AlarmConfig,
+    display: DisplayConfig,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lighting: Option<LightingSettings>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SystemConfig {
+    standby_flags: StandbyFlags,
+    aqua_bus_address: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    increased_current_draw_ma: Option<u16>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SensorConfig {
+    medium: Medium,
+    connector_type: ConnectorType,
+    water_temp_offset_celsius: f64,
+    external_temp_offset_celsius: f64,
+    conductivity_offset_us_per_cm: f64,
+    water_quality_max_us_per_cm: u16,
+    water_quality_min_us_per_cm: u16,
+    power_flags: PowerFlags,
+    power_damping_mw: u16,
+    flow_correction: [FlowCorrectionConfig; 10],
+}
+
+#[derive(Serialize, Deserialize)]
+struct FlowCorrectionConfig {
+    flow_l_per_h: f64,
+    correction_percent: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AlarmConfig {
+    flags: AlarmFlags,
+    startup_delay_s: u8,
+    output_signal: OutputSignal,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    flow_alarm_limit_l_per_h: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    water_temperature_limit_celsius: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    external_temperature_limit_celsius: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    water_quality_limit_percent: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DisplayConfig {
+    temperature_unit: TemperatureUnit,
+    flow_unit: FlowUnit,
+    display_flags: DisplayFlags,
+    page_flags: PageFlags,
+    display_brightness: DisplayBrightness,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    idle_display_brightness: Option<DisplayBrightness>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    next_page_interval_s: Option<u8>,
+    charts: [ChartConfig; 4],
+}
+
+#[derive(Serialize, Deserialize)]
+struct ChartConfig {
+    source: ChartSource,
+    interval_s: f64,
+}
+
+impl From<&Settings> for ConfigFile {
+    fn from(settings: &Settings) -> Self {
+        let Settings {
+            system,
+            sensor,
+            alarms,
+            display,
+            lighting,
+        } = settings;
+
+        let system = SystemConfig {
+            standby_flags: system.standby_flags,
+            aqua_bus_address: system.aqua_bus_address.raw(),
+            increased_current_draw_ma: system.increased_current_draw.map(|x| *x),
+        };
+
+        let sensor = SensorConfig {
+            medium: sensor.medium,
+            connector_type: sensor.connector_type,
+            water_temp_offset_celsius: unscaled(*sensor.water_temp_offset, 100.0),
+            external_temp_offset_celsius: unscaled(*sensor.external_temp_offset, 100.0),
+            conductivity_offset_us_per_cm: unscaled(*sensor.conductivity_offset, 10.0),
+            water_quality_max_us_per_cm: *sensor.water_quality_max,
+            water_quality_min_us_per_cm: *sensor.water_quality_min,
+            power_flags: sensor.power_flags,
+            power_damping_mw: *sensor.power_damping,
+            flow_correction: sensor.flow_correction.map(|(flow, correction)| {
+                FlowCorrectionConfig {
+                    flow_l_per_h: unscaled(*flow, 10.0),
+                    correction_percent: unscaled(*correction, 100.0),
+                }
+            }),
+        };
+
+        let alarms = AlarmConfig {
+            flags: alarms.flags,
+            startup_delay_s: *alarms.startup_delay,
+            output_signal: alarms.output_signal,
+            flow_alarm_limit_l_per_h: alarms.flow_alarm_limit.map(|x| unscaled(*x, 10.0)),
+            water_temperature_limit_celsius: alarms
+                .water_temperature_limit
+                .map(|x| unscaled(*x, 100.0)),
+            external_temperature_limit_celsius: alarms
+                .external_temperature_limit
+                .map(|x| unscaled(*x, 100.0)),
+            water_quality_limit_percent: alarms.water_quality_limit.map(|x| unscaled(*x, 100.0)),
+        };
+
+        let display = DisplayConfig {
+            temperature_unit: display.temperature_unit,
+            flow_unit: display.flow_unit,
+            display_flags: display.display_flags,
+            page_flags: display.page_flags,
+            display_brightness: display.display_brightness,
+            idle_display_brightness: display.idle_display_brightness,
+            next_page_interval_s: display.next_page_interval.map(|x| *x),
+            charts: display.charts.clone().map(|chart| ChartConfig {
+                source: chart.source,
+                interval_s: unscaled(*chart.interval, 10.0),
+            }),
+        };
+
+        Self {
+            version: CONFIG_VERSION,
+            system,
+            sensor,
+            alarms,
+            display,
+            lighting: lighting.clone(),
+        }
+    }
+}
+
+impl TryFrom<ConfigFile> for Settings {
+    type Error = ConfigError;
+
+    fn try_from(config: ConfigFile) -> Result<Self, Self::Error> {
+        Ok(Self {
+            system: config.system.try_into()?,
+            sensor: config.sensor.try_into()?,
+            alarms: config.alarms.try_into()?,
+            display: config.display.try_into()?,
+            lighting: config.lighting,
+        })
+    }
+}
+
+impl TryFrom<SystemConfig> for SystemSettings {
+    type Error = ConfigError;
+
+    fn try_from(config: SystemConfig) -> Result<Self, Self::Error> {
+        Ok(Self {
+            standby_flags: config.standby_flags,
+            aqua_bus_address: AquaBusAddress::try_from(config.aqua_bus_address)
+                .map_err(|err| ConfigError::RangeError("aqua_bus_address", err.into()))?,
+            increased_current_draw: config
+                .increased_current_draw_ma
+                .map(|x| raw("increased_current_draw_ma", x))
+                .transpose()?,
+        })
+    }
+}
+
+impl TryFrom<SensorConfig> for SensorSettings {
+    type Error = ConfigError;
+
+    fn try_from(config: SensorConfig) -> Result<Self, Self::Error> {
+        let mut flow_correction = Vec::with_capacity(10);
+        for point in config.flow_correction {
+            flow_correction.push((
+                scaled("flow_l_per_h", point.flow_l_per_h, 10.0)?,
+                scaled("correction_percent", point.correction_percent, 100.0)?,
+            ));
+        }
+
+        Ok(Self {
+            medium: config.medium,
+            connector_type: config.connector_type,
+            flow_correction: flow_correction
+                .try_into()
+                .unwrap_or_else(|_| unreachable!()),
+            water_temp_offset: scaled(
+                "water_temp_offset_celsius",
+                config.water_temp_offset_celsius,
+                100.0,
+            )?,
+            external_temp_offset: scaled(
+                "external_temp_offset_celsius",
+                config.external_temp_offset_celsius,
+                100.0,
+            )?,
+            conductivity_offset: scaled(
+                "conductivity_offset_us_per_cm",
+                config.conductivity_offset_us_per_cm,
+                10.0,
+            )?,
+            water_quality_max: raw(
+                "water_quality_max_us_per_cm",
+                config.water_quality_max_us_per_cm,
+            )?,
+            water_quality_min: raw(
+                "water_quality_min_us_per_cm",
+                config.water_quality_min_us_per_cm,
+            )?,
+            power_flags: config.power_flags,
+            power_damping: raw("power_damping_mw", config.power_damping_mw)?,
+        })
+    }
+}
+
+impl TryFrom<AlarmConfig> for AlarmSettings {
+    type Error = ConfigError;
+
+    fn try_from(config: AlarmConfig) -> Result<Self, Self::Error> {
+        Ok(Self {
+            flags: config.flags,
+            startup_delay: raw("startup_delay_s", config.startup_delay_s)?,
+            output_signal: config.output_signal,
+            flow_alarm_limit: config
+                .flow_alarm_limit_l_per_h
+                .map(|x| scaled("flow_alarm_limit_l_per_h", x, 10.0))
+                .transpose()?,
+            water_temperature_limit: config
+                .water_temperature_limit_celsius
+                .map(|x| scaled("water_temperature_limit_celsius", x, 100.0))
+                .transpose()?,
+            external_temperature_limit: config
+                .external_temperature_limit_celsius
+                .map(|x| scaled("external_temperature_limit_celsius", x, 100.0))
+                .transpose()?,
+            water_quality_limit: config
+                .water_quality_limit_percent
+                .map(|x| scaled("water_quality_limit_percent", x, 100.0))
+                .transpose()?,
+        })
+    }
+}
+
+impl TryFrom<DisplayConfig> for DisplaySettings {
+    type Error = ConfigError;
+
+    fn try_from(config: DisplayConfig) -> Result<Self, Self::Error> {
+        let mut charts = Vec::with_capacity(4);
+        for chart in config.charts {
+            charts.push(Chart {
+                source: chart.source,
+                interval: scaled("interval_s", chart.interval_s, 10.0)?,
+            });
+        }
+
+        Ok(Self {
+            temperature_unit: config.temperature_unit,
+            flow_unit: config.flow_unit,
+            display_flags: config.display_flags,
+            next_page_interval: config
+                .next_page_interval_s
+                .map(|x| raw("next_page_interval_s", x))
+                .transpose()?,
+            page_flags: config.page_flags,
+            display_brightness: config.display_brightness,
+            idle_display_brightness: config.idle_display_brightness,
+            charts: charts.try_into().unwrap_or_else(|_| unreachable!()),
+        })
+    }
+}
+
+fn unscaled<T: Into<f64>>(value: T, scale: f64) -> f64 {
+    value.into() / scale
+}
+
+fn raw<T, X>(name: &'static str, value: T) -> Result<Wrapped<T, X>, ConfigError>
+where
+    T: Debug + Display + Send + Sync + 'static,
+    X: ValueVerifier<T, Error = RangeError<T>>,
+{
+    Wrapped::from_value(value)
+        .map_err(|err: RangeError<T>| ConfigError::RangeError(name, err.into()))
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn scaled<T, X>(name: &'static str, value: f64, scale: f64) -> Result<Wrapped<T, X>, ConfigError>
+where
+    T: Debug + Display + Send + Sync + TryFrom<i64> + 'static,
+    X: ValueVerifier<T, Error = RangeError<T>>,
+{
+    let scaled = (value * scale).round();
+    if !scaled.is_finite() {
+        return Err(ConfigError::InvalidValue(name, value));
+    }
+
+    let scaled = T::try_from(scaled as i64).map_err(|_| ConfigError::InvalidValue(name, value))?;
+
+    raw(name, scaled)
+}
@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+use std::path::Path;
+
+use super::{ConfigError, ConfigFormat, Settings};
+
+/// Variables used to resolve the placeholders of a configuration template.
+///
+/// A template is a configuration file (see [`ConfigFormat`]) that contains
+/// placeholders, which are replaced before the file is parsed. This way one
+/// template can configure multiple machines with different values:
+///
+/// - `${NAME}` is replaced by the value of the variable `NAME`. Using a
+///   variable that is not defined is an error.
+/// - `${NAME:-default}` is replaced by the value of the variable `NAME`, or
+///   by `default` if the variable is not defined.
+/// - `$$` is replaced by a single `$`.
+///
+/// The values are inserted as they are, so string values have to be quoted
+/// in the template (e.g. `name = "${NAME}"`).
+///
+/// # Example
+///
+/// ```rust
+/// use high_flow_next::protocol::settings::Variables;
+///
+/// let vars = Variables::parse("WATER_TEMP_ALARM = 43.5\n# comment\n");
+/// let text = vars
+///     .substitute("water_temperature_limit_celsius = ${WATER_TEMP_ALARM}\nstartup_delay_s = ${DELAY:-10}")
+///     .unwrap();
+///
+/// assert_eq!(text, "water_temperature_limit_celsius = 43.5\nstartup_delay_s = 10");
+/// ```
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+pub struct Variables {
+    values: BTreeMap<String, String>,
+}
+
+impl Variables {
+    /// Creates a new empty set of variables.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new set of variables from the environment of the current
+    /// process.
+    ///
+    /// Environment variables that are not valid unicode are ignored.
+    #[must_use]
+    pub fn from_env() -> Self {
+        let values = std::env::vars_os()
+            .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+            .collect();
+
+        Self { values }
+    }
+
+    /// Parses the variables from the content of a vars file.
+    ///
+    /// Each line contains a single `NAME = value` pair. Empty lines and lines
+    /// starting with `#` are ignored, as well as lines without `=`. Leading
+    /// and trailing whitespace of the name and the value is removed, and
+    /// values enclosed in double quotes are unquoted.
+    #[must_use]
+    pub fn parse(s: &str) -> Self {
+        let values = s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .map(|(name, value)| {
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|x| x.strip_suffix('"'))
+                    .unwrap_or(value);
+
+                (name.trim().to_owned(), value.to_owned())
+            })
+            .collect();
+
+        Self { values }
+    }
+
+    /// Reads the variables from the vars file at `path` (see
+    /// [`parse`](Self::parse)).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file could not be read.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        Ok(Self::parse(&read_to_string(path)?))
+    }
+
+    /// Sets the variable `name` to `value`.
+    pub fn set<N: Into<String>, V: Into<String>>(&mut self, name: N, value: V) {
+        self.values.insert(name.into(), value.into());
+    }
+
+    /// Returns the value of the variable `name`.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// Adds all variables of `other`, overwriting variables that are already
+    /// defined.
+    ///
+    /// E.g. `Variables::from_file(path)?.merge(Variables::from_env())` uses
+    /// the vars file, but allows to override single values using the
+    /// environment.
+    #[must_use]
+    pub fn merge(mut self, other: Self) -> Self {
+        self.values.extend(other.values);
+
+        self
+    }
+
+    /// Replaces all placeholders in the passed `template` (see
+    /// [`Variables`] for the syntax).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::UndefinedVariable`] if a placeholder references
+    /// a variable that is not defined and has no default value, and
+    /// [`ConfigError::InvalidTemplate`] if a placeholder is not closed.
+    pub fn substitute(&self, template: &str) -> Result<String, ConfigError> {
+        let mut ret = String::with_capacity(template.len());
+        let mut rest = template;
+
+        while let Some(pos) = rest.find('$') {
+            ret.push_str(&rest[..pos]);
+            rest = &rest[pos + 1..];
+
+            if let Some(x) = rest.strip_prefix('$') {
+                ret.push('$');
+                rest = x;
+            } else if let Some(x) = rest.strip_prefix('{') {
+                let offset = template.len() - rest.len() - 1;
+                let end = x.find('}').ok_or(ConfigError::InvalidTemplate(offset))?;
+                let placeholder = &x[..end];
+                let (name, default) = match placeholder.split_once(":-") {
+                    Some((name, default)) => (name, Some(default)),
+                    None => (placeholder, None),
+                };
+
+                let value = self
+                    .get(name)
+                    .or(default)
+                    .ok_or_else(|| ConfigError::UndefinedVariable(name.into()))?;
+
+                ret.push_str(value);
+                rest = &x[end + 1..];
+            } else {
+                ret.push('$');
+            }
+        }
+
+        ret.push_str(rest);
+
+        Ok(ret)
+    }
+}
+
+impl Settings {
+    /// Reads settings from a configuration template (see [`Variables`] for
+    /// the syntax of the placeholders).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the placeholders could not be resolved, or the
+    /// resulting file could not be read (see
+    /// [`from_config_str`](Self::from_config_str)).
+    pub fn from_config_template(
+        s: &str,
+        format: ConfigFormat,
+        vars: &Variables,
+    ) -> Result<Self, ConfigError> {
+        Self::from_config_str(&vars.substitute(s)?, format)
+    }
+}
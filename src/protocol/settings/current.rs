@@ -0,0 +1,206 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::time::Duration;
+
+use super::{
+    simulate::{Inputs, Simulator},
+    CurrentDraw, LightingSettings, Settings,
+};
+
+/// Current drawn by a single color channel of an LED at full brightness, in
+/// milli ampere (mA).
+pub const LED_CHANNEL_CURRENT: f64 = 20.0;
+
+/// Current a USB port provides without increased current draw, in milli
+/// ampere (mA).
+pub const USB_DEFAULT_CURRENT: u16 = 500;
+
+/// Estimated current drawn by the device itself (electronics, display and
+/// the LEDs of the sensor), in milli ampere (mA).
+pub const DEVICE_CURRENT: u16 = 100;
+
+/// Length of the simulation used to estimate the typical current.
+const SIMULATION_DURATION: Duration = Duration::from_secs(10);
+
+/// Time between two simulated frames.
+const SIMULATION_STEP: Duration = Duration::from_millis(50);
+
+/// Estimated current drawn by a device for its lighting configuration.
+///
+/// All currents include the [`DEVICE_CURRENT`]. The estimate assumes common
+/// RGB LEDs drawing [`LED_CHANNEL_CURRENT`] per color channel, the actual
+/// current depends on the LED strip that is used.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct CurrentEstimate {
+    /// Number of LEDs of the strip that are driven by a controller.
+    pub leds: usize,
+
+    /// Peak current while the configured effects are running (see
+    /// [`Simulator`]), in milli ampere (mA).
+    ///
+    /// Effects controlled by a data source are simulated without input
+    /// values.
+    pub typical: u32,
+
+    /// Current if all LEDs are white at the configured brightness, in milli
+    /// ampere (mA).
+    pub worst_case: u32,
+}
+
+impl CurrentEstimate {
+    /// Returns the increased current draw that should be configured for the
+    /// [`typical`](Self::typical) current, or `None` if the default USB
+    /// current is sufficient.
+    ///
+    /// The current is rounded up to 100 mA and limited to the maximum
+    /// current draw the device supports (see [`check`](Self::check)).
+    #[must_use]
+    pub fn suggested_current_draw(&self) -> Option<CurrentDraw> {
+        if self.typical <= u32::from(USB_DEFAULT_CURRENT) {
+            return None;
+        }
+
+        let min = u32::from(CurrentDraw::min_inclusive());
+        let max = u32::from(CurrentDraw::max_inclusive());
+        let value = (self.typical.div_ceil(100) * 100).clamp(min, max);
+
+        CurrentDraw::from_value(u16::try_from(value).ok()?).ok()
+    }
+
+    /// Checks the [`typical`](Self::typical) current against the
+    /// `configured` increased current draw (see
+    /// [`SystemSettings::increased_current_draw`](super::SystemSettings::increased_current_draw)).
+    ///
+    /// Returns `None` if the current is sufficient.
+    #[must_use]
+    pub fn check(&self, configured: Option<CurrentDraw>) -> Option<CurrentWarning> {
+        let required = self.typical;
+        let available = configured.map_or(USB_DEFAULT_CURRENT, |x| *x);
+
+        if required > u32::from(CurrentDraw::max_inclusive()) {
+            Some(CurrentWarning::ExceedsMaximum { required })
+        } else if required > u32::from(available) {
+            Some(CurrentWarning::InsufficientCurrentDraw {
+                required,
+                available,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Warning returned by [`CurrentEstimate::check`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CurrentWarning {
+    /// The configured current draw is lower than the required current. The
+    /// current draw should be increased (see
+    /// [`CurrentEstimate::suggested_current_draw`]).
+    InsufficientCurrentDraw {
+        /// Required current in milli ampere (mA).
+        required: u32,
+
+        /// Available current in milli ampere (mA).
+        available: u16,
+    },
+
+    /// The required current exceeds the maximum current draw of the device.
+    /// The brightness or the number of LEDs should be reduced, or the LED
+    /// strip should be powered externally.
+    ExceedsMaximum {
+        /// Required current in milli ampere (mA).
+        required: u32,
+    },
+}
+
+impl Display for CurrentWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::InsufficientCurrentDraw {
+                required,
+                available,
+            } => write!(
+                f,
+                "LEDs require about {required} mA, but only {available} mA are available"
+            ),
+            Self::ExceedsMaximum { required } => write!(
+                f,
+                "LEDs require about {required} mA, which exceeds the maximum of {} mA",
+                CurrentDraw::max_inclusive()
+            ),
+        }
+    }
+}
+
+impl LightingSettings {
+    /// Estimates the current drawn by the device for this lighting
+    /// configuration.
+    ///
+    /// See [`CurrentEstimate`] for details.
+    #[must_use]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    pub fn estimate_current(&self) -> CurrentEstimate {
+        let brightness = f64::from(*self.brightness) / 255.0;
+        let channel_current = LED_CHANNEL_CURRENT * brightness / 255.0;
+
+        let mut used = [false; 256];
+        for controller in &self.strip_controllers {
+            let start = usize::from(controller.offset);
+            let end = (start + usize::from(controller.length)).min(used.len());
+
+            used[start..end].fill(true);
+        }
+        let leds = used.iter().filter(|x| **x).count();
+
+        let mut simulator = Simulator::new(self.strip_controllers.iter().cloned());
+        let inputs = Inputs::new();
+        let mut time = Duration::ZERO;
+        let mut peak = 0.0_f64;
+        while time <= SIMULATION_DURATION {
+            let current = simulator
+                .render(time, &inputs)
+                .iter()
+                .flatten()
+                .map(|x| f64::from(*x) * channel_current)
+                .sum::<f64>();
+
+            peak = peak.max(current);
+            time += SIMULATION_STEP;
+        }
+
+        let worst_case = leds as f64 * 3.0 * LED_CHANNEL_CURRENT * brightness;
+
+        CurrentEstimate {
+            leds,
+            typical: u32::from(DEVICE_CURRENT) + peak.ceil() as u32,
+            worst_case: u32::from(DEVICE_CURRENT) + worst_case.ceil() as u32,
+        }
+    }
+}
+
+impl Settings {
+    /// Estimates the current drawn by the device for its lighting
+    /// configuration (see [`LightingSettings::estimate_current`]).
+    #[must_use]
+    pub fn estimate_current(&self) -> CurrentEstimate {
+        self.lighting.as_ref().map_or(
+            CurrentEstimate {
+                leds: 0,
+                typical: u32::from(DEVICE_CURRENT),
+                worst_case: u32::from(DEVICE_CURRENT),
+            },
+            LightingSettings::estimate_current,
+        )
+    }
+
+    /// Checks the estimated current of the lighting configuration against the
+    /// configured increased current draw (see [`CurrentEstimate::check`]).
+    #[must_use]
+    pub fn check_current_draw(&self) -> Option<CurrentWarning> {
+        self.estimate_current()
+            .check(self.system.increased_current_draw)
+    }
+}
@@ -0,0 +1,584 @@
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+
+use arrayvec::ArrayVec;
+
+use crate::misc::Wrapped;
+
+use super::access::split_field;
+use super::{
+    Access, AlarmFlags, AlarmSettings, AquaBusAddress, Chart, ChartSource, Color, ConnectorType,
+    Controller, DataSource, DisplayBrightness, DisplayFlags, DisplaySettings, Effect,
+    EffectAmbient, EffectBarGraph, EffectBlink, EffectBreathing, EffectColorChange,
+    EffectColorGradient, EffectColorSequence, EffectColorShift, EffectColorSwitch, EffectFlame,
+    EffectRain, EffectRainbow, EffectScanner, EffectSequence, EffectSoundFlash, EffectSoundShift,
+    EffectSoundSlider, EffectStatic, EffectSwipingRainbow, EffectWave, FlowUnit, LightingSettings,
+    Medium, OutputSignal, PageFlags, PowerFlags, SensorSettings, Settings, SoundEffect,
+    SourceControl, StandbyFlags, SystemSettings, TemperatureUnit, Value, ValueError,
+};
+
+/// Field level difference between two [`Settings`].
+///
+/// Each [`Change`] is identified by the path of the changed field
+/// (e.g. `alarms.water_temperature_limit` or
+/// `lighting.strip_controllers[2].effect.speed`).
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+pub struct SettingsDiff {
+    changes: Vec<Change>,
+}
+
+impl SettingsDiff {
+    /// Calculates the difference between the `old` and the `new` settings.
+    #[must_use]
+    pub fn new(old: &Settings, new: &Settings) -> Self {
+        let mut diff = Self::default();
+
+        old.diff(new, "", &mut diff);
+
+        diff
+    }
+
+    /// Returns `true` if the settings are equal, `false` otherwise.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns the number of changed fields.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Returns the list of changed fields.
+    #[must_use]
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Returns an iterator over the changed fields.
+    pub fn iter(&self) -> std::slice::Iter<'_, Change> {
+        self.changes.iter()
+    }
+
+    /// Adds a change for the passed `path` to the diff.
+    pub fn push<T: Debug + Access>(&mut self, path: &str, old: Option<&T>, new: Option<&T>) {
+        self.changes.push(Change {
+            path: path.into(),
+            old: old.map(|x| format!("{x:?}")),
+            new: new.map(|x| format!("{x:?}")),
+            old_value: old.and_then(|x| x.get("")),
+            new_value: new.and_then(|x| x.get("")),
+        });
+    }
+}
+
+impl Display for SettingsDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> IntoIterator for &'a SettingsDiff {
+    type Item = &'a Change;
+    type IntoIter = std::slice::Iter<'a, Change>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A single changed field of a [`SettingsDiff`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Change {
+    /// Path of the changed field.
+    pub path: String,
+
+    /// Old value of the field (`None` if the field did not exist before).
+    pub old: Option<String>,
+
+    /// New value of the field (`None` if the field does not exist anymore).
+    pub new: Option<String>,
+
+    /// Old value of the field as [`Value`] (`None` if the field did not exist
+    /// before or is not a leaf field, like a whole controller).
+    pub old_value: Option<Value>,
+
+    /// New value of the field as [`Value`] (`None` if the field does not
+    /// exist anymore or is not a leaf field).
+    pub new_value: Option<Value>,
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let old = self.old.as_deref().unwrap_or("<none>");
+        let new = self.new.as_deref().unwrap_or("<none>");
+
+        write!(f, "{}: {old} -> {new}", self.path)
+    }
+}
+
+/// Trait for types that can be compared field by field.
+///
+/// Used to calculate the [`SettingsDiff`].
+pub trait Diff {
+    /// Compares `self` with `other` and adds all differences to `diff`.
+    ///
+    /// `path` is the path of the value itself.
+    fn diff(&self, other: &Self, path: &str, diff: &mut SettingsDiff);
+}
+
+macro_rules! impl_diff_leaf {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Diff for $ty {
+                fn diff(&self, other: &Self, path: &str, diff: &mut SettingsDiff) {
+                    if self != other {
+                        diff.push(path, Some(self), Some(other));
+                    }
+                }
+            }
+        )*
+    };
+}
+
+/// Implements [`Diff`] and [`Access`] for a struct using the listed fields.
+macro_rules! impl_diff_struct {
+    ($ty:ident { $($field:ident),* $(,)? }) => {
+        impl Diff for $ty {
+            fn diff(&self, other: &Self, path: &str, diff: &mut SettingsDiff) {
+                let Self { $($field),* } = self;
+
+                $(
+                    $field.diff(&other.$field, &join(path, stringify!($field)), diff);
+                )*
+            }
+        }
+
+        impl Access for $ty {
+            fn get(&self, path: &str) -> Option<Value> {
+                match split_field(path) {
+                    $( (stringify!($field), path) => Access::get(&self.$field, path), )*
+                    _ => None,
+                }
+            }
+
+            fn set(&mut self, path: &str, value: Value) -> Result<(), ValueError> {
+                match split_field(path) {
+                    $( (stringify!($field), path) => Access::set(&mut self.$field, path, value), )*
+                    _ => Err(ValueError::UnknownPath),
+                }
+            }
+        }
+    };
+}
+
+impl_diff_leaf!(
+    u8,
+    u16,
+    bool,
+    Color,
+    AquaBusAddress,
+    Medium,
+    ConnectorType,
+    OutputSignal,
+    TemperatureUnit,
+    FlowUnit,
+    DisplayBrightness,
+    ChartSource,
+    DataSource,
+    SoundEffect,
+    StandbyFlags,
+    PowerFlags,
+    AlarmFlags,
+    DisplayFlags,
+    PageFlags,
+);
+
+impl<T, X> Diff for Wrapped<T, X>
+where
+    T: Debug + PartialEq,
+    Self: Access,
+{
+    fn diff(&self, other: &Self, path: &str, diff: &mut SettingsDiff) {
+        if **self != **other {
+            diff.changes.push(Change {
+                path: path.into(),
+                old: Some(format!("{:?}", **self)),
+                new: Some(format!("{:?}", **other)),
+                old_value: self.get(""),
+                new_value: other.get(""),
+            });
+        }
+    }
+}
+
+impl<T> Diff for Option<T>
+where
+    T: Diff + Access + Debug,
+{
+    fn diff(&self, other: &Self, path: &str, diff: &mut SettingsDiff) {
+        match (self, other) {
+            (Some(a), Some(b)) => a.diff(b, path, diff),
+            (None, None) => (),
+            (a, b) => diff.push(path, Some(a), Some(b)),
+        }
+    }
+}
+
+impl<T, const N: usize> Diff for [T; N]
+where
+    T: Diff,
+{
+    fn diff(&self, other: &Self, path: &str, diff: &mut SettingsDiff) {
+        for (i, (a, b)) in self.iter().zip(other).enumerate() {
+            a.diff(b, &format!("{path}[{i}]"), diff);
+        }
+    }
+}
+
+impl<T, const N: usize> Diff for ArrayVec<T, N>
+where
+    T: Diff + Access + Debug,
+{
+    fn diff(&self, other: &Self, path: &str, diff: &mut SettingsDiff) {
+        let len = self.len().max(other.len());
+
+        for i in 0..len {
+            let path = format!("{path}[{i}]");
+
+            match (self.as_slice().get(i), other.as_slice().get(i)) {
+                (Some(a), Some(b)) => a.diff(b, &path, diff),
+                (a, b) => diff.push(&path, a, b),
+            }
+        }
+    }
+}
+
+impl<A, B> Diff for (A, B)
+where
+    A: Diff,
+    B: Diff,
+{
+    fn diff(&self, other: &Self, path: &str, diff: &mut SettingsDiff) {
+        self.0.diff(&other.0, &join(path, "0"), diff);
+        self.1.diff(&other.1, &join(path, "1"), diff);
+    }
+}
+
+impl<A, B, C> Diff for (A, B, C)
+where
+    A: Diff,
+    B: Diff,
+    C: Diff,
+{
+    fn diff(&self, other: &Self, path: &str, diff: &mut SettingsDiff) {
+        self.0.diff(&other.0, &join(path, "0"), diff);
+        self.1.diff(&other.1, &join(path, "1"), diff);
+        self.2.diff(&other.2, &join(path, "2"), diff);
+    }
+}
+
+impl Diff for Effect {
+    fn diff(&self, other: &Self, path: &str, diff: &mut SettingsDiff) {
+        match (self, other) {
+            (Self::Static(a), Self::Static(b)) => a.diff(b, path, diff),
+            (Self::Breathing(a), Self::Breathing(b)) => a.diff(b, path, diff),
+            (Self::Rainbow(a), Self::Rainbow(b)) => a.diff(b, path, diff),
+            (Self::Blink(a), Self::Blink(b)) => a.diff(b, path, diff),
+            (Self::ColorChange(a), Self::ColorChange(b)) => a.diff(b, path, diff),
+            (Self::Sequence(a), Self::Sequence(b)) => a.diff(b, path, diff),
+            (Self::Scanner(a), Self::Scanner(b)) | (Self::Laser(a), Self::Laser(b)) => {
+                a.diff(b, path, diff);
+            }
+            (Self::Wave(a), Self::Wave(b)) => a.diff(b, path, diff),
+            (Self::ColorSequence(a), Self::ColorSequence(b)) => a.diff(b, path, diff),
+            (Self::ColorShift(a), Self::ColorShift(b)) => a.diff(b, path, diff),
+            (Self::BarGraph(a), Self::BarGraph(b)) | (Self::SoundBars(a), Self::SoundBars(b)) => {
+                a.diff(b, path, diff);
+            }
+            (Self::Flame(a), Self::Flame(b)) => a.diff(b, path, diff),
+            (Self::Rain(a), Self::Rain(b))
+            | (Self::Snow(a), Self::Snow(b))
+            | (Self::Stardust(a), Self::Stardust(b)) => a.diff(b, path, diff),
+            (Self::ColorSwitch(a), Self::ColorSwitch(b)) => a.diff(b, path, diff),
+            (Self::SwipingRainbow(a), Self::SwipingRainbow(b)) => a.diff(b, path, diff),
+            (Self::SoundFlash(a), Self::SoundFlash(b)) => a.diff(b, path, diff),
+            (Self::SoundSlider(a), Self::SoundSlider(b)) => a.diff(b, path, diff),
+            (Self::SoundShift(a), Self::SoundShift(b)) => a.diff(b, path, diff),
+            (Self::Ambient(a), Self::Ambient(b)) => a.diff(b, path, diff),
+            (Self::ColorGradient(a), Self::ColorGradient(b)) => a.diff(b, path, diff),
+            (a, b) => diff.push(path, Some(&Name(a.name())), Some(&Name(b.name()))),
+        }
+    }
+}
+
+impl_diff_struct!(Settings {
+    system,
+    sensor,
+    alarms,
+    display,
+    lighting
+});
+impl_diff_struct!(SystemSettings {
+    standby_flags,
+    aqua_bus_address,
+    increased_current_draw
+});
+impl_diff_struct!(SensorSettings {
+    medium,
+    connector_type,
+    flow_correction,
+    water_temp_offset,
+    external_temp_offset,
+    conductivity_offset,
+    water_quality_max,
+    water_quality_min,
+    power_flags,
+    power_damping
+});
+impl_diff_struct!(AlarmSettings {
+    flags,
+    startup_delay,
+    flow_alarm_limit,
+    water_temperature_limit,
+    external_temperature_limit,
+    water_quality_limit,
+    output_signal
+});
+impl_diff_struct!(DisplaySettings {
+    temperature_unit,
+    flow_unit,
+    display_flags,
+    next_page_interval,
+    page_flags,
+    display_brightness,
+    idle_display_brightness,
+    charts
+});
+impl_diff_struct!(Chart { source, interval });
+impl_diff_struct!(LightingSettings {
+    brightness,
+    strip_controllers,
+    sensor_controllers
+});
+impl_diff_struct!(Controller {
+    offset,
+    length,
+    effect,
+    data_source,
+    sensor_attenuation_rising,
+    sensor_attenuation_falling
+});
+impl_diff_struct!(SourceControl {
+    input_min,
+    input_max,
+    output_min,
+    output_max
+});
+impl_diff_struct!(EffectStatic {
+    color,
+    source_control_brightness,
+    source_control_saturation
+});
+impl_diff_struct!(EffectBreathing {
+    color,
+    speed,
+    intensity,
+    delay_max_brightness,
+    delay_min_brightness,
+    source_control_speed,
+    source_control_intensity
+});
+impl_diff_struct!(EffectRainbow {
+    color,
+    speed,
+    color_range,
+    reverse_direction,
+    source_control_speed,
+    source_control_brightness
+});
+impl_diff_struct!(EffectBlink {
+    background,
+    colors,
+    speed,
+    fade_in,
+    fade_out,
+    random_color,
+    slide_colors,
+    source_control_speed,
+    source_control_brightness
+});
+impl_diff_struct!(EffectColorChange {
+    colors,
+    speed,
+    fade,
+    random_color,
+    slide_colors,
+    source_control_speed,
+    source_control_brightness
+});
+impl_diff_struct!(EffectSequence {
+    background,
+    colors,
+    speed,
+    smoothness,
+    delay_after_sequence,
+    delay_before_sequence,
+    reverse_direction,
+    fade,
+    random_color,
+    source_control_speed,
+    source_control_brightness
+});
+impl_diff_struct!(EffectScanner {
+    background,
+    inner_color,
+    outer_color,
+    speed,
+    smoothness,
+    width,
+    reverse_direction,
+    fade,
+    random_color,
+    second_color_mode,
+    color_change,
+    circular,
+    source_control_speed,
+    source_control_brightness
+});
+impl_diff_struct!(EffectWave {
+    background,
+    colors,
+    speed,
+    smoothness,
+    width,
+    reverse_direction,
+    random_color,
+    circular,
+    source_control_speed,
+    source_control_brightness
+});
+impl_diff_struct!(EffectColorSequence {
+    colors,
+    speed,
+    smoothness,
+    color_change_speed,
+    reverse_direction,
+    random_color,
+    source_control_speed,
+    source_control_brightness
+});
+impl_diff_struct!(EffectColorShift {
+    color,
+    speed,
+    color_range,
+    total_area,
+    reverse_direction,
+    source_control_speed,
+    source_control_brightness
+});
+impl_diff_struct!(EffectBarGraph {
+    background,
+    peak_color,
+    colors,
+    end_value,
+    rotation,
+    peak_hold_time,
+    reverse_direction,
+    show_peak,
+    show_bar,
+    show_ranges,
+    fade_ranges,
+    source_control_rotation
+});
+impl_diff_struct!(EffectFlame {
+    background,
+    color_primary,
+    color_secondary,
+    intensity,
+    source_control_intensity
+});
+impl_diff_struct!(EffectRain {
+    background,
+    color,
+    speed,
+    items,
+    size,
+    smoothness,
+    reverse_direction,
+    random_color,
+    source_control_speed,
+    source_control_brightness
+});
+impl_diff_struct!(EffectColorSwitch {
+    colors,
+    end_value,
+    fade_ranges,
+    source_control_brightness
+});
+impl_diff_struct!(EffectSwipingRainbow {
+    point_color,
+    strip_color,
+    point_speed,
+    point_smoothness,
+    point_size,
+    color_change_speed,
+    color_range,
+    reverse_direction,
+    source_control_speed,
+    source_control_brightness
+});
+impl_diff_struct!(EffectSoundFlash { background, colors });
+impl_diff_struct!(EffectSoundSlider {
+    background,
+    effects,
+    rotate_color
+});
+impl_diff_struct!(EffectSoundShift {
+    background,
+    effects,
+    rotate_color,
+    idle_speed,
+    activity_speed,
+    reverse_direction
+});
+impl_diff_struct!(EffectAmbient { background });
+impl_diff_struct!(EffectColorGradient {
+    start_color,
+    colors,
+    rotation,
+    reverse_direction,
+    reverse_rotation,
+    source_control_rotation
+});
+
+/// Helper to print a name without quotes in the [`Debug`] output.
+struct Name(&'static str);
+
+impl Debug for Name {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(self.0)
+    }
+}
+
+impl Access for Name {
+    fn get(&self, path: &str) -> Option<Value> {
+        path.is_empty().then(|| Value::Enum(self.0.into()))
+    }
+
+    fn set(&mut self, _path: &str, value: Value) -> Result<(), ValueError> {
+        Err(ValueError::InvalidValue(value))
+    }
+}
+
+fn join(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.into()
+    } else {
+        format!("{path}.{field}")
+    }
+}
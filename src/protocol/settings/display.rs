@@ -1,10 +1,13 @@
 use bitflags::bitflags;
 
-use crate::misc::{Decode, Guard, GuardOutput, IoError, Reader};
+use crate::misc::{Decode, FixedSize, Guard, GuardOutput, IoError, Reader};
 use crate::{define_wrapped, impl_ranged};
 
 /// Display related settings for a high flow NEXT device.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub struct DisplaySettings {
     /// Unit do display temperatures in.
     pub temperature_unit: TemperatureUnit,
@@ -31,6 +34,48 @@ pub struct DisplaySettings {
     pub charts: [Chart; 4],
 }
 
+impl DisplaySettings {
+    /// Creates new display settings with the passed units, pages, brightness
+    /// and `charts`.
+    ///
+    /// The display flags are cleared, the pages are not cycled and the
+    /// brightness is not changed during stand by.
+    #[must_use]
+    pub fn new(
+        temperature_unit: TemperatureUnit,
+        flow_unit: FlowUnit,
+        page_flags: PageFlags,
+        display_brightness: DisplayBrightness,
+        charts: [Chart; 4],
+    ) -> Self {
+        Self {
+            temperature_unit,
+            flow_unit,
+            display_flags: DisplayFlags::empty(),
+            next_page_interval: None,
+            page_flags,
+            display_brightness,
+            idle_display_brightness: None,
+            charts,
+        }
+    }
+}
+
+impl FixedSize for DisplaySettings {
+    const SIZE: usize = TemperatureUnit::SIZE
+        + FlowUnit::SIZE
+        + 1
+        + Option::<NextPageInterval>::SIZE
+        + 2
+        + PageFlags::SIZE
+        + 4
+        + DisplayBrightness::SIZE
+        + Option::<DisplayBrightness>::SIZE
+        + 4
+        + DisplayFlags::SIZE
+        + <[Chart; 4]>::SIZE;
+}
+
 impl Decode for DisplaySettings {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         let temperature_unit = TemperatureUnit::decode(reader)?;
@@ -63,6 +108,8 @@ impl Decode for DisplaySettings {
 ///
 /// Used in [`DisplaySettings::charts`].
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Chart {
     /// Source of the data that is displayed in the chart.
     pub source: ChartSource,
@@ -71,6 +118,10 @@ pub struct Chart {
     pub interval: ChartInterval,
 }
 
+impl FixedSize for Chart {
+    const SIZE: usize = 1 + ChartSource::SIZE + ChartInterval::SIZE;
+}
+
 impl Decode for Chart {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         reader.skip::<1>()?;
@@ -88,6 +139,8 @@ impl Decode for Chart {
 ///
 /// Used in [`DisplaySettings::temperature_unit`].
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum TemperatureUnit {
     /// Degree Celsius (°C)
     C,
@@ -96,6 +149,10 @@ pub enum TemperatureUnit {
     F,
 }
 
+impl FixedSize for TemperatureUnit {
+    const SIZE: usize = u8::SIZE;
+}
+
 impl Decode for TemperatureUnit {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         match reader.read_u8()? {
@@ -106,10 +163,35 @@ impl Decode for TemperatureUnit {
     }
 }
 
+impl TemperatureUnit {
+    /// Converts the passed temperature `value` in this unit to degree Celsius.
+    #[must_use]
+    pub fn to_celsius(self, value: f64) -> f64 {
+        match self {
+            Self::C => value,
+            Self::F => (value - 32.0) * 5.0 / 9.0,
+        }
+    }
+
+    /// Converts the passed temperature `value` from this unit to the unit
+    /// `to`.
+    #[must_use]
+    pub fn convert(self, value: f64, to: Self) -> f64 {
+        let celsius = self.to_celsius(value);
+
+        match to {
+            Self::C => celsius,
+            Self::F => celsius * 9.0 / 5.0 + 32.0,
+        }
+    }
+}
+
 /// Unit do display the flow in.
 ///
 /// Used in [`DisplaySettings::flow_unit`].
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum FlowUnit {
     /// Liter per hour (L/h).
     Liter,
@@ -118,6 +200,10 @@ pub enum FlowUnit {
     Gallons,
 }
 
+impl FixedSize for FlowUnit {
+    const SIZE: usize = u8::SIZE;
+}
+
 impl Decode for FlowUnit {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         match reader.read_u8()? {
@@ -130,6 +216,8 @@ impl Decode for FlowUnit {
 
 /// Display brightness.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum DisplayBrightness {
     /// Maximum display brightness.
     Maximum,
@@ -141,6 +229,10 @@ pub enum DisplayBrightness {
     Low,
 }
 
+impl FixedSize for DisplayBrightness {
+    const SIZE: usize = u8::SIZE;
+}
+
 impl Decode for DisplayBrightness {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         match reader.read_u8()? {
@@ -152,6 +244,10 @@ impl Decode for DisplayBrightness {
     }
 }
 
+impl FixedSize for Option<DisplayBrightness> {
+    const SIZE: usize = u8::SIZE;
+}
+
 impl Decode for Option<DisplayBrightness> {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         match reader.read_u8()? {
@@ -168,6 +264,8 @@ impl Decode for Option<DisplayBrightness> {
 ///
 /// Used in [`Chart::source`].
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ChartSource {
     /// Current water flow.
     Flow,
@@ -191,6 +289,10 @@ pub enum ChartSource {
     SystemVoltage,
 }
 
+impl FixedSize for ChartSource {
+    const SIZE: usize = u8::SIZE;
+}
+
 impl Decode for ChartSource {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         match reader.read_u8()? {
@@ -209,6 +311,7 @@ impl Decode for ChartSource {
 bitflags! {
     /// Different flags uses in [`DisplaySettings::flags`].
     #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct DisplayFlags: u8 {
         /// Rotate the display by 180°
         const ROTATE = 0x01;
@@ -227,6 +330,10 @@ bitflags! {
     }
 }
 
+impl FixedSize for DisplayFlags {
+    const SIZE: usize = u8::SIZE;
+}
+
 impl Decode for DisplayFlags {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         let bits = reader.read_u8()?;
@@ -240,6 +347,7 @@ bitflags! {
     ///
     /// Uses in [`DisplaySettings::page_flags`].
     #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct PageFlags: u16 {
         /// Show the device logo page.
         const DEVICE_INFO = 0x0001;
@@ -291,6 +399,10 @@ bitflags! {
     }
 }
 
+impl FixedSize for PageFlags {
+    const SIZE: usize = u16::SIZE;
+}
+
 impl Decode for PageFlags {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         let bits = reader.read_u16be()?;
@@ -319,6 +431,10 @@ define_wrapped! {
 }
 impl_ranged!(NextPageInterval<u8, NextPageIntervalTag>, 3, 60);
 
+impl FixedSize for Option<NextPageInterval> {
+    const SIZE: usize = u8::SIZE;
+}
+
 impl Decode for Option<NextPageInterval> {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         let val = reader.read_u8()?;
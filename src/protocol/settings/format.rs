@@ -0,0 +1,35 @@
+//! [`defmt::Format`] implementations for the types that can not derive it.
+
+use defmt::{write, Debug2Format, Format, Formatter};
+
+use super::{AlarmFlags, DisplayFlags, PageFlags, PowerFlags, Settings, StandbyFlags};
+
+macro_rules! impl_format_flags {
+    ($name:ident, $format:literal) => {
+        impl Format for $name {
+            fn format(&self, fmt: Formatter<'_>) {
+                write!(fmt, $format, self.bits());
+            }
+        }
+    };
+}
+
+impl_format_flags!(AlarmFlags, "AlarmFlags({=u8:#b})");
+impl_format_flags!(DisplayFlags, "DisplayFlags({=u8:#b})");
+impl_format_flags!(PageFlags, "PageFlags({=u16:#b})");
+impl_format_flags!(PowerFlags, "PowerFlags({=u8:#b})");
+impl_format_flags!(StandbyFlags, "StandbyFlags({=u8:#b})");
+
+impl Format for Settings {
+    fn format(&self, fmt: Formatter<'_>) {
+        write!(
+            fmt,
+            "Settings {{ system: {}, sensor: {}, alarms: {}, display: {}, lighting: {} }}",
+            self.system,
+            self.sensor,
+            self.alarms,
+            self.display,
+            Debug2Format(&self.lighting),
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use std::ops::Range;
+
+use arrayvec::ArrayVec;
+
+use super::{
+    Color, Controller, DataSource, Effect, EffectBarGraph, EffectColorSwitch, EffectPercent,
+    SourceControl,
+};
+
+/// Number of LEDs of the sensor ring.
+pub const SENSOR_RING_LEDS: u8 = 10;
+
+impl DataSource {
+    /// List of all data sources.
+    pub const ALL: [Self; 15] = [
+        Self::Flow,
+        Self::WaterTemperature,
+        Self::ExternalTemperature,
+        Self::Conductivity,
+        Self::WaterQuality,
+        Self::Power,
+        Self::Sound,
+        Self::SoftwareSensor1,
+        Self::SoftwareSensor2,
+        Self::SoftwareSensor3,
+        Self::SoftwareSensor4,
+        Self::SoftwareSensor5,
+        Self::SoftwareSensor6,
+        Self::SoftwareSensor7,
+        Self::SoftwareSensor8,
+    ];
+
+    /// Returns the factor between the physical value of the data source and
+    /// the raw value used by the effects (e.g. `100.0` for temperatures in
+    /// 1/100 °C).
+    ///
+    /// The device does not document the scale of [`Power`](Self::Power) and
+    /// the software sensors, they are assumed to use 1/100 like the
+    /// temperatures.
+    #[must_use]
+    pub fn scale(&self) -> f64 {
+        match self {
+            Self::Flow => 10.0,
+            Self::Conductivity | Self::Sound => 1.0,
+            Self::WaterTemperature
+            | Self::ExternalTemperature
+            | Self::WaterQuality
+            | Self::Power
+            | Self::SoftwareSensor1
+            | Self::SoftwareSensor2
+            | Self::SoftwareSensor3
+            | Self::SoftwareSensor4
+            | Self::SoftwareSensor5
+            | Self::SoftwareSensor6
+            | Self::SoftwareSensor7
+            | Self::SoftwareSensor8 => 100.0,
+        }
+    }
+
+    /// Converts the passed physical `value` to the raw value used by the
+    /// effects (see [`scale`](Self::scale)), clamped to the range of `u16`.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn to_raw(&self, value: f64) -> u16 {
+        (value * self.scale())
+            .round()
+            .clamp(0.0, f64::from(u16::MAX)) as u16
+    }
+}
+
+/// Kind of effect generated by a [`Gauge`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum GaugeStyle {
+    /// A [`BarGraph`](Effect::BarGraph) whose length follows the value, in
+    /// the color of the current value.
+    BarGraph,
+
+    /// A [`ColorSwitch`](Effect::ColorSwitch) that colors all LEDs in the
+    /// color of the current value.
+    ColorSwitch,
+}
+
+/// Generator for a [`Controller`] that displays a reading as gauge on the
+/// sensor ring.
+///
+/// The colors of the `palette` are distributed evenly over the `range` (in
+/// the physical unit of the data source, e.g. °C for
+/// [`DataSource::WaterTemperature`]), and the thresholds are converted to
+/// the raw values of the data source (see [`DataSource::scale`]). Palettes
+/// with more colors than supported by the effect are resampled.
+///
+/// The bar graph of the device always starts at zero, so the start of the
+/// `range` only affects the colors of a [`GaugeStyle::BarGraph`].
+///
+/// # Example
+///
+/// ```rust
+/// use high_flow_next::protocol::settings::{gauge_for, Color, DataSource, Effect};
+///
+/// let palette = [
+///     Color::from_rgb(0, 0, 255),
+///     Color::from_rgb(0, 255, 0),
+///     Color::from_rgb(255, 0, 0),
+/// ];
+/// let controller = gauge_for(DataSource::WaterTemperature, 20.0..45.0, &palette);
+///
+/// let Effect::BarGraph(bar) = &controller.effect else {
+///     unreachable!();
+/// };
+/// assert_eq!(bar.end_value, 4500);
+/// assert_eq!(bar.colors[1].1, 3250);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Gauge {
+    source: DataSource,
+    range: Range<f64>,
+    palette: Vec<Color>,
+    style: GaugeStyle,
+    offset: u8,
+    length: u8,
+    background: Color,
+    brightness: Option<(u8, u8)>,
+}
+
+impl Gauge {
+    /// Creates a new bar graph gauge for the passed `source` on the sensor
+    /// ring.
+    #[must_use]
+    pub fn new(source: DataSource, range: Range<f64>, palette: &[Color]) -> Self {
+        Self {
+            source,
+            range,
+            palette: palette.to_vec(),
+            style: GaugeStyle::BarGraph,
+            offset: 0,
+            length: SENSOR_RING_LEDS,
+            background: Color::from_rgb(0, 0, 0),
+            brightness: None,
+        }
+    }
+
+    /// Sets the kind of effect that is generated.
+    #[must_use]
+    pub fn style(mut self, style: GaugeStyle) -> Self {
+        self.style = style;
+
+        self
+    }
+
+    /// Sets the region of the controller (by default the whole sensor ring).
+    #[must_use]
+    pub fn region(mut self, offset: u8, length: u8) -> Self {
+        self.offset = offset;
+        self.length = length;
+
+        self
+    }
+
+    /// Sets the color of the LEDs that are not part of the bar (black by
+    /// default).
+    #[must_use]
+    pub fn background(mut self, background: Color) -> Self {
+        self.background = background;
+
+        self
+    }
+
+    /// Lets the brightness of a [`GaugeStyle::ColorSwitch`] follow the value,
+    /// from `min` percent at the start of the range to `max` percent at its
+    /// end.
+    #[must_use]
+    pub fn brightness(mut self, min: u8, max: u8) -> Self {
+        self.brightness = Some((min.min(100), max.min(100)));
+
+        self
+    }
+
+    /// Returns the source control that maps the range of the gauge to the
+    /// passed `output` range.
+    #[must_use]
+    pub fn source_control(&self, output_min: u8, output_max: u8) -> SourceControl {
+        SourceControl {
+            input_min: self.source.to_raw(self.range.start),
+            input_max: self.source.to_raw(self.range.end),
+            output_min,
+            output_max,
+        }
+    }
+
+    /// Builds the controller.
+    #[must_use]
+    pub fn controller(&self) -> Controller {
+        let effect = match self.style {
+            GaugeStyle::BarGraph => {
+                let colors = self.ranges();
+
+                Effect::BarGraph(EffectBarGraph {
+                    background: self.background,
+                    peak_color: colors.last().map_or(self.background, |x| x.0),
+                    colors,
+                    end_value: self.source.to_raw(self.range.end),
+                    rotation: EffectPercent::from_percent(0.0),
+                    peak_hold_time: EffectPercent::from_percent(0.0),
+                    reverse_direction: false,
+                    show_peak: false,
+                    show_bar: true,
+                    show_ranges: false,
+                    fade_ranges: true,
+                    source_control_rotation: None,
+                })
+            }
+            GaugeStyle::ColorSwitch => Effect::ColorSwitch(EffectColorSwitch {
+                colors: self.ranges(),
+                end_value: self.source.to_raw(self.range.end),
+                fade_ranges: true,
+                source_control_brightness: self
+                    .brightness
+                    .map(|(min, max)| self.source_control(min, max)),
+            }),
+        };
+
+        Controller {
+            offset: self.offset,
+            length: self.length,
+            effect,
+            data_source: Some(self.source),
+            sensor_attenuation_rising: 0,
+            sensor_attenuation_falling: 0,
+        }
+    }
+
+    /// Distributes the (resampled) palette evenly over the range.
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn ranges<const N: usize>(&self) -> ArrayVec<(Color, u16, bool), N> {
+        let palette = &self.palette;
+        let count = palette.len().min(N);
+        let step = |i: usize, n: usize| {
+            if n > 1 {
+                i as f64 / (n - 1) as f64
+            } else {
+                0.0
+            }
+        };
+
+        (0..count)
+            .map(|i| {
+                let f = step(i, count);
+                let color = palette[(f * (palette.len() - 1) as f64).round() as usize];
+                let value = self.range.start + f * (self.range.end - self.range.start);
+
+                (color, self.source.to_raw(value), true)
+            })
+            .collect()
+    }
+}
+
+/// Builds a bar graph [`Controller`] for the sensor ring that displays the
+/// `source` in the passed `range` using the colors of the `palette` (see
+/// [`Gauge`]).
+#[must_use]
+pub fn gauge_for(source: DataSource, range: Range<f64>, palette: &[Color]) -> Controller {
+    Gauge::new(source, range, palette).controller()
+}
@@ -0,0 +1,234 @@
+use arrayvec::ArrayVec;
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::{Controller, Effect, STRIP_CONTROLLERS};
+
+/// Error returned while importing an [`LedLayout`].
+#[derive(Debug, Error)]
+pub enum LayoutError {
+    /// Error while parsing the description.
+    #[error("JSON Error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The layout contains more zones than the device has strip controllers.
+    #[error("Too many zones (zones={0}, max={STRIP_CONTROLLERS})")]
+    TooManyZones(usize),
+
+    /// The layout contains more LEDs than a controller can address.
+    #[error("Too many LEDs (leds={0}, max={max})", max = u8::MAX)]
+    TooManyLeds(usize),
+}
+
+/// A named zone of an [`LedLayout`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LedZone {
+    /// Name of the zone.
+    pub name: String,
+
+    /// Number of LEDs of the zone.
+    pub leds: usize,
+}
+
+/// Layout of the LED strip connected to the device, as a list of zones.
+///
+/// The layout can be imported from the descriptions used by common RGB
+/// software, so the [`Controller`] regions do not have to be counted
+/// manually:
+///
+/// - [`from_openrgb_json`](Self::from_openrgb_json): a controller of the
+///   `OpenRGB` SDK (`zones` with `name` and `leds_count`)
+/// - [`from_signalrgb_json`](Self::from_signalrgb_json): one or more
+///   `SignalRGB` components (`DisplayName` / `ProductName` and `LedCount`)
+///
+/// The zones are placed one after another on the strip, see
+/// [`controllers`](Self::controllers).
+///
+/// # Example
+///
+/// ```rust
+/// use high_flow_next::protocol::settings::LedLayout;
+///
+/// let layout = LedLayout::from_openrgb_json(
+///     r#"{ "name": "Case", "zones": [
+///         { "name": "Front", "leds_count": 24 },
+///         { "name": "Top", "leds_count": 16 }
+///     ] }"#,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(layout.regions().unwrap(), [(0, 24), (24, 16)]);
+/// ```
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+pub struct LedLayout {
+    zones: Vec<LedZone>,
+}
+
+impl LedLayout {
+    /// Creates a new empty layout.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a zone with the passed `name` and number of `leds`.
+    #[must_use]
+    pub fn zone<S: Into<String>>(mut self, name: S, leds: usize) -> Self {
+        self.zones.push(LedZone {
+            name: name.into(),
+            leds,
+        });
+
+        self
+    }
+
+    /// Returns the zones of the layout.
+    #[must_use]
+    pub fn zones(&self) -> &[LedZone] {
+        &self.zones
+    }
+
+    /// Returns the total number of LEDs of the layout.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LayoutError::TooManyLeds`] if the total does not fit into a
+    /// `usize`.
+    pub fn led_count(&self) -> Result<usize, LayoutError> {
+        self.zones
+            .iter()
+            .try_fold(0_usize, |count, zone| count.checked_add(zone.leds))
+            .ok_or(LayoutError::TooManyLeds(usize::MAX))
+    }
+
+    /// Reads the layout from the JSON description of an `OpenRGB` controller.
+    ///
+    /// Zones without LEDs are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the description could not be parsed.
+    pub fn from_openrgb_json(s: &str) -> Result<Self, LayoutError> {
+        let controller: OpenRgbController = serde_json::from_str(s)?;
+
+        Ok(controller
+            .zones
+            .into_iter()
+            .filter(|zone| zone.leds_count > 0)
+            .fold(Self::new(), |layout, zone| {
+                layout.zone(zone.name, zone.leds_count)
+            }))
+    }
+
+    /// Reads the layout from the JSON description of a single `SignalRGB`
+    /// component, or from an array of components (one zone per component).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the description could not be parsed.
+    pub fn from_signalrgb_json(s: &str) -> Result<Self, LayoutError> {
+        let components = match serde_json::from_str(s)? {
+            SignalRgbComponents::Single(component) => vec![component],
+            SignalRgbComponents::Multiple(components) => components,
+        };
+
+        Ok(components
+            .into_iter()
+            .filter(|component| component.led_count > 0)
+            .fold(Self::new(), |layout, component| {
+                let name = component
+                    .display_name
+                    .or(component.product_name)
+                    .unwrap_or_default();
+
+                layout.zone(name, component.led_count)
+            }))
+    }
+
+    /// Returns the `(offset, length)` regions of the zones, placed one after
+    /// another on the strip.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the layout has more zones than strip controllers,
+    /// or more LEDs than a controller can address.
+    pub fn regions(&self) -> Result<Vec<(u8, u8)>, LayoutError> {
+        if self.zones.len() > STRIP_CONTROLLERS {
+            return Err(LayoutError::TooManyZones(self.zones.len()));
+        }
+
+        let led_count = self.led_count()?;
+        if led_count > usize::from(u8::MAX) {
+            return Err(LayoutError::TooManyLeds(led_count));
+        }
+
+        let mut offset = 0;
+
+        Ok(self
+            .zones
+            .iter()
+            .map(|zone| {
+                // Both values fit into `u8`, the total was checked above
+                let start = u8::try_from(offset).unwrap_or(u8::MAX);
+                let length = u8::try_from(zone.leds).unwrap_or(u8::MAX);
+                offset += zone.leds;
+
+                (start, length)
+            })
+            .collect())
+    }
+
+    /// Returns one strip controller per zone that displays the passed
+    /// `effect`.
+    ///
+    /// The effects of the single controllers can be changed afterwards.
+    ///
+    /// # Errors
+    ///
+    /// See [`regions`](Self::regions).
+    pub fn controllers(
+        &self,
+        effect: &Effect,
+    ) -> Result<ArrayVec<Controller, STRIP_CONTROLLERS>, LayoutError> {
+        Ok(self
+            .regions()?
+            .into_iter()
+            .map(|(offset, length)| Controller {
+                offset,
+                length,
+                effect: effect.clone(),
+                data_source: None,
+                sensor_attenuation_rising: 0,
+                sensor_attenuation_falling: 0,
+            })
+            .collect())
+    }
+}
+
+#[derive(Deserialize)]
+struct OpenRgbController {
+    #[serde(default)]
+    zones: Vec<OpenRgbZone>,
+}
+
+#[derive(Deserialize)]
+struct OpenRgbZone {
+    #[serde(default)]
+    name: String,
+    leds_count: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SignalRgbComponents {
+    Single(SignalRgbComponent),
+    Multiple(Vec<SignalRgbComponent>),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SignalRgbComponent {
+    display_name: Option<String>,
+    product_name: Option<String>,
+    led_count: usize,
+}
@@ -1,26 +1,55 @@
+pub mod simulate;
+
+mod gauge;
+#[cfg(feature = "layout")]
+mod layout;
+#[cfg(feature = "rand")]
+mod random;
+#[cfg(feature = "serde")]
+mod tagged;
+mod toggle;
+
 use std::array::from_fn;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
 
 use arrayvec::ArrayVec;
-use color_space::{FromRgb, Hsv, Rgb};
+use color_space::{FromRgb, Hsv, Rgb, ToRgb};
 
-use crate::misc::{Decode, Guard, GuardOutput, IoError, Reader};
-use crate::{define_wrapped, impl_ranged, impl_verify_simple};
+use crate::misc::{Decode, FixedSize, Guard, GuardOutput, IoError, Reader};
+use crate::{define_wrapped, impl_percent, impl_ranged, impl_verify_simple};
 
 use super::flag_set;
 
+pub use self::gauge::{gauge_for, Gauge, GaugeStyle, SENSOR_RING_LEDS};
+#[cfg(feature = "layout")]
+pub use self::layout::{LayoutError, LedLayout, LedZone};
+#[cfg(feature = "serde")]
+pub use self::tagged::{EffectRecord, ParamValue, UnknownParams};
+pub use self::toggle::{ControllerGroup, DisabledController, DisabledControllers, ToggleError};
+
+/// Maximum number of controllers of the LED strip (see
+/// [`LightingSettings::strip_controllers`]).
+pub const STRIP_CONTROLLERS: usize = 6;
+
 /// Lighting / `RGBpx` related settings for a high flow NEXT device.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub struct LightingSettings {
     /// General Brightness of all LED effects.
     pub brightness: Brightness,
 
     /// List of controllers for the LED strip (external connector)
-    pub strip_controllers: ArrayVec<Controller, 6>,
+    pub strip_controllers: ArrayVec<Controller, STRIP_CONTROLLERS>,
 
     /// List of controllers for the
     pub sensor_controllers: ArrayVec<Controller, 2>,
 }
 
+impl FixedSize for Option<LightingSettings> {
+    const SIZE: usize = Brightness::SIZE + 1 + u8::SIZE + 1 + <[Option<Controller>; 8]>::SIZE;
+}
+
 impl Decode for Option<LightingSettings> {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         let brightness = Brightness::decode(reader)?;
@@ -33,7 +62,7 @@ impl Decode for Option<LightingSettings> {
 
             Ok(R::guard(|_| None))
         } else {
-            let strip_controllers = <[Option<Controller>; 6]>::decode(reader)?;
+            let strip_controllers = <[Option<Controller>; STRIP_CONTROLLERS]>::decode(reader)?;
             let sensor_controllers = <[Option<Controller>; 2]>::decode(reader)?;
 
             Ok(R::guard(|x| {
@@ -51,8 +80,58 @@ impl Decode for Option<LightingSettings> {
     }
 }
 
+impl LightingSettings {
+    /// Creates new lighting settings with the passed `brightness` and without
+    /// any controllers.
+    #[must_use]
+    pub fn new(brightness: Brightness) -> Self {
+        Self {
+            brightness,
+            strip_controllers: ArrayVec::new(),
+            sensor_controllers: ArrayVec::new(),
+        }
+    }
+
+    /// Returns the number of LEDs of the strip that are covered by the strip
+    /// controllers (end of the last controller).
+    #[must_use]
+    pub fn strip_len(&self) -> usize {
+        self.strip_controllers
+            .iter()
+            .map(|x| usize::from(x.offset) + usize::from(x.length))
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Scales the regions of the strip controllers from a strip with `from`
+    /// LEDs to a strip with `to` LEDs (e.g. to copy the lighting to a device
+    /// with a longer strip).
+    ///
+    /// Controllers keep a length of at least one LED.
+    pub fn remap_strip(&mut self, from: usize, to: usize) {
+        if from == 0 || from == to {
+            return;
+        }
+
+        let scale = |x: usize| {
+            let x = (x * to + from / 2) / from;
+
+            u8::try_from(x).unwrap_or(u8::MAX)
+        };
+
+        for controller in &mut self.strip_controllers {
+            let start = usize::from(controller.offset);
+            let end = start + usize::from(controller.length);
+
+            controller.offset = scale(start);
+            controller.length = scale(end).saturating_sub(controller.offset).max(1);
+        }
+    }
+}
+
 /// Defines a single LED effect.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Controller {
     /// Offset in the LED strip (in number of LEDs).
     pub offset: u8,
@@ -74,6 +153,13 @@ pub struct Controller {
 }
 
 /// Defines different effects that are displayed for a specific [`Controller`].
+///
+/// Human readable formats store the effect as object with the `kind` of the
+/// effect (the [`name`](Self::name)) followed by its `params`. Parameters
+/// that are not known by this version are ignored when loading the effect
+/// (use `EffectRecord` to preserve them), and the externally tagged format
+/// of older versions (`{ "Rainbow": { ... } }`) is accepted as well. Binary
+/// formats store the index of the effect followed by its parameters.
 #[allow(missing_docs)]
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Effect {
@@ -103,8 +189,140 @@ pub enum Effect {
     ColorGradient(EffectColorGradient),
 }
 
+impl Effect {
+    /// Returns the name of the effect (the name of the enum variant).
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        self.kind().name()
+    }
+
+    /// Returns the kind of the effect.
+    #[must_use]
+    pub fn kind(&self) -> EffectKind {
+        match self {
+            Self::Static(_) => EffectKind::Static,
+            Self::Breathing(_) => EffectKind::Breathing,
+            Self::Rainbow(_) => EffectKind::Rainbow,
+            Self::Blink(_) => EffectKind::Blink,
+            Self::ColorChange(_) => EffectKind::ColorChange,
+            Self::Sequence(_) => EffectKind::Sequence,
+            Self::Scanner(_) => EffectKind::Scanner,
+            Self::Laser(_) => EffectKind::Laser,
+            Self::Wave(_) => EffectKind::Wave,
+            Self::ColorSequence(_) => EffectKind::ColorSequence,
+            Self::ColorShift(_) => EffectKind::ColorShift,
+            Self::BarGraph(_) => EffectKind::BarGraph,
+            Self::Flame(_) => EffectKind::Flame,
+            Self::Rain(_) => EffectKind::Rain,
+            Self::Snow(_) => EffectKind::Snow,
+            Self::Stardust(_) => EffectKind::Stardust,
+            Self::ColorSwitch(_) => EffectKind::ColorSwitch,
+            Self::SwipingRainbow(_) => EffectKind::SwipingRainbow,
+            Self::SoundFlash(_) => EffectKind::SoundFlash,
+            Self::SoundBars(_) => EffectKind::SoundBars,
+            Self::SoundSlider(_) => EffectKind::SoundSlider,
+            Self::SoundShift(_) => EffectKind::SoundShift,
+            Self::Ambient(_) => EffectKind::Ambient,
+            Self::ColorGradient(_) => EffectKind::ColorGradient,
+        }
+    }
+}
+
+/// Kind of an [`Effect`], without the effect specific parameters.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EffectKind {
+    Static,
+    Breathing,
+    Rainbow,
+    Blink,
+    ColorChange,
+    Sequence,
+    Scanner,
+    Laser,
+    Wave,
+    ColorSequence,
+    ColorShift,
+    BarGraph,
+    Flame,
+    Rain,
+    Snow,
+    Stardust,
+    ColorSwitch,
+    SwipingRainbow,
+    SoundFlash,
+    SoundBars,
+    SoundSlider,
+    SoundShift,
+    Ambient,
+    ColorGradient,
+}
+
+impl EffectKind {
+    /// All effect kinds, in the order of their identifiers on the device.
+    pub const ALL: [Self; 24] = [
+        Self::Static,
+        Self::Breathing,
+        Self::Rainbow,
+        Self::Blink,
+        Self::ColorChange,
+        Self::Sequence,
+        Self::Scanner,
+        Self::Laser,
+        Self::Wave,
+        Self::ColorSequence,
+        Self::ColorShift,
+        Self::BarGraph,
+        Self::Flame,
+        Self::Rain,
+        Self::Snow,
+        Self::Stardust,
+        Self::ColorSwitch,
+        Self::SwipingRainbow,
+        Self::SoundFlash,
+        Self::SoundBars,
+        Self::SoundSlider,
+        Self::SoundShift,
+        Self::Ambient,
+        Self::ColorGradient,
+    ];
+
+    /// Returns the name of the effect kind (the name of the enum variant).
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Static => "Static",
+            Self::Breathing => "Breathing",
+            Self::Rainbow => "Rainbow",
+            Self::Blink => "Blink",
+            Self::ColorChange => "ColorChange",
+            Self::Sequence => "Sequence",
+            Self::Scanner => "Scanner",
+            Self::Laser => "Laser",
+            Self::Wave => "Wave",
+            Self::ColorSequence => "ColorSequence",
+            Self::ColorShift => "ColorShift",
+            Self::BarGraph => "BarGraph",
+            Self::Flame => "Flame",
+            Self::Rain => "Rain",
+            Self::Snow => "Snow",
+            Self::Stardust => "Stardust",
+            Self::ColorSwitch => "ColorSwitch",
+            Self::SwipingRainbow => "SwipingRainbow",
+            Self::SoundFlash => "SoundFlash",
+            Self::SoundBars => "SoundBars",
+            Self::SoundSlider => "SoundSlider",
+            Self::SoundShift => "SoundShift",
+            Self::Ambient => "Ambient",
+            Self::ColorGradient => "ColorGradient",
+        }
+    }
+}
+
 /// A static RGB effect with a single constant color.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EffectStatic {
     /// The static display color.
     pub color: Color,
@@ -117,6 +335,7 @@ pub struct EffectStatic {
 
 /// A breathing effect that smoothly fades a color in and out.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EffectBreathing {
     /// The breathing base color.
     pub color: Color,
@@ -138,6 +357,7 @@ pub struct EffectBreathing {
 
 /// A rainbow effect cycling through a color spectrum.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EffectRainbow {
     /// Base color (used as reference).
     pub color: Color,
@@ -158,6 +378,7 @@ pub struct EffectRainbow {
 
 /// A blinking effect alternating between background and foreground colors.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(clippy::struct_excessive_bools)]
 pub struct EffectBlink {
     /// Background color while blinking.
@@ -185,6 +406,7 @@ pub struct EffectBlink {
 
 /// A color-change effect cycling through a fixed set of colors.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EffectColorChange {
     /// Colors to cycle through.
     pub colors: ArrayVec<Color, 6>,
@@ -207,6 +429,7 @@ pub struct EffectColorChange {
 
 /// A sequence effect displaying multiple colors in order with delays.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EffectSequence {
     /// Background color.
     pub background: Color,
@@ -237,6 +460,7 @@ pub struct EffectSequence {
 
 /// A scanner effect sweeping a light point across the LEDs.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(clippy::struct_excessive_bools)]
 pub struct EffectScanner {
     /// Background color of the scan.
@@ -274,6 +498,7 @@ pub struct EffectScanner {
 
 /// A wave effect moving a multicolor pattern across LEDs.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EffectWave {
     /// Background color.
     pub background: Color,
@@ -302,6 +527,7 @@ pub struct EffectWave {
 
 /// A color sequence effect shifting through defined colors with a set speed.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EffectColorSequence {
     /// Sequence of colors.
     pub colors: ArrayVec<Color, 6>,
@@ -326,6 +552,7 @@ pub struct EffectColorSequence {
 
 /// A color-shift effect scrolling colors across a given area.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EffectColorShift {
     /// Base color.
     pub color: Color,
@@ -348,6 +575,7 @@ pub struct EffectColorShift {
 
 /// A bar graph effect mapping values to colored LED ranges.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(clippy::struct_excessive_bools)]
 pub struct EffectBarGraph {
     /// Background color.
@@ -381,6 +609,7 @@ pub struct EffectBarGraph {
 
 /// A flame-like randomized flickering effect.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EffectFlame {
     /// Background color.
     pub background: Color,
@@ -398,6 +627,7 @@ pub struct EffectFlame {
 
 /// A rain effect simulating falling drops.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EffectRain {
     /// Background color.
     pub background: Color,
@@ -426,6 +656,7 @@ pub struct EffectRain {
 
 /// A color switch effect cycling between defined color ranges.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EffectColorSwitch {
     /// Colors and ranges with thresholds.
     pub colors: ArrayVec<(Color, u16, bool), 6>,
@@ -442,6 +673,7 @@ pub struct EffectColorSwitch {
 
 /// A swiping rainbow effect with a moving point and strip color.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EffectSwipingRainbow {
     /// Color of the moving point.
     pub point_color: Color,
@@ -470,6 +702,7 @@ pub struct EffectSwipingRainbow {
 
 /// A sound-reactive flash effect.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EffectSoundFlash {
     /// Background color.
     pub background: Color,
@@ -479,6 +712,7 @@ pub struct EffectSoundFlash {
 
 /// A sound-reactive slider effect.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EffectSoundSlider {
     /// Background color.
     pub background: Color,
@@ -490,6 +724,7 @@ pub struct EffectSoundSlider {
 
 /// A sound-reactive shifting effect.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EffectSoundShift {
     /// Background color.
     pub background: Color,
@@ -507,6 +742,7 @@ pub struct EffectSoundShift {
 
 /// An ambient background effect.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EffectAmbient {
     /// Background color.
     pub background: Color,
@@ -514,6 +750,7 @@ pub struct EffectAmbient {
 
 /// A gradient effect blending multiple colors.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EffectColorGradient {
     /// Starting color of the gradient.
     pub start_color: Color,
@@ -532,6 +769,28 @@ pub struct EffectColorGradient {
     pub source_control_rotation: Option<SourceControl>,
 }
 
+/// Number of bytes of the effect specific part of a [`Controller`].
+pub(super) const EFFECT_SIZE: usize = 60;
+
+impl FixedSize for Option<Controller> {
+    const SIZE: usize = 70;
+}
+
+// offset, length, effect, flags, data source, rising and falling attenuation,
+// effect specific part and a trailing padding byte
+const _: () = assert!(
+    u8::SIZE
+        + u8::SIZE
+        + u8::SIZE
+        + u16::SIZE
+        + Option::<DataSource>::SIZE
+        + u8::SIZE
+        + u8::SIZE
+        + EFFECT_SIZE
+        + 1
+        == Option::<Controller>::SIZE
+);
+
 impl Decode for Option<Controller> {
     #[allow(clippy::too_many_lines)]
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
@@ -549,11 +808,18 @@ impl Decode for Option<Controller> {
 
         let effect = match effect {
             0x00 => {
-                reader.skip::<61>()?;
+                reader.skip::<{ EFFECT_SIZE + 1 }>()?;
 
                 return Ok(R::guard(|_| None));
             }
             0x01 => {
+                const {
+                    assert!(
+                        2 * SourceControl::SIZE + 24 + Color::SIZE + <[Color; 5]>::SIZE
+                            == EFFECT_SIZE
+                    );
+                };
+
                 let source_control_brightness = SourceControl::decode_opt(reader, sc0)?;
                 let source_control_saturation = SourceControl::decode_opt(reader, sc1)?;
 
@@ -571,6 +837,18 @@ impl Decode for Option<Controller> {
                 })
             }
             0x02 => {
+                const {
+                    assert!(
+                        2 * SourceControl::SIZE
+                            + 2 * EffectPercent::SIZE
+                            + 2 * EffectDelay::SIZE
+                            + 16
+                            + Color::SIZE
+                            + <[Color; 5]>::SIZE
+                            == EFFECT_SIZE
+                    );
+                };
+
                 let source_control_speed = SourceControl::decode_opt(reader, sc0)?;
                 let source_control_intensity = SourceControl::decode_opt(reader, sc1)?;
 
@@ -596,6 +874,17 @@ impl Decode for Option<Controller> {
                 })
             }
             0x03 => {
+                const {
+                    assert!(
+                        2 * SourceControl::SIZE
+                            + 2 * EffectPercent::SIZE
+                            + 20
+                            + Color::SIZE
+                            + <[Color; 5]>::SIZE
+                            == EFFECT_SIZE
+                    );
+                };
+
                 let source_control_speed = SourceControl::decode_opt(reader, sc0)?;
                 let source_control_brightness = SourceControl::decode_opt(reader, sc1)?;
 
@@ -618,6 +907,17 @@ impl Decode for Option<Controller> {
                 })
             }
             0x04 => {
+                const {
+                    assert!(
+                        2 * SourceControl::SIZE
+                            + EffectPercent::SIZE
+                            + u16::SIZE
+                            + 20
+                            + <[Color; 6]>::SIZE
+                            == EFFECT_SIZE
+                    );
+                };
+
                 let source_control_speed = SourceControl::decode_opt(reader, sc0)?;
                 let source_control_brightness = SourceControl::decode_opt(reader, sc1)?;
 
@@ -644,6 +944,17 @@ impl Decode for Option<Controller> {
                 })
             }
             0x05 => {
+                const {
+                    assert!(
+                        2 * SourceControl::SIZE
+                            + EffectPercent::SIZE
+                            + u16::SIZE
+                            + 20
+                            + <[Color; 6]>::SIZE
+                            == EFFECT_SIZE
+                    );
+                };
+
                 let source_control_speed = SourceControl::decode_opt(reader, sc0)?;
                 let source_control_brightness = SourceControl::decode_opt(reader, sc1)?;
 
@@ -666,6 +977,18 @@ impl Decode for Option<Controller> {
                 })
             }
             0x07 => {
+                const {
+                    assert!(
+                        2 * SourceControl::SIZE
+                            + 2 * EffectPercent::SIZE
+                            + u16::SIZE
+                            + 2 * EffectDelay::SIZE
+                            + 14
+                            + <[Color; 6]>::SIZE
+                            == EFFECT_SIZE
+                    );
+                };
+
                 let source_control_speed = SourceControl::decode_opt(reader, sc0)?;
                 let source_control_brightness = SourceControl::decode_opt(reader, sc1)?;
 
@@ -697,6 +1020,18 @@ impl Decode for Option<Controller> {
                 })
             }
             0x08 | 0x09 => {
+                const {
+                    assert!(
+                        2 * SourceControl::SIZE
+                            + 2 * EffectPercent::SIZE
+                            + EffectWidth::SIZE
+                            + 18
+                            + 3 * Color::SIZE
+                            + <[Color; 3]>::SIZE
+                            == EFFECT_SIZE
+                    );
+                };
+
                 let source_control_speed = SourceControl::decode_opt(reader, sc0)?;
                 let source_control_brightness = SourceControl::decode_opt(reader, sc1)?;
 
@@ -736,6 +1071,18 @@ impl Decode for Option<Controller> {
                 })
             }
             0x0A => {
+                const {
+                    assert!(
+                        2 * SourceControl::SIZE
+                            + 2 * EffectPercent::SIZE
+                            + EffectWidth::SIZE
+                            + u16::SIZE
+                            + 16
+                            + <[Color; 6]>::SIZE
+                            == EFFECT_SIZE
+                    );
+                };
+
                 let source_control_speed = SourceControl::decode_opt(reader, sc0)?;
                 let source_control_brightness = SourceControl::decode_opt(reader, sc1)?;
 
@@ -765,6 +1112,19 @@ impl Decode for Option<Controller> {
                 })
             }
             0x0B => {
+                const {
+                    assert!(
+                        2 * SourceControl::SIZE
+                            + 2 * EffectPercent::SIZE
+                            + 2
+                            + u16::SIZE
+                            + EffectWidth::SIZE
+                            + 14
+                            + <[Color; 6]>::SIZE
+                            == EFFECT_SIZE
+                    );
+                };
+
                 let source_control_speed = SourceControl::decode_opt(reader, sc0)?;
                 let source_control_brightness = SourceControl::decode_opt(reader, sc1)?;
 
@@ -791,6 +1151,18 @@ impl Decode for Option<Controller> {
                 })
             }
             0x0C => {
+                const {
+                    assert!(
+                        2 * SourceControl::SIZE
+                            + 2 * EffectPercent::SIZE
+                            + EffectWidth::SIZE
+                            + 18
+                            + Color::SIZE
+                            + <[Color; 5]>::SIZE
+                            == EFFECT_SIZE
+                    );
+                };
+
                 let source_control_speed = SourceControl::decode_opt(reader, sc0)?;
                 let source_control_brightness = SourceControl::decode_opt(reader, sc1)?;
 
@@ -815,6 +1187,21 @@ impl Decode for Option<Controller> {
                 })
             }
             0x0D | 0x15 => {
+                const {
+                    assert!(
+                        2 * SourceControl::SIZE
+                            + 2 * u16::SIZE
+                            + EffectPercent::SIZE
+                            + u16::SIZE
+                            + <[u16; 3]>::SIZE
+                            + EffectPercent::SIZE
+                            + 8
+                            + 2 * Color::SIZE
+                            + <[Color; 4]>::SIZE
+                            == EFFECT_SIZE
+                    );
+                };
+
                 let source_control_rotation = SourceControl::decode_opt(reader, sc0)?;
                 SourceControl::skip_bytes(reader)?;
 
@@ -872,6 +1259,17 @@ impl Decode for Option<Controller> {
                 })
             }
             0x0E => {
+                const {
+                    assert!(
+                        2 * SourceControl::SIZE
+                            + EffectWidth::SIZE
+                            + 22
+                            + 3 * Color::SIZE
+                            + <[Color; 3]>::SIZE
+                            == EFFECT_SIZE
+                    );
+                };
+
                 let source_control_intensity = SourceControl::decode_opt(reader, sc0)?;
                 SourceControl::skip_bytes(reader)?;
 
@@ -894,6 +1292,19 @@ impl Decode for Option<Controller> {
                 })
             }
             0x0F..=0x11 => {
+                const {
+                    assert!(
+                        2 * SourceControl::SIZE
+                            + EffectWidth::SIZE
+                            + RainItems::SIZE
+                            + 2 * EffectWidth::SIZE
+                            + 16
+                            + 2 * Color::SIZE
+                            + <[Color; 4]>::SIZE
+                            == EFFECT_SIZE
+                    );
+                };
+
                 let source_control_speed = SourceControl::decode_opt(reader, sc0)?;
                 let source_control_brightness = SourceControl::decode_opt(reader, sc1)?;
 
@@ -930,6 +1341,18 @@ impl Decode for Option<Controller> {
                 })
             }
             0x12 => {
+                const {
+                    assert!(
+                        2 * SourceControl::SIZE
+                            + u16::SIZE
+                            + <[u16; 6]>::SIZE
+                            + u16::SIZE
+                            + 8
+                            + <[Color; 6]>::SIZE
+                            == EFFECT_SIZE
+                    );
+                };
+
                 let source_control_brightness = SourceControl::decode_opt(reader, sc1)?;
                 SourceControl::skip_bytes(reader)?;
 
@@ -965,6 +1388,17 @@ impl Decode for Option<Controller> {
                 })
             }
             0x13 => {
+                const {
+                    assert!(
+                        2 * SourceControl::SIZE
+                            + 5 * EffectWidth::SIZE
+                            + 14
+                            + 2 * Color::SIZE
+                            + <[Color; 4]>::SIZE
+                            == EFFECT_SIZE
+                    );
+                };
+
                 let source_control_speed = SourceControl::decode_opt(reader, sc0)?;
                 let source_control_brightness = SourceControl::decode_opt(reader, sc1)?;
 
@@ -995,6 +1429,17 @@ impl Decode for Option<Controller> {
                 })
             }
             0x14 => {
+                const {
+                    assert!(
+                        2 * SourceControl::SIZE
+                            + 24
+                            + Color::SIZE
+                            + <[Color; 4]>::SIZE
+                            + Color::SIZE
+                            == EFFECT_SIZE
+                    );
+                };
+
                 SourceControl::skip_bytes(reader)?;
                 SourceControl::skip_bytes(reader)?;
 
@@ -1012,6 +1457,20 @@ impl Decode for Option<Controller> {
                 })
             }
             0x16 => {
+                const {
+                    assert!(
+                        2 * SourceControl::SIZE
+                            + <[SoundEffect; 4]>::SIZE
+                            + <[SoundEffectSpeed; 4]>::SIZE
+                            + EffectPercent::SIZE
+                            + 6
+                            + Color::SIZE
+                            + <[Color; 4]>::SIZE
+                            + Color::SIZE
+                            == EFFECT_SIZE
+                    );
+                };
+
                 SourceControl::skip_bytes(reader)?;
                 SourceControl::skip_bytes(reader)?;
 
@@ -1042,6 +1501,20 @@ impl Decode for Option<Controller> {
                 })
             }
             0x17 => {
+                const {
+                    assert!(
+                        2 * SourceControl::SIZE
+                            + EffectPercent::SIZE
+                            + <[SoundEffectSpeed; 2]>::SIZE
+                            + 2 * EffectPercent::SIZE
+                            + 14
+                            + Color::SIZE
+                            + <[Color; 2]>::SIZE
+                            + <[Color; 3]>::SIZE
+                            == EFFECT_SIZE
+                    );
+                };
+
                 SourceControl::skip_bytes(reader)?;
                 SourceControl::skip_bytes(reader)?;
 
@@ -1081,6 +1554,13 @@ impl Decode for Option<Controller> {
                 })
             }
             0x18 => {
+                const {
+                    assert!(
+                        2 * SourceControl::SIZE + 24 + Color::SIZE + <[Color; 5]>::SIZE
+                            == EFFECT_SIZE
+                    );
+                };
+
                 SourceControl::skip_bytes(reader)?;
                 SourceControl::skip_bytes(reader)?;
 
@@ -1096,6 +1576,21 @@ impl Decode for Option<Controller> {
                 })
             }
             0x21 => {
+                const {
+                    assert!(
+                        2 * SourceControl::SIZE
+                            + 4
+                            + EffectPercent::SIZE
+                            + u16::SIZE
+                            + <[u16; 3]>::SIZE
+                            + 10
+                            + <[Color; 2]>::SIZE
+                            + Color::SIZE
+                            + <[Color; 3]>::SIZE
+                            == EFFECT_SIZE
+                    );
+                };
+
                 let source_control_rotation = SourceControl::decode_opt(reader, sc0)?;
                 SourceControl::skip_bytes(reader)?;
 
@@ -1151,6 +1646,7 @@ impl Decode for Option<Controller> {
 /// The actual values of `input_min` and `input_max` depend on the selected data source
 /// (e.g. temperature, flow rate, sensor value).
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SourceControl {
     /// Minimum expected value of the input signal (depends on the data source).
     pub input_min: u16,
@@ -1162,6 +1658,34 @@ pub struct SourceControl {
     pub output_max: u8,
 }
 
+impl SourceControl {
+    /// Maps the passed `input` value of the data source to the output range.
+    ///
+    /// The input is expected in the raw unit of the data source (see
+    /// [`SensorReadings::value_for`](crate::monitor::SensorReadings::value_for))
+    /// and is clamped to the input range. If the input range is empty, the
+    /// output switches from `output_min` to `output_max` at `input_max`.
+    #[must_use]
+    pub fn map(&self, input: f32) -> f32 {
+        let input_min = f32::from(self.input_min);
+        let input_max = f32::from(self.input_max);
+        let output_min = f32::from(self.output_min);
+        let output_max = f32::from(self.output_max);
+
+        let f = if input_max > input_min {
+            ((input - input_min) / (input_max - input_min)).clamp(0.0, 1.0)
+        } else {
+            f32::from(u8::from(input >= input_max))
+        };
+
+        output_min + f * (output_max - output_min)
+    }
+}
+
+impl FixedSize for SourceControl {
+    const SIZE: usize = 6;
+}
+
 impl Decode for SourceControl {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         let input_min = reader.read_u16be()?;
@@ -1177,11 +1701,24 @@ impl Decode for SourceControl {
         }))
     }
 }
-/// A wrapper around [`Hsv`] representing a color used in effects.
+/// A color used in effects.
+///
+/// The color is stored in the compact binary representation used by the
+/// device (see [`from_bytes`](Self::from_bytes)), so it only takes four bytes
+/// of memory. The HSV components are not stored as a field, they are
+/// calculated on demand using [`hsv`](Self::hsv) (or the conversion into
+/// [`Hsv`]). Colors created from HSV or RGB values are quantized to the
+/// precision of the device, see
+/// [`quantization_error`](Self::quantization_error).
 ///
-/// Provides convenience constructors from HSV, RGB, and hexadecimal RGB values.
-#[derive(Debug, Clone)]
-pub struct Color(pub Hsv);
+/// Provides convenience constructors from HSV, RGB, hexadecimal RGB and
+/// gamma encoded sRGB values, and from color temperatures.
+///
+/// The device stores the hue as one of six sections of 60° and an offset of
+/// 1/255 section inside it (about 0.24°), and the saturation and value in
+/// steps of 1/255.
+#[derive(Clone, Copy)]
+pub struct Color([u8; 4]);
 
 impl Color {
     /// Creates a [`Color`] directly from HSV components.
@@ -1189,9 +1726,25 @@ impl Color {
     /// - `h`: Hue, usually in degrees `[0.0 .. 360.0)`.
     /// - `s`: Saturation `[0.0 .. 1.0]`.
     /// - `v`: Value (brightness) `[0.0 .. 1.0]`.
+    ///
+    /// The conversion is lossy: the components are rounded to the precision
+    /// of the device, so [`hsv`](Self::hsv) may return slightly different
+    /// values (see [`quantization_error`](Self::quantization_error)).
+    ///
+    /// ```rust
+    /// use high_flow_next::protocol::settings::Color;
+    ///
+    /// let color = Color::from_hsv(120.0, 1.0, 0.5);
+    /// assert_eq!(color.hsv().v, 128.0 / 255.0);
+    /// ```
     #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
     pub fn from_hsv(h: f64, s: f64, v: f64) -> Self {
-        Self(Hsv::new(h, s, v))
+        let h = h.clamp(0.0, 360.0);
+        let h_section = (h / 60.0).floor().min(5.0);
+        let h_offset = (h - 60.0 * h_section) / 60.0 * 255.0;
+
+        Self([h_section, h_offset, s * 255.0, v * 255.0].map(|x| x.round().clamp(0.0, 255.0) as u8))
     }
 
     /// Creates a [`Color`] from 8-bit RGB components.
@@ -1201,11 +1754,7 @@ impl Color {
     /// - `b`: Blue channel `[0 .. 255]`.
     #[must_use]
     pub fn from_rgb(r: u8, g: u8, b: u8) -> Self {
-        Self(Hsv::from_rgb(&Rgb::new(
-            f64::from(r),
-            f64::from(g),
-            f64::from(b),
-        )))
+        Hsv::from_rgb(&Rgb::new(f64::from(r), f64::from(g), f64::from(b))).into()
     }
 
     /// Creates a [`Color`] from a packed hexadecimal RGB value.
@@ -1220,49 +1769,242 @@ impl Color {
     /// ```
     #[must_use]
     pub fn from_rgb_hex(hex: u32) -> Self {
-        Self(Hsv::from_rgb(&Rgb::from_hex(hex)))
+        Hsv::from_rgb(&Rgb::from_hex(hex)).into()
+    }
+
+    /// Creates a [`Color`] from 8-bit sRGB components.
+    ///
+    /// [`from_rgb`](Self::from_rgb) passes the components to the device
+    /// unchanged. Colors picked in a color picker or taken from a photo are
+    /// gamma encoded, so they look brighter and less saturated on the LEDs.
+    /// This constructor decodes the sRGB transfer function first, so the
+    /// light emitted by the LEDs matches the picked color.
+    ///
+    /// The linear values are quantized to the device precision afterwards.
+    /// The step of 1/255 in the value is much coarser than the resolution of
+    /// the sRGB encoding for dark colors: all components below `7` map to a
+    /// value of zero, and dark colors lose most of their hue and
+    /// saturation precision.
+    #[must_use]
+    pub fn from_rgb_srgb(r: u8, g: u8, b: u8) -> Self {
+        let [r, g, b] = [r, g, b].map(|x| srgb_to_linear(f64::from(x) / 255.0) * 255.0);
+
+        Hsv::from_rgb(&Rgb::new(r, g, b)).into()
+    }
+
+    /// Creates a [`Color`] from a color temperature in Kelvin (e.g. `2700.0`
+    /// for a warm white light bulb or `6500.0` for daylight).
+    ///
+    /// The temperature is clamped to `1000.0..=40000.0`. The color of the
+    /// black body radiator is calculated with the approximation by Tanner
+    /// Helland, interpreted as sRGB (see [`from_rgb_srgb`](Self::from_rgb_srgb))
+    /// and returned at full brightness.
+    ///
+    /// All colors of this range lie between red-orange and blue, so only the
+    /// saturation and the hue offset within the first or the fourth hue
+    /// section change. A step of 1/255 in the saturation corresponds to about
+    /// 20 K near 3000 K and about 100 K above 10000 K, so
+    /// temperatures that close to each other may result in the same color.
+    #[must_use]
+    pub fn from_kelvin(kelvin: f64) -> Self {
+        let t = kelvin.clamp(1000.0, 40000.0) / 100.0;
+
+        let r = if t <= 66.0 {
+            255.0
+        } else {
+            329.698_727_446 * (t - 60.0).powf(-0.133_204_759_2)
+        };
+        let g = if t <= 66.0 {
+            99.470_802_586_1 * t.ln() - 161.119_568_166_1
+        } else {
+            288.122_169_528_3 * (t - 60.0).powf(-0.075_514_849_2)
+        };
+        let b = if t >= 66.0 {
+            255.0
+        } else if t <= 19.0 {
+            0.0
+        } else {
+            138.517_731_223_1 * (t - 10.0).ln() - 305.044_792_730_7
+        };
+
+        let [r, g, b] = [r, g, b].map(|x| srgb_to_linear((x / 255.0).clamp(0.0, 1.0)) * 255.0);
+
+        Hsv::from_rgb(&Rgb::new(r, g, b)).into()
+    }
+
+    /// Returns the difference between the color the device stores for the
+    /// requested HSV components and the requested components (see
+    /// [`from_hsv`](Self::from_hsv)).
+    ///
+    /// The hue difference is given in degrees within `-180.0..180.0`, the
+    /// saturation and value differences as fractions of `1.0`. All components
+    /// are `0.0` if the color can be represented exactly. The hue error of a
+    /// color within the valid range is at most about 0.12°, the saturation
+    /// and value errors are at most 1/510.
+    #[must_use]
+    pub fn quantization_error(h: f64, s: f64, v: f64) -> Hsv {
+        let stored = Self::from_hsv(h, s, v).hsv();
+
+        Hsv::new(
+            (stored.h - h + 180.0).rem_euclid(360.0) - 180.0,
+            stored.s - s,
+            stored.v - v,
+        )
+    }
+
+    /// Returns the difference between the RGB components the device displays
+    /// for the requested RGB components and the requested components (see
+    /// [`from_rgb`](Self::from_rgb)).
+    ///
+    /// All components are `0` if the color can be represented exactly.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn quantization_error_rgb(r: u8, g: u8, b: u8) -> [i16; 3] {
+        let stored = Self::from_rgb(r, g, b).hsv().to_rgb();
+
+        [(stored.r, r), (stored.g, g), (stored.b, b)]
+            .map(|(stored, requested)| stored.round() as i16 - i16::from(requested))
+    }
+
+    /// Creates a [`Color`] from its binary representation used by the device
+    /// (hue section, hue offset, saturation and value).
+    ///
+    /// Hue sections above `5` are clamped to the last section, so the hue of
+    /// every color is within `0.0..=360.0`.
+    #[must_use]
+    pub fn from_bytes(bytes: [u8; 4]) -> Self {
+        let [h_section, h_offset, s, v] = bytes;
+
+        Self([h_section.min(5), h_offset, s, v])
+    }
+
+    /// Converts the [`Color`] into its binary representation used by the
+    /// device (see [`from_bytes`](Self::from_bytes)).
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; 4] {
+        self.0
+    }
+
+    /// Returns the HSV components of the color (`h` in degrees, `s` and `v`
+    /// in `0.0..=1.0`).
+    #[must_use]
+    pub fn hsv(&self) -> Hsv {
+        let [h_section, h_offset, s, v] = self.0.map(f64::from);
+
+        Hsv::new(
+            60.0 * h_section + 60.0 * h_offset / 255.0,
+            s / 255.0,
+            v / 255.0,
+        )
+    }
+}
+
+/// Decodes a component of the sRGB transfer function (`0.0..=1.0`).
+fn srgb_to_linear(x: f64) -> f64 {
+    if x <= 0.040_45 {
+        x / 12.92
+    } else {
+        ((x + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+impl Debug for Color {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Hsv { h, s, v } = self.hsv();
+
+        f.debug_struct("Color")
+            .field("h", &h)
+            .field("s", &s)
+            .field("v", &v)
+            .finish()
     }
 }
 
 impl From<Hsv> for Color {
     fn from(value: Hsv) -> Self {
-        Self(value)
+        Self::from_hsv(value.h, value.s, value.v)
     }
 }
 
 impl From<Color> for Hsv {
     fn from(value: Color) -> Self {
-        value.0
+        value.hsv()
     }
 }
 
 impl Eq for Color {}
 
+/// Two colors are equal if they describe the same HSV color, even if their
+/// binary representation differs (e.g. a hue of 60° may be encoded as end of
+/// the first or start of the second hue section).
 impl PartialEq for Color {
     fn eq(&self, other: &Self) -> bool {
-        self.0.h.eq(&other.0.h) && self.0.s.eq(&other.0.s) && self.0.v.eq(&other.0.v)
+        let a = self.hsv();
+        let b = other.hsv();
+
+        a.h.eq(&b.h) && a.s.eq(&b.s) && a.v.eq(&b.v)
+    }
+}
+
+/// Serializes the color as its HSV components (`h` in degrees, `s` and `v`
+/// in `0.0..=1.0`) for human readable formats, and as its binary
+/// representation (see [`Color::to_bytes`]) for compact formats.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Color {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        if !serializer.is_human_readable() {
+            return self.to_bytes().serialize(serializer);
+        }
+
+        let hsv = self.hsv();
+
+        let mut s = serializer.serialize_struct("Color", 3)?;
+        s.serialize_field("h", &hsv.h)?;
+        s.serialize_field("s", &hsv.s)?;
+        s.serialize_field("v", &hsv.v)?;
+        s.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Color {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "Color")]
+        struct Components {
+            h: f64,
+            s: f64,
+            v: f64,
+        }
+
+        if !deserializer.is_human_readable() {
+            return <[u8; 4]>::deserialize(deserializer).map(Self::from_bytes);
+        }
+
+        let Components { h, s, v } = Components::deserialize(deserializer)?;
+
+        Ok(Self::from_hsv(h, s, v))
     }
 }
 
+impl FixedSize for Color {
+    const SIZE: usize = 4;
+}
+
 impl Decode for Color {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
-        let h_section = f64::from(reader.read_u8()?);
-        let h_offset = f64::from(reader.read_u8()?);
-        let s = f64::from(reader.read_u8()?);
-        let v = f64::from(reader.read_u8()?);
-
-        Ok(R::guard(|_| {
-            Self::from_hsv(
-                60.0 * h_section + 60.0 * h_offset / 255.0,
-                s / 255.0,
-                v / 255.0,
-            )
-        }))
+        let mut bytes = [0; 4];
+        reader.read_exact(&mut bytes)?;
+
+        Ok(R::guard(|_| Self::from_bytes(bytes)))
     }
 }
 /// Represents the origin of a control signal that can be mapped into an effect
 /// parameter using [`SourceControl`].
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DataSource {
     /// Flow rate measured by the device.
     Flow,
@@ -1296,6 +2038,10 @@ pub enum DataSource {
     SoftwareSensor8,
 }
 
+impl FixedSize for Option<DataSource> {
+    const SIZE: usize = u16::SIZE;
+}
+
 impl Decode for Option<DataSource> {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         match reader.read_u16be()? {
@@ -1321,7 +2067,8 @@ impl Decode for Option<DataSource> {
 }
 
 /// Defines how LEDs should react spatially to sound input.
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SoundEffect {
     /// Expands outward from the center of the LED strip or area.
     OutwardsFromCenter,
@@ -1337,6 +2084,10 @@ pub enum SoundEffect {
     AllLEDs,
 }
 
+impl FixedSize for SoundEffect {
+    const SIZE: usize = u16::SIZE;
+}
+
 impl Decode for SoundEffect {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         match reader.read_u16be()? {
@@ -1359,6 +2110,14 @@ define_wrapped! {
 }
 impl_verify_simple!(Brightness<u8, BrightnessTag>);
 
+impl From<u8> for Brightness {
+    fn from(value: u8) -> Self {
+        match Self::from_value(value) {
+            Ok(value) => value,
+        }
+    }
+}
+
 define_wrapped! {
     /// General-purpose percentage value used for effect speed, intensity, and similar parameters.
     ///
@@ -1366,6 +2125,7 @@ define_wrapped! {
     pub type EffectPercent<u16, EffectPercentTag>;
 }
 impl_ranged!(EffectPercent<u16, EffectPercentTag>, 0, 100);
+impl_percent!(EffectPercent<u16, EffectPercentTag>, 1.0);
 
 define_wrapped! {
     /// Delay time used in effects (e.g. breathing min/max delays, sequence waits).
@@ -0,0 +1,359 @@
+//! Generation of random effect configurations.
+
+use std::array::from_fn;
+
+use arrayvec::ArrayVec;
+use rand::Rng;
+
+use crate::misc::{Ranged, Wrapped};
+
+use super::{
+    Color, Effect, EffectAmbient, EffectBarGraph, EffectBlink, EffectBreathing, EffectColorChange,
+    EffectColorGradient, EffectColorSequence, EffectColorShift, EffectColorSwitch, EffectFlame,
+    EffectKind, EffectRain, EffectRainbow, EffectScanner, EffectSequence, EffectSoundFlash,
+    EffectSoundShift, EffectSoundSlider, EffectStatic, EffectSwipingRainbow, EffectWave,
+    SoundEffect, SourceControl,
+};
+
+/// Positions of the color gradient stops are given in 1/1000 of the length.
+const GRADIENT_END: u16 = 1000;
+
+impl Effect {
+    /// Creates an effect of the passed `kind` with random parameters.
+    ///
+    /// All parameters are within the range the device accepts. Lists of
+    /// colors contain at least one color, value thresholds are sorted in
+    /// ascending order and the input range of a [`SourceControl`] is never
+    /// empty.
+    #[must_use]
+    #[allow(clippy::too_many_lines)]
+    pub fn random<R: Rng + ?Sized>(kind: EffectKind, rng: &mut R) -> Self {
+        match kind {
+            EffectKind::Static => Self::Static(EffectStatic {
+                color: Color::random(rng),
+                source_control_brightness: random_source_control(rng),
+                source_control_saturation: random_source_control(rng),
+            }),
+            EffectKind::Breathing => Self::Breathing(EffectBreathing {
+                color: Color::random(rng),
+                speed: random_wrapped(rng),
+                intensity: random_wrapped(rng),
+                delay_max_brightness: random_wrapped(rng),
+                delay_min_brightness: random_wrapped(rng),
+                source_control_speed: random_source_control(rng),
+                source_control_intensity: random_source_control(rng),
+            }),
+            EffectKind::Rainbow => Self::Rainbow(EffectRainbow {
+                color: Color::random(rng),
+                speed: random_wrapped(rng),
+                color_range: random_wrapped(rng),
+                reverse_direction: rng.random(),
+                source_control_speed: random_source_control(rng),
+                source_control_brightness: random_source_control(rng),
+            }),
+            EffectKind::Blink => Self::Blink(EffectBlink {
+                background: Color::random(rng),
+                colors: random_list(rng, Color::random),
+                speed: random_wrapped(rng),
+                fade_in: rng.random(),
+                fade_out: rng.random(),
+                random_color: rng.random(),
+                slide_colors: rng.random(),
+                source_control_speed: random_source_control(rng),
+                source_control_brightness: random_source_control(rng),
+            }),
+            EffectKind::ColorChange => Self::ColorChange(EffectColorChange {
+                colors: random_list(rng, Color::random),
+                speed: random_wrapped(rng),
+                fade: rng.random(),
+                random_color: rng.random(),
+                slide_colors: rng.random(),
+                source_control_speed: random_source_control(rng),
+                source_control_brightness: random_source_control(rng),
+            }),
+            EffectKind::Sequence => Self::Sequence(EffectSequence {
+                background: Color::random(rng),
+                colors: random_list(rng, Color::random),
+                speed: random_wrapped(rng),
+                smoothness: random_wrapped(rng),
+                delay_after_sequence: random_wrapped(rng),
+                delay_before_sequence: random_wrapped(rng),
+                reverse_direction: rng.random(),
+                fade: rng.random(),
+                random_color: rng.random(),
+                source_control_speed: random_source_control(rng),
+                source_control_brightness: random_source_control(rng),
+            }),
+            EffectKind::Scanner => Self::Scanner(random_scanner(rng)),
+            EffectKind::Laser => Self::Laser(random_scanner(rng)),
+            EffectKind::Wave => Self::Wave(EffectWave {
+                background: Color::random(rng),
+                colors: random_list(rng, Color::random),
+                speed: random_wrapped(rng),
+                smoothness: random_wrapped(rng),
+                width: random_wrapped(rng),
+                reverse_direction: rng.random(),
+                random_color: rng.random(),
+                circular: rng.random(),
+                source_control_speed: random_source_control(rng),
+                source_control_brightness: random_source_control(rng),
+            }),
+            EffectKind::ColorSequence => Self::ColorSequence(EffectColorSequence {
+                colors: random_list(rng, Color::random),
+                speed: random_wrapped(rng),
+                smoothness: random_wrapped(rng),
+                color_change_speed: random_wrapped(rng),
+                reverse_direction: rng.random(),
+                random_color: rng.random(),
+                source_control_speed: random_source_control(rng),
+                source_control_brightness: random_source_control(rng),
+            }),
+            EffectKind::ColorShift => Self::ColorShift(EffectColorShift {
+                color: Color::random(rng),
+                speed: random_wrapped(rng),
+                color_range: random_wrapped(rng),
+                total_area: random_wrapped(rng),
+                reverse_direction: rng.random(),
+                source_control_speed: random_source_control(rng),
+                source_control_brightness: random_source_control(rng),
+            }),
+            EffectKind::BarGraph => Self::BarGraph(random_bar_graph(rng)),
+            EffectKind::SoundBars => Self::SoundBars(random_bar_graph(rng)),
+            EffectKind::Flame => Self::Flame(EffectFlame {
+                background: Color::random(rng),
+                color_primary: Color::random(rng),
+                color_secondary: Color::random(rng),
+                intensity: random_wrapped(rng),
+                source_control_intensity: random_source_control(rng),
+            }),
+            EffectKind::Rain => Self::Rain(random_rain(rng)),
+            EffectKind::Snow => Self::Snow(random_rain(rng)),
+            EffectKind::Stardust => Self::Stardust(random_rain(rng)),
+            EffectKind::ColorSwitch => {
+                let end_value = rng.random();
+                let colors = random_ranges(rng, end_value, |rng, value| {
+                    (Color::random(rng), value, rng.random())
+                });
+
+                Self::ColorSwitch(EffectColorSwitch {
+                    colors,
+                    end_value,
+                    fade_ranges: rng.random(),
+                    source_control_brightness: random_source_control(rng),
+                })
+            }
+            EffectKind::SwipingRainbow => Self::SwipingRainbow(EffectSwipingRainbow {
+                point_color: Color::random(rng),
+                strip_color: Color::random(rng),
+                point_speed: random_wrapped(rng),
+                point_smoothness: random_wrapped(rng),
+                point_size: random_wrapped(rng),
+                color_change_speed: random_wrapped(rng),
+                color_range: random_wrapped(rng),
+                reverse_direction: rng.random(),
+                source_control_speed: random_source_control(rng),
+                source_control_brightness: random_source_control(rng),
+            }),
+            EffectKind::SoundFlash => Self::SoundFlash(EffectSoundFlash {
+                background: Color::random(rng),
+                colors: from_fn(|_| Color::random(rng)),
+            }),
+            EffectKind::SoundSlider => Self::SoundSlider(EffectSoundSlider {
+                background: Color::random(rng),
+                effects: from_fn(|_| {
+                    (
+                        Color::random(rng),
+                        SoundEffect::random(rng),
+                        random_wrapped(rng),
+                    )
+                }),
+                rotate_color: random_wrapped(rng),
+            }),
+            EffectKind::SoundShift => Self::SoundShift(EffectSoundShift {
+                background: Color::random(rng),
+                effects: from_fn(|_| (Color::random(rng), random_wrapped(rng), rng.random())),
+                rotate_color: random_wrapped(rng),
+                idle_speed: random_wrapped(rng),
+                activity_speed: random_wrapped(rng),
+                reverse_direction: rng.random(),
+            }),
+            EffectKind::Ambient => Self::Ambient(EffectAmbient {
+                background: Color::random(rng),
+            }),
+            EffectKind::ColorGradient => Self::ColorGradient(EffectColorGradient {
+                start_color: Color::random(rng),
+                colors: random_ranges(rng, GRADIENT_END, |rng, position| {
+                    (Color::random(rng), position)
+                }),
+                rotation: random_wrapped(rng),
+                reverse_direction: rng.random(),
+                reverse_rotation: rng.random(),
+                source_control_rotation: random_source_control(rng),
+            }),
+        }
+    }
+
+    /// Creates an effect of a random kind with random parameters (see
+    /// [`random`](Self::random)).
+    #[must_use]
+    pub fn random_any<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let kind = EffectKind::random(rng);
+
+        Self::random(kind, rng)
+    }
+}
+
+impl EffectKind {
+    /// Returns a random effect kind.
+    #[must_use]
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::ALL[rng.random_range(0..Self::ALL.len())]
+    }
+}
+
+impl Color {
+    /// Returns a random color.
+    ///
+    /// Every color the device can represent is equally likely.
+    #[must_use]
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::from_bytes([
+            rng.random_range(0..=5),
+            rng.random(),
+            rng.random(),
+            rng.random(),
+        ])
+    }
+}
+
+impl SourceControl {
+    /// Returns a random source control with a non-empty input range and an
+    /// output range within `0..=100`.
+    #[must_use]
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let input_min = rng.random_range(0..u16::MAX);
+        let input_max = rng.random_range(input_min + 1..=u16::MAX);
+        let output_min = rng.random_range(0..=100);
+        let output_max = rng.random_range(output_min..=100);
+
+        Self {
+            input_min,
+            input_max,
+            output_min,
+            output_max,
+        }
+    }
+}
+
+impl SoundEffect {
+    /// Returns a random sound effect.
+    #[must_use]
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        match rng.random_range(0..6) {
+            0 => Self::OutwardsFromCenter,
+            1 => Self::InwardsToCenterA,
+            2 => Self::InwardsToCenterB,
+            3 => Self::FromLeft,
+            4 => Self::FromRight,
+            _ => Self::AllLEDs,
+        }
+    }
+}
+
+fn random_scanner<R: Rng + ?Sized>(rng: &mut R) -> EffectScanner {
+    EffectScanner {
+        background: Color::random(rng),
+        inner_color: Color::random(rng),
+        outer_color: Color::random(rng),
+        speed: random_wrapped(rng),
+        smoothness: random_wrapped(rng),
+        width: random_wrapped(rng),
+        reverse_direction: rng.random(),
+        fade: rng.random(),
+        random_color: rng.random(),
+        second_color_mode: rng.random(),
+        color_change: rng.random(),
+        circular: rng.random(),
+        source_control_speed: random_source_control(rng),
+        source_control_brightness: random_source_control(rng),
+    }
+}
+
+fn random_bar_graph<R: Rng + ?Sized>(rng: &mut R) -> EffectBarGraph {
+    let end_value = rng.random();
+    let colors = random_ranges(rng, end_value, |rng, value| {
+        (Color::random(rng), value, rng.random())
+    });
+
+    EffectBarGraph {
+        background: Color::random(rng),
+        peak_color: Color::random(rng),
+        colors,
+        end_value,
+        rotation: random_wrapped(rng),
+        peak_hold_time: random_wrapped(rng),
+        reverse_direction: rng.random(),
+        show_peak: rng.random(),
+        show_bar: rng.random(),
+        show_ranges: rng.random(),
+        fade_ranges: rng.random(),
+        source_control_rotation: random_source_control(rng),
+    }
+}
+
+fn random_rain<R: Rng + ?Sized>(rng: &mut R) -> EffectRain {
+    EffectRain {
+        background: Color::random(rng),
+        color: Color::random(rng),
+        speed: random_wrapped(rng),
+        items: random_wrapped(rng),
+        size: random_wrapped(rng),
+        smoothness: random_wrapped(rng),
+        reverse_direction: rng.random(),
+        random_color: rng.random(),
+        source_control_speed: random_source_control(rng),
+        source_control_brightness: random_source_control(rng),
+    }
+}
+
+fn random_wrapped<R, X>(rng: &mut R) -> Wrapped<u16, X>
+where
+    R: Rng + ?Sized,
+    X: Ranged<u16>,
+{
+    let value = rng.random_range(X::min_inclusive()..=X::max_inclusive());
+
+    Wrapped::from_value(value).unwrap_or_else(|_| unreachable!())
+}
+
+fn random_source_control<R: Rng + ?Sized>(rng: &mut R) -> Option<SourceControl> {
+    rng.random_bool(0.5).then(|| SourceControl::random(rng))
+}
+
+/// Returns a list with at least one and at most `N` random items.
+fn random_list<R, T, F, const N: usize>(rng: &mut R, mut f: F) -> ArrayVec<T, N>
+where
+    R: Rng + ?Sized,
+    F: FnMut(&mut R) -> T,
+{
+    let len = rng.random_range(1..=N);
+
+    (0..len).map(|_| f(rng)).collect()
+}
+
+/// Returns a list with at least one and at most `N` random items, each with
+/// a threshold in `0..=end`. The thresholds are sorted in ascending order.
+fn random_ranges<R, T, F, const N: usize>(rng: &mut R, end: u16, mut f: F) -> ArrayVec<T, N>
+where
+    R: Rng + ?Sized,
+    F: FnMut(&mut R, u16) -> T,
+{
+    let len = rng.random_range(1..=N);
+
+    let mut values = (0..len)
+        .map(|_| rng.random_range(0..=end))
+        .collect::<ArrayVec<u16, N>>();
+    values.sort_unstable();
+
+    values.into_iter().map(|value| f(rng, value)).collect()
+}
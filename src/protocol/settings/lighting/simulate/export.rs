@@ -0,0 +1,212 @@
+//! Export of simulated effects as animated images.
+
+use std::io::{Error as StdIoError, Write};
+use std::time::Duration;
+
+use thiserror::Error;
+
+use super::{Inputs, LedColor, Simulator};
+
+/// Color of the border drawn around every LED.
+const BORDER: LedColor = [0x20, 0x20, 0x20];
+
+/// Options used to render an animation of a [`Simulator`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationOptions {
+    /// Length of the animation.
+    pub duration: Duration,
+
+    /// Number of frames per second.
+    pub frame_rate: u16,
+
+    /// Size of a single LED in pixels (including a one pixel border).
+    pub led_size: u16,
+
+    /// Number of LEDs per row. If `None` all LEDs are drawn in a single row.
+    pub leds_per_row: Option<usize>,
+
+    /// Values of the data sources used while rendering.
+    pub inputs: Inputs,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_secs(5),
+            frame_rate: 20,
+            led_size: 16,
+            leds_per_row: None,
+            inputs: Inputs::default(),
+        }
+    }
+}
+
+/// Error returned while exporting an animation.
+#[derive(Debug, Error)]
+pub enum ExportError {
+    /// The options or the LED layout do not describe a valid image.
+    #[error("Invalid animation: {0}")]
+    InvalidAnimation(&'static str),
+
+    /// Error while writing the image.
+    #[error("IO Error: {0}")]
+    IoError(#[from] StdIoError),
+
+    /// Error while encoding the GIF image.
+    #[cfg(feature = "gif")]
+    #[error("GIF Error: {0}")]
+    Gif(#[from] gif::EncodingError),
+
+    /// Error while encoding the APNG image.
+    #[cfg(feature = "apng")]
+    #[error("PNG Error: {0}")]
+    Png(#[from] png::EncodingError),
+}
+
+impl Simulator {
+    /// Writes the effects as an animated GIF.
+    ///
+    /// The GIF format stores frame delays in hundredths of a second, so frame
+    /// rates above 50 are not displayed accurately by most viewers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the options do not describe a valid image or the
+    /// image could not be written.
+    #[cfg(feature = "gif")]
+    pub fn write_gif<W: Write>(
+        &mut self,
+        writer: W,
+        options: &AnimationOptions,
+    ) -> Result<(), ExportError> {
+        use gif::{Encoder, Frame, Repeat};
+
+        let layout = Layout::new(self, options)?;
+        let width = u16::try_from(layout.width)
+            .map_err(|_| ExportError::InvalidAnimation("image is too wide"))?;
+        let height = u16::try_from(layout.height)
+            .map_err(|_| ExportError::InvalidAnimation("image is too high"))?;
+        let delay = (100 / options.frame_rate).max(1);
+
+        let mut encoder = Encoder::new(writer, width, height, &[])?;
+        encoder.set_repeat(Repeat::Infinite)?;
+
+        for index in 0..layout.frames {
+            let pixels = layout.render(self, options, index);
+            let mut frame = Frame::from_rgb_speed(width, height, &pixels, 10);
+            frame.delay = delay;
+
+            encoder.write_frame(&frame)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the effects as an animated PNG.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the options do not describe a valid image or the
+    /// image could not be written.
+    #[cfg(feature = "apng")]
+    pub fn write_apng<W: Write>(
+        &mut self,
+        writer: W,
+        options: &AnimationOptions,
+    ) -> Result<(), ExportError> {
+        use png::{BitDepth, ColorType, Encoder};
+
+        let layout = Layout::new(self, options)?;
+        let width = u32::try_from(layout.width)
+            .map_err(|_| ExportError::InvalidAnimation("image is too wide"))?;
+        let height = u32::try_from(layout.height)
+            .map_err(|_| ExportError::InvalidAnimation("image is too high"))?;
+        let frames = u32::try_from(layout.frames)
+            .map_err(|_| ExportError::InvalidAnimation("too many frames"))?;
+
+        let mut encoder = Encoder::new(writer, width, height);
+        encoder.set_color(ColorType::Rgb);
+        encoder.set_depth(BitDepth::Eight);
+        encoder.set_animated(frames, 0)?;
+        encoder.set_frame_delay(1, options.frame_rate)?;
+
+        let mut writer = encoder.write_header()?;
+        for index in 0..layout.frames {
+            let pixels = layout.render(self, options, index);
+
+            writer.write_image_data(&pixels)?;
+        }
+        writer.finish()?;
+
+        Ok(())
+    }
+}
+
+/// Placement of the LEDs inside the image.
+struct Layout {
+    columns: usize,
+    width: usize,
+    height: usize,
+    frames: usize,
+}
+
+impl Layout {
+    fn new(simulator: &Simulator, options: &AnimationOptions) -> Result<Self, ExportError> {
+        let leds = simulator.led_count();
+        if leds == 0 {
+            return Err(ExportError::InvalidAnimation("no LEDs to render"));
+        }
+        if options.frame_rate == 0 {
+            return Err(ExportError::InvalidAnimation("frame rate is zero"));
+        }
+        if options.led_size < 3 {
+            return Err(ExportError::InvalidAnimation("LED size is too small"));
+        }
+
+        let columns = options.leds_per_row.unwrap_or(leds).clamp(1, leds);
+        let rows = leds.div_ceil(columns);
+        let size = usize::from(options.led_size);
+
+        let frames = options.duration.as_millis() * u128::from(options.frame_rate) / 1000;
+        let frames = usize::try_from(frames)
+            .map_err(|_| ExportError::InvalidAnimation("too many frames"))?
+            .max(1);
+
+        Ok(Self {
+            columns,
+            width: columns * size,
+            height: rows * size,
+            frames,
+        })
+    }
+
+    /// Renders the frame with the passed `index` into a buffer of RGB pixels.
+    fn render(
+        &self,
+        simulator: &mut Simulator,
+        options: &AnimationOptions,
+        index: usize,
+    ) -> Vec<u8> {
+        let time = Duration::from_secs(index as u64) / u32::from(options.frame_rate);
+        let leds = simulator.render(time, &options.inputs);
+        let size = usize::from(options.led_size);
+
+        let mut pixels = vec![0; self.width * self.height * 3];
+        for (y, row) in pixels.chunks_exact_mut(self.width * 3).enumerate() {
+            for (x, pixel) in row.chunks_exact_mut(3).enumerate() {
+                let (dx, dy) = (x % size, y % size);
+                let led = y / size * self.columns + x / size;
+                let border = dx == 0 || dy == 0 || dx == size - 1 || dy == size - 1;
+
+                let color = match leds.get(led) {
+                    Some(color) if !border => *color,
+                    _ => BORDER,
+                };
+
+                pixel.copy_from_slice(&color);
+            }
+        }
+
+        pixels
+    }
+}
@@ -0,0 +1,932 @@
+//! Software simulation of the LED effects.
+//!
+//! The [`Simulator`] renders the effects of a list of [`Controller`]s into
+//! RGB values for every LED, at an arbitrary point in time and for optional
+//! values of the [`DataSource`]s. This can be used to preview a lighting
+//! configuration without a device, to test effect settings or to mirror the
+//! effects to other RGB hardware.
+//!
+//! The rendering of the device firmware is not documented. The simulator
+//! mimics the parameters (speed, width, smoothness, colors, [`SourceControl`]
+//! mapping and the sensor attenuation of the controller), but the frames are
+//! an approximation and not bit-exact to what the device displays. Effects
+//! that use randomness in the firmware use a deterministic noise instead, so
+//! rendering the same time twice produces the same frame.
+
+#[cfg(any(feature = "gif", feature = "apng"))]
+mod export;
+
+use std::f64::consts::PI;
+use std::time::{Duration, Instant};
+
+use color_space::ToRgb;
+
+use crate::misc::Clock;
+use crate::monitor::SensorReadings;
+
+use super::{
+    Color, Controller, DataSource, Effect, EffectBarGraph, EffectBlink, EffectBreathing,
+    EffectColorChange, EffectColorGradient, EffectColorSequence, EffectColorShift,
+    EffectColorSwitch, EffectFlame, EffectRain, EffectRainbow, EffectScanner, EffectSequence,
+    EffectSoundFlash, EffectSoundShift, EffectSoundSlider, EffectStatic, EffectSwipingRainbow,
+    EffectWave, SoundEffect, SourceControl,
+};
+
+#[cfg(any(feature = "gif", feature = "apng"))]
+pub use export::{AnimationOptions, ExportError};
+
+/// RGB value of a single LED.
+pub type LedColor = [u8; 3];
+
+/// Values of the [`DataSource`]s used by data controlled effects.
+///
+/// The values are expected in the unit the device uses for the data source,
+/// which is also the unit of [`SourceControl::input_min`] and
+/// [`SourceControl::input_max`]. The level of [`DataSource::Sound`] is
+/// expected in percent (`0.0..=100.0`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Inputs {
+    values: Vec<(DataSource, f64)>,
+}
+
+impl Inputs {
+    /// Creates a new empty set of inputs.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the value of the passed `source` and returns the updated inputs.
+    #[must_use]
+    pub fn with(mut self, source: DataSource, value: f64) -> Self {
+        self.set(source, value);
+
+        self
+    }
+
+    /// Sets the value of the passed `source`.
+    pub fn set(&mut self, source: DataSource, value: f64) {
+        match self.values.iter_mut().find(|(s, _)| *s == source) {
+            Some((_, v)) => *v = value,
+            None => self.values.push((source, value)),
+        }
+    }
+
+    /// Returns the value of the passed `source`, if set.
+    #[must_use]
+    pub fn get(&self, source: DataSource) -> Option<f64> {
+        self.values
+            .iter()
+            .find(|(s, _)| *s == source)
+            .map(|(_, v)| *v)
+    }
+}
+
+/// Takes the values of all data sources available in the `readings`
+/// (see [`SensorReadings::value_for`]).
+impl From<&SensorReadings> for Inputs {
+    fn from(readings: &SensorReadings) -> Self {
+        let values = DataSource::ALL
+            .into_iter()
+            .filter_map(|source| Some((source, f64::from(readings.value_for(source)?))))
+            .collect();
+
+        Self { values }
+    }
+}
+
+/// Renders the effects of a list of [`Controller`]s.
+///
+/// See the [module documentation](self) for details.
+#[derive(Debug, Clone)]
+pub struct Simulator {
+    controllers: Vec<Controller>,
+    led_count: usize,
+    inputs: Vec<Option<f64>>,
+    time: Option<Duration>,
+}
+
+impl Simulator {
+    /// Creates a new simulator for the passed `controllers`.
+    ///
+    /// The number of LEDs is set to the end of the last controller. Later
+    /// controllers are drawn on top of earlier ones if they overlap.
+    pub fn new<I>(controllers: I) -> Self
+    where
+        I: IntoIterator<Item = Controller>,
+    {
+        let controllers = controllers.into_iter().collect::<Vec<_>>();
+        let led_count = controllers
+            .iter()
+            .map(|c| usize::from(c.offset) + usize::from(c.length))
+            .max()
+            .unwrap_or_default();
+
+        Self {
+            inputs: vec![None; controllers.len()],
+            controllers,
+            led_count,
+            time: None,
+        }
+    }
+
+    /// Sets the number of LEDs to render and returns the updated simulator.
+    #[must_use]
+    pub fn with_led_count(mut self, led_count: usize) -> Self {
+        self.led_count = led_count;
+
+        self
+    }
+
+    /// Returns the number of rendered LEDs.
+    #[must_use]
+    pub fn led_count(&self) -> usize {
+        self.led_count
+    }
+
+    /// Returns the simulated controllers.
+    #[must_use]
+    pub fn controllers(&self) -> &[Controller] {
+        &self.controllers
+    }
+
+    /// Renders the LEDs at the passed `time` using the passed `inputs`.
+    ///
+    /// The values of the data sources are filtered using the sensor
+    /// attenuation of the controllers, based on the time passed since the
+    /// previous call. If `time` is before the previous call, the filters are
+    /// reset and the inputs are used as they are.
+    pub fn render(&mut self, time: Duration, inputs: &Inputs) -> Vec<LedColor> {
+        let dt = self
+            .time
+            .and_then(|prev| time.checked_sub(prev))
+            .map(|dt| dt.as_secs_f64());
+        self.time = Some(time);
+
+        let mut leds = vec![[0.0; 3]; self.led_count];
+        for (controller, input) in self.controllers.iter().zip(&mut self.inputs) {
+            let target = controller.data_source.and_then(|s| inputs.get(s));
+            *input = attenuate(controller, *input, target, dt);
+
+            let start = usize::from(controller.offset).min(leds.len());
+            let end = (start + usize::from(controller.length)).min(leds.len());
+            let ctx = Context {
+                time: time.as_secs_f64(),
+                input: *input,
+                sound: inputs.get(DataSource::Sound),
+                len: usize::from(controller.length),
+            };
+
+            render_effect(&controller.effect, &ctx, &mut leds[start..end]);
+        }
+
+        leds.into_iter().map(to_led).collect()
+    }
+
+    /// Renders the LEDs at the current time of the passed `clock`, relative
+    /// to `start` (see [`render`](Self::render)).
+    pub fn render_with_clock<K>(
+        &mut self,
+        clock: &K,
+        start: Instant,
+        inputs: &Inputs,
+    ) -> Vec<LedColor>
+    where
+        K: Clock,
+    {
+        self.render(clock.now().saturating_duration_since(start), inputs)
+    }
+}
+
+/// Applies the sensor attenuation of the `controller` to the `target` value.
+fn attenuate(
+    controller: &Controller,
+    current: Option<f64>,
+    target: Option<f64>,
+    dt: Option<f64>,
+) -> Option<f64> {
+    let (Some(current), Some(target), Some(dt)) = (current, target, dt) else {
+        return target;
+    };
+
+    let attenuation = if target > current {
+        controller.sensor_attenuation_rising
+    } else {
+        controller.sensor_attenuation_falling
+    };
+    if attenuation == 0 {
+        return Some(target);
+    }
+
+    // The attenuation is interpreted as time constant in 1/10 seconds.
+    let tau = f64::from(attenuation) / 10.0;
+
+    Some(current + (target - current) * (1.0 - (-dt / tau).exp()))
+}
+
+type Rgb = [f64; 3];
+
+struct Context {
+    time: f64,
+    input: Option<f64>,
+    sound: Option<f64>,
+    len: usize,
+}
+
+impl Context {
+    /// Maps the input of the controller using the passed `control` and returns
+    /// the result as factor, or `default` if the effect is not controlled.
+    fn control(&self, control: Option<&SourceControl>, default: f64) -> f64 {
+        match (control, self.input) {
+            (Some(control), Some(input)) => control_output(control, input) / 100.0,
+            _ => default,
+        }
+    }
+
+    /// Like [`control`](Self::control) but for a speed given in percent.
+    fn speed(&self, control: Option<&SourceControl>, speed: u16) -> f64 {
+        self.control(control, f64::from(speed) / 100.0) * 100.0
+    }
+
+    /// Current level of the sound input (`0.0..=1.0`).
+    fn sound(&self) -> f64 {
+        (self.sound.unwrap_or_default() / 100.0).clamp(0.0, 1.0)
+    }
+
+    /// Returns the position of the LED with the passed `index` (`0.0..1.0`).
+    #[allow(clippy::cast_precision_loss)]
+    fn position(&self, index: usize, reverse: bool) -> f64 {
+        let x = (index as f64 + 0.5) / self.len.max(1) as f64;
+
+        if reverse {
+            1.0 - x
+        } else {
+            x
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn len(&self) -> f64 {
+        self.len.max(1) as f64
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn control_output(control: &SourceControl, input: f64) -> f64 {
+    f64::from(control.map(input as f32))
+}
+
+#[allow(clippy::too_many_lines)]
+fn render_effect(effect: &Effect, ctx: &Context, leds: &mut [Rgb]) {
+    match effect {
+        Effect::Static(e) => fill(leds, |_| static_color(e, ctx)),
+        Effect::Breathing(e) => {
+            let color = breathing(e, ctx);
+
+            fill(leds, |_| color);
+        }
+        Effect::Rainbow(e) => fill(leds, |i| rainbow(e, ctx, i)),
+        Effect::Blink(e) => {
+            let color = blink(e, ctx);
+
+            fill(leds, |_| color);
+        }
+        Effect::ColorChange(e) => fill(leds, |i| color_change(e, ctx, i)),
+        Effect::Sequence(e) => fill(leds, |i| sequence(e, ctx, i)),
+        Effect::Scanner(e) | Effect::Laser(e) => fill(leds, |i| scanner(e, ctx, i)),
+        Effect::Wave(e) => fill(leds, |i| wave(e, ctx, i)),
+        Effect::ColorSequence(e) => fill(leds, |i| color_sequence(e, ctx, i)),
+        Effect::ColorShift(e) => fill(leds, |i| color_shift(e, ctx, i)),
+        Effect::BarGraph(e) => {
+            let value = ctx.input.unwrap_or_default();
+
+            fill(leds, |i| bar_graph(e, ctx, value, i));
+        }
+        Effect::SoundBars(e) => {
+            let value = ctx.sound() * f64::from(e.end_value);
+
+            fill(leds, |i| bar_graph(e, ctx, value, i));
+        }
+        Effect::Flame(e) => fill(leds, |i| flame(e, ctx, i)),
+        Effect::Rain(e) => fill(leds, |i| rain(e, ctx, i, 1.0)),
+        Effect::Snow(e) => fill(leds, |i| rain(e, ctx, i, 0.3)),
+        Effect::Stardust(e) => fill(leds, |i| stardust(e, ctx, i)),
+        Effect::ColorSwitch(e) => {
+            let color = color_switch(e, ctx);
+
+            fill(leds, |_| color);
+        }
+        Effect::SwipingRainbow(e) => fill(leds, |i| swiping_rainbow(e, ctx, i)),
+        Effect::SoundFlash(e) => {
+            let color = sound_flash(e, ctx);
+
+            fill(leds, |_| color);
+        }
+        Effect::SoundSlider(e) => fill(leds, |i| sound_slider(e, ctx, i)),
+        Effect::SoundShift(e) => fill(leds, |i| sound_shift(e, ctx, i)),
+        Effect::Ambient(e) => fill(leds, |_| rgb(e.background)),
+        Effect::ColorGradient(e) => fill(leds, |i| color_gradient(e, ctx, i)),
+    }
+}
+
+fn fill<F>(leds: &mut [Rgb], mut f: F)
+where
+    F: FnMut(usize) -> Rgb,
+{
+    for (i, led) in leds.iter_mut().enumerate() {
+        *led = f(i);
+    }
+}
+
+fn static_color(e: &EffectStatic, ctx: &Context) -> Rgb {
+    let hsv = e.color.hsv();
+    let s = hsv.s * ctx.control(e.source_control_saturation.as_ref(), 1.0);
+    let v = hsv.v * ctx.control(e.source_control_brightness.as_ref(), 1.0);
+
+    hsv_rgb(hsv.h, s, v)
+}
+
+fn breathing(e: &EffectBreathing, ctx: &Context) -> Rgb {
+    let speed = ctx.speed(e.source_control_speed.as_ref(), *e.speed);
+    let intensity = ctx.control(
+        e.source_control_intensity.as_ref(),
+        f64::from(*e.intensity) / 100.0,
+    );
+
+    // Rise, hold at maximum, fall and hold at minimum brightness.
+    let ramp = period(speed) / 2.0;
+    let hold_max = f64::from(*e.delay_max_brightness) / 10.0;
+    let hold_min = f64::from(*e.delay_min_brightness) / 10.0;
+    let t = ctx.time % (2.0 * ramp + hold_max + hold_min);
+
+    let level = if t < ramp {
+        ease(t / ramp)
+    } else if t < ramp + hold_max {
+        1.0
+    } else if t < 2.0 * ramp + hold_max {
+        1.0 - ease((t - ramp - hold_max) / ramp)
+    } else {
+        0.0
+    };
+
+    scale(rgb(e.color), 1.0 - intensity * (1.0 - level))
+}
+
+fn rainbow(e: &EffectRainbow, ctx: &Context, i: usize) -> Rgb {
+    let speed = ctx.speed(e.source_control_speed.as_ref(), *e.speed);
+    let brightness = ctx.control(e.source_control_brightness.as_ref(), 1.0);
+
+    let hsv = e.color.hsv();
+    let x = ctx.position(i, e.reverse_direction);
+    let shift = ctx.time / period(speed);
+    let range = f64::from(*e.color_range) / 100.0;
+    let h = hsv.h + 360.0 * (range * x - shift);
+
+    hsv_rgb(h, hsv.s, hsv.v * brightness)
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn blink(e: &EffectBlink, ctx: &Context) -> Rgb {
+    let speed = ctx.speed(e.source_control_speed.as_ref(), *e.speed);
+    let brightness = ctx.control(e.source_control_brightness.as_ref(), 1.0);
+
+    let background = rgb(e.background);
+    if e.colors.is_empty() {
+        return scale(background, brightness);
+    }
+
+    let cycle = ctx.time / period(speed);
+    let index = cycle.floor() as u64;
+    let phase = cycle.fract();
+
+    let color = e.colors[pick(index, e.colors.len(), e.random_color)];
+    let color = if e.slide_colors && phase < 0.5 {
+        let next = e.colors[pick(index + 1, e.colors.len(), e.random_color)];
+
+        mix(rgb(color), rgb(next), phase * 2.0)
+    } else {
+        rgb(color)
+    };
+
+    // The color is shown in the first half of the cycle, the background in
+    // the second one.
+    let mut level = if phase < 0.5 { 1.0 } else { 0.0 };
+    if e.fade_in && phase < 0.125 {
+        level = phase / 0.125;
+    }
+    if e.fade_out && (0.375..0.5).contains(&phase) {
+        level = (0.5 - phase) / 0.125;
+    }
+
+    scale(mix(background, color, level), brightness)
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn color_change(e: &EffectColorChange, ctx: &Context, i: usize) -> Rgb {
+    let speed = ctx.speed(e.source_control_speed.as_ref(), *e.speed);
+    let brightness = ctx.control(e.source_control_brightness.as_ref(), 1.0);
+
+    if e.colors.is_empty() {
+        return [0.0; 3];
+    }
+
+    let mut cycle = ctx.time / period(speed);
+    if e.slide_colors {
+        cycle += ctx.position(i, false);
+    }
+
+    let index = cycle.floor() as u64;
+    let current = rgb(e.colors[pick(index, e.colors.len(), e.random_color)]);
+    let color = if e.fade {
+        let next = rgb(e.colors[pick(index + 1, e.colors.len(), e.random_color)]);
+
+        mix(current, next, ease(cycle.fract()))
+    } else {
+        current
+    };
+
+    scale(color, brightness)
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn sequence(e: &EffectSequence, ctx: &Context, i: usize) -> Rgb {
+    let speed = ctx.speed(e.source_control_speed.as_ref(), *e.speed);
+    let brightness = ctx.control(e.source_control_brightness.as_ref(), 1.0);
+
+    let background = rgb(e.background);
+    if e.colors.is_empty() {
+        return scale(background, brightness);
+    }
+
+    // Wait, fill the LEDs one after the other and wait again.
+    let fill_time = period(speed);
+    let before = f64::from(*e.delay_before_sequence) / 10.0;
+    let after = f64::from(*e.delay_after_sequence) / 10.0;
+    let cycle_time = before + fill_time + after;
+
+    let index = (ctx.time / cycle_time).floor() as u64;
+    let t = ctx.time % cycle_time;
+    let progress = ((t - before) / fill_time).clamp(0.0, 1.0);
+
+    let x = ctx.position(i, e.reverse_direction);
+    let edge = if e.fade {
+        (f64::from(*e.smoothness) / 100.0).max(1.0 / ctx.len())
+    } else {
+        1.0 / ctx.len()
+    };
+    let level = ((progress - x) / edge + 0.5).clamp(0.0, 1.0);
+
+    let color = rgb(e.colors[pick(index, e.colors.len(), e.random_color)]);
+
+    scale(mix(background, color, level), brightness)
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn scanner(e: &EffectScanner, ctx: &Context, i: usize) -> Rgb {
+    let speed = ctx.speed(e.source_control_speed.as_ref(), *e.speed);
+    let brightness = ctx.control(e.source_control_brightness.as_ref(), 1.0);
+
+    let cycle = ctx.time / period(speed);
+    let position = if e.circular {
+        cycle.fract()
+    } else {
+        1.0 - (1.0 - 2.0 * cycle.fract()).abs()
+    };
+
+    let x = ctx.position(i, e.reverse_direction);
+    let mut distance = (x - position).abs();
+    if e.circular {
+        distance = distance.min(1.0 - distance);
+    }
+
+    let width = f64::from(*e.width) / 100.0 / 2.0;
+    let smooth = f64::from(*e.smoothness) / 100.0 * width;
+
+    let (mut inner, mut outer) = (rgb(e.inner_color), rgb(e.outer_color));
+    if e.random_color || e.color_change {
+        let hue = 360.0 * noise(cycle.floor() as u64, 0);
+        inner = hsv_rgb(hue, 1.0, 1.0);
+        outer = hsv_rgb(hue, 1.0, 0.5);
+    }
+
+    let background = rgb(e.background);
+    let color = if distance <= width {
+        mix(inner, outer, distance / width)
+    } else if e.fade && distance <= width + smooth {
+        mix(outer, background, (distance - width) / smooth)
+    } else {
+        background
+    };
+
+    scale(color, brightness)
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn wave(e: &EffectWave, ctx: &Context, i: usize) -> Rgb {
+    let speed = ctx.speed(e.source_control_speed.as_ref(), *e.speed);
+    let brightness = ctx.control(e.source_control_brightness.as_ref(), 1.0);
+
+    let background = rgb(e.background);
+    if e.colors.is_empty() {
+        return scale(background, brightness);
+    }
+
+    // Colored segments of the configured width, separated by segments of the
+    // background color.
+    let width = f64::from(*e.width) / 100.0;
+    let x = ctx.position(i, e.reverse_direction);
+    let offset = x / width - ctx.time / period(speed) * 2.0;
+    let segment = offset.floor() as i64;
+
+    let smooth = f64::from(*e.smoothness) / 100.0 / 2.0;
+    let frac = offset.fract();
+    let level = if smooth > 0.0 {
+        (frac.min(1.0 - frac) / smooth).min(1.0)
+    } else {
+        1.0
+    };
+
+    if segment.rem_euclid(2) != 0 {
+        return scale(background, brightness);
+    }
+
+    let index = segment.div_euclid(2) as u64;
+    let color = rgb(e.colors[pick(index, e.colors.len(), e.random_color)]);
+
+    scale(mix(background, color, level), brightness)
+}
+
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+fn color_sequence(e: &EffectColorSequence, ctx: &Context, i: usize) -> Rgb {
+    let speed = ctx.speed(e.source_control_speed.as_ref(), *e.speed);
+    let brightness = ctx.control(e.source_control_brightness.as_ref(), 1.0);
+
+    if e.colors.is_empty() {
+        return [0.0; 3];
+    }
+
+    let x = ctx.position(i, e.reverse_direction);
+    let offset = x * e.colors.len() as f64 + ctx.time / period(speed);
+    let index = offset.floor() as u64;
+
+    let current = rgb(e.colors[pick(index, e.colors.len(), e.random_color)]);
+    let next = rgb(e.colors[pick(index + 1, e.colors.len(), e.random_color)]);
+
+    let smooth = f64::from(*e.smoothness) / 100.0;
+    let frac = offset.fract();
+    let level = if smooth > 0.0 {
+        ((frac - (1.0 - smooth)) / smooth).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    scale(mix(current, next, ease(level)), brightness)
+}
+
+fn color_shift(e: &EffectColorShift, ctx: &Context, i: usize) -> Rgb {
+    let speed = ctx.speed(e.source_control_speed.as_ref(), *e.speed);
+    let brightness = ctx.control(e.source_control_brightness.as_ref(), 1.0);
+
+    let hsv = e.color.hsv();
+    let area = (f64::from(*e.total_area) / 100.0).max(1.0 / ctx.len());
+    let x = ctx.position(i, e.reverse_direction) / area;
+    let range = f64::from(*e.color_range) / 100.0 * 360.0;
+    let h = hsv.h + range * (PI * 2.0 * (x - ctx.time / period(speed))).sin() / 2.0;
+
+    hsv_rgb(h, hsv.s, hsv.v * brightness)
+}
+
+fn bar_graph(e: &EffectBarGraph, ctx: &Context, value: f64, i: usize) -> Rgb {
+    let end = f64::from(e.end_value.max(1));
+    let level = (value / end).clamp(0.0, 1.0);
+    let x = ctx.position(i, e.reverse_direction);
+    let step = 1.0 / ctx.len();
+
+    if e.show_peak && (x - level).abs() < step / 2.0 {
+        return rgb(e.peak_color);
+    }
+    if !e.show_bar || x > level {
+        return rgb(e.background);
+    }
+
+    // Value the LED represents (or the current value if the ranges are not
+    // displayed along the bar).
+    let value = if e.show_ranges { x * end } else { value };
+
+    range_color(&e.colors, value, e.fade_ranges).unwrap_or_else(|| rgb(e.peak_color))
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn flame(e: &EffectFlame, ctx: &Context, i: usize) -> Rgb {
+    let intensity = ctx.control(
+        e.source_control_intensity.as_ref(),
+        f64::from(*e.intensity) / 100.0,
+    );
+
+    // Smoothly interpolated noise that changes 10 times per second.
+    let t = ctx.time * 10.0;
+    let step = t.floor() as u64;
+    let led = i as u64;
+    let flicker = lerp(noise(led, step), noise(led, step + 1), ease(t.fract()));
+    let tint = noise(led + 1000, step / 4);
+
+    let color = mix(rgb(e.color_primary), rgb(e.color_secondary), tint);
+
+    mix(rgb(e.background), color, 1.0 - intensity * flicker)
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn rain(e: &EffectRain, ctx: &Context, i: usize, factor: f64) -> Rgb {
+    let speed = ctx.speed(e.source_control_speed.as_ref(), *e.speed) * factor;
+    let brightness = ctx.control(e.source_control_brightness.as_ref(), 1.0);
+
+    let x = ctx.position(i, e.reverse_direction);
+    let size = (f64::from(*e.size) / 100.0 / 2.0).max(1.0 / ctx.len());
+    let tail = f64::from(*e.smoothness) / 100.0;
+
+    let mut color = rgb(e.background);
+    for drop in 0..u64::from(*e.items) {
+        let cycle = ctx.time / period(speed) + noise(drop, 0);
+        let position = cycle.fract();
+        let distance = position - x;
+
+        let level = if (0.0..size).contains(&distance) {
+            1.0
+        } else if tail > 0.0 && distance >= size && distance < size + tail {
+            1.0 - (distance - size) / tail
+        } else {
+            continue;
+        };
+
+        let drop_color = if e.random_color {
+            hsv_rgb(360.0 * noise(drop, cycle.floor() as u64), 1.0, 1.0)
+        } else {
+            rgb(e.color)
+        };
+
+        color = mix(color, drop_color, level);
+    }
+
+    scale(color, brightness)
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn stardust(e: &EffectRain, ctx: &Context, i: usize) -> Rgb {
+    let speed = ctx.speed(e.source_control_speed.as_ref(), *e.speed);
+    let brightness = ctx.control(e.source_control_brightness.as_ref(), 1.0);
+
+    let x = ctx.position(i, false);
+    let size = (f64::from(*e.size) / 100.0 / 2.0).max(1.0 / ctx.len());
+
+    // Stars appear at random positions and fade in and out.
+    let mut color = rgb(e.background);
+    for star in 0..u64::from(*e.items) {
+        let cycle = ctx.time / period(speed) + noise(star, 0);
+        let index = cycle.floor() as u64;
+        let position = noise(star, index + 1);
+
+        if (x - position).abs() > size {
+            continue;
+        }
+
+        let star_color = if e.random_color {
+            hsv_rgb(360.0 * noise(star + 1000, index), 1.0, 1.0)
+        } else {
+            rgb(e.color)
+        };
+
+        color = mix(color, star_color, (cycle.fract() * PI).sin());
+    }
+
+    scale(color, brightness)
+}
+
+fn color_switch(e: &EffectColorSwitch, ctx: &Context) -> Rgb {
+    let brightness = ctx.control(e.source_control_brightness.as_ref(), 1.0);
+    let value = ctx.input.unwrap_or_default();
+
+    let color = range_color(&e.colors, value, e.fade_ranges).unwrap_or_default();
+
+    scale(color, brightness)
+}
+
+fn swiping_rainbow(e: &EffectSwipingRainbow, ctx: &Context, i: usize) -> Rgb {
+    let speed = ctx.speed(e.source_control_speed.as_ref(), *e.point_speed);
+    let brightness = ctx.control(e.source_control_brightness.as_ref(), 1.0);
+
+    let x = ctx.position(i, e.reverse_direction);
+
+    let strip = e.strip_color.hsv();
+    let range = f64::from(*e.color_range) / 100.0 * 360.0;
+    let shift = ctx.time / period(f64::from(*e.color_change_speed)) * 360.0;
+    let strip = hsv_rgb(strip.h + range * x + shift, strip.s, strip.v);
+
+    let position = (ctx.time / period(speed)).fract();
+    let size = (f64::from(*e.point_size) / 100.0 / 2.0).max(1.0 / ctx.len());
+    let smooth = f64::from(*e.point_smoothness) / 100.0 * size;
+    let distance = (x - position).abs();
+
+    let level = if distance <= size {
+        1.0
+    } else if smooth > 0.0 && distance <= size + smooth {
+        1.0 - (distance - size) / smooth
+    } else {
+        0.0
+    };
+
+    scale(mix(strip, rgb(e.point_color), level), brightness)
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn sound_flash(e: &EffectSoundFlash, ctx: &Context) -> Rgb {
+    let index = ctx.time.floor() as usize % e.colors.len();
+
+    mix(rgb(e.background), rgb(e.colors[index]), ctx.sound())
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn sound_slider(e: &EffectSoundSlider, ctx: &Context, i: usize) -> Rgb {
+    let rotation = f64::from(*e.rotate_color) / 100.0;
+    let index = (ctx.time * rotation).floor() as usize % e.effects.len();
+    let (color, effect, _speed) = &e.effects[index];
+
+    let level = ctx.sound();
+    let x = ctx.position(i, false);
+    let center = (x - 0.5).abs() * 2.0;
+
+    let lit = match effect {
+        SoundEffect::OutwardsFromCenter => center <= level,
+        SoundEffect::InwardsToCenterA | SoundEffect::InwardsToCenterB => center >= 1.0 - level,
+        SoundEffect::FromLeft => x <= level,
+        SoundEffect::FromRight => 1.0 - x <= level,
+        SoundEffect::AllLEDs => {
+            return mix(rgb(e.background), rgb(*color), level);
+        }
+    };
+
+    if lit && level > 0.0 {
+        rgb(*color)
+    } else {
+        rgb(e.background)
+    }
+}
+
+fn sound_shift(e: &EffectSoundShift, ctx: &Context, i: usize) -> Rgb {
+    let level = ctx.sound();
+    let idle = f64::from(*e.idle_speed);
+    let active = f64::from(*e.activity_speed);
+    let speed = idle + (active - idle) * level;
+
+    let x = ctx.position(i, e.reverse_direction);
+    let u = (x * 2.0 - ctx.time / period(speed)).rem_euclid(2.0);
+    let (color, _speed, active) = &e.effects[usize::from(u >= 1.0)];
+
+    let level = if *active { level } else { 1.0 };
+
+    mix(rgb(e.background), rgb(*color), level)
+}
+
+fn color_gradient(e: &EffectColorGradient, ctx: &Context, i: usize) -> Rgb {
+    // Positions of the gradient stops are given in 1/1000 of the length; the
+    // gradient wraps around to the start color.
+    const END: f64 = 1000.0;
+
+    let speed = ctx.speed(e.source_control_rotation.as_ref(), *e.rotation);
+    let rotation = if speed > 0.0 {
+        let shift = ctx.time / period(speed);
+
+        if e.reverse_rotation {
+            -shift
+        } else {
+            shift
+        }
+    } else {
+        0.0
+    };
+
+    let x = (ctx.position(i, e.reverse_direction) + rotation).rem_euclid(1.0) * END;
+
+    let mut prev = (rgb(e.start_color), 0.0);
+    for (color, position) in &e.colors {
+        let next = (rgb(*color), f64::from(*position));
+        if x < next.1 {
+            return mix(prev.0, next.0, (x - prev.1) / (next.1 - prev.1).max(1.0));
+        }
+
+        prev = next;
+    }
+
+    mix(
+        prev.0,
+        rgb(e.start_color),
+        (x - prev.1) / (END - prev.1).max(1.0),
+    )
+}
+
+/// Returns the color of the range `value` belongs to.
+///
+/// The ranges are given as `(color, threshold, enabled)`. If `fade` is set the
+/// colors of adjacent ranges are blended.
+fn range_color(ranges: &[(Color, u16, bool)], value: f64, fade: bool) -> Option<Rgb> {
+    let mut ranges = ranges
+        .iter()
+        .map(|(color, threshold, _)| (color, *threshold));
+    let (first, mut threshold) = ranges.next()?;
+    let mut color = rgb(*first);
+
+    if value < f64::from(threshold) {
+        return Some(color);
+    }
+
+    for (next, next_threshold) in ranges {
+        if value < f64::from(next_threshold) {
+            if fade {
+                let f = (value - f64::from(threshold))
+                    / f64::from(next_threshold.saturating_sub(threshold).max(1));
+
+                return Some(mix(color, rgb(*next), f));
+            }
+
+            return Some(color);
+        }
+
+        color = rgb(*next);
+        threshold = next_threshold;
+    }
+
+    Some(color)
+}
+
+/// Returns the duration of an effect cycle (in seconds) for the passed speed
+/// (in percent): 20 seconds at `0`, half a second at `100`.
+fn period(speed: f64) -> f64 {
+    20.0 * 0.025_f64.powf(speed.clamp(0.0, 100.0) / 100.0)
+}
+
+/// Returns the index of the color to use in the cycle `index`.
+#[allow(clippy::cast_possible_truncation)]
+fn pick(index: u64, count: usize, random: bool) -> usize {
+    let count = count as u64;
+
+    if random {
+        (splitmix(index) % count) as usize
+    } else {
+        (index % count) as usize
+    }
+}
+
+/// Deterministic noise in `0.0..1.0`.
+#[allow(clippy::cast_precision_loss)]
+fn noise(a: u64, b: u64) -> f64 {
+    (splitmix(a ^ splitmix(b)) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+fn splitmix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+    x ^ (x >> 31)
+}
+
+fn ease(f: f64) -> f64 {
+    let f = f.clamp(0.0, 1.0);
+
+    (1.0 - (f * PI).cos()) / 2.0
+}
+
+fn lerp(a: f64, b: f64, f: f64) -> f64 {
+    a + (b - a) * f
+}
+
+fn rgb(color: Color) -> Rgb {
+    let hsv = color.hsv();
+
+    hsv_rgb(hsv.h, hsv.s, hsv.v)
+}
+
+fn hsv_rgb(h: f64, s: f64, v: f64) -> Rgb {
+    let hsv = color_space::Hsv::new(h.rem_euclid(360.0), s.clamp(0.0, 1.0), v.clamp(0.0, 1.0));
+    let rgb = hsv.to_rgb();
+
+    [rgb.r / 255.0, rgb.g / 255.0, rgb.b / 255.0]
+}
+
+fn mix(a: Rgb, b: Rgb, f: f64) -> Rgb {
+    let f = f.clamp(0.0, 1.0);
+
+    [0, 1, 2].map(|i| lerp(a[i], b[i], f))
+}
+
+fn scale(a: Rgb, f: f64) -> Rgb {
+    a.map(|x| x * f.clamp(0.0, 1.0))
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn to_led(rgb: Rgb) -> LedColor {
+    rgb.map(|x| (x * 255.0).round().clamp(0.0, 255.0) as u8)
+}
@@ -0,0 +1,620 @@
+//! Stable serde representation of the [`Effect`].
+//!
+//! Human readable formats (JSON, TOML, YAML) store an effect as object with
+//! the `kind` of the effect followed by its `params`:
+//!
+//! ```json
+//! { "kind": "Rainbow", "params": { "speed": 50, "color_range": 100, ... } }
+//! ```
+//!
+//! The `params` may also be stored before the `kind`, they are buffered as
+//! [`ParamValue`] until the kind is known.
+//!
+//! The effect specific structs may gain new parameters in later versions, so
+//! parameters that are not known by this version are ignored when loading an
+//! [`Effect`] and kept in the [`EffectRecord`]. The externally tagged format
+//! written by older versions (`{ "Rainbow": { ... } }`) is accepted as well.
+//!
+//! Binary formats keep the compact representation (variant index followed
+//! by the parameters).
+
+use std::collections::BTreeMap;
+use std::fmt::{Formatter, Result as FmtResult};
+use std::marker::PhantomData;
+
+use serde::de::value::{MapAccessDeserializer, MapDeserializer, SeqDeserializer};
+use serde::de::{
+    DeserializeSeed, EnumAccess, Error as DeError, IgnoredAny, IntoDeserializer, MapAccess,
+    SeqAccess, VariantAccess, Visitor,
+};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{Effect, EffectKind};
+
+/// Names of the effect kinds, in the order of [`EffectKind::ALL`].
+const NAMES: [&str; 24] = {
+    let mut names = [""; 24];
+
+    let mut i = 0;
+    while i < names.len() {
+        names[i] = EffectKind::ALL[i].name();
+        i += 1;
+    }
+
+    names
+};
+
+/// Parameters of a stored effect that are not known by this version of the
+/// crate.
+pub type UnknownParams = BTreeMap<String, ParamValue>;
+
+/// Value of a parameter that is not known by this version of the crate.
+#[derive(Debug, Clone, PartialEq)]
+#[allow(missing_docs)]
+pub enum ParamValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    String(String),
+    List(Vec<ParamValue>),
+    Map(BTreeMap<String, ParamValue>),
+}
+
+/// [`Effect`] loaded from a human readable format, together with the
+/// parameters that are not known by this version of the crate.
+///
+/// Tools that store lighting configurations should load and save the effects
+/// as records, so parameters written by a newer version are preserved if the
+/// configuration is edited with an older one. The [`Effect`] itself drops
+/// them.
+///
+/// Records only support human readable (self-describing) formats.
+///
+/// # Example
+///
+/// ```rust
+/// use high_flow_next::protocol::settings::{EffectKind, EffectRecord};
+///
+/// let json = r#"{
+///     "kind": "Static",
+///     "params": { "color": { "h": 120.0, "s": 1.0, "v": 0.5 }, "glow": 3 }
+/// }"#;
+///
+/// let record = serde_json::from_str::<EffectRecord>(json).unwrap();
+/// assert_eq!(record.effect.kind(), EffectKind::Static);
+/// assert!(record.unknown.contains_key("glow"));
+///
+/// let saved = serde_json::to_value(&record).unwrap();
+/// assert_eq!(saved["params"]["glow"], 3);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectRecord {
+    /// The loaded effect.
+    pub effect: Effect,
+
+    /// Parameters of the effect that are not known by this version.
+    pub unknown: UnknownParams,
+}
+
+impl From<Effect> for EffectRecord {
+    fn from(effect: Effect) -> Self {
+        Self {
+            effect,
+            unknown: UnknownParams::new(),
+        }
+    }
+}
+
+impl From<EffectRecord> for Effect {
+    fn from(record: EffectRecord) -> Self {
+        record.effect
+    }
+}
+
+impl Serialize for Effect {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            return serialize_tagged(self, None, serializer);
+        }
+
+        let index = kind_index(self.kind());
+
+        macro_rules! variant {
+            ($x:ident) => {
+                serializer.serialize_newtype_variant("Effect", index, self.name(), $x)
+            };
+        }
+
+        match self {
+            Self::Static(x) => variant!(x),
+            Self::Breathing(x) => variant!(x),
+            Self::Rainbow(x) => variant!(x),
+            Self::Blink(x) => variant!(x),
+            Self::ColorChange(x) => variant!(x),
+            Self::Sequence(x) => variant!(x),
+            Self::Scanner(x) | Self::Laser(x) => variant!(x),
+            Self::Wave(x) => variant!(x),
+            Self::ColorSequence(x) => variant!(x),
+            Self::ColorShift(x) => variant!(x),
+            Self::BarGraph(x) | Self::SoundBars(x) => variant!(x),
+            Self::Flame(x) => variant!(x),
+            Self::Rain(x) | Self::Snow(x) | Self::Stardust(x) => variant!(x),
+            Self::ColorSwitch(x) => variant!(x),
+            Self::SwipingRainbow(x) => variant!(x),
+            Self::SoundFlash(x) => variant!(x),
+            Self::SoundSlider(x) => variant!(x),
+            Self::SoundShift(x) => variant!(x),
+            Self::Ambient(x) => variant!(x),
+            Self::ColorGradient(x) => variant!(x),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Effect {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let visitor = EffectVisitor { capture: false };
+
+        let (effect, _) = if deserializer.is_human_readable() {
+            deserializer.deserialize_map(visitor)?
+        } else {
+            deserializer.deserialize_enum("Effect", &NAMES, visitor)?
+        };
+
+        Ok(effect)
+    }
+}
+
+impl Serialize for EffectRecord {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_tagged(&self.effect, Some(&self.unknown), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for EffectRecord {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (effect, unknown) = deserializer.deserialize_map(EffectVisitor { capture: true })?;
+
+        Ok(Self { effect, unknown })
+    }
+}
+
+impl Serialize for ParamValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Null => serializer.serialize_unit(),
+            Self::Bool(x) => serializer.serialize_bool(*x),
+            Self::Int(x) => serializer.serialize_i64(*x),
+            Self::UInt(x) => serializer.serialize_u64(*x),
+            Self::Float(x) => serializer.serialize_f64(*x),
+            Self::String(x) => serializer.serialize_str(x),
+            Self::List(x) => x.serialize(serializer),
+            Self::Map(x) => x.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ParamValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ValueVisitor;
+
+        impl<'de> Visitor<'de> for ValueVisitor {
+            type Value = ParamValue;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> FmtResult {
+                f.write_str("any value")
+            }
+
+            fn visit_unit<E>(self) -> Result<ParamValue, E> {
+                Ok(ParamValue::Null)
+            }
+
+            fn visit_none<E>(self) -> Result<ParamValue, E> {
+                Ok(ParamValue::Null)
+            }
+
+            fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<ParamValue, D::Error> {
+                ParamValue::deserialize(d)
+            }
+
+            fn visit_bool<E>(self, v: bool) -> Result<ParamValue, E> {
+                Ok(ParamValue::Bool(v))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<ParamValue, E> {
+                Ok(ParamValue::Int(v))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<ParamValue, E> {
+                Ok(ParamValue::UInt(v))
+            }
+
+            fn visit_f64<E>(self, v: f64) -> Result<ParamValue, E> {
+                Ok(ParamValue::Float(v))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<ParamValue, E> {
+                Ok(ParamValue::String(v.into()))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<ParamValue, A::Error> {
+                let mut items = Vec::new();
+                while let Some(item) = seq.next_element()? {
+                    items.push(item);
+                }
+
+                Ok(ParamValue::List(items))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<ParamValue, A::Error> {
+                let mut items = BTreeMap::new();
+                while let Some((key, value)) = map.next_entry()? {
+                    items.insert(key, value);
+                }
+
+                Ok(ParamValue::Map(items))
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+/// [`Deserializer`] for buffered [`ParamValue`]s.
+struct ParamDeserializer<E> {
+    value: ParamValue,
+    error: PhantomData<E>,
+}
+
+impl<E> ParamDeserializer<E> {
+    fn new(value: ParamValue) -> Self {
+        Self {
+            value,
+            error: PhantomData,
+        }
+    }
+}
+
+impl<E: DeError> IntoDeserializer<'_, E> for ParamDeserializer<E> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de, E: DeError> Deserializer<'de> for ParamDeserializer<E> {
+    type Error = E;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+        match self.value {
+            ParamValue::Null => visitor.visit_unit(),
+            ParamValue::Bool(x) => visitor.visit_bool(x),
+            ParamValue::Int(x) => visitor.visit_i64(x),
+            ParamValue::UInt(x) => visitor.visit_u64(x),
+            ParamValue::Float(x) => visitor.visit_f64(x),
+            ParamValue::String(x) => visitor.visit_string(x),
+            ParamValue::List(x) => {
+                visitor.visit_seq(SeqDeserializer::new(x.into_iter().map(Self::new)))
+            }
+            ParamValue::Map(x) => visitor.visit_map(MapDeserializer::new(
+                x.into_iter().map(|(key, value)| (key, Self::new(value))),
+            )),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+        match self.value {
+            ParamValue::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, E> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, E> {
+        match self.value {
+            ParamValue::String(x) => visitor.visit_enum(x.into_deserializer()),
+            ParamValue::Map(x) => visitor.visit_enum(MapAccessDeserializer::new(
+                MapDeserializer::new(x.into_iter().map(|(key, value)| (key, Self::new(value)))),
+            )),
+            value => Self::new(value).deserialize_any(visitor),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+/// Writes `effect` as `kind` and `params`, adding the `unknown` parameters
+/// to the parameters of the effect.
+fn serialize_tagged<S: Serializer>(
+    effect: &Effect,
+    unknown: Option<&UnknownParams>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut s = serializer.serialize_struct("Effect", 2)?;
+    s.serialize_field("kind", effect.name())?;
+    s.serialize_field("params", &Params { effect, unknown })?;
+    s.end()
+}
+
+/// Parameters of an [`Effect`], optionally extended by unknown ones.
+struct Params<'a> {
+    effect: &'a Effect,
+    unknown: Option<&'a UnknownParams>,
+}
+
+impl Serialize for Params<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        macro_rules! params {
+            ($x:ident) => {
+                match self.unknown {
+                    Some(unknown) if !unknown.is_empty() => WithUnknownRef {
+                        params: $x,
+                        unknown,
+                    }
+                    .serialize(serializer),
+                    _ => $x.serialize(serializer),
+                }
+            };
+        }
+
+        match self.effect {
+            Effect::Static(x) => params!(x),
+            Effect::Breathing(x) => params!(x),
+            Effect::Rainbow(x) => params!(x),
+            Effect::Blink(x) => params!(x),
+            Effect::ColorChange(x) => params!(x),
+            Effect::Sequence(x) => params!(x),
+            Effect::Scanner(x) | Effect::Laser(x) => params!(x),
+            Effect::Wave(x) => params!(x),
+            Effect::ColorSequence(x) => params!(x),
+            Effect::ColorShift(x) => params!(x),
+            Effect::BarGraph(x) | Effect::SoundBars(x) => params!(x),
+            Effect::Flame(x) => params!(x),
+            Effect::Rain(x) | Effect::Snow(x) | Effect::Stardust(x) => params!(x),
+            Effect::ColorSwitch(x) => params!(x),
+            Effect::SwipingRainbow(x) => params!(x),
+            Effect::SoundFlash(x) => params!(x),
+            Effect::SoundSlider(x) => params!(x),
+            Effect::SoundShift(x) => params!(x),
+            Effect::Ambient(x) => params!(x),
+            Effect::ColorGradient(x) => params!(x),
+        }
+    }
+}
+
+/// Parameters of an effect together with the unknown ones.
+#[derive(Serialize)]
+struct WithUnknownRef<'a, T> {
+    #[serde(flatten)]
+    params: &'a T,
+
+    #[serde(flatten)]
+    unknown: &'a UnknownParams,
+}
+
+/// Parameters of an effect together with the unknown ones.
+#[derive(Deserialize)]
+struct WithUnknown<T> {
+    #[serde(flatten)]
+    params: T,
+
+    #[serde(flatten)]
+    unknown: UnknownParams,
+}
+
+/// Reads the tagged and the legacy externally tagged format (as map), or
+/// the compact format (as enum).
+struct EffectVisitor {
+    capture: bool,
+}
+
+impl<'de> Visitor<'de> for EffectVisitor {
+    type Value = (Effect, UnknownParams);
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("an effect with `kind` and `params`")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let Some(key) = map.next_key::<String>()? else {
+            return Err(A::Error::missing_field("kind"));
+        };
+
+        if key != "kind" && key != "params" {
+            let kind = kind_from_name(&key).map_err(A::Error::custom)?;
+            let result = map.next_value_seed(ParamsSeed {
+                kind,
+                capture: self.capture,
+            })?;
+
+            return match map.next_key::<IgnoredAny>()? {
+                None => Ok(result),
+                Some(_) => Err(A::Error::custom("expected a single effect")),
+            };
+        }
+
+        let mut kind = None;
+        let mut buffered = None::<ParamValue>;
+        let mut result = None;
+        let mut key = Some(key);
+        while let Some(name) = key {
+            match name.as_str() {
+                "kind" if kind.is_none() => {
+                    let value = map.next_value_seed(KindSeed)?;
+                    kind = Some(value);
+
+                    if let Some(params) = buffered.take() {
+                        result =
+                            Some(
+                                ParamsSeed {
+                                    kind: value,
+                                    capture: self.capture,
+                                }
+                                .deserialize(ParamDeserializer::<A::Error>::new(params))?,
+                            );
+                    }
+                }
+                "kind" => return Err(A::Error::duplicate_field("kind")),
+                "params" if result.is_some() || buffered.is_some() => {
+                    return Err(A::Error::duplicate_field("params"));
+                }
+                "params" => match kind {
+                    Some(kind) => {
+                        result = Some(map.next_value_seed(ParamsSeed {
+                            kind,
+                            capture: self.capture,
+                        })?);
+                    }
+                    None => buffered = Some(map.next_value()?),
+                },
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+
+            key = map.next_key()?;
+        }
+
+        match (kind, result) {
+            (None, _) => Err(A::Error::missing_field("kind")),
+            (Some(_), None) => Err(A::Error::missing_field("params")),
+            (Some(_), Some(result)) => Ok(result),
+        }
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        let (kind, variant) = data.variant_seed(KindSeed)?;
+
+        variant.newtype_variant_seed(ParamsSeed {
+            kind,
+            capture: self.capture,
+        })
+    }
+}
+
+/// Reads an [`EffectKind`] from its name or its index.
+struct KindSeed;
+
+impl<'de> DeserializeSeed<'de> for KindSeed {
+    type Value = EffectKind;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<EffectKind, D::Error> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl Visitor<'_> for KindSeed {
+    type Value = EffectKind;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("the kind of an effect")
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<EffectKind, E> {
+        usize::try_from(v)
+            .ok()
+            .and_then(|i| EffectKind::ALL.get(i).copied())
+            .ok_or_else(|| E::custom(format_args!("unknown effect index {v}")))
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<EffectKind, E> {
+        kind_from_name(v).map_err(E::custom)
+    }
+}
+
+/// Reads the parameters of an effect of the passed `kind`.
+struct ParamsSeed {
+    kind: EffectKind,
+    capture: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for ParamsSeed {
+    type Value = (Effect, UnknownParams);
+
+    fn deserialize<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        let c = self.capture;
+
+        match self.kind {
+            EffectKind::Static => params(d, c, Effect::Static),
+            EffectKind::Breathing => params(d, c, Effect::Breathing),
+            EffectKind::Rainbow => params(d, c, Effect::Rainbow),
+            EffectKind::Blink => params(d, c, Effect::Blink),
+            EffectKind::ColorChange => params(d, c, Effect::ColorChange),
+            EffectKind::Sequence => params(d, c, Effect::Sequence),
+            EffectKind::Scanner => params(d, c, Effect::Scanner),
+            EffectKind::Laser => params(d, c, Effect::Laser),
+            EffectKind::Wave => params(d, c, Effect::Wave),
+            EffectKind::ColorSequence => params(d, c, Effect::ColorSequence),
+            EffectKind::ColorShift => params(d, c, Effect::ColorShift),
+            EffectKind::BarGraph => params(d, c, Effect::BarGraph),
+            EffectKind::Flame => params(d, c, Effect::Flame),
+            EffectKind::Rain => params(d, c, Effect::Rain),
+            EffectKind::Snow => params(d, c, Effect::Snow),
+            EffectKind::Stardust => params(d, c, Effect::Stardust),
+            EffectKind::ColorSwitch => params(d, c, Effect::ColorSwitch),
+            EffectKind::SwipingRainbow => params(d, c, Effect::SwipingRainbow),
+            EffectKind::SoundFlash => params(d, c, Effect::SoundFlash),
+            EffectKind::SoundBars => params(d, c, Effect::SoundBars),
+            EffectKind::SoundSlider => params(d, c, Effect::SoundSlider),
+            EffectKind::SoundShift => params(d, c, Effect::SoundShift),
+            EffectKind::Ambient => params(d, c, Effect::Ambient),
+            EffectKind::ColorGradient => params(d, c, Effect::ColorGradient),
+        }
+    }
+}
+
+/// Reads the parameters `T` of an effect and wraps them using `variant`.
+///
+/// If `capture` is set, the unknown parameters are returned as well.
+fn params<'de, D, T>(
+    deserializer: D,
+    capture: bool,
+    variant: fn(T) -> Effect,
+) -> Result<(Effect, UnknownParams), D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    if capture {
+        let WithUnknown { params, unknown } = WithUnknown::<T>::deserialize(deserializer)?;
+
+        Ok((variant(params), unknown))
+    } else {
+        Ok((variant(T::deserialize(deserializer)?), UnknownParams::new()))
+    }
+}
+
+fn kind_index(kind: EffectKind) -> u32 {
+    EffectKind::ALL
+        .iter()
+        .position(|x| *x == kind)
+        .and_then(|i| u32::try_from(i).ok())
+        .unwrap_or_default()
+}
+
+fn kind_from_name(name: &str) -> Result<EffectKind, String> {
+    NAMES
+        .iter()
+        .position(|x| *x == name)
+        .map(|i| EffectKind::ALL[i])
+        .ok_or_else(|| format!("unknown effect kind `{name}`"))
+}
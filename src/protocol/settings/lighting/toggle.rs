@@ -0,0 +1,222 @@
+use thiserror::Error;
+
+use super::{Controller, LightingSettings};
+
+/// Group of controllers of the [`LightingSettings`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ControllerGroup {
+    /// Controllers of the LED strip (see
+    /// [`LightingSettings::strip_controllers`]).
+    Strip,
+
+    /// Controllers of the sensor LEDs (see
+    /// [`LightingSettings::sensor_controllers`]).
+    Sensor,
+}
+
+/// Controller that was disabled using [`DisabledControllers::disable`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DisabledController {
+    /// Name the controller was disabled with.
+    pub name: String,
+
+    /// Group the controller belongs to.
+    pub group: ControllerGroup,
+
+    /// Index of the controller inside its group at the time it was disabled.
+    pub index: usize,
+
+    /// Configuration of the controller.
+    pub controller: Controller,
+}
+
+/// Error returned by [`DisabledControllers`].
+#[derive(Debug, Clone, Eq, PartialEq, Error)]
+pub enum ToggleError {
+    /// The group does not contain a controller with the passed index.
+    #[error("No {0:?} controller with index {1}")]
+    UnknownController(ControllerGroup, usize),
+
+    /// A controller with the passed name is already disabled.
+    #[error("A controller named {0:?} is already disabled")]
+    NameInUse(String),
+
+    /// No controller with the passed name is disabled.
+    #[error("No controller named {0:?} is disabled")]
+    UnknownName(String),
+
+    /// All controller slots of the group are in use.
+    #[error("No free {0:?} controller slot")]
+    NoFreeSlot(ControllerGroup),
+}
+
+/// Host-side profile of temporarily disabled controllers.
+///
+/// On the device a disabled controller is an empty effect slot (effect id
+/// `0x00`), which does not store any configuration, so the controller is not
+/// part of the decoded [`LightingSettings`] anymore. Disabling a controller
+/// using [`disable`](Self::disable) removes it from the settings and keeps its
+/// configuration in this profile, [`enable`](Self::enable) restores it. This
+/// way zones can be toggled on and off without losing their settings.
+///
+/// With the `serde` feature enabled the profile can be stored next to the
+/// configuration of the application.
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct DisabledControllers {
+    controllers: Vec<DisabledController>,
+}
+
+impl DisabledControllers {
+    /// Creates a new profile without any disabled controllers.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of disabled controllers.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.controllers.len()
+    }
+
+    /// Returns `true` if no controller is disabled.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.controllers.is_empty()
+    }
+
+    /// Returns an iterator over the disabled controllers.
+    pub fn iter(&self) -> impl Iterator<Item = &DisabledController> {
+        self.controllers.iter()
+    }
+
+    /// Returns the disabled controller with the passed `name`.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&DisabledController> {
+        self.controllers.iter().find(|x| x.name == name)
+    }
+
+    /// Returns `true` if a controller with the passed `name` is disabled.
+    #[must_use]
+    pub fn is_disabled(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Disables the controller at `index` of the passed `group`, and keeps
+    /// its configuration under the passed `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the controller does not exist or the `name` is
+    /// already used by another disabled controller. The `settings` are not
+    /// changed in this case.
+    pub fn disable<S: Into<String>>(
+        &mut self,
+        settings: &mut LightingSettings,
+        group: ControllerGroup,
+        index: usize,
+        name: S,
+    ) -> Result<(), ToggleError> {
+        let name = name.into();
+        if self.is_disabled(&name) {
+            return Err(ToggleError::NameInUse(name));
+        }
+
+        let controller = match group {
+            ControllerGroup::Strip => settings.strip_controllers.pop_at(index),
+            ControllerGroup::Sensor => settings.sensor_controllers.pop_at(index),
+        }
+        .ok_or(ToggleError::UnknownController(group, index))?;
+
+        self.controllers.push(DisabledController {
+            name,
+            group,
+            index,
+            controller,
+        });
+
+        Ok(())
+    }
+
+    /// Re-enables the controller disabled under the passed `name` and
+    /// removes it from the profile.
+    ///
+    /// The controller is inserted at the index it had when it was disabled
+    /// (or at the end of its group, if the group got shorter in the
+    /// meantime).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no controller with the passed `name` is disabled
+    /// or all controller slots of the group are in use. The profile and the
+    /// `settings` are not changed in this case.
+    pub fn enable(
+        &mut self,
+        settings: &mut LightingSettings,
+        name: &str,
+    ) -> Result<(), ToggleError> {
+        let pos = self
+            .controllers
+            .iter()
+            .position(|x| x.name == name)
+            .ok_or_else(|| ToggleError::UnknownName(name.into()))?;
+
+        let DisabledController { group, index, .. } = self.controllers[pos];
+        let is_full = match group {
+            ControllerGroup::Strip => settings.strip_controllers.is_full(),
+            ControllerGroup::Sensor => settings.sensor_controllers.is_full(),
+        };
+        if is_full {
+            return Err(ToggleError::NoFreeSlot(group));
+        }
+
+        let controller = self.controllers.remove(pos).controller;
+        match group {
+            ControllerGroup::Strip => {
+                let index = index.min(settings.strip_controllers.len());
+                settings.strip_controllers.insert(index, controller);
+            }
+            ControllerGroup::Sensor => {
+                let index = index.min(settings.sensor_controllers.len());
+                settings.sensor_controllers.insert(index, controller);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Toggles the controller named `name`: if it is disabled it is enabled
+    /// again (see [`enable`](Self::enable)), otherwise the controller at
+    /// `index` of `group` is disabled (see [`disable`](Self::disable)).
+    ///
+    /// Returns `true` if the controller is enabled afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of [`enable`](Self::enable) or
+    /// [`disable`](Self::disable).
+    pub fn toggle(
+        &mut self,
+        settings: &mut LightingSettings,
+        group: ControllerGroup,
+        index: usize,
+        name: &str,
+    ) -> Result<bool, ToggleError> {
+        if self.is_disabled(name) {
+            self.enable(settings, name)?;
+
+            Ok(true)
+        } else {
+            self.disable(settings, group, index, name)?;
+
+            Ok(false)
+        }
+    }
+}
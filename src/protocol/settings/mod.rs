@@ -3,28 +3,76 @@
 //! This module contains all types and decoding logic related to device
 //! configuration and runtime settings. The `Settings` struct is the top-
 //! level container.
+//!
+//! With the `serde` feature enabled all types of the settings model implement
+//! `Serialize` and `Deserialize`. Field and variant names are taken from the
+//! Rust definitions, wrapped values are serialized as their raw primitive
+//! value in the unit documented at the type (e.g. [`Flow`] in 1/10 l/h), and
+//! are validated against their range when deserialized. [`Color`]s are
+//! serialized as their HSV components.
+//!
+//! Compact (not human readable) formats like `postcard` or `bincode` are
+//! supported as well. In these formats [`Color`]s are serialized as the four
+//! bytes used by the device, so the model does not contain any floating point
+//! values, and enum variants are identified by their index. The order of the
+//! enum variants is therefore part of the format and must not be changed.
+//!
+//! With the `defmt` feature enabled the settings implement `defmt::Format`.
+//! Flags are formatted as their raw bits, the [`LightingSettings`] are
+//! formatted using their `Debug` implementation.
 
+mod access;
 mod alarm;
+mod capture;
+mod current;
+mod diff;
 mod display;
 mod lighting;
+mod reserved;
+mod schema;
 mod sensor;
 mod system;
+mod value;
+
+#[cfg(feature = "config")]
+mod config;
+#[cfg(feature = "defmt")]
+mod format;
+#[cfg(feature = "uom")]
+mod units;
 
 use std::{array::from_fn, ops::BitAnd};
 
 use crate::{
     define_wrapped, impl_ranged,
-    misc::{Decode, Guard, GuardOutput, IoError, Reader},
+    misc::{Decode, FixedSize, Guard, GuardOutput, IoError, Reader},
 };
 
+pub use self::access::Access;
 pub use self::alarm::*;
+pub use self::capture::*;
+#[cfg(feature = "config")]
+pub use self::config::*;
+pub use self::current::*;
+pub use self::diff::*;
 pub use self::display::*;
 pub use self::lighting::*;
+pub use self::reserved::*;
+pub use self::schema::*;
 pub use self::sensor::*;
 pub use self::system::*;
+pub use self::value::*;
 
 /// Settings of a high flow NEXT device
+///
+/// The settings structs are `#[non_exhaustive]`, so fields for new firmware
+/// features can be added without breaking changes. Instances are created by
+/// decoding a [`Frame`](super::Frame) (or from a config file with the
+/// `config` feature) or using the `new` constructors, and their fields can
+/// be read and modified directly.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub struct Settings {
     /// System related settings.
     pub system: SystemSettings,
@@ -42,6 +90,56 @@ pub struct Settings {
     pub lighting: Option<LightingSettings>,
 }
 
+impl Settings {
+    /// Creates new settings from the passed parts, without lighting settings.
+    #[must_use]
+    pub fn new(
+        system: SystemSettings,
+        sensor: SensorSettings,
+        alarms: AlarmSettings,
+        display: DisplaySettings,
+    ) -> Self {
+        Self {
+            system,
+            sensor,
+            alarms,
+            display,
+            lighting: None,
+        }
+    }
+}
+
+impl FixedSize for Settings {
+    const SIZE: usize = 679;
+}
+
+// Ties the layout read by `Settings::decode` to the length of the settings
+// payload, so a refactoring that changes the number of bytes read fails to
+// compile instead of producing checksum mismatches at runtime.
+const _: () = assert!(
+    u16::SIZE
+        + DisplaySettings::SIZE
+        + Option::<CurrentDraw>::SIZE
+        + AquaBusAddress::SIZE
+        + 2 * TempOffset::SIZE
+        + Medium::SIZE
+        + ConnectorType::SIZE
+        + <[FlowCorrection; 10]>::SIZE
+        + <[Flow; 10]>::SIZE
+        + Option::<LightingSettings>::SIZE
+        + StandbyFlags::SIZE
+        + 2
+        + ConductivityOffset::SIZE
+        + 2 * Conductivity::SIZE
+        + 1
+        + PowerFlags::SIZE
+        + PowerDamping::SIZE
+        + AlarmSettings::SIZE
+        + 1
+        == Settings::SIZE,
+    "decoded layout does not match the length of the settings payload"
+);
+
 impl Decode for Settings {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         let _version = reader.read_u16be()?;
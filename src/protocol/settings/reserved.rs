@@ -0,0 +1,182 @@
+use crate::misc::{Decode, FixedSize, GuardOutput, IoError, Reader};
+
+use super::{Settings, SettingsSchema};
+
+/// Raw content of the regions of the settings payload that are skipped when
+/// decoding the [`Settings`].
+///
+/// Some of these regions plausibly contain real data (additional flags, data
+/// of future firmware features), so decoding them separately allows to observe
+/// how they change without modeling them in [`Settings`]. The type is decoded
+/// from the same payload as the settings (the data between op code and
+/// checksum), or from a complete frame using [`ReservedFields::from_frame`].
+///
+/// The fields are named after the position of the region in the payload. The
+/// regions are located using the [`SettingsSchema`], which lists them with the
+/// `reserved.` prefix.
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReservedFields {
+    /// Version of the settings structure (first two bytes of the payload).
+    pub version: u16,
+
+    /// Byte following the [`DisplaySettings::flow_unit`](super::DisplaySettings::flow_unit).
+    pub display_after_flow_unit: u8,
+
+    /// Bytes following the [`DisplaySettings::next_page_interval`](super::DisplaySettings::next_page_interval).
+    pub display_after_page_interval: [u8; 2],
+
+    /// Bytes following the [`DisplaySettings::page_flags`](super::DisplaySettings::page_flags).
+    pub display_after_page_flags: [u8; 4],
+
+    /// Bytes following the [`DisplaySettings::idle_display_brightness`](super::DisplaySettings::idle_display_brightness).
+    pub display_after_brightness: [u8; 4],
+
+    /// Leading byte of each of the [`DisplaySettings::charts`](super::DisplaySettings::charts).
+    pub charts: [u8; 4],
+
+    /// Leading byte of the [`SystemSettings::increased_current_draw`](super::SystemSettings::increased_current_draw).
+    pub current_draw: u8,
+
+    /// Byte following the [`LightingSettings::brightness`](super::LightingSettings::brightness).
+    pub lighting_after_brightness: u8,
+
+    /// Byte following the flags of the lighting settings.
+    pub lighting_after_flags: u8,
+
+    /// Bytes following the [`SystemSettings::standby_flags`](super::SystemSettings::standby_flags).
+    pub after_standby_flags: [u8; 2],
+
+    /// Byte following the [`SensorSettings::water_quality_min`](super::SensorSettings::water_quality_min).
+    pub after_water_quality: u8,
+
+    /// Byte following the [`AlarmSettings::flags`](super::AlarmSettings::flags)
+    /// and the enabled alarms.
+    pub alarm_after_flags: u8,
+
+    /// Last byte of the payload.
+    pub trailing: u8,
+}
+
+impl ReservedFields {
+    /// Decodes the reserved regions from a complete settings frame (op code,
+    /// payload and checksum).
+    ///
+    /// The checksum is not verified, so the reserved regions of corrupted
+    /// frames can be inspected as well.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IoError::InvalidValue`] if the frame is not a settings frame,
+    /// or an I/O error if the frame is truncated.
+    pub fn from_frame(frame: &[u8]) -> Result<Self, IoError> {
+        let mut reader = frame;
+
+        let op_code = reader.read_u8()?;
+        if op_code != 0x03 {
+            return Err(IoError::InvalidValue("OpCode", op_code.into()));
+        }
+
+        Self::decode(&mut reader)
+    }
+
+    /// Returns an iterator over the name and the raw bytes of each region.
+    ///
+    /// The `version` is yielded in big-endian byte order, as it is stored in
+    /// the payload.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, Vec<u8>)> + '_ {
+        [
+            ("version", self.version.to_be_bytes().to_vec()),
+            (
+                "display_after_flow_unit",
+                vec![self.display_after_flow_unit],
+            ),
+            (
+                "display_after_page_interval",
+                self.display_after_page_interval.to_vec(),
+            ),
+            (
+                "display_after_page_flags",
+                self.display_after_page_flags.to_vec(),
+            ),
+            (
+                "display_after_brightness",
+                self.display_after_brightness.to_vec(),
+            ),
+            ("charts", self.charts.to_vec()),
+            ("current_draw", vec![self.current_draw]),
+            (
+                "lighting_after_brightness",
+                vec![self.lighting_after_brightness],
+            ),
+            ("lighting_after_flags", vec![self.lighting_after_flags]),
+            ("after_standby_flags", self.after_standby_flags.to_vec()),
+            ("after_water_quality", vec![self.after_water_quality]),
+            ("alarm_after_flags", vec![self.alarm_after_flags]),
+            ("trailing", vec![self.trailing]),
+        ]
+        .into_iter()
+    }
+
+    /// Returns the names of the regions that differ between `self` and
+    /// `other`.
+    #[must_use]
+    pub fn changed(&self, other: &Self) -> Vec<&'static str> {
+        self.iter()
+            .zip(other.iter())
+            .filter(|((_, a), (_, b))| a != b)
+            .map(|((name, _), _)| name)
+            .collect()
+    }
+
+    fn set(&mut self, name: &str, bytes: &[u8]) {
+        match name {
+            "version" => self.version = u16::from_be_bytes([bytes[0], bytes[1]]),
+            "display_after_flow_unit" => self.display_after_flow_unit = bytes[0],
+            "display_after_page_interval" => {
+                self.display_after_page_interval.copy_from_slice(bytes);
+            }
+            "display_after_page_flags" => self.display_after_page_flags.copy_from_slice(bytes),
+            "display_after_brightness" => self.display_after_brightness.copy_from_slice(bytes),
+            "current_draw" => self.current_draw = bytes[0],
+            "lighting_after_brightness" => self.lighting_after_brightness = bytes[0],
+            "lighting_after_flags" => self.lighting_after_flags = bytes[0],
+            "after_standby_flags" => self.after_standby_flags.copy_from_slice(bytes),
+            "after_water_quality" => self.after_water_quality = bytes[0],
+            "alarm_after_flags" => self.alarm_after_flags = bytes[0],
+            "trailing" => self.trailing = bytes[0],
+            name => {
+                let index = name
+                    .strip_prefix("charts[")
+                    .and_then(|x| x.strip_suffix(']'))
+                    .and_then(|x| x.parse::<usize>().ok());
+
+                match index {
+                    Some(index) => self.charts[index] = bytes[0],
+                    None => unreachable!("Unknown reserved region: {name}"),
+                }
+            }
+        }
+    }
+}
+
+impl FixedSize for ReservedFields {
+    const SIZE: usize = Settings::SIZE;
+}
+
+impl Decode for ReservedFields {
+    fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
+        let mut payload = vec![0; Self::SIZE];
+        reader.read_exact(&mut payload)?;
+
+        let mut this = Self::default();
+        for field in &SettingsSchema::new() {
+            if let Some(name) = field.name.strip_prefix("reserved.") {
+                // The offsets of the schema include the op code.
+                this.set(name, &payload[field.offset - 1..][..field.len]);
+            }
+        }
+
+        Ok(R::guard(|_| this))
+    }
+}
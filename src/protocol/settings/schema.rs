@@ -0,0 +1,290 @@
+use std::ops::RangeInclusive;
+
+use crate::misc::{Decode, FixedSize, Ranged, Wrapped};
+
+use super::{
+    lighting::EFFECT_SIZE, AlarmFlags, AquaBusAddress, Brightness, ChartInterval, ChartSource,
+    Conductivity, ConductivityOffset, ConnectorType, Controller, CurrentDraw, DataSource,
+    DisplayBrightness, DisplayFlags, Flow, FlowCorrection, FlowUnit, Medium, NextPageInterval,
+    OutputSignal, PageFlags, PowerDamping, PowerFlags, StandbyFlags, StartupDelay, TempOffset,
+    Temperature, TemperatureUnit, WaterQuality,
+};
+
+/// Machine readable description of the layout of the settings frame.
+///
+/// Lists every field of the frame (including the op code, the checksum and
+/// the reserved regions of [`ReservedFields`](super::ReservedFields)) with its
+/// offset, length, type and valid range, so external tools (hex editors, web
+/// UIs) can highlight and edit frames without duplicating the layout. The
+/// lengths and ranges are taken from the types used by the decoders, the
+/// valid values of enumerations are determined by probing their decoders.
+///
+/// Fields are named by their path in the [`Settings`](super::Settings) (e.g.
+/// `alarms.water_temperature_limit`), reserved regions are prefixed with
+/// `reserved.`. The controllers are listed by their slot in the frame
+/// (`lighting.controllers[0..6]` for the strip, `lighting.controllers[6..8]`
+/// for the sensor); the effect specific part of a controller depends on the
+/// effect and is described as one opaque field.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SettingsSchema {
+    fields: Vec<FieldSchema>,
+}
+
+impl SettingsSchema {
+    /// Creates the description of the settings frame.
+    #[must_use]
+    pub fn new() -> Self {
+        let mut b = Builder::default();
+
+        b.field::<u8>("op_code", "u8");
+        b.field::<u16>("reserved.version", "u16");
+
+        // Display settings
+        b.enumeration::<TemperatureUnit>("display.temperature_unit", "TemperatureUnit");
+        b.enumeration::<FlowUnit>("display.flow_unit", "FlowUnit");
+        b.reserved("reserved.display_after_flow_unit", 1);
+        b.wrapped::<NextPageInterval>("display.next_page_interval", "NextPageInterval");
+        b.reserved("reserved.display_after_page_interval", 2);
+        b.field::<PageFlags>("display.page_flags", "PageFlags");
+        b.reserved("reserved.display_after_page_flags", 4);
+        b.enumeration::<DisplayBrightness>("display.display_brightness", "DisplayBrightness");
+        b.enumeration::<Option<DisplayBrightness>>(
+            "display.idle_display_brightness",
+            "DisplayBrightness",
+        );
+        b.reserved("reserved.display_after_brightness", 4);
+        b.field::<DisplayFlags>("display.display_flags", "DisplayFlags");
+        for i in 0..4 {
+            b.reserved(format!("reserved.charts[{i}]"), 1);
+            b.enumeration::<ChartSource>(format!("display.charts[{i}].source"), "ChartSource");
+            b.wrapped::<ChartInterval>(format!("display.charts[{i}].interval"), "ChartInterval");
+        }
+
+        // System and sensor settings
+        b.reserved("reserved.current_draw", 1);
+        b.field::<u8>("system.increased_current_draw.flags", "u8");
+        b.wrapped::<CurrentDraw>("system.increased_current_draw", "CurrentDraw");
+        b.enumeration::<AquaBusAddress>("system.aqua_bus_address", "AquaBusAddress");
+        b.wrapped::<TempOffset>("sensor.water_temp_offset", "TempOffset");
+        b.wrapped::<TempOffset>("sensor.external_temp_offset", "TempOffset");
+        b.enumeration::<Medium>("sensor.medium", "Medium");
+        b.enumeration::<ConnectorType>("sensor.connector_type", "ConnectorType");
+        for i in 0..10 {
+            b.wrapped::<FlowCorrection>(
+                format!("sensor.flow_correction[{i}].value"),
+                "FlowCorrection",
+            );
+        }
+        for i in 0..10 {
+            b.wrapped::<Flow>(format!("sensor.flow_correction[{i}].flow"), "Flow");
+        }
+
+        // Lighting settings
+        b.field::<Brightness>("lighting.brightness", "Brightness");
+        b.reserved("reserved.lighting_after_brightness", 1);
+        b.field::<u8>("lighting.flags", "u8");
+        b.reserved("reserved.lighting_after_flags", 1);
+        for i in 0..8 {
+            let name = |field: &str| format!("lighting.controllers[{i}].{field}");
+
+            b.field::<u8>(name("offset"), "u8");
+            b.field::<u8>(name("length"), "u8");
+            b.field::<u8>(name("effect"), "u8");
+            b.field::<u16>(name("flags"), "u16");
+            b.enumeration::<Option<DataSource>>(name("data_source"), "DataSource");
+            b.field::<u8>(name("sensor_attenuation_rising"), "u8");
+            b.field::<u8>(name("sensor_attenuation_falling"), "u8");
+            b.push(name("effect_data"), "EffectData", EFFECT_SIZE, None, None);
+            b.reserved(name("padding"), 1);
+        }
+
+        b.field::<StandbyFlags>("system.standby_flags", "StandbyFlags");
+        b.reserved("reserved.after_standby_flags", 2);
+        b.wrapped::<ConductivityOffset>("sensor.conductivity_offset", "ConductivityOffset");
+        b.wrapped::<Conductivity>("sensor.water_quality_max", "Conductivity");
+        b.wrapped::<Conductivity>("sensor.water_quality_min", "Conductivity");
+        b.reserved("reserved.after_water_quality", 1);
+        b.field::<PowerFlags>("sensor.power_flags", "PowerFlags");
+        b.wrapped::<PowerDamping>("sensor.power_damping", "PowerDamping");
+
+        // Alarm settings
+        b.field::<AlarmFlags>("alarms.flags", "AlarmFlags");
+        b.field::<u8>("alarms.enabled", "u8");
+        b.reserved("reserved.alarm_after_flags", 1);
+        b.wrapped::<StartupDelay>("alarms.startup_delay", "StartupDelay");
+        b.wrapped::<Flow>("alarms.flow_alarm_limit", "Flow");
+        b.wrapped::<Temperature>("alarms.water_temperature_limit", "Temperature");
+        b.wrapped::<Temperature>("alarms.external_temperature_limit", "Temperature");
+        b.wrapped::<WaterQuality>("alarms.water_quality_limit", "WaterQuality");
+        b.enumeration::<OutputSignal>("alarms.output_signal", "OutputSignal");
+
+        b.reserved("reserved.trailing", 1);
+        b.field::<u16>("checksum", "u16");
+
+        Self { fields: b.fields }
+    }
+
+    /// Returns the fields of the frame, ordered by their offset.
+    #[must_use]
+    pub fn fields(&self) -> &[FieldSchema] {
+        &self.fields
+    }
+
+    /// Returns an iterator over the fields of the frame.
+    pub fn iter(&self) -> std::slice::Iter<'_, FieldSchema> {
+        self.fields.iter()
+    }
+
+    /// Returns the field with the passed `name`.
+    #[must_use]
+    pub fn field(&self, name: &str) -> Option<&FieldSchema> {
+        self.fields.iter().find(|x| x.name == name)
+    }
+
+    /// Returns the field that contains the byte at `offset` of the frame.
+    #[must_use]
+    pub fn field_at(&self, offset: usize) -> Option<&FieldSchema> {
+        let index = self.fields.partition_point(|x| x.offset + x.len <= offset);
+
+        self.fields.get(index)
+    }
+}
+
+impl Default for SettingsSchema {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> IntoIterator for &'a SettingsSchema {
+    type Item = &'a FieldSchema;
+    type IntoIter = std::slice::Iter<'a, FieldSchema>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Description of a single field of the [`SettingsSchema`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FieldSchema {
+    /// Path of the field (e.g. `display.charts[2].interval`).
+    pub name: String,
+
+    /// Offset of the field in the frame (including the op code).
+    pub offset: usize,
+
+    /// Number of bytes of the field.
+    pub len: usize,
+
+    /// Name of the type the field is decoded as, or `reserved` for reserved
+    /// regions. Multi byte values are stored in big-endian byte order.
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub ty: &'static str,
+
+    /// Valid range of the raw value, if the type is restricted to a range.
+    pub range: Option<RangeInclusive<i64>>,
+
+    /// Valid raw values, if the type is an enumeration. The values are not
+    /// necessarily contiguous (e.g. the `DataSource` of a controller).
+    pub values: Option<Vec<i64>>,
+}
+
+impl FieldSchema {
+    /// Returns `true` if the field is a reserved region.
+    #[must_use]
+    pub fn is_reserved(&self) -> bool {
+        self.ty == RESERVED
+    }
+}
+
+const RESERVED: &str = "reserved";
+
+// The header fields of a controller listed in `SettingsSchema::new` and the
+// effect specific part must cover the whole controller.
+const _: () = assert!(
+    u8::SIZE
+        + u8::SIZE
+        + u8::SIZE
+        + u16::SIZE
+        + Option::<DataSource>::SIZE
+        + u8::SIZE
+        + u8::SIZE
+        + EFFECT_SIZE
+        + 1
+        == Option::<Controller>::SIZE
+);
+
+#[derive(Default)]
+struct Builder {
+    offset: usize,
+    fields: Vec<FieldSchema>,
+}
+
+impl Builder {
+    fn push(
+        &mut self,
+        name: impl Into<String>,
+        ty: &'static str,
+        len: usize,
+        range: Option<RangeInclusive<i64>>,
+        values: Option<Vec<i64>>,
+    ) {
+        self.fields.push(FieldSchema {
+            name: name.into(),
+            offset: self.offset,
+            len,
+            ty,
+            range,
+            values,
+        });
+
+        self.offset += len;
+    }
+
+    fn field<T: FixedSize>(&mut self, name: impl Into<String>, ty: &'static str) {
+        self.push(name, ty, T::SIZE, None, None);
+    }
+
+    fn enumeration<T: FixedSize + Decode>(&mut self, name: impl Into<String>, ty: &'static str) {
+        let values = (0..1_u32 << (8 * T::SIZE))
+            .filter(|raw| {
+                let bytes = raw.to_be_bytes();
+
+                T::decode(&mut &bytes[bytes.len() - T::SIZE..]).is_ok()
+            })
+            .map(i64::from)
+            .collect::<Vec<_>>();
+        let range = values.first().copied().zip(values.last().copied());
+        let range = range.map(|(min, max)| min..=max);
+
+        self.push(name, ty, T::SIZE, range, Some(values));
+    }
+
+    fn wrapped<W>(&mut self, name: impl Into<String>, ty: &'static str)
+    where
+        W: WrappedRange,
+    {
+        self.push(name, ty, W::SIZE, Some(W::range()), None);
+    }
+
+    fn reserved(&mut self, name: impl Into<String>, len: usize) {
+        self.push(name, RESERVED, len, None, None);
+    }
+}
+
+trait WrappedRange: FixedSize {
+    fn range() -> RangeInclusive<i64>;
+}
+
+impl<T, X> WrappedRange for Wrapped<T, X>
+where
+    T: FixedSize + Ord + Into<i64>,
+    X: Ranged<T>,
+{
+    fn range() -> RangeInclusive<i64> {
+        Self::min_inclusive().into()..=Self::max_inclusive().into()
+    }
+}
@@ -1,12 +1,15 @@
 use bitflags::bitflags;
 
-use crate::misc::{Decode, GuardOutput, IoError, Reader};
-use crate::{define_wrapped, impl_ranged};
+use crate::misc::{Decode, FixedSize, GuardOutput, IoError, Reader};
+use crate::{define_wrapped, impl_percent, impl_ranged};
 
 use super::Flow;
 
 /// Sensor related settings for a high flow NEXT device.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub struct SensorSettings {
     /// Medium that is used as coolant.
     pub medium: Medium,
@@ -39,10 +42,42 @@ pub struct SensorSettings {
     pub power_damping: PowerDamping,
 }
 
+impl SensorSettings {
+    /// Creates new sensor settings for the passed `medium`, `connector_type`,
+    /// `flow_correction` table and water quality range.
+    ///
+    /// The sensor offsets are zero, the power calculation flags are cleared
+    /// and the power is not damped.
+    #[must_use]
+    pub fn new(
+        medium: Medium,
+        connector_type: ConnectorType,
+        flow_correction: [(Flow, FlowCorrection); 10],
+        water_quality_max: Conductivity,
+        water_quality_min: Conductivity,
+    ) -> Self {
+        Self {
+            medium,
+            connector_type,
+            flow_correction,
+            water_temp_offset: TempOffset::from_value(0).unwrap_or_else(|_| unreachable!()),
+            external_temp_offset: TempOffset::from_value(0).unwrap_or_else(|_| unreachable!()),
+            conductivity_offset: ConductivityOffset::from_value(0)
+                .unwrap_or_else(|_| unreachable!()),
+            water_quality_max,
+            water_quality_min,
+            power_flags: PowerFlags::empty(),
+            power_damping: PowerDamping::from_value(0).unwrap_or_else(|_| unreachable!()),
+        }
+    }
+}
+
 /// Medium that is used as coolant.
 ///
 /// Used in [`SensorSettings::medium`].
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Medium {
     /// DP Ultra
     DpUltra,
@@ -51,6 +86,36 @@ pub enum Medium {
     DistilledWater,
 }
 
+impl Medium {
+    /// Returns the specific heat capacity of the medium in joule per kilogram
+    /// and kelvin (J/(kg·K)) at room temperature.
+    ///
+    /// DP Ultra is a water based coolant, so the value is an approximation.
+    #[must_use]
+    pub fn specific_heat(&self) -> f64 {
+        match self {
+            Self::DpUltra => 4_100.0,
+            Self::DistilledWater => 4_182.0,
+        }
+    }
+
+    /// Returns the density of the medium in kilogram per liter (kg/l) at
+    /// room temperature.
+    ///
+    /// DP Ultra is a water based coolant, so the value is an approximation.
+    #[must_use]
+    pub fn density(&self) -> f64 {
+        match self {
+            Self::DpUltra => 1.0,
+            Self::DistilledWater => 0.998,
+        }
+    }
+}
+
+impl FixedSize for Medium {
+    const SIZE: usize = u8::SIZE;
+}
+
 impl Decode for Medium {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         match reader.read_u8()? {
@@ -65,6 +130,8 @@ impl Decode for Medium {
 ///
 /// Used in [`SensorSettings::connector_type`].
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ConnectorType {
     /// Inner diameter > 7mm
     InnerDiameterGt7mm,
@@ -73,6 +140,10 @@ pub enum ConnectorType {
     InnerDiameterLt7mm,
 }
 
+impl FixedSize for ConnectorType {
+    const SIZE: usize = u8::SIZE;
+}
+
 impl Decode for ConnectorType {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         match reader.read_u8()? {
@@ -86,12 +157,17 @@ impl Decode for ConnectorType {
 bitflags! {
     /// Flags to control the power calculation.
     #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct PowerFlags: u8 {
         /// Enable automatic offset compensation in standby.
         const AUTOMATIC_POWER_OFFSET_COMPENSATION = 0x01;
     }
 }
 
+impl FixedSize for PowerFlags {
+    const SIZE: usize = u8::SIZE;
+}
+
 impl Decode for PowerFlags {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         let bits = reader.read_u8()?;
@@ -107,6 +183,7 @@ define_wrapped! {
     pub type FlowCorrection<i16, FlowCorrectionTag>;
 }
 impl_ranged!(FlowCorrection<i16, FlowCorrectionTag>, -5000, 5000);
+impl_percent!(FlowCorrection<i16, FlowCorrectionTag>, 100.0);
 
 define_wrapped! {
     /// Water temperature offset used in [`SensorSettings`].
@@ -1,10 +1,16 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
 use bitflags::bitflags;
 
-use crate::misc::{Decode, Guard, GuardOutput, IoError, Reader};
+use crate::locale::Message;
+use crate::misc::{Decode, FixedSize, Guard, GuardOutput, IoError, RangeError, Reader};
 use crate::{define_wrapped, impl_ranged};
 
 /// System related settings for a high flow NEXT device.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub struct SystemSettings {
     /// Stand-by flags.
     pub standby_flags: StandbyFlags,
@@ -19,6 +25,7 @@ pub struct SystemSettings {
 bitflags! {
     /// Stand-by flags used in [`SystemSettings::standby_flags`].
     #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct StandbyFlags: u8 {
         /// Enter standby if USB is not connected.
         const STANDBY_NO_USB = 0x01;
@@ -43,6 +50,141 @@ bitflags! {
     }
 }
 
+impl StandbyFlags {
+    /// Conditions that put the device into standby.
+    const TRIGGERS: Self = Self::STANDBY_NO_USB
+        .union(Self::STANDBY_ON_SUSPEND)
+        .union(Self::STANDBY_ON_ABUS_LOSS);
+
+    /// Standby when the PC is off or suspended, with display and LEDs turned
+    /// off and alarm detection and volume counter paused.
+    ///
+    /// Use this if the device should be completely dark and silent while the
+    /// PC is not running.
+    #[must_use]
+    pub fn fully_dark() -> Self {
+        Self::STANDBY_NO_USB
+            | Self::STANDBY_ON_SUSPEND
+            | Self::DISPLAY_OFF
+            | Self::LEDS_DISABLED
+            | Self::DISABLE_ALARM_DETECT
+            | Self::DISABLE_VOLUME_COUNTER
+    }
+
+    /// Standby when the PC is off or suspended, with display and LEDs turned
+    /// off but alarm detection and volume counter still running.
+    ///
+    /// Use this if the device should be dark while the PC is not running,
+    /// but still raise alarms (e.g. if the pump keeps running).
+    #[must_use]
+    pub fn keep_alarms_armed() -> Self {
+        Self::STANDBY_NO_USB | Self::STANDBY_ON_SUSPEND | Self::DISPLAY_OFF | Self::LEDS_DISABLED
+    }
+
+    /// Returns a description of the active standby behaviors, one sentence
+    /// per behavior.
+    ///
+    /// If no condition to enter the standby is set, the other flags have no
+    /// effect, which is reported as well.
+    #[must_use]
+    pub fn describe(&self) -> Vec<&'static str> {
+        self.messages().into_iter().map(|x| x.english).collect()
+    }
+
+    /// Returns the localizable [`Message`]s of the description (see
+    /// [`describe`](Self::describe)).
+    #[must_use]
+    pub fn messages(&self) -> Vec<Message> {
+        const DESCRIPTIONS: [(StandbyFlags, &str, &str); 7] = [
+            (
+                StandbyFlags::STANDBY_NO_USB,
+                "standby.no_usb",
+                "Enters standby if USB is not connected.",
+            ),
+            (
+                StandbyFlags::STANDBY_ON_SUSPEND,
+                "standby.on_suspend",
+                "Enters standby if the USB host is suspended.",
+            ),
+            (
+                StandbyFlags::STANDBY_ON_ABUS_LOSS,
+                "standby.on_abus_loss",
+                "Enters standby if the Aqua-Bus connection is lost.",
+            ),
+            (
+                StandbyFlags::DISPLAY_OFF,
+                "standby.display_off",
+                "Turns the display off in standby.",
+            ),
+            (
+                StandbyFlags::LEDS_DISABLED,
+                "standby.leds_disabled",
+                "Turns the LEDs off in standby.",
+            ),
+            (
+                StandbyFlags::DISABLE_ALARM_DETECT,
+                "standby.disable_alarm_detect",
+                "Does not detect alarms in standby.",
+            ),
+            (
+                StandbyFlags::DISABLE_VOLUME_COUNTER,
+                "standby.disable_volume_counter",
+                "Pauses the volume counter in standby.",
+            ),
+        ];
+
+        if !self.intersects(Self::TRIGGERS) {
+            return vec![Message::new(
+                "standby.never",
+                "Never enters standby, the other standby flags have no effect.",
+            )];
+        }
+
+        DESCRIPTIONS
+            .iter()
+            .filter(|(flag, _, _)| self.contains(*flag))
+            .map(|(_, key, english)| Message::new(key, english))
+            .collect()
+    }
+}
+
+impl SystemSettings {
+    /// Creates new system settings with the passed `standby_flags` and
+    /// `aqua_bus_address`, and without increased current draw.
+    #[must_use]
+    pub fn new(standby_flags: StandbyFlags, aqua_bus_address: AquaBusAddress) -> Self {
+        Self {
+            standby_flags,
+            aqua_bus_address,
+            increased_current_draw: None,
+        }
+    }
+
+    /// Creates system settings with the default Aqua-Bus address, no
+    /// increased current draw and a [fully dark](StandbyFlags::fully_dark)
+    /// standby.
+    #[must_use]
+    pub fn fully_dark_standby() -> Self {
+        Self::with_standby(StandbyFlags::fully_dark())
+    }
+
+    /// Creates system settings with the default Aqua-Bus address, no
+    /// increased current draw and a standby that
+    /// [keeps the alarms armed](StandbyFlags::keep_alarms_armed).
+    #[must_use]
+    pub fn keep_alarms_armed() -> Self {
+        Self::with_standby(StandbyFlags::keep_alarms_armed())
+    }
+
+    fn with_standby(standby_flags: StandbyFlags) -> Self {
+        Self::new(standby_flags, AquaBusAddress::default())
+    }
+}
+
+impl FixedSize for StandbyFlags {
+    const SIZE: usize = u8::SIZE;
+}
+
 impl Decode for StandbyFlags {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         let bits = reader.read_u8()?;
@@ -51,13 +193,97 @@ impl Decode for StandbyFlags {
     }
 }
 
-define_wrapped! {
-    /// Aqua-Bus address assigned to a device used in [`SystemSettings::aqua_bus_address`].
-    ///
-    /// Valid value range: 58..61
-    pub type AquaBusAddress<u8, AquaBusAddressTag>;
+/// Aqua-Bus address assigned to a device used in [`SystemSettings::aqua_bus_address`].
+///
+/// A high flow NEXT can use one of four addresses (raw values `58..=61`),
+/// so up to four devices can be connected to the same Aqua-Bus. Every device
+/// on the bus needs a unique address (see
+/// [`DeviceManager::address_conflicts`](crate::device::DeviceManager::address_conflicts)).
+///
+/// The [`Display`] implementation uses the names shown by aquasuite.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "u8", into = "u8")
+)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AquaBusAddress {
+    /// First address (raw value `58`), the factory default.
+    #[default]
+    First,
+    /// Second address (raw value `59`).
+    Second,
+    /// Third address (raw value `60`).
+    Third,
+    /// Fourth address (raw value `61`).
+    Fourth,
+}
+
+impl AquaBusAddress {
+    /// All valid addresses, in ascending order.
+    pub const ALL: [Self; 4] = [Self::First, Self::Second, Self::Third, Self::Fourth];
+
+    /// Returns the raw address used on the bus.
+    #[must_use]
+    pub fn raw(self) -> u8 {
+        match self {
+            Self::First => 58,
+            Self::Second => 59,
+            Self::Third => 60,
+            Self::Fourth => 61,
+        }
+    }
+
+    /// Returns the number of the address (`1..=4`) as shown by aquasuite.
+    #[must_use]
+    pub fn number(self) -> u8 {
+        self.raw() - 57
+    }
+}
+
+impl Display for AquaBusAddress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "high flow NEXT {}", self.number())
+    }
+}
+
+impl From<AquaBusAddress> for u8 {
+    fn from(value: AquaBusAddress) -> Self {
+        value.raw()
+    }
+}
+
+impl TryFrom<u8> for AquaBusAddress {
+    type Error = RangeError<u8>;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            58 => Ok(Self::First),
+            59 => Ok(Self::Second),
+            60 => Ok(Self::Third),
+            61 => Ok(Self::Fourth),
+            val => Err(RangeError {
+                min: 58,
+                max: 61,
+                val,
+            }),
+        }
+    }
+}
+
+impl FixedSize for AquaBusAddress {
+    const SIZE: usize = u8::SIZE;
+}
+
+impl Decode for AquaBusAddress {
+    fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
+        let val = reader.read_u8()?;
+        let ret = R::guard(|_| Self::try_from(val));
+
+        Ok(R::Guard::transpose_result(ret)?)
+    }
 }
-impl_ranged!(AquaBusAddress<u8, AquaBusAddressTag>, 58, 61);
 
 define_wrapped! {
     /// Increased USB current draw used in [`SystemSettings::increased_current_draw`].
@@ -67,6 +293,10 @@ define_wrapped! {
 }
 impl_ranged!(CurrentDraw<u16, CurrentDrawTag>, 500, 2000);
 
+impl FixedSize for Option<CurrentDraw> {
+    const SIZE: usize = 1 + u8::SIZE + CurrentDraw::SIZE;
+}
+
 impl Decode for Option<CurrentDraw> {
     fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
         reader.skip::<1>()?;
@@ -0,0 +1,53 @@
+//! Conversions from the wrapped wire types to [`uom`] quantities.
+//!
+//! The values are interpreted in the metric units of the device (°C and l/h),
+//! independent of the units configured for the display.
+
+use uom::si::electric_current::milliampere;
+use uom::si::electrical_conductivity::siemens_per_centimeter;
+use uom::si::f64::{
+    ElectricCurrent, ElectricalConductivity, Power, TemperatureInterval, ThermodynamicTemperature,
+    VolumeRate,
+};
+use uom::si::power::milliwatt;
+use uom::si::temperature_interval::degree_celsius as interval_degree_celsius;
+use uom::si::thermodynamic_temperature::degree_celsius;
+use uom::si::volume_rate::liter_per_minute;
+
+use super::{Conductivity, CurrentDraw, Flow, PowerDamping, TempOffset, Temperature};
+
+impl From<Flow> for VolumeRate {
+    fn from(value: Flow) -> Self {
+        Self::new::<liter_per_minute>(f64::from(*value) / 10.0 / 60.0)
+    }
+}
+
+impl From<Temperature> for ThermodynamicTemperature {
+    fn from(value: Temperature) -> Self {
+        Self::new::<degree_celsius>(f64::from(*value) / 100.0)
+    }
+}
+
+impl From<TempOffset> for TemperatureInterval {
+    fn from(value: TempOffset) -> Self {
+        Self::new::<interval_degree_celsius>(f64::from(*value) / 100.0)
+    }
+}
+
+impl From<Conductivity> for ElectricalConductivity {
+    fn from(value: Conductivity) -> Self {
+        Self::new::<siemens_per_centimeter>(f64::from(*value) / 1_000_000.0)
+    }
+}
+
+impl From<PowerDamping> for Power {
+    fn from(value: PowerDamping) -> Self {
+        Self::new::<milliwatt>(f64::from(*value))
+    }
+}
+
+impl From<CurrentDraw> for ElectricCurrent {
+    fn from(value: CurrentDraw) -> Self {
+        Self::new::<milliampere>(f64::from(*value))
+    }
+}
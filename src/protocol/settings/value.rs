@@ -0,0 +1,397 @@
+use std::borrow::Cow;
+use std::convert::Infallible;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+
+use bitflags::Flags;
+use thiserror::Error;
+
+use crate::misc::{AnyRangeError, Percent, RangeError};
+
+use super::{
+    AlarmFlags, AquaBusAddress, Brightness, ChartInterval, ChartSource, Color, Conductivity,
+    ConductivityOffset, ConnectorType, CurrentDraw, DataSource, DisplayBrightness, DisplayFlags,
+    EffectDelay, EffectPercent, EffectPercentTag, EffectWidth, Flow, FlowCorrection,
+    FlowCorrectionTag, FlowUnit, Medium, NextPageInterval, OutputSignal, PageFlags, PowerDamping,
+    PowerFlags, RainItems, SoundEffect, SoundEffectSpeed, StandbyFlags, StartupDelay, TempOffset,
+    Temperature, TemperatureUnit, WaterQuality, WaterQualityTag,
+};
+
+/// Dynamic representation of a single value of the [`Settings`](super::Settings).
+///
+/// Every leaf type of the settings model can be converted into a [`Value`]
+/// (using [`From`]) and back (using [`TryFrom`]), so tools can read, write
+/// and compare fields without knowing their concrete type (see
+/// [`Access`](super::Access) and [`Change`](super::Change)).
+///
+/// Numeric values are stored as raw integers in the unit documented at the
+/// variant, so the conversion is lossless. Plain [`Integer`](Self::Integer)s
+/// are accepted by all numeric types.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Value {
+    /// Value of an optional field that is not set.
+    None,
+
+    /// A boolean value.
+    Bool(bool),
+
+    /// An integer without a specific unit (e.g. [`Brightness`] or an `u8`).
+    Integer(i64),
+
+    /// A temperature (or temperature offset) in 1/100 degree (1/100 °C / °F).
+    Temperature(i64),
+
+    /// A flow in 1/10 liter / gallons per hour.
+    Flow(i64),
+
+    /// A percentage in 1/100 percent (1/100 %).
+    Percent(i64),
+
+    /// A color.
+    Color(Color),
+
+    /// The raw bits of a set of flags (e.g. [`PageFlags`]).
+    Flags(u16),
+
+    /// The name of an enum variant (e.g. `DistilledWater` for
+    /// [`Medium::DistilledWater`]).
+    Enum(Cow<'static, str>),
+}
+
+impl Value {
+    /// Returns the value as floating point number in its natural unit
+    /// (degree, l/h, percent), or `None` if the value is not numeric.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn to_f64(&self) -> Option<f64> {
+        match *self {
+            Self::Integer(x) => Some(x as f64),
+            Self::Temperature(x) | Self::Percent(x) => Some(x as f64 / 100.0),
+            Self::Flow(x) => Some(x as f64 / 10.0),
+            Self::Flags(x) => Some(x.into()),
+            Self::None | Self::Bool(_) | Self::Color(_) | Self::Enum(_) => None,
+        }
+    }
+
+    /// Returns the raw numeric value if `self` is an [`Integer`](Self::Integer)
+    /// or a value created by `expected`.
+    fn raw(&self, expected: fn(i64) -> Self) -> Option<i64> {
+        match *self {
+            Self::Integer(x) => Some(x),
+            Self::Temperature(x) | Self::Flow(x) | Self::Percent(x) if expected(x) == *self => {
+                Some(x)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Display for Value {
+    #[allow(clippy::cast_precision_loss)]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::None => f.write_str("None"),
+            Self::Bool(x) => write!(f, "{x}"),
+            Self::Integer(x) => write!(f, "{x}"),
+            Self::Temperature(x) => write!(f, "{:.2} °", *x as f64 / 100.0),
+            Self::Flow(x) => write!(f, "{:.1}", *x as f64 / 10.0),
+            Self::Percent(x) => write!(f, "{:.2} %", *x as f64 / 100.0),
+            Self::Color(x) => write!(f, "{x:?}"),
+            Self::Flags(x) => write!(f, "{x:#06X}"),
+            Self::Enum(x) => f.write_str(x),
+        }
+    }
+}
+
+/// Error returned when reading or writing a field using its path
+/// (see [`Access`](super::Access)).
+#[derive(Debug, Error)]
+pub enum ValueError {
+    /// The path does not name a field of the settings.
+    #[error("Unknown path")]
+    UnknownPath,
+
+    /// The value can not be stored in the field, because it has a different
+    /// type or names an unknown enum variant.
+    #[error("Invalid value: {0}")]
+    InvalidValue(Value),
+
+    /// The value is out of the range of the field.
+    #[error("Range Error: {0}")]
+    RangeError(AnyRangeError),
+}
+
+impl From<Infallible> for ValueError {
+    fn from(error: Infallible) -> Self {
+        match error {}
+    }
+}
+
+impl<T> From<RangeError<T>> for ValueError
+where
+    T: Debug + Display + Send + Sync + 'static,
+{
+    fn from(error: RangeError<T>) -> Self {
+        Self::RangeError(error.into())
+    }
+}
+
+/* Primitives */
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl TryFrom<Value> for bool {
+    type Error = ValueError;
+
+    fn try_from(value: Value) -> Result<Self, ValueError> {
+        match value {
+            Value::Bool(x) => Ok(x),
+            value => Err(ValueError::InvalidValue(value)),
+        }
+    }
+}
+
+impl From<Color> for Value {
+    fn from(value: Color) -> Self {
+        Self::Color(value)
+    }
+}
+
+impl TryFrom<Value> for Color {
+    type Error = ValueError;
+
+    fn try_from(value: Value) -> Result<Self, ValueError> {
+        match value {
+            Value::Color(x) => Ok(x),
+            value => Err(ValueError::InvalidValue(value)),
+        }
+    }
+}
+
+macro_rules! impl_value_integer {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Self {
+                    Self::Integer(value.into())
+                }
+            }
+
+            impl TryFrom<Value> for $ty {
+                type Error = ValueError;
+
+                fn try_from(value: Value) -> Result<Self, ValueError> {
+                    match value {
+                        Value::Integer(x) => x.try_into().map_err(|_| ValueError::InvalidValue(value)),
+                        value => Err(ValueError::InvalidValue(value)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_value_integer!(u8, u16);
+
+/* Wrapped types */
+
+macro_rules! impl_value_wrapped {
+    ($variant:ident: $($ty:ident<$base:ty>),* $(,)?) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Self {
+                    Self::$variant(<$base>::from(value).into())
+                }
+            }
+
+            impl TryFrom<Value> for $ty {
+                type Error = ValueError;
+
+                fn try_from(value: Value) -> Result<Self, ValueError> {
+                    let raw = value
+                        .raw(Value::$variant)
+                        .and_then(|x| <$base>::try_from(x).ok())
+                        .ok_or(ValueError::InvalidValue(value))?;
+
+                    Ok(Self::try_from(raw)?)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_value_percent {
+    ($($ty:ident<$base:ty, $tag:ident>),* $(,)?) => {
+        $(
+            impl From<$ty> for Value {
+                #[allow(clippy::cast_possible_truncation)]
+                fn from(value: $ty) -> Self {
+                    Self::Percent((value.percent() * 100.0).round() as i64)
+                }
+            }
+
+            impl TryFrom<Value> for $ty {
+                type Error = ValueError;
+
+                #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+                fn try_from(value: Value) -> Result<Self, ValueError> {
+                    let raw = value
+                        .raw(Value::Percent)
+                        .map(|x| x as f64 * <$tag as Percent>::SCALE / 100.0)
+                        .filter(|x| x.fract() == 0.0)
+                        .and_then(|x| <$base>::try_from(x as i64).ok())
+                        .ok_or(ValueError::InvalidValue(value))?;
+
+                    Ok(Self::try_from(raw)?)
+                }
+            }
+        )*
+    };
+}
+
+impl_value_wrapped!(Integer:
+    Brightness<u8>,
+    EffectDelay<u16>,
+    EffectWidth<u16>,
+    RainItems<u16>,
+    SoundEffectSpeed<u16>,
+    PowerDamping<u16>,
+    Conductivity<u16>,
+    ConductivityOffset<i16>,
+    StartupDelay<u8>,
+    ChartInterval<u16>,
+    NextPageInterval<u8>,
+    CurrentDraw<u16>,
+);
+impl_value_wrapped!(Temperature: Temperature<u16>, TempOffset<i16>);
+impl_value_wrapped!(Flow: Flow<u16>);
+impl_value_percent!(
+    EffectPercent<u16, EffectPercentTag>,
+    FlowCorrection<i16, FlowCorrectionTag>,
+    WaterQuality<u16, WaterQualityTag>,
+);
+
+/* Flags */
+
+macro_rules! impl_value_flags {
+    ($($ty:ident),* $(,)?) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Self {
+                    Self::Flags(value.bits().into())
+                }
+            }
+
+            impl TryFrom<Value> for $ty {
+                type Error = ValueError;
+
+                fn try_from(value: Value) -> Result<Self, ValueError> {
+                    let bits = match value {
+                        Value::Flags(x) => i64::from(x),
+                        Value::Integer(x) => x,
+                        value => return Err(ValueError::InvalidValue(value)),
+                    };
+
+                    <$ty as Flags>::Bits::try_from(bits)
+                        .map(Self::from_bits_retain)
+                        .map_err(|_| ValueError::InvalidValue(value))
+                }
+            }
+        )*
+    };
+}
+
+impl_value_flags!(
+    StandbyFlags,
+    PowerFlags,
+    AlarmFlags,
+    DisplayFlags,
+    PageFlags
+);
+
+/* Enums */
+
+macro_rules! impl_value_enum {
+    ($($ty:ident { $($variant:ident),* $(,)? })*) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Self {
+                    let name = match value {
+                        $( $ty::$variant => stringify!($variant), )*
+                    };
+
+                    Self::Enum(Cow::Borrowed(name))
+                }
+            }
+
+            impl TryFrom<Value> for $ty {
+                type Error = ValueError;
+
+                fn try_from(value: Value) -> Result<Self, ValueError> {
+                    if let Value::Enum(name) = &value {
+                        match &**name {
+                            $( stringify!($variant) => return Ok(Self::$variant), )*
+                            _ => (),
+                        }
+                    }
+
+                    Err(ValueError::InvalidValue(value))
+                }
+            }
+        )*
+    };
+}
+
+impl_value_enum! {
+    AquaBusAddress { First, Second, Third, Fourth }
+    Medium { DpUltra, DistilledWater }
+    ConnectorType { InnerDiameterGt7mm, InnerDiameterLt7mm }
+    OutputSignal {
+        ConstantSpeed,
+        HighFlowSensor,
+        FanFromFlow,
+        PulseOnAlarm,
+        PermanentOn,
+        PermanentOff,
+    }
+    TemperatureUnit { C, F }
+    FlowUnit { Liter, Gallons }
+    DisplayBrightness { Maximum, Medium, Low }
+    ChartSource {
+        Flow,
+        WaterTemp,
+        ExternalTemp,
+        Conductivity,
+        WaterQuality,
+        PowerConsumption,
+        SystemVoltage,
+    }
+    DataSource {
+        Flow,
+        WaterTemperature,
+        ExternalTemperature,
+        Conductivity,
+        WaterQuality,
+        Power,
+        Sound,
+        SoftwareSensor1,
+        SoftwareSensor2,
+        SoftwareSensor3,
+        SoftwareSensor4,
+        SoftwareSensor5,
+        SoftwareSensor6,
+        SoftwareSensor7,
+        SoftwareSensor8,
+    }
+    SoundEffect {
+        OutwardsFromCenter,
+        InwardsToCenterA,
+        InwardsToCenterB,
+        FromLeft,
+        FromRight,
+        AllLEDs,
+    }
+}
@@ -0,0 +1,364 @@
+//! Small HTTP server exposing the readings and settings as JSON.
+//!
+//! The [`ApiServer`] serves the following endpoints, so dashboards (e.g.
+//! Grafana with a JSON / Infinity data source) can be used without an MQTT
+//! broker or a Prometheus server:
+//!
+//! - `/readings`: latest [`SensorReadings`] as JSON object
+//! - `/history`: all readings of the [`History`] as JSON array, optionally
+//!   limited to the time range `?from=<ms>&to=<ms>` (milliseconds since the
+//!   unix epoch, as passed by Grafana's `${__from}` and `${__to}`)
+//! - `/settings`: cached settings of the device (see [`SettingsCache`])
+//!
+//! Each readings object contains the `timestamp` in milliseconds since the
+//! unix epoch, followed by the values of all available [`Channel`]s and the
+//! external sensors (in `external`).
+//!
+//! The server is backed by a [`ServerState`] that is shared with the code
+//! polling the device. Readings are added using the [`ServerSink`], the
+//! settings using [`ServerState::update_settings`].
+//!
+//! Requests are handled one after another on the thread calling
+//! [`ApiServer::serve`], so a slow client delays the other clients until the
+//! [timeout](ApiServer::with_timeout) elapsed. `serve` only needs a shared
+//! reference, so it can be called from multiple threads to handle requests
+//! in parallel. The head of a request is limited to
+//! [`MAX_REQUEST_SIZE`](ApiServer::MAX_REQUEST_SIZE) bytes.
+//!
+//! Cross-origin requests from browser based dashboards are rejected by the
+//! browser, unless the origin of the dashboard is allowed using
+//! [`ApiServer::with_cors`].
+//!
+//! The `serve` example implements the `highflow serve` command on top of this
+//! module.
+
+use std::io::{BufRead, BufReader, Error as IoError, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, SystemTime};
+
+use serde_json::{Map, Value};
+
+use crate::device::SettingsCache;
+use crate::monitor::{Channel, History, SensorReadings, Sink, Timestamp};
+use crate::protocol::Settings;
+
+/// State that is served by the [`ApiServer`].
+#[derive(Debug, Clone)]
+pub struct ServerState {
+    /// Readings served by `/readings` and `/history`.
+    pub history: History,
+
+    /// Settings served by `/settings`.
+    pub settings: Option<Settings>,
+}
+
+impl ServerState {
+    /// Creates a new state with an empty history of the passed `capacity`.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            history: History::new(capacity),
+            settings: None,
+        }
+    }
+
+    /// Updates the served settings from the passed `cache`.
+    ///
+    /// Settings that were not read yet keep the previously served settings.
+    pub fn update_settings(&mut self, cache: &SettingsCache) {
+        if let Some(settings) = cache.settings() {
+            self.settings = Some(settings.clone());
+        }
+    }
+
+    /// Handles a request with the passed `method` and `target` (path and
+    /// query) and returns the response.
+    #[must_use]
+    pub fn handle(&self, method: &str, target: &str) -> Response {
+        if method != "GET" {
+            return Response::error(405, "Method Not Allowed");
+        }
+
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        match path.trim_end_matches('/') {
+            "" => Response::json(&serde_json::json!({
+                "endpoints": ["/readings", "/history", "/settings"],
+            })),
+            "/readings" => match self.history.latest() {
+                Some(readings) => Response::json(&readings_json(readings)),
+                None => Response::error(404, "No readings available"),
+            },
+            "/history" => {
+                let mut from = None::<SystemTime>;
+                let mut to = None::<SystemTime>;
+
+                for (key, value) in query.split('&').filter_map(|x| x.split_once('=')) {
+                    let bound = match key {
+                        "from" => &mut from,
+                        "to" => &mut to,
+                        _ => continue,
+                    };
+
+                    let Some(time) = value
+                        .parse()
+                        .ok()
+                        .and_then(Timestamp::from_unix_millis)
+                        .map(SystemTime::from)
+                    else {
+                        return Response::error(400, "Invalid time range");
+                    };
+
+                    *bound = Some(time);
+                }
+
+                let readings = self
+                    .history
+                    .iter()
+                    .filter(|x| from.is_none_or(|from| x.captured_at >= from))
+                    .filter(|x| to.is_none_or(|to| x.captured_at < to))
+                    .map(readings_json)
+                    .collect::<Vec<_>>();
+
+                Response::json(&Value::Array(readings))
+            }
+            "/settings" => match &self.settings {
+                Some(settings) => match serde_json::to_value(settings) {
+                    Ok(value) => Response::json(&value),
+                    Err(error) => Response::error(500, &error.to_string()),
+                },
+                None => Response::error(404, "No settings available"),
+            },
+            _ => Response::error(404, "Not Found"),
+        }
+    }
+}
+
+/// Response of the [`ApiServer`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Response {
+    /// HTTP status code.
+    pub status: u16,
+
+    /// JSON body.
+    pub body: String,
+}
+
+impl Response {
+    fn json(value: &Value) -> Self {
+        Self {
+            status: 200,
+            body: value.to_string(),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: serde_json::json!({ "error": message }).to_string(),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            431 => "Request Header Fields Too Large",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// [`Sink`] that adds the published readings to the history of a shared
+/// [`ServerState`].
+#[derive(Debug, Clone)]
+pub struct ServerSink(pub Arc<Mutex<ServerState>>);
+
+impl Sink for ServerSink {
+    fn publish(&mut self, readings: &SensorReadings) -> Result<(), IoError> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .history
+            .push(readings.clone());
+
+        Ok(())
+    }
+}
+
+/// HTTP server exposing a shared [`ServerState`] as JSON.
+///
+/// See the [module documentation](self) for details.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::sync::{Arc, Mutex};
+///
+/// use high_flow_next::server::{ApiServer, ServerSink, ServerState};
+///
+/// let state = Arc::new(Mutex::new(ServerState::new(3600)));
+/// // Publish the readings to the sink, e.g. using a `Publisher` on another thread.
+/// let sink = ServerSink(state.clone());
+///
+/// let server = ApiServer::bind("127.0.0.1:8080", state).unwrap();
+/// server.serve().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct ApiServer {
+    listener: TcpListener,
+    state: Arc<Mutex<ServerState>>,
+    timeout: Duration,
+    cors: Option<String>,
+}
+
+impl ApiServer {
+    /// Maximum size of the request line and the headers of a request in
+    /// bytes. Larger requests are answered with status `431`.
+    pub const MAX_REQUEST_SIZE: u64 = 8 * 1024;
+
+    /// Creates a new server listening on `addr` that serves the passed
+    /// `state`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server could not bind to the address.
+    pub fn bind<A>(addr: A, state: Arc<Mutex<ServerState>>) -> Result<Self, IoError>
+    where
+        A: ToSocketAddrs,
+    {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            state,
+            timeout: Duration::from_secs(5),
+            cors: None,
+        })
+    }
+
+    /// Sets the timeout for reading a request and writing the response
+    /// (default: 5 seconds) and returns the updated server.
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+
+        self
+    }
+
+    /// Allows cross-origin requests from the passed `origin` (e.g.
+    /// `http://localhost:3000` or `*` for any origin) and returns the updated
+    /// server.
+    ///
+    /// Cross-origin requests are not allowed by default.
+    #[must_use]
+    pub fn with_cors<S: Into<String>>(mut self, origin: S) -> Self {
+        self.cors = Some(origin.into());
+
+        self
+    }
+
+    /// Returns the address the server is listening on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address could not be determined.
+    pub fn local_addr(&self) -> Result<SocketAddr, IoError> {
+        self.listener.local_addr()
+    }
+
+    /// Returns the state served by the server.
+    #[must_use]
+    pub fn state(&self) -> &Arc<Mutex<ServerState>> {
+        &self.state
+    }
+
+    /// Handles incoming requests until accepting a connection fails.
+    ///
+    /// Errors of single connections (e.g. clients closing the connection
+    /// early) are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if accepting a connection failed.
+    pub fn serve(&self) -> Result<(), IoError> {
+        loop {
+            let (stream, _) = self.listener.accept()?;
+            let _ = self.handle_connection(stream);
+        }
+    }
+
+    /// Accepts a single connection and handles its request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection could not be accepted, or the
+    /// request could not be read or answered.
+    pub fn serve_one(&self) -> Result<(), IoError> {
+        let (stream, _) = self.listener.accept()?;
+
+        self.handle_connection(stream)
+    }
+
+    fn handle_connection(&self, mut stream: TcpStream) -> Result<(), IoError> {
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        let mut reader = BufReader::new(&stream).take(Self::MAX_REQUEST_SIZE);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 2 {
+            line.clear();
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let response = match (parts.next(), parts.next()) {
+            _ if reader.limit() == 0 => Response::error(431, "Request Too Large"),
+            (Some(method), Some(target)) => self
+                .state
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .handle(method, target),
+            _ => Response::error(400, "Bad Request"),
+        };
+
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n",
+            response.status,
+            response.reason(),
+            response.body.len(),
+        )?;
+        if let Some(origin) = &self.cors {
+            write!(stream, "Access-Control-Allow-Origin: {origin}\r\n")?;
+        }
+        write!(stream, "Connection: close\r\n\r\n{}", response.body)?;
+
+        stream.flush()
+    }
+}
+
+fn readings_json(readings: &SensorReadings) -> Value {
+    let mut object = Map::new();
+    object.insert(
+        "timestamp".into(),
+        Timestamp::from(readings.captured_at).unix_millis().into(),
+    );
+
+    for channel in Channel::ALL {
+        if let Some(value) = readings.value(channel) {
+            object.insert(channel.name().into(), value.into());
+        }
+    }
+
+    let external = readings
+        .external
+        .iter()
+        .map(|(name, value)| (name.clone(), Value::from(*value)))
+        .collect::<Map<_, _>>();
+    object.insert("external".into(), Value::Object(external));
+
+    Value::Object(object)
+}
@@ -0,0 +1,79 @@
+//! Helpers for tests comparing [`Settings`](crate::protocol::settings::Settings).
+//!
+//! Comparing the settings with `assert_eq!` prints the `Debug` output of both
+//! values on mismatch, which is hard to read for a structure of this size.
+//! [`assert_settings_eq!`](crate::assert_settings_eq) only prints the paths
+//! of the fields that differ (see
+//! [`SettingsDiff`](crate::protocol::settings::SettingsDiff)), so a test can
+//! compare the whole decoded settings against the expected value instead of
+//! asserting every field on its own.
+//!
+//! # Example
+//!
+//! ```rust
+//! use high_flow_next::assert_settings_eq;
+//! use high_flow_next::protocol::settings::AlarmSettings;
+//!
+//! fn check(decoded: &AlarmSettings, expected: &AlarmSettings) {
+//!     assert_settings_eq!(*decoded, *expected);
+//!     assert_settings_eq!(
+//!         decoded.water_temperature_limit,
+//!         expected.water_temperature_limit,
+//!         "water temperature limit",
+//!     );
+//! }
+//! ```
+
+use std::fmt::Debug;
+
+use crate::protocol::settings::{Diff, SettingsDiff};
+
+/// Asserts that two values of the settings model are equal.
+///
+/// Works for every type implementing [`Diff`], [`PartialEq`] and [`Debug`]
+/// (e.g. [`Settings`](crate::protocol::settings::Settings), any of its
+/// sections or a single [`Controller`](crate::protocol::settings::Controller)).
+/// On mismatch the panic message lists the changed fields as
+/// `path: left -> right`. An optional format string and arguments are added
+/// to the message, like for `assert_eq!`.
+#[macro_export]
+macro_rules! assert_settings_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        if let Some(diff) = $crate::testing::settings_mismatch(&$left, &$right) {
+            panic!("assertion `left == right` failed\n{diff}");
+        }
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        if let Some(diff) = $crate::testing::settings_mismatch(&$left, &$right) {
+            panic!(
+                "assertion `left == right` failed: {}\n{diff}",
+                format_args!($($arg)+)
+            );
+        }
+    };
+}
+
+/// Returns the field-path diff between `left` and `right`, or `None` if the
+/// values are equal.
+///
+/// Used by [`assert_settings_eq!`](crate::assert_settings_eq). If the values
+/// are not equal but the difference is not covered by [`Diff`], the `Debug`
+/// output of both values is returned instead.
+#[must_use]
+pub fn settings_mismatch<T>(left: &T, right: &T) -> Option<String>
+where
+    T: Diff + PartialEq + Debug,
+{
+    if left == right {
+        return None;
+    }
+
+    let mut diff = SettingsDiff::default();
+    left.diff(right, "", &mut diff);
+
+    if diff.is_empty() {
+        Some(format!(" left: {left:?}\nright: {right:?}"))
+    } else {
+        Some(diff.to_string())
+    }
+}
@@ -0,0 +1,54 @@
+//! JavaScript bindings for the protocol layer.
+//!
+//! Only available with the `wasm` feature. The crate can then be compiled to
+//! WebAssembly (e.g. using `wasm-pack`) to build a browser based configurator
+//! on top of `WebHID`: the raw feature reports are read by the browser and
+//! passed to the functions of this module.
+//!
+//! Decoded values are returned as plain JavaScript objects following the
+//! `serde` representation of the settings model (see
+//! [`settings`](crate::protocol::settings)). Settings can only be decoded,
+//! see the [`device`](crate::device) module.
+
+use wasm_bindgen::prelude::*;
+
+use crate::backup::SettingsArchive;
+use crate::misc::Decode;
+use crate::protocol::{settings::SettingsDiff, Frame, Settings};
+
+/// Decodes the passed raw settings `frame` and returns the settings as
+/// JavaScript object.
+#[wasm_bindgen(js_name = decodeSettings)]
+pub fn decode_settings(frame: &[u8]) -> Result<JsValue, JsError> {
+    let settings = settings(frame)?;
+
+    Ok(serde_wasm_bindgen::to_value(&settings)?)
+}
+
+/// Decodes the passed settings `archive` (see [`SettingsArchive`]) and
+/// returns the raw settings frame stored in it.
+#[wasm_bindgen(js_name = decodeArchive)]
+pub fn decode_archive(archive: &[u8]) -> Result<Vec<u8>, JsError> {
+    let archive = SettingsArchive::decode(&mut &archive[..])?;
+
+    Ok(archive.frame().to_vec())
+}
+
+/// Compares the settings of the two raw frames and returns the changes as
+/// human readable strings.
+#[wasm_bindgen(js_name = diffSettings)]
+pub fn diff_settings(old: &[u8], new: &[u8]) -> Result<Vec<String>, JsError> {
+    let old = settings(old)?;
+    let new = settings(new)?;
+
+    Ok(SettingsDiff::new(&old, &new)
+        .iter()
+        .map(ToString::to_string)
+        .collect())
+}
+
+fn settings(frame: &[u8]) -> Result<Settings, JsError> {
+    match Frame::decode(&mut &frame[..])? {
+        Frame::Settings(settings) => Ok(settings),
+    }
+}
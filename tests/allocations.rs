@@ -0,0 +1,113 @@
+#![allow(missing_docs)]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use high_flow_next::{
+    device::{Device, Transport},
+    misc::{Decode, IoError},
+    protocol::{settings::SettingsSchema, Frame, Settings},
+};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|x| x.set(x.get() + 1));
+
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn decode_without_allocations() {
+    assert_eq!(
+        allocations(|| drop(std::hint::black_box(Vec::<u8>::with_capacity(16)))),
+        1,
+        "counting allocator"
+    );
+
+    for path in [
+        "tests/assets/default.frame",
+        "tests/assets/effects_0.frame",
+        "tests/assets/effects_1.frame",
+        "tests/assets/effects_2.frame",
+    ] {
+        let data = std::fs::read(path).unwrap();
+
+        let count = allocations(|| {
+            let frame = Frame::decode(&mut &data[..]).unwrap();
+            std::hint::black_box(frame);
+        });
+
+        assert_eq!(count, 0, "{path}");
+    }
+}
+
+struct Replay(Vec<u8>);
+
+impl Transport for Replay {
+    fn get_feature_report(&mut self, buffer: &mut [u8]) -> Result<usize, IoError> {
+        buffer[..self.0.len()].copy_from_slice(&self.0);
+
+        Ok(self.0.len())
+    }
+
+    fn send_feature_report(&mut self, _data: &[u8]) -> Result<(), IoError> {
+        Ok(())
+    }
+}
+
+#[test]
+fn poll_without_allocations() {
+    let data = std::fs::read("tests/assets/default.frame").unwrap();
+    let mut device = Device::new(Replay(data.clone()));
+
+    let count = allocations(|| {
+        for _ in 0..100 {
+            let settings = device.read_settings().unwrap();
+            std::hint::black_box(settings);
+        }
+    });
+
+    assert_eq!(count, 0);
+    assert_eq!(device.report(), &data[..]);
+}
+
+#[test]
+fn decode_errors_without_allocations() {
+    let data = std::fs::read("tests/assets/default.frame").unwrap();
+    let schema = SettingsSchema::new();
+    let field = schema.field("sensor.power_damping").unwrap();
+    let invalid = field.range.as_ref().unwrap().end() + 1;
+
+    let offset = field.offset - 1;
+    let mut payload = data[1..data.len() - 2].to_vec();
+    payload[offset..offset + field.len].copy_from_slice(&invalid.to_be_bytes()[8 - field.len..]);
+
+    let count = allocations(|| {
+        let err = Settings::decode(&mut &payload[..]).unwrap_err();
+        assert!(matches!(err, IoError::RangeError(_)));
+        std::hint::black_box(err);
+    });
+
+    assert_eq!(count, 0);
+}
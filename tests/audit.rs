@@ -0,0 +1,110 @@
+#![allow(missing_docs)]
+
+use std::fs::read;
+
+use high_flow_next::{
+    audit::{AuditRecord, AuditedTransport, LogStore},
+    device::{Device, Transport},
+    misc::IoError,
+};
+
+/// Transport that returns the last settings report sent to it.
+struct Emulator {
+    settings: Vec<u8>,
+    sent: usize,
+}
+
+impl Transport for Emulator {
+    fn get_feature_report(&mut self, buffer: &mut [u8]) -> Result<usize, IoError> {
+        buffer[..self.settings.len()].copy_from_slice(&self.settings);
+
+        Ok(self.settings.len())
+    }
+
+    fn send_feature_report(&mut self, data: &[u8]) -> Result<(), IoError> {
+        self.sent += 1;
+        if data[0] == 0x03 {
+            self.settings = data.to_vec();
+        }
+
+        Ok(())
+    }
+}
+
+fn emulator() -> Emulator {
+    Emulator {
+        settings: read("tests/assets/default.frame").unwrap(),
+        sent: 0,
+    }
+}
+
+#[test]
+fn records_settings_writes() {
+    let effects = read("tests/assets/effects_0.frame").unwrap();
+
+    let transport = AuditedTransport::new(emulator(), Vec::<AuditRecord>::new());
+    let mut device = Device::new(transport);
+
+    device.write_report(&[0x02, 0x00]).unwrap();
+    assert!(device.transport().store().is_empty());
+
+    device.transport_mut().set_source("alice");
+    device.write_report(&effects).unwrap();
+    device.transport_mut().set_source("bob");
+    device.write_report(&effects).unwrap();
+
+    let (emulator, records) = device.into_inner().into_inner();
+    assert_eq!(emulator.sent, 3);
+    assert_eq!(records.len(), 2);
+
+    assert_eq!(records[0].source, "alice");
+    assert!(!records[0].diff.is_empty());
+    assert!(records[0]
+        .diff
+        .iter()
+        .any(|change| change.path.starts_with("lighting")));
+
+    assert_eq!(records[1].source, "bob");
+    assert!(records[1].diff.is_empty());
+    assert!(records[1]
+        .to_string()
+        .ends_with("settings written by bob (unchanged)"));
+}
+
+#[test]
+fn rejects_invalid_settings() {
+    let mut report = read("tests/assets/effects_0.frame").unwrap();
+    let last = report.len() - 1;
+    report[last] ^= 0xFF;
+
+    let transport = AuditedTransport::new(emulator(), LogStore::new(Vec::new()));
+    let mut device = Device::new(transport);
+
+    assert!(matches!(
+        device.write_report(&report),
+        Err(IoError::ChecksumMismatch)
+    ));
+
+    let (emulator, log) = device.into_inner().into_inner();
+    assert_eq!(emulator.sent, 0);
+    assert!(log.into_inner().is_empty());
+}
+
+#[test]
+fn log_store() {
+    let effects = read("tests/assets/effects_0.frame").unwrap();
+
+    let transport = AuditedTransport::new(emulator(), LogStore::new(Vec::new()));
+    let mut device = Device::new(transport);
+    device.write_report(&effects).unwrap();
+
+    let (_, log) = device.into_inner().into_inner();
+    let log = String::from_utf8(log.into_inner()).unwrap();
+    let mut lines = log.lines();
+
+    assert!(lines
+        .next()
+        .unwrap()
+        .ends_with("settings written by unknown"));
+    assert!(lines.all(|line| line.starts_with("    ")));
+}
@@ -0,0 +1,80 @@
+#![allow(missing_docs)]
+
+use std::fs::read;
+
+use high_flow_next::{
+    backup::SettingsArchive,
+    misc::{checksum, Decode, IoError},
+};
+
+#[test]
+fn round_trip() {
+    let frame = read("tests/assets/default.frame").unwrap();
+    let archive = SettingsArchive::new(0x0102, frame.clone()).unwrap();
+
+    let mut buffer = Vec::new();
+    archive.write(&mut buffer).unwrap();
+
+    let restored = SettingsArchive::decode(&mut &buffer[..]).unwrap();
+    assert_eq!(restored, archive);
+    assert_eq!(restored.firmware_version(), 0x0102);
+    assert_eq!(restored.frame(), &frame[..]);
+    assert_eq!(restored.restore(0x0102, false).unwrap(), &frame[..]);
+    assert_eq!(restored.json(), None);
+
+    let archive = archive.with_json(r#"{ "sensor": {} }"#.into());
+    let mut buffer = Vec::new();
+    archive.write(&mut buffer).unwrap();
+
+    let restored = SettingsArchive::decode(&mut &buffer[..]).unwrap();
+    assert_eq!(restored, archive);
+    assert_eq!(restored.json(), Some(r#"{ "sensor": {} }"#));
+}
+
+#[test]
+fn version_1() {
+    let frame = read("tests/assets/default.frame").unwrap();
+    let len = u16::try_from(frame.len()).unwrap();
+
+    let mut buffer = b"HFNB".to_vec();
+    buffer.extend_from_slice(&1_u16.to_be_bytes());
+    buffer.extend_from_slice(&0x0102_u16.to_be_bytes());
+    buffer.extend_from_slice(&len.to_be_bytes());
+    buffer.extend_from_slice(&frame);
+    buffer.extend_from_slice(&checksum(&buffer).to_be_bytes());
+
+    let archive = SettingsArchive::decode(&mut &buffer[..]).unwrap();
+    assert_eq!(archive.firmware_version(), 0x0102);
+    assert_eq!(archive.frame(), &frame[..]);
+    assert_eq!(archive.json(), None);
+}
+
+#[test]
+fn firmware_mismatch() {
+    let frame = read("tests/assets/default.frame").unwrap();
+    let archive = SettingsArchive::new(0x0102, frame.clone()).unwrap();
+
+    assert!(matches!(
+        archive.restore(0x0103, false),
+        Err(IoError::FirmwareMismatch {
+            expected: 0x0102,
+            actual: 0x0103
+        })
+    ));
+    assert_eq!(archive.restore(0x0103, true).unwrap(), &frame[..]);
+}
+
+#[test]
+fn corrupted() {
+    let frame = read("tests/assets/default.frame").unwrap();
+    let archive = SettingsArchive::new(0x0102, frame).unwrap();
+
+    let mut buffer = Vec::new();
+    archive.write(&mut buffer).unwrap();
+    buffer[7] ^= 0xFF;
+
+    assert!(matches!(
+        SettingsArchive::decode(&mut &buffer[..]),
+        Err(IoError::ChecksumMismatch)
+    ));
+}
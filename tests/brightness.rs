@@ -0,0 +1,108 @@
+#![allow(missing_docs)]
+
+use std::fs::read;
+use std::time::{Duration, Instant};
+
+use high_flow_next::{
+    misc::Decode,
+    monitor::{BrightnessLevel, BrightnessScheduler},
+    protocol::{
+        settings::{Brightness, DisplayBrightness, Settings},
+        Frame,
+    },
+};
+
+const HOUR: u64 = 60 * 60;
+
+fn settings() -> Settings {
+    let data = read("tests/assets/default.frame").unwrap();
+    let Frame::Settings(settings) = Frame::decode(&mut &data[..]).unwrap();
+
+    settings
+}
+
+fn day() -> BrightnessLevel {
+    BrightnessLevel::new(DisplayBrightness::Maximum, Some(DisplayBrightness::Medium))
+}
+
+fn night() -> BrightnessLevel {
+    BrightnessLevel::new(DisplayBrightness::Low, None)
+}
+
+#[test]
+fn time_of_day() {
+    let scheduler = BrightnessScheduler::new()
+        .at(Duration::from_secs(22 * HOUR), night())
+        .at(Duration::from_secs(7 * HOUR), day());
+
+    let target = |hour| scheduler.target(Duration::from_secs(hour * HOUR), None);
+
+    assert_eq!(target(3), Some(night()));
+    assert_eq!(target(7), Some(day()));
+    assert_eq!(target(12), Some(day()));
+    assert_eq!(target(23), Some(night()));
+    assert_eq!(target(24 + 12), Some(day()));
+
+    assert_eq!(
+        BrightnessScheduler::new().target(Duration::ZERO, None),
+        None
+    );
+}
+
+#[test]
+fn ambient_with_hysteresis() {
+    let mut scheduler = BrightnessScheduler::new()
+        .at(Duration::ZERO, day())
+        .above(0.0, night())
+        .above(100.0, day())
+        .hysteresis(10.0)
+        .min_interval(Duration::ZERO);
+
+    let mut settings = settings();
+    let now = Instant::now();
+    let mut update = |ambient| scheduler.update(&mut settings, now, Duration::ZERO, ambient);
+
+    assert!(update(Some(50.0)));
+    assert!(!update(Some(105.0)));
+    assert!(update(Some(115.0)));
+    assert!(!update(Some(95.0)));
+    assert!(update(Some(85.0)));
+    assert!(update(None));
+}
+
+#[test]
+fn minimal_writes() {
+    let mut scheduler = BrightnessScheduler::new()
+        .at(Duration::from_secs(7 * HOUR), day())
+        .at(
+            Duration::from_secs(22 * HOUR),
+            night().leds(Brightness::from_value(50).unwrap()),
+        );
+
+    let mut settings = settings();
+    settings.display.display_brightness = DisplayBrightness::Maximum;
+    settings.display.idle_display_brightness = Some(DisplayBrightness::Medium);
+
+    let now = Instant::now();
+    let noon = Duration::from_secs(12 * HOUR);
+    let midnight = Duration::ZERO;
+
+    assert!(!scheduler.update(&mut settings, now, noon, None));
+    assert!(scheduler.update(&mut settings, now, midnight, None));
+    assert_eq!(settings.display.display_brightness, DisplayBrightness::Low);
+    assert_eq!(settings.display.idle_display_brightness, None);
+    if let Some(lighting) = &settings.lighting {
+        assert_eq!(*lighting.brightness, 50);
+    }
+
+    let later = now + Duration::from_mins(1);
+    assert!(!scheduler.update(&mut settings, later, noon, None));
+    assert_eq!(settings.display.display_brightness, DisplayBrightness::Low);
+
+    let later = now + BrightnessScheduler::DEFAULT_MIN_INTERVAL;
+    assert!(scheduler.update(&mut settings, later, noon, None));
+    assert_eq!(
+        settings.display.display_brightness,
+        DisplayBrightness::Maximum
+    );
+}
@@ -0,0 +1,62 @@
+#![allow(missing_docs)]
+#![cfg(feature = "bundle")]
+
+use std::fs::read;
+
+use high_flow_next::bundle::{SupportBundle, REDACTED};
+
+fn file<'a>(files: &'a [(&'static str, Vec<u8>)], name: &str) -> Option<&'a [u8]> {
+    files.iter().find(|x| x.0 == name).map(|x| &x.1[..])
+}
+
+#[test]
+fn files() {
+    let frame = read("tests/assets/default.frame").unwrap();
+    let bundle = SupportBundle::new(frame.clone())
+        .firmware_version(0x0102)
+        .serial_number("ABC-123")
+        .path("/dev/hidraw3");
+
+    let files = bundle.files().unwrap();
+    let info = String::from_utf8(file(&files, "info.txt").unwrap().to_vec()).unwrap();
+    assert!(info.contains("firmware version: 0x0102"));
+    assert!(info.contains(&format!("serial number: {REDACTED}")));
+    assert!(!info.contains("ABC-123"));
+    assert!(!info.contains("hidraw"));
+
+    assert_eq!(file(&files, "settings.frame").unwrap(), &frame[..]);
+    let json: serde_json::Value =
+        serde_json::from_slice(file(&files, "settings.json").unwrap()).unwrap();
+    assert!(json.get("alarms").is_some());
+    assert!(file(&files, "readings.csv").unwrap().is_empty());
+
+    let files = bundle.redact(false).files().unwrap();
+    let info = String::from_utf8(file(&files, "info.txt").unwrap().to_vec()).unwrap();
+    assert!(info.contains("serial number: ABC-123"));
+}
+
+#[test]
+fn invalid_frame() {
+    let mut frame = read("tests/assets/default.frame").unwrap();
+    let last = frame.len() - 1;
+    frame[last] ^= 0xFF;
+
+    let files = SupportBundle::new(frame).files().unwrap();
+    assert!(file(&files, "settings.json").is_none());
+    assert_eq!(
+        file(&files, "settings.error.txt").unwrap(),
+        b"Checksum does not match!"
+    );
+}
+
+#[test]
+fn archive() {
+    let frame = read("tests/assets/default.frame").unwrap();
+
+    let mut buffer = Vec::new();
+    SupportBundle::new(frame).write(&mut buffer).unwrap();
+
+    assert_eq!(buffer.len() % 512, 0);
+    assert_eq!(&buffer[..8], b"info.txt");
+    assert_eq!(&buffer[257..262], b"ustar");
+}
@@ -0,0 +1,80 @@
+#![allow(missing_docs)]
+
+use std::fs::read;
+
+use high_flow_next::{
+    misc::{checksum, Decode},
+    protocol::{
+        settings::{ByteRange, CaptureDiff, SettingsDiff},
+        Frame,
+    },
+};
+
+fn update_checksum(frame: &mut [u8]) {
+    let len = frame.len();
+    let crc = checksum(&frame[1..len - 2]);
+
+    frame[len - 2..].copy_from_slice(&crc.to_be_bytes());
+}
+
+#[test]
+fn known_fields() {
+    let old = read("tests/assets/effects_0.frame").unwrap();
+    let new = read("tests/assets/effects_1.frame").unwrap();
+    let diff = CaptureDiff::new(&old, &new).unwrap();
+
+    let Frame::Settings(old_settings) = Frame::decode(&mut &old[..]).unwrap();
+    let Frame::Settings(new_settings) = Frame::decode(&mut &new[..]).unwrap();
+
+    assert!(!diff.is_empty());
+    assert_eq!(
+        diff.settings(),
+        &SettingsDiff::new(&old_settings, &new_settings)
+    );
+
+    // Not all bytes of the controller configuration are part of the settings
+    // model yet, but the checksum is never reported.
+    assert!(diff
+        .unknown()
+        .iter()
+        .all(|x| x.range.start >= 1 && x.range.end <= old.len() - 2));
+}
+
+#[test]
+fn unknown_bytes() {
+    let old = read("tests/assets/default.frame").unwrap();
+
+    let mut new = old.clone();
+    let offset = new
+        .windows(4)
+        .position(|x| x == [0x02, 0xBC, 0x02, 0x58])
+        .unwrap();
+    new[offset + 1] = 0xBD;
+    new[offset + 2] = 0x03;
+    update_checksum(&mut new);
+
+    let diff = CaptureDiff::new(&old, &new).unwrap();
+
+    assert!(diff.settings().is_empty());
+    assert_eq!(
+        diff.unknown(),
+        [ByteRange {
+            range: offset + 1..offset + 3,
+            old: vec![0xBC, 0x02],
+            new: vec![0xBD, 0x03],
+        }]
+    );
+    assert_eq!(
+        diff.to_string(),
+        format!("{:#06X}..{:#06X}: BC 02 -> BD 03\n", offset + 1, offset + 3)
+    );
+}
+
+#[test]
+fn equal() {
+    let data = read("tests/assets/default.frame").unwrap();
+    let diff = CaptureDiff::new(&data, &data).unwrap();
+
+    assert!(diff.is_empty());
+    assert_eq!(diff.to_string(), "");
+}
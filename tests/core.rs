@@ -0,0 +1,79 @@
+#![allow(missing_docs)]
+
+use high_flow_next::core::{
+    decode_frame, decode_frames, read_frame, Decode, FrameBuilder, FrameSet, GuardOutput, IoError,
+    Reader,
+};
+
+/// Frames of a hypothetical sibling device.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum PumpFrame {
+    Speed(u16),
+    Mode(u8),
+}
+
+impl FrameSet for PumpFrame {
+    fn frame_size(op_code: u8) -> Option<usize> {
+        match op_code {
+            0x01 => Some(1 + 2 + 2),
+            0x02 => Some(1 + 1 + 2),
+            _ => None,
+        }
+    }
+
+    fn decode_payload<R: Reader>(
+        op_code: u8,
+        reader: &mut R,
+    ) -> Result<GuardOutput<R, Self>, IoError> {
+        match op_code {
+            0x01 => {
+                let speed = reader.read_u16be()?;
+
+                Ok(R::guard(|_| Self::Speed(speed)))
+            }
+            0x02 => {
+                let mode = reader.read_u8()?;
+
+                Ok(R::guard(|_| Self::Mode(mode)))
+            }
+            op_code => Err(IoError::InvalidValue("OpCode", op_code.into())),
+        }
+    }
+}
+
+impl Decode for PumpFrame {
+    fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
+        read_frame(reader)
+    }
+}
+
+#[test]
+fn sibling_device() {
+    let data = [
+        FrameBuilder::new(0x01).u16be(2400).build(),
+        FrameBuilder::new(0x02).u8(3).build(),
+    ]
+    .concat();
+
+    assert_eq!(
+        decode_frames::<PumpFrame>(&data).unwrap(),
+        [PumpFrame::Speed(2400), PumpFrame::Mode(3)]
+    );
+    assert_eq!(
+        PumpFrame::decode(&mut &data[..]).unwrap(),
+        PumpFrame::Speed(2400)
+    );
+
+    let mut reader = &data[5..];
+    assert_eq!(
+        decode_frame::<PumpFrame>(&mut reader).unwrap(),
+        PumpFrame::Mode(3)
+    );
+    assert!(reader.is_empty());
+
+    let corrupted = FrameBuilder::new(0x02).u8(3).crc(0).build();
+    assert!(decode_frames::<PumpFrame>(&corrupted)
+        .unwrap_err()
+        .is_checksum_mismatch());
+    assert!(decode_frames::<PumpFrame>(&[0x07, 0x00, 0x00]).is_err());
+}
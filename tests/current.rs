@@ -0,0 +1,90 @@
+#![allow(missing_docs, clippy::unreadable_literal)]
+
+use std::fs::read;
+
+use high_flow_next::{
+    misc::Decode,
+    protocol::{
+        settings::{
+            Brightness, Color, Controller, CurrentDraw, CurrentEstimate, CurrentWarning, Effect,
+            EffectStatic, LightingSettings, DEVICE_CURRENT,
+        },
+        Frame,
+    },
+};
+
+fn lighting(brightness: u8, color: u32, leds: &[(u8, u8)]) -> LightingSettings {
+    let mut lighting = LightingSettings::new(Brightness::from_value(brightness).unwrap());
+    lighting.strip_controllers = leds
+        .iter()
+        .map(|&(offset, length)| Controller {
+            offset,
+            length,
+            effect: Effect::Static(EffectStatic {
+                color: Color::from_rgb_hex(color),
+                source_control_brightness: None,
+                source_control_saturation: None,
+            }),
+            data_source: None,
+            sensor_attenuation_rising: 0,
+            sensor_attenuation_falling: 0,
+        })
+        .collect();
+
+    lighting
+}
+
+#[test]
+fn static_colors() {
+    let estimate = lighting(255, 0xFF0000, &[(0, 10), (5, 10)]).estimate_current();
+    assert_eq!(
+        estimate,
+        CurrentEstimate {
+            leds: 15,
+            typical: 100 + 15 * 20,
+            worst_case: 100 + 15 * 60,
+        }
+    );
+    assert_eq!(estimate.suggested_current_draw(), None);
+    assert_eq!(estimate.check(None), None);
+
+    let estimate = lighting(255, 0xFFFFFF, &[(0, 20)]).estimate_current();
+    assert_eq!(estimate.typical, 1300);
+    assert_eq!(estimate.suggested_current_draw().map(|x| *x), Some(1300));
+    assert_eq!(
+        estimate.check(None),
+        Some(CurrentWarning::InsufficientCurrentDraw {
+            required: 1300,
+            available: 500,
+        })
+    );
+    assert_eq!(
+        estimate.check(Some(CurrentDraw::from_value(1500).unwrap())),
+        None
+    );
+
+    let estimate = lighting(128, 0xFFFFFF, &[(0, 20)]).estimate_current();
+    assert_eq!(estimate.typical, 100 + 603);
+
+    let estimate = lighting(255, 0xFFFFFF, &[(0, 90)]).estimate_current();
+    assert_eq!(estimate.suggested_current_draw().map(|x| *x), Some(2000));
+    assert_eq!(
+        estimate.check(None),
+        Some(CurrentWarning::ExceedsMaximum { required: 5500 })
+    );
+}
+
+#[test]
+fn settings() {
+    let data = read("tests/assets/default.frame").unwrap();
+    let Frame::Settings(settings) = Frame::decode(&mut &data[..]).unwrap();
+
+    let estimate = settings.estimate_current();
+    assert_eq!(estimate.leds, 90);
+    assert!(estimate.typical > u32::from(DEVICE_CURRENT));
+    assert!(estimate.typical <= estimate.worst_case);
+    assert_eq!(
+        settings.check_current_draw(),
+        estimate.check(settings.system.increased_current_draw)
+    );
+}
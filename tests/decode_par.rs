@@ -0,0 +1,53 @@
+#![allow(missing_docs)]
+#![cfg(feature = "rayon")]
+
+use high_flow_next::{
+    misc::IoError,
+    protocol::{decode_frames, decode_frames_par, Frame},
+};
+
+const FRAME: &[u8] = include_bytes!("assets/default.frame");
+
+#[test]
+fn same_as_sequential() {
+    let paths = [
+        "tests/assets/default.frame",
+        "tests/assets/effects_0.frame",
+        "tests/assets/effects_1.frame",
+        "tests/assets/effects_2.frame",
+    ];
+    let data = paths
+        .map(|path| std::fs::read(path).unwrap())
+        .concat()
+        .repeat(2_500);
+
+    let expected = decode_frames(&data).unwrap();
+    let actual = decode_frames_par(&data)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(expected.len(), 10_000);
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn frame_size() {
+    assert_eq!(Frame::size(0x03), Some(FRAME.len()));
+    assert_eq!(Frame::size(0xFF), None);
+}
+
+#[test]
+fn errors_in_order() {
+    let mut data = FRAME.repeat(3);
+    let last = 2 * FRAME.len() - 1;
+    data[last] ^= 0xFF;
+    data.extend_from_slice(&FRAME[..10]);
+
+    let results = decode_frames_par(&data).collect::<Vec<_>>();
+
+    assert_eq!(results.len(), 4);
+    assert!(results[0].is_ok());
+    assert!(matches!(results[1], Err(IoError::ChecksumMismatch)));
+    assert!(results[2].is_ok());
+    assert!(results[3].is_err());
+}
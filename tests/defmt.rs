@@ -0,0 +1,23 @@
+#![allow(missing_docs)]
+#![cfg(feature = "defmt")]
+
+use high_flow_next::{
+    misc::IoError,
+    monitor::{Channel, SensorReadings},
+    protocol::{
+        settings::{Flow, Settings},
+        Frame,
+    },
+};
+
+fn assert_format<T: defmt::Format>() {}
+
+#[test]
+fn format_impls() {
+    assert_format::<IoError>();
+    assert_format::<Channel>();
+    assert_format::<SensorReadings>();
+    assert_format::<Flow>();
+    assert_format::<Settings>();
+    assert_format::<Frame>();
+}
@@ -0,0 +1,328 @@
+#![allow(missing_docs)]
+
+use std::time::{Duration, Instant, SystemTime};
+
+use high_flow_next::{
+    device::{
+        AddressConflict, Device, DeviceHealth, DeviceManager, SettingsCache, SettingsEvent,
+        Transport,
+    },
+    misc::{Decode, IoError},
+    monitor::WatchdogEvent,
+    protocol::{settings::AquaBusAddress, Frame},
+};
+
+struct Replay(Vec<u8>);
+
+impl Transport for Replay {
+    fn get_feature_report(&mut self, buffer: &mut [u8]) -> Result<usize, IoError> {
+        buffer[..self.0.len()].copy_from_slice(&self.0);
+
+        Ok(self.0.len())
+    }
+
+    fn send_feature_report(&mut self, _data: &[u8]) -> Result<(), IoError> {
+        Ok(())
+    }
+}
+
+#[test]
+fn address() {
+    for (raw, address) in (58..=61).zip(AquaBusAddress::ALL) {
+        assert_eq!(AquaBusAddress::try_from(raw).unwrap(), address);
+        assert_eq!(u8::from(address), raw);
+    }
+
+    assert!(AquaBusAddress::try_from(57).is_err());
+    assert!(AquaBusAddress::try_from(62).is_err());
+
+    assert_eq!(AquaBusAddress::default(), AquaBusAddress::First);
+    assert_eq!(AquaBusAddress::Third.to_string(), "high flow NEXT 3");
+}
+
+#[test]
+fn find_conflicts() {
+    use AquaBusAddress::{First, Fourth, Second};
+
+    assert!(AddressConflict::find([First, Second, Fourth]).is_empty());
+
+    let conflicts = AddressConflict::find([Second, First, Second, First, Second]);
+    assert_eq!(
+        conflicts,
+        [
+            AddressConflict {
+                address: First,
+                devices: vec![1, 3],
+            },
+            AddressConflict {
+                address: Second,
+                devices: vec![0, 2, 4],
+            },
+        ]
+    );
+    assert_eq!(
+        conflicts[1].to_string(),
+        "Aqua-Bus address high flow NEXT 2 is used by devices 0, 2, 4"
+    );
+}
+
+#[test]
+fn manager() {
+    let frame = std::fs::read("tests/assets/default.frame").unwrap();
+    let devices = (0..2).map(|_| Device::new(Replay(frame.clone()))).collect();
+
+    let mut manager = DeviceManager::new(devices);
+    assert_eq!(manager.devices().len(), 2);
+    assert_eq!(
+        manager.aqua_bus_addresses().unwrap(),
+        [AquaBusAddress::First, AquaBusAddress::First]
+    );
+    assert_eq!(
+        manager.address_conflicts().unwrap(),
+        [AddressConflict {
+            address: AquaBusAddress::First,
+            devices: vec![0, 1],
+        }]
+    );
+}
+
+#[test]
+fn dry_run() {
+    let default = std::fs::read("tests/assets/default.frame").unwrap();
+    let mut device = Device::new(Replay(default));
+
+    let mut settings = device.read_settings().unwrap();
+    let dry_run = device.write_settings_dry_run(&settings).unwrap();
+    assert!(dry_run.is_noop());
+    assert_eq!(
+        dry_run.to_string(),
+        "No changes\nWarning: LEDs require about 2051 mA, which exceeds the maximum of 2000 mA\n"
+    );
+
+    settings.system.aqua_bus_address = AquaBusAddress::Fourth;
+    let dry_run = device.write_settings_dry_run(&settings).unwrap();
+    assert!(!dry_run.is_noop());
+    assert_eq!(dry_run.diff.changes()[0].path, "system.aqua_bus_address");
+}
+
+/// Returns the passed frames one after another, repeating the last one.
+struct Sequence(Vec<Vec<u8>>);
+
+impl Transport for Sequence {
+    fn get_feature_report(&mut self, buffer: &mut [u8]) -> Result<usize, IoError> {
+        let frame = if self.0.len() > 1 {
+            self.0.remove(0)
+        } else {
+            self.0[0].clone()
+        };
+        buffer[..frame.len()].copy_from_slice(&frame);
+
+        Ok(frame.len())
+    }
+
+    fn send_feature_report(&mut self, _data: &[u8]) -> Result<(), IoError> {
+        Ok(())
+    }
+}
+
+#[test]
+fn watch_settings() {
+    let default = std::fs::read("tests/assets/default.frame").unwrap();
+    let effects = std::fs::read("tests/assets/effects_0.frame").unwrap();
+
+    let mut device = Device::new(Sequence(vec![
+        default.clone(),
+        default.clone(),
+        effects,
+        default,
+    ]));
+    let mut watch = device.watch_settings(Duration::from_millis(1));
+
+    let diff = watch.next().unwrap().unwrap();
+    assert!(!diff.is_empty());
+    assert!(watch.cache().settings().is_some());
+
+    let diff = watch.next().unwrap().unwrap();
+    assert!(!diff.is_empty());
+
+    let mut device = Device::new(Flaky(0));
+    let mut watch = device.watch_settings(Duration::from_millis(1));
+    assert!(watch.next().unwrap().is_err());
+}
+
+#[test]
+fn settings_cache() {
+    let default = std::fs::read("tests/assets/default.frame").unwrap();
+    let effects = std::fs::read("tests/assets/effects_0.frame").unwrap();
+
+    let mut device = Device::new(Replay(default.clone()));
+    let mut cache = SettingsCache::new(Duration::from_secs(5));
+    let start = Instant::now();
+
+    assert_eq!(cache.poll(&mut device, start).unwrap(), None);
+    assert_eq!(cache.raw(), &default[..]);
+    assert!(cache.settings().is_some());
+
+    *device.transport_mut() = Replay(effects.clone());
+    assert_eq!(
+        cache
+            .poll(&mut device, start + Duration::from_secs(1))
+            .unwrap(),
+        None
+    );
+    assert_eq!(cache.raw(), &default[..]);
+
+    let Some(SettingsEvent::SettingsChanged(diff)) = cache
+        .poll(&mut device, start + Duration::from_secs(5))
+        .unwrap()
+    else {
+        panic!("Expected settings changed event");
+    };
+    assert!(!diff.is_empty());
+    assert_eq!(cache.raw(), &effects[..]);
+
+    assert_eq!(cache.refresh(&mut device, start).unwrap(), None);
+
+    cache.invalidate();
+    assert!(cache.settings().is_none());
+}
+
+struct Flaky(u32);
+
+impl Transport for Flaky {
+    fn get_feature_report(&mut self, _buffer: &mut [u8]) -> Result<usize, IoError> {
+        self.0 += 1;
+
+        if self.0.is_multiple_of(2) {
+            Err(IoError::IoError(std::io::Error::other(
+                "device disconnected",
+            )))
+        } else {
+            Ok(0)
+        }
+    }
+
+    fn send_feature_report(&mut self, _data: &[u8]) -> Result<(), IoError> {
+        Ok(())
+    }
+}
+
+#[test]
+fn device_health() {
+    let mut device = Device::new(Flaky(0));
+    for _ in 0..4 {
+        let _ = device.read_report(0x01);
+    }
+
+    let health = device.health();
+    assert_eq!(health.requests(), 4);
+    assert_eq!(health.usb_errors(), 2);
+    assert_eq!(health.crc_mismatches(), 0);
+    assert!((health.score() - 0.5).abs() < 1e-9);
+
+    let start = SystemTime::UNIX_EPOCH + Duration::from_hours(1);
+    let mut health = DeviceHealth::new();
+    health.record_request(start, Ok(()));
+    health.record_watchdog(&WatchdogEvent::Stale {
+        last_seen: Some(start + Duration::from_secs(30)),
+        at: start + Duration::from_mins(1),
+    });
+    health.record_watchdog(&WatchdogEvent::Recovered {
+        at: start + Duration::from_secs(90),
+        after: Duration::from_mins(1),
+    });
+    assert_eq!(health.stale_intervals(), 1);
+    assert_eq!(health.stale_time(), Duration::from_mins(1));
+    assert!((health.stale_rate() - 60.0 / 90.0).abs() < 1e-9);
+
+    let mut metrics = String::new();
+    health.write_metrics(&mut metrics, "hfn");
+    assert!(metrics.contains("hfn_health_requests_total 1\n"));
+    assert!(metrics.contains("hfn_health_stale_intervals_total 1\n"));
+    assert!(metrics.contains("hfn_health_stale_seconds 60\n"));
+
+    health.reset();
+    assert_eq!(health, DeviceHealth::default());
+    assert!((health.score() - 1.0).abs() < f64::EPSILON);
+}
+
+#[test]
+fn corrupt_frame() {
+    let default = std::fs::read("tests/assets/default.frame").unwrap();
+    let mut corrupted = default.clone();
+    corrupted[100] ^= 0xFF;
+
+    let mut device = Device::new(Replay(corrupted.clone()));
+    let Err(IoError::CorruptFrame(frame)) = device.read_settings() else {
+        panic!("Expected a corrupt frame");
+    };
+    assert_eq!(frame.frame, corrupted);
+    assert_eq!(frame.previous, None);
+    assert!(!frame.is_truncated());
+    assert_eq!(device.health().crc_mismatches(), 1);
+
+    let mut device = Device::new(Replay(default.clone())).with_retained_frame_limit(16);
+    device.read_settings().unwrap();
+    device.transport_mut().0 = corrupted.clone();
+    let err = device.read_settings().unwrap_err();
+    assert!(err.is_checksum_mismatch());
+    let IoError::CorruptFrame(frame) = err else {
+        panic!("Expected a corrupt frame");
+    };
+    assert_eq!(frame.frame, corrupted[..16]);
+    assert_eq!(frame.len, corrupted.len());
+    assert_eq!(frame.previous.as_deref(), Some(&default[..16]));
+    assert!(frame.is_truncated());
+
+    let mut device = Device::new(Replay(corrupted)).with_retained_frame_limit(0);
+    assert!(matches!(
+        device.read_settings(),
+        Err(IoError::ChecksumMismatch)
+    ));
+}
+
+#[test]
+fn sync_lighting() {
+    let load = |path| {
+        let frame = std::fs::read(path).unwrap();
+        let Frame::Settings(settings) = Frame::decode(&mut &frame[..]).unwrap();
+
+        (Device::new(Replay(frame)), settings)
+    };
+
+    let (source_device, source) = load("tests/assets/effects_1.frame");
+    let (target_device, target) = load("tests/assets/default.frame");
+    let mut manager = DeviceManager::new(vec![source_device, target_device]);
+
+    let source_lighting = source.lighting.as_ref().unwrap();
+    let target_len = target.lighting.as_ref().unwrap().strip_len();
+
+    let mut remapped = source_lighting.clone();
+    remapped.remap_strip(target_len, target_len / 2);
+    assert_eq!(remapped.strip_len(), target_len / 2);
+    for (remapped, original) in remapped
+        .strip_controllers
+        .iter()
+        .zip(&source_lighting.strip_controllers)
+    {
+        assert_eq!(remapped.offset, original.offset.div_ceil(2));
+        assert_eq!(remapped.effect, original.effect);
+    }
+
+    let synced = manager.sync_lighting(0, &[1]).unwrap();
+    let lighting = synced[0].lighting.as_ref().unwrap();
+    assert_eq!(lighting.strip_len(), target_len);
+    assert_eq!(
+        lighting.sensor_controllers,
+        source_lighting.sensor_controllers
+    );
+    assert_eq!(synced[0].display, target.display);
+
+    let synced = manager.sync_settings(0, &[1]).unwrap();
+    assert_eq!(synced[0].display, source.display);
+    assert_eq!(synced[0].lighting.as_ref().unwrap(), lighting);
+    assert_eq!(
+        synced[0].system.aqua_bus_address,
+        target.system.aqua_bus_address
+    );
+}
@@ -0,0 +1,140 @@
+#![allow(missing_docs)]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use high_flow_next::{
+    misc::{Decode, FixedSize, GuardOutput, IoError, Reader},
+    protocol::{
+        decode_frames, AnyFrame, FrameBuilder, FrameHooks, HookedFrame, ProtocolRegistry, RawFrame,
+    },
+};
+
+const DEFAULT: &[u8] = include_bytes!("assets/default.frame");
+
+#[test]
+fn build() {
+    // Rebuilding the payload of a captured frame results in the same frame.
+    let payload = &DEFAULT[1..DEFAULT.len() - 2];
+    let frame = FrameBuilder::new(0x03).bytes(payload).build();
+    assert_eq!(frame, DEFAULT);
+    assert!(decode_frames(&frame).is_ok());
+
+    let frame = FrameBuilder::new(0x03).bytes(payload).crc(0).build();
+    assert!(decode_frames(&frame).unwrap_err().is_checksum_mismatch());
+
+    let builder = FrameBuilder::new(0x42).u8(1).i16be(-2).u16be(0x0304);
+    assert_eq!(builder.op_code(), 0x42);
+    assert_eq!(builder.payload(), [0x01, 0xFF, 0xFE, 0x03, 0x04]);
+}
+
+#[test]
+fn hooks() {
+    let seen = Rc::new(RefCell::new(Vec::new()));
+
+    let mut hooks = FrameHooks::new();
+    assert!(!hooks.register(0x03, 0, |_| Ok(())));
+    assert!(hooks.register(0x42, 2, {
+        let seen = seen.clone();
+
+        move |frame| {
+            seen.borrow_mut().push(frame.payload.clone());
+
+            if frame.payload[0] == 0xFF {
+                Err(IoError::InvalidValue("Experiment", 0xFF))
+            } else {
+                Ok(())
+            }
+        }
+    }));
+    assert_eq!(hooks.size(0x42), Some(5));
+    assert_eq!(hooks.size(0x43), None);
+
+    let experiment = RawFrame {
+        op_code: 0x42,
+        payload: vec![0x12, 0x34],
+    };
+    let data = [&experiment.to_bytes()[..], DEFAULT].concat();
+    let mut reader = &data[..];
+
+    assert_eq!(
+        hooks.decode(&mut reader).unwrap(),
+        HookedFrame::Raw(experiment)
+    );
+    assert!(matches!(
+        hooks.decode(&mut reader).unwrap(),
+        HookedFrame::Frame(_)
+    ));
+    assert!(reader.is_empty());
+
+    let data = FrameBuilder::new(0x42).u16be(0xFF00).build();
+    assert!(hooks.decode(&mut &data[..]).is_err());
+
+    let data = FrameBuilder::new(0x42).u16be(0x0000).crc(0).build();
+    assert!(hooks
+        .decode(&mut &data[..])
+        .unwrap_err()
+        .is_checksum_mismatch());
+
+    assert_eq!(*seen.borrow(), [vec![0x12, 0x34], vec![0xFF, 0x00]]);
+
+    assert!(hooks.unregister(0x42));
+    assert!(hooks.decode(&mut &data[..]).is_err());
+}
+
+/// Frame type of a "downstream" device.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+struct FanSpeed {
+    rpm: u16,
+    duty: u8,
+}
+
+impl FixedSize for FanSpeed {
+    const SIZE: usize = 3;
+}
+
+impl Decode for FanSpeed {
+    fn decode<R: Reader>(reader: &mut R) -> Result<GuardOutput<R, Self>, IoError> {
+        let rpm = reader.read_u16be()?;
+        let duty = reader.read_u8()?;
+
+        Ok(R::guard(|_| Self { rpm, duty }))
+    }
+}
+
+#[test]
+fn registry() {
+    let mut registry = ProtocolRegistry::new();
+    assert!(!registry.register::<FanSpeed>(0x03));
+    assert!(registry.register::<FanSpeed>(0x10));
+    assert!(registry.is_registered(0x10));
+    assert_eq!(registry.size(0x10), Some(6));
+
+    let fan = FrameBuilder::new(0x10).u16be(1200).u8(40).build();
+    let data = [&fan[..], DEFAULT].concat();
+    let frames = registry.decode_all(&data).unwrap();
+
+    assert_eq!(frames.len(), 2);
+    assert!(matches!(
+        frames[0],
+        AnyFrame::Extension { op_code: 0x10, .. }
+    ));
+    assert_eq!(
+        frames[0].downcast_ref::<FanSpeed>(),
+        Some(&FanSpeed {
+            rpm: 1200,
+            duty: 40
+        })
+    );
+    assert!(frames[0].downcast_ref::<u16>().is_none());
+    assert!(matches!(frames[1], AnyFrame::Frame(_)));
+
+    assert!(registry.decode_all(&fan[..4]).is_err());
+    assert!(registry.decode_all(&[0x11, 0x00]).is_err());
+
+    let corrupted = FrameBuilder::new(0x10).u16be(1200).u8(40).crc(0).build();
+    assert!(registry
+        .decode_all(&corrupted)
+        .unwrap_err()
+        .is_checksum_mismatch());
+}
@@ -0,0 +1,93 @@
+//! Golden-file tests for the captured frames in `tests/assets`.
+//!
+//! Every `<name>.frame` capture has an expected JSON snapshot of the decoded
+//! settings stored next to it as `<name>.json`. To add support for a new
+//! capture, drop the `.frame` file into `tests/assets` and run
+//!
+//! ```text
+//! UPDATE_GOLDEN=1 cargo test --features serde --test golden
+//! ```
+//!
+//! to generate the snapshot. Review the generated JSON and commit both files.
+
+#![allow(missing_docs)]
+#![cfg(feature = "serde")]
+
+use std::env::var_os;
+use std::fs::{read, read_dir, read_to_string, write};
+use std::path::Path;
+
+use high_flow_next::{
+    misc::{Decode, FixedSize},
+    protocol::{settings::SettingsDiff, Frame, Settings},
+};
+
+const ASSETS: &str = "tests/assets";
+
+#[test]
+fn golden() {
+    let update = var_os("UPDATE_GOLDEN").is_some();
+
+    let mut frames = read_dir(ASSETS)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|x| x == "frame"))
+        .collect::<Vec<_>>();
+    frames.sort();
+
+    assert!(!frames.is_empty(), "No captures found in {ASSETS}");
+
+    let failures = frames
+        .iter()
+        .filter_map(|path| check(path, update).err())
+        .collect::<Vec<_>>();
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+/// Decodes the capture at `path` and compares it with its snapshot.
+fn check(path: &Path, update: bool) -> Result<(), String> {
+    let name = path.display();
+    let data = read(path).unwrap();
+
+    if data.len() != 1 + Settings::SIZE + 2 {
+        return Err(format!("{name}: unexpected frame size {}", data.len()));
+    }
+
+    let Frame::Settings(settings) =
+        Frame::decode(&mut &data[..]).map_err(|error| format!("{name}: {error}"))?;
+
+    let snapshot = path.with_extension("json");
+    let actual = serde_json::to_string_pretty(&settings).unwrap() + "\n";
+
+    if update {
+        write(&snapshot, &actual).unwrap();
+    }
+
+    let Ok(expected) = read_to_string(&snapshot) else {
+        return Err(format!(
+            "{name}: snapshot {} is missing (run with `UPDATE_GOLDEN=1` to create it)",
+            snapshot.display()
+        ));
+    };
+
+    // Round trip: the snapshot has to deserialize into the decoded settings.
+    let expected_settings = serde_json::from_str::<Settings>(&expected)
+        .map_err(|error| format!("{}: {error}", snapshot.display()))?;
+    if expected_settings != settings {
+        return Err(format!(
+            "{name}: decoded settings do not match the snapshot {}:\n{}",
+            snapshot.display(),
+            SettingsDiff::new(&expected_settings, &settings)
+        ));
+    }
+
+    if expected != actual {
+        return Err(format!(
+            "{name}: serialized settings do not match the snapshot {} (run with `UPDATE_GOLDEN=1` to update it)",
+            snapshot.display()
+        ));
+    }
+
+    Ok(())
+}
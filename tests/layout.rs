@@ -0,0 +1,94 @@
+#![allow(missing_docs, clippy::unreadable_literal)]
+#![cfg(feature = "layout")]
+
+use high_flow_next::protocol::settings::{
+    Color, Effect, EffectStatic, LayoutError, LedLayout, LedZone,
+};
+
+#[test]
+fn openrgb() {
+    let layout = LedLayout::from_openrgb_json(
+        r#"{
+            "name": "Aquacomputer high flow NEXT",
+            "zones": [
+                { "name": "Reservoir", "type": 1, "leds_count": 12 },
+                { "name": "Unused", "type": 1, "leds_count": 0 },
+                { "name": "Case", "type": 1, "leds_count": 30 }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        layout.zones(),
+        [
+            LedZone {
+                name: "Reservoir".into(),
+                leds: 12,
+            },
+            LedZone {
+                name: "Case".into(),
+                leds: 30,
+            },
+        ]
+    );
+    assert_eq!(layout.led_count().unwrap(), 42);
+
+    let effect = Effect::Static(EffectStatic {
+        color: Color::from_rgb_hex(0xFF0000),
+        source_control_brightness: None,
+        source_control_saturation: None,
+    });
+    let controllers = layout.controllers(&effect).unwrap();
+    assert_eq!(controllers.len(), 2);
+    assert_eq!((controllers[1].offset, controllers[1].length), (12, 30));
+    assert_eq!(controllers[1].effect, effect);
+}
+
+#[test]
+fn signalrgb() {
+    let single = LedLayout::from_signalrgb_json(
+        r#"{ "ProductName": "Strip", "DisplayName": "Desk", "LedCount": 20, "Width": 20, "Height": 1 }"#,
+    )
+    .unwrap();
+    assert_eq!(single.regions().unwrap(), [(0, 20)]);
+    assert_eq!(single.zones()[0].name, "Desk");
+
+    let multiple = LedLayout::from_signalrgb_json(
+        r#"[
+            { "ProductName": "Fan", "LedCount": 8 },
+            { "ProductName": "Fan", "LedCount": 8 }
+        ]"#,
+    )
+    .unwrap();
+    assert_eq!(multiple.regions().unwrap(), [(0, 8), (8, 8)]);
+}
+
+#[test]
+fn limits() {
+    let layout = (0..7).fold(LedLayout::new(), |layout, i| {
+        layout.zone(format!("Zone {i}"), 1)
+    });
+    assert!(matches!(
+        layout.regions(),
+        Err(LayoutError::TooManyZones(7))
+    ));
+
+    let layout = LedLayout::new().zone("A", 200).zone("B", 100);
+    assert!(matches!(
+        layout.regions(),
+        Err(LayoutError::TooManyLeds(300))
+    ));
+
+    let layout = LedLayout::new().zone("A", usize::MAX).zone("B", 2);
+    assert!(matches!(
+        layout.led_count(),
+        Err(LayoutError::TooManyLeds(usize::MAX))
+    ));
+    assert!(matches!(
+        layout.regions(),
+        Err(LayoutError::TooManyLeds(usize::MAX))
+    ));
+
+    assert!(LedLayout::from_openrgb_json("{").is_err());
+}
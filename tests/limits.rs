@@ -0,0 +1,136 @@
+#![allow(missing_docs)]
+
+use high_flow_next::{
+    misc::IoError,
+    protocol::{decode_frames, DecodeLimits, Frame, ResyncedFrame},
+};
+
+const DEFAULT: &[u8] = include_bytes!("assets/default.frame");
+const EFFECTS: &[u8] = include_bytes!("assets/effects_0.frame");
+
+fn limit(result: Result<Vec<Frame>, IoError>) -> (&'static str, usize) {
+    result.unwrap_err().limit_exceeded().unwrap()
+}
+
+#[test]
+fn unlimited() {
+    let data = [DEFAULT, EFFECTS, DEFAULT].concat();
+
+    assert_eq!(
+        DecodeLimits::default().decode_frames(&data).unwrap(),
+        decode_frames(&data).unwrap()
+    );
+}
+
+#[test]
+fn limits() {
+    let data = [DEFAULT, EFFECTS, DEFAULT].concat();
+
+    let limits = DecodeLimits {
+        max_input_len: 1000,
+        ..DecodeLimits::default()
+    };
+    assert_eq!(
+        limit(limits.decode_frames(&data)),
+        ("max_input_len", data.len())
+    );
+
+    let limits = DecodeLimits {
+        max_frames: 2,
+        ..DecodeLimits::default()
+    };
+    assert_eq!(limit(limits.decode_frames(&data)), ("max_frames", 3));
+    assert_eq!(
+        limits
+            .decode_frames(&data[..2 * DEFAULT.len()])
+            .unwrap()
+            .len(),
+        2
+    );
+
+    let limits = DecodeLimits {
+        max_payload_len: 100,
+        ..DecodeLimits::default()
+    };
+    assert_eq!(
+        limit(limits.decode_frames(&data)),
+        ("max_payload_len", DEFAULT.len() - 3)
+    );
+
+    let controllers = {
+        let frames = decode_frames(EFFECTS).unwrap();
+        let Frame::Settings(settings) = &frames[0];
+        let lighting = settings.lighting.as_ref().unwrap();
+
+        lighting.strip_controllers.len() + lighting.sensor_controllers.len()
+    };
+    let limits = DecodeLimits {
+        max_controllers: controllers - 1,
+        ..DecodeLimits::default()
+    };
+    assert_eq!(
+        limit(limits.decode_frames(&data)),
+        ("max_controllers", controllers)
+    );
+
+    let error = limits.decode_frames(&data).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!("Decode limit exceeded (limit=max_controllers, actual={controllers})")
+    );
+}
+
+#[test]
+fn resync() {
+    let garbage = [0x42; 64];
+    let data = [DEFAULT, &garbage, EFFECTS, DEFAULT].concat();
+
+    let limits = DecodeLimits {
+        max_discarded: 16,
+        ..DecodeLimits::default()
+    };
+    let mut decoder = limits.decode_frames_resync(&data);
+    assert_eq!(decoder.by_ref().count(), 1);
+    assert_eq!(
+        decoder.error().unwrap().limit_exceeded(),
+        Some(("max_discarded", 17))
+    );
+    assert_eq!(decoder.next(), None);
+
+    let limits = DecodeLimits {
+        max_frames: 2,
+        ..DecodeLimits::default()
+    };
+    let mut decoder = limits.decode_frames_resync(&data);
+    let frames = decoder.by_ref().collect::<Vec<_>>();
+    assert_eq!(
+        frames.iter().map(|x| x.discarded).collect::<Vec<_>>(),
+        [0, garbage.len()]
+    );
+    assert_eq!(
+        decoder.error().unwrap().limit_exceeded(),
+        Some(("max_frames", 3))
+    );
+    assert_eq!(decoder.discarded(), garbage.len());
+
+    let data = [DEFAULT, EFFECTS, &garbage, DEFAULT].concat();
+    let mut decoder = limits.decode_frames_resync(&data);
+    assert_eq!(decoder.by_ref().count(), 2);
+    assert_eq!(
+        decoder.error().unwrap().limit_exceeded(),
+        Some(("max_frames", 3))
+    );
+    assert_eq!(decoder.discarded(), garbage.len());
+
+    let limits = DecodeLimits {
+        max_input_len: 10,
+        ..DecodeLimits::default()
+    };
+    let mut decoder = limits.decode_frames_resync(&data);
+    assert_eq!(decoder.next(), None::<ResyncedFrame>);
+    assert!(decoder.error().is_some());
+
+    let mut decoder = DecodeLimits::default().decode_frames_resync(&data);
+    assert_eq!(decoder.by_ref().count(), 3);
+    assert!(decoder.error().is_none());
+}
@@ -0,0 +1,79 @@
+#![allow(missing_docs)]
+
+use std::fs::File;
+
+use high_flow_next::{
+    locale::{Catalog, Localize},
+    misc::Decode,
+    monitor::{Channel, Page},
+    protocol::{
+        settings::{EffectKind, Flow, StandbyFlags},
+        Frame,
+    },
+};
+
+fn german() -> Catalog {
+    Catalog::from_iter([
+        ("effect.color_change", "Farbwechsel"),
+        ("page.chart", "Diagramm {index}"),
+        ("channel.flow", "Durchfluss"),
+        (
+            "standby.display_off",
+            "Schaltet das Display im Standby aus.",
+        ),
+        (
+            "alarm.flow_below",
+            "Alarm, wenn der Durchfluss unter {value} l/h fällt.",
+        ),
+    ])
+}
+
+#[test]
+fn names() {
+    let catalog = german();
+
+    assert_eq!(EffectKind::ColorChange.localize(&catalog), "Farbwechsel");
+    assert_eq!(
+        EffectKind::SwipingRainbow.localize(&catalog),
+        "Swiping rainbow"
+    );
+    assert_eq!(Page::Chart(3).localize(&catalog), "Diagramm 4");
+    assert_eq!(Page::Chart(3).localize(&Catalog::new()), "Chart 4");
+    assert_eq!(Page::FlowVolume.localize(&catalog), "Flow / volume");
+    assert_eq!(Channel::Flow.localize(&catalog), "Durchfluss");
+
+    for kind in EffectKind::ALL {
+        assert!(kind.key().starts_with("effect."));
+    }
+}
+
+#[test]
+fn messages() {
+    let catalog = german();
+
+    let standby = StandbyFlags::keep_alarms_armed()
+        .messages()
+        .iter()
+        .map(|x| x.localize(&catalog))
+        .collect::<Vec<_>>();
+    assert_eq!(standby[2], "Schaltet das Display im Standby aus.");
+    assert_eq!(standby[3], "Turns the LEDs off in standby.");
+
+    let mut reader = File::open("tests/assets/default.frame").unwrap();
+    let Frame::Settings(mut settings) = Frame::decode(&mut reader).unwrap();
+    settings.alarms.flow_alarm_limit = Some(Flow::from_value(600).unwrap());
+
+    let messages = settings.alarms.messages();
+    assert_eq!(
+        messages[0].localize(&catalog),
+        "Alarm, wenn der Durchfluss unter 60.0 l/h fällt."
+    );
+    assert_eq!(
+        messages[0].to_string(),
+        "Raises an alarm if the flow drops below 60.0 l/h."
+    );
+    assert_eq!(
+        settings.alarms.describe(),
+        messages.iter().map(ToString::to_string).collect::<Vec<_>>()
+    );
+}
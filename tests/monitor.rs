@@ -0,0 +1,1052 @@
+#![allow(missing_docs, clippy::float_cmp)]
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Error, ErrorKind, Read, Write};
+use std::net::TcpListener;
+use std::sync::{mpsc::channel, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use high_flow_next::{
+    misc::{Clock, Decode, ManualClock},
+    monitor::{
+        encode_openmetrics, AdvisoryKind, AlarmEngine, AlarmEvent, AlarmId, AlarmOverride,
+        AlarmRule, AttenuationFilter, Batched, CalibrationSession, Channel, CircuitBreaker,
+        Comparison, Condition, ConductivitySpikeDetector, Confidence, CsvSink, DeadbandFilter,
+        Detector, DriftAnalyzer, DriftState, EmailNotifier, ErrorPolicy, ExponentialFilter, Filter,
+        FlowDropDetector, FlowTrendDetector, FnSource, History, Interpolation, Merger,
+        Notification, Notifier, PowerStateSource, PrometheusSink, Publisher, Resampled, Resampler,
+        Scheduler, SensorReadings, Sink, SinkEvent, StandbyEvent, StandbyMirror, Statistics,
+        SysfsPowerState, TimedExponentialFilter, Timestamp, Totalizer, TotalizerState,
+        UsbPowerState, WatchEvent, Watchdog, WatchdogEvent, Watcher, WebhookNotifier, Window,
+        SOFTWARE_SENSOR_SOURCE,
+    },
+    protocol::{
+        settings::{
+            Brightness, Color, Conductivity, DataSource, Effect, Flow, Medium, PowerDamping,
+            SourceControl, StandbyFlags, Temperature, WaterQuality,
+        },
+        Frame,
+    },
+};
+
+fn readings(secs: u64, flow: u16, water_temperature: u16) -> SensorReadings {
+    SensorReadings {
+        captured_at: SystemTime::UNIX_EPOCH + Duration::from_secs(secs),
+        flow: Flow::from_value(flow).unwrap(),
+        water_temperature: Some(Temperature::from_value(water_temperature).unwrap()),
+        external_temperature: None,
+        conductivity: Conductivity::from_value(20).unwrap(),
+        water_quality: WaterQuality::from_value(9_500).unwrap(),
+        power: 12.5,
+        voltage: 5.02,
+        external: BTreeMap::new(),
+    }
+}
+
+#[test]
+fn readings_value() {
+    let readings = readings(0, 1_234, 3_050);
+
+    assert_eq!(readings.value(Channel::Flow), Some(123.4));
+    assert_eq!(readings.value(Channel::WaterTemperature), Some(30.5));
+    assert_eq!(readings.value(Channel::ExternalTemperature), None);
+    assert_eq!(readings.value(Channel::WaterQuality), Some(95.0));
+    assert_eq!(readings.value(Channel::Power), Some(12.5));
+}
+
+#[test]
+fn value_for() {
+    let mut readings = readings(0, 1_800, 3_000);
+    readings
+        .external
+        .insert(format!("{SOFTWARE_SENSOR_SOURCE}/3"), 42.5);
+
+    assert_eq!(readings.value_for(DataSource::Flow), Some(1_800.0));
+    assert_eq!(
+        readings.value_for(DataSource::WaterTemperature),
+        Some(3_000.0)
+    );
+    assert_eq!(readings.value_for(DataSource::ExternalTemperature), None);
+    assert_eq!(readings.value_for(DataSource::Conductivity), Some(20.0));
+    assert_eq!(readings.value_for(DataSource::WaterQuality), Some(9_500.0));
+    assert_eq!(readings.value_for(DataSource::Power), Some(1_250.0));
+    assert_eq!(readings.value_for(DataSource::Sound), None);
+    assert_eq!(
+        readings.value_for(DataSource::SoftwareSensor3),
+        Some(4_250.0)
+    );
+    assert_eq!(readings.value_for(DataSource::SoftwareSensor4), None);
+
+    let control = SourceControl {
+        input_min: 2_000,
+        input_max: 4_000,
+        output_min: 0,
+        output_max: 100,
+    };
+    let input = readings.value_for(DataSource::WaterTemperature).unwrap();
+    assert_eq!(control.map(input), 50.0);
+}
+
+#[test]
+fn heat_load() {
+    let mut readings = readings(0, 1_800, 3_000);
+    assert_eq!(readings.heat_load(Medium::DistilledWater), None);
+
+    readings.external_temperature = Some(Temperature::from_value(2_800).unwrap());
+    let heat_load = readings.heat_load(Medium::DistilledWater).unwrap();
+    assert!((heat_load - 0.05 * 0.998 * 4_182.0 * 2.0).abs() < 1e-9);
+
+    let heat_load = readings.heat_load_from(Medium::DpUltra, 31.0).unwrap();
+    assert!((heat_load + 205.0).abs() < 1e-9);
+}
+
+#[test]
+fn statistics() {
+    let short = Window::Duration(Duration::from_secs(10));
+    let mut statistics = Statistics::new([short, Window::Boot]);
+
+    for i in 0..20 {
+        statistics.push(&readings(i, 1_000 + u16::try_from(i).unwrap() * 10, 3_000));
+    }
+
+    let flow = statistics.get(short, Channel::Flow).unwrap();
+    assert_eq!(flow.count, 11);
+    assert_eq!(flow.min, 109.0);
+    assert_eq!(flow.max, 119.0);
+    assert_eq!(flow.mean, 114.0);
+    assert_eq!(flow.p50, Some(114.0));
+    assert_eq!(flow.p99, Some(119.0));
+
+    let flow = statistics.get(Window::Boot, Channel::Flow).unwrap();
+    assert_eq!(flow.count, 20);
+    assert_eq!(flow.min, 100.0);
+    assert_eq!(flow.max, 119.0);
+    assert_eq!(flow.p50, None);
+
+    assert!(statistics
+        .get(Window::Boot, Channel::ExternalTemperature)
+        .is_none());
+    assert_eq!(
+        statistics.percentile(short, Channel::WaterTemperature, 90.0),
+        Some(30.0)
+    );
+
+    let snapshot = statistics.snapshot();
+    assert_eq!(snapshot.len(), 2);
+    assert_eq!(snapshot[0].window, short);
+    assert_eq!(snapshot[0].channels.len(), Channel::ALL.len() - 1);
+}
+
+#[test]
+fn alarm_engine() {
+    let mut engine = AlarmEngine::new();
+    let id = engine.add_rule(
+        AlarmRule::new(Channel::WaterTemperature, Comparison::Above, 40.0)
+            .for_duration(Duration::from_secs(5))
+            .hysteresis(2.0),
+    );
+
+    assert!(engine.evaluate(&readings(0, 1_000, 4_100)).is_empty());
+    assert!(engine.evaluate(&readings(3, 1_000, 4_100)).is_empty());
+    assert!(!engine.is_active(id));
+
+    let events = engine.evaluate(&readings(5, 1_000, 4_200));
+    assert_eq!(
+        events,
+        [AlarmEvent::Raised {
+            id,
+            value: 42.0,
+            at: SystemTime::UNIX_EPOCH + Duration::from_secs(5),
+        }]
+    );
+    assert!(engine.is_active(id));
+
+    assert!(engine.evaluate(&readings(6, 1_000, 3_900)).is_empty());
+    let events = engine.evaluate(&readings(7, 1_000, 3_800));
+    assert_eq!(events.len(), 1);
+    assert!(matches!(events[0], AlarmEvent::Cleared { value, .. } if value == 38.0));
+    assert_eq!(engine.active().count(), 0);
+
+    assert!(engine.evaluate(&readings(8, 1_000, 4_100)).is_empty());
+    assert!(engine.evaluate(&readings(9, 1_000, 3_000)).is_empty());
+    assert!(engine.evaluate(&readings(14, 1_000, 4_100)).is_empty());
+}
+
+#[test]
+fn alarm_engine_with_clock() {
+    let clock = ManualClock::new();
+    let mut engine = AlarmEngine::new();
+    let id = engine.add_rule(
+        AlarmRule::new(Channel::WaterTemperature, Comparison::Above, 40.0)
+            .for_duration(Duration::from_secs(5)),
+    );
+
+    // The capture time of the readings is ignored.
+    let readings = readings(0, 1_000, 4_100);
+    assert!(engine.evaluate_with_clock(&readings, &clock).is_empty());
+
+    clock.advance(Duration::from_secs(5));
+    let events = engine.evaluate_with_clock(&readings, &clock);
+    assert_eq!(
+        events,
+        [AlarmEvent::Raised {
+            id,
+            value: 41.0,
+            at: clock.system_time(),
+        }]
+    );
+}
+
+#[test]
+fn filters() {
+    let mut filter = ExponentialFilter::new(0.5);
+    assert_eq!(filter.update(10.0), 10.0);
+    assert_eq!(filter.update(20.0), 15.0);
+    assert_eq!(filter.value(), Some(15.0));
+
+    let mut filter = AttenuationFilter::new(0, 3);
+    assert_eq!(filter.update(10.0), 10.0);
+    assert_eq!(filter.update(20.0), 20.0);
+    assert_eq!(filter.update(12.0), 18.0);
+
+    let mut filter = DeadbandFilter::from_power_damping(PowerDamping::from_value(500).unwrap());
+    assert_eq!(filter.update(10.0), 10.0);
+    assert_eq!(filter.update(10.4), 10.0);
+    assert_eq!(filter.update(10.6), 10.6);
+
+    let mut filter = TimedExponentialFilter::new(Duration::from_secs(1));
+    let t0 = SystemTime::UNIX_EPOCH;
+    assert_eq!(filter.update_at(t0, 0.0), 0.0);
+    let value = filter.update_at(t0 + Duration::from_secs(1), 1.0);
+    assert!((value - (1.0 - (-1.0f64).exp())).abs() < 1e-9);
+}
+
+#[test]
+fn totalizer() {
+    let mut totalizer = Totalizer::new(SystemTime::UNIX_EPOCH);
+
+    totalizer.push(&readings(0, 1_000, 3_000));
+    totalizer.push(&readings(36, 1_000, 3_000));
+    totalizer.push(&readings(72, 2_000, 3_000));
+    assert!((totalizer.volume() - 2.5).abs() < 1e-9);
+
+    totalizer.push(&readings(1_000, 2_000, 3_000));
+    assert!((totalizer.volume() - 2.5).abs() < 1e-9);
+
+    let state = TotalizerState::from_bytes(&totalizer.state().to_bytes());
+    assert_eq!(state, totalizer.state());
+
+    let mut totalizer = Totalizer::from_state(state);
+    totalizer.push(&readings(2_000, 1_000, 3_000));
+    totalizer.push(&readings(2_036, 1_000, 3_000));
+    assert!((totalizer.volume() - 3.5).abs() < 1e-9);
+}
+
+#[test]
+fn watcher() {
+    let (sender, receiver) = channel();
+    let drops = Arc::new(Mutex::new(Vec::<WatchEvent>::new()));
+
+    let mut watcher = Watcher::new();
+    let low_flow = watcher.watch_channel(
+        Condition::Threshold {
+            channel: Channel::Flow,
+            comparison: Comparison::Below,
+            threshold: 40.0,
+        },
+        sender,
+    );
+    let temperature_drop = watcher.watch(
+        Condition::Drop {
+            channel: Channel::WaterTemperature,
+            amount: 5.0,
+            within: Duration::from_mins(10),
+        },
+        {
+            let drops = drops.clone();
+            move |event| drops.lock().unwrap().push(event.clone())
+        },
+    );
+
+    watcher.push(&readings(0, 1_000, 3_500));
+    watcher.push(&readings(60, 300, 3_200));
+    watcher.push(&readings(120, 200, 2_900));
+    watcher.push(&readings(180, 1_000, 2_800));
+    watcher.push(&readings(240, 300, 2_800));
+
+    let events = receiver.try_iter().collect::<Vec<_>>();
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().all(|x| x.id == low_flow));
+    assert_eq!(events[0].value, 30.0);
+
+    let drops = drops.lock().unwrap();
+    assert_eq!(drops.len(), 1);
+    assert_eq!(drops[0].id, temperature_drop);
+    assert_eq!(drops[0].value, 29.0);
+
+    drop(receiver);
+    watcher.push(&readings(300, 1_000, 2_800));
+    watcher.push(&readings(360, 300, 2_800));
+    assert_eq!(watcher.len(), 1);
+}
+
+#[test]
+fn history() {
+    let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+
+    let mut history = History::new(10);
+    history.extend((0..20).map(|i| readings(i, 1_000 + u16::try_from(i).unwrap() * 10, 3_000)));
+
+    assert_eq!(history.len(), 10);
+    assert_eq!(history.iter().next().unwrap().captured_at, at(10));
+    assert_eq!(history.latest().unwrap().captured_at, at(19));
+    assert_eq!(history.range(at(12), at(15)).count(), 3);
+    assert_eq!(history.range(at(30), at(40)).count(), 0);
+
+    let points = history.downsample(Channel::Flow, at(10), at(20), 2);
+    assert_eq!(points, [(at(10), 112.0), (at(15), 117.0)]);
+}
+
+#[test]
+fn scheduler() {
+    let mut scheduler = Scheduler::<Vec<&str>, &str>::new().max_backoff(Duration::from_secs(4));
+    let readings = scheduler.add_task(Duration::from_secs(1), |log| {
+        log.push("readings");
+
+        Ok(())
+    });
+    let settings = scheduler.add_task(Duration::from_secs(1), |_| Err("busy"));
+
+    let t0 = Instant::now();
+    let at = |secs| t0 + Duration::from_secs(secs);
+    let mut log = Vec::new();
+
+    let failures = scheduler.run_pending(&mut log, at(0));
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].id, settings);
+    assert_eq!(failures[0].retry_in, Duration::from_secs(2));
+    assert_eq!(scheduler.next_due(), Some(at(1)));
+
+    assert!(scheduler.run_pending(&mut log, at(1)).is_empty());
+    assert_eq!(
+        scheduler.run_pending(&mut log, at(2))[0].retry_in,
+        Duration::from_secs(4)
+    );
+    assert_eq!(
+        scheduler.run_pending(&mut log, at(6))[0].retry_in,
+        Duration::from_secs(4)
+    );
+
+    assert_eq!(log.len(), 4);
+    assert_eq!(scheduler.failures(readings), Some(0));
+    assert_eq!(scheduler.failures(settings), Some(3));
+}
+
+#[test]
+fn scheduler_with_clock() {
+    let mut scheduler = Scheduler::<Vec<&str>, &str>::new().max_backoff(Duration::from_secs(4));
+    scheduler.add_task(Duration::from_secs(1), |log| {
+        log.push("readings");
+
+        Ok(())
+    });
+    scheduler.add_task(Duration::from_secs(1), |_| Err("busy"));
+
+    // The failing task runs at 0s, 2s and 6s.
+    let clock = ManualClock::new();
+    let mut log = Vec::new();
+    let mut failures = 0;
+    scheduler.run_with_clock(&mut log, &clock, |_| {
+        failures += 1;
+
+        failures < 3
+    });
+
+    assert_eq!(clock.elapsed(), Duration::from_secs(6));
+    assert_eq!(log.len(), 7);
+}
+
+#[test]
+fn detectors() {
+    let mut detector = FlowDropDetector::default();
+    assert!(detector.push(&readings(0, 1_000, 3_000)).is_none());
+    assert!(detector.push(&readings(1, 900, 3_000)).is_none());
+    let advisory = detector.push(&readings(2, 300, 3_000)).unwrap();
+    assert_eq!(advisory.kind, AdvisoryKind::SuddenFlowDrop);
+    assert_eq!(advisory.confidence, Confidence::High);
+    assert_eq!(advisory.reference, 100.0);
+    assert!(detector.push(&readings(3, 300, 3_000)).is_none());
+
+    let mut detector = ConductivitySpikeDetector::default();
+    let mut spike = readings(60, 1_000, 3_000);
+    spike.conductivity = Conductivity::from_value(32).unwrap();
+    assert!(detector.push(&readings(0, 1_000, 3_000)).is_none());
+    let advisory = detector.push(&spike).unwrap();
+    assert_eq!(advisory.kind, AdvisoryKind::ConductivitySpike);
+    assert_eq!(advisory.confidence, Confidence::Low);
+
+    let mut detector = FlowTrendDetector::new(Duration::from_hours(10), 1.0);
+    let advisories = (0..=10)
+        .filter_map(|i| {
+            detector.push(&readings(
+                i * 3600,
+                1_000 - u16::try_from(i).unwrap() * 20,
+                3_000,
+            ))
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(advisories.len(), 1);
+    assert_eq!(advisories[0].kind, AdvisoryKind::FlowDegradation);
+    assert_eq!(advisories[0].confidence, Confidence::High);
+    assert!((advisories[0].value + 2.0).abs() < 1e-9);
+}
+
+#[test]
+fn merger() {
+    let mut merger = Merger::new();
+    merger.add_source(FnSource::new("hwmon0", || {
+        Ok(vec![("temp1".into(), 27.5), ("temp2".into(), 41.0)])
+    }));
+    merger.add_source(FnSource::new("broken", || {
+        Err(Error::new(ErrorKind::NotFound, "gone"))
+    }));
+
+    let mut readings = readings(0, 1_800, 3_000);
+    let errors = merger.merge(&mut readings);
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].0, "broken");
+    assert_eq!(readings.external("hwmon0/temp1"), Some(27.5));
+    assert_eq!(readings.external("hwmon0/temp3"), None);
+
+    let inlet = readings.external("hwmon0/temp1").unwrap();
+    assert!(
+        readings
+            .heat_load_from(Medium::DistilledWater, inlet)
+            .unwrap()
+            > 0.0
+    );
+}
+
+struct FailingSink;
+
+impl Sink for FailingSink {
+    fn publish(&mut self, _readings: &SensorReadings) -> Result<(), Error> {
+        Err(Error::other("offline"))
+    }
+}
+
+#[test]
+fn sinks() {
+    let mut sink = Batched::new(CsvSink::new(Vec::new()), 2);
+    sink.publish(&readings(1, 1_000, 3_000)).unwrap();
+    assert!(sink.into_inner().into_inner().is_empty());
+
+    let mut sink = Batched::new(CsvSink::new(Vec::new()), 2);
+    sink.publish(&readings(1, 1_000, 3_000)).unwrap();
+    sink.publish(&readings(2, 1_010, 3_000)).unwrap();
+    let csv = String::from_utf8(sink.into_inner().into_inner()).unwrap();
+    let lines = csv.lines().collect::<Vec<_>>();
+    assert_eq!(
+        lines[0],
+        "timestamp,flow,water_temperature,external_temperature,conductivity,water_quality,power,voltage"
+    );
+    assert_eq!(lines[2], "2.000,101,30,,20,95,12.5,5.02");
+
+    let online = Arc::new(Mutex::new(false));
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let inner = FlakySink {
+        online: online.clone(),
+        received: received.clone(),
+    };
+    let mut sink = Batched::new(inner, 2);
+    sink.publish(&readings(1, 1_000, 3_000)).unwrap();
+    for secs in 2..10 {
+        assert!(sink.publish(&readings(secs, 1_000, 3_000)).is_err());
+    }
+    assert_eq!(sink.buffered(), 2);
+    *online.lock().unwrap() = true;
+    sink.flush().unwrap();
+    assert_eq!(sink.buffered(), 0);
+    assert_eq!(
+        *received.lock().unwrap(),
+        [
+            SystemTime::UNIX_EPOCH + Duration::from_secs(8),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(9)
+        ]
+    );
+
+    let mut sink = PrometheusSink::default();
+    sink.publish(&readings(1, 1_000, 3_000)).unwrap();
+    assert!(sink
+        .metrics()
+        .contains("# TYPE high_flow_next_flow gauge\nhigh_flow_next_flow 100\n"));
+    assert!(!sink.metrics().contains("external_temperature"));
+
+    let mut publisher = Publisher::new();
+    publisher.add_sink(PrometheusSink::default(), ErrorPolicy::Disable);
+    publisher.add_sink(FailingSink, ErrorPolicy::Retry(2));
+    publisher.add_sink(FailingSink, ErrorPolicy::Disable);
+
+    assert_eq!(publisher.publish(&readings(1, 1_000, 3_000)).len(), 2);
+    assert_eq!(publisher.len(), 2);
+}
+
+struct FlakySink {
+    online: Arc<Mutex<bool>>,
+    received: Arc<Mutex<Vec<SystemTime>>>,
+}
+
+impl Sink for FlakySink {
+    fn publish(&mut self, readings: &SensorReadings) -> Result<(), Error> {
+        if !*self.online.lock().unwrap() {
+            return Err(Error::other("broker is down"));
+        }
+
+        self.received.lock().unwrap().push(readings.captured_at);
+
+        Ok(())
+    }
+}
+
+#[test]
+fn circuit_breaker() {
+    let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+    let clock = ManualClock::new();
+    let online = Arc::new(Mutex::new(false));
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let inner = FlakySink {
+        online: online.clone(),
+        received: received.clone(),
+    };
+
+    let mut sink = CircuitBreaker::with_clock(inner, &clock)
+        .with_capacity(4)
+        .with_threshold(2)
+        .with_backoff(Duration::from_secs(1), Duration::from_secs(3));
+
+    sink.publish(&readings(1, 1_000, 3_000)).unwrap();
+    assert_eq!(
+        sink.take_events(),
+        [SinkEvent::Failed {
+            failures: 1,
+            retry_in: Duration::from_secs(1),
+        }]
+    );
+    assert!(!sink.is_open());
+
+    // Backing off, the readings are only buffered.
+    sink.publish(&readings(2, 1_000, 3_000)).unwrap();
+    assert!(sink.take_events().is_empty());
+    assert_eq!(sink.buffered(), 2);
+
+    clock.advance(Duration::from_secs(1));
+    sink.publish(&readings(3, 1_000, 3_000)).unwrap();
+    assert_eq!(
+        sink.take_events(),
+        [
+            SinkEvent::Failed {
+                failures: 2,
+                retry_in: Duration::from_secs(2),
+            },
+            SinkEvent::Tripped { failures: 2 },
+        ]
+    );
+    assert!(sink.is_open());
+
+    for secs in 4..7 {
+        sink.publish(&readings(secs, 1_000, 3_000)).unwrap();
+    }
+    assert_eq!(sink.take_events(), [SinkEvent::Dropped { count: 2 }]);
+    assert_eq!(sink.buffered(), 4);
+
+    clock.advance(Duration::from_secs(2));
+    assert!(sink.flush().is_err());
+    assert_eq!(
+        sink.take_events(),
+        [SinkEvent::Failed {
+            failures: 3,
+            retry_in: Duration::from_secs(3),
+        }]
+    );
+    assert_eq!(
+        sink.next_attempt(),
+        Some(clock.now() + Duration::from_secs(3))
+    );
+
+    *online.lock().unwrap() = true;
+    sink.publish(&readings(7, 1_000, 3_000)).unwrap();
+    assert!(received.lock().unwrap().is_empty());
+
+    clock.advance(Duration::from_secs(3));
+    sink.publish(&readings(8, 1_000, 3_000)).unwrap();
+    assert_eq!(
+        sink.take_events(),
+        [
+            SinkEvent::Dropped { count: 2 },
+            SinkEvent::Recovered {
+                failures: 3,
+                delivered: 4,
+            },
+        ]
+    );
+    assert!(!sink.is_open());
+    assert_eq!(sink.buffered(), 0);
+    assert_eq!(sink.next_attempt(), None);
+    assert_eq!(*received.lock().unwrap(), [at(5), at(6), at(7), at(8)]);
+
+    let (sender, events) = channel();
+    let mut publisher = Publisher::new();
+    publisher.add_sink(
+        CircuitBreaker::new(FailingSink).with_events(sender),
+        ErrorPolicy::Disable,
+    );
+    assert!(publisher.publish(&readings(1, 1_000, 3_000)).is_empty());
+    assert_eq!(publisher.len(), 1);
+    assert!(matches!(
+        events.try_recv(),
+        Ok(SinkEvent::Failed { failures: 1, .. })
+    ));
+    assert_eq!(publisher.flush().len(), 1);
+}
+
+#[test]
+fn circuit_breaker_events() {
+    let mut sink = CircuitBreaker::new(FailingSink)
+        .with_threshold(5)
+        .with_backoff(Duration::ZERO, Duration::ZERO);
+    for secs in 0..100 {
+        sink.publish(&readings(secs, 1_000, 3_000)).unwrap();
+    }
+    assert!(matches!(
+        sink.take_events()[..],
+        [
+            SinkEvent::Failed { failures: 5, .. },
+            SinkEvent::Tripped { failures: 5 },
+            SinkEvent::Failed { failures: 100, .. },
+        ]
+    ));
+}
+
+#[test]
+fn resampler() {
+    let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+    let flow = |x: &SensorReadings| u16::from(x.flow);
+    let temperature = |x: &SensorReadings| u16::from(x.water_temperature.unwrap());
+
+    let mut resampler = Resampler::new(Duration::from_secs(2), Interpolation::Linear);
+    assert!(resampler.push(&readings(1, 1_000, 2_000)).is_empty());
+
+    let samples = resampler.push(&readings(3, 2_000, 3_000));
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0].captured_at, at(2));
+    assert_eq!(flow(&samples[0]), 1_500);
+    assert_eq!(temperature(&samples[0]), 2_500);
+
+    let samples = resampler.push(&readings(6, 2_600, 3_000));
+    assert_eq!(samples.len(), 2);
+    assert_eq!(samples[0].captured_at, at(4));
+    assert_eq!(flow(&samples[0]), 2_200);
+    assert_eq!(samples[1].captured_at, at(6));
+    assert_eq!(flow(&samples[1]), 2_600);
+
+    assert!(resampler.push(&readings(5, 1_000, 2_000)).is_empty());
+
+    let mut resampler = Resampler::new(Duration::from_secs(2), Interpolation::LastValue);
+    assert_eq!(resampler.push(&readings(2, 1_000, 2_000)).len(), 1);
+    let samples = resampler.push(&readings(5, 2_000, 3_000));
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0].captured_at, at(4));
+    assert_eq!(flow(&samples[0]), 1_000);
+
+    let mut resampler = Resampler::new(Duration::from_secs(2), Interpolation::Linear)
+        .with_max_gap(Duration::from_secs(5));
+    resampler.push(&readings(1, 1_000, 2_000));
+    let samples = resampler.push(&readings(21, 2_000, 3_000));
+    assert_eq!(samples.len(), 0);
+    let samples = resampler.push(&readings(23, 3_000, 3_000));
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0].captured_at, at(22));
+    assert_eq!(flow(&samples[0]), 2_500);
+
+    let mut sink = Resampled::new(
+        CsvSink::new(Vec::new()),
+        Resampler::new(Duration::from_secs(2), Interpolation::Linear),
+    );
+    sink.publish(&readings(1, 1_000, 2_000)).unwrap();
+    sink.publish(&readings(3, 2_000, 2_000)).unwrap();
+    sink.publish(&readings(7, 3_000, 2_000)).unwrap();
+    sink.flush().unwrap();
+    let csv = String::from_utf8(sink.into_inner().into_inner()).unwrap();
+    let lines = csv.lines().skip(1).collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        [
+            "2.000,150,20,,20,95,12.5,5.02",
+            "4.000,225,20,,20,95,12.5,5.02",
+            "6.000,275,20,,20,95,12.5,5.02",
+        ]
+    );
+}
+
+#[test]
+fn notifiers() {
+    let mut engine = AlarmEngine::new();
+    let id = engine.add_rule(AlarmRule::new(Channel::Flow, Comparison::Below, 50.0));
+    let events = engine.evaluate(&readings(1, 125, 2_000));
+    let notification = engine.notification(&events[0]).unwrap();
+    assert_eq!(
+        notification,
+        Notification {
+            id,
+            raised: true,
+            channel: Channel::Flow,
+            comparison: Comparison::Below,
+            threshold: 50.0,
+            value: 12.5,
+            at: SystemTime::UNIX_EPOCH + Duration::from_secs(1),
+        }
+    );
+    assert_eq!(notification.title(), "High Flow Next: flow alarm raised");
+    assert_eq!(
+        notification.message(),
+        "The flow is 12.5 l/h (below the limit of 50 l/h)."
+    );
+    assert_eq!(
+        notification.to_json(),
+        r#"{"alarm":0,"state":"raised","channel":"flow","comparison":"below","threshold":50,"value":12.5,"timestamp":1.000,"title":"High Flow Next: flow alarm raised","message":"The flow is 12.5 l/h (below the limit of 50 l/h)."}"#
+    );
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = thread::spawn(move || {
+        let mut requests = Vec::new();
+
+        for status in ["200 OK", "500 Internal Server Error"] {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            while !String::from_utf8_lossy(&request).ends_with('}') {
+                let len = stream.read(&mut buffer).unwrap();
+                request.extend_from_slice(&buffer[..len]);
+            }
+            write!(stream, "HTTP/1.1 {status}\r\nContent-Length: 0\r\n\r\n").unwrap();
+            requests.push(String::from_utf8(request).unwrap());
+        }
+
+        requests
+    });
+
+    let mut webhook = WebhookNotifier::new(&format!("http://127.0.0.1:{port}/hook"))
+        .unwrap()
+        .with_header("Authorization", "Bearer secret")
+        .unwrap();
+    webhook.notify(&notification).unwrap();
+    assert!(webhook.notify(&notification).is_err());
+
+    let requests = server.join().unwrap();
+    assert!(requests[0].starts_with("POST /hook HTTP/1.1\r\n"));
+    assert!(requests[0].contains("\r\nAuthorization: Bearer secret\r\n"));
+    assert!(requests[0].ends_with(&format!("\r\n\r\n{}", notification.to_json())));
+
+    assert!(WebhookNotifier::new("https://example.com/hook").is_err());
+    assert!(WebhookNotifier::new("http://example.com:port/hook").is_err());
+    assert!(WebhookNotifier::new("http://example.com/x\r\nEvil: 1").is_err());
+    assert!(WebhookNotifier::new("http://example.com/x HTTP/1.0").is_err());
+    assert!(WebhookNotifier::new("http://example.com\r\nEvil/hook").is_err());
+    assert!(WebhookNotifier::new("http://exa\0mple.com/hook").is_err());
+
+    let webhook = WebhookNotifier::new("http://example.com/hook").unwrap();
+    assert!(webhook
+        .clone()
+        .with_header("X-Token", "secret\r\nX-Injected: 1")
+        .is_err());
+    assert!(webhook
+        .clone()
+        .with_header("X-Token\r\n", "secret")
+        .is_err());
+    assert!(webhook.with_header("X-Token:", "secret").is_err());
+
+    let email = EmailNotifier::new("monitor@localhost", ["admin@localhost"]).unwrap();
+    assert_eq!(
+        email.message(&notification),
+        "From: monitor@localhost\r\nTo: admin@localhost\r\nSubject: High Flow Next: flow alarm raised\r\nContent-Type: text/plain; charset=utf-8\r\n\r\nThe flow is 12.5 l/h (below the limit of 50 l/h).\r\n"
+    );
+
+    let mut notifiers: Vec<Box<dyn Notifier>> = vec![Box::new(
+        email.with_program("high-flow-next-missing-sendmail"),
+    )];
+    assert!(notifiers.notify(&notification).is_err());
+
+    assert!(EmailNotifier::new("monitor@localhost\r\nBcc: evil@example.com", ["a@b"]).is_err());
+    assert!(
+        EmailNotifier::new("monitor@localhost", ["a@b", "c@d\nBcc: evil@example.com"]).is_err()
+    );
+}
+
+#[test]
+fn watchdog() {
+    let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+    let mut watchdog = Watchdog::new(Duration::from_secs(5), at(0));
+
+    assert_eq!(watchdog.check(at(3)), None);
+    assert!(watchdog.feed(&readings(3, 1_000, 3_000)).is_none());
+    assert_eq!(watchdog.check(at(8)), None);
+    assert_eq!(
+        watchdog.check(at(9)),
+        Some(WatchdogEvent::Stale {
+            last_seen: Some(at(3)),
+            at: at(9),
+        })
+    );
+    assert_eq!(watchdog.check(at(10)), None);
+    assert!(watchdog.is_stale());
+
+    assert_eq!(
+        watchdog.feed(&readings(20, 1_000, 3_000)),
+        Some(WatchdogEvent::Recovered {
+            at: at(20),
+            after: Duration::from_secs(12),
+        })
+    );
+    assert!(!watchdog.is_stale());
+}
+
+#[test]
+fn standby_mirror() {
+    let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+    let mut watchdog = Watchdog::new(Duration::from_secs(5), at(0));
+    let mut mirror = StandbyMirror::new(StandbyFlags::fully_dark());
+    assert!(mirror.should_poll());
+    assert!(!mirror.is_standby());
+
+    assert!(watchdog.feed(&readings(3, 1_000, 3_000)).is_none());
+    assert_eq!(mirror.update(UsbPowerState::Active, at(4)), None);
+    assert_eq!(
+        mirror.update(UsbPowerState::Suspended, at(5)),
+        Some(StandbyEvent::Paused {
+            at: at(5),
+            state: UsbPowerState::Suspended,
+            standby: true,
+        })
+    );
+    assert!(!mirror.should_poll());
+    assert!(mirror.alarms_paused());
+    assert!(mirror.volume_counter_paused());
+    assert_eq!(mirror.update(UsbPowerState::Disconnected, at(6)), None);
+
+    assert_eq!(mirror.check_watchdog(&mut watchdog, at(100)), None);
+    assert!(!watchdog.is_stale());
+
+    assert_eq!(
+        mirror.update(UsbPowerState::Active, at(200)),
+        Some(StandbyEvent::Resumed {
+            at: at(200),
+            after: Duration::from_secs(195),
+        })
+    );
+    assert_eq!(mirror.check_watchdog(&mut watchdog, at(204)), None);
+    assert_eq!(watchdog.last_seen(), None);
+    assert_eq!(
+        mirror.check_watchdog(&mut watchdog, at(206)),
+        Some(WatchdogEvent::Stale {
+            last_seen: None,
+            at: at(206),
+        })
+    );
+
+    let mut mirror = StandbyMirror::new(StandbyFlags::keep_alarms_armed());
+    let mut state = || Ok(UsbPowerState::Suspended);
+    let event = mirror.poll(&mut state, at(0)).unwrap();
+    assert!(matches!(
+        event,
+        Some(StandbyEvent::Paused { standby: true, .. })
+    ));
+    assert!(!mirror.alarms_paused());
+
+    let mut mirror = StandbyMirror::new(StandbyFlags::STANDBY_NO_USB);
+    let mut failing = || Err(Error::other("no power management"));
+    assert!(mirror.poll(&mut failing, at(0)).is_err());
+    assert!(mirror.should_poll());
+    let event = mirror.update(UsbPowerState::Suspended, at(1));
+    assert!(matches!(
+        event,
+        Some(StandbyEvent::Paused { standby: false, .. })
+    ));
+
+    let dir = std::env::temp_dir().join(format!("high_flow_next_standby_{}", std::process::id()));
+    let mut sysfs = SysfsPowerState::new(&dir);
+    assert_eq!(sysfs.power_state().unwrap(), UsbPowerState::Disconnected);
+    std::fs::create_dir_all(dir.join("power")).unwrap();
+    std::fs::write(dir.join("power/runtime_status"), "active\n").unwrap();
+    assert_eq!(sysfs.power_state().unwrap(), UsbPowerState::Active);
+    std::fs::write(dir.join("power/runtime_status"), "suspended\n").unwrap();
+    assert_eq!(sysfs.power_state().unwrap(), UsbPowerState::Suspended);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn calibration() {
+    assert!(CalibrationSession::default().fit().is_err());
+
+    let mut session = CalibrationSession::default();
+    for device in [50.0, 100.0, 150.0, 250.0] {
+        session.record(device * 1.02, device);
+    }
+
+    let calibration = session.fit().unwrap();
+    assert!(calibration
+        .table
+        .iter()
+        .all(|(_, correction)| **correction == 200));
+    assert!(calibration.max_error < 1e-9);
+    assert_eq!(*calibration.table[9].0, 3000);
+
+    let mut session = CalibrationSession::default();
+    session.record(20.0, 10.0);
+    assert!(session.fit().is_err());
+}
+
+#[test]
+fn drift_analyzer() {
+    let day = |days: u64| SystemTime::UNIX_EPOCH + Duration::from_hours(days * 24);
+    let sample = |days: u64, conductivity: u16| {
+        let mut readings = readings(days * 86_400, 1_000, 3_000);
+        readings.conductivity = Conductivity::from_value(conductivity).unwrap();
+
+        readings
+    };
+
+    let mut analyzer = DriftAnalyzer::new(day(0));
+    assert!(analyzer.push(&sample(0, 500)));
+    assert!(!analyzer.push(&readings(60, 1_000, 3_000)));
+    assert_eq!(analyzer.drift_rate(), None);
+
+    for days in 1..=10 {
+        assert!(analyzer.push(&sample(days, 500 + u16::try_from(days).unwrap() * 2)));
+    }
+
+    let state = DriftState::from_bytes(&analyzer.state().to_bytes());
+    assert_eq!(state, analyzer.state());
+
+    let analyzer = DriftAnalyzer::from_state(state);
+    assert!((analyzer.drift_rate().unwrap() - 2.0).abs() < 1e-9);
+    assert!((analyzer.days_until(540.0, day(10)).unwrap() - 10.0).abs() < 1e-9);
+    assert_eq!(analyzer.days_until(400.0, day(10)), Some(0.0));
+}
+
+#[test]
+fn timestamp() {
+    let time = Timestamp::from_unix_millis(1_500).unwrap();
+    assert_eq!(time.unix_millis(), 1_500);
+    assert!((time.unix_seconds() - 1.5).abs() < 1e-9);
+    assert_eq!(
+        Timestamp::from_unix_millis(-250).unwrap().unix_millis(),
+        -250,
+        "timestamps before the epoch"
+    );
+
+    let mut history = History::new(10);
+    history.extend((0..5).map(|secs| readings(secs, 1000, 2500)));
+    assert_eq!(history.latest().unwrap().timestamp().unix_millis(), 4_000);
+
+    let from = Timestamp::from_unix_millis(1_000).unwrap();
+    let to = Timestamp::from_unix_millis(3_000).unwrap();
+    assert_eq!(history.range(from, to).count(), 2);
+
+    #[cfg(feature = "time")]
+    {
+        let date = time::OffsetDateTime::from(time);
+        assert_eq!(date.unix_timestamp_nanos(), 1_500_000_000);
+        assert_eq!(Timestamp::from(date), time);
+        assert_eq!(
+            history.range(from, time::OffsetDateTime::from(to)).count(),
+            2
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    {
+        let date = chrono::DateTime::<chrono::Utc>::from(time);
+        assert_eq!(date.timestamp_millis(), 1_500);
+        assert_eq!(Timestamp::from(date), time);
+        assert_eq!(history.range(date, to).count(), 1);
+    }
+}
+
+#[test]
+fn openmetrics() {
+    let mut statistics = Statistics::default();
+    for (secs, flow) in [(0, 1000), (1, 1100)] {
+        statistics.push(&readings(secs, flow, 2500));
+    }
+
+    let mut last = readings(2, 1200, 2500);
+    statistics.push(&last);
+    last.external.insert("pump/rpm".into(), 2400.0);
+
+    let metrics = encode_openmetrics(&last, &statistics);
+    assert!(metrics.contains("# TYPE high_flow_next_flow gauge\n"));
+    assert!(metrics.contains("high_flow_next_flow 120 2.000\n"));
+    assert!(metrics.contains("# TYPE high_flow_next_flow_window summary\n"));
+    assert!(metrics.contains("high_flow_next_flow_window{window=\"5m\",quantile=\"0.5\"} 110\n"));
+    assert!(metrics.contains("high_flow_next_flow_window_count{window=\"boot\"} 3\n"));
+    assert!(metrics.contains("high_flow_next_flow_window_sum{window=\"1h\"} 330\n"));
+    assert!(metrics.contains("high_flow_next_flow_window_max{window=\"boot\"} 120\n"));
+    assert!(metrics.contains("high_flow_next_external{name=\"pump/rpm\"} 2400 2.000\n"));
+    assert!(metrics.ends_with("# EOF\n"));
+}
+
+#[test]
+fn alarm_override() {
+    let mut reader = File::open("tests/assets/default.frame").unwrap();
+    let Frame::Settings(mut settings) = Frame::decode(&mut reader).unwrap();
+    let original = settings.lighting.clone().unwrap();
+
+    let mut alarm_override = AlarmOverride::blink(Color::from_rgb(255, 0, 0))
+        .brightness(Brightness::from_value(255).unwrap());
+    let event = |id, raised| {
+        let at = SystemTime::UNIX_EPOCH;
+        if raised {
+            AlarmEvent::Raised {
+                id: AlarmId(id),
+                value: 0.0,
+                at,
+            }
+        } else {
+            AlarmEvent::Cleared {
+                id: AlarmId(id),
+                value: 0.0,
+                at,
+            }
+        }
+    };
+
+    assert!(alarm_override.handle(&event(0, true), &mut settings));
+    assert!(!alarm_override.handle(&event(1, true), &mut settings));
+    assert!(alarm_override.is_active());
+    assert_eq!(alarm_override.saved(), Some(&original));
+
+    let lighting = settings.lighting.as_ref().unwrap();
+    assert_eq!(
+        lighting.strip_controllers.len(),
+        original.strip_controllers.len()
+    );
+    for (controller, original) in lighting
+        .strip_controllers
+        .iter()
+        .zip(&original.strip_controllers)
+    {
+        assert!(matches!(controller.effect, Effect::Blink(_)));
+        assert_eq!(controller.offset, original.offset);
+        assert_eq!(controller.length, original.length);
+    }
+
+    assert!(!alarm_override.handle(&event(0, false), &mut settings));
+    assert!(alarm_override.is_active());
+    assert!(alarm_override.handle(&event(1, false), &mut settings));
+    assert!(!alarm_override.is_active());
+    assert_eq!(settings.lighting, Some(original));
+}
@@ -0,0 +1,125 @@
+#![allow(missing_docs)]
+
+use std::collections::BTreeMap;
+use std::fs::read;
+use std::time::{Duration, SystemTime};
+
+use high_flow_next::{
+    misc::Decode,
+    monitor::{DisplayPreview, Framebuffer, History, Page, SensorReadings, DISPLAY_WIDTH},
+    protocol::{
+        settings::{
+            ChartSource, Conductivity, DisplayFlags, DisplaySettings, Flow, PageFlags, Temperature,
+            WaterQuality,
+        },
+        Frame,
+    },
+};
+
+fn settings() -> DisplaySettings {
+    let data = read("tests/assets/default.frame").unwrap();
+    let Frame::Settings(settings) = Frame::decode(&mut &data[..]).unwrap();
+
+    settings.display
+}
+
+fn readings(secs: u64, flow: u16) -> SensorReadings {
+    SensorReadings {
+        captured_at: SystemTime::UNIX_EPOCH + Duration::from_secs(secs),
+        flow: Flow::from_value(flow).unwrap(),
+        water_temperature: Some(Temperature::from_value(3_050).unwrap()),
+        external_temperature: None,
+        conductivity: Conductivity::from_value(20).unwrap(),
+        water_quality: WaterQuality::from_value(9_500).unwrap(),
+        power: 12.5,
+        voltage: 5.02,
+        external: BTreeMap::new(),
+    }
+}
+
+#[test]
+fn pages() {
+    let mut settings = settings();
+    settings.page_flags = PageFlags::FLOW | PageFlags::TEMPERATURES | PageFlags::CHART2;
+
+    let preview = DisplayPreview::new(&settings);
+    assert_eq!(
+        preview.pages(),
+        [Page::Flow, Page::Temperatures, Page::Chart(1)]
+    );
+
+    let rendered = preview.render_all();
+    assert_eq!(rendered.len(), 3);
+    assert!(rendered.iter().all(|(_, fb)| fb.lit_pixels() > 0));
+}
+
+#[test]
+fn values() {
+    let mut settings = settings();
+    settings.display_flags = DisplayFlags::empty();
+
+    let readings = readings(0, 1_234);
+    let without = DisplayPreview::new(&settings).render(Page::Flow);
+    let with = DisplayPreview::new(&settings)
+        .with_readings(&readings)
+        .render(Page::Flow);
+
+    assert_ne!(without, with);
+    assert_eq!(without.width(), DISPLAY_WIDTH);
+
+    // The title is the same, the value differs
+    let rows = |fb: &Framebuffer, rows: std::ops::Range<usize>| {
+        rows.flat_map(|y| (0..fb.width()).map(move |x| (x, y)))
+            .map(|(x, y)| fb.pixel(x, y))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(rows(&without, 0..12), rows(&with, 0..12));
+    assert_ne!(rows(&without, 12..48), rows(&with, 12..48));
+}
+
+#[test]
+fn flags() {
+    let mut settings = settings();
+    settings.display_flags = DisplayFlags::empty();
+    let normal = DisplayPreview::new(&settings).render(Page::DeviceInfo);
+
+    settings.display_flags = DisplayFlags::INVERT;
+    let inverted = DisplayPreview::new(&settings).render(Page::DeviceInfo);
+    assert_eq!(
+        inverted.lit_pixels(),
+        normal.width() * normal.height() - normal.lit_pixels()
+    );
+
+    settings.display_flags = DisplayFlags::ROTATE;
+    let rotated = DisplayPreview::new(&settings).render(Page::DeviceInfo);
+    assert_eq!(rotated.pixel(0, 0), normal.pixel(127, 63));
+    assert_eq!(rotated.pixel(40, 20), normal.pixel(87, 43));
+}
+
+#[test]
+fn chart() {
+    let mut settings = settings();
+    settings.display_flags = DisplayFlags::empty();
+    settings.charts[0].source = ChartSource::Flow;
+
+    let mut history = History::new(1000);
+    let empty = DisplayPreview::new(&settings).render(Page::Chart(0));
+
+    for secs in 0..1000 {
+        history.push(readings(secs, u16::try_from(secs).unwrap()));
+    }
+    let chart = DisplayPreview::new(&settings)
+        .with_history(&history)
+        .render(Page::Chart(0));
+
+    assert!(chart.lit_pixels() > empty.lit_pixels());
+}
+
+#[test]
+fn pbm() {
+    let fb = DisplayPreview::new(&settings()).render(Page::DeviceInfo);
+    let pbm = fb.to_pbm();
+
+    assert!(pbm.starts_with(b"P4\n128 64\n"));
+    assert_eq!(pbm.len(), 10 + 128 / 8 * 64);
+}
@@ -0,0 +1,155 @@
+#![allow(missing_docs)]
+#![cfg(feature = "config")]
+
+use std::fs::{read, remove_dir_all, File};
+use std::time::Duration;
+
+use high_flow_next::{
+    device::{Device, Transport},
+    misc::{Decode, IoError},
+    profiles::{LocalTime, Profile, ProfileError, ProfileLibrary, ProfileSwitcher},
+    protocol::{Frame, Settings},
+};
+
+struct Replay(Vec<u8>);
+
+impl Transport for Replay {
+    fn get_feature_report(&mut self, buffer: &mut [u8]) -> Result<usize, IoError> {
+        buffer[..self.0.len()].copy_from_slice(&self.0);
+
+        Ok(self.0.len())
+    }
+
+    fn send_feature_report(&mut self, _data: &[u8]) -> Result<(), IoError> {
+        Ok(())
+    }
+}
+
+fn load(path: &str) -> Settings {
+    let mut reader = File::open(path).unwrap();
+    let Frame::Settings(settings) = Frame::decode(&mut reader).unwrap();
+
+    settings
+}
+
+#[test]
+fn library() {
+    let dir = std::env::temp_dir().join(format!("high_flow_next_profiles_{}", std::process::id()));
+    let library = ProfileLibrary::new(&dir);
+    assert!(library.names().unwrap().is_empty());
+
+    let default = load("tests/assets/default.frame");
+    let effects = load("tests/assets/effects_1.frame");
+    let gaming = Profile::Lighting(effects.lighting.clone().unwrap());
+
+    library.save("gaming", &gaming).unwrap();
+    library
+        .save("full", &Profile::Settings(default.clone()))
+        .unwrap();
+    assert_eq!(library.names().unwrap(), ["full", "gaming"]);
+    assert_eq!(library.load("gaming").unwrap(), gaming);
+    assert_eq!(
+        library.load("full").unwrap(),
+        Profile::Settings(default.clone())
+    );
+
+    assert!(matches!(
+        library.load("silent"),
+        Err(ProfileError::NotFound(name)) if name == "silent"
+    ));
+    assert!(matches!(
+        library.save("../escape", &gaming),
+        Err(ProfileError::InvalidName(_))
+    ));
+
+    let mut device = Device::new(Replay(read("tests/assets/default.frame").unwrap()));
+    let applied = device.apply(&library, "gaming").unwrap();
+    assert_eq!(applied.lighting, effects.lighting);
+    assert_eq!(applied.system, default.system);
+    assert_eq!(applied.display, default.display);
+
+    library.remove("gaming").unwrap();
+    assert_eq!(library.names().unwrap(), ["full"]);
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn switcher() {
+    let dir = std::env::temp_dir().join(format!("high_flow_next_switcher_{}", std::process::id()));
+    let library = ProfileLibrary::new(&dir);
+    let effects = load("tests/assets/effects_2.frame");
+    library
+        .save(
+            "night",
+            &Profile::Lighting(effects.lighting.clone().unwrap()),
+        )
+        .unwrap();
+
+    let mut switcher = ProfileSwitcher::new()
+        .at(Duration::from_hours(7), "day")
+        .at(Duration::from_hours(22), "night");
+    let mut device = Device::new(Replay(read("tests/assets/default.frame").unwrap()));
+
+    let morning = LocalTime::new(1, Duration::from_hours(8));
+    assert_eq!(switcher.target(morning), Some("day"));
+    assert!(matches!(
+        switcher.poll(&mut device, &library, morning),
+        Err(ProfileError::NotFound(_))
+    ));
+    assert_eq!(switcher.active(), None);
+
+    let evening = LocalTime::new(1, Duration::from_hours(23));
+    let (name, settings) = switcher
+        .poll(&mut device, &library, evening)
+        .unwrap()
+        .unwrap();
+    assert_eq!(name, "night");
+    assert_eq!(settings.lighting, effects.lighting);
+    assert_eq!(switcher.active(), None);
+
+    // The profile is applied again until the write was reported.
+    assert!(switcher
+        .poll(&mut device, &library, evening)
+        .unwrap()
+        .is_some());
+    switcher.mark_applied(&name);
+    assert_eq!(switcher.active(), Some("night"));
+
+    let night = LocalTime::new(2, Duration::from_hours(3));
+    assert!(switcher
+        .poll(&mut device, &library, night)
+        .unwrap()
+        .is_none());
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(feature = "solar")]
+fn sunrise_sunset() {
+    use high_flow_next::profiles::Location;
+
+    // Berlin, June 21st (CEST)
+    let berlin = Location::new(52.52, 13.405, 120);
+    let sunrise = berlin.sunrise(172).unwrap().as_secs() / 60;
+    let sunset = berlin.sunset(172).unwrap().as_secs() / 60;
+    assert!((4 * 60 + 35..=4 * 60 + 53).contains(&sunrise), "{sunrise}");
+    assert!((21 * 60 + 25..=21 * 60 + 43).contains(&sunset), "{sunset}");
+
+    // Polar night in Tromsø
+    assert!(Location::new(69.65, 18.96, 60).sunrise(355).is_none());
+
+    let switcher = ProfileSwitcher::new()
+        .location(berlin)
+        .at_sunrise("day")
+        .at_sunset("night");
+    assert_eq!(
+        switcher.target(LocalTime::new(172, Duration::from_hours(12))),
+        Some("day")
+    );
+    assert_eq!(
+        switcher.target(LocalTime::new(172, Duration::from_hours(2))),
+        Some("night")
+    );
+}
@@ -0,0 +1,53 @@
+#![allow(missing_docs)]
+#![cfg(feature = "protobuf")]
+
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
+
+use high_flow_next::{
+    monitor::{proto, AlarmEvent, AlarmId, SensorReadings},
+    protocol::settings::{Conductivity, Flow, Temperature, WaterQuality},
+};
+use prost::Message;
+
+#[test]
+fn readings() {
+    let readings = SensorReadings {
+        captured_at: SystemTime::UNIX_EPOCH + Duration::from_millis(1_500),
+        flow: Flow::from_value(1234).unwrap(),
+        water_temperature: Some(Temperature::from_value(2550).unwrap()),
+        external_temperature: None,
+        conductivity: Conductivity::from_value(15).unwrap(),
+        water_quality: WaterQuality::from_value(9850).unwrap(),
+        power: 12.5,
+        voltage: 12.1,
+        external: BTreeMap::from([("pump/rpm".into(), 2400.0)]),
+    };
+
+    let message = proto::SensorReadings::from(&readings);
+    assert_eq!(message.captured_at_ms, 1_500);
+    assert!((message.flow - 123.4).abs() < 1e-9);
+    assert_eq!(message.water_temperature, Some(25.5));
+    assert_eq!(message.external_temperature, None);
+    assert_eq!(message.external.get("pump/rpm"), Some(&2400.0));
+
+    let decoded = proto::SensorReadings::decode(&*message.encode_to_vec()).unwrap();
+    assert_eq!(decoded, message);
+}
+
+#[test]
+fn alarm_event() {
+    let event = AlarmEvent::Cleared {
+        id: AlarmId(3),
+        value: 42.0,
+        at: SystemTime::UNIX_EPOCH + Duration::from_secs(10),
+    };
+
+    let message = proto::AlarmEvent::from(&event);
+    assert_eq!(message.kind(), proto::alarm_event::Kind::Cleared);
+    assert_eq!(message.alarm_id, 3);
+    assert_eq!(message.at_ms, 10_000);
+
+    let decoded = proto::AlarmEvent::decode(&*message.encode_to_vec()).unwrap();
+    assert_eq!(decoded, message);
+}
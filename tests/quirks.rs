@@ -0,0 +1,84 @@
+#![allow(missing_docs)]
+
+use high_flow_next::protocol::{
+    decode_frames, settings::Temperature, Frame, Quirk, QuirkDatabase, QuirkError, QuirkRule,
+};
+
+const EFFECTS: &[u8] = include_bytes!("assets/effects_0.frame");
+
+fn rule(min_firmware: u16, max_firmware: u16, quirk: Quirk) -> QuirkRule {
+    QuirkRule {
+        min_firmware,
+        max_firmware,
+        quirk,
+    }
+}
+
+#[test]
+fn apply() {
+    let mut quirks = QuirkDatabase::builtin();
+    quirks.add(rule(
+        1000,
+        1009,
+        Quirk::Scale {
+            path: "alarms.water_temperature_limit".into(),
+            numerator: 10,
+            denominator: 11,
+        },
+    ));
+    quirks.add(rule(
+        1005,
+        1009,
+        Quirk::Absent {
+            path: "alarms.water_temperature_limit".into(),
+        },
+    ));
+
+    let expected = decode_frames(EFFECTS).unwrap();
+    assert_eq!(quirks.decode_frames(1010, EFFECTS).unwrap(), expected);
+
+    let [Frame::Settings(settings)] = &quirks.decode_frames(1000, EFFECTS).unwrap()[..] else {
+        panic!("Expected a single frame");
+    };
+    assert_eq!(
+        settings.alarms.water_temperature_limit,
+        Some(Temperature::from_value(4100).unwrap())
+    );
+
+    let [Frame::Settings(settings)] = &quirks.decode_frames(1005, EFFECTS).unwrap()[..] else {
+        panic!("Expected a single frame");
+    };
+    assert_eq!(settings.alarms.water_temperature_limit, None);
+}
+
+#[test]
+fn invalid() {
+    let mut quirks = QuirkDatabase::new();
+    quirks.add(rule(
+        0,
+        u16::MAX,
+        Quirk::Absent {
+            path: "sensor.medium".into(),
+        },
+    ));
+    assert!(matches!(
+        quirks.decode_frames(1000, EFFECTS),
+        Err(QuirkError::ValueError(path, _)) if path == "sensor.medium"
+    ));
+
+    let mut quirks = QuirkDatabase::new();
+    quirks.add(rule(
+        0,
+        u16::MAX,
+        Quirk::Scale {
+            path: "alarms.unknown".into(),
+            numerator: 1,
+            denominator: 1,
+        },
+    ));
+    assert!(quirks.decode_frames(1000, EFFECTS).is_err());
+    assert!(matches!(
+        quirks.decode_frames(1000, &EFFECTS[..10]),
+        Err(QuirkError::IoError(_))
+    ));
+}
@@ -0,0 +1,78 @@
+#![allow(missing_docs)]
+#![cfg(feature = "rand")]
+
+use std::time::Duration;
+
+use rand::{rngs::SmallRng, SeedableRng};
+
+use high_flow_next::protocol::settings::{
+    simulate::{Inputs, Simulator},
+    Controller, Effect, EffectKind,
+};
+
+#[test]
+fn kinds() {
+    let mut rng = SmallRng::seed_from_u64(0);
+
+    for kind in EffectKind::ALL {
+        for _ in 0..100 {
+            assert_eq!(Effect::random(kind, &mut rng).kind(), kind);
+        }
+    }
+}
+
+#[test]
+fn valid_parameters() {
+    let mut rng = SmallRng::seed_from_u64(1);
+
+    for _ in 0..1000 {
+        match Effect::random_any(&mut rng) {
+            Effect::Blink(e) => assert!(!e.colors.is_empty()),
+            Effect::BarGraph(e) | Effect::SoundBars(e) => {
+                assert!(!e.colors.is_empty());
+                assert!(e.colors.windows(2).all(|w| w[0].1 <= w[1].1));
+                assert!(e.colors.iter().all(|c| c.1 <= e.end_value));
+            }
+            Effect::ColorSwitch(e) => {
+                assert!(e.colors.windows(2).all(|w| w[0].1 <= w[1].1));
+                assert!(e.colors.iter().all(|c| c.1 <= e.end_value));
+            }
+            Effect::ColorGradient(e) => {
+                assert!(e.colors.windows(2).all(|w| w[0].1 <= w[1].1));
+                assert!(e.colors.iter().all(|c| c.1 <= 1000));
+            }
+            Effect::Rain(e) | Effect::Snow(e) | Effect::Stardust(e) => {
+                assert!((1..=4).contains(&*e.items));
+                assert!((1..=100).contains(&*e.speed));
+            }
+            Effect::Static(e) => {
+                if let Some(sc) = e.source_control_brightness {
+                    assert!(sc.input_min < sc.input_max);
+                    assert!(sc.output_min <= sc.output_max && sc.output_max <= 100);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+#[test]
+fn simulate() {
+    let mut rng = SmallRng::seed_from_u64(2);
+
+    let controllers = EffectKind::ALL.map(|kind| Controller {
+        offset: 0,
+        length: 20,
+        effect: Effect::random(kind, &mut rng),
+        data_source: None,
+        sensor_attenuation_rising: 0,
+        sensor_attenuation_falling: 0,
+    });
+
+    for controller in controllers {
+        let mut simulator = Simulator::new([controller]);
+        let leds = simulator.render(Duration::from_secs(3), &Inputs::new());
+
+        assert_eq!(leds.len(), 20);
+    }
+}
@@ -0,0 +1,51 @@
+#![allow(missing_docs)]
+
+use std::fs::read;
+
+use high_flow_next::{misc::IoError, protocol::settings::ReservedFields};
+
+#[test]
+fn default() {
+    let data = read("tests/assets/default.frame").unwrap();
+    let reserved = ReservedFields::from_frame(&data).unwrap();
+
+    assert_eq!(
+        reserved,
+        ReservedFields {
+            version: 1,
+            display_after_brightness: [0x02, 0xBC, 0x02, 0x58],
+            trailing: 1,
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        reserved.iter().find(|(name, _)| *name == "version"),
+        Some(("version", vec![0x00, 0x01]))
+    );
+}
+
+#[test]
+fn changed() {
+    let data = read("tests/assets/default.frame").unwrap();
+    let old = ReservedFields::from_frame(&data).unwrap();
+
+    let mut data = data;
+    let len = data.len();
+    data[2] = 0x02;
+    data[len - 3] = 0xFF;
+    let new = ReservedFields::from_frame(&data).unwrap();
+
+    assert_eq!(old.changed(&new), vec!["version", "trailing"]);
+    assert!(old.changed(&old).is_empty());
+}
+
+#[test]
+fn invalid_op_code() {
+    let mut data = read("tests/assets/default.frame").unwrap();
+    data[0] = 0x42;
+
+    assert!(matches!(
+        ReservedFields::from_frame(&data),
+        Err(IoError::InvalidValue("OpCode", 0x42))
+    ));
+}
@@ -0,0 +1,65 @@
+#![allow(missing_docs)]
+
+use high_flow_next::protocol::{decode_frames, decode_frames_resync, ResyncedFrame};
+
+const DEFAULT: &[u8] = include_bytes!("assets/default.frame");
+const EFFECTS: &[u8] = include_bytes!("assets/effects_0.frame");
+
+#[test]
+fn in_sync() {
+    let data = [DEFAULT, EFFECTS].concat();
+    let expected = decode_frames(&data).unwrap();
+
+    let mut decoder = decode_frames_resync(&data);
+    let frames = decoder.by_ref().collect::<Vec<_>>();
+
+    assert_eq!(
+        frames,
+        expected
+            .into_iter()
+            .map(|frame| ResyncedFrame {
+                discarded: 0,
+                frame
+            })
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(decoder.discarded(), 0);
+}
+
+#[test]
+fn lost_sync() {
+    // Garbage, a truncated frame, a corrupted frame and trailing garbage
+    let mut corrupted = EFFECTS.to_vec();
+    corrupted[100] ^= 0xFF;
+
+    let data = [
+        &[0x03, 0x00, 0x42][..],
+        DEFAULT,
+        &EFFECTS[..200],
+        EFFECTS,
+        &corrupted,
+        DEFAULT,
+        &[0x03, 0x01],
+    ]
+    .concat();
+
+    let mut decoder = decode_frames_resync(&data);
+    let frames = decoder.by_ref().collect::<Vec<_>>();
+    let discarded = frames.iter().map(|x| x.discarded).collect::<Vec<_>>();
+
+    assert_eq!(frames.len(), 3);
+    assert_eq!(discarded, [3, 200, corrupted.len()]);
+    assert_eq!(
+        frames[1].frame,
+        decode_frames(EFFECTS).unwrap().pop().unwrap()
+    );
+    assert_eq!(decoder.discarded(), 3 + 200 + corrupted.len() + 2);
+}
+
+#[test]
+fn garbage_only() {
+    let mut decoder = decode_frames_resync(&DEFAULT[1..]);
+
+    assert_eq!(decoder.next(), None);
+    assert_eq!(decoder.discarded(), DEFAULT.len() - 1);
+}
@@ -0,0 +1,123 @@
+#![allow(missing_docs)]
+
+use std::fs::read;
+
+use high_flow_next::{
+    misc::{Decode, FixedSize},
+    protocol::{
+        settings::{ReservedFields, SettingsSchema},
+        Frame, Settings,
+    },
+};
+
+#[test]
+fn layout() {
+    let schema = SettingsSchema::new();
+
+    let mut offset = 0;
+    for field in &schema {
+        assert_eq!(field.offset, offset, "{}", field.name);
+        offset += field.len;
+    }
+    assert_eq!(offset, 1 + Settings::SIZE + 2);
+
+    let field = schema.field("alarms.water_temperature_limit").unwrap();
+    assert_eq!(field.len, 2);
+    assert_eq!(field.ty, "Temperature");
+    assert_eq!(field.range, Some(0..=10_000));
+
+    assert_eq!(schema.field_at(0).unwrap().name, "op_code");
+    assert_eq!(schema.field_at(2).unwrap().name, "reserved.version");
+    assert_eq!(schema.field_at(offset - 1).unwrap().name, "checksum");
+    assert!(schema.field_at(offset).is_none());
+}
+
+#[test]
+fn matches_decoder() {
+    let data = read("tests/assets/default.frame").unwrap();
+    let Frame::Settings(settings) = Frame::decode(&mut &data[..]).unwrap();
+    let reserved = ReservedFields::from_frame(&data).unwrap();
+
+    let schema = SettingsSchema::new();
+    let bytes = |name: &str| {
+        let field = schema.field(name).unwrap();
+
+        &data[field.offset..field.offset + field.len]
+    };
+
+    for (name, value) in reserved.iter() {
+        let prefix = format!("reserved.{name}");
+        let raw = schema
+            .iter()
+            .filter(|x| x.name == prefix || x.name.starts_with(&format!("{prefix}[")))
+            .flat_map(|x| data[x.offset..x.offset + x.len].iter().copied())
+            .collect::<Vec<_>>();
+
+        assert_eq!(raw, value, "{name}");
+    }
+
+    assert_eq!(
+        bytes("display.charts[1].interval"),
+        settings.display.charts[1].interval.to_be_bytes()
+    );
+    assert_eq!(
+        bytes("sensor.water_quality_min"),
+        settings.sensor.water_quality_min.to_be_bytes()
+    );
+    assert_eq!(
+        bytes("alarms.startup_delay"),
+        [*settings.alarms.startup_delay]
+    );
+}
+
+#[test]
+fn reserved_regions() {
+    let schema = SettingsSchema::new();
+    let reserved = ReservedFields::default();
+
+    for field in schema.iter().filter(|x| x.name.starts_with("reserved.")) {
+        let name = field.name["reserved.".len()..].split('[').next().unwrap();
+
+        assert!(
+            reserved.iter().any(|(x, _)| x == name),
+            "{} is missing in ReservedFields",
+            field.name
+        );
+    }
+}
+
+#[test]
+fn enumerations() {
+    let data = read("tests/assets/default.frame").unwrap();
+    let schema = SettingsSchema::new();
+
+    let field = schema.field("alarms.output_signal").unwrap();
+    assert_eq!(field.range, Some(0..=5));
+    assert_eq!(field.values, Some((0..=5).collect()));
+
+    let field = schema.field("lighting.controllers[0].data_source").unwrap();
+    let values = field.values.as_ref().unwrap();
+    assert_eq!(field.range, Some(0..=0xFFFF));
+    assert!(values.contains(&0x1C));
+    assert!(!values.contains(&0x0E));
+
+    for field in schema.iter().filter(|x| x.values.is_some()) {
+        let values = field.values.as_ref().unwrap();
+        let invalid = field.range.as_ref().unwrap().end() + 1;
+        let invalid = (invalid < 1 << (8 * field.len)).then_some(invalid);
+
+        for raw in values.iter().copied().chain(invalid) {
+            let mut payload = data[1..data.len() - 2].to_vec();
+            let offset = field.offset - 1;
+            let bytes = raw.to_be_bytes();
+            payload[offset..offset + field.len].copy_from_slice(&bytes[8 - field.len..]);
+
+            assert_eq!(
+                Settings::decode(&mut &payload[..]).is_ok(),
+                values.contains(&raw),
+                "{} = {raw}",
+                field.name
+            );
+        }
+    }
+}
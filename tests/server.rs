@@ -0,0 +1,171 @@
+#![allow(missing_docs)]
+#![cfg(feature = "server")]
+
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use high_flow_next::{
+    device::{Device, SettingsCache, Transport},
+    misc::IoError,
+    monitor::{SensorReadings, Sink},
+    protocol::settings::{Conductivity, Flow, Temperature, WaterQuality},
+    server::{ApiServer, ServerSink, ServerState},
+};
+use serde_json::Value;
+
+struct Replay(Vec<u8>);
+
+impl Transport for Replay {
+    fn get_feature_report(&mut self, buffer: &mut [u8]) -> Result<usize, IoError> {
+        buffer[..self.0.len()].copy_from_slice(&self.0);
+
+        Ok(self.0.len())
+    }
+
+    fn send_feature_report(&mut self, _data: &[u8]) -> Result<(), IoError> {
+        Ok(())
+    }
+}
+
+fn readings(secs: u64, flow: u16) -> SensorReadings {
+    SensorReadings {
+        captured_at: SystemTime::UNIX_EPOCH + Duration::from_secs(secs),
+        flow: Flow::from_value(flow).unwrap(),
+        water_temperature: Some(Temperature::from_value(2_500).unwrap()),
+        external_temperature: None,
+        conductivity: Conductivity::from_value(20).unwrap(),
+        water_quality: WaterQuality::from_value(9_500).unwrap(),
+        power: 12.5,
+        voltage: 5.02,
+        external: BTreeMap::from([("radiator".into(), 31.5)]),
+    }
+}
+
+fn json(body: &str) -> Value {
+    serde_json::from_str(body).unwrap()
+}
+
+#[test]
+fn endpoints() {
+    let state = Arc::new(Mutex::new(ServerState::new(10)));
+    let mut sink = ServerSink(state.clone());
+
+    let response = state.lock().unwrap().handle("GET", "/readings");
+    assert_eq!(response.status, 404);
+
+    sink.publish(&readings(1, 1_000)).unwrap();
+    sink.publish(&readings(2, 1_100)).unwrap();
+    sink.publish(&readings(3, 1_200)).unwrap();
+
+    let state = state.lock().unwrap();
+
+    let response = state.handle("GET", "/readings");
+    assert_eq!(response.status, 200);
+    assert_eq!(
+        json(&response.body),
+        serde_json::json!({
+            "timestamp": 3000,
+            "flow": 120.0,
+            "water_temperature": 25.0,
+            "conductivity": 20.0,
+            "water_quality": 95.0,
+            "power": 12.5,
+            "voltage": 5.02,
+            "external": { "radiator": 31.5 },
+        })
+    );
+
+    let response = state.handle("GET", "/history");
+    assert_eq!(json(&response.body).as_array().unwrap().len(), 3);
+
+    let response = state.handle("GET", "/history?from=2000&to=3000");
+    let history = json(&response.body);
+    assert_eq!(history.as_array().unwrap().len(), 1);
+    assert_eq!(history[0]["timestamp"], 2000);
+
+    let response = state.handle("GET", "/history?from=2000");
+    assert_eq!(json(&response.body).as_array().unwrap().len(), 2);
+
+    let response = state.handle("GET", "/history?_=abc&from=2000&format=table");
+    assert_eq!(response.status, 200);
+    assert_eq!(json(&response.body).as_array().unwrap().len(), 2);
+
+    assert_eq!(state.handle("GET", "/history?from=abc").status, 400);
+    assert_eq!(state.handle("GET", "/settings").status, 404);
+    assert_eq!(state.handle("GET", "/unknown").status, 404);
+    assert_eq!(state.handle("POST", "/readings").status, 405);
+}
+
+#[test]
+fn settings() {
+    let default = std::fs::read("tests/assets/default.frame").unwrap();
+    let mut device = Device::new(Replay(default));
+    let mut cache = SettingsCache::new(Duration::from_secs(5));
+
+    let mut state = ServerState::new(10);
+    state.update_settings(&cache);
+    assert!(state.settings.is_none());
+
+    cache.poll(&mut device, Instant::now()).unwrap();
+    state.update_settings(&cache);
+
+    let response = state.handle("GET", "/settings");
+    assert_eq!(response.status, 200);
+    assert!(json(&response.body).get("alarms").is_some());
+}
+
+#[test]
+fn serve() {
+    let state = Arc::new(Mutex::new(ServerState::new(10)));
+    ServerSink(state.clone())
+        .publish(&readings(1, 1_000))
+        .unwrap();
+
+    let server = ApiServer::bind("127.0.0.1:0", state.clone()).unwrap();
+    let addr = server.local_addr().unwrap();
+    let handle = thread::spawn(move || server.serve_one());
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(stream, "GET /readings HTTP/1.1\r\nHost: {addr}\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    handle.join().unwrap().unwrap();
+
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(head.contains("\r\nContent-Type: application/json\r\n"));
+    assert!(!head.contains("Access-Control-Allow-Origin"));
+    assert_eq!(json(body)["flow"], 100.0);
+
+    // Cross-origin requests are opt-in, oversized requests are rejected.
+    let server = ApiServer::bind("127.0.0.1:0", state)
+        .unwrap()
+        .with_cors("http://localhost:3000");
+    let addr = server.local_addr().unwrap();
+    let handle = thread::spawn(move || {
+        server.serve_one()?;
+        server.serve_one()
+    });
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(stream, "GET /readings HTTP/1.1\r\nHost: {addr}\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.contains("\r\nAccess-Control-Allow-Origin: http://localhost:3000\r\n"));
+
+    // The request is not terminated, so the server reads it completely and
+    // does not reset the connection.
+    let mut request = String::from("GET /readings HTTP/1.1\r\nX-Padding: ");
+    let size = usize::try_from(ApiServer::MAX_REQUEST_SIZE).unwrap();
+    request.extend(std::iter::repeat_n('x', size - request.len()));
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.write_all(request.as_bytes()).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    handle.join().unwrap().unwrap();
+    assert!(response.starts_with("HTTP/1.1 431 "));
+}
@@ -0,0 +1,138 @@
+#![allow(missing_docs)]
+#![cfg(feature = "config")]
+
+use std::fs::File;
+
+use high_flow_next::{
+    misc::Decode,
+    protocol::{
+        settings::{ConfigError, ConfigFormat, Variables},
+        Frame, Settings,
+    },
+};
+
+fn load(path: &str) -> Settings {
+    let mut reader = File::open(path).unwrap();
+    let Frame::Settings(settings) = Frame::decode(&mut reader).unwrap();
+
+    settings
+}
+
+#[test]
+fn round_trip() {
+    for path in [
+        "tests/assets/default.frame",
+        "tests/assets/effects_0.frame",
+        "tests/assets/effects_1.frame",
+        "tests/assets/effects_2.frame",
+    ] {
+        let settings = load(path);
+
+        for format in [ConfigFormat::Toml, ConfigFormat::Yaml] {
+            let config = settings.to_config_str(format).unwrap();
+            let actual = Settings::from_config_str(&config, format).unwrap();
+
+            assert_eq!(settings, actual, "{path} ({format:?})");
+        }
+    }
+}
+
+#[test]
+fn toml_format() {
+    let settings = load("tests/assets/default.frame");
+    let config = settings.to_config_str(ConfigFormat::Toml).unwrap();
+
+    assert!(config.starts_with("version = 1\n"), "{config}");
+    assert!(config.contains("water_temp_offset_celsius = "), "{config}");
+    assert!(config.contains("power_damping_mw = "), "{config}");
+    assert!(config.contains("flow_l_per_h = "), "{config}");
+}
+
+#[test]
+fn version_check() {
+    let settings = load("tests/assets/default.frame");
+    let config = settings.to_config_str(ConfigFormat::Toml).unwrap();
+    let config = config.replacen("version = 1", "version = 2", 1);
+
+    assert!(matches!(
+        Settings::from_config_str(&config, ConfigFormat::Toml),
+        Err(ConfigError::UnsupportedVersion(2))
+    ));
+}
+
+#[test]
+fn range_check() {
+    let settings = load("tests/assets/default.frame");
+    let config = settings.to_config_str(ConfigFormat::Yaml).unwrap();
+    let config = config
+        .lines()
+        .map(|line| match line.split_once("water_temp_offset_celsius:") {
+            Some((indent, _)) => format!("{indent}water_temp_offset_celsius: 20.0"),
+            None => line.to_owned(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let Err(ConfigError::RangeError("water_temp_offset_celsius", err)) =
+        Settings::from_config_str(&config, ConfigFormat::Yaml)
+    else {
+        panic!("Expected range error");
+    };
+
+    let typed = err.downcast_ref::<i16>().unwrap();
+    assert_eq!((typed.min, typed.max, typed.val), (-1500, 1500, 2000));
+    assert!(err.downcast_ref::<u16>().is_none());
+    assert_eq!(err.to_f64().unwrap().val.to_bits(), 2000.0_f64.to_bits());
+    assert_eq!(err.to_owned().max, "1500");
+}
+
+#[test]
+fn format_from_path() {
+    assert_eq!(
+        ConfigFormat::from_path("hfn.toml"),
+        Some(ConfigFormat::Toml)
+    );
+    assert_eq!(ConfigFormat::from_path("hfn.yml"), Some(ConfigFormat::Yaml));
+    assert_eq!(ConfigFormat::from_path("hfn.json"), None);
+}
+
+#[test]
+fn template() {
+    let settings = load("tests/assets/default.frame");
+    let config = settings.to_config_str(ConfigFormat::Toml).unwrap();
+    let template = config
+        .lines()
+        .map(
+            |line| match line.split_once("water_temp_offset_celsius =") {
+                Some((indent, _)) => {
+                    format!("{indent}water_temp_offset_celsius = ${{TEMP_OFFSET}}")
+                }
+                None => line.to_owned(),
+            },
+        )
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let vars = Variables::parse("# offsets\nTEMP_OFFSET = \"-1.25\"\n");
+    let settings = Settings::from_config_template(&template, ConfigFormat::Toml, &vars).unwrap();
+    assert_eq!(*settings.sensor.water_temp_offset, -125);
+
+    assert!(matches!(
+        Settings::from_config_template(&template, ConfigFormat::Toml, &Variables::new()),
+        Err(ConfigError::UndefinedVariable(name)) if name == "TEMP_OFFSET"
+    ));
+
+    let mut vars = Variables::new();
+    vars.set("A", "1");
+    assert_eq!(
+        vars.substitute("$$A=${A}, B=${B:-2}, $x").unwrap(),
+        "$A=1, B=2, $x"
+    );
+    assert!(matches!(
+        vars.substitute("a = ${A"),
+        Err(ConfigError::InvalidTemplate(4))
+    ));
+
+    let vars = Variables::parse("A = 1\nB = 2").merge(Variables::parse("B = 3"));
+    assert_eq!((vars.get("A"), vars.get("B")), (Some("1"), Some("3")));
+}
@@ -0,0 +1,67 @@
+#![allow(missing_docs)]
+
+use std::fs::File;
+
+use high_flow_next::{
+    misc::Decode,
+    protocol::{
+        settings::{Change, Settings, SettingsDiff, Value},
+        Frame,
+    },
+};
+
+fn load(path: &str) -> Settings {
+    let mut reader = File::open(path).unwrap();
+    let Frame::Settings(settings) = Frame::decode(&mut reader).unwrap();
+
+    settings
+}
+
+#[test]
+fn equal() {
+    let settings = load("tests/assets/default.frame");
+    let diff = SettingsDiff::new(&settings, &settings.clone());
+
+    assert!(diff.is_empty());
+    assert_eq!(diff.to_string(), "");
+}
+
+#[test]
+fn changed() {
+    let old = load("tests/assets/effects_0.frame");
+    let new = load("tests/assets/effects_1.frame");
+    let diff = SettingsDiff::new(&old, &new);
+
+    let change = |path: &str| diff.iter().find(|x| x.path == path).cloned();
+
+    assert_eq!(
+        change("lighting.strip_controllers[0].effect"),
+        Some(Change {
+            path: "lighting.strip_controllers[0].effect".into(),
+            old: Some("Static".into()),
+            new: Some("Flame".into()),
+            old_value: Some(Value::Enum("Static".into())),
+            new_value: Some(Value::Enum("Flame".into())),
+        })
+    );
+    assert_eq!(
+        change("lighting.strip_controllers[5].sensor_attenuation_rising"),
+        Some(Change {
+            path: "lighting.strip_controllers[5].sensor_attenuation_rising".into(),
+            old: Some("29".into()),
+            new: Some("10".into()),
+            old_value: Some(Value::Integer(29)),
+            new_value: Some(Value::Integer(10)),
+        })
+    );
+    assert_eq!(
+        change("lighting.strip_controllers[4].data_source")
+            .unwrap()
+            .to_string(),
+        "lighting.strip_controllers[4].data_source: None -> Some(Flow)"
+    );
+
+    let change = change("lighting.strip_controllers[4].data_source").unwrap();
+    assert_eq!(change.old_value, Some(Value::None));
+    assert_eq!(change.new_value, Some(Value::Enum("Flow".into())));
+}
@@ -0,0 +1,130 @@
+#![allow(missing_docs)]
+
+//! Fuzzing of the settings decoder through the device layer.
+//!
+//! Settings frames are generated by mutating the bytes of the captured
+//! frames (with a valid checksum), written as raw reports to an emulator
+//! through the audited device path, read back and compared with the directly
+//! decoded frame. Both sides use the same decoder, so this checks the device
+//! path, the diff and the serde representation against the decoder, but not
+//! an encoder (settings can not be encoded, see the `device` module).
+
+use std::fs::read;
+
+use high_flow_next::{
+    audit::{AuditRecord, AuditedTransport},
+    device::{Device, Transport},
+    misc::{checksum, Decode, IoError},
+    protocol::{
+        settings::{Access, Settings, SettingsDiff},
+        Frame,
+    },
+};
+use proptest::prelude::*;
+
+const FRAMES: [&str; 4] = [
+    "tests/assets/default.frame",
+    "tests/assets/effects_0.frame",
+    "tests/assets/effects_1.frame",
+    "tests/assets/effects_2.frame",
+];
+
+/// Transport that returns the last settings report sent to it.
+struct Emulator {
+    settings: Vec<u8>,
+}
+
+impl Transport for Emulator {
+    fn get_feature_report(&mut self, buffer: &mut [u8]) -> Result<usize, IoError> {
+        buffer[..self.settings.len()].copy_from_slice(&self.settings);
+
+        Ok(self.settings.len())
+    }
+
+    fn send_feature_report(&mut self, data: &[u8]) -> Result<(), IoError> {
+        if data[0] == 0x03 {
+            self.settings = data.to_vec();
+        }
+
+        Ok(())
+    }
+}
+
+fn decode(frame: &[u8]) -> Result<Settings, IoError> {
+    let Frame::Settings(settings) = Frame::decode(&mut &frame[..])?;
+
+    Ok(settings)
+}
+
+/// Applies the `mutations` (offset into the payload, new value) to the frame
+/// and fixes the checksum.
+fn mutate(mut frame: Vec<u8>, mutations: &[(usize, u8)]) -> Vec<u8> {
+    let size = Frame::size(frame[0]).unwrap();
+    let payload = 1..size - 2;
+
+    for (offset, value) in mutations {
+        frame[payload.start + offset % payload.len()] = *value;
+    }
+
+    let crc = checksum(&frame[payload.clone()]);
+    frame[payload.end..size].copy_from_slice(&crc.to_be_bytes());
+
+    frame
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    fn settings_roundtrip(
+        index in 0..FRAMES.len(),
+        mutations in prop::collection::vec((any::<usize>(), any::<u8>()), 0..16),
+    ) {
+        let original = read(FRAMES[index]).unwrap();
+        let base = decode(&original).unwrap();
+        let frame = mutate(original.clone(), &mutations);
+        let direct = decode(&frame);
+
+        let transport = AuditedTransport::new(
+            Emulator { settings: original.clone() },
+            Vec::<AuditRecord>::new(),
+        );
+        let mut device = Device::new(transport);
+
+        let written = device.write_report(&frame);
+        prop_assert_eq!(written.is_ok(), direct.is_ok());
+
+        let Ok(settings) = direct else {
+            return Ok(());
+        };
+
+        // The device path decodes the same settings as the frame decoder.
+        let read_back = device.read_settings().unwrap();
+        prop_assert_eq!(&read_back, &settings);
+        prop_assert_eq!(device.report(), &frame[..]);
+
+        // The diff matches the changed bytes and the changed values.
+        let diff = SettingsDiff::new(&base, &settings);
+        prop_assert_eq!(diff.is_empty(), base == settings);
+        if frame == original {
+            prop_assert!(diff.is_empty());
+        }
+        for change in &diff {
+            if let Some(value) = &change.new_value {
+                prop_assert_eq!(settings.get(&change.path), Some(value.clone()));
+            }
+        }
+
+        // The audit trail records the same diff.
+        let records = device.transport().store();
+        prop_assert_eq!(records.len(), 1);
+        prop_assert_eq!(&records[0].diff, &diff);
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&settings).unwrap();
+            let decoded: Settings = serde_json::from_str(&json).unwrap();
+            prop_assert_eq!(decoded, settings);
+        }
+    }
+}
@@ -3,15 +3,18 @@
 use std::fs::File;
 
 use high_flow_next::{
-    misc::Decode,
+    misc::{Decode, FixedSize, IoError, PositionReader},
     protocol::{
+        decode_frames,
         settings::{
-            AlarmFlags, Chart, ChartInterval, ChartSource, Color, ConnectorType, DataSource,
-            DisplayBrightness, DisplayFlags, Effect, Flow, FlowCorrection, FlowUnit, Medium,
-            OutputSignal, PageFlags, PowerFlags, SoundEffect, SoundEffectSpeed, SourceControl,
-            StandbyFlags, Temperature, TemperatureUnit,
+            AlarmConfig, AlarmFlags, AlarmIndicator, AlarmSettings, AquaBusAddress, Chart,
+            ChartInterval, ChartSource, Color, ConnectorType, Controller, ControllerGroup,
+            DataSource, DisabledControllers, DisplayBrightness, DisplayFlags, DisplaySettings,
+            Effect, EffectPercent, Flow, FlowCorrection, FlowUnit, Medium, OutputSignal, PageFlags,
+            PowerFlags, SensorSettings, SoundEffect, SoundEffectSpeed, SourceControl, StandbyFlags,
+            SystemSettings, Temperature, TemperatureUnit, ToggleError, WaterQuality,
         },
-        Frame,
+        Frame, Settings,
     },
 };
 
@@ -43,7 +46,7 @@ fn default() {
     /* System */
 
     assert_eq!(values.system.standby_flags, StandbyFlags::empty());
-    assert_eq!(*values.system.aqua_bus_address, 58);
+    assert_eq!(values.system.aqua_bus_address, AquaBusAddress::First);
     assert_eq!(values.system.increased_current_draw, None);
 
     /* Sensor */
@@ -354,7 +357,7 @@ fn effects_0() {
             | StandbyFlags::LEDS_DISABLED
             | StandbyFlags::DISABLE_VOLUME_COUNTER
     );
-    assert_eq!(*values.system.aqua_bus_address, 58);
+    assert_eq!(values.system.aqua_bus_address, AquaBusAddress::First);
     assert_eq!(values.system.increased_current_draw.as_deref(), Some(&600));
 
     /* Sensor */
@@ -713,7 +716,7 @@ fn effects_1() {
             | StandbyFlags::LEDS_DISABLED
             | StandbyFlags::DISABLE_VOLUME_COUNTER
     );
-    assert_eq!(*values.system.aqua_bus_address, 58);
+    assert_eq!(values.system.aqua_bus_address, AquaBusAddress::First);
     assert_eq!(values.system.increased_current_draw.as_deref(), Some(&600));
 
     /* Sensor */
@@ -1050,7 +1053,7 @@ fn effects_2() {
             | StandbyFlags::LEDS_DISABLED
             | StandbyFlags::DISABLE_VOLUME_COUNTER
     );
-    assert_eq!(*values.system.aqua_bus_address, 58);
+    assert_eq!(values.system.aqua_bus_address, AquaBusAddress::First);
     assert_eq!(values.system.increased_current_draw.as_deref(), Some(&600));
 
     /* Sensor */
@@ -1263,3 +1266,363 @@ fn effects_2() {
     assert!(strip.next().is_none());
     assert!(sensor.next().is_none());
 }
+
+#[test]
+fn multiple_frames() {
+    let paths = [
+        "tests/assets/default.frame",
+        "tests/assets/effects_0.frame",
+        "tests/assets/effects_1.frame",
+        "tests/assets/effects_2.frame",
+    ];
+    let data = paths.map(|path| std::fs::read(path).unwrap()).concat();
+
+    let frames = decode_frames(&data).unwrap();
+    assert_eq!(frames.len(), paths.len());
+
+    for (path, frame) in paths.iter().zip(&frames) {
+        let mut reader = File::open(path).unwrap();
+        assert_eq!(&Frame::decode(&mut reader).unwrap(), frame, "{path}");
+    }
+
+    assert!(decode_frames(&data[..data.len() - 1]).is_err());
+    assert!(decode_frames(&[]).unwrap().is_empty());
+}
+
+#[test]
+fn color() {
+    assert_eq!(std::mem::size_of::<Color>(), 4);
+
+    let color = Color::from_bytes([2, 0x80, 0xFF, 0x33]);
+    let hsv = color.hsv();
+    assert!((hsv.h - (120.0 + 60.0 * 128.0 / 255.0)).abs() < 1e-9);
+    assert!((hsv.s - 1.0).abs() < 1e-9);
+    assert!((hsv.v - 0.2).abs() < 1e-9);
+
+    assert_eq!(
+        Color::from_hsv(hsv.h, hsv.s, hsv.v).to_bytes(),
+        color.to_bytes()
+    );
+    assert_eq!(
+        Color::from_bytes([0, 0xFF, 0xFF, 0xFF]),
+        Color::from_bytes([1, 0, 0xFF, 0xFF])
+    );
+
+    // Invalid hue sections are clamped to the last section.
+    let color = Color::from_bytes([7, 0xFF, 0xFF, 0xFF]);
+    assert_eq!(color.to_bytes(), [5, 0xFF, 0xFF, 0xFF]);
+    assert!((color.hsv().h - 360.0).abs() < 1e-9);
+}
+
+#[test]
+fn color_srgb_and_kelvin() {
+    assert_eq!(
+        Color::from_rgb_srgb(255, 128, 0).to_bytes(),
+        Color::from_rgb(255, 55, 0).to_bytes()
+    );
+    assert_eq!(Color::from_rgb_srgb(6, 6, 6).to_bytes()[3], 0);
+    assert_eq!(
+        Color::from_rgb_srgb(255, 255, 255),
+        Color::from_rgb(255, 255, 255)
+    );
+
+    let white = Color::from_kelvin(6600.0).hsv();
+    assert!(white.s < 0.01 && (white.v - 1.0).abs() < 1e-9);
+
+    let warm = Color::from_kelvin(2700.0).hsv();
+    assert!((15.0..25.0).contains(&warm.h) && warm.s > 0.85);
+
+    let cold = Color::from_kelvin(20000.0).hsv();
+    assert!((220.0..230.0).contains(&cold.h));
+
+    assert_eq!(Color::from_kelvin(100.0), Color::from_kelvin(1000.0));
+}
+
+#[test]
+fn quantization() {
+    let exact = Color::quantization_error(120.0, 1.0, 0.2);
+    assert_eq!((exact.h, exact.s, exact.v), (0.0, 0.0, 0.0));
+
+    let error = Color::quantization_error(359.99, 0.5, 0.5);
+    assert!(error.h.abs() <= 60.0 / 510.0 + 1e-9);
+    assert!(error.s.abs() <= 1.0 / 510.0 && error.s != 0.0);
+    assert!(error.v.abs() <= 1.0 / 510.0);
+
+    assert_eq!(Color::quantization_error_rgb(255, 0, 0), [0, 0, 0]);
+    assert!(Color::quantization_error_rgb(5, 10, 240)
+        .iter()
+        .any(|x| *x != 0));
+
+    assert_eq!(*EffectPercent::from_percent(33.4), 33);
+    assert!((EffectPercent::quantization_error(33.4) + 0.4).abs() < 1e-9);
+    assert_eq!(*EffectPercent::from_percent(150.0), 100);
+    assert_eq!(*EffectPercent::from_percent(f64::NAN), 0);
+
+    assert_eq!(*FlowCorrection::from_percent(-12.345), -1235);
+    assert!((FlowCorrection::from_percent(-12.34).percent() + 12.34).abs() < 1e-9);
+    assert!(WaterQuality::quantization_error(50.005).abs() < 0.01);
+}
+
+#[test]
+fn standby_presets() {
+    let dark = SystemSettings::fully_dark_standby();
+    assert!(dark
+        .standby_flags
+        .contains(StandbyFlags::DISABLE_ALARM_DETECT));
+    assert_eq!(dark.aqua_bus_address, AquaBusAddress::First);
+
+    let armed = SystemSettings::keep_alarms_armed().standby_flags;
+    assert!(!armed.contains(StandbyFlags::DISABLE_ALARM_DETECT));
+    assert_eq!(
+        armed.describe(),
+        [
+            "Enters standby if USB is not connected.",
+            "Enters standby if the USB host is suspended.",
+            "Turns the display off in standby.",
+            "Turns the LEDs off in standby.",
+        ]
+    );
+
+    assert_eq!(
+        (StandbyFlags::DISPLAY_OFF | StandbyFlags::LEDS_DISABLED).describe(),
+        ["Never enters standby, the other standby flags have no effect."]
+    );
+}
+
+#[test]
+fn constructors() {
+    let mut reader = File::open("tests/assets/default.frame").unwrap();
+    let Frame::Settings(expected) = Frame::decode(&mut reader).unwrap();
+
+    let system = SystemSettings::new(
+        expected.system.standby_flags,
+        expected.system.aqua_bus_address,
+    );
+
+    let sensor = &expected.sensor;
+    let mut sensor = SensorSettings::new(
+        sensor.medium,
+        sensor.connector_type,
+        sensor.flow_correction,
+        sensor.water_quality_max,
+        sensor.water_quality_min,
+    );
+    sensor.power_flags = expected.sensor.power_flags;
+    sensor.power_damping = expected.sensor.power_damping;
+
+    let mut alarms = AlarmSettings::new(expected.alarms.output_signal);
+    assert!(alarms.describe().is_empty());
+    alarms.flags = expected.alarms.flags;
+    alarms.startup_delay = expected.alarms.startup_delay;
+    AlarmConfig::from(&expected).apply(&mut alarms).unwrap();
+
+    let display = &expected.display;
+    let mut display = DisplaySettings::new(
+        display.temperature_unit,
+        display.flow_unit,
+        display.page_flags,
+        display.display_brightness,
+        display.charts.clone(),
+    );
+    display.display_flags = expected.display.display_flags;
+    display.next_page_interval = expected.display.next_page_interval;
+    display.idle_display_brightness = expected.display.idle_display_brightness;
+
+    let mut settings = Settings::new(system, sensor, alarms, display);
+    assert_eq!(settings.lighting, None);
+    settings.lighting.clone_from(&expected.lighting);
+
+    assert_eq!(settings, expected);
+}
+
+#[test]
+fn alarm_config() {
+    let mut reader = File::open("tests/assets/default.frame").unwrap();
+    let Frame::Settings(settings) = Frame::decode(&mut reader).unwrap();
+    let mut alarms = settings.alarms;
+
+    AlarmConfig::new()
+        .flow_below(60.0)
+        .water_temp_above(43.0)
+        .indicator(AlarmIndicator::ACOUSTIC | AlarmIndicator::OPTICAL)
+        .startup_delay(10)
+        .disable_signal_output_during_alarm(false)
+        .apply(&mut alarms)
+        .unwrap();
+
+    assert_eq!(
+        alarms.flow_alarm_limit,
+        Some(Flow::from_value(600).unwrap())
+    );
+    assert_eq!(
+        alarms.water_temperature_limit,
+        Some(Temperature::from_value(4300).unwrap())
+    );
+    assert_eq!(alarms.external_temperature_limit, None);
+    assert_eq!(alarms.water_quality_limit, None);
+    assert!(alarms
+        .flags
+        .contains(AlarmFlags::ENABLE_ACUSTIC_INDICATOR | AlarmFlags::ENABLE_OPTICAL_INDICATOR));
+    assert_eq!(
+        alarms.describe(),
+        [
+            "Raises an alarm if the flow drops below 60.0 l/h.",
+            "Raises an alarm if the water temperature rises above 43.00 °C.",
+            "Beeps during an alarm.",
+            "Flashes the red ring during an alarm.",
+            "Ignores alarms for 10 s after startup.",
+        ]
+    );
+
+    let config = AlarmConfig::from(&alarms);
+    assert_eq!(config.build(&alarms).unwrap(), alarms);
+
+    let before = alarms.clone();
+    let err = AlarmConfig::new()
+        .water_temp_above(150.0)
+        .apply(&mut alarms)
+        .unwrap_err();
+    let IoError::RangeError(err) = err else {
+        panic!("Expected range error");
+    };
+    let err = err.downcast::<f64>().unwrap();
+    assert_eq!(err.max.to_bits(), 100.0_f64.to_bits());
+    assert_eq!(alarms, before);
+}
+
+#[test]
+fn temperature_unit_limits() {
+    use TemperatureUnit::{C, F};
+
+    let mut reader = File::open("tests/assets/default.frame").unwrap();
+    let Frame::Settings(mut settings) = Frame::decode(&mut reader).unwrap();
+    assert_eq!(settings.display.temperature_unit, C);
+
+    assert_eq!(settings.water_temperature_limit_in(C), Some(45.0));
+    assert_eq!(settings.water_temperature_limit_in(F), Some(113.0));
+    assert_eq!(settings.external_temperature_limit_in(C), None);
+
+    // 45 °C exceeds the maximum of 100 °F.
+    let before = settings.clone();
+    let err = settings.set_temperature_unit(F).unwrap_err();
+    assert!(matches!(err, IoError::RangeError(_)));
+    assert_eq!(settings, before);
+
+    settings
+        .set_water_temperature_limit_in(Some(30.0), C)
+        .unwrap();
+    settings.set_temperature_unit(F).unwrap();
+    assert_eq!(settings.display.temperature_unit, F);
+    assert_eq!(
+        settings.alarms.water_temperature_limit,
+        Some(Temperature::from_value(8600).unwrap())
+    );
+    assert_eq!(settings.water_temperature_limit_in(C), Some(30.0));
+
+    settings
+        .set_external_temperature_limit_in(Some(20.0), C)
+        .unwrap();
+    assert_eq!(
+        settings.alarms.external_temperature_limit,
+        Some(Temperature::from_value(6800).unwrap())
+    );
+
+    let config = AlarmConfig::from(&settings);
+    assert_eq!(config.build_for(&settings).unwrap(), settings.alarms);
+
+    let before = settings.clone();
+    let err = settings
+        .set_water_temperature_limit_in(Some(60.0), C)
+        .unwrap_err();
+    let IoError::RangeError(err) = err else {
+        panic!("Expected range error");
+    };
+    let err = err.downcast::<f64>().unwrap();
+    assert!((err.max - 37.78).abs() < 0.01);
+    assert_eq!(settings, before);
+
+    settings.set_water_temperature_limit_in(None, F).unwrap();
+    assert_eq!(settings.alarms.water_temperature_limit, None);
+
+    let mut alarms = settings.alarms.clone();
+    AlarmConfig::new()
+        .water_temp_above(35.0)
+        .apply_to(&mut settings)
+        .unwrap();
+    AlarmConfig::new()
+        .water_temp_above(35.0)
+        .apply(&mut alarms)
+        .unwrap();
+    assert_eq!(
+        settings.alarms.water_temperature_limit,
+        Some(Temperature::from_value(9500).unwrap())
+    );
+    assert_eq!(
+        alarms.water_temperature_limit,
+        Some(Temperature::from_value(3500).unwrap())
+    );
+}
+
+#[test]
+fn disabled_controllers() {
+    let mut reader = File::open("tests/assets/default.frame").unwrap();
+    let Frame::Settings(values) = Frame::decode(&mut reader).unwrap();
+    let original = values.lighting.unwrap();
+    let mut lighting = original.clone();
+    let mut profile = DisabledControllers::new();
+
+    profile
+        .disable(&mut lighting, ControllerGroup::Strip, 1, "case")
+        .unwrap();
+    assert_eq!(
+        lighting.strip_controllers.len(),
+        original.strip_controllers.len() - 1
+    );
+    assert_eq!(
+        profile.get("case").unwrap().controller,
+        original.strip_controllers[1]
+    );
+    assert_eq!(
+        profile.disable(&mut lighting, ControllerGroup::Strip, 0, "case"),
+        Err(ToggleError::NameInUse("case".into()))
+    );
+    assert_eq!(
+        profile.disable(&mut lighting, ControllerGroup::Sensor, 9, "sensor"),
+        Err(ToggleError::UnknownController(ControllerGroup::Sensor, 9))
+    );
+
+    assert!(profile
+        .toggle(&mut lighting, ControllerGroup::Strip, 1, "case")
+        .unwrap());
+    assert!(profile.is_empty());
+    assert_eq!(lighting, original);
+
+    assert_eq!(
+        profile.enable(&mut lighting, "case"),
+        Err(ToggleError::UnknownName("case".into()))
+    );
+}
+
+fn decoded_size<T: Decode>(data: &[u8]) -> usize {
+    let mut data = data;
+    let mut reader = PositionReader::new(&mut data);
+    T::decode(&mut reader).unwrap();
+
+    reader.position()
+}
+
+#[test]
+fn fixed_sizes() {
+    let data = [0; 128];
+
+    assert_eq!(
+        decoded_size::<Option<Controller>>(&data),
+        Option::<Controller>::SIZE
+    );
+    assert_eq!(decoded_size::<SourceControl>(&data), SourceControl::SIZE);
+    assert_eq!(decoded_size::<Color>(&data), Color::SIZE);
+    assert_eq!(decoded_size::<[Flow; 10]>(&data), <[Flow; 10]>::SIZE);
+
+    let frame = std::fs::read("tests/assets/default.frame").unwrap();
+    assert_eq!(decoded_size::<Settings>(&frame[1..]), Settings::SIZE);
+}
@@ -0,0 +1,207 @@
+#![allow(missing_docs)]
+#![cfg(feature = "serde")]
+
+use std::fs::File;
+
+use high_flow_next::{
+    misc::Decode,
+    protocol::{
+        settings::{Color, Effect, EffectKind, EffectRecord, EffectStatic, Flow, ParamValue},
+        Frame, Settings,
+    },
+};
+
+fn load(path: &str) -> Settings {
+    let mut reader = File::open(path).unwrap();
+    let Frame::Settings(settings) = Frame::decode(&mut reader).unwrap();
+
+    settings
+}
+
+#[test]
+fn json_round_trip() {
+    for path in [
+        "tests/assets/default.frame",
+        "tests/assets/effects_0.frame",
+        "tests/assets/effects_1.frame",
+        "tests/assets/effects_2.frame",
+    ] {
+        let settings = load(path);
+
+        let json = serde_json::to_string(&settings).unwrap();
+        let actual = serde_json::from_str::<Settings>(&json).unwrap();
+
+        assert_eq!(settings, actual, "{path}");
+    }
+}
+
+#[test]
+fn json_format() {
+    let settings = load("tests/assets/default.frame");
+    let json = serde_json::to_value(&settings).unwrap();
+
+    assert_eq!(json["sensor"]["flow_correction"][0][0], 200);
+    assert_eq!(json["sensor"]["medium"], "DpUltra");
+
+    // The value is stored in steps of 1/255, so 0.5 is rounded to 128/255.
+    let color = serde_json::to_value(Color::from_hsv(120.0, 1.0, 0.5)).unwrap();
+    let error = Color::quantization_error(120.0, 1.0, 0.5);
+    assert_eq!((error.h, error.s), (0.0, 0.0));
+    assert!(error.v > 0.0 && error.v <= 1.0 / 510.0);
+    assert_eq!(
+        color,
+        serde_json::json!({ "h": 120.0, "s": 1.0, "v": 0.5 + error.v })
+    );
+}
+
+#[test]
+fn json_range_check() {
+    assert_eq!(*serde_json::from_str::<Flow>("3000").unwrap(), 3000);
+    assert!(serde_json::from_str::<Flow>("3001").is_err());
+}
+
+#[test]
+fn postcard_round_trip() {
+    for path in [
+        "tests/assets/default.frame",
+        "tests/assets/effects_0.frame",
+        "tests/assets/effects_1.frame",
+        "tests/assets/effects_2.frame",
+    ] {
+        let settings = load(path);
+
+        let bytes = postcard::to_stdvec(&settings).unwrap();
+        let actual = postcard::from_bytes::<Settings>(&bytes).unwrap();
+
+        assert_eq!(settings, actual, "{path}");
+        assert!(bytes.len() < serde_json::to_vec(&settings).unwrap().len() / 4);
+    }
+}
+
+#[test]
+fn postcard_color() {
+    let color = Color::from_bytes([0x02, 0x80, 0xFF, 0x40]);
+    let bytes = postcard::to_stdvec(&color).unwrap();
+
+    assert_eq!(bytes, [0x02, 0x80, 0xFF, 0x40]);
+    assert_eq!(postcard::from_bytes::<Color>(&bytes).unwrap(), color);
+}
+
+#[test]
+fn json_legacy_effects() {
+    for name in ["default", "effects_0", "effects_1", "effects_2"] {
+        let settings = load(&format!("tests/assets/{name}.frame"));
+
+        let json = std::fs::read_to_string(format!("tests/assets/legacy/{name}.json")).unwrap();
+        let actual = serde_json::from_str::<Settings>(&json).unwrap();
+
+        assert_eq!(settings, actual, "{name}");
+    }
+}
+
+#[test]
+fn json_effect_format() {
+    let settings = load("tests/assets/default.frame");
+    let effect = &settings.lighting.as_ref().unwrap().strip_controllers[0].effect;
+
+    let json = serde_json::to_value(effect).unwrap();
+    assert_eq!(json["kind"], "Rainbow");
+    assert_eq!(json["params"]["speed"], 50);
+
+    // Unknown parameters and additional fields are ignored, missing optional
+    // parameters are accepted.
+    let json = serde_json::json!({
+        "kind": "Static",
+        "params": { "color": { "h": 120.0, "s": 1.0, "v": 0.5 }, "glow": [1, 2] },
+        "version": 2,
+    });
+    let effect = serde_json::from_value::<Effect>(json.clone()).unwrap();
+    let Effect::Static(EffectStatic {
+        color,
+        source_control_brightness,
+        ..
+    }) = &effect
+    else {
+        panic!("Expected static effect");
+    };
+    assert_eq!(*color, Color::from_hsv(120.0, 1.0, 0.5));
+    assert_eq!(*source_control_brightness, None);
+
+    let record = serde_json::from_value::<EffectRecord>(json).unwrap();
+    assert_eq!(record.effect, effect);
+    assert_eq!(
+        record.unknown["glow"],
+        ParamValue::List(vec![ParamValue::UInt(1), ParamValue::UInt(2)])
+    );
+
+    let saved = serde_json::to_value(&record).unwrap();
+    assert_eq!(saved["kind"], "Static");
+    assert_eq!(saved["params"]["glow"], serde_json::json!([1, 2]));
+    assert_eq!(
+        serde_json::from_value::<EffectRecord>(saved).unwrap(),
+        record
+    );
+    assert_eq!(
+        serde_json::to_value(EffectRecord::from(effect.clone())).unwrap(),
+        serde_json::to_value(&effect).unwrap()
+    );
+
+    let err = serde_json::from_str::<Effect>(r#"{ "kind": "Fireworks", "params": {} }"#)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("unknown effect kind `Fireworks`"), "{err}");
+    assert!(serde_json::from_str::<Effect>(r#"{ "kind": "Static" }"#).is_err());
+    assert!(serde_json::from_str::<Effect>(r#"{ "params": {} }"#).is_err());
+
+    for kind in EffectKind::ALL {
+        assert_eq!(serde_json::to_value(kind).unwrap(), kind.name());
+    }
+}
+
+#[test]
+fn json_params_before_kind() {
+    for path in [
+        "tests/assets/effects_0.frame",
+        "tests/assets/effects_1.frame",
+        "tests/assets/effects_2.frame",
+    ] {
+        for controller in &load(path).lighting.unwrap().strip_controllers {
+            let effect = &controller.effect;
+            let json = serde_json::to_value(effect).unwrap();
+            let json = format!(
+                r#"{{ "params": {}, "kind": {} }}"#,
+                json["params"], json["kind"]
+            );
+
+            assert_eq!(&serde_json::from_str::<Effect>(&json).unwrap(), effect);
+        }
+    }
+
+    let json = r#"{
+        "params": { "color": { "h": 120.0, "s": 1.0, "v": 0.5 }, "glow": 3 },
+        "kind": "Static"
+    }"#;
+    let record = serde_json::from_str::<EffectRecord>(json).unwrap();
+    assert_eq!(record.effect.kind(), EffectKind::Static);
+    assert_eq!(record.unknown["glow"], ParamValue::UInt(3));
+
+    let json = r#"{ "params": {}, "params": {}, "kind": "Static" }"#;
+    assert!(serde_json::from_str::<Effect>(json).is_err());
+}
+
+#[test]
+fn postcard_effect() {
+    let settings = load("tests/assets/effects_1.frame");
+
+    for controller in &settings.lighting.unwrap().strip_controllers {
+        let effect = &controller.effect;
+        let bytes = postcard::to_stdvec(effect).unwrap();
+
+        let index = EffectKind::ALL
+            .iter()
+            .position(|x| *x == effect.kind())
+            .unwrap();
+        assert_eq!(usize::from(bytes[0]), index);
+        assert_eq!(&postcard::from_bytes::<Effect>(&bytes).unwrap(), effect);
+    }
+}
@@ -0,0 +1,236 @@
+#![allow(missing_docs, clippy::unreadable_literal)]
+
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
+
+use high_flow_next::{
+    misc::{Clock, Decode, ManualClock},
+    monitor::SensorReadings,
+    protocol::{
+        settings::{
+            simulate::{Inputs, Simulator},
+            Color, Conductivity, Controller, DataSource, Effect, EffectStatic, Flow, Gauge,
+            GaugeStyle, SourceControl, WaterQuality, SENSOR_RING_LEDS,
+        },
+        Frame,
+    },
+};
+
+fn static_controller(offset: u8, length: u8, color: Color) -> Controller {
+    Controller {
+        offset,
+        length,
+        effect: Effect::Static(EffectStatic {
+            color,
+            source_control_brightness: None,
+            source_control_saturation: None,
+        }),
+        data_source: None,
+        sensor_attenuation_rising: 0,
+        sensor_attenuation_falling: 0,
+    }
+}
+
+fn brightness_controlled(attenuation: u8) -> Controller {
+    let mut controller = static_controller(0, 1, Color::from_rgb_hex(0xFFFFFF));
+    controller.data_source = Some(DataSource::Flow);
+    controller.sensor_attenuation_rising = attenuation;
+    controller.sensor_attenuation_falling = attenuation;
+    controller.effect = Effect::Static(EffectStatic {
+        color: Color::from_rgb_hex(0xFFFFFF),
+        source_control_brightness: Some(SourceControl {
+            input_min: 0,
+            input_max: 1000,
+            output_min: 0,
+            output_max: 100,
+        }),
+        source_control_saturation: None,
+    });
+
+    controller
+}
+
+#[test]
+fn static_colors() {
+    let mut simulator = Simulator::new([
+        static_controller(2, 3, Color::from_rgb_hex(0xFF0000)),
+        static_controller(4, 2, Color::from_rgb_hex(0x0000FF)),
+    ]);
+    assert_eq!(simulator.led_count(), 6);
+
+    let leds = simulator.render(Duration::ZERO, &Inputs::new());
+    assert_eq!(
+        leds,
+        [
+            [0, 0, 0],
+            [0, 0, 0],
+            [255, 0, 0],
+            [255, 0, 0],
+            [0, 0, 255],
+            [0, 0, 255],
+        ]
+    );
+
+    let mut simulator = simulator.with_led_count(3);
+    assert_eq!(simulator.render(Duration::ZERO, &Inputs::new()).len(), 3);
+}
+
+#[test]
+fn source_control() {
+    let mut simulator = Simulator::new([brightness_controlled(0)]);
+
+    let leds = simulator.render(Duration::ZERO, &Inputs::new());
+    assert_eq!(leds, [[255, 255, 255]]);
+
+    let leds = simulator.render(Duration::ZERO, &Inputs::new().with(DataSource::Flow, 500.0));
+    assert_eq!(leds, [[128, 128, 128]]);
+
+    let leds = simulator.render(
+        Duration::ZERO,
+        &Inputs::new().with(DataSource::Flow, 2000.0),
+    );
+    assert_eq!(leds, [[255, 255, 255]]);
+}
+
+#[test]
+fn source_control_from_readings() {
+    let readings = SensorReadings {
+        captured_at: SystemTime::UNIX_EPOCH,
+        flow: Flow::from_value(500).unwrap(),
+        water_temperature: None,
+        external_temperature: None,
+        conductivity: Conductivity::from_value(20).unwrap(),
+        water_quality: WaterQuality::from_value(9_500).unwrap(),
+        power: 0.0,
+        voltage: 5.0,
+        external: BTreeMap::new(),
+    };
+    let inputs = Inputs::from(&readings);
+    assert_eq!(inputs.get(DataSource::Flow), Some(500.0));
+    assert_eq!(inputs.get(DataSource::WaterTemperature), None);
+
+    let mut simulator = Simulator::new([brightness_controlled(0)]);
+    let leds = simulator.render(Duration::ZERO, &inputs);
+    assert_eq!(leds, [[128, 128, 128]]);
+}
+
+#[test]
+fn attenuation() {
+    let mut simulator = Simulator::new([brightness_controlled(10)]);
+    let low = Inputs::new().with(DataSource::Flow, 0.0);
+    let high = Inputs::new().with(DataSource::Flow, 1000.0);
+
+    assert_eq!(simulator.render(Duration::ZERO, &low), [[0, 0, 0]]);
+
+    // Time constant of one second: 1 - 1/e of the step after one second.
+    let leds = simulator.render(Duration::from_secs(1), &high);
+    assert_eq!(leds, [[161, 161, 161]]);
+
+    let leds = simulator.render(Duration::from_secs(10), &high);
+    assert_eq!(leds, [[255, 255, 255]]);
+
+    // Going back in time resets the filter.
+    assert_eq!(simulator.render(Duration::ZERO, &low), [[0, 0, 0]]);
+}
+
+#[test]
+fn attenuation_with_clock() {
+    let clock = ManualClock::new();
+    let start = clock.now();
+    let mut simulator = Simulator::new([brightness_controlled(10)]);
+    let low = Inputs::new().with(DataSource::Flow, 0.0);
+    let high = Inputs::new().with(DataSource::Flow, 1000.0);
+
+    let leds = simulator.render_with_clock(&clock, start, &low);
+    assert_eq!(leds, [[0, 0, 0]]);
+
+    clock.advance(Duration::from_secs(1));
+    let leds = simulator.render_with_clock(&clock, start, &high);
+    assert_eq!(leds, [[161, 161, 161]]);
+}
+
+#[test]
+fn gauge() {
+    let palette = [Color::from_rgb_hex(0x0000FF), Color::from_rgb_hex(0xFF0000)];
+    let gauge = Gauge::new(DataSource::WaterTemperature, 20.0..45.0, &palette);
+    let inputs = |celsius: f64| {
+        Inputs::new().with(
+            DataSource::WaterTemperature,
+            f64::from(DataSource::WaterTemperature.to_raw(celsius)),
+        )
+    };
+
+    let mut simulator = Simulator::new([gauge.controller()]);
+    assert_eq!(simulator.led_count(), usize::from(SENSOR_RING_LEDS));
+
+    let leds = simulator.render(Duration::ZERO, &inputs(22.5));
+    assert_eq!(leds.iter().filter(|x| **x != [0, 0, 0]).count(), 5);
+    assert!(leds[..5].iter().all(|x| *x == [26, 0, 230]));
+
+    let leds = simulator.render(Duration::ZERO, &inputs(50.0));
+    assert!(leds.iter().all(|x| *x == [255, 0, 0]));
+
+    let controller = gauge
+        .style(GaugeStyle::ColorSwitch)
+        .brightness(50, 100)
+        .controller();
+    let Effect::ColorSwitch(effect) = &controller.effect else {
+        panic!("Expected color switch: {controller:?}");
+    };
+    assert_eq!(
+        effect.colors.iter().map(|x| x.1).collect::<Vec<_>>(),
+        [2000, 4500]
+    );
+    assert_eq!(
+        effect.source_control_brightness,
+        Some(SourceControl {
+            input_min: 2000,
+            input_max: 4500,
+            output_min: 50,
+            output_max: 100,
+        })
+    );
+
+    let mut simulator = Simulator::new([controller]);
+    let leds = simulator.render(Duration::ZERO, &inputs(20.0));
+    assert!(leds.iter().all(|x| *x == [0, 0, 128]));
+}
+
+#[test]
+fn deterministic() {
+    let controllers = [
+        "tests/assets/default.frame",
+        "tests/assets/effects_0.frame",
+        "tests/assets/effects_1.frame",
+        "tests/assets/effects_2.frame",
+    ]
+    .into_iter()
+    .flat_map(|path| {
+        let data = std::fs::read(path).unwrap();
+        let Frame::Settings(settings) = Frame::decode(&mut &data[..]).unwrap();
+        let lighting = settings.lighting.unwrap();
+
+        lighting
+            .strip_controllers
+            .into_iter()
+            .chain(lighting.sensor_controllers)
+    })
+    .collect::<Vec<_>>();
+
+    let inputs = Inputs::new()
+        .with(DataSource::Flow, 1500.0)
+        .with(DataSource::WaterTemperature, 2500.0)
+        .with(DataSource::Sound, 60.0);
+
+    for controller in controllers {
+        let name = controller.effect.name();
+        let mut a = Simulator::new([controller.clone()]).with_led_count(32);
+        let mut b = Simulator::new([controller]).with_led_count(32);
+
+        for millis in (0..10_000).step_by(250) {
+            let time = Duration::from_millis(millis);
+
+            assert_eq!(a.render(time, &inputs), b.render(time, &inputs), "{name}");
+        }
+    }
+}
@@ -0,0 +1,84 @@
+#![allow(missing_docs, clippy::unreadable_literal)]
+#![cfg(any(feature = "gif", feature = "apng"))]
+
+use std::time::Duration;
+
+use high_flow_next::protocol::settings::{
+    simulate::{AnimationOptions, ExportError, Simulator},
+    Color, Controller, Effect, EffectStatic,
+};
+
+fn simulator() -> Simulator {
+    Simulator::new([Controller {
+        offset: 0,
+        length: 10,
+        effect: Effect::Static(EffectStatic {
+            color: Color::from_rgb_hex(0xFF0000),
+            source_control_brightness: None,
+            source_control_saturation: None,
+        }),
+        data_source: None,
+        sensor_attenuation_rising: 0,
+        sensor_attenuation_falling: 0,
+    }])
+}
+
+fn options() -> AnimationOptions {
+    AnimationOptions {
+        duration: Duration::from_secs(1),
+        frame_rate: 10,
+        led_size: 8,
+        leds_per_row: Some(4),
+        ..AnimationOptions::default()
+    }
+}
+
+#[cfg(feature = "gif")]
+#[test]
+fn gif() {
+    let mut data = Vec::new();
+    simulator().write_gif(&mut data, &options()).unwrap();
+
+    let mut decoder = gif::DecodeOptions::new();
+    decoder.set_color_output(gif::ColorOutput::RGBA);
+    let mut decoder = decoder.read_info(&data[..]).unwrap();
+    assert_eq!((decoder.width(), decoder.height()), (32, 24));
+
+    let mut frames = 0;
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        assert_eq!(frame.delay, 10);
+        // Center of the first LED
+        let offset = (4 * 32 + 4) * 4;
+        assert_eq!(&frame.buffer[offset..offset + 3], &[0xFF, 0x00, 0x00]);
+        frames += 1;
+    }
+    assert_eq!(frames, 10);
+}
+
+#[cfg(feature = "apng")]
+#[test]
+fn apng() {
+    let mut data = Vec::new();
+    simulator().write_apng(&mut data, &options()).unwrap();
+
+    let decoder = png::Decoder::new(std::io::Cursor::new(data));
+    let reader = decoder.read_info().unwrap();
+    let info = reader.info();
+    assert_eq!((info.width, info.height), (32, 24));
+    assert_eq!(info.animation_control.unwrap().num_frames, 10);
+}
+
+#[test]
+fn invalid_options() {
+    let options = AnimationOptions {
+        frame_rate: 0,
+        ..AnimationOptions::default()
+    };
+
+    #[cfg(feature = "gif")]
+    let result = simulator().write_gif(Vec::new(), &options);
+    #[cfg(not(feature = "gif"))]
+    let result = simulator().write_apng(Vec::new(), &options);
+
+    assert!(matches!(result, Err(ExportError::InvalidAnimation(_))));
+}
@@ -0,0 +1,70 @@
+#![allow(missing_docs)]
+#![cfg(feature = "testing")]
+
+use std::fs::File;
+use std::panic::catch_unwind;
+
+use high_flow_next::{
+    assert_settings_eq,
+    misc::Decode,
+    protocol::{
+        settings::{Temperature, TemperatureUnit},
+        Frame, Settings,
+    },
+    testing::settings_mismatch,
+};
+
+fn load(path: &str) -> Settings {
+    let mut reader = File::open(path).unwrap();
+    let Frame::Settings(settings) = Frame::decode(&mut reader).unwrap();
+
+    settings
+}
+
+#[test]
+fn equal() {
+    let settings = load("tests/assets/default.frame");
+
+    assert_settings_eq!(settings, settings.clone());
+    assert_settings_eq!(settings.alarms, settings.clone().alarms, "alarms");
+}
+
+#[test]
+fn mismatch() {
+    let old = load("tests/assets/default.frame");
+    let mut new = old.clone();
+    new.display.temperature_unit = TemperatureUnit::F;
+    new.alarms.water_temperature_limit = Some(Temperature::from_value(5000).unwrap());
+
+    assert_eq!(
+        settings_mismatch(&old, &new).unwrap(),
+        "alarms.water_temperature_limit: 4500 -> 5000\n\
+         display.temperature_unit: C -> F\n"
+    );
+    assert_eq!(
+        settings_mismatch(&old.display, &new.display).unwrap(),
+        "temperature_unit: C -> F\n"
+    );
+
+    let message = catch_unwind(|| assert_settings_eq!(old, new, "frame {}", 1))
+        .unwrap_err()
+        .downcast::<String>()
+        .unwrap();
+
+    assert!(message.starts_with("assertion `left == right` failed: frame 1\n"));
+    assert!(message.ends_with("display.temperature_unit: C -> F\n"));
+}
+
+#[test]
+fn effects() {
+    let old = load("tests/assets/effects_0.frame");
+    let new = load("tests/assets/effects_1.frame");
+
+    let old = &old.lighting.unwrap().strip_controllers[0];
+    let new = &new.lighting.unwrap().strip_controllers[0];
+
+    assert_eq!(
+        settings_mismatch(old, new).unwrap().lines().next(),
+        Some("effect: Static -> Flame")
+    );
+}
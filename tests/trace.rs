@@ -0,0 +1,117 @@
+#![allow(missing_docs)]
+
+use high_flow_next::{
+    device::{Device, TraceRequest, TracingTransport, Transport},
+    misc::IoError,
+};
+
+struct Replay(Vec<u8>, &'static str);
+
+impl Transport for Replay {
+    fn get_feature_report(&mut self, buffer: &mut [u8]) -> Result<usize, IoError> {
+        buffer[..self.0.len()].copy_from_slice(&self.0);
+
+        Ok(self.0.len())
+    }
+
+    fn send_feature_report(&mut self, _data: &[u8]) -> Result<(), IoError> {
+        Err(IoError::IoError(std::io::Error::other(self.1)))
+    }
+}
+
+fn messages(diagram: &str) -> Vec<String> {
+    diagram
+        .lines()
+        .map(|line| match line.split_once("] ") {
+            Some((head, tail)) => format!("{}] {tail}", &head[..=head.find('[').unwrap()]),
+            None => line.into(),
+        })
+        .collect()
+}
+
+#[test]
+fn sequence_diagram() {
+    let frame = std::fs::read("tests/assets/default.frame").unwrap();
+    let len = frame.len();
+
+    let mut device = Device::new(TracingTransport::new(Replay(frame.clone(), "device busy")));
+    device.read_settings().unwrap();
+    assert!(device.write_report(&frame).is_err());
+
+    let trace = device.transport_mut().take_trace();
+    assert!(device.transport().trace().is_empty());
+    assert_eq!(trace.len(), 2);
+
+    let entries = trace.entries().collect::<Vec<_>>();
+    assert_eq!(entries[0].request, TraceRequest::GetFeatureReport(0x03));
+    assert_eq!(entries[0].response.as_deref(), Ok(&frame[..]));
+    assert_eq!(entries[1].request, TraceRequest::SendFeatureReport(frame));
+    assert_eq!(entries[1].response, Err("IO Error: device busy".into()));
+
+    assert_eq!(
+        messages(&trace.to_mermaid()),
+        [
+            "sequenceDiagram".to_string(),
+            "    participant Host".into(),
+            "    participant Device as high flow NEXT".into(),
+            "    Host->>Device: [] GET_REPORT 0x03".into(),
+            format!("    Device-->>Host: 0x03 (settings, {len} bytes)"),
+            format!("    Host->>Device: [] SET_REPORT 0x03 (settings, {len} bytes)"),
+            "    Device--xHost: error: IO Error: device busy".into(),
+        ]
+    );
+    assert_eq!(
+        messages(&trace.to_plantuml()),
+        [
+            "@startuml".to_string(),
+            "participant Host".into(),
+            "participant \"high flow NEXT\" as Device".into(),
+            "Host -> Device: [] GET_REPORT 0x03".into(),
+            format!("Device --> Host: 0x03 (settings, {len} bytes)"),
+            format!("Host -> Device: [] SET_REPORT 0x03 (settings, {len} bytes)"),
+            "Device -->x Host: error: IO Error: device busy".into(),
+            "@enduml".into(),
+        ]
+    );
+}
+
+#[test]
+fn escape() {
+    let frame = std::fs::read("tests/assets/default.frame").unwrap();
+
+    let transport = TracingTransport::new(Replay(frame.clone(), "a#b; c\r\nnext"));
+    let mut device = Device::new(transport);
+    assert!(device.write_report(&frame).is_err());
+
+    let trace = device.transport_mut().take_trace();
+    assert_eq!(
+        trace.to_mermaid().lines().last(),
+        Some("    Device--xHost: error: IO Error: a#35;b#59; c<br>next")
+    );
+    assert_eq!(
+        trace.to_plantuml().lines().nth_back(1),
+        Some("Device -->x Host: error: IO Error: a#b; c\\nnext")
+    );
+}
+
+#[test]
+fn limit() {
+    let frame = std::fs::read("tests/assets/default.frame").unwrap();
+
+    let mut device = Device::new(TracingTransport::new(Replay(frame, "device busy")).with_limit(2));
+    for _ in 0..5 {
+        device.read_settings().unwrap();
+    }
+    assert_eq!(device.transport().trace().len(), 2);
+
+    let mut corrupt = vec![0x03, 0x00];
+    corrupt.resize(8, 0);
+    let mut device = Device::new(TracingTransport::new(Replay(corrupt, "device busy")));
+    assert!(device.read_settings().is_err());
+
+    let (_, trace) = device.into_inner().into_inner();
+    let entry = trace.entries().next().unwrap();
+    assert!(entry
+        .describe_response()
+        .starts_with("0x03 (invalid frame: "));
+}
@@ -0,0 +1,26 @@
+#![allow(missing_docs)]
+#![cfg(feature = "uom")]
+
+use high_flow_next::protocol::settings::{Conductivity, Flow, PowerDamping, Temperature};
+use uom::si::{
+    electrical_conductivity::siemens_per_meter,
+    f64::{ElectricalConductivity, Power, ThermodynamicTemperature, VolumeRate},
+    power::watt,
+    thermodynamic_temperature::kelvin,
+    volume_rate::liter_per_second,
+};
+
+#[test]
+fn conversions() {
+    let flow = VolumeRate::from(Flow::from_value(1_800).unwrap());
+    assert!((flow.get::<liter_per_second>() - 0.05).abs() < 1e-9);
+
+    let temperature = ThermodynamicTemperature::from(Temperature::from_value(2_500).unwrap());
+    assert!((temperature.get::<kelvin>() - 298.15).abs() < 1e-9);
+
+    let conductivity = ElectricalConductivity::from(Conductivity::from_value(20).unwrap());
+    assert!((conductivity.get::<siemens_per_meter>() - 0.002).abs() < 1e-9);
+
+    let power = Power::from(PowerDamping::from_value(1_500).unwrap());
+    assert!((power.get::<watt>() - 1.5).abs() < 1e-9);
+}
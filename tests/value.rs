@@ -0,0 +1,155 @@
+#![allow(missing_docs)]
+
+use std::fs::File;
+
+use high_flow_next::{
+    misc::Decode,
+    protocol::{
+        settings::{
+            Access, EffectPercent, Flow, FlowCorrection, Medium, PageFlags, Settings, TempOffset,
+            Temperature, Value, ValueError,
+        },
+        Frame,
+    },
+};
+
+fn load(path: &str) -> Settings {
+    let mut reader = File::open(path).unwrap();
+    let Frame::Settings(settings) = Frame::decode(&mut reader).unwrap();
+
+    settings
+}
+
+#[test]
+fn conversions() {
+    let value = Value::from(Temperature::from_value(4500).unwrap());
+    assert_eq!(value, Value::Temperature(4500));
+    assert_eq!(value.to_f64(), Some(45.0));
+    assert_eq!(value.to_string(), "45.00 °");
+    assert_eq!(
+        TempOffset::try_from(Value::Temperature(-250)).unwrap(),
+        TempOffset::from_value(-250).unwrap()
+    );
+
+    assert_eq!(
+        Value::from(Flow::from_value(1200).unwrap()),
+        Value::Flow(1200)
+    );
+    assert_eq!(
+        Value::from(EffectPercent::from_value(12).unwrap()),
+        Value::Percent(1200)
+    );
+    assert_eq!(
+        Value::from(FlowCorrection::from_value(-250).unwrap()),
+        Value::Percent(-250)
+    );
+    assert_eq!(
+        EffectPercent::try_from(Value::Percent(5000)).unwrap(),
+        EffectPercent::from_value(50).unwrap()
+    );
+    assert!(matches!(
+        EffectPercent::try_from(Value::Percent(5050)),
+        Err(ValueError::InvalidValue(Value::Percent(5050)))
+    ));
+
+    assert_eq!(Value::from(Medium::DpUltra), Value::Enum("DpUltra".into()));
+    assert_eq!(
+        Medium::try_from(Value::Enum("DistilledWater".into())).unwrap(),
+        Medium::DistilledWater
+    );
+    assert!(Medium::try_from(Value::Enum("Glycol".into())).is_err());
+
+    assert_eq!(Value::from(PageFlags::FLOW), Value::Flags(0x0002));
+    assert_eq!(
+        PageFlags::try_from(Value::Flags(0x0002)).unwrap(),
+        PageFlags::FLOW
+    );
+
+    // Plain integers are accepted by every numeric type, other units are not.
+    assert_eq!(
+        Flow::try_from(Value::Integer(300)).unwrap(),
+        Flow::from_value(300).unwrap()
+    );
+    assert!(Flow::try_from(Value::Temperature(300)).is_err());
+    assert!(matches!(
+        Flow::try_from(Value::Flow(5000)),
+        Err(ValueError::RangeError(_))
+    ));
+}
+
+#[test]
+fn get() {
+    let settings = load("tests/assets/effects_0.frame");
+
+    assert_eq!(
+        settings.get("alarms.water_temperature_limit"),
+        Some(Value::Temperature(4510))
+    );
+    assert_eq!(settings.get("alarms.flow_alarm_limit"), Some(Value::None));
+    assert_eq!(
+        settings.get("sensor.flow_correction[3].0"),
+        Some(Value::Flow(700))
+    );
+    assert_eq!(
+        settings.get("lighting.strip_controllers[0].effect"),
+        Some(Value::Enum("Static".into()))
+    );
+    assert_eq!(
+        settings.get("lighting.strip_controllers[0].offset"),
+        Some(Value::Integer(0))
+    );
+
+    assert_eq!(settings.get("alarms"), None);
+    assert_eq!(settings.get("alarms.unknown"), None);
+    assert_eq!(settings.get("sensor.flow_correction[10].0"), None);
+    assert_eq!(settings.get("lighting.strip_controllers[6].offset"), None);
+}
+
+#[test]
+fn set() {
+    let mut settings = load("tests/assets/default.frame");
+
+    settings
+        .set("alarms.water_temperature_limit", Value::Temperature(5000))
+        .unwrap();
+    settings
+        .set("alarms.flow_alarm_limit", Value::Flow(300))
+        .unwrap();
+    settings
+        .set("sensor.medium", Value::Enum("DistilledWater".into()))
+        .unwrap();
+    settings
+        .set("sensor.flow_correction[0].1", Value::Percent(-150))
+        .unwrap();
+    settings
+        .set("alarms.external_temperature_limit", Value::None)
+        .unwrap();
+
+    assert_eq!(
+        settings.alarms.water_temperature_limit,
+        Some(Temperature::from_value(5000).unwrap())
+    );
+    assert_eq!(
+        settings.alarms.flow_alarm_limit,
+        Some(Flow::from_value(300).unwrap())
+    );
+    assert_eq!(settings.sensor.medium, Medium::DistilledWater);
+    assert_eq!(
+        settings.sensor.flow_correction[0].1,
+        FlowCorrection::from_value(-150).unwrap()
+    );
+    assert_eq!(settings.alarms.external_temperature_limit, None);
+
+    assert!(matches!(
+        settings.set("alarms.unknown", Value::Integer(1)),
+        Err(ValueError::UnknownPath)
+    ));
+    assert!(matches!(
+        settings.set("sensor.medium", Value::Integer(1)),
+        Err(ValueError::InvalidValue(_))
+    ));
+    assert!(matches!(
+        settings.set("alarms.water_temperature_limit", Value::Temperature(20_000)),
+        Err(ValueError::RangeError(_))
+    ));
+}
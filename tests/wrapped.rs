@@ -0,0 +1,114 @@
+#![allow(missing_docs)]
+
+use std::collections::{BTreeSet, HashMap};
+
+use high_flow_next::protocol::settings::{
+    Brightness, EffectPercent, Flow, FlowCorrection, TempOffset,
+};
+
+#[test]
+fn arithmetic() {
+    let percent = EffectPercent::from_value(90).unwrap();
+
+    assert_eq!(percent.checked_add(10).as_deref(), Some(&100));
+    assert_eq!(percent.checked_add(11), None);
+    assert_eq!(percent.checked_sub(91), None);
+    assert_eq!(*percent.saturating_add(20), 100);
+    assert_eq!(*percent.saturating_sub(200), 0);
+
+    let offset = TempOffset::from_value(-1400).unwrap();
+    assert_eq!(offset.checked_add(-100).as_deref(), Some(&-1500));
+    assert_eq!(offset.checked_sub(101), None);
+    assert_eq!(*offset.saturating_add(i16::MIN), -1500);
+    assert_eq!(*offset.saturating_sub(i16::MIN), 1500);
+}
+
+#[test]
+fn lerp() {
+    let from = EffectPercent::from_value(20).unwrap();
+    let to = EffectPercent::from_value(80).unwrap();
+
+    assert_eq!(*from.lerp(&to, 0.0), 20);
+    assert_eq!(*from.lerp(&to, 0.5), 50);
+    assert_eq!(*from.lerp(&to, 1.0), 80);
+    assert_eq!(*to.lerp(&from, 0.25), 65);
+    assert_eq!(*from.lerp(&to, 2.0), 80);
+    assert_eq!(*from.lerp(&to, f64::NAN), 20);
+
+    let from = FlowCorrection::from_value(-5000).unwrap();
+    let to = FlowCorrection::from_value(5000).unwrap();
+    assert_eq!(*from.lerp(&to, 0.5), 0);
+}
+
+#[test]
+fn fade() {
+    let mut speed = EffectPercent::from_value(0).unwrap();
+
+    let steps = std::iter::from_fn(|| {
+        speed = speed.checked_add(40)?;
+
+        Some(*speed)
+    })
+    .collect::<Vec<_>>();
+
+    assert_eq!(steps, [40, 80]);
+}
+
+#[test]
+fn fraction() {
+    assert!(TempOffset::from_value(-1500).unwrap().fraction().abs() < f32::EPSILON);
+    assert!((TempOffset::from_value(0).unwrap().fraction() - 0.5).abs() < f32::EPSILON);
+    assert!((EffectPercent::from_value(100).unwrap().fraction() - 1.0).abs() < f32::EPSILON);
+    assert!((Flow::from_value(750).unwrap().fraction() - 0.25).abs() < f32::EPSILON);
+
+    assert_eq!(*TempOffset::from_fraction(0.75), 750);
+    assert_eq!(*EffectPercent::from_fraction(0.333), 33);
+    assert_eq!(*EffectPercent::from_fraction(1.5), 100);
+    assert_eq!(*EffectPercent::from_fraction(f32::NAN), 0);
+
+    let flow = Flow::from_value(1234).unwrap();
+    assert_eq!(Flow::from_fraction(flow.fraction()), flow);
+}
+
+#[test]
+fn conversions() {
+    let flow = Flow::try_from(1200).unwrap();
+
+    assert!(flow == 1200);
+    assert!(1200 == flow);
+    assert!(flow < 1500 && flow > 60);
+    assert!(60 < flow);
+    assert_eq!(u16::from(flow), 1200);
+
+    let error = Flow::try_from(3001).unwrap_err();
+    assert_eq!((error.min, error.max, error.val), (0, 3000, 3001));
+
+    assert_eq!(Brightness::from(42), 42);
+    assert_eq!(
+        TempOffset::try_from(-1500).ok(),
+        Some(TempOffset::from_value(-1500).unwrap())
+    );
+}
+
+#[test]
+fn map_keys() {
+    let flows = [1200, 300, 1200, 3000]
+        .map(|x| Flow::from_value(x).unwrap())
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+    assert_eq!(
+        flows.into_iter().map(u16::from).collect::<Vec<_>>(),
+        [300, 1200, 3000]
+    );
+
+    let mut counts = HashMap::<TempOffset, usize>::new();
+    for x in [-10, 20, -10] {
+        *counts
+            .entry(TempOffset::from_value(x).unwrap())
+            .or_default() += 1;
+    }
+    assert_eq!(counts[&TempOffset::from_value(-10).unwrap()], 2);
+    assert_eq!(counts.len(), 2);
+
+    assert!(TempOffset::from_value(-10).unwrap() < TempOffset::from_value(20).unwrap());
+}